@@ -100,8 +100,16 @@ export interface SharedInstanceUpdatePreview {
 	latestVersion: number
 	updateAvailable: boolean
 	diffs: SharedInstanceUpdateDiff[]
+	configConflicts: SharedInstanceConfigConflict[]
 }
 
+export interface SharedInstanceConfigConflict {
+	path: string
+	keys: string[]
+}
+
+export type SharedInstanceConfigResolution = 'keep_local' | 'use_remote'
+
 export interface SharedInstanceUpdateDiff {
 	type:
 		| 'added'
@@ -221,9 +229,13 @@ export async function install_shared_instance(
 	})
 }
 
-export async function install_update_shared_instance(instanceId: string) {
+export async function install_update_shared_instance(
+	instanceId: string,
+	configResolutions?: Record<string, SharedInstanceConfigResolution>,
+) {
 	return await invoke<InstallJobSnapshot>('plugin:install|install_update_shared_instance', {
 		instanceId,
+		configResolutions,
 	})
 }
 
@@ -0,0 +1,15 @@
+import { invoke } from '@tauri-apps/api/core'
+
+// Gets the OpenPGP key fingerprint a project's files must be signed with, if pinned
+export async function get_pinned_signing_key(projectId: string) {
+	return await invoke<string | null>('plugin:signing-keys|signing_keys_get_pinned', { projectId })
+}
+
+// Only install files of a project whose signature was verified against this key
+export async function pin_signing_key(projectId: string, fingerprint: string) {
+	return await invoke<void>('plugin:signing-keys|signing_keys_pin', { projectId, fingerprint })
+}
+
+export async function unpin_signing_key(projectId: string) {
+	return await invoke<void>('plugin:signing-keys|signing_keys_unpin', { projectId })
+}
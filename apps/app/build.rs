@@ -155,6 +155,7 @@ fn main() {
                         "install_get_modpack_preview",
                         "install_create_instance",
                         "install_create_modpack_instance",
+                        "install_create_collection_instance",
                         "install_get_shared_instance_preview",
                         "install_accept_shared_instance_invite",
                         "install_get_shared_instance_update_preview",
@@ -196,6 +197,18 @@ fn main() {
                         DefaultPermissionRule::AllowAllCommands,
                     ),
             )
+            .plugin(
+                "signing-keys",
+                InlinedPlugin::new()
+                    .commands(&[
+                        "signing_keys_get_pinned",
+                        "signing_keys_pin",
+                        "signing_keys_unpin",
+                    ])
+                    .default_permission(
+                        DefaultPermissionRule::AllowAllCommands,
+                    ),
+            )
             .plugin(
                 "instance",
                 InlinedPlugin::new()
@@ -254,6 +267,8 @@ fn main() {
                         "instance_share_unlink",
                         "instance_share_unpublish",
                         "instance_export_mrpack",
+                        "instance_export_packwiz",
+                        "instance_export_mmc",
                         "instance_get_pack_export_candidates",
                     ])
                     .default_permission(
@@ -375,6 +390,13 @@ fn main() {
                         "friend_statuses",
                         "add_friend",
                         "remove_friend",
+                        "invite_to_world",
+                        "stop_hosting_world",
+                        "world_invites",
+                        "world_invite_instances",
+                        "create_world_invite_instance",
+                        "join_world_invite",
+                        "decline_world_invite",
                     ])
                     .default_permission(
                         DefaultPermissionRule::AllowAllCommands,
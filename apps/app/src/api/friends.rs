@@ -1,5 +1,8 @@
 use tauri::plugin::TauriPlugin;
+use theseus::friends::{WorldInviteInstance, WorldInvitePayload};
+use theseus::install::InstallJobSnapshot;
 use theseus::prelude::{UserFriend, UserStatus};
+use uuid::Uuid;
 
 pub fn init<R: tauri::Runtime>() -> TauriPlugin<R> {
     tauri::plugin::Builder::new("friends")
@@ -7,7 +10,14 @@ pub fn init<R: tauri::Runtime>() -> TauriPlugin<R> {
             friends,
             friend_statuses,
             add_friend,
-            remove_friend
+            remove_friend,
+            invite_to_world,
+            stop_hosting_world,
+            world_invites,
+            world_invite_instances,
+            create_world_invite_instance,
+            join_world_invite,
+            decline_world_invite
         ])
         .build()
 }
@@ -31,3 +41,56 @@ pub async fn add_friend(user_id: &str) -> crate::api::Result<()> {
 pub async fn remove_friend(user_id: &str) -> crate::api::Result<()> {
     Ok(theseus::friends::remove_friend(user_id).await?)
 }
+
+#[tauri::command]
+pub async fn invite_to_world(
+    instance_id: &str,
+    world_name: &str,
+    port: u16,
+    user_id: &str,
+) -> crate::api::Result<()> {
+    Ok(theseus::friends::invite_to_world(
+        instance_id,
+        world_name,
+        port,
+        user_id,
+    )
+    .await?)
+}
+
+#[tauri::command]
+pub async fn stop_hosting_world(port: u16) -> crate::api::Result<()> {
+    Ok(theseus::friends::stop_hosting_world(port).await?)
+}
+
+#[tauri::command]
+pub async fn world_invites() -> crate::api::Result<Vec<WorldInvitePayload>> {
+    Ok(theseus::friends::world_invites().await?)
+}
+
+#[tauri::command]
+pub async fn world_invite_instances(
+    socket: Uuid,
+) -> crate::api::Result<Vec<WorldInviteInstance>> {
+    Ok(theseus::friends::world_invite_instances(socket).await?)
+}
+
+#[tauri::command]
+pub async fn create_world_invite_instance(
+    socket: Uuid,
+) -> crate::api::Result<InstallJobSnapshot> {
+    Ok(theseus::friends::create_world_invite_instance(socket).await?)
+}
+
+#[tauri::command]
+pub async fn join_world_invite(
+    socket: Uuid,
+    instance_id: &str,
+) -> crate::api::Result<()> {
+    Ok(theseus::friends::join_world_invite(socket, instance_id).await?)
+}
+
+#[tauri::command]
+pub async fn decline_world_invite(socket: Uuid) -> crate::api::Result<()> {
+    Ok(theseus::friends::decline_world_invite(socket).await?)
+}
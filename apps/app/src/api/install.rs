@@ -1,14 +1,15 @@
 use crate::api::Result;
 use crate::api::instance::InstanceLink;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use theseus::data::ModLoader;
 use theseus::install::{
     InstallJobSnapshot, InstallModpackPreview, InstallPostInstallEdit,
 };
 use theseus::instance::{
-    SharedInstanceInstallPreview, SharedInstanceInviteInstallPreview,
-    SharedInstanceUpdatePreview,
+    SharedInstanceConfigResolution, SharedInstanceInstallPreview,
+    SharedInstanceInviteInstallPreview, SharedInstanceUpdatePreview,
 };
 use theseus::pack::import::ImportLauncherType;
 use theseus::pack::install_from::CreatePackLocation;
@@ -20,6 +21,7 @@ pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
             install_get_modpack_preview,
             install_create_instance,
             install_create_modpack_instance,
+            install_create_collection_instance,
             install_get_shared_instance_preview,
             install_accept_shared_instance_invite,
             install_get_shared_instance_update_preview,
@@ -112,6 +114,24 @@ pub async fn install_create_modpack_instance(
     .await?)
 }
 
+#[tauri::command]
+pub async fn install_create_collection_instance(
+    collection_id: String,
+    name: Option<String>,
+    game_version: String,
+    loader: ModLoader,
+    loader_version: Option<String>,
+) -> Result<InstallJobSnapshot> {
+    Ok(theseus::install::create_collection_instance(
+        collection_id,
+        name.map(|name| name.trim().to_string()),
+        game_version,
+        loader,
+        loader_version,
+    )
+    .await?)
+}
+
 #[tauri::command]
 pub async fn install_get_shared_instance_preview(
     shared_instance_id: String,
@@ -169,8 +189,13 @@ pub async fn install_shared_instance(
 #[tauri::command]
 pub async fn install_update_shared_instance(
     instance_id: String,
+    config_resolutions: Option<HashMap<String, SharedInstanceConfigResolution>>,
 ) -> Result<InstallJobSnapshot> {
-    Ok(theseus::instance::update_shared_instance(&instance_id).await?)
+    Ok(theseus::instance::update_shared_instance(
+        &instance_id,
+        config_resolutions.unwrap_or_default(),
+    )
+    .await?)
 }
 
 #[tauri::command]
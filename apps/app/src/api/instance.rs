@@ -75,6 +75,8 @@ pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
             instance_share_unlink,
             instance_share_unpublish,
             instance_export_mrpack,
+            instance_export_packwiz,
+            instance_export_mmc,
             instance_get_pack_export_candidates,
         ])
         .build()
@@ -815,6 +817,44 @@ pub async fn instance_export_mrpack(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn instance_export_packwiz(
+    instance_id: &str,
+    export_location: PathBuf,
+    included_overrides: Vec<String>,
+    excluded_overrides: Vec<String>,
+    version_id: Option<String>,
+    description: Option<String>,
+) -> Result<()> {
+    theseus::instance::export_packwiz(
+        instance_id,
+        export_location,
+        included_overrides,
+        excluded_overrides,
+        version_id,
+        description,
+    )
+    .await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn instance_export_mmc(
+    instance_id: &str,
+    export_location: PathBuf,
+    included_overrides: Vec<String>,
+    excluded_overrides: Vec<String>,
+) -> Result<()> {
+    theseus::instance::export_mmc(
+        instance_id,
+        export_location,
+        included_overrides,
+        excluded_overrides,
+    )
+    .await?;
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn instance_get_pack_export_candidates(
     instance_id: &str,
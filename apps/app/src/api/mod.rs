@@ -16,6 +16,7 @@ pub mod process;
 pub mod reports;
 pub mod settings;
 pub mod shortcuts;
+pub mod signing_keys;
 pub mod tags;
 pub mod users;
 pub mod utils;
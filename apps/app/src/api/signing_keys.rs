@@ -0,0 +1,31 @@
+use crate::api::Result;
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri::plugin::Builder::new("signing-keys")
+        .invoke_handler(tauri::generate_handler![
+            signing_keys_get_pinned,
+            signing_keys_pin,
+            signing_keys_unpin,
+        ])
+        .build()
+}
+
+#[tauri::command]
+pub async fn signing_keys_get_pinned(
+    project_id: &str,
+) -> Result<Option<String>> {
+    Ok(theseus::signing_keys::get_pinned_signing_key(project_id).await?)
+}
+
+#[tauri::command]
+pub async fn signing_keys_pin(
+    project_id: &str,
+    fingerprint: &str,
+) -> Result<()> {
+    Ok(theseus::signing_keys::pin_signing_key(project_id, fingerprint).await?)
+}
+
+#[tauri::command]
+pub async fn signing_keys_unpin(project_id: &str) -> Result<()> {
+    Ok(theseus::signing_keys::unpin_signing_key(project_id).await?)
+}
@@ -255,6 +255,7 @@ fn main() {
         .plugin(api::reports::init())
         .plugin(api::settings::init())
         .plugin(api::shortcuts::init())
+        .plugin(api::signing_keys::init())
         .plugin(api::tags::init())
         .plugin(api::users::init())
         .plugin(api::utils::init())
@@ -3,4 +3,5 @@ import type { Component } from 'vue'
 export default {
 	// Finance
 	'payment-statement': () => import('./finance/PaymentStatement.vue'),
+	'revenue-statement': () => import('./finance/RevenueStatement.vue'),
 } as Record<string, () => Promise<{ default: Component }>>
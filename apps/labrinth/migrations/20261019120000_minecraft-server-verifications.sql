@@ -0,0 +1,11 @@
+CREATE TABLE minecraft_server_verifications (
+    project_id BIGINT PRIMARY KEY REFERENCES mods(id) ON DELETE CASCADE,
+    token TEXT NOT NULL,
+    -- consecutive failed re-checks since the last successful one
+    failed_checks INTEGER NOT NULL DEFAULT 0,
+    last_checked TIMESTAMPTZ NULL,
+    created TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX minecraft_server_verifications_last_checked
+    ON minecraft_server_verifications (last_checked NULLS FIRST);
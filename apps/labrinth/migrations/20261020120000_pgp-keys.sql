@@ -0,0 +1,22 @@
+CREATE TABLE pgp_keys (
+	id BIGSERIAL PRIMARY KEY,
+	user_id BIGINT NULL REFERENCES users(id) ON DELETE CASCADE,
+	organization_id BIGINT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+	fingerprint VARCHAR(64) NOT NULL,
+	public_key TEXT NOT NULL,
+	created TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+	CHECK ((user_id IS NULL) <> (organization_id IS NULL))
+);
+
+CREATE UNIQUE INDEX pgp_keys_user_id_fingerprint_unique
+	ON pgp_keys(user_id, fingerprint)
+	WHERE user_id IS NOT NULL;
+
+CREATE UNIQUE INDEX pgp_keys_organization_id_fingerprint_unique
+	ON pgp_keys(organization_id, fingerprint)
+	WHERE organization_id IS NOT NULL;
+
+ALTER TABLE files
+	ADD COLUMN signature_status VARCHAR(32) NULL,
+	ADD COLUMN signature_fingerprint VARCHAR(64) NULL;
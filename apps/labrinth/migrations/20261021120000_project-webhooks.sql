@@ -0,0 +1,43 @@
+CREATE TABLE project_webhooks (
+	id BIGINT PRIMARY KEY,
+	project_id BIGINT NULL REFERENCES mods(id) ON DELETE CASCADE,
+	organization_id BIGINT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+	url VARCHAR(2048) NOT NULL,
+	-- key deliveries are HMAC-SHA256 signed with
+	secret VARCHAR(255) NOT NULL,
+	events VARCHAR(64)[] NOT NULL,
+	created TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+	CHECK ((project_id IS NULL) <> (organization_id IS NULL))
+);
+
+CREATE INDEX project_webhooks_project_id ON project_webhooks(project_id);
+CREATE INDEX project_webhooks_organization_id ON project_webhooks(organization_id);
+
+CREATE TABLE project_webhook_deliveries (
+	id BIGSERIAL PRIMARY KEY,
+	webhook_id BIGINT NOT NULL REFERENCES project_webhooks(id) ON DELETE CASCADE,
+	event VARCHAR(64) NOT NULL,
+	payload JSONB NOT NULL,
+	status VARCHAR(32) NOT NULL,
+	attempt_count INTEGER NOT NULL DEFAULT 0,
+	next_attempt TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	-- HTTP status code or error of the latest attempt
+	response_code INTEGER NULL,
+	error TEXT NULL,
+	created TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX project_webhook_deliveries_queue
+	ON project_webhook_deliveries(status, next_attempt ASC);
+CREATE INDEX project_webhook_deliveries_webhook_id
+	ON project_webhook_deliveries(webhook_id, created DESC);
+
+-- Give team owners the new MANAGE_WEBHOOKS permissions
+UPDATE team_members
+SET permissions = permissions | 1024
+WHERE is_owner = TRUE;
+
+UPDATE team_members
+SET organization_permissions = organization_permissions | 256
+WHERE is_owner = TRUE AND organization_permissions IS NOT NULL;
@@ -0,0 +1,36 @@
+-- Entries don't reference the users, projects and organizations they're about,
+-- so that they outlive them
+CREATE TABLE audit_log (
+	id BIGSERIAL PRIMARY KEY,
+	actor_id BIGINT NOT NULL,
+	project_id BIGINT NULL,
+	organization_id BIGINT NULL,
+	action VARCHAR(64) NOT NULL,
+	target JSONB NOT NULL,
+	-- changed fields of the target before and after the change
+	before JSONB NULL,
+	after JSONB NULL,
+	created TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX audit_log_project_id ON audit_log(project_id, created DESC);
+CREATE INDEX audit_log_organization_id ON audit_log(organization_id, created DESC);
+
+CREATE FUNCTION audit_log_append_only() RETURNS TRIGGER AS $$
+BEGIN
+	RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_log_append_only
+	BEFORE UPDATE OR DELETE ON audit_log
+	FOR EACH ROW EXECUTE FUNCTION audit_log_append_only();
+
+-- Give team owners the new VIEW_AUDIT_LOG permissions
+UPDATE team_members
+SET permissions = permissions | 2048
+WHERE is_owner = TRUE;
+
+UPDATE team_members
+SET organization_permissions = organization_permissions | 512
+WHERE is_owner = TRUE AND organization_permissions IS NOT NULL;
@@ -0,0 +1,31 @@
+CREATE TABLE organization_roles (
+	id BIGINT PRIMARY KEY,
+	organization_id BIGINT NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+	name VARCHAR(64) NOT NULL,
+	-- default project permissions of members with this role
+	permissions BIGINT NOT NULL,
+	organization_permissions BIGINT NOT NULL,
+	created TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+	UNIQUE (organization_id, name)
+);
+
+-- project permissions of members with a role in a single organization project
+CREATE TABLE organization_role_project_overrides (
+	role_id BIGINT NOT NULL REFERENCES organization_roles(id) ON DELETE CASCADE,
+	project_id BIGINT NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+	permissions BIGINT NOT NULL,
+
+	PRIMARY KEY (role_id, project_id)
+);
+
+-- if set, the member's permissions are those of the role instead of their own
+ALTER TABLE team_members
+	ADD COLUMN role_id BIGINT NULL REFERENCES organization_roles(id) ON DELETE SET NULL;
+
+CREATE INDEX team_members_role_id ON team_members(role_id);
+
+-- Give organization owners the new MANAGE_ROLES permission
+UPDATE team_members
+SET organization_permissions = organization_permissions | 1024
+WHERE is_owner = TRUE AND organization_permissions IS NOT NULL;
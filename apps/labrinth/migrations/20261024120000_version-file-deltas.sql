@@ -0,0 +1,13 @@
+-- binary deltas between the primary files of consecutive versions of a project
+CREATE TABLE version_file_deltas (
+	from_file_id BIGINT NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+	to_file_id BIGINT NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+	-- null when the delta was not worth storing, or could not be computed
+	url VARCHAR(2048) NULL,
+	size INTEGER NULL,
+	created TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+	PRIMARY KEY (from_file_id, to_file_id)
+);
+
+CREATE INDEX version_file_deltas_to_file_id ON version_file_deltas (to_file_id);
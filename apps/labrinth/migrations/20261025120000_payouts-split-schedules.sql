@@ -0,0 +1,37 @@
+-- Every payouts split a team member has had or is scheduled to have, so that
+-- payouts can be split by the value in effect at the time revenue was earned
+CREATE TABLE team_member_payouts_splits (
+	team_id BIGINT NOT NULL REFERENCES teams(id) ON DELETE CASCADE,
+	user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+	payouts_split NUMERIC(40, 20) NOT NULL,
+	effective_from TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	PRIMARY KEY (team_id, user_id, effective_from)
+);
+
+-- Record immediate changes to `team_members.payouts_split`, whichever code
+-- path makes them
+CREATE FUNCTION record_team_member_payouts_split() RETURNS TRIGGER AS $$
+BEGIN
+	INSERT INTO team_member_payouts_splits (team_id, user_id, payouts_split, effective_from)
+	VALUES (NEW.team_id, NEW.user_id, NEW.payouts_split, now())
+	ON CONFLICT (team_id, user_id, effective_from) DO UPDATE
+	SET payouts_split = EXCLUDED.payouts_split;
+	RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER team_members_payouts_split_insert
+	AFTER INSERT ON team_members
+	FOR EACH ROW EXECUTE FUNCTION record_team_member_payouts_split();
+
+CREATE TRIGGER team_members_payouts_split_update
+	AFTER UPDATE OF payouts_split ON team_members
+	FOR EACH ROW
+	WHEN (OLD.payouts_split IS DISTINCT FROM NEW.payouts_split)
+	EXECUTE FUNCTION record_team_member_payouts_split();
+
+-- Existing splits have applied for as long as we know
+INSERT INTO team_member_payouts_splits (team_id, user_id, payouts_split, effective_from)
+SELECT team_id, user_id, payouts_split, 'epoch'
+FROM team_members
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,17 @@
+CREATE TABLE moderation_macros (
+	id BIGINT PRIMARY KEY,
+	name VARCHAR(64) NOT NULL UNIQUE,
+	-- thread message posted when the macro is applied, with `{variable}` placeholders
+	message TEXT NOT NULL,
+	-- whether the thread message is only visible to moderators
+	private BOOLEAN NOT NULL DEFAULT FALSE,
+	-- status the project is set to, if any
+	status VARCHAR(128) NULL,
+	-- appended to the moderation note of the project's owner, if any
+	note TEXT NULL,
+	created TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	last_modified TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	last_modified_by BIGINT NULL REFERENCES users(id) ON DELETE SET NULL,
+	uses BIGINT NOT NULL DEFAULT 0,
+	last_used TIMESTAMPTZ NULL
+);
@@ -0,0 +1,42 @@
+CREATE TABLE project_transfers (
+	id BIGINT PRIMARY KEY,
+	project_id BIGINT NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+	requested_by BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+	-- owner of the project when the transfer was requested, exactly one of which is set
+	previous_owner_user_id BIGINT NULL REFERENCES users(id) ON DELETE CASCADE,
+	previous_owner_organization_id BIGINT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+	-- owner the project is being transferred to, exactly one of which is set
+	new_owner_user_id BIGINT NULL REFERENCES users(id) ON DELETE CASCADE,
+	new_owner_organization_id BIGINT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+	-- whether the previous owner's payouts which aren't available yet move to the new owner
+	transfer_pending_payouts BOOLEAN NOT NULL,
+	-- whether the previous owner's payouts split moves to the new owner
+	transfer_payouts_split BOOLEAN NOT NULL,
+	-- one of 'pending', 'accepted', 'declined' or 'cancelled'
+	status VARCHAR(32) NOT NULL DEFAULT 'pending',
+	created TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	resolved TIMESTAMPTZ NULL,
+	resolved_by BIGINT NULL REFERENCES users(id) ON DELETE SET NULL,
+	CHECK ((previous_owner_user_id IS NULL) <> (previous_owner_organization_id IS NULL)),
+	CHECK ((new_owner_user_id IS NULL) <> (new_owner_organization_id IS NULL))
+);
+
+-- A project can only have one pending transfer at a time
+CREATE UNIQUE INDEX project_transfers_pending_project
+	ON project_transfers (project_id)
+	WHERE status = 'pending';
+
+CREATE INDEX project_transfers_new_owner_user_id
+	ON project_transfers (new_owner_user_id)
+	WHERE new_owner_user_id IS NOT NULL;
+
+CREATE INDEX project_transfers_new_owner_organization_id
+	ON project_transfers (new_owner_organization_id)
+	WHERE new_owner_organization_id IS NOT NULL;
+
+INSERT INTO notifications_types
+	(name, delivery_priority, expose_in_user_preferences, expose_in_site_notifications)
+VALUES ('project_transfer_requested', 1, FALSE, TRUE);
+
+INSERT INTO users_notifications_preferences (user_id, channel, notification_type, enabled)
+VALUES (NULL, 'email', 'project_transfer_requested', FALSE);
@@ -2,11 +2,14 @@ use crate::database;
 use crate::database::PgPool;
 use crate::database::models::ids::DBUserId;
 use crate::database::models::notification_item::NotificationBuilder;
+use crate::database::models::payouts_split_item::DBPayoutsSplit;
+use crate::database::models::team_item::DBTeamMember;
 use crate::file_hosting::FileHost;
 use crate::models::notifications::NotificationBody;
 use crate::queue::analytics::cache::cache_analytics;
 use crate::queue::billing::{index_billing, index_subscriptions};
 use crate::queue::email::EmailQueue;
+use crate::queue::file_deltas::compute_pending_file_deltas;
 use crate::queue::file_scan::scan_all_pending_files;
 use crate::queue::payouts::{
     PayoutsQueue, index_payouts_notifications,
@@ -14,6 +17,7 @@ use crate::queue::payouts::{
     process_payout, remove_payouts_for_refunded_charges,
 };
 use crate::search::SearchBackend;
+use crate::sync::realtime::broadcast_realtime_notifications;
 use crate::util::anrok;
 use actix_web::web;
 use clap::ValueEnum;
@@ -37,15 +41,26 @@ pub enum BackgroundTask {
     /// Queries server project analytics (e.g. number of verified plays in last
     /// 2 weeks for server projects) and caches them in Redis.
     CacheAnalytics,
-    /// Attempts to ping Minecraft Java servers as if we were a client, to
-    /// collect info on if they're online, game version, description, etc.
+    /// Attempts to ping Minecraft Java and Bedrock servers as if we were a
+    /// client, to collect info on if they're online, game version,
+    /// description, etc.
     PingMinecraftJavaServers,
+    /// Re-checks that verified Minecraft Java server listings still prove
+    /// control of their address, and unverifies those that keep failing.
+    RecheckServerVerifications,
     /// Finds files of versions which have not been scanned for attributions
     /// yet, extracts them to find file overrides, and finds any overrides which
     /// require attribution from the creator.
     ScanPendingFiles,
+    /// Computes binary deltas between the primary files of consecutive
+    /// versions of each project, so clients can update content without
+    /// downloading the full file.
+    ComputeFileDeltas,
     /// Queues Discord Creator Club role claim emails for newly eligible users.
     DiscordRoleEmailCampaign,
+    /// Sends pending project webhook deliveries, including retries of failed
+    /// ones which are due.
+    DeliverProjectWebhooks,
 }
 
 impl BackgroundTask {
@@ -120,6 +135,10 @@ impl BackgroundTask {
                 )
                 .await
             }
+            RecheckServerVerifications => {
+                recheck_server_verifications(pool, redis_pool, kafka_client)
+                    .await
+            }
             ScanPendingFiles => {
                 scan_all_pending_files(
                     &pool,
@@ -128,9 +147,13 @@ impl BackgroundTask {
                 )
                 .await
             }
+            ComputeFileDeltas => {
+                compute_pending_file_deltas(&pool, file_host.into_inner()).await
+            }
             DiscordRoleEmailCampaign => {
                 discord_role_email_campaign(pool, redis_pool).await
             }
+            DeliverProjectWebhooks => deliver_project_webhooks(pool).await,
         }
     }
 }
@@ -156,6 +179,25 @@ pub async fn run_email(email_queue: EmailQueue) -> eyre::Result<()> {
     Ok(())
 }
 
+pub async fn deliver_project_webhooks(pool: PgPool) -> eyre::Result<()> {
+    let client = crate::queue::project_webhooks::build_client();
+
+    // Only send 20 deliveries at a time, to reduce transaction length, for a
+    // total of 1000 deliveries.
+    for _ in 0..50 {
+        let delivered =
+            crate::queue::project_webhooks::deliver_pending(&pool, &client, 20)
+                .await
+                .wrap_err("failed to deliver project webhooks")?;
+        if !delivered {
+            info!("No more project webhooks to deliver");
+            break;
+        }
+    }
+
+    Ok(())
+}
+
 pub async fn update_bank_balances(pool: PgPool) -> eyre::Result<()> {
     let payouts_queue = PayoutsQueue::new();
 
@@ -232,6 +274,15 @@ pub async fn payouts(
     redis_pool: RedisPool,
 ) -> eyre::Result<()> {
     info!("Started running payouts");
+    let updated_teams = DBPayoutsSplit::apply_due(&pool)
+        .await
+        .wrap_err("applying scheduled payouts splits failed")?;
+    for team_id in updated_teams {
+        DBTeamMember::clear_cache(team_id, &redis_pool)
+            .await
+            .wrap_err("clearing cached team failed")?;
+    }
+
     process_payout(&pool, &clickhouse)
         .await
         .wrap_err("payout processing failed")?;
@@ -312,19 +363,23 @@ pub async fn discord_role_email_campaign(
 
     let count = user_ids.len();
 
-    if !user_ids.is_empty() {
+    let notification_ids = if user_ids.is_empty() {
+        Vec::new()
+    } else {
         NotificationBuilder {
             body: NotificationBody::DiscordRoleCreatorClub,
         }
         .insert_many(user_ids, &mut txn, &redis_pool)
         .await
-        .wrap_err("failed to queue Discord role email notifications")?;
-    }
+        .wrap_err("failed to queue Discord role email notifications")?
+    };
 
     txn.commit()
         .await
         .wrap_err("failed to commit Discord role email campaign transaction")?;
 
+    broadcast_realtime_notifications(&redis_pool, notification_ids).await;
+
     info!(count, "Finished indexing Discord role email campaign");
     Ok(())
 }
@@ -360,7 +415,7 @@ pub async fn ping_minecraft_java_servers(
     clickhouse: clickhouse::Client,
     kafka_client: web::Data<crate::util::kafka::KafkaClientState>,
 ) -> eyre::Result<()> {
-    info!("Started pinging Minecraft Java servers");
+    info!("Started pinging Minecraft servers");
 
     let incremental_search_queue =
         crate::search::incremental::IncrementalSearchQueue::new(kafka_client);
@@ -375,13 +430,42 @@ pub async fn ping_minecraft_java_servers(
         .ping_minecraft_java_servers()
         .await
         .wrap_err("failed to ping Minecraft Java servers")?;
+    server_ping_queue
+        .ping_minecraft_bedrock_servers()
+        .await
+        .wrap_err("failed to ping Minecraft Bedrock servers")?;
+    incremental_search_queue
+        .drain()
+        .await
+        .wrap_err("failed to drain incremental search queue")?;
+    info!("Successfully pinged Minecraft servers");
+
+    info!("Done pinging Minecraft servers");
+    Ok(())
+}
+
+pub async fn recheck_server_verifications(
+    pool: PgPool,
+    redis_pool: RedisPool,
+    kafka_client: web::Data<crate::util::kafka::KafkaClientState>,
+) -> eyre::Result<()> {
+    info!("Started re-checking server verifications");
+
+    let incremental_search_queue =
+        crate::search::incremental::IncrementalSearchQueue::new(kafka_client);
+    crate::queue::server_verification::recheck_server_verifications(
+        &pool,
+        &redis_pool,
+        &incremental_search_queue,
+    )
+    .await
+    .wrap_err("failed to re-check server verifications")?;
     incremental_search_queue
         .drain()
         .await
         .wrap_err("failed to drain incremental search queue")?;
-    info!("Successfully pinged Minecraft Java servers");
 
-    info!("Done pinging Minecraft Java servers");
+    info!("Done re-checking server verifications");
     Ok(())
 }
 
@@ -262,6 +262,16 @@ pub async fn run_migrations_on_database(
         .execute()
         .await?;
 
+    client
+        .query(&format!(
+            "
+            ALTER TABLE {database}.{MINECRAFT_JAVA_SERVER_PINGS} {cluster_line}
+            ADD COLUMN IF NOT EXISTS edition LowCardinality(String) DEFAULT 'java'
+            "
+        ))
+        .execute()
+        .await?;
+
     client
         .query(&format!(
             "
@@ -6,5 +6,6 @@ pub use models::DBProject;
 pub use models::DBVersion;
 pub use postgres_database::{
     Acquire, Executor, MIGRATOR, PgPool, PgTransaction, ReadOnlyPgPool,
-    check_for_migrations, connect_all, register_and_set_metrics,
+    check_for_migrations, connect_all, query_analysis_layer,
+    register_and_set_metrics,
 };
@@ -0,0 +1,228 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::team_item::TeamAssociationId;
+use super::{DBOrganizationId, DBProjectId, DBUserId, DatabaseError};
+use crate::models::audit_log::{AuditLogAction, AuditLogTarget, diff};
+
+pub struct AuditLogEntryBuilder {
+    pub actor_id: DBUserId,
+    /// If set and `organization_id` isn't, the entry is also attributed to
+    /// the organization the project currently belongs to.
+    pub project_id: Option<DBProjectId>,
+    pub organization_id: Option<DBOrganizationId>,
+    pub action: AuditLogAction,
+    pub target: AuditLogTarget,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
+
+impl AuditLogEntryBuilder {
+    pub fn project(
+        actor_id: DBUserId,
+        project_id: DBProjectId,
+        action: AuditLogAction,
+        target: AuditLogTarget,
+    ) -> Self {
+        Self {
+            actor_id,
+            project_id: Some(project_id),
+            organization_id: None,
+            action,
+            target,
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn organization(
+        actor_id: DBUserId,
+        organization_id: DBOrganizationId,
+        action: AuditLogAction,
+        target: AuditLogTarget,
+    ) -> Self {
+        Self {
+            actor_id,
+            project_id: None,
+            organization_id: Some(organization_id),
+            action,
+            target,
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn team(
+        actor_id: DBUserId,
+        association: TeamAssociationId,
+        action: AuditLogAction,
+        target: AuditLogTarget,
+    ) -> Self {
+        match association {
+            TeamAssociationId::Project(project_id) => {
+                Self::project(actor_id, project_id, action, target)
+            }
+            TeamAssociationId::Organization(organization_id) => {
+                Self::organization(actor_id, organization_id, action, target)
+            }
+        }
+    }
+
+    pub fn before(mut self, before: &impl Serialize) -> Self {
+        self.before = Some(
+            serde_json::to_value(before).expect("serialization shouldn't fail"),
+        );
+        self
+    }
+
+    pub fn after(mut self, after: &impl Serialize) -> Self {
+        self.after = Some(
+            serde_json::to_value(after).expect("serialization shouldn't fail"),
+        );
+        self
+    }
+
+    /// Records only the fields which differ between `before` and `after`.
+    ///
+    /// Returns `None` if nothing changed, in which case there's nothing to
+    /// log.
+    pub fn changes(
+        mut self,
+        before: &impl Serialize,
+        after: &impl Serialize,
+    ) -> Option<Self> {
+        let (before, after) = diff(before, after)?;
+        self.before = Some(before);
+        self.after = Some(after);
+        Some(self)
+    }
+
+    pub async fn insert(
+        &self,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "
+            INSERT INTO audit_log (actor_id, project_id, organization_id, action, target, before, after)
+            VALUES (
+                $1, $2,
+                COALESCE($3, (SELECT organization_id FROM mods WHERE id = $2)),
+                $4, $5, $6, $7
+            )
+            ",
+            self.actor_id as DBUserId,
+            self.project_id.map(|x| x.0),
+            self.organization_id.map(|x| x.0),
+            self.action.as_str(),
+            serde_json::to_value(&self.target)?,
+            self.before,
+            self.after,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DBAuditLogEntry {
+    pub id: i64,
+    pub actor_id: DBUserId,
+    pub project_id: Option<DBProjectId>,
+    pub organization_id: Option<DBOrganizationId>,
+    pub action: AuditLogAction,
+    pub target: AuditLogTarget,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub created: DateTime<Utc>,
+}
+
+struct AuditLogQueryResult {
+    id: i64,
+    actor_id: i64,
+    project_id: Option<i64>,
+    organization_id: Option<i64>,
+    action: String,
+    target: serde_json::Value,
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+    created: DateTime<Utc>,
+}
+
+impl TryFrom<AuditLogQueryResult> for DBAuditLogEntry {
+    type Error = DatabaseError;
+
+    fn try_from(r: AuditLogQueryResult) -> Result<Self, Self::Error> {
+        Ok(DBAuditLogEntry {
+            id: r.id,
+            actor_id: DBUserId(r.actor_id),
+            project_id: r.project_id.map(DBProjectId),
+            organization_id: r.organization_id.map(DBOrganizationId),
+            action: AuditLogAction::from_string(&r.action),
+            target: serde_json::from_value(r.target)?,
+            before: r.before,
+            after: r.after,
+            created: r.created,
+        })
+    }
+}
+
+impl DBAuditLogEntry {
+    /// Gets a page of a project's entries, newest first.
+    pub async fn get_project(
+        project_id: DBProjectId,
+        count: i64,
+        offset: i64,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<Vec<Self>, DatabaseError> {
+        sqlx::query_as!(
+            AuditLogQueryResult,
+            "
+            SELECT id, actor_id, project_id, organization_id, action, target, before, after, created
+            FROM audit_log
+            WHERE project_id = $1
+            ORDER BY created DESC, id DESC
+            OFFSET $3
+            LIMIT $2
+            ",
+            project_id as DBProjectId,
+            count,
+            offset,
+        )
+        .fetch_all(exec)
+        .await?
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect()
+    }
+
+    /// Gets a page of an organization's entries, including those of the
+    /// projects it owned at the time, newest first.
+    pub async fn get_organization(
+        organization_id: DBOrganizationId,
+        count: i64,
+        offset: i64,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<Vec<Self>, DatabaseError> {
+        sqlx::query_as!(
+            AuditLogQueryResult,
+            "
+            SELECT id, actor_id, project_id, organization_id, action, target, before, after, created
+            FROM audit_log
+            WHERE organization_id = $1
+            ORDER BY created DESC, id DESC
+            OFFSET $3
+            LIMIT $2
+            ",
+            organization_id as DBOrganizationId,
+            count,
+            offset,
+        )
+        .fetch_all(exec)
+        .await?
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect()
+    }
+}
@@ -67,6 +67,12 @@ pub enum DBFlow {
         #[serde_binhum(binary(with = "json_string"))]
         state: DiscoverableAuthentication,
     },
+    /// Authenticates a single realtime event stream, for clients which can't
+    /// set the `Authorization` header.
+    EventStream {
+        user_id: DBUserId,
+        scopes: Scopes,
+    },
 }
 
 mod json_string {
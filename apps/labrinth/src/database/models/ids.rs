@@ -2,11 +2,12 @@ use super::DatabaseError;
 use crate::database::PgTransaction;
 use crate::models::ids::{
     AffiliateCodeId, AnalyticsEventId, AttributionGroupId, CampaignDonationId,
-    ChargeId, CollectionId, FileId, ImageId, NotificationId,
+    ChargeId, CollectionId, FileId, ImageId, ModerationMacroId, NotificationId,
     OAuthAccessTokenId, OAuthClientAuthorizationId, OAuthClientId,
-    OAuthRedirectUriId, OrganizationId, PasskeyId, PatId, PayoutId, ProductId,
-    ProductPriceId, ProjectId, ReportId, SessionId, TeamId, TeamMemberId,
-    ThreadId, ThreadMessageId, UserSubscriptionId, VersionId,
+    OAuthRedirectUriId, OrganizationId, OrganizationRoleId, PasskeyId, PatId,
+    PayoutId, ProductId, ProductPriceId, ProjectId, ProjectTransferId,
+    ProjectWebhookId, ReportId, SessionId, TeamId, TeamMemberId, ThreadId,
+    ThreadMessageId, UserSubscriptionId, VersionId,
 };
 use ariadne::ids::base62_impl::to_base62;
 use ariadne::ids::{UserId, random_base62_rng, random_base62_rng_range};
@@ -184,6 +185,10 @@ db_id_interface!(
     ImageId,
     generator: generate_image_id @ "uploaded_images",
 );
+db_id_interface!(
+    ModerationMacroId,
+    generator: generate_moderation_macro_id @ "moderation_macros",
+);
 db_id_interface!(
     NotificationId,
     generator: generate_notification_id @ "notifications",
@@ -217,6 +222,10 @@ db_id_interface!(
     PayoutId,
     generator: generate_payout_id @ "payouts",
 );
+db_id_interface!(
+    OrganizationRoleId,
+    generator: generate_organization_role_id @ "organization_roles",
+);
 db_id_interface!(
     ProductId,
     generator: generate_product_id @ "products",
@@ -229,6 +238,14 @@ db_id_interface!(
     ProjectId,
     generator: generate_project_id @ "mods",
 );
+db_id_interface!(
+    ProjectTransferId,
+    generator: generate_project_transfer_id @ "project_transfers",
+);
+db_id_interface!(
+    ProjectWebhookId,
+    generator: generate_project_webhook_id @ "project_webhooks",
+);
 db_id_interface!(
     ReportId,
     generator: generate_report_id @ "reports",
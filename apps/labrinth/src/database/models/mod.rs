@@ -2,6 +2,7 @@ use thiserror::Error;
 
 pub mod affiliate_code_item;
 pub mod analytics_event_item;
+pub mod audit_log_item;
 pub mod blocked_user_item;
 pub mod categories;
 pub mod charge_item;
@@ -15,6 +16,7 @@ pub mod legacy_loader_fields;
 pub mod loader_fields;
 pub mod moderation_external_item;
 pub mod moderation_lock_item;
+pub mod moderation_macro_item;
 pub mod moderation_note_item;
 pub mod notification_item;
 pub mod notifications_deliveries_item;
@@ -24,15 +26,21 @@ pub mod oauth_client_authorization_item;
 pub mod oauth_client_item;
 pub mod oauth_token_item;
 pub mod organization_item;
+pub mod organization_role_item;
 pub mod passkey_item;
 pub mod pat_item;
 pub mod payout_item;
+pub mod payouts_split_item;
 pub mod payouts_values_notifications;
+pub mod pgp_key_item;
 pub mod product_item;
 pub mod products_tax_identifier_item;
 pub mod project_disclosure_item;
 pub mod project_item;
+pub mod project_transfer_item;
+pub mod project_webhook_item;
 pub mod report_item;
+pub mod server_verification_item;
 pub mod session_item;
 pub mod team_item;
 pub mod thread_item;
@@ -45,6 +53,7 @@ pub mod users_notifications_preferences_item;
 pub mod users_redeemals;
 pub mod users_subscriptions_affiliations;
 pub mod users_subscriptions_credits;
+pub mod version_file_delta_item;
 pub mod version_item;
 
 pub use affiliate_code_item::DBAffiliateCode;
@@ -0,0 +1,174 @@
+use chrono::{DateTime, Utc};
+
+use super::{DBModerationMacroId, DBUserId, DatabaseError};
+use crate::models::projects::ProjectStatus;
+
+/// A canned moderator response, which posts a thread message and may set a
+/// project's status and add to its owner's moderation note when applied.
+#[derive(Debug, Clone)]
+pub struct DBModerationMacro {
+    pub id: DBModerationMacroId,
+    pub name: String,
+    pub message: String,
+    pub private: bool,
+    pub status: Option<ProjectStatus>,
+    pub note: Option<String>,
+    pub created: DateTime<Utc>,
+    pub last_modified: DateTime<Utc>,
+    pub last_modified_by: Option<DBUserId>,
+    pub uses: i64,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+impl DBModerationMacro {
+    pub async fn insert(
+        &self,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "
+            INSERT INTO moderation_macros (id, name, message, private, status, note, created, last_modified, last_modified_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ",
+            self.id as DBModerationMacroId,
+            self.name,
+            self.message,
+            self.private,
+            self.status.map(|x| x.as_str()),
+            self.note,
+            self.created,
+            self.last_modified,
+            self.last_modified_by.map(|x| x.0),
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get<'a, E>(
+        id: DBModerationMacroId,
+        exec: E,
+    ) -> Result<Option<Self>, DatabaseError>
+    where
+        E: crate::database::Executor<'a, Database = sqlx::Postgres>,
+    {
+        let row = sqlx::query!(
+            "
+            SELECT id, name, message, private, status, note, created, last_modified, last_modified_by, uses, last_used
+            FROM moderation_macros
+            WHERE id = $1
+            ",
+            id as DBModerationMacroId,
+        )
+        .fetch_optional(exec)
+        .await?;
+
+        Ok(row.map(|r| DBModerationMacro {
+            id: DBModerationMacroId(r.id),
+            name: r.name,
+            message: r.message,
+            private: r.private,
+            status: r.status.as_deref().map(ProjectStatus::from_string),
+            note: r.note,
+            created: r.created,
+            last_modified: r.last_modified,
+            last_modified_by: r.last_modified_by.map(DBUserId),
+            uses: r.uses,
+            last_used: r.last_used,
+        }))
+    }
+
+    pub async fn get_all<'a, E>(exec: E) -> Result<Vec<Self>, DatabaseError>
+    where
+        E: crate::database::Executor<'a, Database = sqlx::Postgres>,
+    {
+        let rows = sqlx::query!(
+            "
+            SELECT id, name, message, private, status, note, created, last_modified, last_modified_by, uses, last_used
+            FROM moderation_macros
+            ORDER BY name
+            ",
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| DBModerationMacro {
+                id: DBModerationMacroId(r.id),
+                name: r.name,
+                message: r.message,
+                private: r.private,
+                status: r.status.as_deref().map(ProjectStatus::from_string),
+                note: r.note,
+                created: r.created,
+                last_modified: r.last_modified,
+                last_modified_by: r.last_modified_by.map(DBUserId),
+                uses: r.uses,
+                last_used: r.last_used,
+            })
+            .collect())
+    }
+
+    /// Replaces the macro's editable fields with those of `self`.
+    pub async fn update(
+        &self,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "
+            UPDATE moderation_macros
+            SET name = $2, message = $3, private = $4, status = $5, note = $6, last_modified = $7, last_modified_by = $8
+            WHERE id = $1
+            ",
+            self.id as DBModerationMacroId,
+            self.name,
+            self.message,
+            self.private,
+            self.status.map(|x| x.as_str()),
+            self.note,
+            self.last_modified,
+            self.last_modified_by.map(|x| x.0),
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove(
+        id: DBModerationMacroId,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query!(
+            "
+            DELETE FROM moderation_macros
+            WHERE id = $1
+            ",
+            id as DBModerationMacroId,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn record_use(
+        id: DBModerationMacroId,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "
+            UPDATE moderation_macros
+            SET uses = uses + 1, last_used = NOW()
+            WHERE id = $1
+            ",
+            id as DBModerationMacroId,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+}
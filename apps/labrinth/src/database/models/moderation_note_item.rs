@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use xredis::RedisPool;
 
 use super::{DBOrganizationId, DBUserId, DatabaseError};
+use crate::database::PgTransaction;
 
 const MODERATION_NOTES_USERS_NAMESPACE: &str = "moderation_notes_users:v4";
 const MODERATION_NOTES_ORGANIZATIONS_NAMESPACE: &str =
@@ -271,6 +272,61 @@ impl DBModerationNote {
         Ok(result)
     }
 
+    /// Appends a paragraph to a user's or organization's notes, creating
+    /// them if there are none.
+    pub async fn append(
+        user_id: Option<DBUserId>,
+        organization_id: Option<DBOrganizationId>,
+        last_author: DBUserId,
+        notes: &str,
+        transaction: &mut PgTransaction<'_>,
+    ) -> Result<(), DatabaseError> {
+        // The notes may be created concurrently between the update and
+        // insert, in which case the update is tried again.
+        for _ in 0..2 {
+            let appended = sqlx::query_scalar!(
+                r#"
+                UPDATE moderation_notes
+                SET
+                  last_modified = NOW(),
+                  last_author = $1,
+                  version = version + 1,
+                  notes = CASE WHEN notes = '' THEN $2 ELSE notes || E'\n\n' || $2 END
+                WHERE
+                  ($3::bigint IS NOT NULL AND user_id = $3)
+                  OR ($4::bigint IS NOT NULL AND organization_id = $4)
+                RETURNING version
+                "#,
+                last_author.0,
+                notes,
+                user_id.map(|x| x.0),
+                organization_id.map(|x| x.0),
+            )
+            .fetch_optional(&mut *transaction)
+            .await?;
+            if appended.is_some() {
+                return Ok(());
+            }
+
+            let inserted = Self::insert(
+                user_id,
+                organization_id,
+                last_author,
+                Some(notes),
+                None,
+                &mut *transaction,
+            )
+            .await?;
+            if inserted.is_some() {
+                return Ok(());
+            }
+        }
+
+        Err(DatabaseError::Internal(eyre::eyre!(
+            "moderation note was modified concurrently"
+        )))
+    }
+
     pub async fn clear_user_cache(
         user_id: DBUserId,
         redis: &RedisPool,
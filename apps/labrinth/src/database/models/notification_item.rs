@@ -40,9 +40,10 @@ impl NotificationBuilder {
         user: DBUserId,
         transaction: &mut PgTransaction<'_>,
         redis: &RedisPool,
-    ) -> Result<(), DatabaseError> {
-        self.insert_many(vec![user], transaction, redis).await?;
-        Ok(())
+    ) -> Result<DBNotificationId, DatabaseError> {
+        let notification_ids =
+            self.insert_many(vec![user], transaction, redis).await?;
+        Ok(notification_ids[0])
     }
 
     pub async fn insert_many_payout_notifications(
@@ -50,7 +51,7 @@ impl NotificationBuilder {
         dates_available: Vec<DateTime<Utc>>,
         transaction: &mut PgTransaction<'_>,
         redis: &RedisPool,
-    ) -> Result<(), DatabaseError> {
+    ) -> Result<Vec<DBNotificationId>, DatabaseError> {
         let notification_ids =
             generate_many_notification_ids(users.len(), &mut *transaction)
                 .await?;
@@ -101,7 +102,7 @@ impl NotificationBuilder {
         .await?;
 
         if inserted_rows.is_empty() {
-            return Ok(());
+            return Ok(Vec::new());
         }
 
         let inserted_notification_ids: Vec<i64> =
@@ -128,7 +129,10 @@ impl NotificationBuilder {
         )
         .await?;
 
-        Ok(())
+        Ok(inserted_notification_ids
+            .into_iter()
+            .map(DBNotificationId)
+            .collect())
     }
 
     async fn insert_many_records(
@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use super::{
+    DBOrganizationId, DBOrganizationRoleId, DBProjectId, DatabaseError,
+};
+use crate::models::teams::{OrganizationPermissions, ProjectPermissions};
+
+/// A named set of permissions which members of an organization can be
+/// assigned, instead of having their permissions set individually.
+#[derive(Debug, Clone)]
+pub struct DBOrganizationRole {
+    pub id: DBOrganizationRoleId,
+    pub organization_id: DBOrganizationId,
+    pub name: String,
+    /// The default permissions of the role's members in the organization's
+    /// projects.
+    pub permissions: ProjectPermissions,
+    pub organization_permissions: OrganizationPermissions,
+    /// Permissions of the role's members in specific projects, replacing
+    /// `permissions`.
+    pub project_overrides: HashMap<DBProjectId, ProjectPermissions>,
+    pub created: DateTime<Utc>,
+}
+
+impl DBOrganizationRole {
+    pub async fn insert(
+        &self,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "
+            INSERT INTO organization_roles (id, organization_id, name, permissions, organization_permissions, created)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ",
+            self.id as DBOrganizationRoleId,
+            self.organization_id as DBOrganizationId,
+            self.name,
+            self.permissions.bits() as i64,
+            self.organization_permissions.bits() as i64,
+            self.created,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get<'a, E>(
+        id: DBOrganizationRoleId,
+        exec: E,
+    ) -> Result<Option<Self>, DatabaseError>
+    where
+        E: crate::database::Executor<'a, Database = sqlx::Postgres> + Copy,
+    {
+        Ok(Self::get_many(&[id], exec).await?.into_iter().next())
+    }
+
+    pub async fn get_many<'a, E>(
+        ids: &[DBOrganizationRoleId],
+        exec: E,
+    ) -> Result<Vec<Self>, DatabaseError>
+    where
+        E: crate::database::Executor<'a, Database = sqlx::Postgres> + Copy,
+    {
+        let ids = ids.iter().map(|x| x.0).collect::<Vec<_>>();
+
+        let roles = sqlx::query!(
+            "
+            SELECT id, organization_id, name, permissions, organization_permissions, created
+            FROM organization_roles
+            WHERE id = ANY($1)
+            ORDER BY name
+            ",
+            &ids,
+        )
+        .fetch_all(exec)
+        .await?;
+
+        let mut overrides = Self::get_project_overrides(&ids, exec).await?;
+
+        Ok(roles
+            .into_iter()
+            .map(|r| DBOrganizationRole {
+                id: DBOrganizationRoleId(r.id),
+                organization_id: DBOrganizationId(r.organization_id),
+                name: r.name,
+                permissions: ProjectPermissions::from_bits(
+                    r.permissions as u64,
+                )
+                .unwrap_or_default(),
+                organization_permissions: OrganizationPermissions::from_bits(
+                    r.organization_permissions as u64,
+                )
+                .unwrap_or_default(),
+                project_overrides: overrides.remove(&r.id).unwrap_or_default(),
+                created: r.created,
+            })
+            .collect())
+    }
+
+    pub async fn get_organization<'a, E>(
+        organization_id: DBOrganizationId,
+        exec: E,
+    ) -> Result<Vec<Self>, DatabaseError>
+    where
+        E: crate::database::Executor<'a, Database = sqlx::Postgres> + Copy,
+    {
+        let ids = sqlx::query!(
+            "
+            SELECT id
+            FROM organization_roles
+            WHERE organization_id = $1
+            ",
+            organization_id as DBOrganizationId,
+        )
+        .fetch_all(exec)
+        .await?
+        .into_iter()
+        .map(|x| DBOrganizationRoleId(x.id))
+        .collect::<Vec<_>>();
+
+        Self::get_many(&ids, exec).await
+    }
+
+    async fn get_project_overrides<'a, E>(
+        role_ids: &[i64],
+        exec: E,
+    ) -> Result<
+        HashMap<i64, HashMap<DBProjectId, ProjectPermissions>>,
+        DatabaseError,
+    >
+    where
+        E: crate::database::Executor<'a, Database = sqlx::Postgres>,
+    {
+        let mut overrides: HashMap<i64, HashMap<_, _>> = HashMap::new();
+
+        for o in sqlx::query!(
+            "
+            SELECT role_id, project_id, permissions
+            FROM organization_role_project_overrides
+            WHERE role_id = ANY($1)
+            ",
+            role_ids,
+        )
+        .fetch_all(exec)
+        .await?
+        {
+            overrides.entry(o.role_id).or_default().insert(
+                DBProjectId(o.project_id),
+                ProjectPermissions::from_bits(o.permissions as u64)
+                    .unwrap_or_default(),
+            );
+        }
+
+        Ok(overrides)
+    }
+
+    /// Updates the name and permissions of the role.
+    pub async fn update(
+        &self,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "
+            UPDATE organization_roles
+            SET name = $2, permissions = $3, organization_permissions = $4
+            WHERE id = $1
+            ",
+            self.id as DBOrganizationRoleId,
+            self.name,
+            self.permissions.bits() as i64,
+            self.organization_permissions.bits() as i64,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes the role. Members which had it assigned fall back to their own
+    /// permissions.
+    pub async fn remove(
+        id: DBOrganizationRoleId,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "
+            DELETE FROM organization_roles
+            WHERE id = $1
+            ",
+            id as DBOrganizationRoleId,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_project_override(
+        id: DBOrganizationRoleId,
+        project_id: DBProjectId,
+        permissions: ProjectPermissions,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "
+            INSERT INTO organization_role_project_overrides (role_id, project_id, permissions)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (role_id, project_id)
+            DO UPDATE SET permissions = EXCLUDED.permissions
+            ",
+            id as DBOrganizationRoleId,
+            project_id as DBProjectId,
+            permissions.bits() as i64,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove_project_override(
+        id: DBOrganizationRoleId,
+        project_id: DBProjectId,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "
+            DELETE FROM organization_role_project_overrides
+            WHERE role_id = $1 AND project_id = $2
+            ",
+            id as DBOrganizationRoleId,
+            project_id as DBProjectId,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Removes the overrides of all of an organization's roles for a project,
+    /// for when it leaves the organization.
+    pub async fn remove_project_overrides(
+        organization_id: DBOrganizationId,
+        project_id: DBProjectId,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "
+            DELETE FROM organization_role_project_overrides o
+            USING organization_roles r
+            WHERE o.role_id = r.id AND r.organization_id = $1 AND o.project_id = $2
+            ",
+            organization_id as DBOrganizationId,
+            project_id as DBProjectId,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+}
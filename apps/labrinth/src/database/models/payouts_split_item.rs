@@ -0,0 +1,185 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use crate::database::models::{DBTeamId, DBUserId, DatabaseError};
+
+/// A payouts split a team member had, has or will have from a point in time.
+///
+/// Immediate changes to `team_members.payouts_split` are recorded here by a
+/// database trigger. Changes scheduled for the future are only stored here
+/// until [`DBPayoutsSplit::apply_due`] copies them to the team member.
+#[derive(Debug, Clone)]
+pub struct DBPayoutsSplit {
+    pub team_id: DBTeamId,
+    pub user_id: DBUserId,
+    pub payouts_split: Decimal,
+    pub effective_from: DateTime<Utc>,
+}
+
+impl DBPayoutsSplit {
+    /// Schedules a team member's payouts split to change at `effective_from`.
+    pub async fn schedule(
+        team_id: DBTeamId,
+        user_id: DBUserId,
+        payouts_split: Decimal,
+        effective_from: DateTime<Utc>,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "
+            INSERT INTO team_member_payouts_splits (team_id, user_id, payouts_split, effective_from)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (team_id, user_id, effective_from) DO UPDATE
+            SET payouts_split = EXCLUDED.payouts_split
+            ",
+            team_id as DBTeamId,
+            user_id as DBUserId,
+            payouts_split,
+            effective_from,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Removes the changes to a team member's payouts split which are
+    /// scheduled to take effect in the future.
+    pub async fn remove_scheduled(
+        team_id: DBTeamId,
+        user_id: DBUserId,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "
+            DELETE FROM team_member_payouts_splits
+            WHERE team_id = $1 AND user_id = $2 AND effective_from > NOW()
+            ",
+            team_id as DBTeamId,
+            user_id as DBUserId,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets every change to the payouts splits of members of `team_ids` which
+    /// took effect before `before`, oldest first.
+    pub async fn get_many(
+        team_ids: &[DBTeamId],
+        before: DateTime<Utc>,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<Vec<Self>, DatabaseError> {
+        let team_ids = team_ids.iter().map(|x| x.0).collect::<Vec<_>>();
+
+        let rows = sqlx::query!(
+            "
+            SELECT team_id, user_id, payouts_split, effective_from
+            FROM team_member_payouts_splits
+            WHERE team_id = ANY($1) AND effective_from < $2
+            ORDER BY effective_from
+            ",
+            &team_ids[..],
+            before,
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Self {
+                team_id: DBTeamId(row.team_id),
+                user_id: DBUserId(row.user_id),
+                payouts_split: row.payouts_split,
+                effective_from: row.effective_from,
+            })
+            .collect())
+    }
+
+    /// Gets the payouts splits a user had in `team_ids` between `from` and
+    /// `to`, including the one already in effect at `from`, oldest first.
+    pub async fn get_in_effect(
+        team_ids: &[DBTeamId],
+        user_id: DBUserId,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<Vec<Self>, DatabaseError> {
+        let team_ids = team_ids.iter().map(|x| x.0).collect::<Vec<_>>();
+
+        let rows = sqlx::query!(
+            "
+            SELECT s.team_id, s.user_id, s.payouts_split, s.effective_from
+            FROM team_member_payouts_splits s
+            WHERE
+                s.team_id = ANY($1)
+                AND s.user_id = $2
+                AND s.effective_from < $4
+                AND s.effective_from >= COALESCE(
+                    (
+                        SELECT MAX(p.effective_from)
+                        FROM team_member_payouts_splits p
+                        WHERE
+                            p.team_id = s.team_id
+                            AND p.user_id = s.user_id
+                            AND p.effective_from <= $3
+                    ),
+                    '-infinity'
+                )
+            ORDER BY s.effective_from
+            ",
+            &team_ids[..],
+            user_id as DBUserId,
+            from,
+            to,
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Self {
+                team_id: DBTeamId(row.team_id),
+                user_id: DBUserId(row.user_id),
+                payouts_split: row.payouts_split,
+                effective_from: row.effective_from,
+            })
+            .collect())
+    }
+
+    /// Copies scheduled payouts splits which have taken effect to their team
+    /// members, returning the teams which were updated.
+    pub async fn apply_due(
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<Vec<DBTeamId>, DatabaseError> {
+        let rows = sqlx::query!(
+            "
+            UPDATE team_members tm
+            SET payouts_split = s.payouts_split
+            FROM (
+                SELECT DISTINCT ON (team_id, user_id) team_id, user_id, payouts_split
+                FROM team_member_payouts_splits
+                WHERE effective_from <= NOW()
+                ORDER BY team_id, user_id, effective_from DESC
+            ) s
+            WHERE
+                tm.team_id = s.team_id
+                AND tm.user_id = s.user_id
+                AND tm.payouts_split IS DISTINCT FROM s.payouts_split
+            RETURNING tm.team_id
+            "
+        )
+        .fetch_all(exec)
+        .await?;
+
+        let mut team_ids = rows
+            .into_iter()
+            .map(|row| DBTeamId(row.team_id))
+            .collect::<Vec<_>>();
+        team_ids.sort_by_key(|id| id.0);
+        team_ids.dedup();
+
+        Ok(team_ids)
+    }
+}
@@ -0,0 +1,173 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{DBOrganizationId, DBProjectId, DBUserId, DatabaseError};
+
+/// An OpenPGP public key registered to a user or an organization, used to
+/// verify detached signatures on their projects' version files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DBPgpKey {
+    pub user_id: Option<DBUserId>,
+    pub organization_id: Option<DBOrganizationId>,
+    /// Uppercase hex fingerprint of the primary key.
+    pub fingerprint: String,
+    /// The ASCII-armored public key.
+    pub public_key: String,
+    pub created: DateTime<Utc>,
+}
+
+impl DBPgpKey {
+    pub async fn insert(
+        &self,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO pgp_keys (user_id, organization_id, fingerprint, public_key, created)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            self.user_id.map(|x| x.0),
+            self.organization_id.map(|x| x.0),
+            self.fingerprint,
+            self.public_key,
+            self.created,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_user(
+        user_id: DBUserId,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<Vec<Self>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT user_id, organization_id, fingerprint, public_key, created
+            FROM pgp_keys
+            WHERE user_id = $1
+            ORDER BY created
+            "#,
+            user_id as DBUserId,
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Self {
+                user_id: row.user_id.map(DBUserId),
+                organization_id: row.organization_id.map(DBOrganizationId),
+                fingerprint: row.fingerprint,
+                public_key: row.public_key,
+                created: row.created,
+            })
+            .collect())
+    }
+
+    pub async fn get_organization(
+        organization_id: DBOrganizationId,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<Vec<Self>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT user_id, organization_id, fingerprint, public_key, created
+            FROM pgp_keys
+            WHERE organization_id = $1
+            ORDER BY created
+            "#,
+            organization_id as DBOrganizationId,
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Self {
+                user_id: row.user_id.map(DBUserId),
+                organization_id: row.organization_id.map(DBOrganizationId),
+                fingerprint: row.fingerprint,
+                public_key: row.public_key,
+                created: row.created,
+            })
+            .collect())
+    }
+
+    /// Gets the keys which may sign a project's files: those of the
+    /// project's accepted team members and of its organization.
+    pub async fn get_for_project(
+        project_id: DBProjectId,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<Vec<Self>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT k.user_id, k.organization_id, k.fingerprint, k.public_key, k.created
+            FROM mods m
+            INNER JOIN pgp_keys k ON
+                k.organization_id = m.organization_id
+                OR k.user_id IN (
+                    SELECT tm.user_id
+                    FROM team_members tm
+                    WHERE tm.team_id = m.team_id AND tm.accepted = TRUE
+                )
+            WHERE m.id = $1
+            "#,
+            project_id as DBProjectId,
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Self {
+                user_id: row.user_id.map(DBUserId),
+                organization_id: row.organization_id.map(DBOrganizationId),
+                fingerprint: row.fingerprint,
+                public_key: row.public_key,
+                created: row.created,
+            })
+            .collect())
+    }
+
+    /// Removes a key from a user, returning whether it was registered.
+    pub async fn remove_user(
+        user_id: DBUserId,
+        fingerprint: &str,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM pgp_keys
+            WHERE user_id = $1 AND fingerprint = $2
+            "#,
+            user_id as DBUserId,
+            fingerprint,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Removes a key from an organization, returning whether it was
+    /// registered.
+    pub async fn remove_organization(
+        organization_id: DBOrganizationId,
+        fingerprint: &str,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM pgp_keys
+            WHERE organization_id = $1 AND fingerprint = $2
+            "#,
+            organization_id as DBOrganizationId,
+            fingerprint,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
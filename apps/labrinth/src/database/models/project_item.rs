@@ -576,89 +576,125 @@ impl DBProject {
     where
         E: crate::database::Acquire<'a, Database = sqlx::Postgres>,
     {
-        let val = redis.get_cached_keys_with_slug(
-            PROJECTS_NAMESPACE,
-            PROJECTS_SLUGS_NAMESPACE,
-            false,
-            project_strings,
-            |ids| async move {
-                let mut exec = exec
-                    .acquire()
-                    .await?;
-                let project_ids_parsed: Vec<i64> = ids
-                    .iter()
-                    .filter_map(|x| parse_base62(&x.to_string()).ok())
-                    .map(|x| x as i64)
-                    .collect();
-                let slugs = ids
-                    .into_iter()
-                    .map(|x| x.to_string().to_lowercase())
-                    .collect::<Vec<_>>();
+        let val = redis
+            .get_cached_keys_with_slug(
+                PROJECTS_NAMESPACE,
+                PROJECTS_SLUGS_NAMESPACE,
+                false,
+                project_strings,
+                |ids| Self::fetch_many(ids, exec, redis),
+            )
+            .await
+            .wrap_internal_err("fetching cached projects")?;
+
+        Ok(val)
+    }
+
+    /// Fetches projects from the database without going through the cache,
+    /// so that a transaction sees the changes it has made.
+    pub async fn get_id_uncached<'a, E>(
+        id: DBProjectId,
+        exec: E,
+        redis: &RedisPool,
+    ) -> Result<Option<ProjectQueryResult>, DatabaseError>
+    where
+        E: crate::database::Acquire<'a, Database = sqlx::Postgres>,
+    {
+        Ok(Self::fetch_many(
+            vec![crate::models::ids::ProjectId::from(id)],
+            exec,
+            redis,
+        )
+        .await?
+        .into_iter()
+        .next()
+        .map(|(_, (_, project))| project))
+    }
+
+    async fn fetch_many<'a, E, T: Display>(
+        ids: Vec<T>,
+        exec: E,
+        redis: &RedisPool,
+    ) -> Result<DashMap<i64, (Option<String>, ProjectQueryResult)>, DatabaseError>
+    where
+        E: crate::database::Acquire<'a, Database = sqlx::Postgres>,
+    {
+        let mut exec = exec.acquire().await?;
+        let project_ids_parsed: Vec<i64> = ids
+            .iter()
+            .filter_map(|x| parse_base62(&x.to_string()).ok())
+            .map(|x| x as i64)
+            .collect();
+        let slugs = ids
+            .into_iter()
+            .map(|x| x.to_string().to_lowercase())
+            .collect::<Vec<_>>();
 
-                let all_version_ids = DashSet::new();
-                let versions: DashMap<DBProjectId, Vec<(DBVersionId, DateTime<Utc>)>> = sqlx::query!(
-                    "
+        let all_version_ids = DashSet::new();
+        let versions: DashMap<DBProjectId, Vec<(DBVersionId, DateTime<Utc>)>> = sqlx::query!(
+            "
                     SELECT DISTINCT mod_id, v.id as id, date_published
                     FROM mods m
                     INNER JOIN versions v ON m.id = v.mod_id AND v.status = ANY($3)
                     WHERE m.id = ANY($1) OR m.slug = ANY($2)
                     ",
-                    &project_ids_parsed,
-                    &slugs,
-                    &*crate::models::projects::VersionStatus::iterator()
-                        .filter(|x| x.is_listed())
-                        .map(|x| x.to_string())
-                        .collect::<Vec<String>>()
-                )
-                    .fetch(&mut exec)
-                    .try_fold(
-                        DashMap::new(),
-                        |acc: DashMap<DBProjectId, Vec<(DBVersionId, DateTime<Utc>)>>, m| {
-                            let version_id = DBVersionId(m.id);
-                            let date_published = m.date_published;
-                            all_version_ids.insert(version_id);
-                            acc.entry(DBProjectId(m.mod_id))
-                                .or_default()
-                                .push((version_id, date_published));
-                            async move { Ok(acc) }
-                        },
-                    )
-                    .await?;
+            &project_ids_parsed,
+            &slugs,
+            &*crate::models::projects::VersionStatus::iterator()
+                .filter(|x| x.is_listed())
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>()
+        )
+            .fetch(&mut exec)
+            .try_fold(
+                DashMap::new(),
+                |acc: DashMap<DBProjectId, Vec<(DBVersionId, DateTime<Utc>)>>, m| {
+                    let version_id = DBVersionId(m.id);
+                    let date_published = m.date_published;
+                    all_version_ids.insert(version_id);
+                    acc.entry(DBProjectId(m.mod_id))
+                        .or_default()
+                        .push((version_id, date_published));
+                    async move { Ok(acc) }
+                },
+            )
+            .await?;
 
-                let loader_field_enum_value_ids = DashSet::new();
-                let version_fields: DashMap<DBProjectId, Vec<QueryVersionField>> = sqlx::query!(
-                    "
+        let loader_field_enum_value_ids = DashSet::new();
+        let version_fields: DashMap<DBProjectId, Vec<QueryVersionField>> = sqlx::query!(
+            "
                     SELECT DISTINCT mod_id, version_id, field_id, int_value, enum_value, string_value
                     FROM versions v
                     INNER JOIN version_fields vf ON v.id = vf.version_id
                     WHERE v.id = ANY($1)
                     ",
-                    &all_version_ids.iter().map(|x| x.0).collect::<Vec<_>>()
-                )
-                    .fetch(&mut exec)
-                    .try_fold(
-                        DashMap::new(),
-                        |acc: DashMap<DBProjectId, Vec<QueryVersionField>>, m| {
-                            let qvf = QueryVersionField {
-                                version_id: DBVersionId(m.version_id),
-                                field_id: LoaderFieldId(m.field_id),
-                                int_value: m.int_value,
-                                enum_value: if m.enum_value == -1  { None } else { Some(LoaderFieldEnumValueId(m.enum_value)) },
-                                string_value: m.string_value,
-                            };
-
-                            if m.enum_value != -1 {
-                                loader_field_enum_value_ids.insert(LoaderFieldEnumValueId(m.enum_value));
-                            }
-
-                            acc.entry(DBProjectId(m.mod_id)).or_default().push(qvf);
-                            async move { Ok(acc) }
-                        },
-                    )
-                    .await?;
+            &all_version_ids.iter().map(|x| x.0).collect::<Vec<_>>()
+        )
+            .fetch(&mut exec)
+            .try_fold(
+                DashMap::new(),
+                |acc: DashMap<DBProjectId, Vec<QueryVersionField>>, m| {
+                    let qvf = QueryVersionField {
+                        version_id: DBVersionId(m.version_id),
+                        field_id: LoaderFieldId(m.field_id),
+                        int_value: m.int_value,
+                        enum_value: if m.enum_value == -1  { None } else { Some(LoaderFieldEnumValueId(m.enum_value)) },
+                        string_value: m.string_value,
+                    };
+
+                    if m.enum_value != -1 {
+                        loader_field_enum_value_ids.insert(LoaderFieldEnumValueId(m.enum_value));
+                    }
+
+                    acc.entry(DBProjectId(m.mod_id)).or_default().push(qvf);
+                    async move { Ok(acc) }
+                },
+            )
+            .await?;
 
-                let loader_field_enum_values: Vec<QueryLoaderFieldEnumValue> = sqlx::query!(
-                    r#"
+        let loader_field_enum_values: Vec<QueryLoaderFieldEnumValue> =
+            sqlx::query!(
+                r#"
                     SELECT DISTINCT id, enum_id, value, ordering, created,
                     metadata->>'type' AS "ty?",
                     (metadata->>'major')::boolean AS "major?"
@@ -666,87 +702,87 @@ impl DBProject {
                     WHERE id = ANY($1)
                     ORDER BY enum_id, ordering, created DESC
                     "#,
-                    &loader_field_enum_value_ids
-                        .iter()
-                        .map(|x| x.0)
-                        .collect::<Vec<_>>()
-                )
-                    .fetch(&mut exec)
-                    .map_ok(|m| QueryLoaderFieldEnumValue {
-                        id: LoaderFieldEnumValueId(m.id),
-                        enum_id: LoaderFieldEnumId(m.enum_id),
-                        value: m.value,
-                        ordering: m.ordering,
-                        created: m.created,
-                        ty: m.ty,
-                        major: m.major,
-                    })
-                    .try_collect()
-                    .await?;
-
-                let mods_gallery: DashMap<DBProjectId, Vec<DBGalleryItem>> = sqlx::query!(
-                    "
+                &loader_field_enum_value_ids
+                    .iter()
+                    .map(|x| x.0)
+                    .collect::<Vec<_>>()
+            )
+            .fetch(&mut exec)
+            .map_ok(|m| QueryLoaderFieldEnumValue {
+                id: LoaderFieldEnumValueId(m.id),
+                enum_id: LoaderFieldEnumId(m.enum_id),
+                value: m.value,
+                ordering: m.ordering,
+                created: m.created,
+                ty: m.ty,
+                major: m.major,
+            })
+            .try_collect()
+            .await?;
+
+        let mods_gallery: DashMap<DBProjectId, Vec<DBGalleryItem>> = sqlx::query!(
+            "
                     SELECT DISTINCT mod_id, mg.image_url, mg.raw_image_url, mg.featured, mg.name, mg.description, mg.created, mg.ordering
                     FROM mods_gallery mg
                     INNER JOIN mods m ON mg.mod_id = m.id
                     WHERE m.id = ANY($1) OR m.slug = ANY($2)
                     ",
-                    &project_ids_parsed,
-                    &slugs
-                ).fetch(&mut exec)
-                    .try_fold(DashMap::new(), |acc : DashMap<DBProjectId, Vec<DBGalleryItem>>, m| {
-                        acc.entry(DBProjectId(m.mod_id))
-                            .or_default()
-                            .push(DBGalleryItem {
-                                image_url: m.image_url,
-                                raw_image_url: m.raw_image_url,
-                                featured: m.featured.unwrap_or(false),
-                                name: m.name,
-                                description: m.description,
-                                created: m.created,
-                                ordering: m.ordering,
-                            });
-                        async move { Ok(acc) }
-                    }
-                    )
-                    .await?;
+            &project_ids_parsed,
+            &slugs
+        ).fetch(&mut exec)
+            .try_fold(DashMap::new(), |acc : DashMap<DBProjectId, Vec<DBGalleryItem>>, m| {
+                acc.entry(DBProjectId(m.mod_id))
+                    .or_default()
+                    .push(DBGalleryItem {
+                        image_url: m.image_url,
+                        raw_image_url: m.raw_image_url,
+                        featured: m.featured.unwrap_or(false),
+                        name: m.name,
+                        description: m.description,
+                        created: m.created,
+                        ordering: m.ordering,
+                    });
+                async move { Ok(acc) }
+            }
+            )
+            .await?;
 
-                let links: DashMap<DBProjectId, Vec<LinkUrl>> = sqlx::query!(
-                    "
+        let links: DashMap<DBProjectId, Vec<LinkUrl>> = sqlx::query!(
+            "
                     SELECT DISTINCT joining_mod_id as mod_id, joining_platform_id as platform_id, lp.name as platform_name, url, lp.donation as donation
                     FROM mods_links ml
                     INNER JOIN mods m ON ml.joining_mod_id = m.id
                     INNER JOIN link_platforms lp ON ml.joining_platform_id = lp.id
                     WHERE m.id = ANY($1) OR m.slug = ANY($2)
                     ",
-                    &project_ids_parsed,
-                    &slugs
-                ).fetch(&mut exec)
-                    .try_fold(DashMap::new(), |acc : DashMap<DBProjectId, Vec<LinkUrl>>, m| {
-                        acc.entry(DBProjectId(m.mod_id))
-                            .or_default()
-                            .push(LinkUrl {
-                                platform_id: LinkPlatformId(m.platform_id),
-                                platform_name: m.platform_name,
-                                url: m.url,
-                                donation: m.donation,
-                            });
-                        async move { Ok(acc) }
-                    }
-                    )
-                    .await?;
-
-                #[derive(Default)]
-                struct VersionLoaderData {
-                    loaders: Vec<String>,
-                    project_types: Vec<String>,
-                    games: Vec<String>,
-                    loader_loader_field_ids: Vec<LoaderFieldId>,
-                }
+            &project_ids_parsed,
+            &slugs
+        ).fetch(&mut exec)
+            .try_fold(DashMap::new(), |acc : DashMap<DBProjectId, Vec<LinkUrl>>, m| {
+                acc.entry(DBProjectId(m.mod_id))
+                    .or_default()
+                    .push(LinkUrl {
+                        platform_id: LinkPlatformId(m.platform_id),
+                        platform_name: m.platform_name,
+                        url: m.url,
+                        donation: m.donation,
+                    });
+                async move { Ok(acc) }
+            }
+            )
+            .await?;
+
+        #[derive(Default)]
+        struct VersionLoaderData {
+            loaders: Vec<String>,
+            project_types: Vec<String>,
+            games: Vec<String>,
+            loader_loader_field_ids: Vec<LoaderFieldId>,
+        }
 
-                let loader_field_ids = DashSet::new();
-                let loaders_ptypes_games: DashMap<DBProjectId, VersionLoaderData> = sqlx::query!(
-                    "
+        let loader_field_ids = DashSet::new();
+        let loaders_ptypes_games: DashMap<DBProjectId, VersionLoaderData> = sqlx::query!(
+            "
                     SELECT DISTINCT mod_id,
                         ARRAY_AGG(DISTINCT l.loader) filter (where l.loader is not null) loaders,
                         ARRAY_AGG(DISTINCT pt.name) filter (where pt.name is not null) project_types,
@@ -763,55 +799,55 @@ impl DBProject {
                     WHERE v.id = ANY($1)
                     GROUP BY mod_id
                     ",
-                    &all_version_ids.iter().map(|x| x.0).collect::<Vec<_>>()
-                ).fetch(&mut exec)
-                    .map_ok(|m| {
-                        let project_id = DBProjectId(m.mod_id);
-
-                        // Add loader fields to the set we need to fetch
-                        let loader_loader_field_ids = m.loader_fields.unwrap_or_default().into_iter().map(LoaderFieldId).collect::<Vec<_>>();
-                        for loader_field_id in &loader_loader_field_ids {
-                            loader_field_ids.insert(*loader_field_id);
-                        }
-
-                        // Add loader + loader associated data to the map
-                        let version_loader_data = VersionLoaderData {
-                            loaders: m.loaders.unwrap_or_default(),
-                            project_types: m.project_types.unwrap_or_default(),
-                            games: m.games.unwrap_or_default(),
-                            loader_loader_field_ids,
-                        };
-
-                        (project_id, version_loader_data)
+            &all_version_ids.iter().map(|x| x.0).collect::<Vec<_>>()
+        ).fetch(&mut exec)
+            .map_ok(|m| {
+                let project_id = DBProjectId(m.mod_id);
+
+                // Add loader fields to the set we need to fetch
+                let loader_loader_field_ids = m.loader_fields.unwrap_or_default().into_iter().map(LoaderFieldId).collect::<Vec<_>>();
+                for loader_field_id in &loader_loader_field_ids {
+                    loader_field_ids.insert(*loader_field_id);
+                }
 
-                    }
-                    )
-                    .try_collect()
-                    .await?;
+                // Add loader + loader associated data to the map
+                let version_loader_data = VersionLoaderData {
+                    loaders: m.loaders.unwrap_or_default(),
+                    project_types: m.project_types.unwrap_or_default(),
+                    games: m.games.unwrap_or_default(),
+                    loader_loader_field_ids,
+                };
+
+                (project_id, version_loader_data)
 
-                let loader_fields: Vec<QueryLoaderField> = sqlx::query!(
-                    "
+            }
+            )
+            .try_collect()
+            .await?;
+
+        let loader_fields: Vec<QueryLoaderField> = sqlx::query!(
+            "
                     SELECT DISTINCT id, field, field_type, enum_type, min_val, max_val, optional
                     FROM loader_fields lf
                     WHERE id = ANY($1)
                     ",
-                    &loader_field_ids.iter().map(|x| x.0).collect::<Vec<_>>()
-                )
-                    .fetch(&mut exec)
-                    .map_ok(|m| QueryLoaderField {
-                        id: LoaderFieldId(m.id),
-                        field: m.field,
-                        field_type: m.field_type,
-                        enum_type: m.enum_type.map(LoaderFieldEnumId),
-                        min_val: m.min_val,
-                        max_val: m.max_val,
-                        optional: m.optional,
-                    })
-                    .try_collect()
-                    .await?;
-
-                let project_rows = sqlx::query!(
-                    r#"
+            &loader_field_ids.iter().map(|x| x.0).collect::<Vec<_>>()
+        )
+            .fetch(&mut exec)
+            .map_ok(|m| QueryLoaderField {
+                id: LoaderFieldId(m.id),
+                field: m.field,
+                field_type: m.field_type,
+                enum_type: m.enum_type.map(LoaderFieldEnumId),
+                min_val: m.min_val,
+                max_val: m.max_val,
+                optional: m.optional,
+            })
+            .try_collect()
+            .await?;
+
+        let project_rows = sqlx::query!(
+            r#"
                     SELECT m.id id, m.name name, m.summary summary, m.downloads downloads, m.follows follows,
                     m.icon_url icon_url, m.raw_icon_url raw_icon_url, m.description description, m.published published,
                     m.approved approved, m.queued, m.status status, m.requested_status requested_status,
@@ -832,127 +868,146 @@ impl DBProject {
                     WHERE m.id = ANY($1) OR m.slug = ANY($2)
                     GROUP BY t.id, m.id
                     "#,
-                    &project_ids_parsed,
-                    &slugs,
-                )
-                .fetch_all(&mut exec)
-                .await?;
+            &project_ids_parsed,
+            &slugs,
+        )
+        .fetch_all(&mut exec)
+        .await?;
 
-                let project_components = project_rows
+        let project_components = project_rows
+            .iter()
+            .map(|row| {
+                (ProjectId::from(DBProjectId(row.id)), &row.components.0)
+            })
+            .collect::<Vec<_>>();
+        let project_query_context = exp::project::fetch_query_context(
+            &project_components,
+            &mut exec,
+            redis,
+        )
+        .await
+        .wrap_err("failed to fetch project query context")?;
+
+        let projects = project_rows.into_iter().try_fold(
+            DashMap::new(),
+            |acc, m| -> Result<_, DatabaseError> {
+                let id = m.id;
+                let project_id = DBProjectId(id);
+                let VersionLoaderData {
+                    loaders,
+                    mut project_types,
+                    games,
+                    loader_loader_field_ids,
+                } = loaders_ptypes_games
+                    .remove(&project_id)
+                    .map(|x| x.1)
+                    .unwrap_or_default();
+                // Each version is a tuple of (DBVersionId, DateTime<Utc>)
+                let mut versions = versions
+                    .remove(&project_id)
+                    .map(|x| x.1)
+                    .unwrap_or_default();
+                versions.sort_by_key(|a| a.1);
+                let mut gallery = mods_gallery
+                    .remove(&project_id)
+                    .map(|x| x.1)
+                    .unwrap_or_default();
+                let urls =
+                    links.remove(&project_id).map(|x| x.1).unwrap_or_default();
+                let version_fields = version_fields
+                    .remove(&project_id)
+                    .map(|x| x.1)
+                    .unwrap_or_default();
+
+                let loader_fields = loader_fields
                     .iter()
-                    .map(|row| {
-                        (
-                            ProjectId::from(DBProjectId(row.id)),
-                            &row.components.0,
-                        )
-                    })
+                    .filter(|x| loader_loader_field_ids.contains(&x.id))
                     .collect::<Vec<_>>();
-                let project_query_context = exp::project::fetch_query_context(
-                    &project_components,
-                    &mut exec,
-                    redis,
-                )
-                .await
-                .wrap_err("failed to fetch project query context")?;
-
-                let projects = project_rows
-                    .into_iter()
-                    .try_fold(DashMap::new(), |acc, m| -> Result<_, DatabaseError> {
-                        let id = m.id;
-                        let project_id = DBProjectId(id);
-                        let VersionLoaderData {
-                            loaders,
-                            mut project_types,
-                            games,
-                            loader_loader_field_ids,
-                        } = loaders_ptypes_games.remove(&project_id).map(|x|x.1).unwrap_or_default();
-                        // Each version is a tuple of (DBVersionId, DateTime<Utc>)
-                        let mut versions = versions.remove(&project_id).map(|x| x.1).unwrap_or_default();
-                        versions.sort_by_key(|a| a.1);
-                        let mut gallery = mods_gallery.remove(&project_id).map(|x| x.1).unwrap_or_default();
-                        let urls = links.remove(&project_id).map(|x| x.1).unwrap_or_default();
-                        let version_fields = version_fields.remove(&project_id).map(|x| x.1).unwrap_or_default();
-
-                        let loader_fields = loader_fields.iter()
-                            .filter(|x| loader_loader_field_ids.contains(&x.id))
-                            .collect::<Vec<_>>();
-
-                        let components_serial = m.components.0;
-                        exp::compat::correct_project_types(
-                            &components_serial,
-                            &mut project_types,
-                        );
-                        let components = components_serial.clone().into_query(
-                            ProjectId::from(project_id),
-                            &project_query_context,
-                        )
-                        .wrap_err("failed to populate query components")?;
-
-                        let project = ProjectQueryResult {
-                            inner: DBProject {
-                                id: DBProjectId(id),
-                                team_id: DBTeamId(m.team_id),
-                                organization_id: m.organization_id.map(DBOrganizationId),
-                                name: m.name.clone(),
-                                summary: m.summary.clone(),
-                                downloads: m.downloads,
-                                icon_url: m.icon_url.clone(),
-                                raw_icon_url: m.raw_icon_url.clone(),
-                                published: m.published,
-                                updated: versions.iter().map(|x| x.1).next_back().unwrap_or(m.published),
-                                license_url: m.license_url.clone(),
-                                status: ProjectStatus::from_string(
-                                    &m.status,
-                                ),
-                                requested_status: m.requested_status.map(|x| ProjectStatus::from_string(
-                                    &x,
-                                )),
-                                license: m.license.clone(),
-                                slug: m.slug.clone(),
-                                description: m.description.clone(),
-                                follows: m.follows,
-                                moderation_message: m.moderation_message,
-                                moderation_message_body: m.moderation_message_body,
-                                approved: m.approved,
-                                webhook_sent: m.webhook_sent,
-                                color: m.color.map(|x| x as u32),
-                                queued: m.queued,
-                                monetization_status: MonetizationStatus::from_string(
-                                    &m.monetization_status,
-                                ),
-                                side_types_migration_review_status: SideTypesMigrationReviewStatus::from_string(
-                                    &m.side_types_migration_review_status,
-                                ),
-                                loaders,
-                                components: components_serial,
-                            },
-                            categories: m.categories.unwrap_or_default(),
-                            additional_categories: m.additional_categories.unwrap_or_default(),
-                            project_types,
-                            games,
-                            versions: versions.into_iter().map(|x| x.0).collect(),
-                            gallery_items: {
-                                gallery.sort_by_key(|a| a.ordering);
-                                gallery
-                            },
-                            urls,
-                            aggregate_version_fields: VersionField::from_query_json(version_fields, &loader_fields, &loader_field_enum_values, true),
-                            thread_id: DBThreadId(m.thread_id),
-                            components,
-                        };
-
-                        acc.insert(m.id, (m.slug, project));
-                        Ok(acc)
-                    })
-                    ?;
-
-                Ok::<_, DatabaseError>(projects)
+
+                let components_serial = m.components.0;
+                exp::compat::correct_project_types(
+                    &components_serial,
+                    &mut project_types,
+                );
+                let components = components_serial
+                    .clone()
+                    .into_query(
+                        ProjectId::from(project_id),
+                        &project_query_context,
+                    )
+                    .wrap_err("failed to populate query components")?;
+
+                let project = ProjectQueryResult {
+                    inner: DBProject {
+                        id: DBProjectId(id),
+                        team_id: DBTeamId(m.team_id),
+                        organization_id: m
+                            .organization_id
+                            .map(DBOrganizationId),
+                        name: m.name.clone(),
+                        summary: m.summary.clone(),
+                        downloads: m.downloads,
+                        icon_url: m.icon_url.clone(),
+                        raw_icon_url: m.raw_icon_url.clone(),
+                        published: m.published,
+                        updated: versions
+                            .iter()
+                            .map(|x| x.1)
+                            .next_back()
+                            .unwrap_or(m.published),
+                        license_url: m.license_url.clone(),
+                        status: ProjectStatus::from_string(&m.status),
+                        requested_status: m
+                            .requested_status
+                            .map(|x| ProjectStatus::from_string(&x)),
+                        license: m.license.clone(),
+                        slug: m.slug.clone(),
+                        description: m.description.clone(),
+                        follows: m.follows,
+                        moderation_message: m.moderation_message,
+                        moderation_message_body: m.moderation_message_body,
+                        approved: m.approved,
+                        webhook_sent: m.webhook_sent,
+                        color: m.color.map(|x| x as u32),
+                        queued: m.queued,
+                        monetization_status: MonetizationStatus::from_string(
+                            &m.monetization_status,
+                        ),
+                        side_types_migration_review_status:
+                            SideTypesMigrationReviewStatus::from_string(
+                                &m.side_types_migration_review_status,
+                            ),
+                        loaders,
+                        components: components_serial,
+                    },
+                    categories: m.categories.unwrap_or_default(),
+                    additional_categories: m
+                        .additional_categories
+                        .unwrap_or_default(),
+                    project_types,
+                    games,
+                    versions: versions.into_iter().map(|x| x.0).collect(),
+                    gallery_items: {
+                        gallery.sort_by_key(|a| a.ordering);
+                        gallery
+                    },
+                    urls,
+                    aggregate_version_fields: VersionField::from_query_json(
+                        version_fields,
+                        &loader_fields,
+                        &loader_field_enum_values,
+                        true,
+                    ),
+                    thread_id: DBThreadId(m.thread_id),
+                    components,
+                };
+
+                acc.insert(m.id, (m.slug, project));
+                Ok(acc)
             },
-        )
-        .await
-        .wrap_internal_err("fetching cached projects")?;
+        )?;
 
-        Ok(val)
+        Ok::<_, DatabaseError>(projects)
     }
 
     pub async fn get_dependencies<'a, E>(
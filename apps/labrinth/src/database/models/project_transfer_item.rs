@@ -0,0 +1,227 @@
+use chrono::{DateTime, Utc};
+
+use super::{
+    DBOrganizationId, DBProjectId, DBProjectTransferId, DBUserId, DatabaseError,
+};
+use crate::models::project_transfers::ProjectTransferStatus;
+
+/// A request to move a project to another user or organization.
+#[derive(Debug, Clone)]
+pub struct DBProjectTransfer {
+    pub id: DBProjectTransferId,
+    pub project_id: DBProjectId,
+    pub requested_by: DBUserId,
+    pub previous_owner_user_id: Option<DBUserId>,
+    pub previous_owner_organization_id: Option<DBOrganizationId>,
+    pub new_owner_user_id: Option<DBUserId>,
+    pub new_owner_organization_id: Option<DBOrganizationId>,
+    pub transfer_pending_payouts: bool,
+    pub transfer_payouts_split: bool,
+    pub status: ProjectTransferStatus,
+    pub created: DateTime<Utc>,
+    pub resolved: Option<DateTime<Utc>>,
+    pub resolved_by: Option<DBUserId>,
+}
+
+struct ProjectTransferQueryResult {
+    id: i64,
+    project_id: i64,
+    requested_by: i64,
+    previous_owner_user_id: Option<i64>,
+    previous_owner_organization_id: Option<i64>,
+    new_owner_user_id: Option<i64>,
+    new_owner_organization_id: Option<i64>,
+    transfer_pending_payouts: bool,
+    transfer_payouts_split: bool,
+    status: String,
+    created: DateTime<Utc>,
+    resolved: Option<DateTime<Utc>>,
+    resolved_by: Option<i64>,
+}
+
+macro_rules! select_project_transfers_with_predicate {
+    ($predicate:literal $(, $($param0:expr $(, $param:expr)* $(,)?)?)?) => {
+        sqlx::query_as!(
+            ProjectTransferQueryResult,
+            r#"
+            SELECT
+                id, project_id, requested_by,
+                previous_owner_user_id, previous_owner_organization_id,
+                new_owner_user_id, new_owner_organization_id,
+                transfer_pending_payouts, transfer_payouts_split,
+                status, created, resolved, resolved_by
+            FROM project_transfers
+            "#
+                + $predicate
+            $($(, $param0 $(, $param)* )?)?
+        )
+    };
+}
+
+impl From<ProjectTransferQueryResult> for DBProjectTransfer {
+    fn from(r: ProjectTransferQueryResult) -> Self {
+        DBProjectTransfer {
+            id: DBProjectTransferId(r.id),
+            project_id: DBProjectId(r.project_id),
+            requested_by: DBUserId(r.requested_by),
+            previous_owner_user_id: r.previous_owner_user_id.map(DBUserId),
+            previous_owner_organization_id: r
+                .previous_owner_organization_id
+                .map(DBOrganizationId),
+            new_owner_user_id: r.new_owner_user_id.map(DBUserId),
+            new_owner_organization_id: r
+                .new_owner_organization_id
+                .map(DBOrganizationId),
+            transfer_pending_payouts: r.transfer_pending_payouts,
+            transfer_payouts_split: r.transfer_payouts_split,
+            status: ProjectTransferStatus::from_string(&r.status),
+            created: r.created,
+            resolved: r.resolved,
+            resolved_by: r.resolved_by.map(DBUserId),
+        }
+    }
+}
+
+impl DBProjectTransfer {
+    pub async fn insert(
+        &self,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "
+            INSERT INTO project_transfers (
+                id, project_id, requested_by,
+                previous_owner_user_id, previous_owner_organization_id,
+                new_owner_user_id, new_owner_organization_id,
+                transfer_pending_payouts, transfer_payouts_split,
+                status, created
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ",
+            self.id as DBProjectTransferId,
+            self.project_id as DBProjectId,
+            self.requested_by as DBUserId,
+            self.previous_owner_user_id.map(|x| x.0),
+            self.previous_owner_organization_id.map(|x| x.0),
+            self.new_owner_user_id.map(|x| x.0),
+            self.new_owner_organization_id.map(|x| x.0),
+            self.transfer_pending_payouts,
+            self.transfer_payouts_split,
+            self.status.as_str(),
+            self.created,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get<'a, E>(
+        id: DBProjectTransferId,
+        exec: E,
+    ) -> Result<Option<Self>, DatabaseError>
+    where
+        E: crate::database::Executor<'a, Database = sqlx::Postgres>,
+    {
+        Ok(select_project_transfers_with_predicate!(
+            "WHERE id = $1",
+            id as DBProjectTransferId,
+        )
+        .fetch_optional(exec)
+        .await?
+        .map(Into::into))
+    }
+
+    pub async fn get_pending_for_project<'a, E>(
+        project_id: DBProjectId,
+        exec: E,
+    ) -> Result<Option<Self>, DatabaseError>
+    where
+        E: crate::database::Executor<'a, Database = sqlx::Postgres>,
+    {
+        Ok(select_project_transfers_with_predicate!(
+            "WHERE project_id = $1 AND status = 'pending'",
+            project_id as DBProjectId,
+        )
+        .fetch_optional(exec)
+        .await?
+        .map(Into::into))
+    }
+
+    /// Fetches every transfer of a project, newest first.
+    pub async fn get_all_for_project<'a, E>(
+        project_id: DBProjectId,
+        exec: E,
+    ) -> Result<Vec<Self>, DatabaseError>
+    where
+        E: crate::database::Executor<'a, Database = sqlx::Postgres>,
+    {
+        Ok(select_project_transfers_with_predicate!(
+            "WHERE project_id = $1 ORDER BY created DESC",
+            project_id as DBProjectId,
+        )
+        .fetch_all(exec)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+
+    /// Fetches the pending transfers from or to a user, or from or to any of
+    /// the given organizations.
+    pub async fn get_pending_for_owners<'a, E>(
+        user_id: DBUserId,
+        organization_ids: &[DBOrganizationId],
+        exec: E,
+    ) -> Result<Vec<Self>, DatabaseError>
+    where
+        E: crate::database::Executor<'a, Database = sqlx::Postgres>,
+    {
+        let organization_ids =
+            organization_ids.iter().map(|x| x.0).collect::<Vec<_>>();
+
+        Ok(select_project_transfers_with_predicate!(
+            "
+            WHERE status = 'pending' AND (
+                requested_by = $1
+                OR previous_owner_user_id = $1
+                OR new_owner_user_id = $1
+                OR previous_owner_organization_id = ANY($2)
+                OR new_owner_organization_id = ANY($2)
+            )
+            ORDER BY created DESC
+            ",
+            user_id as DBUserId,
+            &organization_ids,
+        )
+        .fetch_all(exec)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+
+    /// Marks a pending transfer as resolved with `status`, returning whether
+    /// it was still pending.
+    pub async fn resolve(
+        id: DBProjectTransferId,
+        status: ProjectTransferStatus,
+        resolved_by: DBUserId,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query!(
+            "
+            UPDATE project_transfers
+            SET status = $2, resolved = NOW(), resolved_by = $3
+            WHERE id = $1 AND status = 'pending'
+            ",
+            id as DBProjectTransferId,
+            status.as_str(),
+            resolved_by as DBUserId,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
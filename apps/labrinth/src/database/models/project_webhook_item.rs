@@ -0,0 +1,343 @@
+use chrono::{DateTime, Utc};
+
+use super::{DBOrganizationId, DBProjectId, DBProjectWebhookId, DatabaseError};
+use crate::models::project_webhooks::{
+    ProjectWebhookDeliveryStatus, ProjectWebhookEvent, ProjectWebhookEventType,
+};
+
+/// An endpoint subscribed to events of a project, or of all projects in an
+/// organization.
+#[derive(Debug, Clone)]
+pub struct DBProjectWebhook {
+    pub id: DBProjectWebhookId,
+    pub project_id: Option<DBProjectId>,
+    pub organization_id: Option<DBOrganizationId>,
+    pub url: String,
+    /// Key deliveries are HMAC-SHA256 signed with.
+    pub secret: String,
+    pub events: Vec<ProjectWebhookEventType>,
+    pub created: DateTime<Utc>,
+}
+
+struct ProjectWebhookQueryResult {
+    id: i64,
+    project_id: Option<i64>,
+    organization_id: Option<i64>,
+    url: String,
+    secret: String,
+    events: Vec<String>,
+    created: DateTime<Utc>,
+}
+
+impl From<ProjectWebhookQueryResult> for DBProjectWebhook {
+    fn from(r: ProjectWebhookQueryResult) -> Self {
+        DBProjectWebhook {
+            id: DBProjectWebhookId(r.id),
+            project_id: r.project_id.map(DBProjectId),
+            organization_id: r.organization_id.map(DBOrganizationId),
+            url: r.url,
+            secret: r.secret,
+            events: r
+                .events
+                .iter()
+                .filter_map(|x| ProjectWebhookEventType::from_string(x))
+                .collect(),
+            created: r.created,
+        }
+    }
+}
+
+impl DBProjectWebhook {
+    pub async fn insert(
+        &self,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        let events = self.events.iter().map(|x| x.as_str()).collect::<Vec<_>>();
+
+        sqlx::query!(
+            "
+            INSERT INTO project_webhooks (id, project_id, organization_id, url, secret, events, created)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ",
+            self.id as DBProjectWebhookId,
+            self.project_id.map(|x| x.0),
+            self.organization_id.map(|x| x.0),
+            self.url,
+            self.secret,
+            &events as &[&str],
+            self.created,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get(
+        id: DBProjectWebhookId,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<Option<Self>, DatabaseError> {
+        let result = sqlx::query_as!(
+            ProjectWebhookQueryResult,
+            "
+            SELECT id, project_id, organization_id, url, secret, events, created
+            FROM project_webhooks
+            WHERE id = $1
+            ",
+            id as DBProjectWebhookId,
+        )
+        .fetch_optional(exec)
+        .await?;
+
+        Ok(result.map(Into::into))
+    }
+
+    pub async fn get_project(
+        project_id: DBProjectId,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<Vec<Self>, DatabaseError> {
+        let results = sqlx::query_as!(
+            ProjectWebhookQueryResult,
+            "
+            SELECT id, project_id, organization_id, url, secret, events, created
+            FROM project_webhooks
+            WHERE project_id = $1
+            ORDER BY created
+            ",
+            project_id as DBProjectId,
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(results.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_organization(
+        organization_id: DBOrganizationId,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<Vec<Self>, DatabaseError> {
+        let results = sqlx::query_as!(
+            ProjectWebhookQueryResult,
+            "
+            SELECT id, project_id, organization_id, url, secret, events, created
+            FROM project_webhooks
+            WHERE organization_id = $1
+            ORDER BY created
+            ",
+            organization_id as DBOrganizationId,
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(results.into_iter().map(Into::into).collect())
+    }
+
+    /// Updates the URL and subscribed events of the webhook.
+    pub async fn update(
+        &self,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        let events = self.events.iter().map(|x| x.as_str()).collect::<Vec<_>>();
+
+        sqlx::query!(
+            "
+            UPDATE project_webhooks
+            SET url = $2, events = $3
+            WHERE id = $1
+            ",
+            self.id as DBProjectWebhookId,
+            self.url,
+            &events as &[&str],
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove(
+        id: DBProjectWebhookId,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "
+            DELETE FROM project_webhooks
+            WHERE id = $1
+            ",
+            id as DBProjectWebhookId,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// A delivery of an event to a webhook, and the outcome of its latest
+/// attempt.
+#[derive(Debug, Clone)]
+pub struct DBProjectWebhookDelivery {
+    pub id: i64,
+    pub webhook_id: DBProjectWebhookId,
+    pub payload: ProjectWebhookEvent,
+    pub status: ProjectWebhookDeliveryStatus,
+    pub attempt_count: i32,
+    pub next_attempt: DateTime<Utc>,
+    pub response_code: Option<i32>,
+    pub error: Option<String>,
+    pub created: DateTime<Utc>,
+}
+
+/// A delivery locked for processing, along with where to send it.
+pub struct ProcessableDelivery {
+    pub delivery: DBProjectWebhookDelivery,
+    pub url: String,
+    pub secret: String,
+}
+
+impl DBProjectWebhookDelivery {
+    /// Queues a delivery of `event` to every webhook subscribed to it, on the
+    /// event's project or on the project's organization.
+    pub async fn insert_for_event(
+        event: &ProjectWebhookEvent,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        let project_id: DBProjectId = event.project_id().into();
+
+        sqlx::query!(
+            "
+            INSERT INTO project_webhook_deliveries (webhook_id, event, payload, status)
+            SELECT w.id, $2::varchar, $3, $4
+            FROM mods m
+            INNER JOIN project_webhooks w ON
+                w.project_id = m.id
+                OR w.organization_id = m.organization_id
+            WHERE m.id = $1 AND $2::varchar = ANY(w.events)
+            ",
+            project_id as DBProjectId,
+            event.event_type().as_str(),
+            serde_json::to_value(event)?,
+            ProjectWebhookDeliveryStatus::Pending.as_str(),
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets the latest deliveries to a webhook, newest first.
+    pub async fn get_webhook(
+        webhook_id: DBProjectWebhookId,
+        limit: i64,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<Vec<Self>, DatabaseError> {
+        let rows = sqlx::query!(
+            "
+            SELECT id, webhook_id, payload, status, attempt_count, next_attempt, response_code, error, created
+            FROM project_webhook_deliveries
+            WHERE webhook_id = $1
+            ORDER BY created DESC
+            LIMIT $2
+            ",
+            webhook_id as DBProjectWebhookId,
+            limit,
+        )
+        .fetch_all(exec)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(Self {
+                    id: row.id,
+                    webhook_id: DBProjectWebhookId(row.webhook_id),
+                    payload: serde_json::from_value(row.payload)?,
+                    status: ProjectWebhookDeliveryStatus::from_string(
+                        &row.status,
+                    ),
+                    attempt_count: row.attempt_count,
+                    next_attempt: row.next_attempt,
+                    response_code: row.response_code,
+                    error: row.error,
+                    created: row.created,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns pending deliveries which are due, using a row-level `UPDATE`
+    /// lock, barring the provided limit.
+    pub async fn lock_processable(
+        limit: i64,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<Vec<ProcessableDelivery>, DatabaseError> {
+        let rows = sqlx::query!(
+            "
+            SELECT
+                d.id, d.webhook_id, d.payload, d.status, d.attempt_count, d.next_attempt,
+                d.response_code, d.error, d.created, w.url, w.secret
+            FROM project_webhook_deliveries d
+            INNER JOIN project_webhooks w ON w.id = d.webhook_id
+            WHERE d.status = $1 AND d.next_attempt <= NOW()
+            ORDER BY d.next_attempt ASC
+            LIMIT $2
+            FOR UPDATE OF d
+            SKIP LOCKED
+            ",
+            ProjectWebhookDeliveryStatus::Pending.as_str(),
+            limit,
+        )
+        .fetch_all(exec)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(ProcessableDelivery {
+                    delivery: Self {
+                        id: row.id,
+                        webhook_id: DBProjectWebhookId(row.webhook_id),
+                        payload: serde_json::from_value(row.payload)?,
+                        status: ProjectWebhookDeliveryStatus::from_string(
+                            &row.status,
+                        ),
+                        attempt_count: row.attempt_count,
+                        next_attempt: row.next_attempt,
+                        response_code: row.response_code,
+                        error: row.error,
+                        created: row.created,
+                    },
+                    url: row.url,
+                    secret: row.secret,
+                })
+            })
+            .collect()
+    }
+
+    /// Updates the outcome of the delivery's latest attempt.
+    pub async fn update(
+        &self,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "
+            UPDATE project_webhook_deliveries
+            SET
+              status = $2,
+              attempt_count = $3,
+              next_attempt = $4,
+              response_code = $5,
+              error = $6
+            WHERE id = $1
+            ",
+            self.id,
+            self.status.as_str(),
+            self.attempt_count,
+            self.next_attempt,
+            self.response_code,
+            self.error,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+}
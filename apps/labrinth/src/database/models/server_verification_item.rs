@@ -0,0 +1,170 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+
+use crate::database::models::{DBProjectId, DatabaseError};
+use crate::models::exp::minecraft::ServerVerification;
+
+/// Verification token issued to a Minecraft Java server listing.
+///
+/// Whether the listing is currently verified lives on the project's
+/// `minecraft_java_server` component, so it's cached and indexed along with
+/// the rest of the project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DBServerVerification {
+    pub project_id: DBProjectId,
+    pub token: String,
+    pub failed_checks: i32,
+    pub last_checked: Option<DateTime<Utc>>,
+    pub created: DateTime<Utc>,
+}
+
+/// A verified server listing that is due to be checked again.
+pub struct ServerVerificationRecheck {
+    pub project_id: DBProjectId,
+    pub token: String,
+    pub address: String,
+}
+
+impl DBServerVerification {
+    pub async fn get(
+        project_id: DBProjectId,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<Option<Self>, DatabaseError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT project_id, token, failed_checks, last_checked, created
+            FROM minecraft_server_verifications
+            WHERE project_id = $1
+            "#,
+            project_id as DBProjectId,
+        )
+        .fetch_optional(exec)
+        .await?;
+
+        Ok(row.map(|row| Self {
+            project_id: DBProjectId(row.project_id),
+            token: row.token,
+            failed_checks: row.failed_checks,
+            last_checked: row.last_checked,
+            created: row.created,
+        }))
+    }
+
+    /// Issues `token` to the project, unless it already has one.
+    pub async fn insert(
+        project_id: DBProjectId,
+        token: &str,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO minecraft_server_verifications (project_id, token)
+            VALUES ($1, $2)
+            ON CONFLICT (project_id) DO NOTHING
+            "#,
+            project_id as DBProjectId,
+            token,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records the result of a check, returning the number of consecutive
+    /// failed checks.
+    pub async fn record_check(
+        project_id: DBProjectId,
+        passed: bool,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<i32, DatabaseError> {
+        let failed_checks = sqlx::query_scalar!(
+            r#"
+            UPDATE minecraft_server_verifications
+            SET
+                last_checked = now(),
+                failed_checks = CASE WHEN $2 THEN 0 ELSE failed_checks + 1 END
+            WHERE project_id = $1
+            RETURNING failed_checks
+            "#,
+            project_id as DBProjectId,
+            passed,
+        )
+        .fetch_one(exec)
+        .await?;
+
+        Ok(failed_checks)
+    }
+
+    /// Sets or clears the verification on the project's
+    /// `minecraft_java_server` component.
+    ///
+    /// Does nothing if the project's address is no longer `address`, so a
+    /// check can't verify an address that was changed while it ran. Returns
+    /// whether the project was updated.
+    pub async fn set_project_verification(
+        project_id: DBProjectId,
+        address: &str,
+        verification: Option<&ServerVerification>,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE mods
+            SET components = CASE
+                WHEN $3::jsonb IS NULL
+                    THEN components #- '{minecraft_java_server,verification}'
+                ELSE jsonb_set(components, '{minecraft_java_server,verification}', $3)
+            END
+            WHERE
+                id = $1
+                AND components -> 'minecraft_java_server' ->> 'address' = $2
+            "#,
+            project_id as DBProjectId,
+            address,
+            verification.map(Json) as Option<Json<&ServerVerification>>,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Finds verified server listings which haven't been checked since
+    /// `checked_before`, least recently checked first.
+    pub async fn get_due_for_recheck(
+        checked_before: DateTime<Utc>,
+        limit: i64,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<Vec<ServerVerificationRecheck>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                v.project_id,
+                v.token,
+                m.components -> 'minecraft_java_server' ->> 'address' AS "address!"
+            FROM minecraft_server_verifications v
+            INNER JOIN mods m ON m.id = v.project_id
+            WHERE
+                m.components -> 'minecraft_java_server' ? 'verification'
+                AND (v.last_checked IS NULL OR v.last_checked < $1)
+            ORDER BY v.last_checked ASC NULLS FIRST
+            LIMIT $2
+            "#,
+            checked_before,
+            limit,
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ServerVerificationRecheck {
+                project_id: DBProjectId(row.project_id),
+                token: row.token,
+                address: row.address,
+            })
+            .collect())
+    }
+}
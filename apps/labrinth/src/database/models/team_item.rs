@@ -1,4 +1,4 @@
-use super::{DBOrganization, DBProject, ids::*};
+use super::{DBProject, ids::*};
 use crate::{
     database::PgTransaction,
     models::teams::{OrganizationPermissions, ProjectPermissions},
@@ -10,7 +10,7 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use xredis::RedisPool;
 
-const TEAMS_NAMESPACE: &str = "teams:v4";
+const TEAMS_NAMESPACE: &str = "teams:v5";
 
 pub struct TeamBuilder {
     pub members: Vec<TeamMemberBuilder>,
@@ -186,6 +186,11 @@ pub struct DBTeamMember {
     pub accepted: bool,
     pub payouts_split: Decimal,
     pub ordering: i64,
+
+    // The organization role assigned to the user in this organization team
+    // If set, lookups of accepted members return the role's permissions in
+    // place of the ones stored for the member
+    pub role_id: Option<DBOrganizationRoleId>,
 }
 
 impl DBTeamMember {
@@ -221,7 +226,7 @@ impl DBTeamMember {
                     "
                     SELECT id, team_id, role AS member_role, is_owner, permissions, organization_permissions,
                     accepted, payouts_split,
-                    ordering, user_id
+                    ordering, user_id, role_id
                     FROM team_members
                     WHERE team_id = ANY($1)
                     ORDER BY team_id, ordering;
@@ -244,6 +249,7 @@ impl DBTeamMember {
                             user_id: DBUserId(m.user_id),
                             payouts_split: m.payouts_split,
                             ordering: m.ordering,
+                            role_id: m.role_id.map(DBOrganizationRoleId),
                         };
 
                         acc.entry(m.team_id)
@@ -272,6 +278,10 @@ impl DBTeamMember {
     }
 
     /// Gets a team member from a user id and team id.  Does not return pending members.
+    ///
+    /// Organization members with an organization role have the role's
+    /// permissions. Use [`Self::get_from_user_id_project_organization`] to
+    /// include the role's override for a project.
     pub async fn get_from_user_id<'a, 'b, E>(
         id: DBTeamId,
         user_id: DBUserId,
@@ -297,14 +307,17 @@ impl DBTeamMember {
         let team_ids_parsed: Vec<i64> = team_ids.iter().map(|x| x.0).collect();
 
         let team_members = sqlx::query!(
-            "
-            SELECT id, team_id, role AS member_role, is_owner, permissions, organization_permissions,
-            accepted, payouts_split, role,
-            ordering, user_id
-            FROM team_members
-            WHERE (team_id = ANY($1) AND user_id = $2 AND accepted = TRUE)
-            ORDER BY ordering
-            ",
+            r#"
+            SELECT tm.id, tm.team_id, tm.role AS member_role, tm.is_owner,
+            COALESCE(r.permissions, tm.permissions) AS "permissions!",
+            COALESCE(r.organization_permissions, tm.organization_permissions) AS organization_permissions,
+            tm.accepted, tm.payouts_split, tm.role,
+            tm.ordering, tm.user_id, tm.role_id
+            FROM team_members tm
+            LEFT JOIN organization_roles r ON r.id = tm.role_id AND NOT tm.is_owner
+            WHERE (tm.team_id = ANY($1) AND tm.user_id = $2 AND tm.accepted = TRUE)
+            ORDER BY tm.ordering
+            "#,
             &team_ids_parsed,
             user_id as DBUserId
         )
@@ -323,6 +336,7 @@ impl DBTeamMember {
             accepted: m.accepted,
             payouts_split: m.payouts_split,
             ordering: m.ordering,
+            role_id: m.role_id.map(DBOrganizationRoleId),
         })
         .try_collect::<Vec<DBTeamMember>>()
         .await?;
@@ -343,7 +357,7 @@ impl DBTeamMember {
             "
             SELECT id, team_id, role AS member_role, is_owner, permissions, organization_permissions,
                 accepted, payouts_split, role,
-                ordering, user_id
+                ordering, user_id, role_id
 
             FROM team_members
             WHERE (team_id = $1 AND user_id = $2)
@@ -373,6 +387,7 @@ impl DBTeamMember {
                 accepted: m.accepted,
                 payouts_split: m.payouts_split,
                 ordering: m.ordering,
+                role_id: m.role_id.map(DBOrganizationRoleId),
             }))
         } else {
             Ok(None)
@@ -386,10 +401,10 @@ impl DBTeamMember {
         sqlx::query!(
             "
             INSERT INTO team_members (
-                id, team_id, user_id, role, permissions, organization_permissions, is_owner, accepted, payouts_split
+                id, team_id, user_id, role, permissions, organization_permissions, is_owner, accepted, payouts_split, role_id
             )
             VALUES (
-                $1, $2, $3, $4, $5, $6, $7, $8, $9
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10
             )
             ",
             self.id as DBTeamMemberId,
@@ -400,7 +415,8 @@ impl DBTeamMember {
             self.organization_permissions.map(|p| p.bits() as i64),
             self.is_owner,
             self.accepted,
-            self.payouts_split
+            self.payouts_split,
+            self.role_id.map(|x| x.0),
         )
         .execute(&mut *transaction)
         .await?;
@@ -438,6 +454,7 @@ impl DBTeamMember {
         new_payouts_split: Option<Decimal>,
         new_ordering: Option<i64>,
         new_is_owner: Option<bool>,
+        new_role_id: Option<Option<DBOrganizationRoleId>>,
         transaction: &mut PgTransaction<'_>,
     ) -> Result<(), super::DatabaseError> {
         if let Some(permissions) = new_permissions {
@@ -546,6 +563,21 @@ impl DBTeamMember {
             .await?;
         }
 
+        if let Some(role_id) = new_role_id {
+            sqlx::query!(
+                "
+                UPDATE team_members
+                SET role_id = $1
+                WHERE (team_id = $2 AND user_id = $3)
+                ",
+                role_id.map(|x| x.0),
+                id as DBTeamId,
+                user_id as DBUserId,
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+
         Ok(())
     }
 
@@ -566,7 +598,7 @@ impl DBTeamMember {
 
         let result = sqlx::query!(
             "
-            SELECT tm.id, tm.team_id, tm.user_id, tm.role, tm.is_owner, tm.permissions, tm.organization_permissions, tm.accepted, tm.payouts_split, tm.ordering
+            SELECT tm.id, tm.team_id, tm.user_id, tm.role, tm.is_owner, tm.permissions, tm.organization_permissions, tm.accepted, tm.payouts_split, tm.ordering, tm.role_id
             FROM mods m
             INNER JOIN team_members tm ON tm.team_id = m.team_id AND user_id = $2 AND accepted = ANY($3)
             WHERE m.id = $1
@@ -596,12 +628,15 @@ impl DBTeamMember {
                 accepted: m.accepted,
                 payouts_split: m.payouts_split,
                 ordering: m.ordering,
+                role_id: m.role_id.map(DBOrganizationRoleId),
             }))
         } else {
             Ok(None)
         }
     }
 
+    /// Gets a member of an organization, with the permissions of their
+    /// organization role if they have one.
     pub async fn get_from_user_id_organization<'a, 'b, E>(
         id: DBOrganizationId,
         user_id: DBUserId,
@@ -617,12 +652,16 @@ impl DBTeamMember {
             vec![true]
         };
         let result = sqlx::query!(
-            "
-            SELECT tm.id, tm.team_id, tm.user_id, tm.role, tm.is_owner, tm.permissions, tm.organization_permissions, tm.accepted, tm.payouts_split, tm.ordering
+            r#"
+            SELECT tm.id, tm.team_id, tm.user_id, tm.role, tm.is_owner,
+            COALESCE(r.permissions, tm.permissions) AS "permissions!",
+            COALESCE(r.organization_permissions, tm.organization_permissions) AS organization_permissions,
+            tm.accepted, tm.payouts_split, tm.ordering, tm.role_id
             FROM organizations o
             INNER JOIN team_members tm ON tm.team_id = o.team_id AND user_id = $2 AND accepted = ANY($3)
+            LEFT JOIN organization_roles r ON r.id = tm.role_id AND NOT tm.is_owner
             WHERE o.id = $1
-            ",
+            "#,
             id as DBOrganizationId,
             user_id as DBUserId,
             &accepted
@@ -648,6 +687,62 @@ impl DBTeamMember {
                 accepted: m.accepted,
                 payouts_split: m.payouts_split,
                 ordering: m.ordering,
+                role_id: m.role_id.map(DBOrganizationRoleId),
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Gets the member of the organization owning a project, with the
+    /// permissions of their organization role in that project if they have
+    /// one. Does not return pending members.
+    pub async fn get_from_user_id_project_organization<'a, 'b, E>(
+        id: DBProjectId,
+        user_id: DBUserId,
+        executor: E,
+    ) -> Result<Option<Self>, super::DatabaseError>
+    where
+        E: crate::database::Executor<'a, Database = sqlx::Postgres>,
+    {
+        let result = sqlx::query!(
+            r#"
+            SELECT tm.id, tm.team_id, tm.user_id, tm.role, tm.is_owner,
+            COALESCE(po.permissions, r.permissions, tm.permissions) AS "permissions!",
+            COALESCE(r.organization_permissions, tm.organization_permissions) AS organization_permissions,
+            tm.accepted, tm.payouts_split, tm.ordering, tm.role_id
+            FROM mods m
+            INNER JOIN organizations o ON o.id = m.organization_id
+            INNER JOIN team_members tm ON tm.team_id = o.team_id AND tm.user_id = $2 AND tm.accepted = TRUE
+            LEFT JOIN organization_roles r ON r.id = tm.role_id AND NOT tm.is_owner
+            LEFT JOIN organization_role_project_overrides po ON po.role_id = r.id AND po.project_id = m.id
+            WHERE m.id = $1
+            "#,
+            id as DBProjectId,
+            user_id as DBUserId
+        )
+            .fetch_optional(executor)
+            .await?;
+
+        if let Some(m) = result {
+            Ok(Some(DBTeamMember {
+                id: DBTeamMemberId(m.id),
+                team_id: DBTeamId(m.team_id),
+                user_id,
+                role: m.role,
+                is_owner: m.is_owner,
+                permissions: ProjectPermissions::from_bits(
+                    m.permissions as u64,
+                )
+                .unwrap_or_default(),
+                organization_permissions: m.organization_permissions.map(|p| {
+                    OrganizationPermissions::from_bits(p as u64)
+                        .unwrap_or_default()
+                }),
+                accepted: m.accepted,
+                payouts_split: m.payouts_split,
+                ordering: m.ordering,
+                role_id: m.role_id.map(DBOrganizationRoleId),
             }))
         } else {
             Ok(None)
@@ -664,7 +759,7 @@ impl DBTeamMember {
     {
         let result = sqlx::query!(
             "
-            SELECT tm.id, tm.team_id, tm.user_id, tm.role, tm.is_owner, tm.permissions, tm.organization_permissions, tm.accepted, tm.payouts_split, tm.ordering, v.mod_id
+            SELECT tm.id, tm.team_id, tm.user_id, tm.role, tm.is_owner, tm.permissions, tm.organization_permissions, tm.accepted, tm.payouts_split, tm.ordering, tm.role_id, v.mod_id
             FROM versions v
             INNER JOIN mods m ON m.id = v.mod_id
             INNER JOIN team_members tm ON tm.team_id = m.team_id AND tm.user_id = $2 AND tm.accepted = TRUE
@@ -694,6 +789,7 @@ impl DBTeamMember {
                 accepted: m.accepted,
                 payouts_split: m.payouts_split,
                 ordering: m.ordering,
+                role_id: m.role_id.map(DBOrganizationRoleId),
             }))
         } else {
             Ok(None)
@@ -714,20 +810,12 @@ impl DBTeamMember {
         let project_team_member =
             Self::get_from_user_id(project.team_id, user_id, executor).await?;
 
-        let organization =
-            DBOrganization::get_associated_organization_project_id(
-                project.id, executor,
+        let organization_team_member =
+            Self::get_from_user_id_project_organization(
+                project.id, user_id, executor,
             )
             .await?;
 
-        let organization_team_member = if let Some(organization) = &organization
-        {
-            Self::get_from_user_id(organization.team_id, user_id, executor)
-                .await?
-        } else {
-            None
-        };
-
         Ok((project_team_member, organization_team_member))
     }
 }
@@ -0,0 +1,177 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::database::models::{DBFileId, DBProjectId, DatabaseError};
+
+/// A binary delta which turns one version file into another.
+///
+/// Pairs which were checked but didn't produce a useful delta are recorded
+/// without a URL, so they aren't computed again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DBVersionFileDelta {
+    pub from_file_id: DBFileId,
+    pub to_file_id: DBFileId,
+    pub url: Option<String>,
+    pub size: Option<i32>,
+    pub created: DateTime<Utc>,
+}
+
+/// The primary file of a version, paired with the primary file of the
+/// project's previous version for a shared loader and game version, which has
+/// no delta computed yet.
+pub struct PendingVersionFileDelta {
+    pub project_id: DBProjectId,
+    pub from_file_id: DBFileId,
+    pub from_url: String,
+    pub to_file_id: DBFileId,
+    pub to_url: String,
+    pub to_size: i32,
+}
+
+impl DBVersionFileDelta {
+    /// Gets the stored delta between two files, if one was worth storing.
+    pub async fn get(
+        from_file_id: DBFileId,
+        to_file_id: DBFileId,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<Option<Self>, DatabaseError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT from_file_id, to_file_id, url, size, created
+            FROM version_file_deltas
+            WHERE from_file_id = $1 AND to_file_id = $2 AND url IS NOT NULL
+            "#,
+            from_file_id as DBFileId,
+            to_file_id as DBFileId,
+        )
+        .fetch_optional(exec)
+        .await?;
+
+        Ok(row.map(|row| Self {
+            from_file_id: DBFileId(row.from_file_id),
+            to_file_id: DBFileId(row.to_file_id),
+            url: row.url,
+            size: row.size,
+            created: row.created,
+        }))
+    }
+
+    /// Records the delta between two files, or that there is no useful one if
+    /// `url` and `size` are `None`.
+    pub async fn insert(
+        from_file_id: DBFileId,
+        to_file_id: DBFileId,
+        url: Option<&str>,
+        size: Option<i32>,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO version_file_deltas (from_file_id, to_file_id, url, size)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (from_file_id, to_file_id) DO UPDATE
+            SET url = EXCLUDED.url, size = EXCLUDED.size, created = now()
+            "#,
+            from_file_id as DBFileId,
+            to_file_id as DBFileId,
+            url,
+            size,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Finds primary version files up to `max_size` bytes which have no delta
+    /// from the previous version's primary file yet, most recent first.
+    ///
+    /// The previous version is the latest earlier one sharing a loader and a
+    /// game version, since that's the file an instance would be updating from.
+    pub async fn get_pending(
+        max_size: i32,
+        limit: i64,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<Vec<PendingVersionFileDelta>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                v.mod_id AS "project_id!",
+                pf.id AS "from_file_id!",
+                pf.url AS "from_url!",
+                f.id AS "to_file_id!",
+                f.url AS "to_url!",
+                f.size AS "to_size!"
+            FROM versions v
+            INNER JOIN files f ON f.version_id = v.id AND f.is_primary
+            INNER JOIN LATERAL (
+                SELECT pf.id, pf.url, pf.size
+                FROM versions pv
+                INNER JOIN files pf ON pf.version_id = pv.id AND pf.is_primary
+                WHERE
+                    pv.mod_id = v.mod_id
+                    AND pv.date_published < v.date_published
+                    -- the previous version for the same loader...
+                    AND EXISTS (
+                        SELECT 1
+                        FROM loaders_versions plv
+                        INNER JOIN loaders_versions lv
+                            ON lv.loader_id = plv.loader_id
+                        WHERE plv.version_id = pv.id AND lv.version_id = v.id
+                    )
+                    -- ...and game version, if the versions have any
+                    AND (
+                        EXISTS (
+                            SELECT 1
+                            FROM version_fields pvf
+                            INNER JOIN loader_fields lf ON lf.id = pvf.field_id
+                            INNER JOIN version_fields vf
+                                ON vf.field_id = pvf.field_id
+                                AND vf.enum_value = pvf.enum_value
+                            WHERE
+                                lf.field = 'game_versions'
+                                AND pvf.version_id = pv.id
+                                AND vf.version_id = v.id
+                        )
+                        OR NOT EXISTS (
+                            SELECT 1
+                            FROM version_fields gvf
+                            INNER JOIN loader_fields lf ON lf.id = gvf.field_id
+                            WHERE
+                                lf.field = 'game_versions'
+                                AND gvf.version_id IN (v.id, pv.id)
+                        )
+                    )
+                ORDER BY pv.date_published DESC
+                LIMIT 1
+            ) pf ON TRUE
+            WHERE
+                f.size <= $1
+                AND pf.size <= $1
+                AND NOT EXISTS (
+                    SELECT 1
+                    FROM version_file_deltas d
+                    WHERE d.from_file_id = pf.id AND d.to_file_id = f.id
+                )
+            ORDER BY v.date_published DESC
+            LIMIT $2
+            "#,
+            max_size,
+            limit,
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| PendingVersionFileDelta {
+                project_id: DBProjectId(row.project_id),
+                from_file_id: DBFileId(row.from_file_id),
+                from_url: row.from_url,
+                to_file_id: DBFileId(row.to_file_id),
+                to_url: row.to_url,
+                to_size: row.to_size,
+            })
+            .collect())
+    }
+}
@@ -9,7 +9,9 @@ use crate::file_hosting::FileHost;
 use crate::models::exp;
 use xredis::RedisPool;
 
-use crate::models::projects::{FileType, VersionStatus};
+use crate::models::projects::{
+    FileSignature, FileSignatureStatus, FileType, VersionStatus,
+};
 use crate::queue::file_scan::scan_file;
 use crate::routes::internal::delphi::DelphiRunParameters;
 use chrono::{DateTime, Utc};
@@ -22,8 +24,8 @@ use std::cmp::Ordering;
 use std::collections::HashMap;
 use tracing::error;
 
-pub const VERSIONS_NAMESPACE: &str = "versions:v4";
-const VERSION_FILES_NAMESPACE: &str = "versions_files:v4";
+pub const VERSIONS_NAMESPACE: &str = "versions:v5";
+const VERSION_FILES_NAMESPACE: &str = "versions_files:v5";
 
 pub async fn cleanup_unused_attribution_files_and_groups(
     transaction: &mut PgTransaction<'_>,
@@ -169,6 +171,7 @@ pub struct VersionFileBuilder {
     pub primary: bool,
     pub size: u32,
     pub file_type: Option<FileType>,
+    pub signature: Option<FileSignature>,
 }
 
 impl VersionFileBuilder {
@@ -185,8 +188,8 @@ impl VersionFileBuilder {
 
         sqlx::query!(
             "
-            INSERT INTO files (id, version_id, url, filename, is_primary, size, file_type)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            INSERT INTO files (id, version_id, url, filename, is_primary, size, file_type, signature_status, signature_fingerprint)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
             ",
             file_id as DBFileId,
             version_id as DBVersionId,
@@ -195,6 +198,8 @@ impl VersionFileBuilder {
             self.primary,
             self.size as i32,
             self.file_type.map(|x| x.as_str()),
+            self.signature.as_ref().map(|x| x.status.as_str()),
+            self.signature.as_ref().and_then(|x| x.key_fingerprint.as_deref()),
         )
         .execute(&mut *transaction)
         .await?;
@@ -595,54 +600,86 @@ impl DBVersion {
     where
         E: crate::database::Acquire<'a, Database = sqlx::Postgres>,
     {
-        let mut val = redis.get_cached_keys(
-            VERSIONS_NAMESPACE,
-            &version_ids.iter().map(|x| x.0).collect::<Vec<_>>(),
-            |version_ids| async move {
-                let mut exec = exec.acquire().await?;
-
-                let loader_field_enum_value_ids = DashSet::new();
-                let version_fields: DashMap<DBVersionId, Vec<QueryVersionField>> = sqlx::query!(
-                    "
+        let mut val = redis
+            .get_cached_keys(
+                VERSIONS_NAMESPACE,
+                &version_ids.iter().map(|x| x.0).collect::<Vec<_>>(),
+                |version_ids| Self::fetch_many(version_ids, exec),
+            )
+            .await?;
+
+        val.sort();
+
+        Ok(val)
+    }
+
+    /// Fetches a version from the database without going through the cache,
+    /// so that a transaction sees the changes it has made.
+    pub async fn get_uncached<'a, E>(
+        id: DBVersionId,
+        exec: E,
+    ) -> Result<Option<VersionQueryResult>, DatabaseError>
+    where
+        E: crate::database::Acquire<'a, Database = sqlx::Postgres>,
+    {
+        Ok(Self::fetch_many(vec![id.0], exec)
+            .await?
+            .into_iter()
+            .next()
+            .map(|(_, version)| version))
+    }
+
+    async fn fetch_many<'a, E>(
+        version_ids: Vec<i64>,
+        exec: E,
+    ) -> Result<DashMap<i64, VersionQueryResult>, DatabaseError>
+    where
+        E: crate::database::Acquire<'a, Database = sqlx::Postgres>,
+    {
+        let mut exec = exec.acquire().await?;
+
+        let loader_field_enum_value_ids = DashSet::new();
+        let version_fields: DashMap<DBVersionId, Vec<QueryVersionField>> = sqlx::query!(
+            "
                     SELECT version_id, field_id, int_value, enum_value, string_value
                     FROM version_fields
                     WHERE version_id = ANY($1)
                     ",
-                    &version_ids
-                )
-                    .fetch(&mut exec)
-                    .try_fold(
-                        DashMap::new(),
-                        |acc: DashMap<DBVersionId, Vec<QueryVersionField>>, m| {
-                            let qvf = QueryVersionField {
-                                version_id: DBVersionId(m.version_id),
-                                field_id: LoaderFieldId(m.field_id),
-                                int_value: m.int_value,
-                                enum_value: if m.enum_value == -1  { None } else { Some(LoaderFieldEnumValueId(m.enum_value)) },
-                                string_value: m.string_value,
-                            };
-
-                            if m.enum_value != -1 {
-                                loader_field_enum_value_ids.insert(LoaderFieldEnumValueId(m.enum_value));
-                            }
+            &version_ids
+        )
+            .fetch(&mut exec)
+            .try_fold(
+                DashMap::new(),
+                |acc: DashMap<DBVersionId, Vec<QueryVersionField>>, m| {
+                    let qvf = QueryVersionField {
+                        version_id: DBVersionId(m.version_id),
+                        field_id: LoaderFieldId(m.field_id),
+                        int_value: m.int_value,
+                        enum_value: if m.enum_value == -1  { None } else { Some(LoaderFieldEnumValueId(m.enum_value)) },
+                        string_value: m.string_value,
+                    };
+
+                    if m.enum_value != -1 {
+                        loader_field_enum_value_ids.insert(LoaderFieldEnumValueId(m.enum_value));
+                    }
 
-                            acc.entry(DBVersionId(m.version_id)).or_default().push(qvf);
-                            async move { Ok(acc) }
-                        },
-                    )
-                    .await?;
+                    acc.entry(DBVersionId(m.version_id)).or_default().push(qvf);
+                    async move { Ok(acc) }
+                },
+            )
+            .await?;
 
-                #[derive(Default)]
-                struct VersionLoaderData {
-                    loaders: Vec<String>,
-                    project_types: Vec<String>,
-                    games: Vec<String>,
-                    loader_loader_field_ids: Vec<LoaderFieldId>,
-                }
+        #[derive(Default)]
+        struct VersionLoaderData {
+            loaders: Vec<String>,
+            project_types: Vec<String>,
+            games: Vec<String>,
+            loader_loader_field_ids: Vec<LoaderFieldId>,
+        }
 
-                let loader_field_ids = DashSet::new();
-                let loaders_ptypes_games: DashMap<DBVersionId, VersionLoaderData> = sqlx::query!(
-                    "
+        let loader_field_ids = DashSet::new();
+        let loaders_ptypes_games: DashMap<DBVersionId, VersionLoaderData> = sqlx::query!(
+            "
                     SELECT DISTINCT version_id,
                         ARRAY_AGG(DISTINCT l.loader) filter (where l.loader is not null) loaders,
                         ARRAY_AGG(DISTINCT pt.name) filter (where pt.name is not null) project_types,
@@ -659,53 +696,54 @@ impl DBVersion {
                     WHERE v.id = ANY($1)
                     GROUP BY version_id
                     ",
-                    &version_ids
-                ).fetch(&mut exec)
-                    .map_ok(|m| {
-                        let version_id = DBVersionId(m.version_id);
-
-                        // Add loader fields to the set we need to fetch
-                        let loader_loader_field_ids = m.loader_fields.unwrap_or_default().into_iter().map(LoaderFieldId).collect::<Vec<_>>();
-                        for loader_field_id in &loader_loader_field_ids {
-                            loader_field_ids.insert(*loader_field_id);
-                        }
+            &version_ids
+        ).fetch(&mut exec)
+            .map_ok(|m| {
+                let version_id = DBVersionId(m.version_id);
+
+                // Add loader fields to the set we need to fetch
+                let loader_loader_field_ids = m.loader_fields.unwrap_or_default().into_iter().map(LoaderFieldId).collect::<Vec<_>>();
+                for loader_field_id in &loader_loader_field_ids {
+                    loader_field_ids.insert(*loader_field_id);
+                }
 
-                        // Add loader + loader associated data to the map
-                        let version_loader_data = VersionLoaderData {
-                            loaders: m.loaders.unwrap_or_default(),
-                            project_types: m.project_types.unwrap_or_default(),
-                            games: m.games.unwrap_or_default(),
-                            loader_loader_field_ids,
-                        };
-                        (version_id,version_loader_data)
+                // Add loader + loader associated data to the map
+                let version_loader_data = VersionLoaderData {
+                    loaders: m.loaders.unwrap_or_default(),
+                    project_types: m.project_types.unwrap_or_default(),
+                    games: m.games.unwrap_or_default(),
+                    loader_loader_field_ids,
+                };
+                (version_id,version_loader_data)
 
-                    }
-                    ).try_collect().await?;
+            }
+            ).try_collect().await?;
 
-                // Fetch all loader fields from any version
-                let loader_fields: Vec<QueryLoaderField> = sqlx::query!(
-                    "
+        // Fetch all loader fields from any version
+        let loader_fields: Vec<QueryLoaderField> = sqlx::query!(
+            "
                     SELECT DISTINCT id, field, field_type, enum_type, min_val, max_val, optional
                     FROM loader_fields lf
                     WHERE id = ANY($1)
                     ",
-                    &loader_field_ids.iter().map(|x| x.0).collect::<Vec<_>>()
-                )
-                    .fetch(&mut exec)
-                    .map_ok(|m| QueryLoaderField {
-                        id: LoaderFieldId(m.id),
-                        field: m.field,
-                        field_type: m.field_type,
-                        enum_type: m.enum_type.map(LoaderFieldEnumId),
-                        min_val: m.min_val,
-                        max_val: m.max_val,
-                        optional: m.optional,
-                    })
-                    .try_collect()
-                    .await?;
+            &loader_field_ids.iter().map(|x| x.0).collect::<Vec<_>>()
+        )
+            .fetch(&mut exec)
+            .map_ok(|m| QueryLoaderField {
+                id: LoaderFieldId(m.id),
+                field: m.field,
+                field_type: m.field_type,
+                enum_type: m.enum_type.map(LoaderFieldEnumId),
+                min_val: m.min_val,
+                max_val: m.max_val,
+                optional: m.optional,
+            })
+            .try_collect()
+            .await?;
 
-                let loader_field_enum_values: Vec<QueryLoaderFieldEnumValue> = sqlx::query!(
-                    r#"
+        let loader_field_enum_values: Vec<QueryLoaderFieldEnumValue> =
+            sqlx::query!(
+                r#"
                     SELECT DISTINCT id, enum_id, value, ordering, created,
                     metadata->>'type' AS "ty?",
                     (metadata->>'major')::boolean AS "major?"
@@ -713,135 +751,136 @@ impl DBVersion {
                     WHERE id = ANY($1)
                     ORDER BY enum_id, ordering, created ASC
                     "#,
-                    &loader_field_enum_value_ids
-                        .iter()
-                        .map(|x| x.0)
-                        .collect::<Vec<_>>()
-                )
-                    .fetch(&mut exec)
-                    .map_ok(|m| QueryLoaderFieldEnumValue {
-                        id: LoaderFieldEnumValueId(m.id),
-                        enum_id: LoaderFieldEnumId(m.enum_id),
-                        value: m.value,
-                        ordering: m.ordering,
-                        created: m.created,
-                        ty: m.ty,
-                        major: m.major,
-                    })
-                    .try_collect()
-                    .await?;
+                &loader_field_enum_value_ids
+                    .iter()
+                    .map(|x| x.0)
+                    .collect::<Vec<_>>()
+            )
+            .fetch(&mut exec)
+            .map_ok(|m| QueryLoaderFieldEnumValue {
+                id: LoaderFieldEnumValueId(m.id),
+                enum_id: LoaderFieldEnumId(m.enum_id),
+                value: m.value,
+                ordering: m.ordering,
+                created: m.created,
+                ty: m.ty,
+                major: m.major,
+            })
+            .try_collect()
+            .await?;
 
-                #[derive(Deserialize)]
-                struct Hash {
-                    pub file_id: DBFileId,
-                    pub algorithm: String,
-                    pub hash: String,
-                }
+        #[derive(Deserialize)]
+        struct Hash {
+            pub file_id: DBFileId,
+            pub algorithm: String,
+            pub hash: String,
+        }
 
-                #[derive(Deserialize)]
-                struct File {
-                    pub id: DBFileId,
-                    pub url: String,
-                    pub filename: String,
-                    pub primary: bool,
-                    pub size: u32,
-                    pub file_type: Option<FileType>,
-                }
+        #[derive(Deserialize)]
+        struct File {
+            pub id: DBFileId,
+            pub url: String,
+            pub filename: String,
+            pub primary: bool,
+            pub size: u32,
+            pub file_type: Option<FileType>,
+            pub signature: Option<FileSignature>,
+        }
 
-                let file_ids = DashSet::new();
-                let reverse_file_map = DashMap::new();
-                let files : DashMap<DBVersionId, Vec<File>> = sqlx::query!(
-                    "
-                    SELECT DISTINCT version_id, f.id, f.url, f.filename, f.is_primary, f.size, f.file_type
+        let file_ids = DashSet::new();
+        let reverse_file_map = DashMap::new();
+        let files : DashMap<DBVersionId, Vec<File>> = sqlx::query!(
+            "
+                    SELECT DISTINCT version_id, f.id, f.url, f.filename, f.is_primary, f.size, f.file_type, f.signature_status, f.signature_fingerprint
                     FROM files f
                     WHERE f.version_id = ANY($1)
                     ",
-                    &version_ids
-                ).fetch(&mut exec)
-                    .try_fold(DashMap::new(), |acc : DashMap<DBVersionId, Vec<File>>, m| {
-                        let file = File {
-                            id: DBFileId(m.id),
-                            url: m.url,
-                            filename: m.filename,
-                            primary: m.is_primary,
-                            size: m.size as u32,
-                            file_type: m.file_type.map(|x| FileType::from_string(&x)),
-                        };
-
-                        file_ids.insert(DBFileId(m.id));
-                        reverse_file_map.insert(DBFileId(m.id), DBVersionId(m.version_id));
-
-                        acc.entry(DBVersionId(m.version_id))
-                            .or_default()
-                            .push(file);
-                        async move { Ok(acc) }
-                    }
-                    ).await?;
+            &version_ids
+        ).fetch(&mut exec)
+            .try_fold(DashMap::new(), |acc : DashMap<DBVersionId, Vec<File>>, m| {
+                let file = File {
+                    id: DBFileId(m.id),
+                    url: m.url,
+                    filename: m.filename,
+                    primary: m.is_primary,
+                    size: m.size as u32,
+                    file_type: m.file_type.map(|x| FileType::from_string(&x)),
+                    signature: m.signature_status.map(|x| FileSignature {
+                        status: FileSignatureStatus::from_string(&x),
+                        key_fingerprint: m.signature_fingerprint,
+                    }),
+                };
+
+                file_ids.insert(DBFileId(m.id));
+                reverse_file_map.insert(DBFileId(m.id), DBVersionId(m.version_id));
+
+                acc.entry(DBVersionId(m.version_id))
+                    .or_default()
+                    .push(file);
+                async move { Ok(acc) }
+            }
+            ).await?;
 
-                let hashes: DashMap<DBVersionId, Vec<Hash>> = sqlx::query!(
-                    "
+        let hashes: DashMap<DBVersionId, Vec<Hash>> = sqlx::query!(
+            "
                     SELECT DISTINCT file_id, algorithm, encode(hash, 'escape') hash
                     FROM hashes
                     WHERE file_id = ANY($1)
                     ",
-                    &file_ids.iter().map(|x| x.0).collect::<Vec<_>>()
-                )
-                    .fetch(&mut exec)
-                    .try_fold(DashMap::new(), |acc: DashMap<DBVersionId, Vec<Hash>>, m| {
-                        if let Some(found_hash) = m.hash {
-                            let hash = Hash {
-                                file_id: DBFileId(m.file_id),
-                                algorithm: m.algorithm,
-                                hash: found_hash,
-                            };
-
-                            if let Some(version_id) = reverse_file_map.get(&DBFileId(m.file_id)) {
-                                acc.entry(*version_id).or_default().push(hash);
-                            }
-                        }
-                        async move { Ok(acc) }
-                    })
-                    .await?;
+            &file_ids.iter().map(|x| x.0).collect::<Vec<_>>()
+        )
+            .fetch(&mut exec)
+            .try_fold(DashMap::new(), |acc: DashMap<DBVersionId, Vec<Hash>>, m| {
+                if let Some(found_hash) = m.hash {
+                    let hash = Hash {
+                        file_id: DBFileId(m.file_id),
+                        algorithm: m.algorithm,
+                        hash: found_hash,
+                    };
+
+                    if let Some(version_id) = reverse_file_map.get(&DBFileId(m.file_id)) {
+                        acc.entry(*version_id).or_default().push(hash);
+                    }
+                }
+                async move { Ok(acc) }
+            })
+            .await?;
 
-                let dependencies : DashMap<DBVersionId, Vec<DependencyQueryResult>> = sqlx::query!(
-                    "
+        let dependencies : DashMap<DBVersionId, Vec<DependencyQueryResult>> = sqlx::query!(
+            "
                     SELECT DISTINCT d.id as dependency_id, dependent_id as version_id, d.mod_dependency_id as dependency_project_id, d.dependency_id as dependency_version_id, d.dependency_file_name as file_name, d.dependency_type as dependency_type
                     FROM dependencies d
                     WHERE dependent_id = ANY($1)
                     ",
-                    &version_ids
-                ).fetch(&mut exec)
-                    .try_fold(DashMap::new(), |acc : DashMap<_,Vec<DependencyQueryResult>>, m| {
-                        let dependency = DependencyQueryResult {
-                            id: m.dependency_id,
-                            project_id: m.dependency_project_id.map(DBProjectId),
-                            version_id: m.dependency_version_id.map(DBVersionId),
-                            file_name: m.file_name,
-                            dependency_type: m.dependency_type,
-                            attribution: None,
-                        };
-
-                        acc.entry(DBVersionId(m.version_id))
-                            .or_default()
-                            .push(dependency);
-                        async move { Ok(acc) }
-                    }
-                    ).await?;
-
-                let dependency_attributions =
-                    crate::queue::file_scan::get_dependency_attributions(
-                        &mut exec,
-                        &version_ids
-                            .iter()
-                            .copied()
-                            .map(DBVersionId)
-                            .collect_vec(),
-                    )
-                    .await
-                    .unwrap_or_default();
-
-                let res = sqlx::query!(
-                    r#"
+            &version_ids
+        ).fetch(&mut exec)
+            .try_fold(DashMap::new(), |acc : DashMap<_,Vec<DependencyQueryResult>>, m| {
+                let dependency = DependencyQueryResult {
+                    id: m.dependency_id,
+                    project_id: m.dependency_project_id.map(DBProjectId),
+                    version_id: m.dependency_version_id.map(DBVersionId),
+                    file_name: m.file_name,
+                    dependency_type: m.dependency_type,
+                    attribution: None,
+                };
+
+                acc.entry(DBVersionId(m.version_id))
+                    .or_default()
+                    .push(dependency);
+                async move { Ok(acc) }
+            }
+            ).await?;
+
+        let dependency_attributions =
+            crate::queue::file_scan::get_dependency_attributions(
+                &mut exec,
+                &version_ids.iter().copied().map(DBVersionId).collect_vec(),
+            )
+            .await
+            .unwrap_or_default();
+
+        let res = sqlx::query!(
+            r#"
                     SELECT v.id id, v.mod_id mod_id, v.author_id author_id, v.name version_name, v.version_number version_number,
                     v.changelog changelog, v.date_published date_published, v.downloads downloads,
                     v.version_type version_type, v.featured featured, v.status status, v.requested_status requested_status, v.ordering ordering,
@@ -849,115 +888,110 @@ impl DBVersion {
                     FROM versions v
                     WHERE v.id = ANY($1);
                     "#,
-                    &version_ids
-                )
-                    .fetch(&mut exec)
-                    .try_fold(DashMap::new(), |acc, v| {
-                        let version_id = DBVersionId(v.id);
-                        let VersionLoaderData {
-                            loaders,
-                            project_types,
-                            games,
-                            loader_loader_field_ids,
-                        } = loaders_ptypes_games.remove(&version_id).map(|x|x.1).unwrap_or_default();
-                        let files = files.remove(&version_id).map(|x|x.1).unwrap_or_default();
-                        let hashes = hashes.remove(&version_id).map(|x|x.1).unwrap_or_default();
-                        let version_fields = version_fields.remove(&version_id).map(|x|x.1).unwrap_or_default();
-                        let dependencies = dependencies.remove(&version_id).map(|x|x.1).unwrap_or_default();
-                        let dependencies = dependencies
-                            .into_iter()
-                            .map(|mut dependency| {
-                                if let Some(attr) = dependency_attributions.get(&dependency.id)
-                                    && (attr.attribution.flame_project.is_some()
-                                        || attr.attribution.resolution.is_some())
-                                {
-                                    dependency.attribution = Some(attr.attribution.clone());
-                                }
+            &version_ids
+        )
+            .fetch(&mut exec)
+            .try_fold(DashMap::new(), |acc, v| {
+                let version_id = DBVersionId(v.id);
+                let VersionLoaderData {
+                    loaders,
+                    project_types,
+                    games,
+                    loader_loader_field_ids,
+                } = loaders_ptypes_games.remove(&version_id).map(|x|x.1).unwrap_or_default();
+                let files = files.remove(&version_id).map(|x|x.1).unwrap_or_default();
+                let hashes = hashes.remove(&version_id).map(|x|x.1).unwrap_or_default();
+                let version_fields = version_fields.remove(&version_id).map(|x|x.1).unwrap_or_default();
+                let dependencies = dependencies.remove(&version_id).map(|x|x.1).unwrap_or_default();
+                let dependencies = dependencies
+                    .into_iter()
+                    .map(|mut dependency| {
+                        if let Some(attr) = dependency_attributions.get(&dependency.id)
+                            && (attr.attribution.flame_project.is_some()
+                                || attr.attribution.resolution.is_some())
+                        {
+                            dependency.attribution = Some(attr.attribution.clone());
+                        }
 
-                                dependency
-                            })
-                            .collect_vec();
-
-                        let loader_fields = loader_fields.iter()
-                            .filter(|x| loader_loader_field_ids.contains(&x.id))
-                            .collect::<Vec<_>>();
-
-                        let components_serial = v.components.0;
-                        let query_version = VersionQueryResult {
-                            inner: DBVersion {
-                                id: DBVersionId(v.id),
-                                project_id: DBProjectId(v.mod_id),
-                                author_id: DBUserId(v.author_id),
-                                name: v.version_name,
-                                version_number: v.version_number,
-                                changelog: v.changelog,
-                                date_published: v.date_published,
-                                downloads: v.downloads,
-                                version_type: v.version_type,
-                                featured: v.featured,
-                                status: VersionStatus::from_string(&v.status),
-                                requested_status: v.requested_status
-                                    .map(|x| VersionStatus::from_string(&x)),
-                                ordering: v.ordering,
-                                components: components_serial,
-                            },
-                            files: {
-                                let mut files = files.into_iter().map(|x| {
-                                    let mut file_hashes = HashMap::new();
-
-                                    for hash in &hashes {
-                                        if hash.file_id == x.id {
-                                            file_hashes.insert(
-                                                hash.algorithm.clone(),
-                                                hash.hash.clone(),
-                                            );
-                                        }
-                                    }
-
-                                    FileQueryResult {
-                                        id: x.id,
-                                        url: x.url.clone(),
-                                        filename: x.filename.clone(),
-                                        hashes: file_hashes,
-                                        primary: x.primary,
-                                        size: x.size,
-                                        file_type: x.file_type,
-                                    }
-                                }).collect::<Vec<_>>();
-
-                                files.sort_by(|a, b| {
-                                    if a.primary {
-                                        Ordering::Less
-                                    } else if b.primary {
-                                        Ordering::Greater
-                                    } else {
-                                        a.filename.cmp(&b.filename)
-                                    }
-                                });
-
-                                files
-                            },
-                            version_fields: VersionField::from_query_json(version_fields, &loader_fields, &loader_field_enum_values, false),
-                            loaders,
-                            project_types,
-                            games,
-                            dependencies,
-                            // TODO populate
-                            components: exp::VersionQuery::default(),
-                        };
-
-                        acc.insert(v.id, query_version);
-                        async move { Ok(acc) }
+                        dependency
                     })
-                    .await?;
-
-                Ok::<_, DatabaseError>(res)
-            },
-        ).await?;
+                    .collect_vec();
+
+                let loader_fields = loader_fields.iter()
+                    .filter(|x| loader_loader_field_ids.contains(&x.id))
+                    .collect::<Vec<_>>();
+
+                let components_serial = v.components.0;
+                let query_version = VersionQueryResult {
+                    inner: DBVersion {
+                        id: DBVersionId(v.id),
+                        project_id: DBProjectId(v.mod_id),
+                        author_id: DBUserId(v.author_id),
+                        name: v.version_name,
+                        version_number: v.version_number,
+                        changelog: v.changelog,
+                        date_published: v.date_published,
+                        downloads: v.downloads,
+                        version_type: v.version_type,
+                        featured: v.featured,
+                        status: VersionStatus::from_string(&v.status),
+                        requested_status: v.requested_status
+                            .map(|x| VersionStatus::from_string(&x)),
+                        ordering: v.ordering,
+                        components: components_serial,
+                    },
+                    files: {
+                        let mut files = files.into_iter().map(|x| {
+                            let mut file_hashes = HashMap::new();
+
+                            for hash in &hashes {
+                                if hash.file_id == x.id {
+                                    file_hashes.insert(
+                                        hash.algorithm.clone(),
+                                        hash.hash.clone(),
+                                    );
+                                }
+                            }
 
-        val.sort();
+                            FileQueryResult {
+                                id: x.id,
+                                url: x.url.clone(),
+                                filename: x.filename.clone(),
+                                hashes: file_hashes,
+                                primary: x.primary,
+                                size: x.size,
+                                file_type: x.file_type,
+                                signature: x.signature,
+                            }
+                        }).collect::<Vec<_>>();
+
+                        files.sort_by(|a, b| {
+                            if a.primary {
+                                Ordering::Less
+                            } else if b.primary {
+                                Ordering::Greater
+                            } else {
+                                a.filename.cmp(&b.filename)
+                            }
+                        });
+
+                        files
+                    },
+                    version_fields: VersionField::from_query_json(version_fields, &loader_fields, &loader_field_enum_values, false),
+                    loaders,
+                    project_types,
+                    games,
+                    dependencies,
+                    // TODO populate
+                    components: exp::VersionQuery::default(),
+                };
+
+                acc.insert(v.id, query_version);
+                async move { Ok(acc) }
+            })
+            .await?;
 
-        Ok(val)
+        Ok::<_, DatabaseError>(res)
     }
 
     pub async fn get_file_from_hash<'a, 'b, E>(
@@ -993,7 +1027,7 @@ impl DBVersion {
             |file_ids| async move {
                 let files = sqlx::query!(
                     "
-                    SELECT f.id, f.version_id, v.mod_id, f.url, f.filename, f.is_primary, f.size, f.file_type,
+                    SELECT f.id, f.version_id, v.mod_id, f.url, f.filename, f.is_primary, f.size, f.file_type, f.signature_status, f.signature_fingerprint,
                     JSONB_AGG(DISTINCT jsonb_build_object('algorithm', h.algorithm, 'hash', encode(h.hash, 'escape'))) filter (where h.hash is not null) hashes
                     FROM files f
                     INNER JOIN versions v on v.id = f.version_id
@@ -1033,6 +1067,10 @@ impl DBVersion {
                                 primary: f.is_primary,
                                 size: f.size as u32,
                                 file_type: f.file_type.map(|x| FileType::from_string(&x)),
+                                signature: f.signature_status.map(|x| FileSignature {
+                                    status: FileSignatureStatus::from_string(&x),
+                                    key_fingerprint: f.signature_fingerprint,
+                                }),
                             };
 
                             acc.insert(key, file);
@@ -1118,6 +1156,7 @@ pub struct FileQueryResult {
     pub primary: bool,
     pub size: u32,
     pub file_type: Option<FileType>,
+    pub signature: Option<FileSignature>,
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -1131,6 +1170,32 @@ pub struct DBFile {
     pub primary: bool,
     pub size: u32,
     pub file_type: Option<FileType>,
+    pub signature: Option<FileSignature>,
+}
+
+impl DBFile {
+    /// Records the result of verifying an existing file against a detached
+    /// signature uploaded after it.
+    pub async fn set_signature(
+        file_id: DBFileId,
+        signature: &FileSignature,
+        exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "
+            UPDATE files
+            SET signature_status = $2, signature_fingerprint = $3
+            WHERE id = $1
+            ",
+            file_id as DBFileId,
+            signature.status.as_str(),
+            signature.key_fingerprint.as_deref(),
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
 }
 
 impl std::cmp::Ord for VersionQueryResult {
@@ -3,6 +3,7 @@ use prometheus::{IntGauge, Registry};
 use sqlx::migrate::{MigrateDatabase, Migrator};
 use sqlx::postgres::PgPoolOptions;
 use sqlx::{Connection, Postgres};
+use sqlx_tracing::analysis::QueryAnalysisLayer;
 use std::ops::{Deref, DerefMut};
 use std::time::Duration;
 use tracing::info;
@@ -83,6 +84,26 @@ pub async fn connect_all() -> Result<(PgPool, ReadOnlyPgPool), sqlx::Error> {
     }
 }
 
+/// Builds the tracing layer which logs slow queries and possible N+1s, and
+/// records per-statement and per-route query metrics.
+///
+/// A threshold of 0 disables that check.
+pub fn query_analysis_layer() -> Result<QueryAnalysisLayer, prometheus::Error> {
+    let mut layer = QueryAnalysisLayer::new()?;
+
+    if ENV.DATABASE_SLOW_QUERY_THRESHOLD_MS > 0 {
+        layer = layer.with_slow_query_threshold(Duration::from_millis(
+            ENV.DATABASE_SLOW_QUERY_THRESHOLD_MS,
+        ));
+    }
+    if ENV.DATABASE_N_PLUS_ONE_THRESHOLD > 0 {
+        layer =
+            layer.with_n_plus_one_threshold(ENV.DATABASE_N_PLUS_ONE_THRESHOLD);
+    }
+
+    Ok(layer)
+}
+
 pub async fn check_for_migrations() -> eyre::Result<()> {
     let uri = &ENV.DATABASE_URL;
     let uri = uri.as_str();
@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::env::ENV;
 use eyre::{Result, WrapErr};
@@ -9,7 +10,9 @@ struct RedisConfig {
 }
 
 impl RedisConfig {
-    fn from_env() -> Result<Self> {
+    fn from_env(
+        cache_locking_strategy: xredis::CacheLockingStrategy,
+    ) -> Result<Self> {
         let inner = xredis::RedisConfig::new(
             ENV.REDIS_TOPOLOGY,
             ENV.REDIS_CONNECTION_TYPE,
@@ -24,7 +27,7 @@ impl RedisConfig {
                 ENV.REDIS_CLUSTER_MIN_CONNECTIONS,
             ),
             (ENV.REDIS_BLOCKING_MAX_CONNECTIONS as usize, 0),
-            ENV.REDIS_CACHE_LOCKING_STRATEGY,
+            cache_locking_strategy,
             ENV.REDIS_READ_REPLICA_STRATEGY,
         )
         .wrap_err("loading Redis configuration from environment")?;
@@ -39,6 +42,16 @@ impl RedisConfig {
             compression_threshold_bytes: ENV.REDIS_COMPRESSION_THRESHOLD_BYTES,
             compression_min_savings_ratio: ENV
                 .REDIS_COMPRESSION_MIN_SAVINGS_RATIO,
+            l1: xredis::L1CacheSettings {
+                max_entries: ENV.REDIS_L1_MAX_ENTRIES,
+                namespace_ttls: ENV
+                    .REDIS_L1_NAMESPACE_TTLS
+                    .iter()
+                    .map(|(namespace, ttl)| {
+                        (namespace.clone(), Duration::from_secs(*ttl))
+                    })
+                    .collect(),
+            },
         };
 
         Ok(Self {
@@ -51,7 +64,21 @@ impl RedisConfig {
 pub async fn from_env(
     meta_namespace: impl Into<Arc<str>>,
 ) -> xredis::RedisPool {
-    let config = RedisConfig::from_env().expect("invalid Redis configuration");
+    with_cache_locking_strategy(
+        meta_namespace,
+        ENV.REDIS_CACHE_LOCKING_STRATEGY,
+    )
+    .await
+}
+
+/// Connects like [`from_env`], but with the given cache locking strategy
+/// instead of the configured one.
+pub async fn with_cache_locking_strategy(
+    meta_namespace: impl Into<Arc<str>>,
+    cache_locking_strategy: xredis::CacheLockingStrategy,
+) -> xredis::RedisPool {
+    let config = RedisConfig::from_env(cache_locking_strategy)
+        .expect("invalid Redis configuration");
     xredis::RedisPool::new(meta_namespace, config.inner, config.cache_settings)
         .await
         .expect("failed to initialize Redis connections")
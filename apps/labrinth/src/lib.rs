@@ -6,7 +6,7 @@ use std::time::Duration;
 use actix_web::web;
 use queue::{
     analytics::AnalyticsQueue, email::EmailQueue, payouts::PayoutsQueue,
-    session::AuthQueue, socket::ActiveSockets,
+    realtime::ActiveEventStreams, session::AuthQueue, socket::ActiveSockets,
 };
 use tracing::{debug, info, warn};
 use xredis::RedisPool;
@@ -26,7 +26,11 @@ use crate::util::archon::ArchonClient;
 use crate::util::http::HttpClient;
 use crate::util::ratelimit::{AsyncRateLimiter, GCRAParameters};
 use crate::util::tiltify::TiltifyClient;
-use sync::friends::{FRIENDS_CHANNEL_NAME, handle_pubsub};
+use sync::friends::{
+    FRIENDS_CHANNEL_NAME, handle_pubsub, handle_tunnel_pubsub,
+    tunnel_channel_name,
+};
+use sync::realtime::REALTIME_CHANNEL_NAME;
 use url::Url;
 use webauthn_rs::{Webauthn, WebauthnBuilder};
 
@@ -67,6 +71,7 @@ pub struct LabrinthConfig {
     pub payouts_queue: web::Data<PayoutsQueue>,
     pub analytics_queue: Arc<AnalyticsQueue>,
     pub active_sockets: web::Data<ActiveSockets>,
+    pub active_event_streams: web::Data<ActiveEventStreams>,
     pub rate_limiter: web::Data<AsyncRateLimiter>,
     pub stripe_client: stripe::Client,
     pub anrok_client: anrok::Client,
@@ -273,6 +278,35 @@ pub fn app_setup(
         });
     }
 
+    {
+        let redis_pool = redis_pool.clone();
+        // Subscribed to for as long as the process runs
+        let channel = tunnel_channel_name(active_sockets.replica_id).leak();
+        let pubsub_messages = redis_pool.subscribe(channel);
+        let sockets = active_sockets.clone();
+        actix_rt::spawn(async move {
+            handle_tunnel_pubsub(pubsub_messages, redis_pool, sockets).await;
+        });
+    }
+
+    let active_event_streams = web::Data::new(ActiveEventStreams::default());
+
+    {
+        let pool = pool.clone();
+        let redis_pool = redis_pool.clone();
+        let pubsub_messages = redis_pool.subscribe(REALTIME_CHANNEL_NAME);
+        let streams = active_event_streams.clone();
+        actix_rt::spawn(async move {
+            sync::realtime::handle_pubsub(
+                pubsub_messages,
+                pool,
+                redis_pool,
+                streams,
+            )
+            .await;
+        });
+    }
+
     let webauthn_origin = Url::parse(&ENV.SITE_URL).expect("invalid SITE_URL");
     let webauthn_rp_id = webauthn_origin
         .host_str()
@@ -299,6 +333,7 @@ pub fn app_setup(
         payouts_queue: web::Data::new(PayoutsQueue::new()),
         analytics_queue,
         active_sockets,
+        active_event_streams,
         rate_limiter: limiter,
         stripe_client,
         anrok_client,
@@ -356,6 +391,7 @@ pub fn app_data_config(
     .app_data(web::Data::new(labrinth_config.analytics_queue.clone()))
     .app_data(web::Data::new(labrinth_config.clickhouse.clone()))
     .app_data(labrinth_config.active_sockets.clone())
+    .app_data(labrinth_config.active_event_streams.clone())
     .app_data(labrinth_config.archon_client.clone())
     .app_data(web::Data::new(labrinth_config.stripe_client.clone()))
     .app_data(web::Data::new(labrinth_config.anrok_client.clone()))
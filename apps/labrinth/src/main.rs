@@ -19,6 +19,7 @@ use labrinth::{app_data_config, app_fallback_config, app_routes_config, env};
 use labrinth::{clickhouse, database, file_hosting};
 use scalar_api_reference::actix_web::config as scalar_config;
 use serde_json::json;
+use sqlx_tracing::analysis::QueryMetrics;
 use std::ffi::CStr;
 use std::sync::Arc;
 use tracing::{Instrument, info, info_span};
@@ -60,8 +61,14 @@ struct Args {
 
 fn main() -> std::io::Result<()> {
     color_eyre::install().expect("failed to install `color-eyre`");
-    modrinth_util::log::init().expect("failed to initialize logging");
+    // Logging depends on the query analysis thresholds, so the environment
+    // is read first.
     env::init().expect("failed to initialize environment variables");
+    let query_analysis = database::query_analysis_layer()
+        .expect("failed to create query analysis layer");
+    let query_metrics = query_analysis.metrics();
+    modrinth_util::log::init_with_layer(false, query_analysis)
+        .expect("failed to initialize logging");
 
     // Sentry must be set up before the async runtime is started
     // <https://docs.sentry.io/platforms/rust/guides/actix-web/>
@@ -80,14 +87,14 @@ fn main() -> std::io::Result<()> {
         }
     }
 
-    actix_rt::System::new().block_on(app())?;
+    actix_rt::System::new().block_on(app(query_metrics))?;
 
     // Sentry guard must live until the end of the app
     drop(sentry);
     Ok(())
 }
 
-async fn app() -> std::io::Result<()> {
+async fn app(query_metrics: QueryMetrics) -> std::io::Result<()> {
     let args = Args::parse();
 
     rustls::crypto::aws_lc_rs::default_provider()
@@ -199,6 +206,9 @@ async fn app() -> std::io::Result<()> {
     database::register_and_set_metrics(&pool, &prometheus.registry)
         .await
         .expect("Failed to register database metrics");
+    query_metrics
+        .register(&prometheus.registry)
+        .expect("Failed to register query analysis metrics");
     redis_pool
         .register_and_set_metrics(&prometheus.registry)
         .await
@@ -163,17 +163,6 @@ component::define! {
     /// Version of a Minecraft Java server listing.
     #[derive(Debug, Clone, Serialize, Deserialize, Validate, utoipa::ToSchema)]
     pub struct JavaServerVersion {}
-
-    /// Listing for a Minecraft Bedrock server.
-    #[derive(Debug, Clone, Serialize, Deserialize, Validate, utoipa::ToSchema)]
-    pub struct BedrockServerProject {
-        #[base()]
-        #[edit(serde(default))]
-        #[create(required)]
-        /// Address (IP or domain name) of the Bedrock server, excluding port.
-        #[validate(length(max = 255))]
-        pub address: String,
-    }
 }
 
 impl ProjectComponent for ModProject {
@@ -209,6 +198,28 @@ pub struct JavaServerProject {
     /// What game content this server is using.
     #[serde(default)]
     pub content: ServerContent,
+    /// Proof that the project's team controls `address`. Only set by
+    /// Labrinth, and cleared whenever the address changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<ServerVerification>,
+}
+
+/// How a server listing's team proved that they control its address.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerVerificationMethod {
+    /// The verification token was in the server's MOTD.
+    Motd,
+    /// The verification token was in a DNS TXT record for the address.
+    Dns,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ServerVerification {
+    pub method: ServerVerificationMethod,
+    pub verified_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate, utoipa::ToSchema)]
@@ -227,6 +238,9 @@ pub struct JavaServerProjectQuery {
     pub ping: Option<JavaServerPing>,
     pub verified_plays_2w: Option<u64>,
     pub verified_plays_4w: Option<u64>,
+    /// Whether the project's team has proved that they control `address`.
+    pub verified: bool,
+    pub verification: Option<ServerVerification>,
 }
 
 impl Component for JavaServerProject {
@@ -262,6 +276,8 @@ impl ComponentQuery for JavaServerProjectQuery {
     ) -> Result<Self> {
         let analytics = context.minecraft_server_analytics.get(&project_id);
         Ok(Self {
+            verified: serial.verification.is_some(),
+            verification: serial.verification,
             address: serial.address,
             content: match serial.content {
                 ServerContent::Vanilla {
@@ -310,11 +326,15 @@ impl ComponentEdit for JavaServerProjectEdit {
         Ok(JavaServerProject {
             address: self.address.wrap_err("missing `address`")?,
             content: self.content.unwrap_or_default(),
+            verification: None,
         })
     }
 
     async fn apply_to(self, component: &mut Self::Component) -> Result<()> {
         if let Some(address) = self.address {
+            if address != component.address {
+                component.verification = None;
+            }
             component.address = address;
         }
         if let Some(content) = self.content {
@@ -324,6 +344,82 @@ impl ComponentEdit for JavaServerProjectEdit {
     }
 }
 
+/// Listing for a Minecraft Bedrock server.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, utoipa::ToSchema)]
+pub struct BedrockServerProject {
+    /// Address (IP or domain name) of the Bedrock server, excluding port.
+    #[validate(length(max = 255))]
+    pub address: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, utoipa::ToSchema)]
+pub struct BedrockServerProjectEdit {
+    #[validate(length(max = 255))]
+    #[serde(default)]
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct BedrockServerProjectQuery {
+    pub address: String,
+    /// Bedrock pings are recorded in the same shape as Java pings, with the
+    /// MOTD as the description.
+    pub ping: Option<JavaServerPing>,
+}
+
+impl Component for BedrockServerProject {
+    type EntityId = ProjectId;
+    type Query = BedrockServerProjectQuery;
+    type Edit = BedrockServerProjectEdit;
+}
+
+impl ComponentQuery for BedrockServerProjectQuery {
+    type Component = BedrockServerProject;
+    type Requirements = ProjectQueryRequirements;
+    type Context = ProjectQueryContext;
+
+    fn collect_requirements(
+        _serial: &Self::Component,
+        project_id: ProjectId,
+        requirements: &mut ProjectQueryRequirements,
+    ) {
+        requirements
+            .minecraft_bedrock_server_pings
+            .insert(project_id);
+    }
+
+    fn populate(
+        serial: Self::Component,
+        project_id: ProjectId,
+        context: &ProjectQueryContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            address: serial.address,
+            ping: context
+                .minecraft_bedrock_server_pings
+                .get(&project_id)
+                .cloned(),
+        })
+    }
+}
+
+impl ComponentEdit for BedrockServerProjectEdit {
+    type Component = BedrockServerProject;
+
+    fn create(self) -> Result<Self::Component> {
+        Ok(BedrockServerProject {
+            address: self.address.wrap_err("missing `address`")?,
+        })
+    }
+
+    async fn apply_to(self, component: &mut Self::Component) -> Result<()> {
+        if let Some(address) = self.address {
+            component.address = address;
+        }
+        Ok(())
+    }
+}
+
 /// What game content a [`JavaServerProject`] is using.
 #[derive(Debug, Clone)]
 #[serde_binhum(schema)]
@@ -401,7 +497,8 @@ pub enum ServerRegion {
     Russia,
 }
 
-/// Recorded ping attempt that Labrinth made to a Minecraft Java server project.
+/// Recorded ping attempt that Labrinth made to a Minecraft Java or Bedrock
+/// server project.
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct JavaServerPing {
     /// When the ping was performed.
@@ -412,7 +509,7 @@ pub struct JavaServerPing {
     pub data: Option<JavaServerPingData>,
 }
 
-/// Ping response data for a Minecraft Java server.
+/// Ping response data for a Minecraft Java or Bedrock server.
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct JavaServerPingData {
     /// How long it took for the Labrinth worker to ping the server.
@@ -177,6 +177,7 @@ component::relations! {
 pub struct ProjectQueryRequirements {
     pub partial_versions: HashSet<VersionId>,
     pub minecraft_java_server_pings: HashSet<ProjectId>,
+    pub minecraft_bedrock_server_pings: HashSet<ProjectId>,
     pub minecraft_server_analytics: HashSet<ProjectId>,
 }
 
@@ -184,6 +185,8 @@ pub struct ProjectQueryContext {
     pub partial_versions: HashMap<VersionId, PartialVersion>,
     pub minecraft_java_server_pings:
         HashMap<ProjectId, minecraft::JavaServerPing>,
+    pub minecraft_bedrock_server_pings:
+        HashMap<ProjectId, minecraft::JavaServerPing>,
     pub minecraft_server_analytics:
         HashMap<ProjectId, MinecraftServerAnalytics>,
 }
@@ -207,6 +210,7 @@ pub async fn fetch_query_context(
     let ProjectQueryRequirements {
         partial_versions,
         minecraft_java_server_pings,
+        minecraft_bedrock_server_pings,
         minecraft_server_analytics,
     } = requirements;
 
@@ -271,6 +275,33 @@ pub async fn fetch_query_context(
             .collect::<HashMap<_, _>>()
     };
 
+    let minecraft_bedrock_server_pings = minecraft_bedrock_server_pings
+        .into_iter()
+        .collect::<Vec<_>>();
+    let minecraft_bedrock_server_pings = if minecraft_bedrock_server_pings
+        .is_empty()
+    {
+        HashMap::new()
+    } else {
+        let ping_keys = minecraft_bedrock_server_pings
+            .iter()
+            .map(|project_id| {
+                redis
+                    .key()
+                    .entity(server_ping::BEDROCK_REDIS_NAMESPACE, project_id)
+            })
+            .collect::<Vec<_>>();
+        redis
+            .get_many_deserialized::<minecraft::JavaServerPing>(&ping_keys)
+            .await?
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, ping)| {
+                ping.map(|ping| (minecraft_bedrock_server_pings[idx], ping))
+            })
+            .collect::<HashMap<_, _>>()
+    };
+
     let minecraft_server_analytics =
         minecraft_server_analytics.into_iter().collect::<Vec<_>>();
 
@@ -297,6 +328,7 @@ pub async fn fetch_query_context(
     Ok(ProjectQueryContext {
         partial_versions,
         minecraft_java_server_pings,
+        minecraft_bedrock_server_pings,
         minecraft_server_analytics,
     })
 }
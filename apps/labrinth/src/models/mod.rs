@@ -4,19 +4,26 @@ pub mod v2;
 pub mod v3;
 
 pub use v3::analytics;
+pub use v3::audit_log;
 pub use v3::billing;
 pub use v3::collections;
 pub use v3::disclosures;
 pub use v3::ids;
 pub use v3::images;
+pub use v3::moderation_macros;
 pub use v3::moderation_notes;
 pub use v3::notifications;
 pub use v3::oauth_clients;
+pub use v3::organization_roles;
 pub use v3::organizations;
 pub use v3::pack;
 pub use v3::pats;
 pub use v3::payouts;
+pub use v3::pgp_keys;
+pub use v3::project_transfers;
+pub use v3::project_webhooks;
 pub use v3::projects;
+pub use v3::realtime;
 pub use v3::reports;
 pub use v3::sessions;
 pub use v3::teams;
@@ -4,8 +4,8 @@ use crate::models::ids::{ThreadMessageId, VersionId};
 use crate::models::v3::billing::PriceDuration;
 use crate::models::{
     ids::{
-        NotificationId, OrganizationId, ProjectId, ReportId, TeamId, ThreadId,
-        UserSubscriptionId,
+        NotificationId, OrganizationId, ProjectId, ProjectTransferId, ReportId,
+        TeamId, ThreadId, UserSubscriptionId,
     },
     notifications::{Notification, NotificationAction, NotificationBody},
     projects::ProjectStatus,
@@ -157,6 +157,13 @@ pub enum LegacyNotificationBody {
         new_owner_user_id: Option<UserId>,
         new_owner_organization_id: Option<OrganizationId>,
     },
+    ProjectTransferRequested {
+        transfer_id: ProjectTransferId,
+        project_id: ProjectId,
+        requested_by: UserId,
+        new_owner_user_id: Option<UserId>,
+        new_owner_organization_id: Option<OrganizationId>,
+    },
     PayoutAvailable {
         amount: u64,
         date_available: DateTime<Utc>,
@@ -215,6 +222,9 @@ impl LegacyNotification {
             NotificationBody::ProjectTransferred { .. } => {
                 Some("project_transferred".to_string())
             }
+            NotificationBody::ProjectTransferRequested { .. } => {
+                Some("project_transfer_requested".to_string())
+            }
             NotificationBody::ResetPassword { .. } => {
                 Some("reset_password".to_string())
             }
@@ -369,6 +379,19 @@ impl LegacyNotification {
                 new_owner_user_id,
                 new_owner_organization_id,
             },
+            NotificationBody::ProjectTransferRequested {
+                transfer_id,
+                project_id,
+                requested_by,
+                new_owner_user_id,
+                new_owner_organization_id,
+            } => LegacyNotificationBody::ProjectTransferRequested {
+                transfer_id,
+                project_id,
+                requested_by,
+                new_owner_user_id,
+                new_owner_organization_id,
+            },
             NotificationBody::PayoutAvailable {
                 amount,
                 date_available,
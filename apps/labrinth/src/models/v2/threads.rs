@@ -136,6 +136,14 @@ impl From<crate::models::v3::threads::MessageBody> for LegacyMessageBody {
             crate::models::v3::threads::MessageBody::ThreadReopen => {
                 LegacyMessageBody::ThreadReopen
             }
+            crate::models::v3::threads::MessageBody::OwnershipTransferred {
+                ..
+            } => LegacyMessageBody::Text {
+                body: "(legacy) Transferred project ownership".into(),
+                private: false,
+                replying_to: None,
+                associated_images: Vec::new(),
+            },
             crate::models::v3::threads::MessageBody::Deleted { private } => {
                 LegacyMessageBody::Deleted { private }
             }
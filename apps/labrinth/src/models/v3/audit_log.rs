@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+
+use ariadne::ids::UserId;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::database::models::audit_log_item::DBAuditLogEntry;
+use crate::database::models::organization_role_item::DBOrganizationRole;
+use crate::database::models::{DBOrganization, DBTeamMember};
+use crate::models::ids::{
+    OrganizationId, OrganizationRoleId, ProjectId, VersionId,
+};
+use crate::models::teams::{OrganizationPermissions, ProjectPermissions};
+
+/// A change made to a project or organization, and who made it.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub actor_id: UserId,
+    /// Set if the change was made to a project, or something belonging to it.
+    pub project_id: Option<ProjectId>,
+    /// Set if the change was made to an organization, or to a project which
+    /// belonged to it at the time.
+    pub organization_id: Option<OrganizationId>,
+    pub action: AuditLogAction,
+    pub target: AuditLogTarget,
+    /// The changed fields of the target before the change, if it existed.
+    #[schema(value_type = Option<Object>)]
+    pub before: Option<serde_json::Value>,
+    /// The changed fields of the target after the change, if it still exists.
+    #[schema(value_type = Option<Object>)]
+    pub after: Option<serde_json::Value>,
+    pub created: DateTime<Utc>,
+}
+
+impl From<DBAuditLogEntry> for AuditLogEntry {
+    fn from(entry: DBAuditLogEntry) -> Self {
+        Self {
+            id: entry.id,
+            actor_id: entry.actor_id.into(),
+            project_id: entry.project_id.map(Into::into),
+            organization_id: entry.organization_id.map(Into::into),
+            action: entry.action,
+            target: entry.target,
+            before: entry.before,
+            after: entry.after,
+            created: entry.created,
+        }
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditLogAction {
+    ProjectEdited,
+    ProjectIconEdited,
+    ProjectIconDeleted,
+    ProjectDeleted,
+    ProjectTransferred,
+    GalleryItemAdded,
+    GalleryItemEdited,
+    GalleryItemDeleted,
+    VersionEdited,
+    VersionDeleted,
+    TeamMemberInvited,
+    TeamMemberJoined,
+    TeamMemberEdited,
+    TeamMemberRemoved,
+    TeamOwnershipTransferred,
+    OrganizationCreated,
+    OrganizationEdited,
+    OrganizationDeleted,
+    OrganizationIconEdited,
+    OrganizationIconDeleted,
+    OrganizationProjectAdded,
+    OrganizationProjectRemoved,
+    OrganizationRoleCreated,
+    OrganizationRoleEdited,
+    OrganizationRoleDeleted,
+    Unknown,
+}
+
+impl AuditLogAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ProjectEdited => "project_edited",
+            Self::ProjectIconEdited => "project_icon_edited",
+            Self::ProjectIconDeleted => "project_icon_deleted",
+            Self::ProjectDeleted => "project_deleted",
+            Self::ProjectTransferred => "project_transferred",
+            Self::GalleryItemAdded => "gallery_item_added",
+            Self::GalleryItemEdited => "gallery_item_edited",
+            Self::GalleryItemDeleted => "gallery_item_deleted",
+            Self::VersionEdited => "version_edited",
+            Self::VersionDeleted => "version_deleted",
+            Self::TeamMemberInvited => "team_member_invited",
+            Self::TeamMemberJoined => "team_member_joined",
+            Self::TeamMemberEdited => "team_member_edited",
+            Self::TeamMemberRemoved => "team_member_removed",
+            Self::TeamOwnershipTransferred => "team_ownership_transferred",
+            Self::OrganizationCreated => "organization_created",
+            Self::OrganizationEdited => "organization_edited",
+            Self::OrganizationDeleted => "organization_deleted",
+            Self::OrganizationIconEdited => "organization_icon_edited",
+            Self::OrganizationIconDeleted => "organization_icon_deleted",
+            Self::OrganizationProjectAdded => "organization_project_added",
+            Self::OrganizationProjectRemoved => "organization_project_removed",
+            Self::OrganizationRoleCreated => "organization_role_created",
+            Self::OrganizationRoleEdited => "organization_role_edited",
+            Self::OrganizationRoleDeleted => "organization_role_deleted",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    pub fn from_string(string: &str) -> Self {
+        match string {
+            "project_edited" => Self::ProjectEdited,
+            "project_icon_edited" => Self::ProjectIconEdited,
+            "project_icon_deleted" => Self::ProjectIconDeleted,
+            "project_deleted" => Self::ProjectDeleted,
+            "project_transferred" => Self::ProjectTransferred,
+            "gallery_item_added" => Self::GalleryItemAdded,
+            "gallery_item_edited" => Self::GalleryItemEdited,
+            "gallery_item_deleted" => Self::GalleryItemDeleted,
+            "version_edited" => Self::VersionEdited,
+            "version_deleted" => Self::VersionDeleted,
+            "team_member_invited" => Self::TeamMemberInvited,
+            "team_member_joined" => Self::TeamMemberJoined,
+            "team_member_edited" => Self::TeamMemberEdited,
+            "team_member_removed" => Self::TeamMemberRemoved,
+            "team_ownership_transferred" => Self::TeamOwnershipTransferred,
+            "organization_created" => Self::OrganizationCreated,
+            "organization_edited" => Self::OrganizationEdited,
+            "organization_deleted" => Self::OrganizationDeleted,
+            "organization_icon_edited" => Self::OrganizationIconEdited,
+            "organization_icon_deleted" => Self::OrganizationIconDeleted,
+            "organization_project_added" => Self::OrganizationProjectAdded,
+            "organization_project_removed" => Self::OrganizationProjectRemoved,
+            "organization_role_created" => Self::OrganizationRoleCreated,
+            "organization_role_edited" => Self::OrganizationRoleEdited,
+            "organization_role_deleted" => Self::OrganizationRoleDeleted,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// What a change was made to.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditLogTarget {
+    Project { project_id: ProjectId },
+    Version { version_id: VersionId },
+    GalleryItem { url: String },
+    TeamMember { user_id: UserId },
+    Organization { organization_id: OrganizationId },
+    OrganizationRole { role_id: OrganizationRoleId },
+}
+
+/// The audited fields of a team member.
+#[derive(Serialize)]
+pub struct AuditedTeamMember {
+    pub role: String,
+    pub is_owner: bool,
+    pub accepted: bool,
+    pub permissions: ProjectPermissions,
+    pub organization_permissions: Option<OrganizationPermissions>,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub payouts_split: Decimal,
+    pub ordering: i64,
+    pub role_id: Option<OrganizationRoleId>,
+}
+
+impl From<&DBTeamMember> for AuditedTeamMember {
+    fn from(member: &DBTeamMember) -> Self {
+        Self {
+            role: member.role.clone(),
+            is_owner: member.is_owner,
+            accepted: member.accepted,
+            permissions: member.permissions,
+            organization_permissions: member.organization_permissions,
+            payouts_split: member.payouts_split,
+            ordering: member.ordering,
+            role_id: member.role_id.map(Into::into),
+        }
+    }
+}
+
+/// The audited fields of an organization.
+#[derive(Serialize)]
+pub struct AuditedOrganization {
+    pub slug: String,
+    pub name: String,
+    pub description: String,
+}
+
+impl From<&DBOrganization> for AuditedOrganization {
+    fn from(organization: &DBOrganization) -> Self {
+        Self {
+            slug: organization.slug.clone(),
+            name: organization.name.clone(),
+            description: organization.description.clone(),
+        }
+    }
+}
+
+/// The audited fields of an organization role.
+#[derive(Serialize)]
+pub struct AuditedOrganizationRole {
+    pub name: String,
+    pub permissions: ProjectPermissions,
+    pub organization_permissions: OrganizationPermissions,
+    pub project_overrides: HashMap<ProjectId, ProjectPermissions>,
+}
+
+impl From<&DBOrganizationRole> for AuditedOrganizationRole {
+    fn from(role: &DBOrganizationRole) -> Self {
+        Self {
+            name: role.name.clone(),
+            permissions: role.permissions,
+            organization_permissions: role.organization_permissions,
+            project_overrides: role
+                .project_overrides
+                .iter()
+                .map(|(project_id, permissions)| {
+                    ((*project_id).into(), *permissions)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Strips the fields which are the same in both `before` and `after`.
+///
+/// Returns `None` if nothing changed.
+pub fn diff(
+    before: &impl Serialize,
+    after: &impl Serialize,
+) -> Option<(serde_json::Value, serde_json::Value)> {
+    let before =
+        serde_json::to_value(before).expect("serialization shouldn't fail");
+    let after =
+        serde_json::to_value(after).expect("serialization shouldn't fail");
+
+    let (serde_json::Value::Object(before), serde_json::Value::Object(after)) =
+        (&before, &after)
+    else {
+        return (before != after).then_some((before, after));
+    };
+
+    fn changed(
+        key: &str,
+        value: &serde_json::Value,
+        other: &serde_json::Map<String, serde_json::Value>,
+    ) -> bool {
+        other.get(key).unwrap_or(&serde_json::Value::Null) != value
+    }
+
+    let before_changed = before
+        .iter()
+        .filter(|(key, value)| changed(key, value, after))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect::<serde_json::Map<_, _>>();
+    let after_changed = after
+        .iter()
+        .filter(|(key, value)| changed(key, value, before))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect::<serde_json::Map<_, _>>();
+
+    if before_changed.is_empty() && after_changed.is_empty() {
+        None
+    } else {
+        Some((before_changed.into(), after_changed.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(
+            diff(
+                &json!({ "name": "a", "summary": "b", "color": null }),
+                &json!({ "name": "a", "summary": "c", "color": 1 }),
+            ),
+            Some((
+                json!({ "summary": "b", "color": null }),
+                json!({ "summary": "c", "color": 1 }),
+            ))
+        );
+        assert_eq!(
+            diff(&json!({ "name": "a" }), &json!({ "name": "a" })),
+            None
+        );
+        assert_eq!(
+            diff(&json!({}), &json!({ "icon_url": "x" })),
+            Some((json!({}), json!({ "icon_url": "x" })))
+        );
+    }
+}
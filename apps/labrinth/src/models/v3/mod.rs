@@ -1,20 +1,27 @@
 pub mod affiliate_code;
 pub mod analytics;
 pub mod analytics_event;
+pub mod audit_log;
 pub mod billing;
 pub mod collections;
 pub mod disclosures;
 pub mod ids;
 pub mod images;
+pub mod moderation_macros;
 pub mod moderation_notes;
 pub mod notifications;
 pub mod oauth_clients;
+pub mod organization_roles;
 pub mod organizations;
 pub mod pack;
 pub mod pats;
 pub mod payouts;
+pub mod pgp_keys;
 pub mod preferences;
+pub mod project_transfers;
+pub mod project_webhooks;
 pub mod projects;
+pub mod realtime;
 pub mod reports;
 pub mod sessions;
 pub mod teams;
@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+
+use ariadne::ids::UserId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::database::models::moderation_macro_item::DBModerationMacro;
+use crate::models::ids::ModerationMacroId;
+use crate::models::projects::ProjectStatus;
+
+/// Variables which are filled in from the project a macro is applied to,
+/// rather than given by the moderator applying it.
+pub const PROJECT_VARIABLES: [&str; 4] =
+    ["project_id", "project_name", "project_slug", "project_url"];
+
+/// A canned moderator response. Applying it to a project posts `message`
+/// in the project's thread, sets the project's status to `status` and adds
+/// `note` to its owner's moderation note.
+///
+/// `message` and `note` are templates, in which `{variable}` is replaced by
+/// the value of `variable` and `{{` and `}}` by literal braces.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct ModerationMacro {
+    pub id: ModerationMacroId,
+    pub name: String,
+    pub message: String,
+    /// Whether the thread message is only visible to moderators.
+    pub private: bool,
+    pub status: Option<ProjectStatus>,
+    pub note: Option<String>,
+    /// Variables which must be given when applying the macro.
+    pub variables: Vec<String>,
+    pub created: DateTime<Utc>,
+    pub last_modified: DateTime<Utc>,
+    pub last_modified_by: Option<UserId>,
+    /// How many times the macro has been applied.
+    pub uses: i64,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+impl From<DBModerationMacro> for ModerationMacro {
+    fn from(data: DBModerationMacro) -> Self {
+        let variables = macro_variables(&data.message, data.note.as_deref())
+            .unwrap_or_default();
+
+        Self {
+            id: data.id.into(),
+            name: data.name,
+            message: data.message,
+            private: data.private,
+            status: data.status,
+            note: data.note,
+            variables,
+            created: data.created,
+            last_modified: data.last_modified,
+            last_modified_by: data.last_modified_by.map(Into::into),
+            uses: data.uses,
+            last_used: data.last_used,
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum TemplateError {
+    #[error("unclosed `{{` at position {0}")]
+    Unclosed(usize),
+    #[error("unmatched `}}` at position {0}")]
+    Unmatched(usize),
+    #[error("invalid variable name `{0}`")]
+    InvalidName(String),
+    #[error("missing values for variables: {}", .0.join(", "))]
+    Missing(Vec<String>),
+}
+
+enum Segment<'a> {
+    Text(&'a str),
+    Variable(&'a str),
+}
+
+fn parse_template(template: &str) -> Result<Vec<Segment<'_>>, TemplateError> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut chars = template.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' | '}' if chars.peek().is_some_and(|&(_, next)| next == c) => {
+                segments.push(Segment::Text(&template[start..=i]));
+                chars.next();
+                start = i + 2;
+            }
+            '{' => {
+                let end = template[i..]
+                    .find('}')
+                    .map(|end| i + end)
+                    .ok_or(TemplateError::Unclosed(i))?;
+                let name = &template[i + 1..end];
+                if name.is_empty()
+                    || !name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_')
+                {
+                    return Err(TemplateError::InvalidName(name.to_string()));
+                }
+
+                segments.push(Segment::Text(&template[start..i]));
+                segments.push(Segment::Variable(name));
+                while chars.next_if(|&(j, _)| j <= end).is_some() {}
+                start = end + 1;
+            }
+            '}' => return Err(TemplateError::Unmatched(i)),
+            _ => {}
+        }
+    }
+    segments.push(Segment::Text(&template[start..]));
+
+    Ok(segments)
+}
+
+/// Lists the variables used in a template, in order of first use.
+pub fn template_variables(
+    template: &str,
+) -> Result<Vec<String>, TemplateError> {
+    let mut variables = Vec::<String>::new();
+    for segment in parse_template(template)? {
+        if let Segment::Variable(name) = segment
+            && !variables.iter().any(|x| x == name)
+        {
+            variables.push(name.to_string());
+        }
+    }
+    Ok(variables)
+}
+
+/// Lists the variables used in a macro's templates which aren't
+/// [`PROJECT_VARIABLES`].
+pub fn macro_variables(
+    message: &str,
+    note: Option<&str>,
+) -> Result<Vec<String>, TemplateError> {
+    let mut variables = template_variables(message)?;
+    if let Some(note) = note {
+        for name in template_variables(note)? {
+            if !variables.contains(&name) {
+                variables.push(name);
+            }
+        }
+    }
+    variables.retain(|x| !PROJECT_VARIABLES.contains(&x.as_str()));
+    Ok(variables)
+}
+
+/// Fills in a template's variables.
+pub fn render_template(
+    template: &str,
+    values: &HashMap<String, String>,
+) -> Result<String, TemplateError> {
+    let segments = parse_template(template)?;
+
+    let mut missing = Vec::new();
+    let mut output = String::with_capacity(template.len());
+    for segment in segments {
+        match segment {
+            Segment::Text(text) => output.push_str(text),
+            Segment::Variable(name) => match values.get(name) {
+                Some(value) => output.push_str(value),
+                None => {
+                    if !missing.iter().any(|x| x == name) {
+                        missing.push(name.to_string());
+                    }
+                }
+            },
+        }
+    }
+
+    if !missing.is_empty() {
+        return Err(TemplateError::Missing(missing));
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn renders_variables_and_escapes() {
+        let rendered = render_template(
+            "{project_name} is missing {license}. {{See}} {rule_link}",
+            &values(&[
+                ("project_name", "Sodium"),
+                ("license", "a license"),
+                ("rule_link", "https://modrinth.com/legal/rules"),
+            ]),
+        );
+        assert_eq!(
+            rendered.as_deref(),
+            Ok(
+                "Sodium is missing a license. {See} https://modrinth.com/legal/rules"
+            )
+        );
+    }
+
+    #[test]
+    fn reports_missing_variables_once() {
+        assert_eq!(
+            render_template("{a} {b} {a}", &values(&[])),
+            Err(TemplateError::Missing(vec!["a".into(), "b".into()]))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_templates() {
+        assert_eq!(
+            template_variables("text {unclosed"),
+            Err(TemplateError::Unclosed(5))
+        );
+        assert_eq!(
+            template_variables("a } b"),
+            Err(TemplateError::Unmatched(2))
+        );
+        assert_eq!(
+            template_variables("{not a name}"),
+            Err(TemplateError::InvalidName("not a name".into()))
+        );
+    }
+
+    #[test]
+    fn excludes_project_variables() {
+        assert_eq!(
+            macro_variables(
+                "{project_name}: {reason}",
+                Some("{reason} ({project_url}) {extra}")
+            ),
+            Ok(vec!["reason".into(), "extra".into()])
+        );
+    }
+}
@@ -60,6 +60,7 @@ pub enum NotificationType {
     ProjectStatusApproved,
     ProjectStatusNeutral,
     ProjectTransferred,
+    ProjectTransferRequested,
     PayoutAvailable,
     DiscordRoleCreatorClub,
     Custom,
@@ -102,6 +103,9 @@ impl NotificationType {
             NotificationType::Custom => "custom",
             NotificationType::ProjectStatusNeutral => "project_status_neutral",
             NotificationType::ProjectTransferred => "project_transferred",
+            NotificationType::ProjectTransferRequested => {
+                "project_transfer_requested"
+            }
             NotificationType::DiscordRoleCreatorClub => {
                 "discord_role_creator_club"
             }
@@ -142,6 +146,9 @@ impl NotificationType {
             }
             "project_status_neutral" => NotificationType::ProjectStatusNeutral,
             "project_transferred" => NotificationType::ProjectTransferred,
+            "project_transfer_requested" => {
+                NotificationType::ProjectTransferRequested
+            }
             "discord_role_creator_club" => {
                 NotificationType::DiscordRoleCreatorClub
             }
@@ -224,6 +231,13 @@ pub enum NotificationBody {
         new_owner_user_id: Option<UserId>,
         new_owner_organization_id: Option<OrganizationId>,
     },
+    ProjectTransferRequested {
+        transfer_id: ProjectTransferId,
+        project_id: ProjectId,
+        requested_by: UserId,
+        new_owner_user_id: Option<UserId>,
+        new_owner_organization_id: Option<OrganizationId>,
+    },
     LegacyMarkdown {
         notification_type: Option<String>,
         name: String,
@@ -327,6 +341,9 @@ impl NotificationBody {
             NotificationBody::ProjectTransferred { .. } => {
                 NotificationType::ProjectTransferred
             }
+            NotificationBody::ProjectTransferRequested { .. } => {
+                NotificationType::ProjectTransferRequested
+            }
             NotificationBody::LegacyMarkdown { .. } => {
                 NotificationType::LegacyMarkdown
             }
@@ -580,6 +597,39 @@ impl From<DBNotification> for Notification {
                     "#".to_string(),
                     vec![],
                 ),
+                NotificationBody::ProjectTransferRequested {
+                    transfer_id,
+                    project_id,
+                    new_owner_organization_id,
+                    ..
+                } => (
+                    "You have been offered a project!".to_string(),
+                    if new_owner_organization_id.is_some() {
+                        "A project's owner wants to transfer it to your organization".to_string()
+                    } else {
+                        "A project's owner wants to transfer it to you"
+                            .to_string()
+                    },
+                    format!("/project/{project_id}"),
+                    vec![
+                        NotificationAction {
+                            name: "Accept".to_string(),
+                            action_route: (
+                                "POST".to_string(),
+                                format!("project_transfer/{transfer_id}/accept"),
+                            ),
+                        },
+                        NotificationAction {
+                            name: "Decline".to_string(),
+                            action_route: (
+                                "POST".to_string(),
+                                format!(
+                                    "project_transfer/{transfer_id}/decline"
+                                ),
+                            ),
+                        },
+                    ],
+                ),
                 // Don't expose the `flow` field
                 NotificationBody::ResetPassword { .. } => (
                     "Password reset requested".to_string(),
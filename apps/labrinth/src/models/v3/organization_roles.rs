@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::database::models::organization_role_item::DBOrganizationRole;
+use crate::models::ids::{OrganizationId, OrganizationRoleId, ProjectId};
+use crate::models::teams::{OrganizationPermissions, ProjectPermissions};
+
+/// A named set of permissions which members of an organization can be
+/// assigned, instead of having their permissions set individually.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct OrganizationRole {
+    pub id: OrganizationRoleId,
+    pub organization_id: OrganizationId,
+    pub name: String,
+    /// The default permissions of the role's members in any project of the
+    /// organization.
+    pub permissions: ProjectPermissions,
+    pub organization_permissions: OrganizationPermissions,
+    /// The permissions of the role's members in specific projects of the
+    /// organization, replacing `permissions`.
+    #[schema(value_type = Object)]
+    pub project_overrides: HashMap<ProjectId, ProjectPermissions>,
+    pub created: DateTime<Utc>,
+}
+
+impl From<DBOrganizationRole> for OrganizationRole {
+    fn from(role: DBOrganizationRole) -> Self {
+        Self {
+            id: role.id.into(),
+            organization_id: role.organization_id.into(),
+            name: role.name,
+            permissions: role.permissions,
+            organization_permissions: role.organization_permissions,
+            project_overrides: role
+                .project_overrides
+                .into_iter()
+                .map(|(project_id, permissions)| {
+                    (project_id.into(), permissions)
+                })
+                .collect(),
+            created: role.created,
+        }
+    }
+}
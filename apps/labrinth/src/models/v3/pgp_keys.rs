@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::database::models::pgp_key_item::DBPgpKey;
+
+/// An OpenPGP public key registered to a user or organization. Detached
+/// signatures on their projects' version files are verified against it.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PgpKey {
+    /// Uppercase hex fingerprint of the primary key.
+    pub fingerprint: String,
+    /// The ASCII-armored public key.
+    pub public_key: String,
+    pub created: DateTime<Utc>,
+}
+
+impl From<DBPgpKey> for PgpKey {
+    fn from(key: DBPgpKey) -> Self {
+        Self {
+            fingerprint: key.fingerprint,
+            public_key: key.public_key,
+            created: key.created,
+        }
+    }
+}
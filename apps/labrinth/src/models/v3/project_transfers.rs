@@ -0,0 +1,93 @@
+use ariadne::ids::UserId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::database::models::project_transfer_item::DBProjectTransfer;
+use crate::models::ids::{OrganizationId, ProjectId, ProjectTransferId};
+
+/// A request to move a project to another user or organization, which takes
+/// effect once the recipient accepts it.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct ProjectTransfer {
+    pub id: ProjectTransferId,
+    pub project_id: ProjectId,
+    pub requested_by: UserId,
+    /// Set if the project was owned by a user when the transfer was
+    /// requested.
+    pub previous_owner_user_id: Option<UserId>,
+    /// Set if the project was owned by an organization when the transfer was
+    /// requested.
+    pub previous_owner_organization_id: Option<OrganizationId>,
+    /// Set if the project is being transferred to a user.
+    pub new_owner_user_id: Option<UserId>,
+    /// Set if the project is being transferred to an organization.
+    pub new_owner_organization_id: Option<OrganizationId>,
+    /// Whether payouts the previous owner, or the owner of the previous
+    /// owning organization, has earned from the project, but which aren't
+    /// available yet, move to the new owner.
+    pub transfer_pending_payouts: bool,
+    /// Whether the previous owner's payouts split moves to the new owner.
+    pub transfer_payouts_split: bool,
+    pub status: ProjectTransferStatus,
+    pub created: DateTime<Utc>,
+    pub resolved: Option<DateTime<Utc>>,
+    pub resolved_by: Option<UserId>,
+}
+
+impl From<DBProjectTransfer> for ProjectTransfer {
+    fn from(transfer: DBProjectTransfer) -> Self {
+        Self {
+            id: transfer.id.into(),
+            project_id: transfer.project_id.into(),
+            requested_by: transfer.requested_by.into(),
+            previous_owner_user_id: transfer
+                .previous_owner_user_id
+                .map(Into::into),
+            previous_owner_organization_id: transfer
+                .previous_owner_organization_id
+                .map(Into::into),
+            new_owner_user_id: transfer.new_owner_user_id.map(Into::into),
+            new_owner_organization_id: transfer
+                .new_owner_organization_id
+                .map(Into::into),
+            transfer_pending_payouts: transfer.transfer_pending_payouts,
+            transfer_payouts_split: transfer.transfer_payouts_split,
+            status: transfer.status,
+            created: transfer.created,
+            resolved: transfer.resolved,
+            resolved_by: transfer.resolved_by.map(Into::into),
+        }
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectTransferStatus {
+    Pending,
+    Accepted,
+    Declined,
+    Cancelled,
+}
+
+impl ProjectTransferStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Accepted => "accepted",
+            Self::Declined => "declined",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn from_string(string: &str) -> Self {
+        match string {
+            "accepted" => Self::Accepted,
+            "declined" => Self::Declined,
+            "cancelled" => Self::Cancelled,
+            _ => Self::Pending,
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::database::models::project_webhook_item::{
+    DBProjectWebhook, DBProjectWebhookDelivery,
+};
+use crate::models::ids::{
+    OrganizationId, ProjectId, ProjectWebhookId, ReportId, ThreadId,
+    ThreadMessageId, VersionId,
+};
+use crate::models::projects::ProjectStatus;
+
+/// An endpoint subscribed to events of a project, or of all projects in an
+/// organization.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct ProjectWebhook {
+    pub id: ProjectWebhookId,
+    /// Set if the webhook is subscribed to a single project.
+    pub project_id: Option<ProjectId>,
+    /// Set if the webhook is subscribed to all of an organization's projects.
+    pub organization_id: Option<OrganizationId>,
+    pub url: String,
+    pub events: Vec<ProjectWebhookEventType>,
+    pub created: DateTime<Utc>,
+    /// The key deliveries are signed with. Only returned when the webhook is
+    /// created.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+impl From<DBProjectWebhook> for ProjectWebhook {
+    fn from(webhook: DBProjectWebhook) -> Self {
+        Self {
+            id: webhook.id.into(),
+            project_id: webhook.project_id.map(Into::into),
+            organization_id: webhook.organization_id.map(Into::into),
+            url: webhook.url,
+            events: webhook.events,
+            created: webhook.created,
+            secret: None,
+        }
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectWebhookEventType {
+    VersionPublished,
+    VersionEdited,
+    VersionDeleted,
+    ProjectStatusChanged,
+    ReportCreated,
+    ThreadMessageCreated,
+}
+
+impl ProjectWebhookEventType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::VersionPublished => "version_published",
+            Self::VersionEdited => "version_edited",
+            Self::VersionDeleted => "version_deleted",
+            Self::ProjectStatusChanged => "project_status_changed",
+            Self::ReportCreated => "report_created",
+            Self::ThreadMessageCreated => "thread_message_created",
+        }
+    }
+
+    pub fn from_string(string: &str) -> Option<Self> {
+        Some(match string {
+            "version_published" => Self::VersionPublished,
+            "version_edited" => Self::VersionEdited,
+            "version_deleted" => Self::VersionDeleted,
+            "project_status_changed" => Self::ProjectStatusChanged,
+            "report_created" => Self::ReportCreated,
+            "thread_message_created" => Self::ThreadMessageCreated,
+            _ => return None,
+        })
+    }
+}
+
+/// The body of a webhook delivery.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProjectWebhookEvent {
+    VersionPublished {
+        project_id: ProjectId,
+        version_id: VersionId,
+    },
+    VersionEdited {
+        project_id: ProjectId,
+        version_id: VersionId,
+    },
+    VersionDeleted {
+        project_id: ProjectId,
+        version_id: VersionId,
+    },
+    ProjectStatusChanged {
+        project_id: ProjectId,
+        old_status: ProjectStatus,
+        new_status: ProjectStatus,
+    },
+    ReportCreated {
+        project_id: ProjectId,
+        /// Set if a version of the project was reported.
+        version_id: Option<VersionId>,
+        report_id: ReportId,
+    },
+    ThreadMessageCreated {
+        project_id: ProjectId,
+        thread_id: ThreadId,
+        message_id: ThreadMessageId,
+    },
+}
+
+impl ProjectWebhookEvent {
+    pub fn event_type(&self) -> ProjectWebhookEventType {
+        match self {
+            Self::VersionPublished { .. } => {
+                ProjectWebhookEventType::VersionPublished
+            }
+            Self::VersionEdited { .. } => {
+                ProjectWebhookEventType::VersionEdited
+            }
+            Self::VersionDeleted { .. } => {
+                ProjectWebhookEventType::VersionDeleted
+            }
+            Self::ProjectStatusChanged { .. } => {
+                ProjectWebhookEventType::ProjectStatusChanged
+            }
+            Self::ReportCreated { .. } => {
+                ProjectWebhookEventType::ReportCreated
+            }
+            Self::ThreadMessageCreated { .. } => {
+                ProjectWebhookEventType::ThreadMessageCreated
+            }
+        }
+    }
+
+    pub fn project_id(&self) -> ProjectId {
+        match self {
+            Self::VersionPublished { project_id, .. }
+            | Self::VersionEdited { project_id, .. }
+            | Self::VersionDeleted { project_id, .. }
+            | Self::ProjectStatusChanged { project_id, .. }
+            | Self::ReportCreated { project_id, .. }
+            | Self::ThreadMessageCreated { project_id, .. } => *project_id,
+        }
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectWebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    /// Every attempt failed, and the delivery won't be retried.
+    Failed,
+}
+
+impl ProjectWebhookDeliveryStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Delivered => "delivered",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn from_string(string: &str) -> Self {
+        match string {
+            "delivered" => Self::Delivered,
+            "failed" => Self::Failed,
+            _ => Self::Pending,
+        }
+    }
+}
+
+/// An entry in a webhook's delivery log.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct ProjectWebhookDelivery {
+    pub id: i64,
+    pub event: ProjectWebhookEvent,
+    pub status: ProjectWebhookDeliveryStatus,
+    pub attempt_count: i32,
+    /// When the delivery will next be attempted, if it's pending.
+    pub next_attempt: DateTime<Utc>,
+    /// HTTP status code the endpoint responded with on the latest attempt.
+    pub response_code: Option<i32>,
+    /// Error of the latest attempt, if the endpoint couldn't be reached.
+    pub error: Option<String>,
+    pub created: DateTime<Utc>,
+}
+
+impl From<DBProjectWebhookDelivery> for ProjectWebhookDelivery {
+    fn from(delivery: DBProjectWebhookDelivery) -> Self {
+        Self {
+            id: delivery.id,
+            event: delivery.payload,
+            status: delivery.status,
+            attempt_count: delivery.attempt_count,
+            next_attempt: delivery.next_attempt,
+            response_code: delivery.response_code,
+            error: delivery.error,
+            created: delivery.created,
+        }
+    }
+}
@@ -847,6 +847,7 @@ impl From<VersionQueryResult> for Version {
                     primary: f.primary,
                     size: f.size,
                     file_type: f.file_type,
+                    signature: f.signature,
                 })
                 .collect(),
             dependencies: data
@@ -988,6 +989,10 @@ pub struct VersionFile {
     pub size: u32,
     /// The type of the file
     pub file_type: Option<FileType>,
+    /// The result of verifying the file against a detached OpenPGP
+    /// signature uploaded with it, if there is one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<FileSignature>,
 }
 
 /// A dendency which describes what versions are required, break support, or are optional to the
@@ -1131,6 +1136,52 @@ impl FileType {
     }
 }
 
+/// The result of verifying a version file against its detached OpenPGP
+/// signature.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, PartialEq, Eq, utoipa::ToSchema,
+)]
+pub struct FileSignature {
+    pub status: FileSignatureStatus,
+    /// The fingerprint of the registered key which made the signature, in
+    /// uppercase hex. Only set if the signature was verified.
+    pub key_fingerprint: Option<String>,
+}
+
+#[derive(
+    Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, utoipa::ToSchema,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum FileSignatureStatus {
+    /// The signature was made over the file by a key registered to the
+    /// project's owners.
+    Verified,
+    /// The signature doesn't match the file, or wasn't made by any key
+    /// registered to the project's owners.
+    Mismatched,
+    /// The signature file isn't an OpenPGP signature.
+    Invalid,
+}
+
+impl FileSignatureStatus {
+    // These are constant, so this can remove unnecessary allocations (`to_string`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileSignatureStatus::Verified => "verified",
+            FileSignatureStatus::Mismatched => "mismatched",
+            FileSignatureStatus::Invalid => "invalid",
+        }
+    }
+
+    pub fn from_string(string: &str) -> FileSignatureStatus {
+        match string {
+            "verified" => FileSignatureStatus::Verified,
+            "invalid" => FileSignatureStatus::Invalid,
+            _ => FileSignatureStatus::Mismatched,
+        }
+    }
+}
+
 /// A project loader
 #[derive(
     Serialize, Deserialize, Clone, Debug, PartialEq, Eq, utoipa::ToSchema,
@@ -0,0 +1,26 @@
+use crate::models::ids::{ProjectId, ThreadId};
+use crate::models::notifications::Notification;
+use crate::models::projects::ProjectStatus;
+use crate::models::threads::ThreadMessage;
+use serde::Serialize;
+
+/// An event pushed to a user over the realtime event stream.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RealtimeEvent {
+    /// A notification was sent to the user.
+    Notification { notification: Notification },
+    /// A message was posted in a thread the user can read.
+    ///
+    /// Private messages are only sent to moderators.
+    ThreadMessage {
+        thread_id: ThreadId,
+        message: ThreadMessage,
+    },
+    /// The moderation status of one of the user's projects changed.
+    ProjectStatus {
+        project_id: ProjectId,
+        old_status: ProjectStatus,
+        new_status: ProjectStatus,
+    },
+}
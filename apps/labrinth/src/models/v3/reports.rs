@@ -1,3 +1,4 @@
+use crate::database::models::delphi_report_item::DelphiStatus;
 use crate::database::models::report_item::ReportQueryResult as DBReport;
 use crate::models::ids::{ProjectId, ReportId, ThreadId, VersionId};
 use ariadne::ids::UserId;
@@ -20,7 +21,7 @@ pub struct Report {
     pub thread_id: ThreadId,
 }
 
-#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, utoipa::ToSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum ItemType {
     Project,
@@ -31,6 +32,16 @@ pub enum ItemType {
 }
 
 impl ItemType {
+    pub fn from_string(string: &str) -> ItemType {
+        match string {
+            "project" => ItemType::Project,
+            "version" => ItemType::Version,
+            "user" => ItemType::User,
+            "shared-instance" => ItemType::SharedInstance,
+            _ => ItemType::Unknown,
+        }
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             ItemType::Project => "project",
@@ -77,3 +88,40 @@ impl From<DBReport> for Report {
         }
     }
 }
+
+/// Identifies the open reports of the same type about the same item, which
+/// are triaged together as a [`ReportGroup`].
+#[derive(Serialize, Deserialize, Clone, Debug, utoipa::ToSchema)]
+pub struct ReportGroupKey {
+    pub item_type: ItemType,
+    pub item_id: String,
+    pub report_type: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, utoipa::ToSchema)]
+pub struct ReportGroup {
+    #[serde(flatten)]
+    pub key: ReportGroupKey,
+    /// The group's open reports, oldest first.
+    pub reports: Vec<ReportId>,
+    /// How many different users submitted the reports.
+    pub reporters: u32,
+    pub first_reported: DateTime<Utc>,
+    pub last_reported: DateTime<Utc>,
+    /// Downloads of the reported project, or of the reported version's
+    /// project.
+    pub downloads: Option<i32>,
+    /// The most severe status of any Delphi issue found in the reported
+    /// project, or of the reported version's project.
+    pub delphi_status: Option<DelphiStatus>,
+    /// How urgently the group should be looked at, higher first.
+    ///
+    /// Each reporter adds to the priority by how much their reports can be
+    /// trusted: reports from moderators count double, and reports from
+    /// accounts younger than 30 days and 7 days count half and a quarter,
+    /// as they're cheap to make in bulk. This is scaled by the order of
+    /// magnitude of the item's downloads, so that a popular item is
+    /// prioritized without burying reports on smaller ones, and by 3, 1.5
+    /// or 0.75 if its Delphi status is unsafe, pending or safe.
+    pub priority: f64,
+}
@@ -1,5 +1,5 @@
 use crate::bitflags_serde_impl;
-use crate::models::ids::TeamId;
+use crate::models::ids::{OrganizationRoleId, TeamId};
 use crate::models::users::User;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -28,6 +28,8 @@ bitflags::bitflags! {
         const DELETE_PROJECT = 1 << 7;
         const VIEW_ANALYTICS = 1 << 8;
         const VIEW_PAYOUTS = 1 << 9;
+        const MANAGE_WEBHOOKS = 1 << 10;
+        const VIEW_AUDIT_LOG = 1 << 11;
     }
 }
 
@@ -103,6 +105,9 @@ bitflags::bitflags! {
         const REMOVE_PROJECT = 1 << 5;
         const DELETE_ORGANIZATION = 1 << 6;
         const EDIT_MEMBER_DEFAULT_PERMISSIONS = 1 << 7; // Separate from EDIT_MEMBER
+        const MANAGE_WEBHOOKS = 1 << 8;
+        const VIEW_AUDIT_LOG = 1 << 9;
+        const MANAGE_ROLES = 1 << 10;
         const NONE = 0b0;
     }
 }
@@ -186,6 +191,9 @@ pub struct TeamMember {
     pub payouts_split: Option<Decimal>,
     /// Ordering of the member in the list
     pub ordering: i64,
+    /// The organization role assigned to the user in an organization team.
+    /// While set, the role's permissions are used instead of `permissions` and `organization_permissions`.
+    pub role_id: Option<OrganizationRoleId>,
 }
 
 impl TeamMember {
@@ -228,6 +236,11 @@ impl TeamMember {
                 Some(data.payouts_split)
             },
             ordering: data.ordering,
+            role_id: if override_permissions {
+                None
+            } else {
+                data.role_id.map(Into::into)
+            },
         }
     }
 }
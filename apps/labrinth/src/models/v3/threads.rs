@@ -1,6 +1,6 @@
 use crate::database::models::delphi_report_item::DelphiVerdict;
 use crate::models::ids::{
-    ImageId, ProjectId, ReportId, ThreadId, ThreadMessageId,
+    ImageId, OrganizationId, ProjectId, ReportId, ThreadId, ThreadMessageId,
 };
 use crate::models::projects::ProjectStatus;
 use crate::models::users::User;
@@ -54,6 +54,13 @@ pub enum MessageBody {
     TechReviewExitFileDeleted,
     ThreadClosure,
     ThreadReopen,
+    /// The project was transferred to another user or organization.
+    OwnershipTransferred {
+        previous_owner_user_id: Option<UserId>,
+        previous_owner_organization_id: Option<OrganizationId>,
+        new_owner_user_id: Option<UserId>,
+        new_owner_organization_id: Option<OrganizationId>,
+    },
     Deleted {
         #[serde(default)]
         private: bool,
@@ -70,7 +77,8 @@ impl MessageBody {
             | Self::TechReviewExitFileDeleted => true,
             Self::StatusChange { .. }
             | Self::ThreadClosure
-            | Self::ThreadReopen => false,
+            | Self::ThreadReopen
+            | Self::OwnershipTransferred { .. } => false,
         }
     }
 }
@@ -21,6 +21,7 @@ use crate::models::users::Badges;
 use crate::models::users::User;
 use crate::routes::ApiError;
 use crate::routes::internal::billing::payments::*;
+use crate::sync::realtime::broadcast_realtime_notifications;
 use crate::util::anrok;
 use crate::util::archon::ArchonClient;
 use crate::util::archon::{CreateServerRequest, Specs};
@@ -200,6 +201,7 @@ async fn update_tax_amounts(
             })
             .collect::<FuturesUnordered<_>>();
 
+        let mut notification_ids = Vec::new();
         while let Some(result) = futures.next().await {
             processed_charges += 1;
 
@@ -220,7 +222,7 @@ async fn update_tax_amounts(
                                 || "charge has no subscription ID".to_owned(),
                             )?;
 
-                        NotificationBuilder {
+                        let notification_id = NotificationBuilder {
                             body: NotificationBody::TaxNotification {
                                 subscription_id: subscription_id.into(),
                                 new_amount: charge.amount,
@@ -238,6 +240,7 @@ async fn update_tax_amounts(
                         .insert(charge.user_id, &mut txn, redis)
                         .await
                         .wrap_internal_err("inserting database records for `update_tax_amounts`")?;
+                        notification_ids.push(notification_id);
 
                         charge.tax_amount = new_tax_amount;
                     }
@@ -261,6 +264,8 @@ async fn update_tax_amounts(
             .await
             .wrap_internal_err("committing database transaction")?;
 
+        broadcast_realtime_notifications(redis, notification_ids).await;
+
         if processed_charges >= limit {
             break Ok(());
         }
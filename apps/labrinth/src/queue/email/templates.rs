@@ -913,6 +913,7 @@ async fn collect_template_variables(
 
         NotificationBody::ProjectUpdate { .. }
         | NotificationBody::SharedInstanceInvite { .. }
+        | NotificationBody::ProjectTransferRequested { .. }
         | NotificationBody::ModeratorMessage { .. }
         | NotificationBody::LegacyMarkdown { .. }
         | NotificationBody::Unknown => Ok(EmailTemplate::Static(map)),
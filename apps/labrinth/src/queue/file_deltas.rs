@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use bytes::Bytes;
+use eyre::{Result, eyre};
+use tokio::task::spawn_blocking;
+use tracing::{info, warn};
+
+use crate::database::PgPool;
+use crate::database::models::version_file_delta_item::{
+    DBVersionFileDelta, PendingVersionFileDelta,
+};
+use crate::env::ENV;
+use crate::file_hosting::{FileHost, FileHostPublicity};
+use crate::models::ids::{FileId, ProjectId};
+use crate::util::error::Context;
+
+const PENDING_FILE_DELTA_BATCH_SIZE: i64 = 50;
+
+/// Files larger than this aren't diffed, since both versions are held in
+/// memory while the delta is computed.
+const MAX_FILE_DELTA_SOURCE_SIZE: i32 = 256 * 1024 * 1024;
+
+/// Deltas are only stored if they're at most this fraction of the target
+/// file's size, otherwise downloading the full file is just as good.
+const MAX_FILE_DELTA_RATIO: f64 = 0.8;
+
+/// Computes deltas between the primary files of consecutive versions of each
+/// project which don't have one yet.
+pub async fn compute_pending_file_deltas(
+    db: &PgPool,
+    file_host: Arc<dyn FileHost>,
+) -> Result<()> {
+    loop {
+        let pending = DBVersionFileDelta::get_pending(
+            MAX_FILE_DELTA_SOURCE_SIZE,
+            PENDING_FILE_DELTA_BATCH_SIZE,
+            db,
+        )
+        .await
+        .wrap_err("fetching pending file deltas")?;
+
+        if pending.is_empty() {
+            info!("No more file deltas to compute");
+            break;
+        }
+
+        info!("Computing {} file deltas", pending.len());
+
+        for delta in pending {
+            // A pair which fails is recorded without a delta, so that it
+            // can't block the rest of the queue.
+            let stored = match compute_file_delta(&*file_host, &delta).await {
+                Ok(stored) => stored,
+                Err(err) => {
+                    warn!(
+                        "Failed to compute delta from file {:?} to {:?}: {err:?}",
+                        delta.from_file_id, delta.to_file_id
+                    );
+                    None
+                }
+            };
+
+            DBVersionFileDelta::insert(
+                delta.from_file_id,
+                delta.to_file_id,
+                stored.as_ref().map(|(url, _)| url.as_str()),
+                stored.as_ref().map(|(_, size)| *size),
+                db,
+            )
+            .await
+            .wrap_err("recording file delta")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Computes and uploads a single delta, returning its URL and size if it was
+/// worth storing.
+async fn compute_file_delta(
+    file_host: &dyn FileHost,
+    delta: &PendingVersionFileDelta,
+) -> Result<Option<(String, i32)>> {
+    let from = read_version_file(file_host, &delta.from_url).await?;
+    let to = read_version_file(file_host, &delta.to_url).await?;
+
+    let data = spawn_blocking(move || ariadne::delta::diff(&from, &to))
+        .await
+        .wrap_err("computing delta")?;
+
+    if data.len() as f64 > f64::from(delta.to_size) * MAX_FILE_DELTA_RATIO {
+        return Ok(None);
+    }
+
+    let size = i32::try_from(data.len()).wrap_err("delta too large")?;
+    let key = format!(
+        "data/{}/deltas/{}/{}.mrdelta",
+        ProjectId::from(delta.project_id),
+        FileId::from(delta.from_file_id),
+        FileId::from(delta.to_file_id),
+    );
+    file_host
+        .upload_file(
+            "application/octet-stream",
+            &key,
+            FileHostPublicity::Public,
+            Bytes::from(data),
+        )
+        .await
+        .wrap_err_with(|| eyre!("uploading delta to {key}"))?;
+
+    Ok(Some((format!("{}/{key}", ENV.CDN_URL), size)))
+}
+
+async fn read_version_file(
+    file_host: &dyn FileHost,
+    file_url: &str,
+) -> Result<Bytes> {
+    let key = file_url
+        .strip_prefix(&ENV.CDN_URL)
+        .unwrap_or(file_url)
+        .trim_start_matches('/');
+    let key = urlencoding::decode(key).wrap_err("decoding file URL path")?;
+
+    file_host
+        .read_file(&key, FileHostPublicity::Public)
+        .await
+        .wrap_err_with(|| eyre!("reading file from storage at {key}"))
+}
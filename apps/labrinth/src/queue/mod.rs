@@ -1,9 +1,13 @@
 pub mod analytics;
 pub mod billing;
 pub mod email;
+pub mod file_deltas;
 pub mod file_scan;
 pub mod moderation;
 pub mod payouts;
+pub mod project_webhooks;
+pub mod realtime;
 pub mod server_ping;
+pub mod server_verification;
 pub mod session;
 pub mod socket;
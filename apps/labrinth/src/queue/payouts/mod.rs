@@ -1,4 +1,6 @@
+use crate::database::models::DBTeamId;
 use crate::database::models::notification_item::NotificationBuilder;
+use crate::database::models::payouts_split_item::DBPayoutsSplit;
 use crate::database::models::payouts_values_notifications;
 use crate::database::{PgPool, PgTransaction};
 use crate::env::ENV;
@@ -8,6 +10,7 @@ use crate::models::payouts::{
 };
 use crate::models::projects::MonetizationStatus;
 use crate::routes::ApiError;
+use crate::sync::realtime::broadcast_realtime_notifications;
 use crate::util::error::ApiContext as _;
 use crate::util::error::Context;
 use crate::util::webhook::{
@@ -1022,7 +1025,7 @@ pub async fn process_payout(
     };
 
     struct Project {
-        // user_id, payouts_split
+        // user_id, share of the project's revenue
         team_members: Vec<(i64, Decimal)>,
     }
 
@@ -1036,7 +1039,7 @@ pub async fn process_payout(
 
     let project_org_members = sqlx::query!(
         "
-        SELECT m.id id, tm.user_id user_id, tm.payouts_split payouts_split
+        SELECT m.id id, tm.team_id team_id, tm.user_id user_id, tm.payouts_split payouts_split
         FROM mods m
         INNER JOIN organizations o ON m.organization_id = o.id
         INNER JOIN team_members tm on o.team_id = tm.team_id AND tm.accepted = TRUE
@@ -1050,17 +1053,20 @@ pub async fn process_payout(
             .collect::<Vec<String>>(),
     )
     .fetch(&mut transaction)
-    .try_fold(DashMap::new(), |acc: DashMap<i64, HashMap<i64, Decimal>>, r| {
-        acc.entry(r.id)
-            .or_default()
-            .insert(r.user_id, r.payouts_split);
-        async move { Ok(acc) }
-    })
+    .try_fold(
+        DashMap::new(),
+        |acc: DashMap<i64, HashMap<i64, (i64, Decimal)>>, r| {
+            acc.entry(r.id)
+                .or_default()
+                .insert(r.user_id, (r.team_id, r.payouts_split));
+            async move { Ok(acc) }
+        },
+    )
     .await.wrap_internal_err("inserting project org members into database")?;
 
     let project_team_members = sqlx::query!(
         "
-        SELECT m.id id, tm.user_id user_id, tm.payouts_split payouts_split
+        SELECT m.id id, tm.team_id team_id, tm.user_id user_id, tm.payouts_split payouts_split
         FROM mods m
         INNER JOIN team_members tm on m.team_id = tm.team_id AND tm.accepted = TRUE
         WHERE m.id = ANY($1) AND m.monetization_status = $2 AND m.status = ANY($3)
@@ -1075,47 +1081,79 @@ pub async fn process_payout(
     .fetch(&mut transaction)
     .try_fold(
         DashMap::new(),
-        |acc: DashMap<i64, HashMap<i64, Decimal>>, r| {
+        |acc: DashMap<i64, HashMap<i64, (i64, Decimal)>>, r| {
             acc.entry(r.id)
                 .or_default()
-                .insert(r.user_id, r.payouts_split);
+                .insert(r.user_id, (r.team_id, r.payouts_split));
             async move { Ok(acc) }
         },
     )
     .await.wrap_internal_err("inserting project team members into database")?;
 
+    let mut team_ids = project_team_members
+        .iter()
+        .chain(project_org_members.iter())
+        .flat_map(|x| {
+            x.value()
+                .values()
+                .map(|(team_id, _)| DBTeamId(*team_id))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    team_ids.sort_by_key(|id| id.0);
+    team_ids.dedup();
+
+    // (team_id, user_id) -> (effective_from, payouts_split), oldest first
+    let mut split_history: HashMap<(i64, i64), Vec<(DateTime<Utc>, Decimal)>> =
+        HashMap::new();
+    for split in DBPayoutsSplit::get_many(&team_ids, end, &mut transaction)
+        .await
+        .wrap_internal_err("fetching payouts split history")?
+    {
+        split_history
+            .entry((split.team_id.0, split.user_id.0))
+            .or_default()
+            .push((split.effective_from, split.payouts_split));
+    }
+
     for project_id in project_ids {
-        let team_members: HashMap<i64, Decimal> = project_team_members
-            .remove(&project_id)
-            .unwrap_or((0, HashMap::new()))
-            .1;
-        let org_team_members: HashMap<i64, Decimal> = project_org_members
+        let team_members: HashMap<i64, (i64, Decimal)> = project_team_members
             .remove(&project_id)
             .unwrap_or((0, HashMap::new()))
             .1;
+        let org_team_members: HashMap<i64, (i64, Decimal)> =
+            project_org_members
+                .remove(&project_id)
+                .unwrap_or((0, HashMap::new()))
+                .1;
 
         let mut all_team_members = vec![];
 
-        for (user_id, payouts_split) in org_team_members {
+        for (user_id, member) in org_team_members {
             if !team_members.contains_key(&user_id) {
-                all_team_members.push((user_id, payouts_split));
+                all_team_members.push((user_id, member));
             }
         }
-        for (user_id, payouts_split) in team_members {
-            all_team_members.push((user_id, payouts_split));
+        for (user_id, member) in team_members {
+            all_team_members.push((user_id, member));
         }
 
-        // if all team members are set to zero, we treat as an equal revenue distribution
-        if all_team_members.iter().all(|x| x.1 == Decimal::ZERO) {
-            all_team_members
-                .iter_mut()
-                .for_each(|x| x.1 = Decimal::from(1));
-        }
+        let splits = all_team_members
+            .into_iter()
+            .map(|(user_id, (team_id, payouts_split))| PayoutsSplitMember {
+                user_id,
+                current: payouts_split,
+                history: split_history
+                    .get(&(team_id, user_id))
+                    .cloned()
+                    .unwrap_or_default(),
+            })
+            .collect::<Vec<_>>();
 
         projects_map.insert(
             project_id,
             Project {
-                team_members: all_team_members,
+                team_members: time_weighted_shares(&splits, start, end),
             },
         );
     }
@@ -1189,21 +1227,15 @@ pub async fn process_payout(
             let project_multiplier: Decimal =
                 Decimal::from(**value) / Decimal::from(multipliers.sum);
 
-            let sum_splits: Decimal =
-                project.team_members.iter().map(|x| x.1).sum();
-
-            if sum_splits > Decimal::ZERO {
-                for (user_id, split) in project.team_members {
-                    let payout: Decimal =
-                        payout * project_multiplier * (split / sum_splits);
+            for (user_id, share) in project.team_members {
+                let payout: Decimal = payout * project_multiplier * share;
 
-                    if payout > Decimal::ZERO {
-                        insert_user_ids.push(user_id);
-                        insert_project_ids.push(id);
-                        insert_payouts.push(payout);
-                        insert_starts.push(start);
-                        insert_availables.push(available);
-                    }
+                if payout > Decimal::ZERO {
+                    insert_user_ids.push(user_id);
+                    insert_project_ids.push(id);
+                    insert_payouts.push(payout);
+                    insert_starts.push(start);
+                    insert_availables.push(available);
                 }
             }
         }
@@ -1231,6 +1263,94 @@ pub async fn process_payout(
     Ok(())
 }
 
+/// A member of a project's team, with the payouts splits they've had.
+struct PayoutsSplitMember {
+    user_id: i64,
+    /// Used if the member has no recorded history.
+    current: Decimal,
+    /// When each of the member's payouts splits took effect, oldest first.
+    history: Vec<(DateTime<Utc>, Decimal)>,
+}
+
+impl PayoutsSplitMember {
+    /// Gets the member's payouts split at `time`, or `None` if they weren't a
+    /// member of the team yet.
+    fn split_at(&self, time: DateTime<Utc>) -> Option<Decimal> {
+        if self.history.is_empty() {
+            return Some(self.current);
+        }
+
+        self.history
+            .iter()
+            .rev()
+            .find(|(effective_from, _)| *effective_from <= time)
+            .map(|(_, split)| *split)
+    }
+}
+
+/// Splits a project's revenue between its team members by the payouts splits
+/// in effect over each part of the period from `start` to `end`, returning
+/// each member's share of the revenue.
+///
+/// Whenever every member's payouts split is zero, the revenue for that part of
+/// the period is split equally.
+fn time_weighted_shares(
+    members: &[PayoutsSplitMember],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Vec<(i64, Decimal)> {
+    let total = (end - start).num_milliseconds();
+    if total <= 0 {
+        return Vec::new();
+    }
+
+    let mut boundaries = members
+        .iter()
+        .flat_map(|member| member.history.iter().map(|(time, _)| *time))
+        .filter(|time| *time > start && *time < end)
+        .chain([start, end])
+        .collect::<Vec<_>>();
+    boundaries.sort();
+    boundaries.dedup();
+
+    let mut shares = members
+        .iter()
+        .map(|member| (member.user_id, Decimal::ZERO))
+        .collect::<Vec<_>>();
+
+    for window in boundaries.windows(2) {
+        let [from, to] = window else { continue };
+        let weight = Decimal::from((*to - *from).num_milliseconds())
+            / Decimal::from(total);
+
+        let mut splits = members
+            .iter()
+            .map(|member| member.split_at(*from))
+            .collect::<Vec<_>>();
+
+        // if all team members are set to zero, we treat as an equal revenue distribution
+        if splits.iter().flatten().all(|split| *split == Decimal::ZERO) {
+            splits
+                .iter_mut()
+                .flatten()
+                .for_each(|split| *split = Decimal::from(1));
+        }
+
+        let sum_splits: Decimal = splits.iter().flatten().sum();
+        if sum_splits <= Decimal::ZERO {
+            continue;
+        }
+
+        for ((_, share), split) in shares.iter_mut().zip(splits) {
+            if let Some(split) = split {
+                *share += weight * split / sum_splits;
+            }
+        }
+    }
+
+    shares
+}
+
 // Used for testing, should be the same as the above function
 pub async fn insert_payouts(
     insert_user_ids: Vec<i64>,
@@ -1279,16 +1399,17 @@ pub async fn index_payouts_notifications(
         items.iter().map(|x| x.date_available).collect::<Vec<_>>();
     let user_ids = items.iter().map(|x| x.user_id).collect::<Vec<_>>();
 
-    NotificationBuilder::insert_many_payout_notifications(
-        user_ids,
-        dates_available,
-        &mut transaction,
-        redis,
-    )
-    .await
-    .wrap_internal_err(
-        "inserting database records for `index_payouts_notifications`",
-    )?;
+    let notification_ids =
+        NotificationBuilder::insert_many_payout_notifications(
+            user_ids,
+            dates_available,
+            &mut transaction,
+            redis,
+        )
+        .await
+        .wrap_internal_err(
+            "inserting database records for `index_payouts_notifications`",
+        )?;
     payouts_values_notifications::PayoutsValuesNotification::set_notified_many(
         &payout_ref_ids,
         &mut transaction,
@@ -1303,6 +1424,8 @@ pub async fn index_payouts_notifications(
         .await
         .wrap_internal_err("committing database transaction")?;
 
+    broadcast_realtime_notifications(redis, notification_ids).await;
+
     Ok(())
 }
 
@@ -1462,6 +1585,70 @@ mod tests {
     };
     use rust_decimal::dec;
 
+    #[test]
+    fn test_time_weighted_shares() {
+        let start = Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap();
+        let end = start + Duration::days(1);
+        let midday = start + Duration::hours(12);
+
+        // The first member's split drops from 3 to 1 at midday, and the third
+        // member only joins at midday.
+        let members = vec![
+            PayoutsSplitMember {
+                user_id: 1,
+                current: dec!(1),
+                history: vec![
+                    (start - Duration::days(30), dec!(3)),
+                    (midday, dec!(1)),
+                ],
+            },
+            PayoutsSplitMember {
+                user_id: 2,
+                current: dec!(1),
+                history: vec![(start - Duration::days(30), dec!(1))],
+            },
+            PayoutsSplitMember {
+                user_id: 3,
+                current: dec!(2),
+                history: vec![(midday, dec!(2))],
+            },
+        ];
+
+        let shares = time_weighted_shares(&members, start, end)
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        // 1/2 * 3/4 + 1/2 * 1/4
+        assert_eq!(shares[&1].round_dp(10), dec!(0.5));
+        // 1/2 * 1/4 + 1/2 * 1/4
+        assert_eq!(shares[&2].round_dp(10), dec!(0.25));
+        // 1/2 * 2/4
+        assert_eq!(shares[&3].round_dp(10), dec!(0.25));
+    }
+
+    #[test]
+    fn test_time_weighted_shares_all_zero_is_equal() {
+        let start = Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap();
+        let end = start + Duration::days(1);
+
+        let members = vec![
+            PayoutsSplitMember {
+                user_id: 1,
+                current: Decimal::ZERO,
+                history: Vec::new(),
+            },
+            PayoutsSplitMember {
+                user_id: 2,
+                current: Decimal::ZERO,
+                history: Vec::new(),
+            },
+        ];
+
+        let shares = time_weighted_shares(&members, start, end);
+
+        assert_eq!(shares, vec![(1, dec!(0.5)), (2, dec!(0.5))]);
+    }
+
     async fn setup_payouts_values(
         db: &PgPool,
         entries: Vec<(i64, Decimal, DateTime<Utc>)>, // (user_id, amount, date_available)
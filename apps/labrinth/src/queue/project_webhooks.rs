@@ -0,0 +1,220 @@
+use crate::database::PgPool;
+use crate::database::models::project_webhook_item::{
+    DBProjectWebhookDelivery, ProcessableDelivery,
+};
+use crate::models::project_webhooks::ProjectWebhookDeliveryStatus;
+use crate::util::error::Context;
+use crate::util::ip::{PublicIpResolver, check_public_host};
+use chrono::{TimeDelta, Utc};
+use eyre::eyre;
+use futures::future::join_all;
+use hex::ToHex;
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use rand::distributions::Alphanumeric;
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::SeedableRng;
+use reqwest::header::CONTENT_TYPE;
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+pub const EVENT_HEADER: &str = "X-Modrinth-Event";
+pub const DELIVERY_HEADER: &str = "X-Modrinth-Delivery";
+pub const TIMESTAMP_HEADER: &str = "X-Modrinth-Timestamp";
+/// `sha256=` followed by the hex HMAC-SHA256 of `{timestamp}.{body}`, keyed
+/// with the webhook's secret.
+pub const SIGNATURE_HEADER: &str = "X-Modrinth-Signature";
+
+/// Attempts after which a delivery is given up on.
+const MAX_ATTEMPTS: i32 = 8;
+/// Delay before the first retry, doubled after every further attempt.
+const RETRY_BASE_DELAY: TimeDelta = TimeDelta::seconds(30);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_ERROR_LENGTH: usize = 1024;
+
+pub fn generate_secret() -> String {
+    ChaCha20Rng::from_entropy()
+        .sample_iter(&Alphanumeric)
+        .take(48)
+        .map(char::from)
+        .collect()
+}
+
+/// Signs a delivery's body as sent in [`SIGNATURE_HEADER`].
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC can take keys of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!(
+        "sha256={}",
+        mac.finalize().into_bytes().encode_hex::<String>()
+    )
+}
+
+/// Delay before retrying a delivery which has failed `attempt_count` times.
+fn retry_delay(attempt_count: i32) -> TimeDelta {
+    RETRY_BASE_DELAY
+        * 2i32.pow(attempt_count.saturating_sub(1).clamp(0, 16) as u32)
+}
+
+/// Builds the client deliveries are sent with. Endpoints can only resolve to
+/// public addresses, and redirects aren't followed so they can't send us
+/// elsewhere.
+pub fn build_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .user_agent("Modrinth Webhooks")
+        .timeout(REQUEST_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .dns_resolver(Arc::new(PublicIpResolver))
+        .build()
+        .expect("Failed to build HTTP client")
+}
+
+/// Works on up to `limit` due deliveries.
+///
+/// Returns `Ok(false)` if there were no deliveries to process.
+pub async fn deliver_pending(
+    pool: &PgPool,
+    client: &reqwest::Client,
+    limit: i64,
+) -> eyre::Result<bool> {
+    let mut txn = pool.begin().await.wrap_err("failed to start transaction")?;
+
+    // We hold a FOR UPDATE lock on the rows until the transaction is
+    // committed, so no other workers send them at the same time.
+    let deliveries =
+        DBProjectWebhookDelivery::lock_processable(limit, &mut txn)
+            .await
+            .wrap_err("failed to fetch pending webhook deliveries")?;
+    if deliveries.is_empty() {
+        return Ok(false);
+    }
+
+    let results = join_all(
+        deliveries
+            .into_iter()
+            .map(|delivery| attempt_delivery(client, delivery)),
+    )
+    .await;
+
+    let mut delivered = 0;
+    for delivery in &results {
+        if delivery.status == ProjectWebhookDeliveryStatus::Delivered {
+            delivered += 1;
+        }
+        delivery
+            .update(&mut txn)
+            .await
+            .wrap_err("failed to update webhook delivery")?;
+    }
+
+    txn.commit()
+        .await
+        .wrap_err("failed to commit transaction")?;
+
+    info!(
+        "Attempted {} webhook deliveries, {delivered} delivered",
+        results.len()
+    );
+    Ok(true)
+}
+
+async fn attempt_delivery(
+    client: &reqwest::Client,
+    processable: ProcessableDelivery,
+) -> DBProjectWebhookDelivery {
+    let ProcessableDelivery {
+        mut delivery,
+        url,
+        secret,
+    } = processable;
+
+    let result = send(client, &url, &secret, &delivery).await;
+    delivery.attempt_count += 1;
+
+    let succeeded = match result {
+        Ok(response_code) => {
+            delivery.response_code = Some(i32::from(response_code.as_u16()));
+            delivery.error = None;
+            response_code.is_success()
+        }
+        Err(error) => {
+            delivery.response_code = None;
+            delivery.error = Some(
+                format!("{error:#}")
+                    .chars()
+                    .take(MAX_ERROR_LENGTH)
+                    .collect(),
+            );
+            false
+        }
+    };
+
+    if succeeded {
+        delivery.status = ProjectWebhookDeliveryStatus::Delivered;
+    } else if delivery.attempt_count >= MAX_ATTEMPTS {
+        warn!(
+            delivery_id = delivery.id,
+            "Giving up on webhook delivery after {MAX_ATTEMPTS} attempts"
+        );
+        delivery.status = ProjectWebhookDeliveryStatus::Failed;
+    } else {
+        delivery.next_attempt =
+            Utc::now() + retry_delay(delivery.attempt_count);
+    }
+
+    delivery
+}
+
+async fn send(
+    client: &reqwest::Client,
+    url: &str,
+    secret: &str,
+    delivery: &DBProjectWebhookDelivery,
+) -> eyre::Result<reqwest::StatusCode> {
+    // Names are checked by the client's resolver, which doesn't see IP
+    // literals
+    let url = url::Url::parse(url).wrap_err("invalid webhook URL")?;
+    check_public_host(&url).map_err(|error| eyre!(error))?;
+
+    let body = serde_json::to_vec(&delivery.payload)
+        .expect("webhook events should serialize");
+    let timestamp = Utc::now().timestamp();
+
+    let response = client
+        .post(url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, delivery.payload.event_type().as_str())
+        .header(DELIVERY_HEADER, delivery.id)
+        .header(TIMESTAMP_HEADER, timestamp)
+        .header(SIGNATURE_HEADER, sign(secret, timestamp, &body))
+        .body(body)
+        .send()
+        .await?;
+
+    Ok(response.status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign() {
+        assert_eq!(
+            sign("secret", 1700000000, br#"{"event":"version_published"}"#),
+            "sha256=5da8df74fda9ff591c92a48dd1b322311f39fac6f2e88f325084b95f876ff4c6"
+        );
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(1), TimeDelta::seconds(30));
+        assert_eq!(retry_delay(2), TimeDelta::seconds(60));
+        assert_eq!(retry_delay(7), TimeDelta::seconds(1920));
+    }
+}
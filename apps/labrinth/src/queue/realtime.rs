@@ -0,0 +1,242 @@
+//! Realtime event streams connected to this replica
+
+use crate::models::pats::Scopes;
+use crate::models::realtime::RealtimeEvent;
+use crate::models::users::User;
+use actix_web::web::Bytes;
+use ariadne::ids::UserId;
+use dashmap::{DashMap, DashSet};
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+pub type EventStreamId = u32;
+
+/// How many events are buffered for a client which isn't reading them, before
+/// further events are dropped.
+pub const EVENT_STREAM_BUFFER: usize = 64;
+
+#[derive(Default)]
+pub struct ActiveEventStreams {
+    pub streams: DashMap<EventStreamId, ActiveEventStream>,
+    pub streams_by_user_id: DashMap<UserId, DashSet<EventStreamId>>,
+    pub next_stream_id: AtomicU32,
+}
+
+pub struct ActiveEventStream {
+    pub user: User,
+    pub scopes: Scopes,
+    pub sender: mpsc::Sender<Bytes>,
+}
+
+impl ActiveEventStreams {
+    pub fn add(&self, stream: ActiveEventStream) -> EventStreamId {
+        let id = self.next_stream_id.fetch_add(1, Ordering::Relaxed);
+        self.streams_by_user_id
+            .entry(stream.user.id)
+            .or_default()
+            .insert(id);
+        self.streams.insert(id, stream);
+        id
+    }
+
+    pub fn remove(&self, id: EventStreamId) {
+        if let Some((_, stream)) = self.streams.remove(&id) {
+            self.streams_by_user_id
+                .remove_if(&stream.user.id, |_, streams| {
+                    streams.remove(&id);
+                    streams.is_empty()
+                });
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.streams.is_empty()
+    }
+
+    pub fn has_user(&self, user: UserId) -> bool {
+        self.streams_by_user_id.contains_key(&user)
+    }
+
+    /// Sends an event to every stream matching `filter`. The event is built
+    /// per stream, since what a user sees depends on their role.
+    pub fn send_where(
+        &self,
+        filter: impl Fn(&ActiveEventStream) -> bool,
+        event: impl Fn(&ActiveEventStream) -> RealtimeEvent,
+    ) {
+        for stream in &self.streams {
+            if filter(&stream) {
+                send(&stream, &event(&stream));
+            }
+        }
+    }
+
+    pub fn send_to_user(
+        &self,
+        user: UserId,
+        filter: impl Fn(&ActiveEventStream) -> bool,
+        event: &RealtimeEvent,
+    ) {
+        let Some(stream_ids) = self.streams_by_user_id.get(&user) else {
+            return;
+        };
+        for stream_id in stream_ids.iter() {
+            if let Some(stream) = self.streams.get(&stream_id)
+                && filter(&stream)
+            {
+                send(&stream, event);
+            }
+        }
+    }
+}
+
+/// Queues an event as a Server-Sent Events frame. Closed streams are removed
+/// by their response body when it's dropped, so send failures are ignored.
+fn send(stream: &ActiveEventStream, event: &RealtimeEvent) {
+    let data = match serde_json::to_string(event) {
+        Ok(data) => data,
+        Err(err) => {
+            warn!("Failed to serialize realtime event: {err}");
+            return;
+        }
+    };
+
+    if let Err(mpsc::error::TrySendError::Full(_)) = stream
+        .sender
+        .try_send(Bytes::from(format!("data: {data}\n\n")))
+    {
+        debug!(
+            user_id = %stream.user.id,
+            "Dropped realtime event for a stream which isn't being read"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ids::ProjectId;
+    use crate::models::projects::ProjectStatus;
+    use crate::models::users::{Badges, Role, UserCampaigns};
+    use chrono::Utc;
+
+    fn stream(
+        user_id: u64,
+        scopes: Scopes,
+    ) -> (ActiveEventStream, mpsc::Receiver<Bytes>) {
+        let (sender, receiver) = mpsc::channel(EVENT_STREAM_BUFFER);
+        let user = User {
+            id: UserId(user_id),
+            username: format!("user{user_id}"),
+            avatar_url: None,
+            bio: None,
+            created: Utc::now(),
+            role: Role::Developer,
+            badges: Badges::empty(),
+            campaigns: UserCampaigns { pride_26: None },
+            auth_providers: None,
+            email: None,
+            email_verified: None,
+            has_password: None,
+            has_totp: None,
+            payout_data: None,
+            stripe_customer_id: None,
+            allow_friend_requests: None,
+            eligibility_verified_at: None,
+            moderation_notes: None,
+            github_id: None,
+            discord_id: None,
+            steam_id: None,
+        };
+        (
+            ActiveEventStream {
+                user,
+                scopes,
+                sender,
+            },
+            receiver,
+        )
+    }
+
+    fn event() -> RealtimeEvent {
+        RealtimeEvent::ProjectStatus {
+            project_id: ProjectId(1),
+            old_status: ProjectStatus::Processing,
+            new_status: ProjectStatus::Approved,
+        }
+    }
+
+    #[test]
+    fn streams_are_indexed_by_user() {
+        let streams = ActiveEventStreams::default();
+        let first = streams.add(stream(1, Scopes::all()).0);
+        let second = streams.add(stream(1, Scopes::all()).0);
+        assert_ne!(first, second);
+        assert!(streams.has_user(UserId(1)));
+        assert!(!streams.has_user(UserId(2)));
+
+        streams.remove(first);
+        assert!(streams.has_user(UserId(1)));
+        streams.remove(second);
+        assert!(!streams.has_user(UserId(1)));
+        assert!(streams.is_empty());
+        assert!(streams.streams_by_user_id.is_empty());
+    }
+
+    #[test]
+    fn events_are_sent_to_matching_streams() {
+        let streams = ActiveEventStreams::default();
+        let (matching, mut matching_receiver) = stream(1, Scopes::PROJECT_READ);
+        let (missing_scope, mut missing_scope_receiver) =
+            stream(1, Scopes::NOTIFICATION_READ);
+        let (other_user, mut other_user_receiver) =
+            stream(2, Scopes::PROJECT_READ);
+        streams.add(matching);
+        streams.add(missing_scope);
+        streams.add(other_user);
+
+        streams.send_to_user(
+            UserId(1),
+            |stream| stream.scopes.contains(Scopes::PROJECT_READ),
+            &event(),
+        );
+
+        let frame = matching_receiver.try_recv().unwrap();
+        let data = std::str::from_utf8(&frame)
+            .unwrap()
+            .strip_prefix("data: ")
+            .and_then(|frame| frame.strip_suffix("\n\n"))
+            .unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(data).unwrap(),
+            serde_json::to_value(event()).unwrap()
+        );
+        assert!(missing_scope_receiver.try_recv().is_err());
+        assert!(other_user_receiver.try_recv().is_err());
+
+        streams.send_where(
+            |stream| stream.scopes.contains(Scopes::PROJECT_READ),
+            |_| event(),
+        );
+        assert!(matching_receiver.try_recv().is_ok());
+        assert!(missing_scope_receiver.try_recv().is_err());
+        assert!(other_user_receiver.try_recv().is_ok());
+    }
+
+    #[test]
+    fn events_are_dropped_for_full_streams() {
+        let streams = ActiveEventStreams::default();
+        let (stream, mut receiver) = stream(1, Scopes::all());
+        streams.add(stream);
+
+        for _ in 0..EVENT_STREAM_BUFFER + 1 {
+            streams.send_to_user(UserId(1), |_| true, &event());
+        }
+
+        for _ in 0..EVENT_STREAM_BUFFER {
+            assert!(receiver.try_recv().is_ok());
+        }
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -29,8 +29,71 @@ pub struct ServerPingQueue {
 pub const REDIS_NAMESPACE: &str = "minecraft_java_server_ping:v4";
 pub const REDIS_FAILURE_NAMESPACE: &str =
     "minecraft_java_server_ping_failures:v4";
+pub const BEDROCK_REDIS_NAMESPACE: &str = "minecraft_bedrock_server_ping:v1";
+pub const BEDROCK_REDIS_FAILURE_NAMESPACE: &str =
+    "minecraft_bedrock_server_ping_failures:v1";
+/// Pings for both editions are recorded here, distinguished by `edition`.
 pub const CLICKHOUSE_TABLE: &str = "minecraft_java_server_pings";
 
+/// Which edition of Minecraft a server listing is pinged as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumIter)]
+pub enum ServerEdition {
+    Java,
+    Bedrock,
+}
+
+impl ServerEdition {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Java => "java",
+            Self::Bedrock => "bedrock",
+        }
+    }
+
+    /// Name of the project component holding the server's address.
+    fn component(self) -> &'static str {
+        match self {
+            Self::Java => "minecraft_java_server",
+            Self::Bedrock => "minecraft_bedrock_server",
+        }
+    }
+
+    /// Redis namespace the edition's latest pings are cached under.
+    pub fn redis_namespace(self) -> &'static str {
+        match self {
+            Self::Java => REDIS_NAMESPACE,
+            Self::Bedrock => BEDROCK_REDIS_NAMESPACE,
+        }
+    }
+
+    fn redis_failure_namespace(self) -> &'static str {
+        match self {
+            Self::Java => REDIS_FAILURE_NAMESPACE,
+            Self::Bedrock => BEDROCK_REDIS_FAILURE_NAMESPACE,
+        }
+    }
+
+    fn address(self, components: exp::ProjectSerial) -> Option<String> {
+        match self {
+            Self::Java => components.minecraft_java_server.map(|s| s.address),
+            Self::Bedrock => {
+                components.minecraft_bedrock_server.map(|s| s.address)
+            }
+        }
+    }
+
+    pub async fn ping(
+        self,
+        address: &str,
+        timeout: Option<Duration>,
+    ) -> eyre::Result<exp::minecraft::JavaServerPingData> {
+        match self {
+            Self::Java => ping_server(address, timeout).await,
+            Self::Bedrock => ping_bedrock_server(address, timeout).await,
+        }
+    }
+}
+
 impl ServerPingQueue {
     pub fn new(
         db: PgPool,
@@ -47,24 +110,35 @@ impl ServerPingQueue {
     }
 
     pub async fn ping_minecraft_java_servers(&self) -> eyre::Result<()> {
-        let server_projects = self.find_servers_to_ping().await?;
-        info!("Found {} servers to ping", server_projects.len());
+        self.ping_servers(ServerEdition::Java).await
+    }
+
+    pub async fn ping_minecraft_bedrock_servers(&self) -> eyre::Result<()> {
+        self.ping_servers(ServerEdition::Bedrock).await
+    }
+
+    async fn ping_servers(&self, edition: ServerEdition) -> eyre::Result<()> {
+        let server_projects = self.find_servers_to_ping(edition).await?;
+        info!(
+            "Found {} {} servers to ping",
+            server_projects.len(),
+            edition.as_str()
+        );
 
         let active_pings =
             Arc::new(Semaphore::new(ENV.SERVER_PING_MAX_CONCURRENT));
         let pings = server_projects
             .into_iter()
-            .map(|(project_id, java_server)| {
-                let span = info_span!("ping", %project_id, address = %java_server.address);
+            .map(|(project_id, address)| {
+                let span = info_span!("ping", %project_id, %address, edition = edition.as_str());
 
                 let active_pings = active_pings.clone();
-                let address = java_server.address;
                 let task = async move {
                     let _permit = active_pings.acquire().await.expect("semaphore should not be closed now");
 
                     let mut retries = ENV.SERVER_PING_RETRIES;
                     let result = loop {
-                        match ping_server(&address, None).await {
+                        match edition.ping(&address, None).await {
                             Ok(ping) => {
                                 info!(?ping, "Received successful ping");
                                 break Ok(ping);
@@ -111,18 +185,21 @@ impl ServerPingQueue {
 
             for (project_id, ping) in &pings {
                 let data = ping.data.as_ref();
-                let ping_key =
-                    self.redis.key().entity(REDIS_NAMESPACE, project_id);
+                let ping_key = self
+                    .redis
+                    .key()
+                    .entity(edition.redis_namespace(), project_id);
                 let failure_key = self
                     .redis
                     .key()
-                    .entity(REDIS_FAILURE_NAMESPACE, project_id);
+                    .entity(edition.redis_failure_namespace(), project_id);
 
                 let row = ServerPingRecord {
                     recorded: ping.when.timestamp_nanos_opt().unwrap()
                         / 100_000,
                     project_id: project_id.0,
                     address: ping.address.clone(),
+                    edition: edition.as_str().to_string(),
                     latency_ms: data.map(|d| d.latency.as_millis() as u32),
                     description: data.map(|d| d.description.clone()),
                     version_name: data.map(|d| d.version_name.clone()),
@@ -202,28 +279,31 @@ impl ServerPingQueue {
         let num_total = pings.len();
 
         info!(
-            "Inserted ping results for {} servers - {num_success}/{num_total} successful",
-            pings.len()
+            "Inserted ping results for {} {} servers - {num_success}/{num_total} successful",
+            pings.len(),
+            edition.as_str()
         );
         Ok(())
     }
 
     async fn find_servers_to_ping(
         &self,
-    ) -> eyre::Result<Vec<(ProjectId, exp::minecraft::JavaServerProject)>> {
-        // first select all java servers
+        edition: ServerEdition,
+    ) -> eyre::Result<Vec<(ProjectId, String)>> {
+        // first select all servers of this edition
         let all_server_projects = sqlx::query!(
             r#"
             SELECT id, components AS "components: Json<exp::ProjectSerial>"
             FROM mods
             WHERE
                 status = ANY($1)
-                AND components ? 'minecraft_java_server'
+                AND components ? $2
             "#,
             &ProjectStatus::iterator()
                 .filter(|s| s.is_approved())
                 .map(|s| s.to_string())
-                .collect::<Vec<_>>()
+                .collect::<Vec<_>>(),
+            edition.component(),
         )
         .fetch_all(&self.db)
         .await
@@ -253,7 +333,9 @@ impl ServerPingQueue {
         let ping_keys = all_project_ids
             .iter()
             .map(|project_id| {
-                self.redis.key().entity(REDIS_NAMESPACE, project_id)
+                self.redis
+                    .key()
+                    .entity(edition.redis_namespace(), project_id)
             })
             .collect::<Vec<_>>();
 
@@ -270,17 +352,13 @@ impl ServerPingQueue {
             // - have not had a ping in redis yet
             // - OR their last ping was a failure
             // - OR their last successful ping was more than `SERVER_PING_MIN_INTERVAL_SEC` seconds ago
-            .filter(|(row, ping)| {
-                if row
-                    .components
-                    .0
-                    .minecraft_java_server
-                    .as_ref()
-                    .is_none_or(|p| p.address.trim().is_empty())
-                {
-                    return false;
-                }
-
+            .filter_map(|(row, ping)| {
+                let address = edition
+                    .address(row.components.0)
+                    .filter(|address| !address.trim().is_empty())?;
+                Some((ProjectId::from(DBProjectId(row.id)), address, ping))
+            })
+            .filter(|(_, _, ping)| {
                 let Some(ping) = ping else { return true };
                 if ping.data.is_none() {
                     return true;
@@ -290,10 +368,7 @@ impl ServerPingQueue {
                         ENV.SERVER_PING_MIN_INTERVAL_SEC as i64,
                     )
             })
-            .filter_map(|(row, _)| {
-                let server = row.components.0.minecraft_java_server?;
-                Some((ProjectId::from(DBProjectId(row.id)), server))
-            })
+            .map(|(project_id, address, _)| (project_id, address))
             .collect::<Vec<_>>();
 
         Ok(projects_to_ping)
@@ -319,18 +394,14 @@ pub async fn ping_server(
     };
 
     let task = async move {
-        let conn = async_minecraft_ping::ConnectionConfig::build(address)
+        // falls back to the legacy ping for pre-1.7 servers
+        let status = async_minecraft_ping::ConnectionConfig::build(address)
             .with_port(port)
             .with_srv_lookup()
-            .connect()
-            .await
-            .wrap_err("failed to connect to server")?;
-
-        let status = conn
+            .with_timeout(timeout)
             .status()
             .await
-            .wrap_err("failed to get server status")?
-            .status;
+            .wrap_err("failed to get server status")?;
 
         eyre::Ok(exp::minecraft::JavaServerPingData {
             latency: start.elapsed(),
@@ -351,11 +422,49 @@ pub async fn ping_server(
         .flatten()
 }
 
+/// Pings a Bedrock server over RakNet, reporting its MOTD as the
+/// description.
+pub async fn ping_bedrock_server(
+    address: &str,
+    timeout: Option<Duration>,
+) -> eyre::Result<exp::minecraft::JavaServerPingData> {
+    let start = Instant::now();
+    let default_duration = Duration::from_millis(ENV.SERVER_PING_TIMEOUT_MS);
+    let timeout = timeout
+        .map(|duration| duration.min(default_duration))
+        .unwrap_or(default_duration);
+
+    let (address, port) = match address.rsplit_once(':') {
+        Some((addr, port)) => {
+            let port = port.parse::<u16>().wrap_err("invalid port number")?;
+            (addr, port)
+        }
+        None => (address, 19132),
+    };
+
+    let status = async_minecraft_ping::BedrockConnectionConfig::build(address)
+        .with_port(port)
+        .with_timeout(timeout)
+        .ping()
+        .await
+        .wrap_err("failed to ping server")?;
+
+    Ok(exp::minecraft::JavaServerPingData {
+        latency: start.elapsed(),
+        version_name: status.version,
+        version_protocol: status.protocol,
+        description: status.motd,
+        players_online: status.players_online,
+        players_max: status.players_max,
+    })
+}
+
 #[derive(Debug, Row, Serialize, Clone)]
 struct ServerPingRecord {
     recorded: i64,
     project_id: u64,
     address: String,
+    edition: String,
     latency_ms: Option<u32>,
     description: Option<String>,
     version_name: Option<String>,
@@ -383,6 +492,13 @@ mod tests {
         _ = ping_server("invalid.invalid", None).await.unwrap_err();
     }
 
+    #[actix_rt::test]
+    async fn test_ping_bedrock_server_invalid_address() {
+        _ = ping_bedrock_server("invalid.invalid", None)
+            .await
+            .unwrap_err();
+    }
+
     #[actix_rt::test]
     async fn test_ping_zero_timeout() {
         _ = ping_server("play.cubecraft.net", Some(Duration::ZERO))
@@ -0,0 +1,206 @@
+use crate::database::PgPool;
+use crate::database::models::DBProject;
+use crate::database::models::server_verification_item::DBServerVerification;
+use crate::env::ENV;
+use crate::models::exp::minecraft::{
+    ServerVerification, ServerVerificationMethod,
+};
+use crate::models::ids::ProjectId;
+use crate::queue::server_ping;
+use crate::search::incremental::IncrementalSearchQueue;
+use crate::util::error::Context;
+use chrono::{TimeDelta, Utc};
+use futures::future::join;
+use rand::Rng;
+use rand::distributions::Alphanumeric;
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::SeedableRng;
+use tracing::{info, warn};
+use xredis::RedisPool;
+
+pub const TOKEN_PREFIX: &str = "modrinth-verify-";
+/// Subdomain of the server's host which holds the DNS TXT record.
+pub const DNS_RECORD_PREFIX: &str = "_modrinth-verify";
+const RECHECK_BATCH_SIZE: i64 = 500;
+
+pub fn generate_token() -> String {
+    let random = ChaCha20Rng::from_entropy()
+        .sample_iter(&Alphanumeric)
+        .take(24)
+        .map(char::from)
+        .collect::<String>();
+    format!("{TOKEN_PREFIX}{random}")
+}
+
+/// Name of the DNS TXT record that can hold the token for `address`.
+pub fn dns_record_name(address: &str) -> String {
+    let host = address
+        .rsplit_once(':')
+        .map_or(address, |(host, _)| host)
+        .trim_end_matches('.');
+    format!("{DNS_RECORD_PREFIX}.{host}")
+}
+
+/// Checks whether the server at `address` proves control with `token`,
+/// either in a DNS TXT record or in its MOTD.
+///
+/// DNS is preferred, since it keeps passing re-checks while the server is
+/// offline.
+pub async fn check_server(
+    address: &str,
+    token: &str,
+) -> Option<ServerVerificationMethod> {
+    let (dns, motd) =
+        join(check_dns(address, token), check_motd(address, token)).await;
+
+    if dns {
+        Some(ServerVerificationMethod::Dns)
+    } else if motd {
+        Some(ServerVerificationMethod::Motd)
+    } else {
+        None
+    }
+}
+
+async fn check_dns(address: &str, token: &str) -> bool {
+    let record_name = dns_record_name(address);
+    let result = async {
+        let resolver = hickory_resolver::TokioResolver::builder_tokio()
+            .wrap_err("failed to create DNS resolver")?
+            .build();
+        let lookup = resolver
+            .txt_lookup(record_name.as_str())
+            .await
+            .wrap_err("failed to look up TXT record")?;
+        eyre::Ok(lookup.iter().any(|txt| {
+            txt.txt_data()
+                .iter()
+                .any(|data| String::from_utf8_lossy(data).trim() == token)
+        }))
+    }
+    .await;
+
+    result
+        .inspect_err(|err| {
+            info!(%record_name, "No verification TXT record found: {err:#}");
+        })
+        .unwrap_or(false)
+}
+
+async fn check_motd(address: &str, token: &str) -> bool {
+    match server_ping::ping_server(address, None).await {
+        Ok(ping) => ping.description.contains(token),
+        Err(err) => {
+            info!(%address, "Failed to ping server for verification: {err:#}");
+            false
+        }
+    }
+}
+
+/// Re-checks verified server listings which haven't been checked in
+/// `SERVER_VERIFICATION_RECHECK_INTERVAL_SEC`, and unverifies any which fail
+/// `SERVER_VERIFICATION_MAX_FAIL_COUNT` checks in a row.
+pub async fn recheck_server_verifications(
+    pool: &PgPool,
+    redis: &RedisPool,
+    incremental_search_queue: &IncrementalSearchQueue,
+) -> eyre::Result<()> {
+    let checked_before = Utc::now()
+        - TimeDelta::seconds(
+            ENV.SERVER_VERIFICATION_RECHECK_INTERVAL_SEC as i64,
+        );
+    let due = DBServerVerification::get_due_for_recheck(
+        checked_before,
+        RECHECK_BATCH_SIZE,
+        pool,
+    )
+    .await
+    .wrap_err("failed to fetch server verifications to re-check")?;
+    info!("Re-checking {} server verifications", due.len());
+
+    let mut num_unverified = 0;
+    for recheck in &due {
+        let method = check_server(&recheck.address, &recheck.token).await;
+        let failed_checks = DBServerVerification::record_check(
+            recheck.project_id,
+            method.is_some(),
+            pool,
+        )
+        .await
+        .wrap_err("failed to record server verification check")?;
+
+        if method.is_some()
+            || failed_checks < ENV.SERVER_VERIFICATION_MAX_FAIL_COUNT
+        {
+            continue;
+        }
+
+        let updated = DBServerVerification::set_project_verification(
+            recheck.project_id,
+            &recheck.address,
+            None,
+            pool,
+        )
+        .await
+        .wrap_err("failed to unverify server")?;
+        if !updated {
+            continue;
+        }
+        num_unverified += 1;
+        info!(
+            project_id = %ProjectId::from(recheck.project_id),
+            address = %recheck.address,
+            "Unverified server after {failed_checks} failed checks"
+        );
+
+        let clear_cache =
+            DBProject::clear_cache(recheck.project_id, None, None, redis);
+        let queue_search = incremental_search_queue
+            .push_project_change(recheck.project_id.into());
+        let (clear_cache_result, _) = join(clear_cache, queue_search).await;
+        clear_cache_result
+            .inspect_err(|err| warn!("failed to clear project cache: {err:#}"))
+            .ok();
+    }
+
+    info!(
+        "Re-checked {} server verifications - {num_unverified} unverified",
+        due.len()
+    );
+    Ok(())
+}
+
+/// Builds the verification to store on a project which just passed a check.
+pub fn new_verification(
+    method: ServerVerificationMethod,
+) -> ServerVerification {
+    ServerVerification {
+        method,
+        verified_at: Utc::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dns_record_name() {
+        assert_eq!(
+            dns_record_name("play.example.com"),
+            "_modrinth-verify.play.example.com"
+        );
+        assert_eq!(
+            dns_record_name("play.example.com:25566"),
+            "_modrinth-verify.play.example.com"
+        );
+    }
+
+    #[test]
+    fn test_generate_token() {
+        let token = generate_token();
+        assert!(token.starts_with(TOKEN_PREFIX));
+        assert_eq!(token.len(), TOKEN_PREFIX.len() + 24);
+        assert_ne!(token, generate_token());
+    }
+}
@@ -10,6 +10,9 @@ use uuid::Uuid;
 pub type SocketId = u32;
 
 pub struct ActiveSockets {
+    /// Identifies this replica, so that others can send it tunnel traffic
+    /// for the launchers connected to it.
+    pub replica_id: Uuid,
     pub sockets: DashMap<SocketId, ActiveSocket>,
     pub sockets_by_user_id: DashMap<UserId, DashSet<SocketId>>,
     pub next_socket_id: AtomicU32,
@@ -19,6 +22,7 @@ pub struct ActiveSockets {
 impl Default for ActiveSockets {
     fn default() -> Self {
         Self {
+            replica_id: Uuid::new_v4(),
             sockets: DashMap::new(),
             sockets_by_user_id: DashMap::new(),
             next_socket_id: AtomicU32::new(0),
@@ -65,5 +69,10 @@ impl TunnelSocket {
 
 pub enum TunnelSocketType {
     Listening,
-    Connected { connected_to: Uuid },
+    /// Connected to `connected_to`, whose launcher is connected to the
+    /// replica `replica`.
+    Connected {
+        connected_to: Uuid,
+        replica: Uuid,
+    },
 }
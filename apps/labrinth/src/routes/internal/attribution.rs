@@ -7,7 +7,7 @@ use serde::{Deserialize, Serialize};
 use crate::auth::{check_is_moderator_from_headers, get_user_from_headers};
 use crate::database::PgPool;
 use crate::database::models::{
-    DBFileId, DBOrganization, DBProject, DBTeamMember, DBVersion,
+    DBFileId, DBProject, DBTeamMember, DBVersion,
     ids::{
         DBAttributionGroupId, DBProjectId, DBVersionId,
         generate_attribution_group_id,
@@ -1275,23 +1275,14 @@ async fn ensure_can_upload_versions_to_project(
     .await
     .wrap_internal_err("failed to fetch project team member")?;
 
-    let organization = DBOrganization::get_associated_organization_project_id(
-        project_id, pool,
-    )
-    .await
-    .wrap_internal_err("failed to fetch associated organization")?;
-
-    let organization_team_member = if let Some(organization) = organization {
-        DBTeamMember::get_from_user_id(
-            organization.team_id,
+    let organization_team_member =
+        DBTeamMember::get_from_user_id_project_organization(
+            project_id,
             user.id.into(),
             pool,
         )
         .await
-        .wrap_internal_err("failed to fetch organization team member")?
-    } else {
-        None
-    };
+        .wrap_internal_err("failed to fetch organization team member")?;
 
     let permissions = ProjectPermissions::get_permissions_by_role(
         &user.role,
@@ -2,7 +2,7 @@ use self::payments::*;
 use self::update_subscriptions::*;
 use crate::auth::get_user_from_headers;
 use crate::database::models::charge_item::DBCharge;
-use crate::database::models::ids::DBUserSubscriptionId;
+use crate::database::models::ids::{DBNotificationId, DBUserSubscriptionId};
 use crate::database::models::notification_item::NotificationBuilder;
 use crate::database::models::products_tax_identifier_item::product_info_by_product_price_id;
 use crate::database::models::users_subscriptions_affiliations::DBUsersSubscriptionsAffiliations;
@@ -24,6 +24,7 @@ use crate::models::pats::Scopes;
 use crate::models::users::Badges;
 use crate::queue::session::AuthQueue;
 use crate::routes::ApiError;
+use crate::sync::realtime::broadcast_realtime_notifications;
 use crate::util::anrok;
 use crate::util::error::ApiContext as _;
 use crate::util::error::Context as _;
@@ -2574,6 +2575,7 @@ pub async fn stripe_webhook(
                     .await
                     .wrap_api_err("fetching payment intent metadata")?;
 
+                    let mut notification_ids = Vec::new();
                     if metadata.user_item.email.is_some() {
                         let money = rusty_money::Money::from_minor(
                             metadata.charge_item.amount as i64,
@@ -2583,7 +2585,7 @@ pub async fn stripe_webhook(
                             .unwrap_or(rusty_money::iso::USD),
                         );
 
-                        NotificationBuilder {
+                        let notification_id = NotificationBuilder {
                             body: NotificationBody::PaymentFailed {
                                 amount: money.to_string(),
                                 service: if metadata
@@ -2609,12 +2611,15 @@ pub async fn stripe_webhook(
                         .wrap_internal_err(
                             "inserting payment failure notification",
                         )?;
+                        notification_ids.push(notification_id);
                     }
 
                     transaction
                         .commit()
                         .await
                         .wrap_internal_err("committing database transaction")?;
+                    broadcast_realtime_notifications(&redis, notification_ids)
+                        .await;
                 }
             }
             EventType::PaymentMethodAttached => {
@@ -2679,7 +2684,7 @@ async fn apply_credit_many(
     days: i32,
     send_email: bool,
     message: String,
-) -> Result<(), ApiError> {
+) -> Result<Vec<DBNotificationId>, ApiError> {
     let subs_ids: Vec<DBUserSubscriptionId> = subscription_ids
         .iter()
         .map(|id| DBUserSubscriptionId(id.0 as i64))
@@ -2707,6 +2712,7 @@ async fn apply_credit_many(
         Vec::with_capacity(provisioned_count);
     let mut credit_next_dues: Vec<chrono::DateTime<chrono::Utc>> =
         Vec::with_capacity(provisioned_count);
+    let mut notification_ids = Vec::new();
 
     for subscription in subs {
         if subscription.status != SubscriptionStatus::Provisioned {
@@ -2742,7 +2748,7 @@ async fn apply_credit_many(
         credit_next_dues.push(next_due);
 
         if send_email {
-            NotificationBuilder {
+            let notification_id = NotificationBuilder {
                 body: NotificationBody::SubscriptionCredited {
                     subscription_id: subscription.id.into(),
                     days,
@@ -2756,6 +2762,7 @@ async fn apply_credit_many(
             .wrap_internal_err(
                 "inserting database records for `apply_credit_many`",
             )?;
+            notification_ids.push(notification_id);
         }
     }
 
@@ -2772,7 +2779,7 @@ async fn apply_credit_many(
     .map_err(|err| eyre::eyre!(err))
     .wrap_internal_err("inserting subscription credits into database")?;
 
-    Ok(())
+    Ok(notification_ids)
 }
 
 #[derive(Deserialize, utoipa::ToSchema)]
@@ -2847,7 +2854,7 @@ pub async fn credit(
         .await
         .wrap_internal_err("starting database transaction")?;
 
-    match target {
+    let notification_ids = match target {
         CreditTarget::Subscriptions { subscription_ids } => {
             if subscription_ids.is_empty() {
                 return Err(ApiError::Request(eyre::eyre!(
@@ -2864,7 +2871,7 @@ pub async fn credit(
                 message,
             )
             .await
-            .wrap_api_err("crediting user subscriptions")?;
+            .wrap_api_err("crediting user subscriptions")?
         }
         CreditTarget::Nodes { nodes } => {
             if nodes.is_empty() {
@@ -2903,7 +2910,7 @@ pub async fn credit(
                 message,
             )
             .await
-            .wrap_api_err("crediting server subscriptions")?;
+            .wrap_api_err("crediting server subscriptions")?
         }
         CreditTarget::Region { region } => {
             let servers = archon_client
@@ -2930,14 +2937,15 @@ pub async fn credit(
                 message,
             )
             .await
-            .wrap_api_err("crediting region subscriptions")?;
+            .wrap_api_err("crediting region subscriptions")?
         }
-    }
+    };
 
     transaction
         .commit()
         .await
         .wrap_internal_err("committing database transaction")?;
+    broadcast_realtime_notifications(&redis, notification_ids).await;
 
     Ok(HttpResponse::NoContent().finish())
 }
@@ -20,6 +20,7 @@ use crate::routes::internal::external_notifications::EmailFailure::{
 };
 use crate::routes::internal::statuses::broadcast_friends_message;
 use crate::sync::friends::RedisFriendsMessage;
+use crate::sync::realtime::broadcast_realtime_notifications;
 use crate::util::guards::external_notification_key_guard;
 use actix_web::http::StatusCode;
 use actix_web::web;
@@ -221,6 +222,7 @@ async fn create_impl(
         .wrap_internal_err("committing database transaction")?;
 
     broadcast_notifications(&redis, notifications).await;
+    broadcast_realtime_notifications(&redis, notification_ids).await;
 
     if email_strategy == EmailStrategy::Sync {
         let mut email_txn = pool
@@ -403,7 +405,7 @@ pub async fn send_custom_email(
         .await
         .wrap_internal_err("starting database transaction")?;
 
-    NotificationBuilder {
+    let notification_ids = NotificationBuilder {
         body: NotificationBody::Custom {
             title,
             body_md,
@@ -417,6 +419,7 @@ pub async fn send_custom_email(
     txn.commit()
         .await
         .wrap_internal_err("committing database transaction")?;
+    broadcast_realtime_notifications(&redis, notification_ids).await;
 
     Ok(HttpResponse::Accepted().finish())
 }
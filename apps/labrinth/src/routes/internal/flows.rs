@@ -19,6 +19,7 @@ use crate::queue::email::EmailQueue;
 use crate::queue::session::AuthQueue;
 use crate::routes::ApiError;
 use crate::routes::internal::session::issue_session;
+use crate::sync::realtime::broadcast_realtime_notifications;
 use crate::util::captcha::check_hcaptcha;
 use crate::util::error::ApiContext as _;
 use crate::util::error::Context;
@@ -1335,17 +1336,21 @@ pub async fn auth_callback(
                 crate::database::models::DBUser::get_id(id, &**client, &redis)
                     .await?;
 
+            let mut notification_ids = Vec::new();
             if let Some(user) = user {
-                NotificationBuilder {
-                    body: NotificationBody::AuthProviderAdded {
-                        provider: provider.as_str().to_string(),
-                    },
-                }
-                .insert(user.id, &mut transaction, &redis)
-                .await?;
+                notification_ids.push(
+                    NotificationBuilder {
+                        body: NotificationBody::AuthProviderAdded {
+                            provider: provider.as_str().to_string(),
+                        },
+                    }
+                    .insert(user.id, &mut transaction, &redis)
+                    .await?,
+                );
             }
 
             transaction.commit().await?;
+            broadcast_realtime_notifications(&redis, notification_ids).await;
             crate::database::models::DBUser::clear_caches(
                 &[(id, None)],
                 &redis,
@@ -1696,8 +1701,9 @@ pub async fn delete_auth_provider(
             "updating database records for `delete_auth_provider`",
         )?;
 
+    let mut notification_ids = Vec::new();
     if delete_provider.provider != AuthProvider::PayPal {
-        NotificationBuilder {
+        let notification_id = NotificationBuilder {
             body: NotificationBody::AuthProviderRemoved {
                 provider: delete_provider.provider.as_str().to_string(),
             },
@@ -1707,12 +1713,14 @@ pub async fn delete_auth_provider(
         .wrap_internal_err(
             "inserting database records for `delete_auth_provider`",
         )?;
+        notification_ids.push(notification_id);
     }
 
     transaction
         .commit()
         .await
         .wrap_internal_err("committing database transaction")?;
+    broadcast_realtime_notifications(&redis, notification_ids).await;
     crate::database::models::DBUser::clear_caches(
         &[(user.id.into(), None)],
         &redis,
@@ -2624,7 +2632,7 @@ pub async fn finish_2fa_flow(
             codes.push(to_base62(val));
         }
 
-        NotificationBuilder {
+        let notification_id = NotificationBuilder {
             body: NotificationBody::TwoFactorEnabled,
         }
         .insert(user.id.into(), &mut transaction, &redis)
@@ -2637,6 +2645,7 @@ pub async fn finish_2fa_flow(
             .commit()
             .await
             .wrap_internal_err("committing database transaction")?;
+        broadcast_realtime_notifications(&redis, vec![notification_id]).await;
         crate::database::models::DBUser::clear_caches(
             &[(user.id.into(), None)],
             &redis,
@@ -2745,7 +2754,7 @@ pub async fn remove_2fa(
     .await
     .wrap_internal_err("querying database for `remove_2fa`")?;
 
-    NotificationBuilder {
+    let notification_id = NotificationBuilder {
         body: NotificationBody::TwoFactorRemoved,
     }
     .insert(user.id, &mut transaction, &redis)
@@ -2756,6 +2765,7 @@ pub async fn remove_2fa(
         .commit()
         .await
         .wrap_internal_err("committing database transaction")?;
+    broadcast_realtime_notifications(&redis, vec![notification_id]).await;
     crate::database::models::DBUser::clear_caches(&[(user.id, None)], &redis)
         .await
         .wrap_internal_err("clearing cached data from Redis")?;
@@ -3056,30 +3066,27 @@ pub async fn change_password(
             .wrap_internal_err("removing authentication flow from Redis")?;
     }
 
-    if update_password.is_some() {
+    let notification_id = if update_password.is_some() {
         NotificationBuilder {
             body: NotificationBody::PasswordChanged,
         }
         .insert(user.id, &mut transaction, &redis)
         .await
-        .wrap_internal_err(
-            "inserting database records for `change_password`",
-        )?;
+        .wrap_internal_err("inserting database records for `change_password`")?
     } else {
         NotificationBuilder {
             body: NotificationBody::PasswordRemoved,
         }
         .insert(user.id, &mut transaction, &redis)
         .await
-        .wrap_internal_err(
-            "inserting database records for `change_password`",
-        )?;
-    }
+        .wrap_internal_err("inserting database records for `change_password`")?
+    };
 
     transaction
         .commit()
         .await
         .wrap_internal_err("committing database transaction")?;
+    broadcast_realtime_notifications(&redis, vec![notification_id]).await;
     crate::database::models::DBUser::clear_caches(&[(user.id, None)], &redis)
         .await
         .wrap_internal_err("clearing cached data from Redis")?;
@@ -3172,8 +3179,9 @@ pub async fn set_email(
     .await
     .wrap_internal_err("querying database for `set_email`")?;
 
+    let mut notification_ids = Vec::new();
     if let Some(user_email) = user.email.clone() {
-        NotificationBuilder {
+        let notification_id = NotificationBuilder {
             body: NotificationBody::EmailChanged {
                 new_email: email_address.email.clone(),
                 to_email: user_email,
@@ -3182,6 +3190,7 @@ pub async fn set_email(
         .insert(user.id.into(), &mut transaction, &redis)
         .await
         .wrap_internal_err("inserting database records for `set_email`")?;
+        notification_ids.push(notification_id);
     }
 
     if let Some(customer_id) = user
@@ -3225,6 +3234,7 @@ pub async fn set_email(
         .commit()
         .await
         .wrap_internal_err("committing database transaction")?;
+    broadcast_realtime_notifications(&redis, notification_ids).await;
 
     crate::database::models::DBUser::clear_caches(
         &[(user.id.into(), None)],
@@ -12,7 +12,8 @@ use crate::routes::ApiError;
 use crate::util::error::Context;
 use crate::util::gotenberg::{
     GeneratedPdfType, MODRINTH_GENERATED_PDF_TYPE, MODRINTH_PAYMENT_ID,
-    payment_statement_key,
+    MODRINTH_REVENUE_STATEMENT_ID, payment_statement_key,
+    revenue_statement_key,
 };
 use crate::util::guards::internal_network_guard;
 use xredis::RedisPool;
@@ -45,6 +46,7 @@ pub async fn success_callback(
         ModrinthGeneratedPdfType,
     >,
     maybe_payment_id: Option<web::Header<ModrinthPaymentId>>,
+    maybe_revenue_statement_id: Option<web::Header<ModrinthRevenueStatementId>>,
     body: web::Bytes,
     redis: web::Data<RedisPool>,
 ) -> Result<(), ApiError> {
@@ -54,16 +56,17 @@ pub async fn success_callback(
         ?disposition_parameters,
         r#type = r#type.as_str(),
         ?maybe_payment_id,
+        ?maybe_revenue_statement_id,
         body.len = body.len(),
         "Received Gotenberg generated PDF"
     );
 
-    let payout_id = maybe_payment_id
-        .wrap_request_err("no payout ID for document")?
-        .0
-        .0;
-
-    let response_key = payment_statement_key(redis.get_ref(), &payout_id);
+    let response_key = response_key(
+        &redis,
+        r#type,
+        maybe_payment_id,
+        maybe_revenue_statement_id,
+    )?;
     let mut redis = redis
         .connect()
         .await
@@ -116,6 +119,7 @@ pub async fn error_callback(
         ModrinthGeneratedPdfType,
     >,
     maybe_payment_id: Option<web::Header<ModrinthPaymentId>>,
+    maybe_revenue_statement_id: Option<web::Header<ModrinthRevenueStatementId>>,
     web::Json(error_body): web::Json<GotenbergError>,
     redis: web::Data<RedisPool>,
 ) -> Result<(), ApiError> {
@@ -123,15 +127,17 @@ pub async fn error_callback(
         %trace,
         r#type = r#type.as_str(),
         ?maybe_payment_id,
+        ?maybe_revenue_statement_id,
         ?error_body,
         "Received Gotenberg error webhook"
     );
 
-    let payout_id = maybe_payment_id
-        .wrap_request_err("no payout ID for document")?
-        .0
-        .0;
-    let response_key = payment_statement_key(redis.get_ref(), &payout_id);
+    let response_key = response_key(
+        &redis,
+        r#type,
+        maybe_payment_id,
+        maybe_revenue_statement_id,
+    )?;
     let mut redis = redis
         .connect()
         .await
@@ -151,6 +157,32 @@ pub async fn error_callback(
     Ok(())
 }
 
+/// Gets the Redis key which the instance waiting for a document is listening
+/// on, based on the type of the document.
+fn response_key(
+    redis: &RedisPool,
+    r#type: GeneratedPdfType,
+    maybe_payment_id: Option<web::Header<ModrinthPaymentId>>,
+    maybe_revenue_statement_id: Option<web::Header<ModrinthRevenueStatementId>>,
+) -> Result<String, ApiError> {
+    match r#type {
+        GeneratedPdfType::PaymentStatement => {
+            let payout_id = maybe_payment_id
+                .wrap_request_err("no payout ID for document")?
+                .0
+                .0;
+            Ok(payment_statement_key(redis, &payout_id))
+        }
+        GeneratedPdfType::RevenueStatement => {
+            let statement_id = maybe_revenue_statement_id
+                .wrap_request_err("no revenue statement ID for document")?
+                .0
+                .0;
+            Ok(revenue_statement_key(redis, &statement_id))
+        }
+    }
+}
+
 #[derive(Debug)]
 struct GotenbergTrace(String);
 
@@ -232,3 +264,32 @@ impl header::Header for ModrinthPaymentId {
             .map(|id| Self(PayoutId(id)))
     }
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModrinthRevenueStatementId(pub String);
+
+impl header::TryIntoHeaderValue for ModrinthRevenueStatementId {
+    type Error = header::InvalidHeaderValue;
+
+    fn try_into_value(self) -> Result<header::HeaderValue, Self::Error> {
+        header::HeaderValue::from_str(&self.0)
+    }
+}
+
+impl header::Header for ModrinthRevenueStatementId {
+    fn name() -> header::HeaderName {
+        MODRINTH_REVENUE_STATEMENT_ID
+    }
+
+    fn parse<M: HttpMessage>(m: &M) -> Result<Self, ParseError> {
+        m.headers()
+            .get(Self::name())
+            .ok_or(ParseError::Header)?
+            .to_str()
+            .map_err(|_| ParseError::Header)
+            .and_then(|s| {
+                parse_base62(s).map_err(|_| ParseError::Header)?;
+                Ok(Self(s.to_owned()))
+            })
+    }
+}
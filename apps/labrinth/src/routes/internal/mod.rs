@@ -133,6 +133,13 @@ pub fn config(cfg: &mut web::ServiceConfig) {
 		moderation::external_license::add_file,
 		moderation::external_license::reassign_file,
 		moderation::external_license::update_license,
+		moderation::macros::list_macros,
+		moderation::macros::create_macro,
+		moderation::macros::edit_macro,
+		moderation::macros::delete_macro,
+		moderation::macros::apply_macro,
+		moderation::reports::get_report_groups,
+		moderation::reports::close_report_group,
 		affiliate::ingest_click,
 		affiliate::get_all,
 		affiliate::create,
@@ -0,0 +1,618 @@
+use std::collections::HashMap;
+
+use actix_web::{HttpRequest, HttpResponse, delete, get, patch, post, web};
+use chrono::Utc;
+use eyre::eyre;
+use serde::Deserialize;
+use utoipa::ToSchema;
+use xredis::RedisPool;
+
+use crate::auth::check_is_moderator_from_headers;
+use crate::database::PgPool;
+use crate::database::models::ids::generate_moderation_macro_id;
+use crate::database::models::moderation_macro_item::DBModerationMacro;
+use crate::database::models::thread_item::ThreadMessageBuilder;
+use crate::database::models::{
+    DBModerationLock, DBModerationNote, DBProject, DBTeamId, DBUserId,
+};
+use crate::env::ENV;
+use crate::models::ids::{ModerationMacroId, ProjectId};
+use crate::models::moderation_macros::{
+    ModerationMacro, PROJECT_VARIABLES, macro_variables, render_template,
+};
+use crate::models::pats::Scopes;
+use crate::models::projects::{Project, ProjectStatus};
+use crate::models::threads::MessageBody;
+use crate::models::users::User;
+use crate::queue::session::AuthQueue;
+use crate::routes::ApiError;
+use crate::routes::v3::projects::{change_project_status, log_project_edit};
+use crate::search::SearchState;
+use crate::sync::realtime::{
+    broadcast_project_status, broadcast_realtime_notifications,
+    broadcast_thread_message,
+};
+use crate::util::error::Context;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(list_macros)
+        .service(create_macro)
+        .service(edit_macro)
+        .service(delete_macro)
+        .service(apply_macro);
+}
+
+const MAX_NAME_LENGTH: usize = 64;
+const MAX_TEMPLATE_LENGTH: usize = 65536;
+/// The longest thread message that may be posted, as when posting to the
+/// thread directly.
+const MAX_MESSAGE_LENGTH: usize = 65536;
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateModerationMacro {
+    pub name: String,
+    /// Template for the thread message posted when the macro is applied.
+    pub message: String,
+    /// Whether the thread message is only visible to moderators.
+    #[serde(default)]
+    pub private: bool,
+    /// Status to set the project to when the macro is applied.
+    #[serde(default)]
+    pub status: Option<ProjectStatus>,
+    /// Template for the paragraph added to the project owner's moderation
+    /// note when the macro is applied.
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct EditModerationMacro {
+    pub name: Option<String>,
+    pub message: Option<String>,
+    pub private: Option<bool>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "::serde_with::rust::double_option"
+    )]
+    pub status: Option<Option<ProjectStatus>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "::serde_with::rust::double_option"
+    )]
+    pub note: Option<Option<String>>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ApplyModerationMacro {
+    pub project_id: ProjectId,
+    /// Values of the macro's variables. Project variables are filled in
+    /// from the project and can't be given here.
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
+/// List all moderation macros.
+#[utoipa::path(
+	context_path = "/moderation/macros",
+	tag = "moderation",
+	responses((status = OK, body = inline(Vec<ModerationMacro>)))
+)]
+#[get("")]
+pub async fn list_macros(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<web::Json<Vec<ModerationMacro>>, ApiError> {
+    check_is_moderator_from_headers(
+        &req,
+        &**pool,
+        &redis,
+        &session_queue,
+        Scopes::PROJECT_READ,
+    )
+    .await
+    .wrap_auth_err("authenticating moderation macro list")?;
+
+    let macros = DBModerationMacro::get_all(&**pool)
+        .await
+        .wrap_internal_err("fetching moderation macros from database")?;
+
+    Ok(web::Json(macros.into_iter().map(Into::into).collect()))
+}
+
+/// Create a moderation macro.
+///
+/// Only admins may manage macros.
+#[utoipa::path(
+	context_path = "/moderation/macros",
+	tag = "moderation",
+	request_body = CreateModerationMacro,
+	responses((status = OK, body = ModerationMacro))
+)]
+#[post("")]
+pub async fn create_macro(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    body: web::Json<CreateModerationMacro>,
+) -> Result<web::Json<ModerationMacro>, ApiError> {
+    let user = check_is_admin(&req, &pool, &redis, &session_queue).await?;
+    let body = body.into_inner();
+
+    let name = validate_name(&body.name)?;
+    if let Some(status) = body.status {
+        validate_status(status)?;
+    }
+    validate_templates(&body.message, body.note.as_deref())?;
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .wrap_internal_err("starting database transaction")?;
+
+    let existing = DBModerationMacro::get_all(&mut transaction)
+        .await
+        .wrap_internal_err("fetching moderation macros from database")?;
+    if existing.iter().any(|x| x.name == name) {
+        return Err(ApiError::Request(eyre!(
+            "a macro with this name already exists"
+        )));
+    }
+
+    let now = Utc::now();
+    let moderation_macro = DBModerationMacro {
+        id: generate_moderation_macro_id(&mut transaction)
+            .await
+            .wrap_internal_err("generating moderation macro ID")?,
+        name,
+        message: body.message,
+        private: body.private,
+        status: body.status,
+        note: body.note,
+        created: now,
+        last_modified: now,
+        last_modified_by: Some(user.id.into()),
+        uses: 0,
+        last_used: None,
+    };
+    moderation_macro
+        .insert(&mut transaction)
+        .await
+        .wrap_internal_err("inserting moderation macro")?;
+
+    transaction
+        .commit()
+        .await
+        .wrap_internal_err("committing database transaction")?;
+
+    Ok(web::Json(moderation_macro.into()))
+}
+
+/// Edit a moderation macro.
+#[utoipa::path(
+	context_path = "/moderation/macros",
+	tag = "moderation",
+	request_body = EditModerationMacro,
+	responses((status = NO_CONTENT))
+)]
+#[patch("/{id}")]
+pub async fn edit_macro(
+    req: HttpRequest,
+    info: web::Path<(ModerationMacroId,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    body: web::Json<EditModerationMacro>,
+) -> Result<HttpResponse, ApiError> {
+    let user = check_is_admin(&req, &pool, &redis, &session_queue).await?;
+    let id = info.into_inner().0;
+    let body = body.into_inner();
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .wrap_internal_err("starting database transaction")?;
+
+    let mut moderation_macro =
+        DBModerationMacro::get(id.into(), &mut transaction)
+            .await
+            .wrap_internal_err("fetching moderation macro from database")?
+            .wrap_not_found_err("moderation macro not found")?;
+
+    if let Some(name) = &body.name {
+        let name = validate_name(name)?;
+        if name != moderation_macro.name {
+            let existing = DBModerationMacro::get_all(&mut transaction)
+                .await
+                .wrap_internal_err(
+                "fetching moderation macros from database",
+            )?;
+            if existing.iter().any(|x| x.name == name) {
+                return Err(ApiError::Request(eyre!(
+                    "a macro with this name already exists"
+                )));
+            }
+        }
+        moderation_macro.name = name;
+    }
+    if let Some(message) = body.message {
+        moderation_macro.message = message;
+    }
+    if let Some(private) = body.private {
+        moderation_macro.private = private;
+    }
+    if let Some(status) = body.status {
+        if let Some(status) = status {
+            validate_status(status)?;
+        }
+        moderation_macro.status = status;
+    }
+    if let Some(note) = body.note {
+        moderation_macro.note = note;
+    }
+    validate_templates(
+        &moderation_macro.message,
+        moderation_macro.note.as_deref(),
+    )?;
+
+    moderation_macro.last_modified = Utc::now();
+    moderation_macro.last_modified_by = Some(user.id.into());
+    moderation_macro
+        .update(&mut transaction)
+        .await
+        .wrap_internal_err("updating moderation macro")?;
+
+    transaction
+        .commit()
+        .await
+        .wrap_internal_err("committing database transaction")?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Delete a moderation macro.
+#[utoipa::path(
+	context_path = "/moderation/macros",
+	tag = "moderation",
+	responses((status = NO_CONTENT))
+)]
+#[delete("/{id}")]
+pub async fn delete_macro(
+    req: HttpRequest,
+    info: web::Path<(ModerationMacroId,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<HttpResponse, ApiError> {
+    check_is_admin(&req, &pool, &redis, &session_queue).await?;
+    let id = info.into_inner().0;
+
+    let removed = DBModerationMacro::remove(id.into(), &**pool)
+        .await
+        .wrap_internal_err("deleting moderation macro")?;
+    if !removed {
+        return Err(ApiError::NotFound(eyre!("moderation macro not found")));
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Apply a moderation macro to a project.
+///
+/// Posts the macro's message in the project's thread, sets the project's
+/// status and adds to its owner's moderation note, as the macro specifies,
+/// all at once.
+#[utoipa::path(
+	context_path = "/moderation/macros",
+	tag = "moderation",
+	request_body = ApplyModerationMacro,
+	responses((status = NO_CONTENT))
+)]
+#[post("/{id}/apply")]
+pub async fn apply_macro(
+    req: HttpRequest,
+    info: web::Path<(ModerationMacroId,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    search_state: web::Data<SearchState>,
+    body: web::Json<ApplyModerationMacro>,
+) -> Result<HttpResponse, ApiError> {
+    let user = check_is_moderator_from_headers(
+        &req,
+        &**pool,
+        &redis,
+        &session_queue,
+        Scopes::PROJECT_WRITE,
+    )
+    .await
+    .wrap_auth_err("authenticating moderation macro application")?;
+    let id = info.into_inner().0;
+    let body = body.into_inner();
+
+    let moderation_macro = DBModerationMacro::get(id.into(), &**pool)
+        .await
+        .wrap_internal_err("fetching moderation macro from database")?
+        .wrap_not_found_err("moderation macro not found")?;
+    let project = DBProject::get_id(body.project_id.into(), &**pool, &redis)
+        .await
+        .wrap_internal_err("fetching project from database")?
+        .wrap_request_err("project not found")?;
+
+    if let Some(name) = body
+        .variables
+        .keys()
+        .find(|x| PROJECT_VARIABLES.contains(&x.as_str()))
+    {
+        return Err(ApiError::Request(eyre!(
+            "`{name}` is filled in from the project and can't be given"
+        )));
+    }
+    let mut values = body.variables;
+    let slug_or_id = project
+        .inner
+        .slug
+        .clone()
+        .unwrap_or_else(|| body.project_id.to_string());
+    values.extend([
+        ("project_id".to_string(), body.project_id.to_string()),
+        ("project_name".to_string(), project.inner.name.clone()),
+        (
+            "project_url".to_string(),
+            format!("{}/project/{slug_or_id}", ENV.SITE_URL),
+        ),
+        ("project_slug".to_string(), slug_or_id),
+    ]);
+
+    let message = render_template(&moderation_macro.message, &values)
+        .wrap_request_err("rendering macro message")?;
+    if message.len() > MAX_MESSAGE_LENGTH {
+        return Err(ApiError::Request(eyre!(
+            "rendered macro message must be at most {MAX_MESSAGE_LENGTH} bytes"
+        )));
+    }
+    let note = moderation_macro
+        .note
+        .as_deref()
+        .map(|note| render_template(note, &values))
+        .transpose()
+        .wrap_request_err("rendering macro note")?;
+    let status = moderation_macro
+        .status
+        .filter(|status| *status != project.inner.status);
+
+    if let Some(status) = status {
+        check_moderation_lock(&project.inner, status, &user, &pool).await?;
+    }
+
+    let old_project = Project::from(project.clone());
+    let mut transaction = pool
+        .begin()
+        .await
+        .wrap_internal_err("starting database transaction")?;
+
+    let mut message_ids = vec![
+        ThreadMessageBuilder {
+            author_id: Some(user.id.into()),
+            body: MessageBody::Text {
+                body: message,
+                private: moderation_macro.private,
+                replying_to: None,
+                associated_images: Vec::new(),
+            },
+            thread_id: project.thread_id,
+            hide_identity: user.role.is_mod(),
+        }
+        .insert(&mut transaction)
+        .await
+        .wrap_internal_err("inserting macro thread message")?,
+    ];
+
+    let mut notification_ids = Vec::new();
+    if let Some(status) = status {
+        let (message_id, status_notification_ids) = change_project_status(
+            &project,
+            status,
+            &user,
+            true,
+            &pool,
+            &redis,
+            &mut transaction,
+        )
+        .await?;
+        message_ids.push(message_id);
+        notification_ids = status_notification_ids;
+        log_project_edit(user.id.into(), old_project, &mut transaction, &redis)
+            .await?;
+    }
+
+    // Notes are kept on the organization of organization-owned projects,
+    // and on the owner of any others.
+    let note_owner = if let Some(note) = &note {
+        let owner = match project.inner.organization_id {
+            Some(organization_id) => (None, Some(organization_id)),
+            None => {
+                let owner_id = sqlx::query_scalar!(
+                    "
+                    SELECT user_id
+                    FROM team_members
+                    WHERE team_id = $1 AND is_owner = TRUE
+                    ",
+                    project.inner.team_id as DBTeamId,
+                )
+                .fetch_one(&mut transaction)
+                .await
+                .wrap_internal_err("fetching project owner")?;
+                (Some(DBUserId(owner_id)), None)
+            }
+        };
+
+        DBModerationNote::append(
+            owner.0,
+            owner.1,
+            user.id.into(),
+            note,
+            &mut transaction,
+        )
+        .await
+        .wrap_internal_err("appending to moderation note")?;
+        Some(owner)
+    } else {
+        None
+    };
+
+    DBModerationMacro::record_use(moderation_macro.id, &mut transaction)
+        .await
+        .wrap_internal_err("recording moderation macro use")?;
+
+    transaction
+        .commit()
+        .await
+        .wrap_internal_err("committing database transaction")?;
+
+    match note_owner {
+        Some((Some(user_id), _)) => {
+            DBModerationNote::clear_user_cache(user_id, &redis)
+                .await
+                .wrap_internal_err("clearing moderation note cache")?;
+        }
+        Some((_, Some(organization_id))) => {
+            DBModerationNote::clear_organization_cache(organization_id, &redis)
+                .await
+                .wrap_internal_err("clearing moderation note cache")?;
+        }
+        _ => {}
+    }
+
+    for message_id in message_ids {
+        broadcast_thread_message(
+            &redis,
+            project.thread_id.into(),
+            message_id.into(),
+        )
+        .await;
+    }
+    broadcast_realtime_notifications(&redis, notification_ids).await;
+
+    if let Some(status) = status {
+        broadcast_project_status(
+            &redis,
+            project.inner.id.into(),
+            project.inner.status,
+            status,
+        )
+        .await;
+
+        DBProject::clear_cache(
+            project.inner.id,
+            project.inner.slug.clone(),
+            None,
+            &redis,
+        )
+        .await
+        .wrap_internal_err("clearing cached data from Redis")?;
+        if project.inner.status.is_searchable() && !status.is_searchable() {
+            search_state
+                .queue
+                .push_project_removal(project.inner.id.into())
+                .await;
+        } else {
+            search_state
+                .queue
+                .push_project_with_all_versions_change(project.inner.id.into())
+                .await;
+        }
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+async fn check_is_admin(
+    req: &HttpRequest,
+    pool: &PgPool,
+    redis: &RedisPool,
+    session_queue: &AuthQueue,
+) -> Result<User, ApiError> {
+    let user = check_is_moderator_from_headers(
+        req,
+        pool,
+        redis,
+        session_queue,
+        Scopes::PROJECT_WRITE,
+    )
+    .await
+    .wrap_auth_err("authenticating moderation macro management")?;
+    if !user.role.is_admin() {
+        return Err(ApiError::Auth(eyre!(
+            "only admins may manage moderation macros"
+        )));
+    }
+    Ok(user)
+}
+
+/// Blocks non-admin moderators from completing a review while another
+/// moderator holds its checklist lock, as when editing the project.
+async fn check_moderation_lock(
+    project: &DBProject,
+    status: ProjectStatus,
+    user: &User,
+    pool: &PgPool,
+) -> Result<(), ApiError> {
+    if !user.role.is_admin()
+        && project.status == ProjectStatus::Processing
+        && status != ProjectStatus::Processing
+        && let Some(lock) = DBModerationLock::get_with_user(project.id, pool)
+            .await
+            .wrap_internal_err("fetching moderation lock from database")?
+        && lock.moderator_id != DBUserId::from(user.id)
+        && !lock.expired
+    {
+        return Err(ApiError::Auth(eyre!(
+            "This project is currently being moderated by @{}. Please wait for them to finish or for the lock to expire.",
+            lock.moderator_username
+        )));
+    }
+    Ok(())
+}
+
+fn validate_name(name: &str) -> Result<String, ApiError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(ApiError::Request(eyre!(
+            "macro name must be between 1 and {MAX_NAME_LENGTH} characters"
+        )));
+    }
+    Ok(name.to_string())
+}
+
+fn validate_status(status: ProjectStatus) -> Result<(), ApiError> {
+    match status {
+        ProjectStatus::Unknown
+        | ProjectStatus::Scheduled
+        | ProjectStatus::Draft => Err(ApiError::Request(eyre!(
+            "macros can't set a project's status to `{status}`"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn validate_templates(
+    message: &str,
+    note: Option<&str>,
+) -> Result<(), ApiError> {
+    if message.trim().is_empty()
+        || message.len() > MAX_TEMPLATE_LENGTH
+        || note.is_some_and(|x| x.len() > MAX_TEMPLATE_LENGTH)
+    {
+        return Err(ApiError::Request(eyre!(
+            "macro message must be between 1 and {MAX_TEMPLATE_LENGTH} bytes, and note at most {MAX_TEMPLATE_LENGTH}"
+        )));
+    }
+    macro_variables(message, note)
+        .wrap_request_err("invalid macro template")?;
+    Ok(())
+}
@@ -23,7 +23,9 @@ use std::collections::HashMap;
 use xredis::RedisPool;
 
 pub mod external_license;
+pub mod macros;
 mod ownership;
+pub mod reports;
 pub mod tech_review;
 
 pub fn config(cfg: &mut actix_web::web::ServiceConfig) {
@@ -38,6 +40,8 @@ pub fn config(cfg: &mut actix_web::web::ServiceConfig) {
         .service(release_lock_beacon)
         .service(delete_all_locks)
         .service(web::scope("/tech-review").configure(tech_review::config))
+        .service(web::scope("/macros").configure(macros::config))
+        .service(web::scope("/reports").configure(reports::config))
         .service(
             web::scope("/external-license").configure(external_license::config),
         );
@@ -0,0 +1,315 @@
+use actix_web::{HttpRequest, HttpResponse, get, post, web};
+use ariadne::ids::base62_impl::{parse_base62, to_base62};
+use eyre::eyre;
+use serde::Deserialize;
+use xredis::RedisPool;
+
+use crate::auth::check_is_moderator_from_headers;
+use crate::database::PgPool;
+use crate::database::models::delphi_report_item::DelphiStatus;
+use crate::database::models::notification_item::NotificationBuilder;
+use crate::database::models::thread_item::ThreadMessageBuilder;
+use crate::database::models::{DBReportId, DBThreadId, DBUserId};
+use crate::models::notifications::NotificationBody;
+use crate::models::pats::Scopes;
+use crate::models::reports::{ItemType, ReportGroup, ReportGroupKey};
+use crate::models::threads::MessageBody;
+use crate::queue::session::AuthQueue;
+use crate::routes::ApiError;
+use crate::sync::realtime::{
+    broadcast_realtime_notifications, broadcast_thread_message,
+};
+use crate::util::error::Context;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_report_groups).service(close_report_group);
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct ReportGroupsRequestOptions {
+    /// How many groups to fetch.
+    #[serde(default = "default_count")]
+    pub count: u16,
+    /// How many groups to skip.
+    #[serde(default)]
+    pub offset: u32,
+}
+
+fn default_count() -> u16 {
+    100
+}
+
+/// List open reports, grouped by the reported item and report type.
+///
+/// Groups are sorted by priority, highest first, and then by their oldest
+/// report.
+#[utoipa::path(
+	context_path = "/moderation/reports",
+	tag = "moderation",
+	params(
+		("count" = Option<u16>, Query),
+		("offset" = Option<u32>, Query)
+	),
+	responses((status = OK, body = inline(Vec<ReportGroup>)))
+)]
+#[get("/groups")]
+pub async fn get_report_groups(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    web::Query(opts): web::Query<ReportGroupsRequestOptions>,
+) -> Result<web::Json<Vec<ReportGroup>>, ApiError> {
+    check_is_moderator_from_headers(
+        &req,
+        &**pool,
+        &redis,
+        &session_queue,
+        Scopes::REPORT_READ,
+    )
+    .await
+    .wrap_auth_err("authenticating report group list")?;
+
+    // Reports are grouped by the same key as `ReportGroupKey`, and each
+    // reporter's trust is only counted once per group.
+    let groups = sqlx::query!(
+        r#"
+        WITH open_reports AS (
+            SELECT
+                r.id,
+                r.reporter,
+                r.created,
+                rt.name report_type,
+                CASE
+                    WHEN r.mod_id IS NOT NULL THEN 'project'
+                    WHEN r.version_id IS NOT NULL THEN 'version'
+                    WHEN r.user_id IS NOT NULL THEN 'user'
+                    WHEN r.shared_instance_id IS NOT NULL THEN 'shared-instance'
+                    ELSE 'unknown'
+                END item_type,
+                COALESCE(r.mod_id, r.version_id, r.user_id, r.shared_instance_id) item_id,
+                COALESCE(r.mod_id, v.mod_id) project_id,
+                CASE
+                    WHEN u.id IS NULL THEN 0.0
+                    WHEN u.role IN ('moderator', 'admin') THEN 2.0
+                    WHEN u.created > NOW() - INTERVAL '7 days' THEN 0.25
+                    WHEN u.created > NOW() - INTERVAL '30 days' THEN 0.5
+                    ELSE 1.0
+                END reporter_trust
+            FROM reports r
+            INNER JOIN report_types rt ON rt.id = r.report_type_id
+            LEFT JOIN versions v ON v.id = r.version_id
+            LEFT JOIN users u ON u.id = r.reporter
+            WHERE r.closed = FALSE
+        ),
+        first_reports AS (
+            SELECT
+                *,
+                ROW_NUMBER() OVER (
+                    PARTITION BY item_type, item_id, report_type, reporter
+                ) = 1 first_from_reporter
+            FROM open_reports
+        ),
+        groups AS (
+            SELECT
+                item_type,
+                item_id,
+                report_type,
+                project_id,
+                ARRAY_AGG(id ORDER BY created, id) reports,
+                COUNT(DISTINCT reporter) reporters,
+                MIN(created) first_reported,
+                MAX(created) last_reported,
+                SUM(reporter_trust) FILTER (WHERE first_from_reporter) trust
+            FROM first_reports
+            GROUP BY item_type, item_id, report_type, project_id
+        ),
+        scored AS (
+            SELECT
+                g.*,
+                m.downloads,
+                d.status delphi_status,
+                g.trust
+                    * LOG(GREATEST(COALESCE(m.downloads, 0), 0) + 10)
+                    * CASE d.status
+                        WHEN 'unsafe' THEN 3.0
+                        WHEN 'pending' THEN 1.5
+                        WHEN 'safe' THEN 0.75
+                        ELSE 1.0
+                    END priority
+            FROM groups g
+            LEFT JOIN mods m ON m.id = g.project_id
+            CROSS JOIN LATERAL (
+                SELECT
+                    CASE
+                        WHEN bool_or(di.status = 'unsafe') THEN 'unsafe'
+                        WHEN bool_or(di.status = 'pending') THEN 'pending'
+                        WHEN COUNT(*) > 0 THEN 'safe'
+                    END::delphi_report_issue_status status
+                FROM delphi_issue_details_with_statuses di
+                WHERE di.project_id = g.project_id
+            ) d
+        )
+        SELECT
+            item_type "item_type!",
+            item_id,
+            report_type "report_type!",
+            reports "reports!",
+            reporters "reporters!",
+            first_reported "first_reported!",
+            last_reported "last_reported!",
+            downloads "downloads?",
+            delphi_status "delphi_status?: DelphiStatus",
+            priority::float8 "priority!"
+        FROM scored
+        ORDER BY priority DESC, first_reported, item_type, item_id, report_type
+        LIMIT $1 OFFSET $2
+        "#,
+        i64::from(opts.count),
+        i64::from(opts.offset),
+    )
+    .fetch_all(&**pool)
+    .await
+    .wrap_internal_err("fetching report groups from database")?
+    .into_iter()
+    .map(|row| ReportGroup {
+        key: ReportGroupKey {
+            item_type: ItemType::from_string(&row.item_type),
+            item_id: row
+                .item_id
+                .map(|id| to_base62(id as u64))
+                .unwrap_or_default(),
+            report_type: row.report_type,
+        },
+        reports: row
+            .reports
+            .into_iter()
+            .map(|id| DBReportId(id).into())
+            .collect(),
+        reporters: row.reporters as u32,
+        first_reported: row.first_reported,
+        last_reported: row.last_reported,
+        downloads: row.downloads,
+        delphi_status: row.delphi_status,
+        priority: row.priority,
+    })
+    .collect();
+
+    Ok(web::Json(groups))
+}
+
+/// Close every open report in a group.
+///
+/// Each report's thread is closed and its reporter notified, as when
+/// closing the reports one by one.
+#[utoipa::path(
+	context_path = "/moderation/reports",
+	tag = "moderation",
+	request_body = ReportGroupKey,
+	responses((status = NO_CONTENT))
+)]
+#[post("/groups/close")]
+pub async fn close_report_group(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    body: web::Json<ReportGroupKey>,
+) -> Result<HttpResponse, ApiError> {
+    let user = check_is_moderator_from_headers(
+        &req,
+        &**pool,
+        &redis,
+        &session_queue,
+        Scopes::REPORT_WRITE,
+    )
+    .await
+    .wrap_auth_err("authenticating report group closure")?;
+    let key = body.into_inner();
+
+    let item_id = (!key.item_id.is_empty())
+        .then(|| parse_base62(&key.item_id))
+        .transpose()
+        .wrap_request_err("invalid item ID")?
+        .map(|id| id as i64);
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .wrap_internal_err("starting database transaction")?;
+
+    // Reports closed since the group was listed, including by a concurrent
+    // request, aren't returned and so aren't messaged about again.
+    let reports = sqlx::query!(
+        "
+        UPDATE reports r
+        SET closed = TRUE
+        FROM report_types rt, threads t
+        WHERE
+            rt.id = r.report_type_id
+            AND t.report_id = r.id
+            AND r.closed = FALSE
+            AND rt.name = $1
+            AND CASE
+                WHEN r.mod_id IS NOT NULL THEN 'project'
+                WHEN r.version_id IS NOT NULL THEN 'version'
+                WHEN r.user_id IS NOT NULL THEN 'user'
+                WHEN r.shared_instance_id IS NOT NULL THEN 'shared-instance'
+                ELSE 'unknown'
+            END = $2
+            AND COALESCE(r.mod_id, r.version_id, r.user_id, r.shared_instance_id)
+                IS NOT DISTINCT FROM $3
+        RETURNING r.id, r.reporter, t.id thread_id
+        ",
+        key.report_type,
+        key.item_type.as_str(),
+        item_id,
+    )
+    .fetch_all(&mut transaction)
+    .await
+    .wrap_internal_err("closing reports")?;
+    if reports.is_empty() {
+        return Err(ApiError::NotFound(eyre!(
+            "no open reports match this group"
+        )));
+    }
+
+    let mut closure_messages = Vec::with_capacity(reports.len());
+    let mut notification_ids = Vec::with_capacity(reports.len());
+    for report in &reports {
+        let thread_id = DBThreadId(report.thread_id);
+        let message_id = ThreadMessageBuilder {
+            author_id: Some(user.id.into()),
+            body: MessageBody::ThreadClosure,
+            thread_id,
+            hide_identity: user.role.is_mod(),
+        }
+        .insert(&mut transaction)
+        .await
+        .wrap_internal_err("inserting report closure message")?;
+        closure_messages.push((thread_id, message_id));
+
+        let notification_id = NotificationBuilder {
+            body: NotificationBody::ReportStatusUpdated {
+                report_id: DBReportId(report.id).into(),
+            },
+        }
+        .insert(DBUserId(report.reporter), &mut transaction, &redis)
+        .await
+        .wrap_internal_err("inserting report status notification")?;
+        notification_ids.push(notification_id);
+    }
+
+    transaction
+        .commit()
+        .await
+        .wrap_internal_err("committing database transaction")?;
+
+    for (thread_id, message_id) in closure_messages {
+        broadcast_thread_message(&redis, thread_id.into(), message_id.into())
+            .await;
+    }
+    broadcast_realtime_notifications(&redis, notification_ids).await;
+
+    Ok(HttpResponse::NoContent().finish())
+}
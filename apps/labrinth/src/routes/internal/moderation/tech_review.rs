@@ -21,6 +21,7 @@ use crate::{
                 DBDelphiReport, DelphiSeverity, DelphiStatus, DelphiVerdict,
                 ReportIssueDetail,
             },
+            project_webhook_item::DBProjectWebhookDelivery,
             thread_item::ThreadMessageBuilder,
             version_item::VersionQueryResult,
         },
@@ -28,6 +29,7 @@ use crate::{
     models::{
         ids::{FileId, ProjectId, ThreadId, VersionId},
         pats::Scopes,
+        project_webhooks::ProjectWebhookEvent,
         projects::{Project, ProjectStatus},
         threads::{MessageBody, Thread},
     },
@@ -40,6 +42,7 @@ use crate::{
         },
     },
     search::SearchState,
+    sync::realtime::{broadcast_project_status, broadcast_thread_message},
     util::error::Context,
 };
 use eyre::eyre;
@@ -1095,8 +1098,12 @@ pub async fn submit_report(
     .await
     .wrap_internal_err("failed to update reports")?;
 
+    // Realtime events, broadcast once committed
+    let mut message_ids = Vec::new();
+    let mut status_change = None;
+
     if let Some(body) = submit_report.message {
-        ThreadMessageBuilder {
+        let message_id = ThreadMessageBuilder {
             author_id: Some(user.id.into()),
             body: MessageBody::Text {
                 body,
@@ -1110,10 +1117,11 @@ pub async fn submit_report(
         .insert(&mut txn)
         .await
         .wrap_internal_err("failed to add moderator message")?;
+        message_ids.push(message_id);
     }
 
     let verdict = submit_report.verdict;
-    ThreadMessageBuilder {
+    let message_id = ThreadMessageBuilder {
         author_id: Some(user.id.into()),
         body: MessageBody::TechReview { verdict },
         thread_id: record.thread_id,
@@ -1122,6 +1130,7 @@ pub async fn submit_report(
     .insert(&mut txn)
     .await
     .wrap_internal_err("failed to add tech review message")?;
+    message_ids.push(message_id);
 
     if verdict == DelphiVerdict::Unsafe {
         let record = sqlx::query!(
@@ -1153,7 +1162,7 @@ pub async fn submit_report(
         .await
         .wrap_internal_err("failed to mark project as rejected")?;
 
-        ThreadMessageBuilder {
+        let message_id = ThreadMessageBuilder {
             author_id: Some(user.id.into()),
             body: MessageBody::StatusChange {
                 new_status: ProjectStatus::Rejected,
@@ -1165,12 +1174,43 @@ pub async fn submit_report(
         .insert(&mut txn)
         .await
         .wrap_internal_err("failed to add tech review message")?;
+        message_ids.push(message_id);
+        status_change = Some(ProjectStatus::from_string(&record.old_status));
+
+        DBProjectWebhookDelivery::insert_for_event(
+            &ProjectWebhookEvent::ProjectStatusChanged {
+                project_id: project_id.into(),
+                old_status: ProjectStatus::from_string(&record.old_status),
+                new_status: ProjectStatus::Rejected,
+            },
+            &mut txn,
+        )
+        .await
+        .wrap_internal_err("failed to queue project webhook deliveries")?;
     }
 
     txn.commit()
         .await
         .wrap_internal_err("failed to commit transaction")?;
 
+    for message_id in message_ids {
+        broadcast_thread_message(
+            &redis,
+            record.thread_id.into(),
+            message_id.into(),
+        )
+        .await;
+    }
+    if let Some(old_status) = status_change {
+        broadcast_project_status(
+            &redis,
+            project_id.into(),
+            old_status,
+            ProjectStatus::Rejected,
+        )
+        .await;
+    }
+
     if verdict == DelphiVerdict::Unsafe {
         crate::routes::v3::projects::clear_project_cache_and_queue_search(
             &redis,
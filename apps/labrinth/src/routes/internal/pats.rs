@@ -19,6 +19,7 @@ use crate::database::models::notification_item::NotificationBuilder;
 use crate::models::notifications::NotificationBody;
 use crate::models::pats::{PersonalAccessToken, Scopes};
 use crate::queue::session::AuthQueue;
+use crate::sync::realtime::broadcast_realtime_notifications;
 use serde::Deserialize;
 use validator::Validate;
 
@@ -165,7 +166,7 @@ pub async fn create_pat(
     .await
     .wrap_internal_err("inserting database records for `create_pat`")?;
 
-    NotificationBuilder {
+    let notification_id = NotificationBuilder {
         body: NotificationBody::PatCreated {
             token_name: name.clone(),
         },
@@ -177,6 +178,7 @@ pub async fn create_pat(
         .commit()
         .await
         .wrap_internal_err("committing database transaction")?;
+    broadcast_realtime_notifications(&redis, vec![notification_id]).await;
 
     database::models::pat_item::DBPersonalAccessToken::clear_cache(
         vec![(None, None, Some(user.id.into()))],
@@ -8,10 +8,14 @@ use crate::models::pats::Scopes;
 use crate::models::users::User;
 use crate::queue::session::AuthQueue;
 use crate::queue::socket::{
-    ActiveSocket, ActiveSockets, SocketId, TunnelSocketType,
+    ActiveSocket, ActiveSockets, SocketId, TunnelSocket, TunnelSocketType,
 };
 use crate::routes::ApiError;
-use crate::sync::friends::{FRIENDS_CHANNEL_NAME, RedisFriendsMessage};
+use crate::sync::friends::{
+    FRIENDS_CHANNEL_NAME, RedisFriendsMessage, RedisTunnelMessage,
+    WorldInviteRecord, get_world_invite, insert_world_invite,
+    tunnel_channel_name,
+};
 use crate::sync::status::{
     get_user_status, push_back_user_expiry, replace_user_status,
 };
@@ -25,6 +29,7 @@ use ariadne::networking::message::{
 };
 use ariadne::users::UserStatus;
 use chrono::Utc;
+use dashmap::mapref::entry::Entry::Vacant;
 use either::Either;
 use futures_util::future::select;
 use futures_util::{StreamExt, TryStreamExt};
@@ -33,6 +38,7 @@ use std::pin::pin;
 use std::sync::atomic::Ordering;
 use tokio::sync::oneshot::error::TryRecvError;
 use tokio::time::{Duration, sleep};
+use uuid::Uuid;
 use xredis::RedisPool;
 
 pub fn config(cfg: &mut actix_web::web::ServiceConfig) {
@@ -287,33 +293,99 @@ pub async fn ws_init(
                     }
                 }
 
-                ClientToServerMessage::SocketListen { .. } => {
-                    // TODO: Listen to socket
-                    // The code below probably won't need changes, but there's no way to connect to
-                    // a tunnel socket yet, so we shouldn't be storing them
-
-                    // let Some(active_socket) = db.sockets.get(&socket_id) else {
-                    //     return;
-                    // };
-                    // let Vacant(entry) = db.tunnel_sockets.entry(socket) else {
-                    //     continue;
-                    // };
-                    // entry.insert(TunnelSocket::new(
-                    //     socket_id,
-                    //     TunnelSocketType::Listening,
-                    // ));
-                    // active_socket.owned_tunnel_sockets.insert(socket);
-                    // let _ = broadcast_friends(
-                    //     user.id,
-                    //     ServerToClientMessage::FriendSocketListening {
-                    //         user: user.id,
-                    //         socket,
-                    //     },
-                    //     &pool,
-                    //     &db,
-                    //     None,
-                    // )
-                    // .await;
+                ClientToServerMessage::SocketListen { socket } => {
+                    let Some(active_socket) = db.sockets.get(&socket_id) else {
+                        continue;
+                    };
+                    let Vacant(entry) = db.tunnel_sockets.entry(socket) else {
+                        continue;
+                    };
+                    entry.insert(TunnelSocket::new(
+                        socket_id,
+                        TunnelSocketType::Listening,
+                    ));
+                    active_socket.owned_tunnel_sockets.insert(socket);
+                }
+                ClientToServerMessage::SocketConnect {
+                    user: friend,
+                    to_socket,
+                    socket,
+                } => {
+                    if db.tunnel_sockets.contains_key(&socket) {
+                        continue;
+                    }
+                    // Only friends who were invited to a world may connect
+                    // to its listening socket
+                    let invite = get_world_invite(to_socket, user.id, &redis)
+                        .await
+                        .ok()
+                        .flatten()
+                        .filter(|invite| invite.owner == friend);
+                    let is_friend = invite.is_some()
+                        && DBFriend::get_friend(
+                            user.id.into(),
+                            friend.into(),
+                            &**pool,
+                        )
+                        .await
+                        .is_ok_and(|x| x.is_some_and(|x| x.accepted));
+                    let (Some(invite), true) = (invite, is_friend) else {
+                        let _ = send_message_to_socket(
+                            &db,
+                            socket_id,
+                            &ServerToClientMessage::SocketClosed { socket },
+                        )
+                        .await;
+                        continue;
+                    };
+
+                    let Some(active_socket) = db.sockets.get(&socket_id) else {
+                        continue;
+                    };
+                    let Vacant(entry) = db.tunnel_sockets.entry(socket) else {
+                        continue;
+                    };
+                    // The friend's end of the connection
+                    let new_socket = Uuid::new_v4();
+                    entry.insert(TunnelSocket::new(
+                        socket_id,
+                        TunnelSocketType::Connected {
+                            connected_to: new_socket,
+                            replica: invite.replica,
+                        },
+                    ));
+                    active_socket.owned_tunnel_sockets.insert(socket);
+                    drop(active_socket);
+
+                    // This has to be handled in order with any data sent
+                    // afterwards, so it goes through the same channel
+                    if invite.replica == db.replica_id {
+                        accept_tunnel_connection(
+                            friend,
+                            to_socket,
+                            new_socket,
+                            socket,
+                            db.replica_id,
+                            &db,
+                            &redis,
+                        )
+                        .await;
+                    } else if send_tunnel_message(
+                        &redis,
+                        invite.replica,
+                        RedisTunnelMessage::Connect {
+                            owner: friend,
+                            to_socket,
+                            new_socket,
+                            connected_to: socket,
+                            replica: db.replica_id,
+                        },
+                    )
+                    .await
+                    .is_err()
+                    {
+                        close_local_tunnel_socket(socket, &db).await;
+                    }
                 }
                 ClientToServerMessage::SocketClose { socket } => {
                     let Some(active_socket) = db.sockets.get(&socket_id) else {
@@ -326,6 +398,7 @@ pub async fn ws_init(
                     {
                         continue;
                     }
+                    drop(active_socket);
                     let Some((_, tunnel_socket)) =
                         db.tunnel_sockets.remove(&socket)
                     else {
@@ -335,25 +408,24 @@ pub async fn ws_init(
                         TunnelSocketType::Listening => {
                             let _ = broadcast_to_local_friends(
                                 user.id,
-                                ServerToClientMessage::FriendSocketStoppedListening { user: user.id },
+                                ServerToClientMessage::FriendSocketStoppedListening {
+                                    user: user.id,
+                                    socket,
+                                },
                                 &pool,
                                 &db,
                             )
                             .await;
                         }
-                        TunnelSocketType::Connected { connected_to } => {
-                            let Some((_, other)) =
-                                db.tunnel_sockets.remove(&connected_to)
-                            else {
-                                continue;
-                            };
-                            let Some(other_user) = db.sockets.get(&other.owner)
-                            else {
-                                continue;
-                            };
-                            let _ = send_message(
-                                &other_user,
-                                &ServerToClientMessage::SocketClosed { socket },
+                        TunnelSocketType::Connected {
+                            connected_to,
+                            replica,
+                        } => {
+                            let _ = close_tunnel_socket(
+                                connected_to,
+                                replica,
+                                &db,
+                                &redis,
                             )
                             .await;
                         }
@@ -367,25 +439,92 @@ pub async fn ws_init(
                     if tunnel_socket.owner != socket_id {
                         continue;
                     }
-                    let TunnelSocketType::Connected { connected_to } =
-                        tunnel_socket.socket_type
+                    let TunnelSocketType::Connected {
+                        connected_to,
+                        replica,
+                    } = tunnel_socket.socket_type
                     else {
                         continue;
                     };
-                    let Some(other_tunnel) =
-                        db.tunnel_sockets.get(&connected_to)
-                    else {
+                    drop(tunnel_socket);
+
+                    if replica == db.replica_id {
+                        let other_owner = db
+                            .tunnel_sockets
+                            .get(&connected_to)
+                            .map(|other_tunnel| other_tunnel.owner);
+                        let Some(other_user) =
+                            other_owner.and_then(|x| db.sockets.get(&x))
+                        else {
+                            continue;
+                        };
+                        let _ = send_message(
+                            &other_user,
+                            &ServerToClientMessage::SocketData {
+                                socket: connected_to,
+                                data,
+                            },
+                        )
+                        .await;
+                    } else {
+                        let _ = send_tunnel_message(
+                            &redis,
+                            replica,
+                            RedisTunnelMessage::Data {
+                                socket: connected_to,
+                                data,
+                            },
+                        )
+                        .await;
+                    }
+                }
+                ClientToServerMessage::WorldInvite { to, invite } => {
+                    if !invite.is_valid() {
                         continue;
-                    };
-                    let Some(other_user) = db.sockets.get(&other_tunnel.owner)
-                    else {
+                    }
+                    let is_listening = db
+                        .tunnel_sockets
+                        .get(&invite.socket)
+                        .is_some_and(|tunnel_socket| {
+                            tunnel_socket.owner == socket_id
+                                && matches!(
+                                    tunnel_socket.socket_type,
+                                    TunnelSocketType::Listening
+                                )
+                        });
+                    if !is_listening {
                         continue;
-                    };
-                    let _ = send_message(
-                        &other_user,
-                        &ServerToClientMessage::SocketData {
-                            socket: connected_to,
-                            data,
+                    }
+                    let is_friend = DBFriend::get_friend(
+                        user.id.into(),
+                        to.into(),
+                        &**pool,
+                    )
+                    .await
+                    .is_ok_and(|friend| {
+                        friend.is_some_and(|friend| friend.accepted)
+                    });
+                    if !is_friend
+                        || insert_world_invite(
+                            invite.socket,
+                            to,
+                            &WorldInviteRecord {
+                                owner: user.id,
+                                replica: db.replica_id,
+                            },
+                            &redis,
+                        )
+                        .await
+                        .is_err()
+                    {
+                        continue;
+                    }
+                    let _ = broadcast_friends_message(
+                        &redis,
+                        RedisFriendsMessage::WorldInvite {
+                            from: user.id,
+                            to_user: to,
+                            invite,
                         },
                     )
                     .await;
@@ -410,6 +549,18 @@ pub async fn broadcast_friends_message(
         .map_err(Into::into)
 }
 
+/// Sends tunnel traffic to the launchers connected to `replica`.
+pub async fn send_tunnel_message(
+    redis: &RedisPool,
+    replica: Uuid,
+    message: RedisTunnelMessage,
+) -> Result<(), crate::database::models::DatabaseError> {
+    redis
+        .publish(&tunnel_channel_name(replica), message)
+        .await
+        .map_err(Into::into)
+}
+
 pub async fn broadcast_to_local_friends(
     user_id: UserId,
     message: ServerToClientMessage,
@@ -487,6 +638,18 @@ pub async fn send_message_to_user(
     Ok(())
 }
 
+async fn send_message_to_socket(
+    db: &ActiveSockets,
+    socket_id: SocketId,
+    message: &ServerToClientMessage,
+) -> Result<(), crate::database::models::DatabaseError> {
+    if let Some(socket) = db.sockets.get(&socket_id) {
+        send_message(&socket, message).await?;
+    }
+
+    Ok(())
+}
+
 pub async fn send_notification_to_user(
     db: &ActiveSockets,
     user: UserId,
@@ -546,22 +709,13 @@ pub async fn close_socket(
                     )
                     .await;
                 }
-                TunnelSocketType::Connected { connected_to } => {
-                    let Some((_, other)) =
-                        db.tunnel_sockets.remove(&connected_to)
-                    else {
-                        continue;
-                    };
-                    let Some(other_user) = db.sockets.get(&other.owner) else {
-                        continue;
-                    };
-                    let _ = send_message(
-                        &other_user,
-                        &ServerToClientMessage::SocketClosed {
-                            socket: connected_to,
-                        },
-                    )
-                    .await;
+                TunnelSocketType::Connected {
+                    connected_to,
+                    replica,
+                } => {
+                    let _ =
+                        close_tunnel_socket(connected_to, replica, db, redis)
+                            .await;
                 }
             }
         }
@@ -569,3 +723,94 @@ pub async fn close_socket(
 
     Ok(())
 }
+
+/// Connects a friend's tunnel socket, whose launcher is connected to
+/// `replica`, to a listening socket of `owner` on this replica, creating
+/// `new_socket` as the owner's end of the connection.
+pub async fn accept_tunnel_connection(
+    owner: UserId,
+    to_socket: Uuid,
+    new_socket: Uuid,
+    connected_to: Uuid,
+    replica: Uuid,
+    db: &ActiveSockets,
+    redis: &RedisPool,
+) {
+    let owner_socket = db
+        .tunnel_sockets
+        .get(&to_socket)
+        .filter(|tunnel_socket| {
+            matches!(tunnel_socket.socket_type, TunnelSocketType::Listening)
+        })
+        .map(|tunnel_socket| tunnel_socket.owner)
+        .filter(|owner_socket| {
+            db.sockets
+                .get(owner_socket)
+                .is_some_and(|socket| socket.status.user_id == owner)
+        });
+    let Some(owner_socket) = owner_socket else {
+        let _ = close_tunnel_socket(connected_to, replica, db, redis).await;
+        return;
+    };
+
+    db.tunnel_sockets.insert(
+        new_socket,
+        TunnelSocket::new(
+            owner_socket,
+            TunnelSocketType::Connected {
+                connected_to,
+                replica,
+            },
+        ),
+    );
+    if let Some(active_socket) = db.sockets.get(&owner_socket) {
+        active_socket.owned_tunnel_sockets.insert(new_socket);
+        let _ = send_message(
+            &active_socket,
+            &ServerToClientMessage::SocketConnected {
+                to_socket,
+                new_socket,
+            },
+        )
+        .await;
+    }
+}
+
+/// Closes a tunnel socket, through `replica` if its launcher isn't connected
+/// to this one.
+pub async fn close_tunnel_socket(
+    socket: Uuid,
+    replica: Uuid,
+    db: &ActiveSockets,
+    redis: &RedisPool,
+) -> Result<(), crate::database::models::DatabaseError> {
+    if replica == db.replica_id {
+        close_local_tunnel_socket(socket, db).await;
+        return Ok(());
+    }
+
+    send_tunnel_message(redis, replica, RedisTunnelMessage::Closed { socket })
+        .await
+}
+
+/// Closes a tunnel socket if its launcher is connected to this replica,
+/// returning whether it was.
+pub async fn close_local_tunnel_socket(
+    socket: Uuid,
+    db: &ActiveSockets,
+) -> bool {
+    let Some((_, tunnel_socket)) = db.tunnel_sockets.remove(&socket) else {
+        return false;
+    };
+
+    if let Some(owner) = db.sockets.get(&tunnel_socket.owner) {
+        owner.owned_tunnel_sockets.remove(&socket);
+        let _ = send_message(
+            &owner,
+            &ServerToClientMessage::SocketClosed { socket },
+        )
+        .await;
+    }
+
+    true
+}
@@ -1,5 +1,9 @@
-use crate::auth::checks::{is_visible_project, is_visible_version};
+use crate::auth::checks::{
+    filter_visible_project_ids, filter_visible_version_ids, is_visible_project,
+    is_visible_version,
+};
 use crate::database::PgPool;
+use crate::database::models::ids::DBVersionId;
 use crate::database::models::legacy_loader_fields::MinecraftGameVersion;
 use crate::database::models::loader_fields::Loader;
 use crate::database::models::project_item::ProjectQueryResult;
@@ -8,15 +12,19 @@ use crate::database::models::version_item::{
 };
 use crate::models::ids::{ProjectId, VersionId};
 use crate::models::pats::Scopes;
-use crate::models::projects::FileType;
+use crate::models::projects::{DependencyType, FileType};
+use crate::models::users::User;
 use crate::queue::session::AuthQueue;
 use crate::routes::ApiError;
 use crate::util::error::ApiContext as _;
 use crate::util::error::Context;
 use crate::{auth::get_user_from_headers, database};
 use actix_web::{HttpRequest, HttpResponse, get, route, web};
+use itertools::Itertools;
 use quick_xml::escape::escape;
-use std::collections::HashSet;
+use serde::Serialize;
+use sha2::Digest;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use xredis::RedisPool;
 use yaserde::YaSerialize;
 
@@ -70,6 +78,24 @@ pub struct MavenPom {
     version: String,
     name: String,
     description: String,
+    dependencies: PomDependencies,
+}
+
+#[derive(Default, Debug, Clone, YaSerialize)]
+#[yaserde(rename = "dependencies")]
+pub struct PomDependencies {
+    #[yaserde(rename = "dependency")]
+    dependencies: Vec<PomDependency>,
+}
+
+#[derive(Default, Debug, Clone, YaSerialize)]
+#[yaserde(rename = "dependency")]
+pub struct PomDependency {
+    #[yaserde(rename = "groupId")]
+    group_id: String,
+    #[yaserde(rename = "artifactId")]
+    artifact_id: String,
+    version: String,
 }
 
 #[utoipa::path(
@@ -180,6 +206,8 @@ pub async fn maven_metadata(
 async fn find_version(
     project: &ProjectQueryResult,
     vcoords: &String,
+    loader: Option<&str>,
+    db_loaders: &HashSet<String>,
     pool: &PgPool,
     redis: &RedisPool,
 ) -> Result<Option<VersionQueryResult>, ApiError> {
@@ -195,8 +223,10 @@ async fn find_version(
     let exact_matches = all_versions
         .iter()
         .filter(|x| {
-            &x.inner.version_number == vcoords
-                || Some(x.inner.id.0) == id_option
+            (&x.inner.version_number == vcoords
+                || Some(x.inner.id.0) == id_option)
+                && loader
+                    .is_none_or(|loader| x.loaders.iter().any(|y| y == loader))
         })
         .collect::<Vec<_>>();
 
@@ -209,17 +239,11 @@ async fn find_version(
         return Ok(exact_matches.first().map(|x| (*x).clone()));
     };
 
-    let db_loaders: HashSet<String> = Loader::list(pool, redis)
-        .await
-        .wrap_internal_err("fetching loader from Redis")?
-        .into_iter()
-        .map(|x| x.loader)
-        .collect();
-
-    let (loaders, game_versions) = filter
+    let (mut loaders, game_versions) = filter
         .split(',')
         .map(String::from)
         .partition::<Vec<_>, _>(|el| db_loaders.contains(el));
+    loaders.extend(loader.map(String::from));
 
     let matched = all_versions
         .iter()
@@ -232,10 +256,7 @@ async fn find_version(
 
             // For maven in particular, we will hardcode it to use GameVersions rather than generic loader fields, as this is minecraft-java exclusive
             if !game_versions.is_empty() {
-                let version_game_versions =
-                    x.version_fields.clone().into_iter().find_map(|v| {
-                        MinecraftGameVersion::try_from_version_field(&v).ok()
-                    });
+                let version_game_versions = get_game_versions(x);
                 if let Some(version_game_versions) = version_game_versions {
                     bool &= version_game_versions
                         .iter()
@@ -254,6 +275,15 @@ async fn find_version(
         .cloned())
 }
 
+fn get_game_versions(
+    version: &VersionQueryResult,
+) -> Option<Vec<MinecraftGameVersion>> {
+    version
+        .version_fields
+        .iter()
+        .find_map(|v| MinecraftGameVersion::try_from_version_field(v).ok())
+}
+
 fn find_file<'a>(
     project_id: &str,
     vcoords: &str,
@@ -299,33 +329,75 @@ fn find_file<'a>(
     None
 }
 
-#[utoipa::path(
-	tag = "maven",
-	params(
-		("id" = String, Path),
-		("versionnum" = String, Path),
-		("file" = String, Path)
-	),
-	responses(
-		(status = OK, body = String, content_type = "text/xml"),
-		(status = TEMPORARY_REDIRECT)
-	)
-)]
-#[route(
-    "/maven/modrinth/{id}/{versionnum}/{file}",
-    method = "GET",
-    method = "HEAD"
-)]
-pub async fn version_file(
-    req: HttpRequest,
-    params: web::Path<(String, String, String)>,
-    pool: web::Data<PgPool>,
-    redis: web::Data<RedisPool>,
-    session_queue: web::Data<AuthQueue>,
-) -> Result<HttpResponse, ApiError> {
-    let (project_id, vnum, file) = params.into_inner();
+/// Splits a loader classifier off a file name, so that
+/// `{id}-{version}-fabric.jar` refers to the primary file of the Fabric
+/// version with that version number, and `{id}-{version}-fabric-sources.jar`
+/// to its sources.
+///
+/// Returns the loader, if any, and the file name without it.
+fn split_loader_classifier(
+    project_id: &str,
+    vcoords: &str,
+    file: &str,
+    db_loaders: &HashSet<String>,
+) -> (Option<String>, String) {
+    let prefix = format!("{project_id}-{vcoords}-");
+    let Some((name, ext)) = file.rsplit_once('.') else {
+        return (None, file.to_string());
+    };
+
+    if name.len() <= prefix.len()
+        || !name.as_bytes()[..prefix.len()]
+            .eq_ignore_ascii_case(prefix.as_bytes())
+    {
+        return (None, file.to_string());
+    }
+
+    let classifier = &name[prefix.len()..];
+    let Some(loader) = db_loaders
+        .iter()
+        .filter(|loader| {
+            classifier == loader.as_str()
+                || classifier
+                    .strip_prefix(loader.as_str())
+                    .is_some_and(|rest| rest.starts_with('-'))
+        })
+        .max_by_key(|loader| loader.len())
+    else {
+        return (None, file.to_string());
+    };
+
+    let base = &name[..prefix.len() - 1];
+    let file = match &classifier[loader.len()..] {
+        "" => format!("{base}.{ext}"),
+        rest => format!("{base}{rest}.{ext}"),
+    };
+
+    (Some(loader.clone()), file)
+}
+
+/// A request for a file of a version which the user can see.
+struct MavenFileRequest {
+    project: ProjectQueryResult,
+    version: VersionQueryResult,
+    user_option: Option<User>,
+    /// The loader classifier of the requested file, if any.
+    loader: Option<String>,
+    /// The requested file name, without its loader classifier.
+    file: String,
+}
+
+async fn get_file_request(
+    req: &HttpRequest,
+    project_id: &str,
+    vnum: &String,
+    file: &str,
+    pool: &PgPool,
+    redis: &RedisPool,
+    session_queue: &AuthQueue,
+) -> Result<MavenFileRequest, ApiError> {
     let Some(project) =
-        database::models::DBProject::get(&project_id, &**pool, &redis)
+        database::models::DBProject::get(project_id, pool, redis)
             .await
             .wrap_api_err("fetching Maven project")?
     else {
@@ -333,38 +405,434 @@ pub async fn version_file(
     };
 
     let user_option = get_user_from_headers(
-        &req,
-        &**pool,
-        &redis,
-        &session_queue,
+        req,
+        pool,
+        redis,
+        session_queue,
         Scopes::PROJECT_READ,
     )
     .await
     .map(|x| x.1)
     .ok();
 
-    if !is_visible_project(&project.inner, &user_option, &pool, false)
+    if !is_visible_project(&project.inner, &user_option, pool, false)
         .await
         .wrap_api_err("checking project visibility")?
     {
         return Err(ApiError::NotFound(eyre::eyre!("resource not found")));
     }
 
-    let Some(version) = find_version(&project, &vnum, &pool, &redis)
+    let db_loaders: HashSet<String> = Loader::list(pool, redis)
         .await
-        .wrap_api_err("fetching Maven version")?
+        .wrap_internal_err("fetching loader from Redis")?
+        .into_iter()
+        .map(|x| x.loader)
+        .collect();
+
+    let (loader, file) =
+        split_loader_classifier(project_id, vnum, file, &db_loaders);
+
+    let Some(version) = find_version(
+        &project,
+        vnum,
+        loader.as_deref(),
+        &db_loaders,
+        pool,
+        redis,
+    )
+    .await
+    .wrap_api_err("fetching Maven version")?
     else {
         return Err(ApiError::NotFound(eyre::eyre!("resource not found")));
     };
 
-    if !is_visible_version(&version.inner, &user_option, &pool, &redis)
+    if !is_visible_version(&version.inner, &user_option, pool, redis)
         .await
         .wrap_api_err("checking version visibility")?
     {
         return Err(ApiError::NotFound(eyre::eyre!("resource not found")));
     }
 
-    if file.eq_ignore_ascii_case(&format!("{}-{}.pom", &project_id, &vnum)) {
+    Ok(MavenFileRequest {
+        project,
+        version,
+        user_option,
+        loader,
+        file,
+    })
+}
+
+/// The Maven version of each listed version, as in `maven-metadata.xml`: its
+/// version number, or its ID if an earlier version already uses that number.
+fn get_maven_versions(
+    versions: &[VersionQueryResult],
+) -> HashMap<DBVersionId, String> {
+    let mut listed = versions
+        .iter()
+        .filter(|x| x.inner.status.is_listed())
+        .collect::<Vec<_>>();
+    listed.sort_by_key(|x| {
+        (
+            x.inner.ordering.is_none(),
+            x.inner.ordering,
+            x.inner.date_published,
+        )
+    });
+
+    let mut vals = HashSet::new();
+    listed
+        .into_iter()
+        .map(|x| {
+            let value = if vals.contains(&x.inner.version_number) {
+                VersionId::from(x.inner.id).to_string()
+            } else {
+                x.inner.version_number.clone()
+            };
+            vals.insert(value.clone());
+
+            (x.inner.id, value)
+        })
+        .collect()
+}
+
+struct MavenCoordinates {
+    artifact_id: String,
+    version: String,
+}
+
+/// Maps the required dependencies of a version to coordinates in this
+/// repository.
+///
+/// Dependencies on a project rather than a version resolve to its latest
+/// listed version sharing a loader and game version with `version` that was
+/// published no later than `version`, or its earliest one if there's none,
+/// so that generated files don't change as the dependency gets new releases.
+/// Dependencies the user can't see, or which aren't on Modrinth, are skipped.
+async fn get_maven_dependencies(
+    version: &VersionQueryResult,
+    user_option: &Option<User>,
+    pool: &PgPool,
+    redis: &RedisPool,
+) -> Result<Vec<MavenCoordinates>, ApiError> {
+    let required = version
+        .dependencies
+        .iter()
+        .filter(|x| x.dependency_type == DependencyType::Required.as_str())
+        .collect::<Vec<_>>();
+    if required.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Dependencies on a version may not have a project ID stored.
+    let version_projects = database::models::DBVersion::get_many(
+        &required
+            .iter()
+            .filter(|x| x.project_id.is_none())
+            .filter_map(|x| x.version_id)
+            .collect::<Vec<_>>(),
+        pool,
+        redis,
+    )
+    .await
+    .wrap_internal_err("fetching dependency versions")?
+    .into_iter()
+    .map(|x| (x.inner.id, x.inner.project_id))
+    .collect::<HashMap<_, _>>();
+    let required = required
+        .into_iter()
+        .filter_map(|x| {
+            let project_id = x
+                .project_id
+                .or_else(|| version_projects.get(&x.version_id?).copied())?;
+            Some((project_id, x.version_id))
+        })
+        .collect::<Vec<_>>();
+
+    let projects = database::models::DBProject::get_many_ids(
+        &required
+            .iter()
+            .map(|(x, _)| *x)
+            .unique()
+            .collect::<Vec<_>>(),
+        pool,
+        redis,
+    )
+    .await
+    .wrap_api_err("fetching dependency projects")?;
+    let visible_project_ids = filter_visible_project_ids(
+        projects.iter().map(|x| &x.inner).collect(),
+        user_option,
+        pool,
+        false,
+    )
+    .await
+    .wrap_api_err("checking dependency project visibility")?
+    .into_iter()
+    .collect::<HashSet<_>>();
+    let projects = projects
+        .into_iter()
+        .filter(|x| visible_project_ids.contains(&x.inner.id))
+        .map(|x| (x.inner.id, x))
+        .collect::<HashMap<_, _>>();
+
+    let versions = database::models::DBVersion::get_many(
+        &projects
+            .values()
+            .flat_map(|x| x.versions.iter().copied())
+            .collect::<Vec<_>>(),
+        pool,
+        redis,
+    )
+    .await
+    .wrap_internal_err("fetching dependency versions")?;
+
+    // Only versions which were asked for by ID may be hidden, since project
+    // dependencies only resolve to listed versions.
+    let pinned_version_ids = required
+        .iter()
+        .filter_map(|(_, x)| *x)
+        .collect::<HashSet<_>>();
+    let visible_version_ids = filter_visible_version_ids(
+        versions
+            .iter()
+            .filter(|x| pinned_version_ids.contains(&x.inner.id))
+            .map(|x| &x.inner)
+            .collect(),
+        user_option,
+        pool,
+        redis,
+    )
+    .await
+    .wrap_api_err("checking dependency version visibility")?
+    .into_iter()
+    .collect::<HashSet<_>>();
+
+    let versions = versions
+        .into_iter()
+        .into_group_map_by(|x| x.inner.project_id);
+    let maven_versions = versions
+        .iter()
+        .map(|(project_id, versions)| {
+            (*project_id, get_maven_versions(versions))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let game_versions = get_game_versions(version).unwrap_or_default();
+    let mut coordinates = Vec::new();
+
+    for (project_id, version_id) in required {
+        let (Some(project), Some(versions)) =
+            (projects.get(&project_id), versions.get(&project_id))
+        else {
+            continue;
+        };
+
+        let selected = match version_id {
+            Some(version_id) => versions.iter().find(|x| {
+                x.inner.id == version_id
+                    && visible_version_ids.contains(&version_id)
+            }),
+            None => {
+                let (earlier, later): (Vec<_>, Vec<_>) = versions
+                    .iter()
+                    .filter(|x| {
+                        x.inner.status.is_listed()
+                            && x.loaders
+                                .iter()
+                                .any(|y| version.loaders.contains(y))
+                            && (game_versions.is_empty()
+                                || get_game_versions(x).is_none_or(|y| {
+                                    y.iter().any(|y| {
+                                        game_versions
+                                            .iter()
+                                            .any(|z| z.version == y.version)
+                                    })
+                                }))
+                    })
+                    .partition(|x| {
+                        x.inner.date_published <= version.inner.date_published
+                    });
+
+                earlier
+                    .into_iter()
+                    .max_by_key(|x| x.inner.date_published)
+                    .or_else(|| {
+                        later.into_iter().min_by_key(|x| x.inner.date_published)
+                    })
+            }
+        };
+        let Some(selected) = selected else {
+            continue;
+        };
+
+        let maven_version = maven_versions
+            .get(&project_id)
+            .and_then(|x| x.get(&selected.inner.id))
+            .cloned()
+            .unwrap_or_else(|| VersionId::from(selected.inner.id).to_string());
+
+        coordinates.push(MavenCoordinates {
+            artifact_id: project.inner.slug.clone().unwrap_or_else(|| {
+                ProjectId::from(project.inner.id).to_string()
+            }),
+            version: maven_version,
+        });
+    }
+
+    Ok(coordinates)
+}
+
+const MAVEN_METADATA_NAMESPACE: &str = "maven_metadata";
+const MAVEN_METADATA_EXPIRY: i64 = 5 * 60;
+
+/// Comment marking a POM as having Gradle module metadata, which Gradle then
+/// uses instead.
+const GRADLE_METADATA_MARKER: &str =
+    "<!-- do_not_remove: published-with-gradle-metadata -->";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GradleModule {
+    format_version: &'static str,
+    component: GradleComponent,
+    variants: Vec<GradleVariant>,
+}
+
+#[derive(Serialize)]
+struct GradleComponent {
+    group: String,
+    module: String,
+    version: String,
+    attributes: BTreeMap<&'static str, &'static str>,
+}
+
+#[derive(Serialize)]
+struct GradleVariant {
+    name: &'static str,
+    attributes: BTreeMap<&'static str, &'static str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    dependencies: Vec<GradleDependency>,
+    files: Vec<GradleFile>,
+}
+
+#[derive(Serialize)]
+struct GradleDependency {
+    group: String,
+    module: String,
+    version: GradleVersionConstraint,
+}
+
+#[derive(Serialize)]
+struct GradleVersionConstraint {
+    requires: String,
+}
+
+#[derive(Serialize)]
+struct GradleFile {
+    name: String,
+    url: String,
+    size: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sha1: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sha512: Option<String>,
+}
+
+impl GradleFile {
+    fn new(name: String, file: &FileQueryResult) -> Self {
+        Self {
+            url: name.clone(),
+            name,
+            size: file.size,
+            sha1: file.hashes.get("sha1").cloned(),
+            sha512: file.hashes.get("sha512").cloned(),
+        }
+    }
+}
+
+/// Generates the POM or Gradle module metadata of the requested version, if
+/// that's the requested file. Returns the content type and file contents.
+///
+/// Build tools fetch these files together with their checksums, so generated
+/// files are cached briefly instead of resolving dependencies for each.
+async fn generate_metadata_file(
+    request: &MavenFileRequest,
+    project_id: &str,
+    vnum: &str,
+    pool: &PgPool,
+    redis: &RedisPool,
+) -> Result<Option<(&'static str, String)>, ApiError> {
+    let Some((name, ext)) = request.file.rsplit_once('.') else {
+        return Ok(None);
+    };
+    if !name.eq_ignore_ascii_case(&format!("{project_id}-{vnum}"))
+        || !matches!(ext, "pom" | "module")
+    {
+        return Ok(None);
+    }
+    let content_type = if ext == "pom" {
+        "text/xml"
+    } else {
+        "application/json"
+    };
+
+    // Which dependencies are listed depends on what the user can see, so
+    // each user gets their own copy.
+    let mut redis_connection = redis
+        .connect()
+        .await
+        .wrap_internal_err("connecting to Redis")?;
+    let cache_key = redis_connection.key().entity(
+        MAVEN_METADATA_NAMESPACE,
+        format!(
+            "{}:{}:{project_id}:{vnum}:{}:{}",
+            VersionId::from(request.version.inner.id),
+            request
+                .user_option
+                .as_ref()
+                .map(|x| x.id.to_string())
+                .unwrap_or_default(),
+            request.loader.as_deref().unwrap_or_default(),
+            request.file,
+        ),
+    );
+    if let Some(cached) = redis_connection
+        .get_deserialized::<String>(&cache_key)
+        .await
+        .wrap_internal_err("getting cached Maven metadata")?
+    {
+        return Ok(Some((content_type, cached)));
+    }
+
+    let body =
+        render_metadata_file(request, project_id, vnum, ext, pool, redis)
+            .await?;
+    redis_connection
+        .set_serialized(&cache_key, &body, Some(MAVEN_METADATA_EXPIRY))
+        .await
+        .wrap_internal_err("caching Maven metadata")?;
+
+    Ok(Some((content_type, body)))
+}
+
+async fn render_metadata_file(
+    request: &MavenFileRequest,
+    project_id: &str,
+    vnum: &str,
+    ext: &str,
+    pool: &PgPool,
+    redis: &RedisPool,
+) -> Result<String, ApiError> {
+    let base_name = format!("{project_id}-{vnum}");
+    let dependencies = get_maven_dependencies(
+        &request.version,
+        &request.user_option,
+        pool,
+        redis,
+    )
+    .await?;
+
+    if ext == "pom" {
         let respdata = MavenPom {
             schema_location:
                 "http://maven.apache.org/POM/4.0.0 http://maven.apache.org/xsd/maven-4.0.0.xsd"
@@ -372,18 +840,191 @@ pub async fn version_file(
             xsi: "http://www.w3.org/2001/XMLSchema-instance".to_string(),
             model_version: "4.0.0".to_string(),
             group_id: "maven.modrinth".to_string(),
-            artifact_id: project_id,
-            version: vnum,
-            name: project.inner.name,
-            description: escape(project.inner.summary).into_owned(),
+            artifact_id: project_id.to_string(),
+            version: vnum.to_string(),
+            name: request.project.inner.name.clone(),
+            description: escape(&request.project.inner.summary).into_owned(),
+            dependencies: PomDependencies {
+                dependencies: dependencies
+                    .into_iter()
+                    .map(|x| PomDependency {
+                        group_id: "maven.modrinth".to_string(),
+                        artifact_id: x.artifact_id,
+                        version: x.version,
+                    })
+                    .collect(),
+            },
         };
-        return Ok(HttpResponse::Ok().content_type("text/xml").body(
-            yaserde::ser::to_string(&respdata)
-                .map_err(eyre::Report::msg)
-                .wrap_internal_err("serializing Maven project as XML")?,
-        ));
+        let mut pom = yaserde::ser::to_string(&respdata)
+            .map_err(eyre::Report::msg)
+            .wrap_internal_err("serializing Maven project as XML")?;
+
+        // Gradle only looks for the marker inside the project element.
+        if let Some(end) = pom
+            .find("<project")
+            .and_then(|start| pom[start..].find('>').map(|end| start + end + 1))
+        {
+            pom.insert_str(end, GRADLE_METADATA_MARKER);
+        }
+
+        return Ok(pom);
+    }
+
+    // Files are named after the request, so that the URLs resolve back to
+    // the same version when it was selected with a loader classifier.
+    let file_name = |classifier: Option<&str>, ext: &str| {
+        let loader = request
+            .loader
+            .as_ref()
+            .map(|x| format!("-{x}"))
+            .unwrap_or_default();
+        let classifier =
+            classifier.map(|x| format!("-{x}")).unwrap_or_default();
+        format!("{base_name}{loader}{classifier}.{ext}")
+    };
+
+    let library_attributes = |usage| {
+        BTreeMap::from([
+            ("org.gradle.category", "library"),
+            ("org.gradle.dependency.bundling", "external"),
+            ("org.gradle.libraryelements", "jar"),
+            ("org.gradle.usage", usage),
+        ])
+    };
+    let documentation_attributes = |docs_type| {
+        BTreeMap::from([
+            ("org.gradle.category", "documentation"),
+            ("org.gradle.dependency.bundling", "external"),
+            ("org.gradle.docstype", docs_type),
+            ("org.gradle.usage", "java-runtime"),
+        ])
+    };
+    let gradle_dependencies = || {
+        dependencies
+            .iter()
+            .map(|x| GradleDependency {
+                group: "maven.modrinth".to_string(),
+                module: x.artifact_id.clone(),
+                version: GradleVersionConstraint {
+                    requires: x.version.clone(),
+                },
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut variants = Vec::new();
+    if let Some(primary) = request
+        .version
+        .files
+        .iter()
+        .find(|x| x.primary)
+        .or_else(|| request.version.files.first())
+    {
+        let ext = primary
+            .filename
+            .rsplit_once('.')
+            .map_or("jar", |(_, ext)| ext);
+        let files = || vec![GradleFile::new(file_name(None, ext), primary)];
+
+        variants.push(GradleVariant {
+            name: "apiElements",
+            attributes: library_attributes("java-api"),
+            dependencies: gradle_dependencies(),
+            files: files(),
+        });
+        variants.push(GradleVariant {
+            name: "runtimeElements",
+            attributes: library_attributes("java-runtime"),
+            dependencies: gradle_dependencies(),
+            files: files(),
+        });
+    }
+
+    for (file_type, name, docs_type) in [
+        (FileType::SourcesJar, "sourcesElements", "sources"),
+        (FileType::JavadocJar, "javadocElements", "javadoc"),
+    ] {
+        if let Some(file) = request
+            .version
+            .files
+            .iter()
+            .find(|x| x.file_type == Some(file_type))
+        {
+            variants.push(GradleVariant {
+                name,
+                attributes: documentation_attributes(docs_type),
+                dependencies: Vec::new(),
+                files: vec![GradleFile::new(
+                    file_name(Some(docs_type), "jar"),
+                    file,
+                )],
+            });
+        }
+    }
+
+    let status = match request.version.inner.version_type.as_str() {
+        "release" => "release",
+        "beta" => "milestone",
+        _ => "integration",
+    };
+    let module = GradleModule {
+        format_version: "1.1",
+        component: GradleComponent {
+            group: "maven.modrinth".to_string(),
+            module: project_id.to_string(),
+            version: vnum.to_string(),
+            attributes: BTreeMap::from([("org.gradle.status", status)]),
+        },
+        variants,
+    };
+
+    serde_json::to_string(&module)
+        .wrap_internal_err("serializing Gradle module metadata")
+}
+
+#[utoipa::path(
+	tag = "maven",
+	params(
+		("id" = String, Path),
+		("versionnum" = String, Path),
+		("file" = String, Path)
+	),
+	responses(
+		(status = OK, body = String, content_type = "text/xml"),
+		(status = TEMPORARY_REDIRECT)
+	)
+)]
+#[route(
+    "/maven/modrinth/{id}/{versionnum}/{file}",
+    method = "GET",
+    method = "HEAD"
+)]
+pub async fn version_file(
+    req: HttpRequest,
+    params: web::Path<(String, String, String)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<HttpResponse, ApiError> {
+    let (project_id, vnum, file) = params.into_inner();
+    let request = get_file_request(
+        &req,
+        &project_id,
+        &vnum,
+        &file,
+        &pool,
+        &redis,
+        &session_queue,
+    )
+    .await?;
+
+    if let Some((content_type, body)) =
+        generate_metadata_file(&request, &project_id, &vnum, &pool, &redis)
+            .await?
+    {
+        return Ok(HttpResponse::Ok().content_type(content_type).body(body));
     } else if let Some(selected_file) =
-        find_file(&project_id, &vnum, &version, &file)
+        find_file(&project_id, &vnum, &request.version, &request.file)
     {
         return Ok(HttpResponse::TemporaryRedirect()
             .append_header(("location", &*selected_file.url))
@@ -411,52 +1052,34 @@ pub async fn version_file_sha1(
     session_queue: web::Data<AuthQueue>,
 ) -> Result<HttpResponse, ApiError> {
     let (project_id, vnum, file) = params.into_inner();
-    let Some(project) =
-        database::models::DBProject::get(&project_id, &**pool, &redis)
-            .await
-            .wrap_api_err("fetching Maven project")?
-    else {
-        return Err(ApiError::NotFound(eyre::eyre!("resource not found")));
-    };
-
-    let user_option = get_user_from_headers(
+    let request = get_file_request(
         &req,
-        &**pool,
+        &project_id,
+        &vnum,
+        &file,
+        &pool,
         &redis,
         &session_queue,
-        Scopes::PROJECT_READ,
     )
-    .await
-    .map(|x| x.1)
-    .ok();
-
-    if !is_visible_project(&project.inner, &user_option, &pool, false)
-        .await
-        .wrap_api_err("checking project visibility")?
-    {
-        return Err(ApiError::NotFound(eyre::eyre!("resource not found")));
-    }
+    .await?;
 
-    let Some(version) = find_version(&project, &vnum, &pool, &redis)
-        .await
-        .wrap_api_err("fetching Maven version")?
-    else {
-        return Err(ApiError::NotFound(eyre::eyre!("resource not found")));
-    };
-
-    if !is_visible_version(&version.inner, &user_option, &pool, &redis)
-        .await
-        .wrap_api_err("checking version visibility")?
+    if let Some((_, body)) =
+        generate_metadata_file(&request, &project_id, &vnum, &pool, &redis)
+            .await?
     {
-        return Err(ApiError::NotFound(eyre::eyre!("resource not found")));
+        return Ok(
+            HttpResponse::Ok().body(format!("{:x}", sha1::Sha1::digest(body)))
+        );
     }
 
-    Ok(find_file(&project_id, &vnum, &version, &file)
-        .and_then(|file| file.hashes.get("sha1"))
-        .map_or_else(
-            || HttpResponse::NotFound().body(""),
-            |hash_str| HttpResponse::Ok().body(hash_str.clone()),
-        ))
+    Ok(
+        find_file(&project_id, &vnum, &request.version, &request.file)
+            .and_then(|file| file.hashes.get("sha1"))
+            .map_or_else(
+                || HttpResponse::NotFound().body(""),
+                |hash_str| HttpResponse::Ok().body(hash_str.clone()),
+            ),
+    )
 }
 
 #[utoipa::path(
@@ -477,50 +1100,31 @@ pub async fn version_file_sha512(
     session_queue: web::Data<AuthQueue>,
 ) -> Result<HttpResponse, ApiError> {
     let (project_id, vnum, file) = params.into_inner();
-    let Some(project) =
-        database::models::DBProject::get(&project_id, &**pool, &redis)
-            .await
-            .wrap_api_err("fetching Maven project")?
-    else {
-        return Err(ApiError::NotFound(eyre::eyre!("resource not found")));
-    };
-
-    let user_option = get_user_from_headers(
+    let request = get_file_request(
         &req,
-        &**pool,
+        &project_id,
+        &vnum,
+        &file,
+        &pool,
         &redis,
         &session_queue,
-        Scopes::PROJECT_READ,
     )
-    .await
-    .map(|x| x.1)
-    .ok();
+    .await?;
 
-    if !is_visible_project(&project.inner, &user_option, &pool, false)
-        .await
-        .wrap_api_err("checking project visibility")?
+    if let Some((_, body)) =
+        generate_metadata_file(&request, &project_id, &vnum, &pool, &redis)
+            .await?
     {
-        return Err(ApiError::NotFound(eyre::eyre!("resource not found")));
+        return Ok(HttpResponse::Ok()
+            .body(format!("{:x}", sha2::Sha512::digest(body))));
     }
 
-    let Some(version) = find_version(&project, &vnum, &pool, &redis)
-        .await
-        .wrap_api_err("fetching Maven version")?
-    else {
-        return Err(ApiError::NotFound(eyre::eyre!("resource not found")));
-    };
-
-    if !is_visible_version(&version.inner, &user_option, &pool, &redis)
-        .await
-        .wrap_api_err("checking version visibility")?
-    {
-        return Err(ApiError::NotFound(eyre::eyre!("resource not found")));
-    }
-
-    Ok(find_file(&project_id, &vnum, &version, &file)
-        .and_then(|file| file.hashes.get("sha512"))
-        .map_or_else(
-            || HttpResponse::NotFound().body(""),
-            |hash_str| HttpResponse::Ok().body(hash_str.clone()),
-        ))
+    Ok(
+        find_file(&project_id, &vnum, &request.version, &request.file)
+            .and_then(|file| file.hashes.get("sha512"))
+            .map_or_else(
+                || HttpResponse::NotFound().body(""),
+                |hash_str| HttpResponse::Ok().body(hash_str.clone()),
+            ),
+    )
 }
@@ -336,7 +336,9 @@ pub async fn edit_team_member(
             organization_permissions: edit_member.organization_permissions,
             role: edit_member.role.clone(),
             payouts_split: edit_member.payouts_split,
+            payouts_split_effective_from: None,
             ordering: edit_member.ordering,
+            role_id: None,
         }),
         redis,
         session_queue,
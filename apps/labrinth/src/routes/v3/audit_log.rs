@@ -0,0 +1,170 @@
+use actix_web::{HttpRequest, get, web};
+use eyre::eyre;
+use serde::Deserialize;
+use xredis::RedisPool;
+
+use crate::auth::get_user_from_headers;
+use crate::database::PgPool;
+use crate::database::models::audit_log_item::DBAuditLogEntry;
+use crate::database::models::{DBOrganization, DBProject, DBTeamMember};
+use crate::models::audit_log::AuditLogEntry;
+use crate::models::pats::Scopes;
+use crate::models::teams::{OrganizationPermissions, ProjectPermissions};
+use crate::queue::session::AuthQueue;
+use crate::routes::ApiError;
+use crate::util::error::Context;
+
+/// Routes under the `/v3/project` scope.
+pub fn project_config(cfg: &mut web::ServiceConfig) {
+    cfg.service(project_audit_log_get);
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(organization_audit_log_get);
+}
+
+const MAX_COUNT: u16 = 100;
+
+#[derive(Deserialize)]
+pub struct AuditLogRequestOptions {
+    #[serde(default = "default_count")]
+    pub count: u16,
+    #[serde(default)]
+    pub offset: u32,
+}
+
+fn default_count() -> u16 {
+    MAX_COUNT
+}
+
+/// Get a page of a project's audit log, newest first.
+#[utoipa::path(
+	context_path = "/project",
+	tag = "projects",
+	params(
+		("count" = Option<u16>, Query),
+		("offset" = Option<u32>, Query)
+	),
+	responses((status = OK, body = Vec<AuditLogEntry>))
+)]
+#[get("/{id}/audit_log")]
+pub async fn project_audit_log_get(
+    req: HttpRequest,
+    info: web::Path<(String,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    request_opts: web::Query<AuditLogRequestOptions>,
+) -> Result<web::Json<Vec<AuditLogEntry>>, ApiError> {
+    let (_, user) = get_user_from_headers(
+        &req,
+        &**pool,
+        &redis,
+        &session_queue,
+        Scopes::PROJECT_READ,
+    )
+    .await
+    .wrap_auth_err("authenticating API request")?;
+
+    let project = DBProject::get(&info.into_inner().0, &**pool, &redis)
+        .await
+        .wrap_internal_err("fetching project from database")?
+        .wrap_not_found_err("resource not found")?;
+
+    let (team_member, organization_team_member) =
+        DBTeamMember::get_for_project_permissions(
+            &project.inner,
+            user.id.into(),
+            &**pool,
+        )
+        .await
+        .wrap_internal_err("fetching project permissions")?;
+    let permissions = ProjectPermissions::get_permissions_by_role(
+        &user.role,
+        &team_member,
+        &organization_team_member,
+    )
+    .unwrap_or_default();
+    if !permissions.contains(ProjectPermissions::VIEW_AUDIT_LOG) {
+        return Err(ApiError::Auth(eyre!(
+            "you do not have permission to view this project's audit log"
+        )));
+    }
+
+    let entries = DBAuditLogEntry::get_project(
+        project.inner.id,
+        i64::from(request_opts.count.min(MAX_COUNT)),
+        i64::from(request_opts.offset),
+        &**pool,
+    )
+    .await
+    .wrap_internal_err("fetching audit log from database")?;
+
+    Ok(web::Json(entries.into_iter().map(Into::into).collect()))
+}
+
+/// Get a page of an organization's audit log, newest first.
+///
+/// This includes changes to the projects the organization owned at the time.
+#[utoipa::path(
+	tag = "organizations",
+	params(
+		("count" = Option<u16>, Query),
+		("offset" = Option<u32>, Query)
+	),
+	responses((status = OK, body = Vec<AuditLogEntry>))
+)]
+#[get("/organization/{id}/audit_log")]
+pub async fn organization_audit_log_get(
+    req: HttpRequest,
+    info: web::Path<(String,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    request_opts: web::Query<AuditLogRequestOptions>,
+) -> Result<web::Json<Vec<AuditLogEntry>>, ApiError> {
+    let (_, user) = get_user_from_headers(
+        &req,
+        &**pool,
+        &redis,
+        &session_queue,
+        Scopes::ORGANIZATION_READ,
+    )
+    .await
+    .wrap_auth_err("authenticating API request")?;
+
+    let organization =
+        DBOrganization::get(&info.into_inner().0, &**pool, &redis)
+            .await
+            .wrap_internal_err("fetching organization from database")?
+            .wrap_not_found_err("resource not found")?;
+
+    let team_member = DBTeamMember::get_from_user_id(
+        organization.team_id,
+        user.id.into(),
+        &**pool,
+    )
+    .await
+    .wrap_internal_err("fetching organization team member")?;
+    let permissions = OrganizationPermissions::get_permissions_by_role(
+        &user.role,
+        &team_member,
+    )
+    .unwrap_or_default();
+    if !permissions.contains(OrganizationPermissions::VIEW_AUDIT_LOG) {
+        return Err(ApiError::Auth(eyre!(
+            "you do not have permission to view this organization's audit log"
+        )));
+    }
+
+    let entries = DBAuditLogEntry::get_organization(
+        organization.id,
+        i64::from(request_opts.count.min(MAX_COUNT)),
+        i64::from(request_opts.offset),
+        &**pool,
+    )
+    .await
+    .wrap_internal_err("fetching audit log from database")?;
+
+    Ok(web::Json(entries.into_iter().map(Into::into).collect()))
+}
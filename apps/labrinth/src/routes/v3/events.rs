@@ -0,0 +1,185 @@
+use crate::auth::get_user_from_headers;
+use crate::database::PgPool;
+use crate::database::models::DBUser;
+use crate::database::models::flow_item::DBFlow;
+use crate::models::pats::Scopes;
+use crate::models::users::User;
+use crate::queue::realtime::{
+    ActiveEventStream, ActiveEventStreams, EVENT_STREAM_BUFFER, EventStreamId,
+};
+use crate::queue::session::AuthQueue;
+use crate::routes::ApiError;
+use crate::util::error::Context as _;
+use actix_web::web::Bytes;
+use actix_web::{HttpRequest, HttpResponse, get, post, web};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::StreamExt;
+use tokio_stream::wrappers::{IntervalStream, ReceiverStream};
+use xredis::RedisPool;
+
+/// How often a comment is sent on an idle stream, so that proxies don't
+/// close it.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long an event stream ticket can be used for after it's created.
+const TICKET_EXPIRY: chrono::Duration = chrono::Duration::seconds(30);
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(events_ticket).service(events);
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct EventStreamTicket {
+    /// Single-use ticket to open an event stream with.
+    pub ticket: String,
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct EventsQuery {
+    /// Ticket to authenticate with, for clients such as `EventSource` which
+    /// can't set the `Authorization` header. See [`events_ticket`].
+    pub ticket: Option<String>,
+}
+
+/// Removes an event stream once its response body is dropped.
+struct EventStreamGuard {
+    id: EventStreamId,
+    streams: web::Data<ActiveEventStreams>,
+}
+
+impl Drop for EventStreamGuard {
+    fn drop(&mut self) {
+        self.streams.remove(self.id);
+    }
+}
+
+/// Create a ticket to open an event stream with.
+///
+/// The ticket carries the scopes of the token it was created with, expires
+/// after 30 seconds and can only be used once. It's meant for clients such
+/// as `EventSource` which can't set the `Authorization` header, so that the
+/// token itself never ends up in a URL.
+#[utoipa::path(
+    responses((status = OK, body = EventStreamTicket)),
+    security(("bearer_auth" = ["NOTIFICATION_READ"]))
+)]
+#[post("/events/ticket")]
+pub async fn events_ticket(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<web::Json<EventStreamTicket>, ApiError> {
+    let (scopes, user) = get_user_from_headers(
+        &req,
+        &**pool,
+        &redis,
+        &session_queue,
+        Scopes::NOTIFICATION_READ,
+    )
+    .await
+    .wrap_auth_err("authenticating API request")?;
+
+    let ticket = DBFlow::EventStream {
+        user_id: user.id.into(),
+        scopes,
+    }
+    .insert(TICKET_EXPIRY, &redis)
+    .await
+    .wrap_internal_err("inserting event stream ticket")?;
+
+    Ok(web::Json(EventStreamTicket { ticket }))
+}
+
+/// Authenticates an event stream with a ticket from [`events_ticket`],
+/// consuming the ticket.
+async fn authenticate_ticket(
+    ticket: &str,
+    pool: &PgPool,
+    redis: &RedisPool,
+) -> Result<(Scopes, User), ApiError> {
+    let flow = DBFlow::take_if(
+        ticket,
+        |flow| matches!(flow, DBFlow::EventStream { .. }),
+        redis,
+    )
+    .await
+    .wrap_internal_err("fetching event stream ticket")?;
+    let Some(DBFlow::EventStream { user_id, scopes }) = flow else {
+        return Err(ApiError::Auth(eyre::eyre!(
+            "invalid or expired event stream ticket",
+        )));
+    };
+
+    let user = DBUser::get_id(user_id, pool, redis)
+        .await
+        .wrap_internal_err("fetching user from database")?
+        .wrap_auth_err("event stream ticket user no longer exists")?;
+
+    Ok((scopes, User::from_full(user)))
+}
+
+/// Stream realtime events.
+///
+/// Responds with a stream of Server-Sent Events, each carrying a JSON
+/// encoded event. New notifications are always sent. Messages in threads
+/// the user can read require the `THREAD_READ` scope, and moderation status
+/// changes to the user's projects require the `PROJECT_READ` scope.
+///
+/// Authenticates with the `Authorization` header, or a `ticket` from
+/// `POST /events/ticket`.
+#[utoipa::path(
+    params(EventsQuery),
+    responses((status = OK, content_type = "text/event-stream"))
+)]
+#[get("/events")]
+pub async fn events(
+    req: HttpRequest,
+    web::Query(query): web::Query<EventsQuery>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    streams: web::Data<ActiveEventStreams>,
+) -> Result<HttpResponse, ApiError> {
+    let (scopes, user) = match &query.ticket {
+        Some(ticket) => authenticate_ticket(ticket, &pool, &redis).await?,
+        None => get_user_from_headers(
+            &req,
+            &**pool,
+            &redis,
+            &session_queue,
+            Scopes::NOTIFICATION_READ,
+        )
+        .await
+        .wrap_auth_err("authenticating API request")?,
+    };
+
+    let (sender, receiver) = mpsc::channel(EVENT_STREAM_BUFFER);
+    let guard = EventStreamGuard {
+        id: streams.add(ActiveEventStream {
+            user,
+            scopes,
+            sender,
+        }),
+        streams,
+    };
+
+    let keepalive =
+        IntervalStream::new(actix_rt::time::interval(KEEPALIVE_INTERVAL))
+            .map(|_| Bytes::from_static(b": keepalive\n\n"));
+    let body =
+        ReceiverStream::new(receiver)
+            .merge(keepalive)
+            .map(move |frame| {
+                let _ = &guard;
+                Ok::<_, Infallible>(frame)
+            });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(body))
+}
@@ -6,19 +6,26 @@ use serde_json::json;
 
 pub mod analytics_event;
 pub mod analytics_get;
+pub mod audit_log;
 pub mod blocked_users;
 pub mod collections;
 pub mod content;
 pub mod disclosures;
+pub mod events;
 pub mod friends;
 pub mod images;
 pub mod limits;
 pub mod notifications;
+pub mod organization_roles;
 pub mod organizations;
 pub mod payouts;
+pub mod pgp_keys;
 pub mod project_creation;
+pub mod project_transfers;
+pub mod project_webhooks;
 pub mod projects;
 pub mod reports;
+pub mod server_verification;
 pub mod statistics;
 pub mod tags;
 pub mod teams;
@@ -48,18 +55,28 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             .wrap(default_cors())
             .configure(projects::project_config)
             .configure(project_creation::config)
-            .configure(disclosures::config),
+            .configure(disclosures::config)
+            .configure(server_verification::config)
+            .configure(project_webhooks::project_config)
+            .configure(project_transfers::project_config)
+            .configure(audit_log::project_config),
     );
     cfg.service(
         web::scope("/v3")
             .wrap(default_cors())
             .configure(analytics_event::config)
+            .configure(audit_log::config)
             .configure(limits::config)
             .configure(collections::config)
+            .configure(events::config)
             .configure(images::config)
             .configure(notifications::config)
             .configure(oauth_clients::config)
+            .configure(organization_roles::config)
             .configure(organizations::config)
+            .configure(pgp_keys::config)
+            .configure(project_transfers::config)
+            .configure(project_webhooks::config)
             .configure(projects::config)
             .configure(reports::config)
             .configure(statistics::config)
@@ -100,6 +117,8 @@ pub fn config(cfg: &mut web::ServiceConfig) {
 		payouts::payment_methods,
 		payouts::get_balance,
 		payouts::platform_revenue,
+		payouts::revenue_statements,
+		payouts::revenue_statement,
 		projects::project_get,
 		projects::project_get_check,
 		projects::project_delete,
@@ -115,6 +134,20 @@ pub fn config(cfg: &mut web::ServiceConfig) {
 		projects::dependency_list,
 		disclosures::get_project_disclosures,
 		disclosures::modify_project_disclosures,
+		server_verification::server_verification_start,
+		server_verification::server_verification_check,
+		project_webhooks::project_webhooks_get,
+		project_webhooks::project_webhook_create,
+		project_webhooks::project_webhook_edit,
+		project_webhooks::project_webhook_delete,
+		project_webhooks::project_webhook_deliveries_get,
+		project_transfers::project_transfers_get,
+		project_transfers::project_transfer_create,
+		project_transfers::user_project_transfers_get,
+		project_transfers::project_transfer_accept,
+		project_transfers::project_transfer_decline,
+		project_transfers::project_transfer_cancel,
+		audit_log::project_audit_log_get,
 		project_creation::project_create,
 		project_creation::project_create_with_id,
 		project_creation::new::create,
@@ -163,6 +196,24 @@ pub fn config(cfg: &mut web::ServiceConfig) {
 		organizations::organization_projects_remove,
 		organizations::organization_icon_edit,
 		organizations::delete_organization_icon,
+		organization_roles::organization_roles_get,
+		organization_roles::organization_role_create,
+		organization_roles::organization_role_edit,
+		organization_roles::organization_role_delete,
+		organization_roles::organization_role_override_set,
+		organization_roles::organization_role_override_delete,
+		pgp_keys::user_pgp_keys_get,
+		pgp_keys::user_pgp_key_add,
+		pgp_keys::user_pgp_key_delete,
+		pgp_keys::organization_pgp_keys_get,
+		pgp_keys::organization_pgp_key_add,
+		pgp_keys::organization_pgp_key_delete,
+		project_webhooks::organization_webhooks_get,
+		project_webhooks::organization_webhook_create,
+		project_webhooks::organization_webhook_edit,
+		project_webhooks::organization_webhook_delete,
+		project_webhooks::organization_webhook_deliveries_get,
+		audit_log::organization_audit_log_get,
 		super::maven::maven_metadata,
 		super::maven::version_file,
 		super::maven::version_file_sha1,
@@ -230,10 +281,13 @@ pub fn config(cfg: &mut web::ServiceConfig) {
 		version_file::update_individual_files_route,
 		version_file::delete_file_route,
 		version_file::download_version_route,
+		version_file::get_version_file_delta_route,
 		versions::versions_get_route,
 		versions::version_get_route,
 		versions::version_edit_route,
 		versions::version_delete_route,
+		events::events_ticket,
+		events::events,
 		friends::add_friend,
 		friends::remove_friend,
 		friends::friends,
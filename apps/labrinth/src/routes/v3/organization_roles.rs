@@ -0,0 +1,593 @@
+use actix_web::{
+    HttpRequest, HttpResponse, delete, get, patch, post, put, web,
+};
+use chrono::Utc;
+use eyre::eyre;
+use serde::Deserialize;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+use xredis::RedisPool;
+
+use crate::auth::get_user_from_headers;
+use crate::database::PgPool;
+use crate::database::models::audit_log_item::AuditLogEntryBuilder;
+use crate::database::models::ids::generate_organization_role_id;
+use crate::database::models::organization_role_item::DBOrganizationRole;
+use crate::database::models::{
+    DBOrganization, DBProject, DBTeamMember, DBUserId,
+};
+use crate::models::audit_log::{
+    AuditLogAction, AuditLogTarget, AuditedOrganizationRole,
+};
+use crate::models::ids::{OrganizationRoleId, ProjectId};
+use crate::models::organization_roles::OrganizationRole;
+use crate::models::pats::Scopes;
+use crate::models::teams::{OrganizationPermissions, ProjectPermissions};
+use crate::queue::session::AuthQueue;
+use crate::routes::ApiError;
+use crate::util::error::Context;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(organization_roles_get)
+        .service(organization_role_create)
+        .service(organization_role_edit)
+        .service(organization_role_delete)
+        .service(organization_role_override_set)
+        .service(organization_role_override_delete);
+}
+
+const MAX_ROLES: usize = 50;
+const MAX_NAME_LENGTH: usize = 64;
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateOrganizationRole {
+    pub name: String,
+    /// The default permissions of the role's members in any project of the
+    /// organization.
+    #[serde(default)]
+    pub permissions: ProjectPermissions,
+    #[serde(default)]
+    pub organization_permissions: OrganizationPermissions,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct EditOrganizationRole {
+    pub name: Option<String>,
+    pub permissions: Option<ProjectPermissions>,
+    pub organization_permissions: Option<OrganizationPermissions>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct OrganizationRoleOverride {
+    pub permissions: ProjectPermissions,
+}
+
+/// The organization roles are being managed in, and who by, once they have
+/// been checked to have permission to manage them.
+struct RoleManager {
+    organization: DBOrganization,
+    user_id: DBUserId,
+    permissions: OrganizationPermissions,
+}
+
+impl RoleManager {
+    /// Checks that the manager has every permission they're granting, the same
+    /// as when editing a member directly.
+    fn check_grantable(
+        &self,
+        permissions: ProjectPermissions,
+        organization_permissions: OrganizationPermissions,
+    ) -> Result<(), ApiError> {
+        if !self.permissions.contains(organization_permissions) {
+            return Err(ApiError::Request(eyre!(
+                "the role has organization permissions that you don't have"
+            )));
+        }
+        if !permissions.is_empty()
+            && !self.permissions.contains(
+                OrganizationPermissions::EDIT_MEMBER_DEFAULT_PERMISSIONS,
+            )
+        {
+            return Err(ApiError::Auth(eyre!(
+                "you do not have permission to give members default project permissions"
+            )));
+        }
+        Ok(())
+    }
+
+    async fn get_role(
+        &self,
+        role_id: OrganizationRoleId,
+        pool: &PgPool,
+    ) -> Result<DBOrganizationRole, ApiError> {
+        DBOrganizationRole::get(role_id.into(), pool)
+            .await
+            .wrap_internal_err("fetching organization role from database")?
+            .filter(|role| role.organization_id == self.organization.id)
+            .wrap_not_found_err("role not found")
+    }
+}
+
+/// List an organization's roles.
+///
+/// Only members of the organization can see its roles.
+#[utoipa::path(
+	tag = "organizations",
+	responses((status = OK, body = Vec<OrganizationRole>))
+)]
+#[get("/organization/{id}/roles")]
+pub async fn organization_roles_get(
+    req: HttpRequest,
+    info: web::Path<(String,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<web::Json<Vec<OrganizationRole>>, ApiError> {
+    let (_, user) = get_user_from_headers(
+        &req,
+        &**pool,
+        &redis,
+        &session_queue,
+        Scopes::ORGANIZATION_READ,
+    )
+    .await
+    .wrap_auth_err("authenticating API request")?;
+
+    let organization =
+        DBOrganization::get(&info.into_inner().0, &**pool, &redis)
+            .await
+            .wrap_internal_err("fetching organization from database")?
+            .wrap_not_found_err("resource not found")?;
+
+    let team_member = DBTeamMember::get_from_user_id(
+        organization.team_id,
+        user.id.into(),
+        &**pool,
+    )
+    .await
+    .wrap_internal_err("fetching organization team member")?;
+    if OrganizationPermissions::get_permissions_by_role(
+        &user.role,
+        &team_member,
+    )
+    .is_none()
+    {
+        return Err(ApiError::Auth(eyre!(
+            "you must be a member of this organization to view its roles"
+        )));
+    }
+
+    let roles = DBOrganizationRole::get_organization(organization.id, &**pool)
+        .await
+        .wrap_internal_err("fetching organization roles from database")?;
+
+    Ok(web::Json(roles.into_iter().map(Into::into).collect()))
+}
+
+/// Create a role in an organization.
+///
+/// You can only create roles with permissions you have yourself.
+#[utoipa::path(
+	tag = "organizations",
+	request_body = CreateOrganizationRole,
+	responses((status = OK, body = OrganizationRole))
+)]
+#[post("/organization/{id}/roles")]
+pub async fn organization_role_create(
+    req: HttpRequest,
+    info: web::Path<(String,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    body: web::Json<CreateOrganizationRole>,
+) -> Result<web::Json<OrganizationRole>, ApiError> {
+    let manager = get_role_manager(
+        &req,
+        &info.into_inner().0,
+        &pool,
+        &redis,
+        &session_queue,
+    )
+    .await?;
+    let body = body.into_inner();
+
+    let name = validate_name(&body.name)?;
+    manager.check_grantable(body.permissions, body.organization_permissions)?;
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .wrap_internal_err("starting database transaction")?;
+
+    let existing =
+        DBOrganizationRole::get_organization(manager.organization.id, &**pool)
+            .await
+            .wrap_internal_err("fetching organization roles from database")?;
+    if existing.len() >= MAX_ROLES {
+        return Err(ApiError::Request(eyre!(
+            "an organization may only have up to {MAX_ROLES} roles"
+        )));
+    }
+    if existing.iter().any(|role| role.name == name) {
+        return Err(ApiError::Request(eyre!(
+            "a role with this name already exists"
+        )));
+    }
+
+    let role = DBOrganizationRole {
+        id: generate_organization_role_id(&mut transaction)
+            .await
+            .wrap_internal_err("generating organization role ID")?,
+        organization_id: manager.organization.id,
+        name,
+        permissions: body.permissions,
+        organization_permissions: body.organization_permissions,
+        project_overrides: HashMap::default(),
+        created: Utc::now(),
+    };
+    role.insert(&mut transaction)
+        .await
+        .wrap_internal_err("inserting organization role")?;
+
+    AuditLogEntryBuilder::organization(
+        manager.user_id,
+        manager.organization.id,
+        AuditLogAction::OrganizationRoleCreated,
+        AuditLogTarget::OrganizationRole {
+            role_id: role.id.into(),
+        },
+    )
+    .after(&AuditedOrganizationRole::from(&role))
+    .insert(&mut transaction)
+    .await
+    .wrap_internal_err("inserting audit log entry")?;
+
+    transaction
+        .commit()
+        .await
+        .wrap_internal_err("committing database transaction")?;
+
+    Ok(web::Json(role.into()))
+}
+
+/// Edit an organization's role.
+///
+/// Changes apply immediately to every member with the role.
+#[utoipa::path(
+	tag = "organizations",
+	request_body = EditOrganizationRole,
+	responses((status = NO_CONTENT))
+)]
+#[patch("/organization/{id}/roles/{role_id}")]
+pub async fn organization_role_edit(
+    req: HttpRequest,
+    info: web::Path<(String, OrganizationRoleId)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    body: web::Json<EditOrganizationRole>,
+) -> Result<HttpResponse, ApiError> {
+    let (id, role_id) = info.into_inner();
+    let manager =
+        get_role_manager(&req, &id, &pool, &redis, &session_queue).await?;
+    let body = body.into_inner();
+
+    let old_role = manager.get_role(role_id, &pool).await?;
+    // Roles with permissions the manager doesn't have are out of their reach
+    manager.check_grantable(
+        old_role.permissions,
+        old_role.organization_permissions,
+    )?;
+
+    let mut role = old_role.clone();
+    if let Some(name) = &body.name {
+        let name = validate_name(name)?;
+        if name != role.name {
+            let existing = DBOrganizationRole::get_organization(
+                manager.organization.id,
+                &**pool,
+            )
+            .await
+            .wrap_internal_err("fetching organization roles from database")?;
+            if existing.iter().any(|role| role.name == name) {
+                return Err(ApiError::Request(eyre!(
+                    "a role with this name already exists"
+                )));
+            }
+        }
+        role.name = name;
+    }
+    if let Some(permissions) = body.permissions {
+        role.permissions = permissions;
+    }
+    if let Some(organization_permissions) = body.organization_permissions {
+        role.organization_permissions = organization_permissions;
+    }
+    manager.check_grantable(role.permissions, role.organization_permissions)?;
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .wrap_internal_err("starting database transaction")?;
+
+    role.update(&mut transaction)
+        .await
+        .wrap_internal_err("updating organization role")?;
+
+    if let Some(entry) = AuditLogEntryBuilder::organization(
+        manager.user_id,
+        manager.organization.id,
+        AuditLogAction::OrganizationRoleEdited,
+        AuditLogTarget::OrganizationRole {
+            role_id: role.id.into(),
+        },
+    )
+    .changes(
+        &AuditedOrganizationRole::from(&old_role),
+        &AuditedOrganizationRole::from(&role),
+    ) {
+        entry
+            .insert(&mut transaction)
+            .await
+            .wrap_internal_err("inserting audit log entry")?;
+    }
+
+    transaction
+        .commit()
+        .await
+        .wrap_internal_err("committing database transaction")?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Delete an organization's role.
+///
+/// Members which had the role fall back to their own permissions.
+#[utoipa::path(tag = "organizations", responses((status = NO_CONTENT)))]
+#[delete("/organization/{id}/roles/{role_id}")]
+pub async fn organization_role_delete(
+    req: HttpRequest,
+    info: web::Path<(String, OrganizationRoleId)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<HttpResponse, ApiError> {
+    let (id, role_id) = info.into_inner();
+    let manager =
+        get_role_manager(&req, &id, &pool, &redis, &session_queue).await?;
+
+    let role = manager.get_role(role_id, &pool).await?;
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .wrap_internal_err("starting database transaction")?;
+
+    DBOrganizationRole::remove(role.id, &mut transaction)
+        .await
+        .wrap_internal_err("removing organization role")?;
+
+    AuditLogEntryBuilder::organization(
+        manager.user_id,
+        manager.organization.id,
+        AuditLogAction::OrganizationRoleDeleted,
+        AuditLogTarget::OrganizationRole {
+            role_id: role.id.into(),
+        },
+    )
+    .before(&AuditedOrganizationRole::from(&role))
+    .insert(&mut transaction)
+    .await
+    .wrap_internal_err("inserting audit log entry")?;
+
+    transaction
+        .commit()
+        .await
+        .wrap_internal_err("committing database transaction")?;
+    // The role is unassigned from its members
+    DBTeamMember::clear_cache(manager.organization.team_id, &redis)
+        .await
+        .wrap_internal_err("clearing cached data from Redis")?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Override the permissions of an organization role's members in one of the
+/// organization's projects.
+#[utoipa::path(
+	tag = "organizations",
+	request_body = OrganizationRoleOverride,
+	responses((status = NO_CONTENT))
+)]
+#[put("/organization/{id}/roles/{role_id}/projects/{project_id}")]
+pub async fn organization_role_override_set(
+    req: HttpRequest,
+    info: web::Path<(String, OrganizationRoleId, ProjectId)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    body: web::Json<OrganizationRoleOverride>,
+) -> Result<HttpResponse, ApiError> {
+    let (id, role_id, project_id) = info.into_inner();
+    set_override(
+        &req,
+        &id,
+        role_id,
+        project_id,
+        Some(body.into_inner().permissions),
+        &pool,
+        &redis,
+        &session_queue,
+    )
+    .await
+}
+
+/// Remove a role's permission override in a project, so its members have the
+/// role's default permissions there again.
+#[utoipa::path(tag = "organizations", responses((status = NO_CONTENT)))]
+#[delete("/organization/{id}/roles/{role_id}/projects/{project_id}")]
+pub async fn organization_role_override_delete(
+    req: HttpRequest,
+    info: web::Path<(String, OrganizationRoleId, ProjectId)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<HttpResponse, ApiError> {
+    let (id, role_id, project_id) = info.into_inner();
+    set_override(
+        &req,
+        &id,
+        role_id,
+        project_id,
+        None,
+        &pool,
+        &redis,
+        &session_queue,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn set_override(
+    req: &HttpRequest,
+    id: &str,
+    role_id: OrganizationRoleId,
+    project_id: ProjectId,
+    permissions: Option<ProjectPermissions>,
+    pool: &PgPool,
+    redis: &RedisPool,
+    session_queue: &AuthQueue,
+) -> Result<HttpResponse, ApiError> {
+    let manager = get_role_manager(req, id, pool, redis, session_queue).await?;
+
+    let old_role = manager.get_role(role_id, pool).await?;
+    let project = DBProject::get_id(project_id.into(), pool, redis)
+        .await
+        .wrap_internal_err("fetching project from database")?
+        .filter(|project| {
+            project.inner.organization_id == Some(manager.organization.id)
+        })
+        .wrap_request_err("the project does not belong to this organization")?;
+
+    let mut role = old_role.clone();
+    match permissions {
+        Some(permissions) => {
+            manager.check_grantable(
+                permissions,
+                OrganizationPermissions::empty(),
+            )?;
+            role.project_overrides.insert(project.inner.id, permissions);
+        }
+        None => {
+            role.project_overrides.remove(&project.inner.id);
+        }
+    }
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .wrap_internal_err("starting database transaction")?;
+
+    match permissions {
+        Some(permissions) => {
+            DBOrganizationRole::set_project_override(
+                role.id,
+                project.inner.id,
+                permissions,
+                &mut transaction,
+            )
+            .await
+        }
+        None => {
+            DBOrganizationRole::remove_project_override(
+                role.id,
+                project.inner.id,
+                &mut transaction,
+            )
+            .await
+        }
+    }
+    .wrap_internal_err("updating organization role project override")?;
+
+    if let Some(entry) = AuditLogEntryBuilder::organization(
+        manager.user_id,
+        manager.organization.id,
+        AuditLogAction::OrganizationRoleEdited,
+        AuditLogTarget::OrganizationRole {
+            role_id: role.id.into(),
+        },
+    )
+    .changes(
+        &AuditedOrganizationRole::from(&old_role),
+        &AuditedOrganizationRole::from(&role),
+    ) {
+        entry
+            .insert(&mut transaction)
+            .await
+            .wrap_internal_err("inserting audit log entry")?;
+    }
+
+    transaction
+        .commit()
+        .await
+        .wrap_internal_err("committing database transaction")?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+fn validate_name(name: &str) -> Result<String, ApiError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(ApiError::Request(eyre!(
+            "role name must be between 1 and {MAX_NAME_LENGTH} characters"
+        )));
+    }
+    Ok(name.to_string())
+}
+
+async fn get_role_manager(
+    req: &HttpRequest,
+    id: &str,
+    pool: &PgPool,
+    redis: &RedisPool,
+    session_queue: &AuthQueue,
+) -> Result<RoleManager, ApiError> {
+    let (_, user) = get_user_from_headers(
+        req,
+        pool,
+        redis,
+        session_queue,
+        Scopes::ORGANIZATION_WRITE,
+    )
+    .await
+    .wrap_auth_err("authenticating API request")?;
+
+    let organization = DBOrganization::get(id, pool, redis)
+        .await
+        .wrap_internal_err("fetching organization from database")?
+        .wrap_not_found_err("resource not found")?;
+
+    let team_member = DBTeamMember::get_from_user_id(
+        organization.team_id,
+        user.id.into(),
+        pool,
+    )
+    .await
+    .wrap_internal_err("fetching organization team member")?;
+    let permissions = OrganizationPermissions::get_permissions_by_role(
+        &user.role,
+        &team_member,
+    )
+    .unwrap_or_default();
+    if !permissions.contains(OrganizationPermissions::MANAGE_ROLES) {
+        return Err(ApiError::Auth(eyre!(
+            "you do not have permission to manage this organization's roles"
+        )));
+    }
+
+    Ok(RoleManager {
+        organization,
+        user_id: user.id.into(),
+        permissions,
+    })
+}
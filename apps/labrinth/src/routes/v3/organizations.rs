@@ -7,11 +7,16 @@ use crate::auth::{
     filter_visible_projects, get_user_from_headers, require_verified_email,
 };
 use crate::database::PgPool;
+use crate::database::models::audit_log_item::AuditLogEntryBuilder;
+use crate::database::models::organization_role_item::DBOrganizationRole;
 use crate::database::models::team_item::DBTeamMember;
 use crate::database::models::{
     DBModerationNote, DBOrganization, generate_organization_id, team_item,
 };
 use crate::file_hosting::{FileHost, FileHostPublicity};
+use crate::models::audit_log::{
+    AuditLogAction, AuditLogTarget, AuditedOrganization,
+};
 use crate::models::ids::OrganizationId;
 use crate::models::pats::Scopes;
 use crate::models::teams::{OrganizationPermissions, ProjectPermissions};
@@ -202,6 +207,19 @@ pub async fn organization_create(
         color: None,
     };
     organization.clone().insert(&mut transaction).await?;
+
+    AuditLogEntryBuilder::organization(
+        current_user.id.into(),
+        organization_id,
+        AuditLogAction::OrganizationCreated,
+        AuditLogTarget::Organization {
+            organization_id: organization_id.into(),
+        },
+    )
+    .after(&AuditedOrganization::from(&organization))
+    .insert(&mut transaction)
+    .await?;
+
     transaction.commit().await?;
 
     // Only member is the owner, the logged in one
@@ -703,6 +721,35 @@ pub async fn organizations_edit(
                 )?;
             }
 
+            let mut edited_organization =
+                AuditedOrganization::from(&organization_item);
+            if let Some(description) = &new_organization.description {
+                edited_organization.description.clone_from(description);
+            }
+            if let Some(name) = &new_organization.name {
+                edited_organization.name.clone_from(name);
+            }
+            if let Some(slug) = &new_organization.slug {
+                edited_organization.slug = slug.to_lowercase();
+            }
+            if let Some(entry) = AuditLogEntryBuilder::organization(
+                user.id.into(),
+                id,
+                AuditLogAction::OrganizationEdited,
+                AuditLogTarget::Organization {
+                    organization_id: id.into(),
+                },
+            )
+            .changes(
+                &AuditedOrganization::from(&organization_item),
+                &edited_organization,
+            ) {
+                entry
+                    .insert(&mut transaction)
+                    .await
+                    .wrap_internal_err("inserting audit log entry")?;
+            }
+
             transaction
                 .commit()
                 .await
@@ -801,6 +848,19 @@ pub async fn organization_delete(
         .await
         .wrap_internal_err("starting database transaction")?;
 
+    AuditLogEntryBuilder::organization(
+        user.id.into(),
+        organization.id,
+        AuditLogAction::OrganizationDeleted,
+        AuditLogTarget::Organization {
+            organization_id: organization.id.into(),
+        },
+    )
+    .before(&AuditedOrganization::from(&organization))
+    .insert(&mut transaction)
+    .await
+    .wrap_internal_err("inserting audit log entry")?;
+
     // Handle projects- every project that is in this organization needs to have its owner changed the organization owner
     // Now, no project should have an owner if it is in an organization, and also
     // the owner of an organization should not be a team member in any project
@@ -849,6 +909,7 @@ pub async fn organization_delete(
             accepted: true,
             payouts_split: Decimal::ZERO,
             ordering: 0,
+            role_id: None,
         };
         member.insert(&mut transaction).await.wrap_internal_err(
             "inserting database records for `organization_delete`",
@@ -1017,6 +1078,25 @@ pub async fn organization_projects_add(
             "querying database for `organization_projects_add`",
         )?;
 
+        AuditLogEntryBuilder {
+            organization_id: Some(organization.id),
+            ..AuditLogEntryBuilder::project(
+                current_user.id.into(),
+                project_item.inner.id,
+                AuditLogAction::OrganizationProjectAdded,
+                AuditLogTarget::Project {
+                    project_id: project_item.inner.id.into(),
+                },
+            )
+        }
+        .before(&serde_json::json!({ "organization_id": null }))
+        .after(&serde_json::json!({
+            "organization_id": OrganizationId::from(organization.id),
+        }))
+        .insert(&mut transaction)
+        .await
+        .wrap_internal_err("inserting audit log entry")?;
+
         // The former owner is no longer an owner (as it is now 'owned' by the organization, 'given' to them)
         // The former owner is still a member of the project, but not an owner
         // When later removed from the organization, the project will  be owned by whoever is specified as the new owner there
@@ -1218,6 +1298,7 @@ pub async fn organization_projects_remove(
                     accepted: true,
                     payouts_split: Decimal::ZERO,
                     ordering: 0,
+                    role_id: None,
                 };
                 member
                     .insert(&mut transaction)
@@ -1262,6 +1343,36 @@ pub async fn organization_projects_remove(
             "querying database for `organization_projects_remove`",
         )?;
 
+        DBOrganizationRole::remove_project_overrides(
+            organization.id,
+            project_item.inner.id,
+            &mut transaction,
+        )
+        .await
+        .wrap_internal_err("removing organization role project overrides")?;
+
+        AuditLogEntryBuilder {
+            organization_id: Some(organization.id),
+            ..AuditLogEntryBuilder::project(
+                current_user.id.into(),
+                project_item.inner.id,
+                AuditLogAction::OrganizationProjectRemoved,
+                AuditLogTarget::Project {
+                    project_id: project_item.inner.id.into(),
+                },
+            )
+        }
+        .before(&serde_json::json!({
+            "organization_id": OrganizationId::from(organization.id),
+        }))
+        .after(&serde_json::json!({
+            "organization_id": null,
+            "owner": data.new_owner,
+        }))
+        .insert(&mut transaction)
+        .await
+        .wrap_internal_err("inserting audit log entry")?;
+
         transaction
             .commit()
             .await
@@ -1362,6 +1473,7 @@ pub async fn organization_icon_edit(
         }
     }
 
+    let old_icon_url = organization_item.icon_url.clone();
     delete_old_images(
         organization_item.icon_url,
         organization_item.raw_icon_url,
@@ -1412,6 +1524,18 @@ pub async fn organization_icon_edit(
     .await
     .wrap_internal_err("querying database for `organization_icon_edit`")?;
 
+    AuditLogEntryBuilder::organization(
+        user.id.into(),
+        organization_item.id,
+        AuditLogAction::OrganizationIconEdited,
+        AuditLogTarget::Organization { organization_id },
+    )
+    .before(&serde_json::json!({ "icon_url": old_icon_url }))
+    .after(&serde_json::json!({ "icon_url": upload_result.url }))
+    .insert(&mut transaction)
+    .await
+    .wrap_internal_err("inserting audit log entry")?;
+
     transaction
         .commit()
         .await
@@ -1479,6 +1603,7 @@ pub async fn delete_organization_icon(
         }
     }
 
+    let old_icon_url = organization_item.icon_url.clone();
     delete_old_images(
         organization_item.icon_url,
         organization_item.raw_icon_url,
@@ -1505,6 +1630,19 @@ pub async fn delete_organization_icon(
     .await
     .wrap_internal_err("querying database for `delete_organization_icon`")?;
 
+    AuditLogEntryBuilder::organization(
+        user.id.into(),
+        organization_item.id,
+        AuditLogAction::OrganizationIconDeleted,
+        AuditLogTarget::Organization {
+            organization_id: organization_item.id.into(),
+        },
+    )
+    .before(&serde_json::json!({ "icon_url": old_icon_url }))
+    .insert(&mut transaction)
+    .await
+    .wrap_internal_err("inserting audit log entry")?;
+
     transaction
         .commit()
         .await
@@ -1,10 +1,13 @@
 use crate::auth::validate::get_user_record_from_bearer_token;
 use crate::auth::{AuthenticationError, get_user_from_headers};
 use crate::database::PgPool;
-use crate::database::models::DBUserId;
+use crate::database::models::payouts_split_item::DBPayoutsSplit;
+use crate::database::models::{
+    DBOrganization, DBProject, DBProjectId, DBUserId,
+};
 use crate::database::models::{generate_payout_id, users_compliance};
 use crate::env::ENV;
-use crate::models::ids::PayoutId;
+use crate::models::ids::{PayoutId, ProjectId};
 use crate::models::pats::Scopes;
 use crate::models::payouts::{
     PayoutMethod, PayoutMethodType, PayoutStatus, Withdrawal,
@@ -16,9 +19,13 @@ use crate::routes::internal::globals::tax_compliance_payout_threshold;
 use crate::util::avalara1099;
 use crate::util::error::ApiContext as _;
 use crate::util::error::Context;
-use crate::util::gotenberg::GotenbergClient;
+use crate::util::gotenberg::{
+    GotenbergClient, RevenueStatement as RevenueStatementDocument,
+};
+use actix_web::http::header;
 use actix_web::{HttpRequest, HttpResponse, delete, get, post, web};
-use chrono::{DateTime, Duration, Utc};
+use base64::Engine;
+use chrono::{DateTime, Duration, Months, NaiveDate, NaiveTime, Utc};
 use hex::ToHex;
 use hmac::{Hmac, Mac};
 use modrinth_util::decimal::Decimal2dp;
@@ -42,6 +49,8 @@ pub fn config(cfg: &mut actix_web::web::ServiceConfig) {
         .service(payment_methods)
         .service(get_balance)
         .service(platform_revenue)
+        .service(revenue_statements)
+        .service(revenue_statement)
         .service(post_compliance_form);
 }
 
@@ -1349,3 +1358,306 @@ pub async fn platform_revenue(
 
     Ok(HttpResponse::Ok().json(res))
 }
+
+/// A user's revenue from a single project over a month.
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RevenueStatementSummary {
+    pub project_id: ProjectId,
+    /// First day of the month the statement covers.
+    pub month: NaiveDate,
+    /// Revenue of the project across all of its team members.
+    pub gross: Decimal,
+    /// Revenue paid out to the user.
+    pub net: Decimal,
+}
+
+/// List monthly revenue statements.  
+/// Returns one statement for every project and month the user earned revenue
+/// in, most recent first.
+#[utoipa::path(
+	context_path = "/payout",
+	tag = "payouts",
+	responses((status = OK, body = Vec<RevenueStatementSummary>)),
+)]
+#[get("/statements")]
+pub async fn revenue_statements(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<web::Json<Vec<RevenueStatementSummary>>, ApiError> {
+    let (_, user) = get_user_from_headers(
+        &req,
+        &**pool,
+        &redis,
+        &session_queue,
+        Scopes::PAYOUTS_READ,
+    )
+    .await
+    .wrap_auth_err("authenticating API request")?;
+
+    let statements = sqlx::query!(
+        r#"
+        SELECT
+            pv.mod_id AS "project_id!",
+            date_trunc('month', pv.created, 'UTC') AS "month!",
+            SUM(pv.amount) AS "gross!",
+            SUM(pv.amount) FILTER (WHERE pv.user_id = $1) AS "net!"
+        FROM payouts_values pv
+        WHERE pv.mod_id IN (
+            SELECT DISTINCT mod_id
+            FROM payouts_values
+            WHERE user_id = $1 AND mod_id IS NOT NULL
+        )
+        GROUP BY 1, 2
+        HAVING COUNT(*) FILTER (WHERE pv.user_id = $1) > 0
+        ORDER BY 2 DESC, 1
+        "#,
+        DBUserId::from(user.id) as DBUserId
+    )
+    .fetch_all(&**pool)
+    .await
+    .wrap_internal_err("fetching revenue statements from database")?
+    .into_iter()
+    .map(|x| RevenueStatementSummary {
+        project_id: DBProjectId(x.project_id).into(),
+        month: x.month.date_naive(),
+        gross: x.gross,
+        net: x.net,
+    })
+    .collect();
+
+    Ok(web::Json(statements))
+}
+
+#[derive(
+    Default, Debug, Clone, Copy, Serialize, Deserialize, utoipa::ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum RevenueStatementFormat {
+    #[default]
+    Json,
+    Csv,
+    Pdf,
+}
+
+#[derive(Deserialize)]
+pub struct RevenueStatementQuery {
+    #[serde(default)]
+    pub format: RevenueStatementFormat,
+}
+
+/// A user's revenue from a single project over a month, with the payouts
+/// splits they had and a daily breakdown.
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RevenueStatement {
+    pub project_id: ProjectId,
+    pub project_name: String,
+    /// First day of the month the statement covers.
+    pub month: NaiveDate,
+    /// Revenue of the project across all of its team members.
+    pub gross: Decimal,
+    /// Revenue paid out to the user.
+    pub net: Decimal,
+    /// Payouts splits the user had in the project's team, or its
+    /// organization's team, during the month.
+    pub payouts_splits: Vec<RevenueStatementSplit>,
+    pub days: Vec<RevenueStatementDay>,
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RevenueStatementSplit {
+    pub payouts_split: Decimal,
+    pub effective_from: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RevenueStatementDay {
+    pub date: NaiveDate,
+    pub gross: Decimal,
+    pub net: Decimal,
+}
+
+/// Get a monthly revenue statement.  
+/// The month is given as `YYYY-MM`. The statement is returned as JSON by
+/// default, or as a CSV file of daily revenue or a PDF document.
+#[utoipa::path(
+	context_path = "/payout",
+	tag = "payouts",
+	params(
+		("project_id" = ProjectId, Path),
+		("month" = String, Path),
+		("format" = Option<RevenueStatementFormat>, Query)
+	),
+	responses((status = OK, body = RevenueStatement)),
+)]
+#[get("/statements/{project_id}/{month}")]
+pub async fn revenue_statement(
+    req: HttpRequest,
+    info: web::Path<(ProjectId, String)>,
+    query: web::Query<RevenueStatementQuery>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    gotenberg: web::Data<GotenbergClient>,
+) -> Result<HttpResponse, ApiError> {
+    let (_, user) = get_user_from_headers(
+        &req,
+        &**pool,
+        &redis,
+        &session_queue,
+        Scopes::PAYOUTS_READ,
+    )
+    .await
+    .wrap_auth_err("authenticating API request")?;
+
+    let (project_id, month) = info.into_inner();
+    let month = NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")
+        .wrap_request_err("month must be formatted as `YYYY-MM`")?;
+    let start = month.and_time(NaiveTime::MIN).and_utc();
+    let end = month
+        .checked_add_months(Months::new(1))
+        .wrap_request_err("month is out of range")?
+        .and_time(NaiveTime::MIN)
+        .and_utc();
+
+    let days = sqlx::query!(
+        r#"
+        SELECT
+            date_trunc('day', created, 'UTC') AS "day!",
+            SUM(amount) AS "gross!",
+            COALESCE(SUM(amount) FILTER (WHERE user_id = $2), 0) AS "net!"
+        FROM payouts_values
+        WHERE mod_id = $1 AND created >= $3 AND created < $4
+        GROUP BY 1
+        ORDER BY 1
+        "#,
+        DBProjectId::from(project_id) as DBProjectId,
+        DBUserId::from(user.id) as DBUserId,
+        start,
+        end,
+    )
+    .fetch_all(&**pool)
+    .await
+    .wrap_internal_err("fetching revenue from database")?
+    .into_iter()
+    .map(|x| RevenueStatementDay {
+        date: x.day.date_naive(),
+        gross: x.gross,
+        net: x.net,
+    })
+    .collect::<Vec<_>>();
+
+    let gross = days.iter().map(|x| x.gross).sum::<Decimal>();
+    let net = days.iter().map(|x| x.net).sum::<Decimal>();
+    if net <= Decimal::ZERO {
+        return Err(ApiError::NotFound(eyre::eyre!(
+            "no revenue statement for this project and month"
+        )));
+    }
+
+    let project = DBProject::get_id(project_id.into(), &**pool, &redis)
+        .await
+        .wrap_internal_err("fetching project from database")?
+        .wrap_not_found_err("project not found")?
+        .inner;
+
+    let mut team_ids = vec![project.team_id];
+    if let Some(organization_id) = project.organization_id
+        && let Some(organization) =
+            DBOrganization::get_id(organization_id, &**pool, &redis)
+                .await
+                .wrap_internal_err("fetching organization from database")?
+    {
+        team_ids.push(organization.team_id);
+    }
+
+    let mut payouts_splits = DBPayoutsSplit::get_in_effect(
+        &team_ids,
+        user.id.into(),
+        start,
+        end,
+        &**pool,
+    )
+    .await
+    .wrap_internal_err("fetching payouts splits from database")?;
+    // Membership of the project's own team takes precedence over the
+    // organization's, as it does when revenue is paid out
+    if payouts_splits
+        .iter()
+        .any(|split| split.team_id == project.team_id)
+    {
+        payouts_splits.retain(|split| split.team_id == project.team_id);
+    }
+
+    let statement = RevenueStatement {
+        project_id,
+        project_name: project.name,
+        month,
+        gross,
+        net,
+        payouts_splits: payouts_splits
+            .into_iter()
+            .map(|split| RevenueStatementSplit {
+                payouts_split: split.payouts_split,
+                effective_from: split.effective_from,
+            })
+            .collect(),
+        days,
+    };
+
+    let file_name =
+        format!("revenue-statement-{project_id}-{}", month.format("%Y-%m"));
+
+    match query.format {
+        RevenueStatementFormat::Json => Ok(HttpResponse::Ok().json(statement)),
+        RevenueStatementFormat::Csv => {
+            let csv = std::iter::once("date,gross,net".to_owned())
+                .chain(statement.days.iter().map(|day| {
+                    format!("{},{},{}", day.date, day.gross, day.net)
+                }))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            Ok(HttpResponse::Ok()
+                .content_type("text/csv")
+                .insert_header((
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{file_name}.csv\""),
+                ))
+                .body(csv))
+        }
+        RevenueStatementFormat::Pdf => {
+            let document = gotenberg
+                .wait_for_revenue_statement(&RevenueStatementDocument {
+                    project_id: statement.project_id,
+                    project_name: statement.project_name,
+                    recipient_username: user.username,
+                    month: statement.month,
+                    gross_amount: statement.gross,
+                    net_amount: statement.net,
+                    payouts_splits: statement
+                        .payouts_splits
+                        .iter()
+                        .map(|split| {
+                            (split.effective_from, split.payouts_split)
+                        })
+                        .collect(),
+                    currency_code: "USD".into(),
+                })
+                .await
+                .wrap_internal_err("failed to generate revenue statement")?;
+            let body = base64::engine::general_purpose::STANDARD
+                .decode(&document.body)
+                .wrap_internal_err("failed to decode revenue statement")?;
+
+            Ok(HttpResponse::Ok()
+                .content_type("application/pdf")
+                .insert_header((
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{file_name}.pdf\""),
+                ))
+                .body(body))
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, web};
+use chrono::Utc;
+use eyre::eyre;
+use serde::Deserialize;
+use utoipa::ToSchema;
+use xredis::RedisPool;
+
+use crate::auth::get_user_from_headers;
+use crate::database::PgPool;
+use crate::database::models::pgp_key_item::DBPgpKey;
+use crate::database::models::{DBOrganization, DBTeamMember, DBUser};
+use crate::models::pats::Scopes;
+use crate::models::pgp_keys::PgpKey;
+use crate::models::teams::OrganizationPermissions;
+use crate::queue::session::AuthQueue;
+use crate::routes::ApiError;
+use crate::util::error::Context;
+use crate::util::openpgp;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(user_pgp_keys_get)
+        .service(user_pgp_key_add)
+        .service(user_pgp_key_delete)
+        .service(organization_pgp_keys_get)
+        .service(organization_pgp_key_add)
+        .service(organization_pgp_key_delete);
+}
+
+/// Longest ASCII-armored public key we accept.
+const MAX_PUBLIC_KEY_LENGTH: usize = 64 * 1024;
+
+#[derive(Deserialize, ToSchema)]
+pub struct AddPgpKey {
+    /// The ASCII-armored public key.
+    pub public_key: String,
+}
+
+/// List the OpenPGP keys registered to a user.
+#[utoipa::path(tag = "users", responses((status = OK, body = Vec<PgpKey>)))]
+#[get("/user/{id}/pgp-keys")]
+pub async fn user_pgp_keys_get(
+    info: web::Path<(String,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+) -> Result<web::Json<Vec<PgpKey>>, ApiError> {
+    let user = DBUser::get(&info.into_inner().0, &**pool, &redis)
+        .await
+        .wrap_internal_err("fetching user from database")?
+        .wrap_not_found_err("resource not found")?;
+
+    let keys = DBPgpKey::get_user(user.id, &**pool)
+        .await
+        .wrap_internal_err("fetching PGP keys from database")?;
+    Ok(web::Json(keys.into_iter().map(PgpKey::from).collect()))
+}
+
+/// Register an OpenPGP key to a user, to verify signatures on files of
+/// projects they're a member of.
+#[utoipa::path(
+	tag = "users",
+	request_body = AddPgpKey,
+	responses((status = OK, body = PgpKey))
+)]
+#[post("/user/{id}/pgp-keys")]
+pub async fn user_pgp_key_add(
+    req: HttpRequest,
+    info: web::Path<(String,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    body: web::Json<AddPgpKey>,
+) -> Result<web::Json<PgpKey>, ApiError> {
+    let user = get_editable_user(
+        &req,
+        &info.into_inner().0,
+        &pool,
+        &redis,
+        &session_queue,
+    )
+    .await?;
+
+    let existing = DBPgpKey::get_user(user.id, &**pool)
+        .await
+        .wrap_internal_err("fetching PGP keys from database")?;
+    let key = new_key(body.into_inner(), &existing)?;
+    let key = DBPgpKey {
+        user_id: Some(user.id),
+        ..key
+    };
+    key.insert(&**pool)
+        .await
+        .wrap_internal_err("inserting PGP key")?;
+
+    Ok(web::Json(key.into()))
+}
+
+/// Remove an OpenPGP key from a user.
+#[utoipa::path(tag = "users", responses((status = NO_CONTENT)))]
+#[delete("/user/{id}/pgp-keys/{fingerprint}")]
+pub async fn user_pgp_key_delete(
+    req: HttpRequest,
+    info: web::Path<(String, String)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<HttpResponse, ApiError> {
+    let (id, fingerprint) = info.into_inner();
+    let user =
+        get_editable_user(&req, &id, &pool, &redis, &session_queue).await?;
+
+    let removed = DBPgpKey::remove_user(
+        user.id,
+        &fingerprint.to_ascii_uppercase(),
+        &**pool,
+    )
+    .await
+    .wrap_internal_err("removing PGP key")?;
+    if !removed {
+        return Err(ApiError::NotFound(eyre!("PGP key not found")));
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// List the OpenPGP keys registered to an organization.
+#[utoipa::path(
+	tag = "organizations",
+	responses((status = OK, body = Vec<PgpKey>))
+)]
+#[get("/organization/{id}/pgp-keys")]
+pub async fn organization_pgp_keys_get(
+    info: web::Path<(String,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+) -> Result<web::Json<Vec<PgpKey>>, ApiError> {
+    let organization =
+        DBOrganization::get(&info.into_inner().0, &**pool, &redis)
+            .await
+            .wrap_internal_err("fetching organization from database")?
+            .wrap_not_found_err("resource not found")?;
+
+    let keys = DBPgpKey::get_organization(organization.id, &**pool)
+        .await
+        .wrap_internal_err("fetching PGP keys from database")?;
+    Ok(web::Json(keys.into_iter().map(PgpKey::from).collect()))
+}
+
+/// Register an OpenPGP key to an organization, to verify signatures on
+/// files of its projects.
+#[utoipa::path(
+	tag = "organizations",
+	request_body = AddPgpKey,
+	responses((status = OK, body = PgpKey))
+)]
+#[post("/organization/{id}/pgp-keys")]
+pub async fn organization_pgp_key_add(
+    req: HttpRequest,
+    info: web::Path<(String,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    body: web::Json<AddPgpKey>,
+) -> Result<web::Json<PgpKey>, ApiError> {
+    let organization = get_editable_organization(
+        &req,
+        &info.into_inner().0,
+        &pool,
+        &redis,
+        &session_queue,
+    )
+    .await?;
+
+    let existing = DBPgpKey::get_organization(organization.id, &**pool)
+        .await
+        .wrap_internal_err("fetching PGP keys from database")?;
+    let key = new_key(body.into_inner(), &existing)?;
+    let key = DBPgpKey {
+        organization_id: Some(organization.id),
+        ..key
+    };
+    key.insert(&**pool)
+        .await
+        .wrap_internal_err("inserting PGP key")?;
+
+    Ok(web::Json(key.into()))
+}
+
+/// Remove an OpenPGP key from an organization.
+#[utoipa::path(tag = "organizations", responses((status = NO_CONTENT)))]
+#[delete("/organization/{id}/pgp-keys/{fingerprint}")]
+pub async fn organization_pgp_key_delete(
+    req: HttpRequest,
+    info: web::Path<(String, String)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<HttpResponse, ApiError> {
+    let (id, fingerprint) = info.into_inner();
+    let organization =
+        get_editable_organization(&req, &id, &pool, &redis, &session_queue)
+            .await?;
+
+    let removed = DBPgpKey::remove_organization(
+        organization.id,
+        &fingerprint.to_ascii_uppercase(),
+        &**pool,
+    )
+    .await
+    .wrap_internal_err("removing PGP key")?;
+    if !removed {
+        return Err(ApiError::NotFound(eyre!("PGP key not found")));
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Parses a key to register, with no owner set.
+fn new_key(
+    body: AddPgpKey,
+    existing: &[DBPgpKey],
+) -> Result<DBPgpKey, ApiError> {
+    if body.public_key.len() > MAX_PUBLIC_KEY_LENGTH {
+        return Err(ApiError::Request(eyre!("public key is too long")));
+    }
+
+    let fingerprint = openpgp::parse_public_key(&body.public_key)
+        .map_err(ApiError::Request)?;
+    if existing.iter().any(|key| key.fingerprint == fingerprint) {
+        return Err(ApiError::Conflict(eyre!(
+            "a key with fingerprint {fingerprint} is already registered"
+        )));
+    }
+
+    Ok(DBPgpKey {
+        user_id: None,
+        organization_id: None,
+        fingerprint,
+        public_key: body.public_key,
+        created: Utc::now(),
+    })
+}
+
+async fn get_editable_user(
+    req: &HttpRequest,
+    id: &str,
+    pool: &PgPool,
+    redis: &RedisPool,
+    session_queue: &AuthQueue,
+) -> Result<DBUser, ApiError> {
+    let (_, requester) = get_user_from_headers(
+        req,
+        pool,
+        redis,
+        session_queue,
+        Scopes::USER_WRITE,
+    )
+    .await
+    .wrap_auth_err("authenticating API request")?;
+
+    let user = DBUser::get(id, pool, redis)
+        .await
+        .wrap_internal_err("fetching user from database")?
+        .wrap_not_found_err("resource not found")?;
+
+    if requester.id != user.id.into() && !requester.role.is_admin() {
+        return Err(ApiError::Auth(eyre!(
+            "you do not have permission to edit this user's PGP keys"
+        )));
+    }
+
+    Ok(user)
+}
+
+async fn get_editable_organization(
+    req: &HttpRequest,
+    id: &str,
+    pool: &PgPool,
+    redis: &RedisPool,
+    session_queue: &AuthQueue,
+) -> Result<DBOrganization, ApiError> {
+    let (_, requester) = get_user_from_headers(
+        req,
+        pool,
+        redis,
+        session_queue,
+        Scopes::ORGANIZATION_WRITE,
+    )
+    .await
+    .wrap_auth_err("authenticating API request")?;
+
+    let organization = DBOrganization::get(id, pool, redis)
+        .await
+        .wrap_internal_err("fetching organization from database")?
+        .wrap_not_found_err("resource not found")?;
+
+    let team_member = DBTeamMember::get_from_user_id(
+        organization.team_id,
+        requester.id.into(),
+        pool,
+    )
+    .await
+    .wrap_internal_err("fetching organization team member")?;
+    let permissions = OrganizationPermissions::get_permissions_by_role(
+        &requester.role,
+        &team_member,
+    )
+    .unwrap_or_default();
+    if !permissions.contains(OrganizationPermissions::EDIT_DETAILS) {
+        return Err(ApiError::Auth(eyre!(
+            "you do not have permission to edit this organization's PGP keys"
+        )));
+    }
+
+    Ok(organization)
+}
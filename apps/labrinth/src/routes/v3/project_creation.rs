@@ -726,6 +726,7 @@ async fn project_create_inner(
                 version_data.primary_file.as_deref() == Some(name),
                 None,
                 existing_file_names,
+                None,
                 transaction,
                 redis,
             )
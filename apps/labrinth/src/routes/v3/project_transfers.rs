@@ -0,0 +1,883 @@
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, web};
+use ariadne::ids::UserId;
+use chrono::Utc;
+use eyre::eyre;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use utoipa::ToSchema;
+use xredis::RedisPool;
+
+use crate::auth::get_user_from_headers;
+use crate::database::models::audit_log_item::AuditLogEntryBuilder;
+use crate::database::models::ids::{
+    generate_project_transfer_id, generate_team_member_id,
+};
+use crate::database::models::notification_item::NotificationBuilder;
+use crate::database::models::organization_role_item::DBOrganizationRole;
+use crate::database::models::payouts_split_item::DBPayoutsSplit;
+use crate::database::models::project_transfer_item::DBProjectTransfer;
+use crate::database::models::thread_item::ThreadMessageBuilder;
+use crate::database::models::{
+    DBOrganization, DBOrganizationId, DBProject, DBProjectId, DBTeamId,
+    DBTeamMember, DBUser, DBUserId,
+};
+use crate::database::{PgPool, PgTransaction};
+use crate::models::audit_log::{AuditLogAction, AuditLogTarget};
+use crate::models::ids::{OrganizationId, ProjectTransferId};
+use crate::models::notifications::NotificationBody;
+use crate::models::pats::Scopes;
+use crate::models::project_transfers::{
+    ProjectTransfer, ProjectTransferStatus,
+};
+use crate::models::teams::{DEFAULT_ROLE, ProjectPermissions};
+use crate::models::threads::MessageBody;
+use crate::models::users::User;
+use crate::queue::session::AuthQueue;
+use crate::routes::ApiError;
+use crate::search::SearchState;
+use crate::sync::realtime::{
+    broadcast_realtime_notifications, broadcast_thread_message,
+};
+use crate::util::error::ApiContext as _;
+use crate::util::error::Context;
+
+/// Routes under the `/v3/project` scope.
+pub fn project_config(cfg: &mut web::ServiceConfig) {
+    cfg.service(project_transfers_get)
+        .service(project_transfer_create);
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(user_project_transfers_get)
+        .service(project_transfer_accept)
+        .service(project_transfer_decline)
+        .service(project_transfer_cancel);
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateProjectTransfer {
+    /// The user to transfer the project to. Exactly one of this and
+    /// `new_owner_organization_id` must be set.
+    pub new_owner_user_id: Option<UserId>,
+    /// The organization to transfer the project to.
+    pub new_owner_organization_id: Option<OrganizationId>,
+    /// Whether payouts the current owner, or the owner of the current owning
+    /// organization, has earned from the project, but which aren't available
+    /// yet, move to the new owner. Payouts of the organization's other
+    /// members stay with them. Only possible when transferring to a user.
+    pub transfer_pending_payouts: bool,
+    /// Whether the current owner's payouts split moves to the new owner.
+    /// Only possible when transferring from one user to another.
+    pub transfer_payouts_split: bool,
+}
+
+/// The user or organization a project belongs to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Owner {
+    User(DBUserId),
+    Organization(DBOrganizationId),
+}
+
+impl Owner {
+    fn from_ids(
+        user_id: Option<DBUserId>,
+        organization_id: Option<DBOrganizationId>,
+    ) -> Option<Self> {
+        match (user_id, organization_id) {
+            (Some(id), None) => Some(Self::User(id)),
+            (None, Some(id)) => Some(Self::Organization(id)),
+            _ => None,
+        }
+    }
+
+    fn user_id(self) -> Option<DBUserId> {
+        match self {
+            Self::User(id) => Some(id),
+            Self::Organization(_) => None,
+        }
+    }
+
+    fn organization_id(self) -> Option<DBOrganizationId> {
+        match self {
+            Self::User(_) => None,
+            Self::Organization(id) => Some(id),
+        }
+    }
+}
+
+/// List every transfer of a project.
+#[utoipa::path(
+	context_path = "/project",
+	tag = "projects",
+	responses((status = OK, body = Vec<ProjectTransfer>))
+)]
+#[get("/{id}/transfers")]
+pub async fn project_transfers_get(
+    req: HttpRequest,
+    info: web::Path<(String,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<web::Json<Vec<ProjectTransfer>>, ApiError> {
+    let user = get_user_from_headers(
+        &req,
+        &**pool,
+        &redis,
+        &session_queue,
+        Scopes::PROJECT_READ,
+    )
+    .await
+    .wrap_auth_err("authenticating API request")?
+    .1;
+
+    let project = DBProject::get(&info.into_inner().0, &**pool, &redis)
+        .await
+        .wrap_internal_err("fetching project from database")?
+        .wrap_not_found_err("resource not found")?;
+    let owner = get_current_owner(project.inner.id, &**pool).await?;
+    if !user.role.is_admin() && !is_owner(&user, owner, &pool).await? {
+        return Err(ApiError::Auth(eyre!(
+            "you need to own this project to view its transfers"
+        )));
+    }
+
+    let transfers =
+        DBProjectTransfer::get_all_for_project(project.inner.id, &**pool)
+            .await
+            .wrap_internal_err("fetching project transfers from database")?;
+
+    Ok(web::Json(transfers.into_iter().map(Into::into).collect()))
+}
+
+/// Request to transfer a project to another user or organization.
+///
+/// The transfer takes effect once the recipient accepts it. A user accepts
+/// transfers to them, and an organization's owner accepts transfers to it.
+#[utoipa::path(
+	context_path = "/project",
+	tag = "projects",
+	request_body = CreateProjectTransfer,
+	responses((status = OK, body = ProjectTransfer))
+)]
+#[post("/{id}/transfer")]
+pub async fn project_transfer_create(
+    req: HttpRequest,
+    info: web::Path<(String,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    body: web::Json<CreateProjectTransfer>,
+) -> Result<web::Json<ProjectTransfer>, ApiError> {
+    let user = get_user_from_headers(
+        &req,
+        &**pool,
+        &redis,
+        &session_queue,
+        Scopes::PROJECT_WRITE,
+    )
+    .await
+    .wrap_auth_err("authenticating API request")?
+    .1;
+    let body = body.into_inner();
+
+    let project = DBProject::get(&info.into_inner().0, &**pool, &redis)
+        .await
+        .wrap_internal_err("fetching project from database")?
+        .wrap_not_found_err("resource not found")?;
+    let previous_owner = get_current_owner(project.inner.id, &**pool).await?;
+    if !user.role.is_admin() && !is_owner(&user, previous_owner, &pool).await? {
+        return Err(ApiError::Auth(eyre!(
+            "you need to own this project to transfer it"
+        )));
+    }
+
+    let new_owner = Owner::from_ids(
+        body.new_owner_user_id.map(Into::into),
+        body.new_owner_organization_id.map(Into::into),
+    )
+    .wrap_request_err(
+        "exactly one of `new_owner_user_id` and `new_owner_organization_id` must be set",
+    )?;
+    if new_owner == previous_owner {
+        return Err(ApiError::Request(eyre!(
+            "the project already belongs to the new owner"
+        )));
+    }
+    if body.transfer_pending_payouts && new_owner.user_id().is_none() {
+        return Err(ApiError::Request(eyre!(
+            "pending payouts can only be transferred to a user"
+        )));
+    }
+    if body.transfer_payouts_split
+        && !matches!(
+            (previous_owner, new_owner),
+            (Owner::User(_), Owner::User(_))
+        )
+    {
+        return Err(ApiError::Request(eyre!(
+            "payouts splits can only be transferred from one user to another"
+        )));
+    }
+
+    let recipient = match new_owner {
+        Owner::User(id) => {
+            DBUser::get_id(id, &**pool, &redis)
+                .await
+                .wrap_internal_err("fetching user from database")?
+                .wrap_request_err("the new owner does not exist")?;
+            id
+        }
+        Owner::Organization(id) => {
+            DBOrganization::get_id(id, &**pool, &redis)
+                .await
+                .wrap_internal_err("fetching organization from database")?
+                .wrap_request_err("the new owner does not exist")?;
+            get_organization_owner(id, &**pool).await?
+        }
+    };
+
+    if DBProjectTransfer::get_pending_for_project(project.inner.id, &**pool)
+        .await
+        .wrap_internal_err("fetching project transfer from database")?
+        .is_some()
+    {
+        return Err(ApiError::Request(eyre!(
+            "this project already has a pending transfer"
+        )));
+    }
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .wrap_internal_err("starting database transaction")?;
+
+    let transfer = DBProjectTransfer {
+        id: generate_project_transfer_id(&mut transaction)
+            .await
+            .wrap_internal_err("generating project transfer ID")?,
+        project_id: project.inner.id,
+        requested_by: user.id.into(),
+        previous_owner_user_id: previous_owner.user_id(),
+        previous_owner_organization_id: previous_owner.organization_id(),
+        new_owner_user_id: new_owner.user_id(),
+        new_owner_organization_id: new_owner.organization_id(),
+        transfer_pending_payouts: body.transfer_pending_payouts,
+        transfer_payouts_split: body.transfer_payouts_split,
+        status: ProjectTransferStatus::Pending,
+        created: Utc::now(),
+        resolved: None,
+        resolved_by: None,
+    };
+    transfer
+        .insert(&mut transaction)
+        .await
+        .wrap_internal_err("inserting project transfer")?;
+
+    let notification_id = NotificationBuilder {
+        body: NotificationBody::ProjectTransferRequested {
+            transfer_id: transfer.id.into(),
+            project_id: project.inner.id.into(),
+            requested_by: user.id,
+            new_owner_user_id: transfer.new_owner_user_id.map(Into::into),
+            new_owner_organization_id: transfer
+                .new_owner_organization_id
+                .map(Into::into),
+        },
+    }
+    .insert(recipient, &mut transaction, &redis)
+    .await
+    .wrap_internal_err("inserting project transfer notification")?;
+
+    transaction
+        .commit()
+        .await
+        .wrap_internal_err("committing database transaction")?;
+
+    broadcast_realtime_notifications(&redis, vec![notification_id]).await;
+
+    Ok(web::Json(transfer.into()))
+}
+
+/// List the pending transfers of projects from or to the current user or
+/// their organizations.
+#[utoipa::path(
+	tag = "projects",
+	responses((status = OK, body = Vec<ProjectTransfer>))
+)]
+#[get("/project_transfers")]
+pub async fn user_project_transfers_get(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<web::Json<Vec<ProjectTransfer>>, ApiError> {
+    let user = get_user_from_headers(
+        &req,
+        &**pool,
+        &redis,
+        &session_queue,
+        Scopes::PROJECT_READ,
+    )
+    .await
+    .wrap_auth_err("authenticating API request")?
+    .1;
+
+    let organization_ids = DBUser::get_organizations(user.id.into(), &**pool)
+        .await
+        .wrap_internal_err("fetching user organizations from database")?;
+    let transfers = DBProjectTransfer::get_pending_for_owners(
+        user.id.into(),
+        &organization_ids,
+        &**pool,
+    )
+    .await
+    .wrap_internal_err("fetching project transfers from database")?;
+
+    Ok(web::Json(transfers.into_iter().map(Into::into).collect()))
+}
+
+/// Accept a transfer of a project to the current user or their organization.
+///
+/// The project's thread records the transfer, and the requester and the
+/// previous owner are notified.
+#[utoipa::path(tag = "projects", responses((status = NO_CONTENT)))]
+#[post("/project_transfer/{id}/accept")]
+pub async fn project_transfer_accept(
+    req: HttpRequest,
+    info: web::Path<(ProjectTransferId,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    search_state: web::Data<SearchState>,
+) -> Result<HttpResponse, ApiError> {
+    let user = get_user_from_headers(
+        &req,
+        &**pool,
+        &redis,
+        &session_queue,
+        Scopes::PROJECT_WRITE,
+    )
+    .await
+    .wrap_auth_err("authenticating API request")?
+    .1;
+
+    let transfer = get_pending_transfer(info.into_inner().0, &pool).await?;
+    let new_owner = check_is_recipient(&user, &transfer, &pool).await?;
+    let previous_owner = Owner::from_ids(
+        transfer.previous_owner_user_id,
+        transfer.previous_owner_organization_id,
+    )
+    .wrap_internal_err("project transfer has no previous owner")?;
+
+    let project = DBProject::get_id(transfer.project_id, &**pool, &redis)
+        .await
+        .wrap_internal_err("fetching project from database")?
+        .wrap_not_found_err("resource not found")?;
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .wrap_internal_err("starting database transaction")?;
+
+    if get_current_owner(project.inner.id, &mut transaction).await?
+        != previous_owner
+    {
+        return Err(ApiError::Request(eyre!(
+            "the project's owner has changed since the transfer was requested"
+        )));
+    }
+    if !DBProjectTransfer::resolve(
+        transfer.id,
+        ProjectTransferStatus::Accepted,
+        user.id.into(),
+        &mut transaction,
+    )
+    .await
+    .wrap_internal_err("updating project transfer")?
+    {
+        return Err(ApiError::Request(eyre!(
+            "this transfer is no longer pending"
+        )));
+    }
+
+    let previous_owner_user =
+        get_owner_user(previous_owner, &mut transaction).await?;
+    let new_owner_user = get_owner_user(new_owner, &mut transaction).await?;
+
+    apply_transfer(
+        &transfer,
+        &project.inner,
+        previous_owner,
+        new_owner,
+        &mut transaction,
+    )
+    .await?;
+
+    // Only the previous owner's own pending payouts move, since the other
+    // members of an organization never agreed to the transfer
+    if transfer.transfer_pending_payouts
+        && let Owner::User(new_user_id) = new_owner
+    {
+        sqlx::query!(
+            "
+            UPDATE payouts_values
+            SET user_id = $2
+            WHERE mod_id = $1 AND user_id = $3 AND date_available > NOW()
+            ",
+            project.inner.id as DBProjectId,
+            new_user_id as DBUserId,
+            previous_owner_user as DBUserId,
+        )
+        .execute(&mut transaction)
+        .await
+        .wrap_internal_err("transferring pending payouts")?;
+    }
+
+    let message_id = ThreadMessageBuilder {
+        author_id: Some(user.id.into()),
+        body: MessageBody::OwnershipTransferred {
+            previous_owner_user_id: previous_owner.user_id().map(Into::into),
+            previous_owner_organization_id: previous_owner
+                .organization_id()
+                .map(Into::into),
+            new_owner_user_id: new_owner.user_id().map(Into::into),
+            new_owner_organization_id: new_owner
+                .organization_id()
+                .map(Into::into),
+        },
+        thread_id: project.thread_id,
+        hide_identity: user.role.is_mod(),
+    }
+    .insert(&mut transaction)
+    .await
+    .wrap_internal_err("inserting ownership transfer message")?;
+
+    AuditLogEntryBuilder {
+        organization_id: new_owner
+            .organization_id()
+            .or(previous_owner.organization_id()),
+        ..AuditLogEntryBuilder::project(
+            user.id.into(),
+            project.inner.id,
+            AuditLogAction::ProjectTransferred,
+            AuditLogTarget::Project {
+                project_id: project.inner.id.into(),
+            },
+        )
+    }
+    .before(&serde_json::json!({
+        "owner": previous_owner.user_id().map(UserId::from),
+        "organization_id": previous_owner
+            .organization_id()
+            .map(OrganizationId::from),
+    }))
+    .after(&serde_json::json!({
+        "owner": new_owner.user_id().map(UserId::from),
+        "organization_id": new_owner
+            .organization_id()
+            .map(OrganizationId::from),
+    }))
+    .insert(&mut transaction)
+    .await
+    .wrap_internal_err("inserting audit log entry")?;
+
+    let mut notified = vec![transfer.requested_by];
+    if !notified.contains(&previous_owner_user) {
+        notified.push(previous_owner_user);
+    }
+    let mut notification_ids = Vec::new();
+    for user_id in notified {
+        let notification_id = NotificationBuilder {
+            body: NotificationBody::ProjectTransferred {
+                project_id: project.inner.id.into(),
+                new_owner_user_id: new_owner.user_id().map(Into::into),
+                new_owner_organization_id: new_owner
+                    .organization_id()
+                    .map(Into::into),
+            },
+        }
+        .insert(user_id, &mut transaction, &redis)
+        .await
+        .wrap_internal_err("inserting project transferred notification")?;
+        notification_ids.push(notification_id);
+    }
+
+    transaction
+        .commit()
+        .await
+        .wrap_internal_err("committing database transaction")?;
+
+    DBTeamMember::clear_cache(project.inner.team_id, &redis)
+        .await
+        .wrap_internal_err("clearing cached data from Redis")?;
+    DBUser::clear_project_cache(&[previous_owner_user, new_owner_user], &redis)
+        .await
+        .wrap_internal_err("clearing cached user in Redis")?;
+    super::projects::clear_project_cache_and_queue_search(
+        &redis,
+        &search_state,
+        project.inner.id,
+        project.inner.slug,
+        None,
+    )
+    .await
+    .wrap_api_err(
+        "executing `projects::clear_project_cache_and_queue_search`",
+    )?;
+
+    broadcast_thread_message(
+        &redis,
+        project.thread_id.into(),
+        message_id.into(),
+    )
+    .await;
+    broadcast_realtime_notifications(&redis, notification_ids).await;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Decline a transfer of a project to the current user or their
+/// organization.
+#[utoipa::path(tag = "projects", responses((status = NO_CONTENT)))]
+#[post("/project_transfer/{id}/decline")]
+pub async fn project_transfer_decline(
+    req: HttpRequest,
+    info: web::Path<(ProjectTransferId,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<HttpResponse, ApiError> {
+    let user = get_user_from_headers(
+        &req,
+        &**pool,
+        &redis,
+        &session_queue,
+        Scopes::PROJECT_WRITE,
+    )
+    .await
+    .wrap_auth_err("authenticating API request")?
+    .1;
+
+    let transfer = get_pending_transfer(info.into_inner().0, &pool).await?;
+    check_is_recipient(&user, &transfer, &pool).await?;
+
+    resolve_transfer(&transfer, ProjectTransferStatus::Declined, &user, &pool)
+        .await
+}
+
+/// Cancel a pending transfer of a project.
+///
+/// Transfers can be cancelled by whoever requested them or by the project's
+/// current owner.
+#[utoipa::path(tag = "projects", responses((status = NO_CONTENT)))]
+#[delete("/project_transfer/{id}")]
+pub async fn project_transfer_cancel(
+    req: HttpRequest,
+    info: web::Path<(ProjectTransferId,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<HttpResponse, ApiError> {
+    let user = get_user_from_headers(
+        &req,
+        &**pool,
+        &redis,
+        &session_queue,
+        Scopes::PROJECT_WRITE,
+    )
+    .await
+    .wrap_auth_err("authenticating API request")?
+    .1;
+
+    let transfer = get_pending_transfer(info.into_inner().0, &pool).await?;
+    if transfer.requested_by != user.id.into() && !user.role.is_admin() {
+        let owner = get_current_owner(transfer.project_id, &**pool).await?;
+        if !is_owner(&user, owner, &pool).await? {
+            return Err(ApiError::Auth(eyre!(
+                "you don't have permission to cancel this transfer"
+            )));
+        }
+    }
+
+    resolve_transfer(&transfer, ProjectTransferStatus::Cancelled, &user, &pool)
+        .await
+}
+
+async fn get_pending_transfer(
+    id: ProjectTransferId,
+    pool: &PgPool,
+) -> Result<DBProjectTransfer, ApiError> {
+    let transfer = DBProjectTransfer::get(id.into(), pool)
+        .await
+        .wrap_internal_err("fetching project transfer from database")?
+        .wrap_not_found_err("project transfer not found")?;
+    if transfer.status != ProjectTransferStatus::Pending {
+        return Err(ApiError::Request(eyre!(
+            "this transfer is no longer pending"
+        )));
+    }
+
+    Ok(transfer)
+}
+
+async fn resolve_transfer(
+    transfer: &DBProjectTransfer,
+    status: ProjectTransferStatus,
+    user: &User,
+    pool: &PgPool,
+) -> Result<HttpResponse, ApiError> {
+    if !DBProjectTransfer::resolve(transfer.id, status, user.id.into(), pool)
+        .await
+        .wrap_internal_err("updating project transfer")?
+    {
+        return Err(ApiError::Request(eyre!(
+            "this transfer is no longer pending"
+        )));
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Checks that the user can accept or decline a transfer, returning who the
+/// project is being transferred to.
+async fn check_is_recipient(
+    user: &User,
+    transfer: &DBProjectTransfer,
+    pool: &PgPool,
+) -> Result<Owner, ApiError> {
+    let new_owner = Owner::from_ids(
+        transfer.new_owner_user_id,
+        transfer.new_owner_organization_id,
+    )
+    .wrap_internal_err("project transfer has no new owner")?;
+
+    if !is_owner(user, new_owner, pool).await? {
+        return Err(ApiError::Auth(eyre!(
+            "you don't have permission to respond to this transfer"
+        )));
+    }
+
+    Ok(new_owner)
+}
+
+async fn is_owner(
+    user: &User,
+    owner: Owner,
+    pool: &PgPool,
+) -> Result<bool, ApiError> {
+    let owner_id = get_owner_user(owner, pool).await?;
+    Ok(owner_id == user.id.into())
+}
+
+/// Gets the user who owns a project when it belongs to `owner`: the user
+/// it belongs to, or the owner of the organization it belongs to.
+async fn get_owner_user(
+    owner: Owner,
+    exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+) -> Result<DBUserId, ApiError> {
+    match owner {
+        Owner::User(id) => Ok(id),
+        Owner::Organization(id) => get_organization_owner(id, exec).await,
+    }
+}
+
+async fn get_organization_owner(
+    organization_id: DBOrganizationId,
+    exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+) -> Result<DBUserId, ApiError> {
+    let user_id = sqlx::query_scalar!(
+        "
+        SELECT tm.user_id
+        FROM organizations o
+        INNER JOIN team_members tm ON tm.team_id = o.team_id AND tm.is_owner = TRUE
+        WHERE o.id = $1
+        ",
+        organization_id as DBOrganizationId,
+    )
+    .fetch_optional(exec)
+    .await
+    .wrap_internal_err("fetching organization owner from database")?
+    .wrap_internal_err("organization has no owner")?;
+
+    Ok(DBUserId(user_id))
+}
+
+/// Gets the current owner of a project, locking the project's row when in a
+/// transaction.
+async fn get_current_owner(
+    project_id: DBProjectId,
+    exec: impl crate::database::Executor<'_, Database = sqlx::Postgres>,
+) -> Result<Owner, ApiError> {
+    let row = sqlx::query!(
+        r#"
+        SELECT m.organization_id, tm.user_id "owner_user_id?"
+        FROM mods m
+        LEFT JOIN team_members tm ON tm.team_id = m.team_id AND tm.is_owner = TRUE
+        WHERE m.id = $1
+        FOR UPDATE OF m
+        "#,
+        project_id as DBProjectId,
+    )
+    .fetch_optional(exec)
+    .await
+    .wrap_internal_err("fetching project owner from database")?
+    .wrap_not_found_err("resource not found")?;
+
+    // Projects in an organization belong to it, whoever is marked as the
+    // owner of their team
+    match (row.organization_id, row.owner_user_id) {
+        (Some(id), _) => Ok(Owner::Organization(DBOrganizationId(id))),
+        (None, Some(id)) => Ok(Owner::User(DBUserId(id))),
+        (None, None) => Err(ApiError::Internal(eyre!("project has no owner"))),
+    }
+}
+
+/// Moves a project's ownership from `previous_owner` to `new_owner`,
+/// mirroring how projects are added to and removed from organizations.
+async fn apply_transfer(
+    transfer: &DBProjectTransfer,
+    project: &DBProject,
+    previous_owner: Owner,
+    new_owner: Owner,
+    transaction: &mut PgTransaction<'_>,
+) -> Result<(), ApiError> {
+    if let Owner::Organization(organization_id) = previous_owner {
+        DBOrganizationRole::remove_project_overrides(
+            organization_id,
+            project.id,
+            &mut *transaction,
+        )
+        .await
+        .wrap_internal_err("removing organization role project overrides")?;
+    }
+    if let Owner::User(previous_user_id) = previous_owner {
+        // Splits the previous owner scheduled would otherwise take effect
+        // after their split was moved or they left the team
+        DBPayoutsSplit::remove_scheduled(
+            project.team_id,
+            previous_user_id,
+            &mut *transaction,
+        )
+        .await
+        .wrap_internal_err("removing scheduled payouts splits")?;
+    }
+
+    match new_owner {
+        Owner::User(new_user_id) => {
+            let mut payouts_split = None;
+            if let Owner::User(previous_user_id) = previous_owner {
+                let previous_member = DBTeamMember::get_from_user_id_project(
+                    project.id,
+                    previous_user_id,
+                    false,
+                    &mut *transaction,
+                )
+                .await
+                .wrap_internal_err("fetching team member from database")?
+                .wrap_internal_err("previous owner is not a team member")?;
+                if transfer.transfer_payouts_split {
+                    payouts_split = Some(previous_member.payouts_split);
+                }
+
+                // The previous owner stays on the team, as when transferring
+                // ownership within it
+                DBTeamMember::edit_team_member(
+                    project.team_id,
+                    previous_user_id,
+                    None,
+                    None,
+                    None,
+                    None,
+                    payouts_split.map(|_| Decimal::ZERO),
+                    None,
+                    Some(false),
+                    None,
+                    &mut *transaction,
+                )
+                .await
+                .wrap_internal_err("updating team member in database")?;
+            }
+
+            let new_member = DBTeamMember::get_from_user_id_project(
+                project.id,
+                new_user_id,
+                true,
+                &mut *transaction,
+            )
+            .await
+            .wrap_internal_err("fetching team member from database")?;
+            match new_member {
+                Some(member) => {
+                    DBTeamMember::edit_team_member(
+                        project.team_id,
+                        new_user_id,
+                        Some(ProjectPermissions::all()),
+                        None,
+                        None,
+                        Some(true),
+                        payouts_split.map(|split| member.payouts_split + split),
+                        None,
+                        Some(true),
+                        Some(None),
+                        &mut *transaction,
+                    )
+                    .await
+                    .wrap_internal_err("updating team member in database")?;
+                }
+                None => {
+                    DBTeamMember {
+                        id: generate_team_member_id(&mut *transaction)
+                            .await
+                            .wrap_internal_err("generating team member ID")?,
+                        team_id: project.team_id,
+                        user_id: new_user_id,
+                        role: DEFAULT_ROLE.to_owned(),
+                        is_owner: true,
+                        permissions: ProjectPermissions::all(),
+                        organization_permissions: None,
+                        accepted: true,
+                        payouts_split: payouts_split.unwrap_or(Decimal::ZERO),
+                        ordering: 0,
+                        role_id: None,
+                    }
+                    .insert(&mut *transaction)
+                    .await
+                    .wrap_internal_err("inserting team member into database")?;
+                }
+            }
+        }
+        Owner::Organization(organization_id) => {
+            // The organization's owner owns its projects through it, so
+            // neither they nor the previous owner stay on the project's team
+            let organization_owner_id =
+                get_organization_owner(organization_id, &mut *transaction)
+                    .await?;
+            sqlx::query!(
+                "
+                DELETE FROM team_members
+                WHERE team_id = $1 AND (is_owner = TRUE OR user_id = $2)
+                ",
+                project.team_id as DBTeamId,
+                organization_owner_id as DBUserId,
+            )
+            .execute(&mut *transaction)
+            .await
+            .wrap_internal_err("removing project owners from team")?;
+        }
+    }
+
+    sqlx::query!(
+        "
+        UPDATE mods
+        SET organization_id = $2
+        WHERE id = $1
+        ",
+        project.id as DBProjectId,
+        new_owner.organization_id().map(|x| x.0),
+    )
+    .execute(&mut *transaction)
+    .await
+    .wrap_internal_err("updating project organization")?;
+
+    Ok(())
+}
@@ -0,0 +1,616 @@
+use actix_web::{HttpRequest, HttpResponse, delete, get, patch, post, web};
+use chrono::Utc;
+use eyre::eyre;
+use serde::Deserialize;
+use utoipa::ToSchema;
+use xredis::RedisPool;
+
+use crate::auth::get_user_from_headers;
+use crate::database::PgPool;
+use crate::database::models::ids::generate_project_webhook_id;
+use crate::database::models::project_webhook_item::{
+    DBProjectWebhook, DBProjectWebhookDelivery,
+};
+use crate::database::models::{
+    DBOrganization, DBOrganizationId, DBProject, DBProjectId, DBTeamMember,
+};
+use crate::models::ids::ProjectWebhookId;
+use crate::models::pats::Scopes;
+use crate::models::project_webhooks::{
+    ProjectWebhook, ProjectWebhookDelivery, ProjectWebhookEventType,
+};
+use crate::models::teams::{OrganizationPermissions, ProjectPermissions};
+use crate::queue::project_webhooks::generate_secret;
+use crate::queue::session::AuthQueue;
+use crate::routes::ApiError;
+use crate::util::error::Context;
+use crate::util::ip::check_public_host;
+
+/// Routes under the `/v3/project` scope.
+pub fn project_config(cfg: &mut web::ServiceConfig) {
+    cfg.service(project_webhooks_get)
+        .service(project_webhook_create)
+        .service(project_webhook_edit)
+        .service(project_webhook_delete)
+        .service(project_webhook_deliveries_get);
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(organization_webhooks_get)
+        .service(organization_webhook_create)
+        .service(organization_webhook_edit)
+        .service(organization_webhook_delete)
+        .service(organization_webhook_deliveries_get);
+}
+
+const MAX_WEBHOOKS: usize = 10;
+const MAX_URL_LENGTH: usize = 2048;
+const DELIVERY_LOG_LENGTH: i64 = 100;
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateProjectWebhook {
+    /// HTTPS endpoint to send events to.
+    pub url: String,
+    pub events: Vec<ProjectWebhookEventType>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct EditProjectWebhook {
+    pub url: Option<String>,
+    pub events: Option<Vec<ProjectWebhookEventType>>,
+}
+
+/// Who webhooks are being managed for, once the user has been checked to
+/// have permission to manage them.
+#[derive(Clone, Copy)]
+enum WebhookOwner {
+    Project(DBProjectId),
+    Organization(DBOrganizationId),
+}
+
+impl WebhookOwner {
+    fn owns(self, webhook: &DBProjectWebhook) -> bool {
+        match self {
+            Self::Project(id) => webhook.project_id == Some(id),
+            Self::Organization(id) => webhook.organization_id == Some(id),
+        }
+    }
+}
+
+/// List a project's webhooks.
+#[utoipa::path(
+	context_path = "/project",
+	tag = "projects",
+	responses((status = OK, body = Vec<ProjectWebhook>))
+)]
+#[get("/{id}/webhooks")]
+pub async fn project_webhooks_get(
+    req: HttpRequest,
+    info: web::Path<(String,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<web::Json<Vec<ProjectWebhook>>, ApiError> {
+    let owner = get_project_owner(
+        &req,
+        &info.into_inner().0,
+        Scopes::PROJECT_READ,
+        &pool,
+        &redis,
+        &session_queue,
+    )
+    .await?;
+    list_webhooks(owner, &pool).await
+}
+
+/// Subscribe an endpoint to events of a project.
+///
+/// Deliveries are signed with the returned `secret`, which is only returned
+/// here.
+#[utoipa::path(
+	context_path = "/project",
+	tag = "projects",
+	request_body = CreateProjectWebhook,
+	responses((status = OK, body = ProjectWebhook))
+)]
+#[post("/{id}/webhooks")]
+pub async fn project_webhook_create(
+    req: HttpRequest,
+    info: web::Path<(String,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    body: web::Json<CreateProjectWebhook>,
+) -> Result<web::Json<ProjectWebhook>, ApiError> {
+    let owner = get_project_owner(
+        &req,
+        &info.into_inner().0,
+        Scopes::PROJECT_WRITE,
+        &pool,
+        &redis,
+        &session_queue,
+    )
+    .await?;
+    create_webhook(owner, body.into_inner(), &pool).await
+}
+
+/// Edit a project's webhook.
+#[utoipa::path(
+	context_path = "/project",
+	tag = "projects",
+	request_body = EditProjectWebhook,
+	responses((status = NO_CONTENT))
+)]
+#[patch("/{id}/webhooks/{webhook_id}")]
+pub async fn project_webhook_edit(
+    req: HttpRequest,
+    info: web::Path<(String, ProjectWebhookId)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    body: web::Json<EditProjectWebhook>,
+) -> Result<HttpResponse, ApiError> {
+    let (id, webhook_id) = info.into_inner();
+    let owner = get_project_owner(
+        &req,
+        &id,
+        Scopes::PROJECT_WRITE,
+        &pool,
+        &redis,
+        &session_queue,
+    )
+    .await?;
+    edit_webhook(owner, webhook_id, body.into_inner(), &pool).await
+}
+
+/// Remove a project's webhook.
+#[utoipa::path(
+	context_path = "/project",
+	tag = "projects",
+	responses((status = NO_CONTENT))
+)]
+#[delete("/{id}/webhooks/{webhook_id}")]
+pub async fn project_webhook_delete(
+    req: HttpRequest,
+    info: web::Path<(String, ProjectWebhookId)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<HttpResponse, ApiError> {
+    let (id, webhook_id) = info.into_inner();
+    let owner = get_project_owner(
+        &req,
+        &id,
+        Scopes::PROJECT_WRITE,
+        &pool,
+        &redis,
+        &session_queue,
+    )
+    .await?;
+    delete_webhook(owner, webhook_id, &pool).await
+}
+
+/// List the latest deliveries to a project's webhook, newest first.
+#[utoipa::path(
+	context_path = "/project",
+	tag = "projects",
+	responses((status = OK, body = Vec<ProjectWebhookDelivery>))
+)]
+#[get("/{id}/webhooks/{webhook_id}/deliveries")]
+pub async fn project_webhook_deliveries_get(
+    req: HttpRequest,
+    info: web::Path<(String, ProjectWebhookId)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<web::Json<Vec<ProjectWebhookDelivery>>, ApiError> {
+    let (id, webhook_id) = info.into_inner();
+    let owner = get_project_owner(
+        &req,
+        &id,
+        Scopes::PROJECT_READ,
+        &pool,
+        &redis,
+        &session_queue,
+    )
+    .await?;
+    list_deliveries(owner, webhook_id, &pool).await
+}
+
+/// List an organization's webhooks.
+#[utoipa::path(
+	tag = "organizations",
+	responses((status = OK, body = Vec<ProjectWebhook>))
+)]
+#[get("/organization/{id}/webhooks")]
+pub async fn organization_webhooks_get(
+    req: HttpRequest,
+    info: web::Path<(String,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<web::Json<Vec<ProjectWebhook>>, ApiError> {
+    let owner = get_organization_owner(
+        &req,
+        &info.into_inner().0,
+        Scopes::ORGANIZATION_READ,
+        &pool,
+        &redis,
+        &session_queue,
+    )
+    .await?;
+    list_webhooks(owner, &pool).await
+}
+
+/// Subscribe an endpoint to events of all of an organization's projects.
+///
+/// Deliveries are signed with the returned `secret`, which is only returned
+/// here.
+#[utoipa::path(
+	tag = "organizations",
+	request_body = CreateProjectWebhook,
+	responses((status = OK, body = ProjectWebhook))
+)]
+#[post("/organization/{id}/webhooks")]
+pub async fn organization_webhook_create(
+    req: HttpRequest,
+    info: web::Path<(String,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    body: web::Json<CreateProjectWebhook>,
+) -> Result<web::Json<ProjectWebhook>, ApiError> {
+    let owner = get_organization_owner(
+        &req,
+        &info.into_inner().0,
+        Scopes::ORGANIZATION_WRITE,
+        &pool,
+        &redis,
+        &session_queue,
+    )
+    .await?;
+    create_webhook(owner, body.into_inner(), &pool).await
+}
+
+/// Edit an organization's webhook.
+#[utoipa::path(
+	tag = "organizations",
+	request_body = EditProjectWebhook,
+	responses((status = NO_CONTENT))
+)]
+#[patch("/organization/{id}/webhooks/{webhook_id}")]
+pub async fn organization_webhook_edit(
+    req: HttpRequest,
+    info: web::Path<(String, ProjectWebhookId)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    body: web::Json<EditProjectWebhook>,
+) -> Result<HttpResponse, ApiError> {
+    let (id, webhook_id) = info.into_inner();
+    let owner = get_organization_owner(
+        &req,
+        &id,
+        Scopes::ORGANIZATION_WRITE,
+        &pool,
+        &redis,
+        &session_queue,
+    )
+    .await?;
+    edit_webhook(owner, webhook_id, body.into_inner(), &pool).await
+}
+
+/// Remove an organization's webhook.
+#[utoipa::path(tag = "organizations", responses((status = NO_CONTENT)))]
+#[delete("/organization/{id}/webhooks/{webhook_id}")]
+pub async fn organization_webhook_delete(
+    req: HttpRequest,
+    info: web::Path<(String, ProjectWebhookId)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<HttpResponse, ApiError> {
+    let (id, webhook_id) = info.into_inner();
+    let owner = get_organization_owner(
+        &req,
+        &id,
+        Scopes::ORGANIZATION_WRITE,
+        &pool,
+        &redis,
+        &session_queue,
+    )
+    .await?;
+    delete_webhook(owner, webhook_id, &pool).await
+}
+
+/// List the latest deliveries to an organization's webhook, newest first.
+#[utoipa::path(
+	tag = "organizations",
+	responses((status = OK, body = Vec<ProjectWebhookDelivery>))
+)]
+#[get("/organization/{id}/webhooks/{webhook_id}/deliveries")]
+pub async fn organization_webhook_deliveries_get(
+    req: HttpRequest,
+    info: web::Path<(String, ProjectWebhookId)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<web::Json<Vec<ProjectWebhookDelivery>>, ApiError> {
+    let (id, webhook_id) = info.into_inner();
+    let owner = get_organization_owner(
+        &req,
+        &id,
+        Scopes::ORGANIZATION_READ,
+        &pool,
+        &redis,
+        &session_queue,
+    )
+    .await?;
+    list_deliveries(owner, webhook_id, &pool).await
+}
+
+async fn list_webhooks(
+    owner: WebhookOwner,
+    pool: &PgPool,
+) -> Result<web::Json<Vec<ProjectWebhook>>, ApiError> {
+    let webhooks = match owner {
+        WebhookOwner::Project(id) => {
+            DBProjectWebhook::get_project(id, pool).await
+        }
+        WebhookOwner::Organization(id) => {
+            DBProjectWebhook::get_organization(id, pool).await
+        }
+    }
+    .wrap_internal_err("fetching webhooks from database")?;
+
+    Ok(web::Json(
+        webhooks.into_iter().map(ProjectWebhook::from).collect(),
+    ))
+}
+
+async fn create_webhook(
+    owner: WebhookOwner,
+    body: CreateProjectWebhook,
+    pool: &PgPool,
+) -> Result<web::Json<ProjectWebhook>, ApiError> {
+    validate_url(&body.url)?;
+    let events = validate_events(body.events)?;
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .wrap_internal_err("starting database transaction")?;
+
+    let existing = match owner {
+        WebhookOwner::Project(id) => {
+            DBProjectWebhook::get_project(id, &mut transaction).await
+        }
+        WebhookOwner::Organization(id) => {
+            DBProjectWebhook::get_organization(id, &mut transaction).await
+        }
+    }
+    .wrap_internal_err("fetching webhooks from database")?;
+    if existing.len() >= MAX_WEBHOOKS {
+        return Err(ApiError::Request(eyre!(
+            "you may only have up to {MAX_WEBHOOKS} webhooks"
+        )));
+    }
+
+    let webhook = DBProjectWebhook {
+        id: generate_project_webhook_id(&mut transaction)
+            .await
+            .wrap_internal_err("generating webhook ID")?,
+        project_id: match owner {
+            WebhookOwner::Project(id) => Some(id),
+            WebhookOwner::Organization(_) => None,
+        },
+        organization_id: match owner {
+            WebhookOwner::Project(_) => None,
+            WebhookOwner::Organization(id) => Some(id),
+        },
+        url: body.url,
+        secret: generate_secret(),
+        events,
+        created: Utc::now(),
+    };
+    webhook
+        .insert(&mut transaction)
+        .await
+        .wrap_internal_err("inserting webhook")?;
+
+    transaction
+        .commit()
+        .await
+        .wrap_internal_err("committing database transaction")?;
+
+    let secret = webhook.secret.clone();
+    Ok(web::Json(ProjectWebhook {
+        secret: Some(secret),
+        ..webhook.into()
+    }))
+}
+
+async fn edit_webhook(
+    owner: WebhookOwner,
+    webhook_id: ProjectWebhookId,
+    body: EditProjectWebhook,
+    pool: &PgPool,
+) -> Result<HttpResponse, ApiError> {
+    let mut webhook = get_owned_webhook(owner, webhook_id, pool).await?;
+
+    if let Some(url) = body.url {
+        validate_url(&url)?;
+        webhook.url = url;
+    }
+    if let Some(events) = body.events {
+        webhook.events = validate_events(events)?;
+    }
+
+    webhook
+        .update(pool)
+        .await
+        .wrap_internal_err("updating webhook")?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+async fn delete_webhook(
+    owner: WebhookOwner,
+    webhook_id: ProjectWebhookId,
+    pool: &PgPool,
+) -> Result<HttpResponse, ApiError> {
+    let webhook = get_owned_webhook(owner, webhook_id, pool).await?;
+
+    DBProjectWebhook::remove(webhook.id, pool)
+        .await
+        .wrap_internal_err("removing webhook")?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+async fn list_deliveries(
+    owner: WebhookOwner,
+    webhook_id: ProjectWebhookId,
+    pool: &PgPool,
+) -> Result<web::Json<Vec<ProjectWebhookDelivery>>, ApiError> {
+    let webhook = get_owned_webhook(owner, webhook_id, pool).await?;
+
+    let deliveries = DBProjectWebhookDelivery::get_webhook(
+        webhook.id,
+        DELIVERY_LOG_LENGTH,
+        pool,
+    )
+    .await
+    .wrap_internal_err("fetching webhook deliveries from database")?;
+
+    Ok(web::Json(
+        deliveries
+            .into_iter()
+            .map(ProjectWebhookDelivery::from)
+            .collect(),
+    ))
+}
+
+async fn get_owned_webhook(
+    owner: WebhookOwner,
+    webhook_id: ProjectWebhookId,
+    pool: &PgPool,
+) -> Result<DBProjectWebhook, ApiError> {
+    DBProjectWebhook::get(webhook_id.into(), pool)
+        .await
+        .wrap_internal_err("fetching webhook from database")?
+        .filter(|webhook| owner.owns(webhook))
+        .wrap_not_found_err("webhook not found")
+}
+
+fn validate_url(url: &str) -> Result<(), ApiError> {
+    if url.len() > MAX_URL_LENGTH {
+        return Err(ApiError::Request(eyre!("webhook URL is too long")));
+    }
+
+    let parsed =
+        url::Url::parse(url).wrap_request_err("invalid webhook URL")?;
+    if parsed.scheme() != "https" || parsed.host().is_none() {
+        return Err(ApiError::Request(eyre!("webhook URL must use HTTPS")));
+    }
+    // Hosts given as names are checked again on every delivery, since what
+    // they resolve to can change
+    check_public_host(&parsed).map_err(|error| {
+        ApiError::Request(eyre!("webhook URL must be public: {error}"))
+    })?;
+
+    Ok(())
+}
+
+fn validate_events(
+    mut events: Vec<ProjectWebhookEventType>,
+) -> Result<Vec<ProjectWebhookEventType>, ApiError> {
+    events.sort_by_key(|event| event.as_str());
+    events.dedup();
+    if events.is_empty() {
+        return Err(ApiError::Request(eyre!(
+            "webhook must be subscribed to at least one event"
+        )));
+    }
+    Ok(events)
+}
+
+async fn get_project_owner(
+    req: &HttpRequest,
+    id: &str,
+    scopes: Scopes,
+    pool: &PgPool,
+    redis: &RedisPool,
+    session_queue: &AuthQueue,
+) -> Result<WebhookOwner, ApiError> {
+    let (_, user) =
+        get_user_from_headers(req, pool, redis, session_queue, scopes)
+            .await
+            .wrap_auth_err("authenticating API request")?;
+
+    let project = DBProject::get(id, pool, redis)
+        .await
+        .wrap_internal_err("fetching project from database")?
+        .wrap_not_found_err("resource not found")?;
+
+    let (team_member, organization_team_member) =
+        DBTeamMember::get_for_project_permissions(
+            &project.inner,
+            user.id.into(),
+            pool,
+        )
+        .await
+        .wrap_internal_err("fetching project permissions")?;
+    let permissions = ProjectPermissions::get_permissions_by_role(
+        &user.role,
+        &team_member,
+        &organization_team_member,
+    )
+    .unwrap_or_default();
+    if !permissions.contains(ProjectPermissions::MANAGE_WEBHOOKS) {
+        return Err(ApiError::Auth(eyre!(
+            "you do not have permission to manage this project's webhooks"
+        )));
+    }
+
+    Ok(WebhookOwner::Project(project.inner.id))
+}
+
+async fn get_organization_owner(
+    req: &HttpRequest,
+    id: &str,
+    scopes: Scopes,
+    pool: &PgPool,
+    redis: &RedisPool,
+    session_queue: &AuthQueue,
+) -> Result<WebhookOwner, ApiError> {
+    let (_, user) =
+        get_user_from_headers(req, pool, redis, session_queue, scopes)
+            .await
+            .wrap_auth_err("authenticating API request")?;
+
+    let organization = DBOrganization::get(id, pool, redis)
+        .await
+        .wrap_internal_err("fetching organization from database")?
+        .wrap_not_found_err("resource not found")?;
+
+    let team_member = DBTeamMember::get_from_user_id(
+        organization.team_id,
+        user.id.into(),
+        pool,
+    )
+    .await
+    .wrap_internal_err("fetching organization team member")?;
+    let permissions = OrganizationPermissions::get_permissions_by_role(
+        &user.role,
+        &team_member,
+    )
+    .unwrap_or_default();
+    if !permissions.contains(OrganizationPermissions::MANAGE_WEBHOOKS) {
+        return Err(ApiError::Auth(eyre!(
+            "you do not have permission to manage this organization's webhooks"
+        )));
+    }
+
+    Ok(WebhookOwner::Organization(organization.id))
+}
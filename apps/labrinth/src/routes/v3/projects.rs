@@ -5,8 +5,10 @@ use std::collections::HashMap;
 
 use crate::auth::checks::{filter_visible_versions, is_visible_project};
 use crate::auth::{filter_visible_projects, get_user_from_headers};
+use crate::database::models::audit_log_item::AuditLogEntryBuilder;
 use crate::database::models::notification_item::NotificationBuilder;
 use crate::database::models::project_item::{DBGalleryItem, DBModCategory};
+use crate::database::models::project_webhook_item::DBProjectWebhookDelivery;
 use crate::database::models::thread_item::ThreadMessageBuilder;
 use crate::database::models::{
     DBModerationLock, DBProjectId, DBTeamMember, ids as db_ids, image_item,
@@ -15,6 +17,7 @@ use crate::database::{self, models as db_models};
 use crate::database::{PgPool, PgTransaction, ReadOnlyPgPool};
 use crate::env::ENV;
 use crate::file_hosting::{FileHost, FileHostPublicity};
+use crate::models::audit_log::{AuditLogAction, AuditLogTarget};
 use crate::models::disclosures::{
     DisclosureLockStatus, ProjectDisclosure, ProjectDisclosureType,
 };
@@ -22,12 +25,13 @@ use crate::models::ids::{ProjectId, VersionId};
 use crate::models::images::ImageContext;
 use crate::models::notifications::NotificationBody;
 use crate::models::pats::Scopes;
+use crate::models::project_webhooks::ProjectWebhookEvent;
 use crate::models::projects::{
     MonetizationStatus, Project, ProjectStatus, SideTypesMigrationReviewStatus,
 };
 use crate::models::teams::{DEFAULT_ROLE, ProjectPermissions};
 use crate::models::threads::MessageBody;
-use crate::models::users::DELETED_USER;
+use crate::models::users::{DELETED_USER, User};
 use crate::models::{self, exp};
 use crate::queue::session::AuthQueue;
 use crate::routes::ApiError;
@@ -35,6 +39,10 @@ use crate::routes::internal::delphi;
 use crate::search::{
     SearchBackend, SearchQuery, SearchRequest, SearchResults, SearchState,
 };
+use crate::sync::realtime::{
+    broadcast_project_status, broadcast_realtime_notifications,
+    broadcast_thread_message,
+};
 use crate::util::error::Context;
 use crate::util::img;
 use crate::util::img::{delete_old_images, upload_image_optimized};
@@ -471,6 +479,7 @@ pub async fn project_edit_internal(
     };
 
     let id = project_item.inner.id;
+    let old_project = Project::from(project_item.clone());
 
     let (team_member, organization_team_member) =
         db_models::DBTeamMember::get_for_project_permissions(
@@ -538,6 +547,9 @@ pub async fn project_edit_internal(
         .wrap_internal_err("querying database for `project_edit_internal`")?;
     }
 
+    // Realtime events for a status change, broadcast once committed
+    let mut status_change = None;
+
     if let Some(status) = &new_project.status {
         if !perms.contains(ProjectPermissions::EDIT_DETAILS) {
             return Err(ApiError::Auth(eyre::eyre!(
@@ -610,174 +622,17 @@ pub async fn project_edit_internal(
                 ))));
             }
 
-            if status == &ProjectStatus::Processing {
-                if project_item.versions.is_empty() {
-                    return Err(ApiError::Request(eyre::eyre!(String::from(
-                        "Project submitted for review with no initial versions",
-                    ))));
-                }
-
-                sqlx::query!(
-                    "
-                    UPDATE mods
-                    SET moderation_message = NULL, moderation_message_body = NULL, queued = NOW()
-                    WHERE (id = $1)
-                    ",
-                    id as db_ids::DBProjectId,
-                )
-                .execute(&mut transaction)
-                .await
-                .wrap_internal_err(
-                    "querying database for `project_edit_internal`",
-                )?;
-            }
-
-            if status.is_approved() && !project_item.inner.status.is_approved()
-            {
-                sqlx::query!(
-                    "
-                    UPDATE mods
-                    SET approved = NOW()
-                    WHERE id = $1 AND approved IS NULL
-                    ",
-                    id as db_ids::DBProjectId,
-                )
-                .execute(&mut transaction)
-                .await
-                .wrap_internal_err(
-                    "querying database for `project_edit_internal`",
-                )?;
-            }
-
-            if status.is_searchable()
-                && !project_item.inner.webhook_sent
-                && !ENV.PUBLIC_DISCORD_WEBHOOK.is_empty()
-                && project_item.inner.components.minecraft_server.is_none()
-            {
-                crate::util::webhook::send_discord_webhook(
-                    project_item.inner.id.into(),
-                    &pool,
-                    &redis,
-                    &ENV.PUBLIC_DISCORD_WEBHOOK,
-                    None,
-                )
-                .await
-                .ok();
-
-                sqlx::query!(
-                    "
-                    UPDATE mods
-                    SET webhook_sent = TRUE
-                    WHERE id = $1
-                    ",
-                    id as db_ids::DBProjectId,
-                )
-                .execute(&mut transaction)
-                .await
-                .wrap_internal_err(
-                    "querying database for `project_edit_internal`",
-                )?;
-            }
-
-            if user.role.is_mod() && !ENV.MODERATION_SLACK_WEBHOOK.is_empty() {
-                crate::util::webhook::send_slack_project_webhook(
-                    project_item.inner.id.into(),
-                    &pool,
-                    &redis,
-                    &ENV.MODERATION_SLACK_WEBHOOK,
-                    Some(
-                        format!(
-                            "*<{}/user/{}|{}>* changed project status from *{}* to *{}*",
-                            ENV.SITE_URL,
-                            user.username,
-                            user.username,
-                            &project_item.inner.status.as_friendly_str(),
-                            status.as_friendly_str(),
-                        )
-                        .to_string(),
-                    ),
-                )
-                .await
-                .ok();
-            }
-
-            if team_member.is_none_or(|x| !x.accepted) {
-                let notified_members = sqlx::query!(
-                    "
-                    SELECT tm.user_id id
-                    FROM team_members tm
-                    WHERE tm.team_id = $1 AND tm.accepted
-                    ",
-                    project_item.inner.team_id as db_ids::DBTeamId
-                )
-                .fetch(&mut transaction)
-                .map_ok(|c| db_models::DBUserId(c.id))
-                .try_collect::<Vec<_>>()
-                .await
-                .wrap_internal_err("fetching notified members from database")?;
-
-                NotificationBuilder {
-                    body: NotificationBody::StatusChange {
-                        project_id: project_item.inner.id.into(),
-                        old_status: project_item.inner.status,
-                        new_status: *status,
-                    },
-                }
-                .insert_many(notified_members.clone(), &mut transaction, &redis)
-                .await
-                .wrap_internal_err(
-                    "inserting database records for `project_edit_internal`",
-                )?;
-
-                NotificationBuilder {
-                    body: if status.is_approved() {
-                        NotificationBody::ProjectStatusApproved {
-                            project_id: project_item.inner.id.into(),
-                        }
-                    } else {
-                        NotificationBody::ProjectStatusNeutral {
-                            project_id: project_item.inner.id.into(),
-                            old_status: project_item.inner.status,
-                            new_status: *status,
-                        }
-                    },
-                }
-                .insert_many(notified_members, &mut transaction, &redis)
-                .await
-                .wrap_internal_err(
-                    "inserting database records for `project_edit_internal`",
-                )?;
-            }
-
-            ThreadMessageBuilder {
-                author_id: Some(user.id.into()),
-                body: MessageBody::StatusChange {
-                    new_status: *status,
-                    old_status: project_item.inner.status,
-                },
-                thread_id: project_item.thread_id,
-                hide_identity: user.role.is_mod(),
-            }
-            .insert(&mut transaction)
-            .await
-            .wrap_internal_err(
-                "inserting database records for `project_edit_internal`",
-            )?;
-
-            sqlx::query!(
-                "
-                UPDATE mods
-                SET status = $1
-                WHERE (id = $2)
-                ",
-                status.as_str(),
-                id as db_ids::DBProjectId,
+            let (message_id, notification_ids) = change_project_status(
+                &project_item,
+                *status,
+                &user,
+                team_member.is_none_or(|x| !x.accepted),
+                &pool,
+                &redis,
+                &mut transaction,
             )
-            .execute(&mut transaction)
-            .await
-            .wrap_internal_err(
-                "querying database for `project_edit_internal`",
-            )?;
+            .await?;
+            status_change = Some((*status, message_id, notification_ids));
 
             if sync_archival_disclosure
                 && archival_disclosure.is_some_and(|disclosure| {
@@ -1339,11 +1194,31 @@ pub async fn project_edit_internal(
     .await
     .wrap_api_err("deleting unused images")?;
 
+    log_project_edit(user.id.into(), old_project, &mut transaction, &redis)
+        .await?;
+
     transaction
         .commit()
         .await
         .wrap_internal_err("committing database transaction")?;
 
+    if let Some((new_status, message_id, notification_ids)) = status_change {
+        broadcast_thread_message(
+            &redis,
+            project_item.thread_id.into(),
+            message_id.into(),
+        )
+        .await;
+        broadcast_project_status(
+            &redis,
+            project_item.inner.id.into(),
+            project_item.inner.status,
+            new_status,
+        )
+        .await;
+        broadcast_realtime_notifications(&redis, notification_ids).await;
+    }
+
     if became_unsearchable {
         db_models::DBProject::clear_cache(
             project_item.inner.id,
@@ -1385,6 +1260,245 @@ pub async fn project_edit_internal(
     Ok(HttpResponse::NoContent().body(""))
 }
 
+/// Changes a project's status, with the notifications, thread message and
+/// webhooks that go with it, returning the IDs of the thread message and
+/// notifications to broadcast once the transaction commits.
+///
+/// Permission checks are left to the caller. `notify_team` is whether the
+/// project's team is notified of the change, for when someone outside of
+/// it made it.
+pub(crate) async fn change_project_status(
+    project_item: &db_models::project_item::ProjectQueryResult,
+    status: ProjectStatus,
+    user: &User,
+    notify_team: bool,
+    pool: &PgPool,
+    redis: &RedisPool,
+    transaction: &mut PgTransaction<'_>,
+) -> Result<(db_ids::DBThreadMessageId, Vec<db_ids::DBNotificationId>), ApiError>
+{
+    if status == ProjectStatus::Processing {
+        if project_item.versions.is_empty() {
+            return Err(ApiError::Request(eyre::eyre!(String::from(
+                "Project submitted for review with no initial versions",
+            ))));
+        }
+
+        sqlx::query!(
+            "
+            UPDATE mods
+            SET moderation_message = NULL, moderation_message_body = NULL, queued = NOW()
+            WHERE (id = $1)
+            ",
+            project_item.inner.id as db_ids::DBProjectId,
+        )
+        .execute(&mut *transaction)
+        .await
+        .wrap_internal_err(
+            "querying database for `project_edit_internal`",
+        )?;
+    }
+
+    if status.is_approved() && !project_item.inner.status.is_approved() {
+        sqlx::query!(
+            "
+            UPDATE mods
+            SET approved = NOW()
+            WHERE id = $1 AND approved IS NULL
+            ",
+            project_item.inner.id as db_ids::DBProjectId,
+        )
+        .execute(&mut *transaction)
+        .await
+        .wrap_internal_err("querying database for `project_edit_internal`")?;
+    }
+
+    if status.is_searchable()
+        && !project_item.inner.webhook_sent
+        && !ENV.PUBLIC_DISCORD_WEBHOOK.is_empty()
+        && project_item.inner.components.minecraft_server.is_none()
+    {
+        crate::util::webhook::send_discord_webhook(
+            project_item.inner.id.into(),
+            pool,
+            redis,
+            &ENV.PUBLIC_DISCORD_WEBHOOK,
+            None,
+        )
+        .await
+        .ok();
+
+        sqlx::query!(
+            "
+            UPDATE mods
+            SET webhook_sent = TRUE
+            WHERE id = $1
+            ",
+            project_item.inner.id as db_ids::DBProjectId,
+        )
+        .execute(&mut *transaction)
+        .await
+        .wrap_internal_err("querying database for `project_edit_internal`")?;
+    }
+
+    if user.role.is_mod() && !ENV.MODERATION_SLACK_WEBHOOK.is_empty() {
+        crate::util::webhook::send_slack_project_webhook(
+            project_item.inner.id.into(),
+            pool,
+            redis,
+            &ENV.MODERATION_SLACK_WEBHOOK,
+            Some(
+                format!(
+                    "*<{}/user/{}|{}>* changed project status from *{}* to *{}*",
+                    ENV.SITE_URL,
+                    user.username,
+                    user.username,
+                    &project_item.inner.status.as_friendly_str(),
+                    status.as_friendly_str(),
+                )
+                .to_string(),
+            ),
+        )
+        .await
+        .ok();
+    }
+
+    let mut notification_ids = Vec::new();
+    if notify_team {
+        let notified_members = sqlx::query!(
+            "
+            SELECT tm.user_id id
+            FROM team_members tm
+            WHERE tm.team_id = $1 AND tm.accepted
+            ",
+            project_item.inner.team_id as db_ids::DBTeamId
+        )
+        .fetch(&mut *transaction)
+        .map_ok(|c| db_models::DBUserId(c.id))
+        .try_collect::<Vec<_>>()
+        .await
+        .wrap_internal_err("fetching notified members from database")?;
+
+        notification_ids.extend(
+            NotificationBuilder {
+                body: NotificationBody::StatusChange {
+                    project_id: project_item.inner.id.into(),
+                    old_status: project_item.inner.status,
+                    new_status: status,
+                },
+            }
+            .insert_many(notified_members.clone(), &mut *transaction, redis)
+            .await
+            .wrap_internal_err(
+                "inserting database records for `project_edit_internal`",
+            )?,
+        );
+
+        notification_ids.extend(
+            NotificationBuilder {
+                body: if status.is_approved() {
+                    NotificationBody::ProjectStatusApproved {
+                        project_id: project_item.inner.id.into(),
+                    }
+                } else {
+                    NotificationBody::ProjectStatusNeutral {
+                        project_id: project_item.inner.id.into(),
+                        old_status: project_item.inner.status,
+                        new_status: status,
+                    }
+                },
+            }
+            .insert_many(notified_members, &mut *transaction, redis)
+            .await
+            .wrap_internal_err(
+                "inserting database records for `project_edit_internal`",
+            )?,
+        );
+    }
+
+    let message_id = ThreadMessageBuilder {
+        author_id: Some(user.id.into()),
+        body: MessageBody::StatusChange {
+            new_status: status,
+            old_status: project_item.inner.status,
+        },
+        thread_id: project_item.thread_id,
+        hide_identity: user.role.is_mod(),
+    }
+    .insert(&mut *transaction)
+    .await
+    .wrap_internal_err(
+        "inserting database records for `project_edit_internal`",
+    )?;
+
+    sqlx::query!(
+        "
+        UPDATE mods
+        SET status = $1
+        WHERE (id = $2)
+        ",
+        status.as_str(),
+        project_item.inner.id as db_ids::DBProjectId,
+    )
+    .execute(&mut *transaction)
+    .await
+    .wrap_internal_err("querying database for `project_edit_internal`")?;
+
+    DBProjectWebhookDelivery::insert_for_event(
+        &ProjectWebhookEvent::ProjectStatusChanged {
+            project_id: project_item.inner.id.into(),
+            old_status: project_item.inner.status,
+            new_status: status,
+        },
+        &mut *transaction,
+    )
+    .await
+    .wrap_internal_err("queueing project webhook deliveries")?;
+
+    Ok((message_id, notification_ids))
+}
+
+/// Records the changes an edit made to a project in the audit log.
+///
+/// This must be called with the transaction making the edit, before it is
+/// committed, as the edited project is read through it to be compared
+/// against `old_project`.
+pub(crate) async fn log_project_edit(
+    actor_id: db_ids::DBUserId,
+    old_project: Project,
+    transaction: &mut PgTransaction<'_>,
+    redis: &RedisPool,
+) -> Result<(), ApiError> {
+    let Some(new_project) = db_models::DBProject::get_id_uncached(
+        old_project.id.into(),
+        &mut *transaction,
+        redis,
+    )
+    .await
+    .wrap_internal_err("fetching edited project")?
+    else {
+        return Ok(());
+    };
+
+    if let Some(entry) = AuditLogEntryBuilder::project(
+        actor_id,
+        old_project.id.into(),
+        AuditLogAction::ProjectEdited,
+        AuditLogTarget::Project {
+            project_id: old_project.id,
+        },
+    )
+    .changes(&old_project, &Project::from(new_project))
+    {
+        entry
+            .insert(&mut *transaction)
+            .await
+            .wrap_internal_err("inserting audit log entry")?;
+    }
+
+    Ok(())
+}
+
 pub async fn edit_project_categories(
     categories: &Vec<String>,
     perms: &ProjectPermissions,
@@ -1821,6 +1935,8 @@ pub async fn projects_edit(
     let mut changed_projects = Vec::new();
 
     for project in projects_data {
+        let old_project = Project::from(project.clone());
+
         if !user.role.is_mod() {
             let team_member = team_members.iter().find(|x| {
                 x.team_id == project.inner.team_id
@@ -1946,6 +2062,9 @@ pub async fn projects_edit(
             }
         }
 
+        log_project_edit(user.id.into(), old_project, &mut transaction, &redis)
+            .await?;
+
         changed_projects.push((
             project.inner.id,
             project.inner.slug,
@@ -2160,6 +2279,7 @@ pub async fn project_icon_edit_internal(
         }
     }
 
+    let old_icon_url = project_item.inner.icon_url.clone();
     delete_old_images(
         project_item.inner.icon_url,
         project_item.inner.raw_icon_url,
@@ -2210,6 +2330,18 @@ pub async fn project_icon_edit_internal(
     .await
     .wrap_internal_err("querying database for `project_icon_edit_internal`")?;
 
+    AuditLogEntryBuilder::project(
+        user.id.into(),
+        project_item.inner.id,
+        AuditLogAction::ProjectIconEdited,
+        AuditLogTarget::Project { project_id },
+    )
+    .before(&serde_json::json!({ "icon_url": old_icon_url }))
+    .after(&serde_json::json!({ "icon_url": upload_result.url }))
+    .insert(&mut transaction)
+    .await
+    .wrap_internal_err("inserting audit log entry")?;
+
     transaction
         .commit()
         .await
@@ -2312,6 +2444,7 @@ pub async fn delete_project_icon_internal(
         }
     }
 
+    let old_icon_url = project_item.inner.icon_url.clone();
     delete_old_images(
         project_item.inner.icon_url,
         project_item.inner.raw_icon_url,
@@ -2340,6 +2473,19 @@ pub async fn delete_project_icon_internal(
         "querying database for `delete_project_icon_internal`",
     )?;
 
+    AuditLogEntryBuilder::project(
+        user.id.into(),
+        project_item.inner.id,
+        AuditLogAction::ProjectIconDeleted,
+        AuditLogTarget::Project {
+            project_id: project_item.inner.id.into(),
+        },
+    )
+    .before(&serde_json::json!({ "icon_url": old_icon_url }))
+    .insert(&mut transaction)
+    .await
+    .wrap_internal_err("inserting audit log entry")?;
+
     transaction
         .commit()
         .await
@@ -2544,6 +2690,15 @@ pub async fn add_gallery_item_internal(
         created: Utc::now(),
         ordering: item.ordering.unwrap_or(0),
     }];
+    let audit_log_entry = AuditLogEntryBuilder::project(
+        user.id.into(),
+        project_item.inner.id,
+        AuditLogAction::GalleryItemAdded,
+        AuditLogTarget::GalleryItem {
+            url: gallery_item[0].image_url.clone(),
+        },
+    )
+    .after(&gallery_item[0]);
     DBGalleryItem::insert_many(
         gallery_item,
         project_item.inner.id,
@@ -2551,6 +2706,10 @@ pub async fn add_gallery_item_internal(
     )
     .await
     .wrap_internal_err("inserting galleries into database")?;
+    audit_log_entry
+        .insert(&mut transaction)
+        .await
+        .wrap_internal_err("inserting audit log entry")?;
 
     transaction
         .commit()
@@ -2705,6 +2864,27 @@ pub async fn edit_gallery_item_internal(
         }
     }
 
+    let old_item = project_item
+        .gallery_items
+        .iter()
+        .find(|x| x.image_url == item.url)
+        .cloned();
+    let new_item = old_item.clone().map(|mut new_item| {
+        if let Some(featured) = item.featured {
+            new_item.featured = featured;
+        }
+        if let Some(name) = &item.name {
+            new_item.name.clone_from(name);
+        }
+        if let Some(description) = &item.description {
+            new_item.description.clone_from(description);
+        }
+        if let Some(ordering) = item.ordering {
+            new_item.ordering = ordering;
+        }
+        new_item
+    });
+
     let mut transaction = pool
         .begin()
         .await
@@ -2790,6 +2970,23 @@ pub async fn edit_gallery_item_internal(
         )?;
     }
 
+    if let (Some(old_item), Some(new_item)) = (&old_item, &new_item)
+        && let Some(entry) = AuditLogEntryBuilder::project(
+            user.id.into(),
+            project_item.inner.id,
+            AuditLogAction::GalleryItemEdited,
+            AuditLogTarget::GalleryItem {
+                url: old_item.image_url.clone(),
+            },
+        )
+        .changes(old_item, new_item)
+    {
+        entry
+            .insert(&mut transaction)
+            .await
+            .wrap_internal_err("inserting audit log entry")?;
+    }
+
     transaction
         .commit()
         .await
@@ -2923,6 +3120,24 @@ pub async fn delete_gallery_item_internal(
         }
     }
 
+    let old_item = project_item
+        .gallery_items
+        .iter()
+        .find(|x| x.image_url == item.image_url)
+        .cloned();
+    let audit_log_entry = AuditLogEntryBuilder::project(
+        user.id.into(),
+        project_item.inner.id,
+        AuditLogAction::GalleryItemDeleted,
+        AuditLogTarget::GalleryItem {
+            url: item.image_url.clone(),
+        },
+    );
+    let audit_log_entry = match &old_item {
+        Some(old_item) => audit_log_entry.before(old_item),
+        None => audit_log_entry,
+    };
+
     delete_old_images(
         Some(item.image_url),
         Some(item.raw_image_url),
@@ -2950,6 +3165,11 @@ pub async fn delete_gallery_item_internal(
         "querying database for `delete_gallery_item_internal`",
     )?;
 
+    audit_log_entry
+        .insert(&mut transaction)
+        .await
+        .wrap_internal_err("inserting audit log entry")?;
+
     transaction
         .commit()
         .await
@@ -3054,6 +3274,24 @@ pub async fn project_delete_internal(
         .await
         .wrap_internal_err("failed to start transaction")?;
 
+    // Logged before the project is detached from its organization, so that
+    // the entry shows up in the organization's log
+    AuditLogEntryBuilder::project(
+        user.id.into(),
+        project.inner.id,
+        AuditLogAction::ProjectDeleted,
+        AuditLogTarget::Project {
+            project_id: project.inner.id.into(),
+        },
+    )
+    .before(&serde_json::json!({
+        "name": project.inner.name,
+        "slug": project.inner.slug,
+    }))
+    .insert(&mut transaction)
+    .await
+    .wrap_internal_err("failed to insert audit log entry")?;
+
     // rejected & withheld projects are transferred to ghost so moderation data is preserved
     if matches!(
         project.inner.status,
@@ -3139,6 +3377,7 @@ pub async fn project_delete_internal(
             accepted: true,
             payouts_split: Decimal::ZERO,
             ordering: 0,
+            role_id: None,
         }
         .insert(&mut transaction)
         .await
@@ -4,6 +4,7 @@ use crate::database::PgPool;
 use crate::database::models::SharedInstanceId;
 use crate::database::models::image_item;
 use crate::database::models::notification_item::NotificationBuilder;
+use crate::database::models::project_webhook_item::DBProjectWebhookDelivery;
 use crate::database::models::thread_item::{
     ThreadBuilder, ThreadMessageBuilder,
 };
@@ -13,10 +14,14 @@ use crate::models::ids::{ProjectId, VersionId};
 use crate::models::images::{Image, ImageContext};
 use crate::models::notifications::NotificationBody;
 use crate::models::pats::Scopes;
+use crate::models::project_webhooks::ProjectWebhookEvent;
 use crate::models::reports::{ItemType, Report};
 use crate::models::threads::{MessageBody, ThreadType};
 use crate::queue::session::AuthQueue;
 use crate::routes::ApiError;
+use crate::sync::realtime::{
+    broadcast_realtime_notifications, broadcast_thread_message,
+};
 use crate::util::error::ApiContext as _;
 use crate::util::error::Context;
 use crate::util::http::HTTP_CLIENT;
@@ -308,7 +313,7 @@ pub async fn report_create(
     .wrap_internal_err("inserting database records for `report_create`")?;
 
     // Notify the reporter that the report has been submitted
-    NotificationBuilder {
+    let notification_id = NotificationBuilder {
         body: NotificationBody::ReportSubmitted {
             report_id: id.into(),
         },
@@ -317,11 +322,36 @@ pub async fn report_create(
     .await
     .wrap_internal_err("inserting database records for `report_create`")?;
 
+    let reported_project = match (report.project_id, report.version_id) {
+        (Some(project_id), _) => Some((project_id, None)),
+        (None, Some(version_id)) => {
+            database::models::DBVersion::get(version_id, &**pool, &redis)
+                .await
+                .wrap_internal_err("fetching reported version")?
+                .map(|version| (version.inner.project_id, Some(version_id)))
+        }
+        (None, None) => None,
+    };
+    if let Some((project_id, version_id)) = reported_project {
+        DBProjectWebhookDelivery::insert_for_event(
+            &ProjectWebhookEvent::ReportCreated {
+                project_id: project_id.into(),
+                version_id: version_id.map(Into::into),
+                report_id: id.into(),
+            },
+            &mut transaction,
+        )
+        .await
+        .wrap_internal_err("queueing project webhook deliveries")?;
+    }
+
     transaction
         .commit()
         .await
         .wrap_internal_err("committing database transaction")?;
 
+    broadcast_realtime_notifications(&redis, vec![notification_id]).await;
+
     Ok(HttpResponse::Ok().json(Report {
         id: id.into(),
         report_type: new_report.report_type.clone(),
@@ -628,6 +658,7 @@ pub async fn report_edit(
             .wrap_internal_err("updating edit body in database")?;
         }
 
+        let mut closure_message = None;
         if let Some(edit_closed) = edit_report.closed {
             if !user.role.is_mod() {
                 return Err(ApiError::Request(eyre::eyre!(
@@ -635,7 +666,7 @@ pub async fn report_edit(
                 )));
             }
 
-            ThreadMessageBuilder {
+            let message_id = ThreadMessageBuilder {
                 author_id: Some(user.id.into()),
                 body: if !edit_closed && report.closed {
                     MessageBody::ThreadReopen
@@ -650,8 +681,7 @@ pub async fn report_edit(
             .wrap_internal_err(
                 "inserting database records for `report_edit`",
             )?;
-
-            NotificationBuilder {
+            let notification_id = NotificationBuilder {
                 body: NotificationBody::ReportStatusUpdated {
                     report_id: id.into(),
                 },
@@ -661,6 +691,7 @@ pub async fn report_edit(
             .wrap_internal_err(
                 "inserting database records for `report_edit`",
             )?;
+            closure_message = Some((message_id, notification_id));
 
             sqlx::query!(
                 "
@@ -698,6 +729,17 @@ pub async fn report_edit(
             .await
             .wrap_internal_err("committing database transaction")?;
 
+        if let Some((message_id, notification_id)) = closure_message {
+            broadcast_thread_message(
+                &redis,
+                report.thread_id.into(),
+                message_id.into(),
+            )
+            .await;
+            broadcast_realtime_notifications(&redis, vec![notification_id])
+                .await;
+        }
+
         Ok(HttpResponse::NoContent().body(""))
     } else {
         Err(ApiError::NotFound(eyre::eyre!("resource not found")))
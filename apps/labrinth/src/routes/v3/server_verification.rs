@@ -0,0 +1,230 @@
+use actix_web::{HttpRequest, post, web};
+use chrono::{DateTime, Utc};
+use eyre::eyre;
+use serde::Serialize;
+use utoipa::ToSchema;
+use xredis::RedisPool;
+
+use crate::auth::get_user_from_headers;
+use crate::database::PgPool;
+use crate::database::models::server_verification_item::DBServerVerification;
+use crate::database::models::{self as db_models, DBProjectId};
+use crate::models::exp::minecraft::ServerVerification;
+use crate::models::pats::Scopes;
+use crate::models::teams::ProjectPermissions;
+use crate::queue::server_verification;
+use crate::queue::session::AuthQueue;
+use crate::routes::ApiError;
+use crate::routes::v3::projects::clear_project_cache_and_queue_search;
+use crate::search::SearchState;
+use crate::util::error::Context;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(server_verification_start)
+        .service(server_verification_check);
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ServerVerificationStatus {
+    /// Token to put in the server's MOTD, or in a DNS TXT record named
+    /// `dns_record`.
+    pub token: String,
+    pub dns_record: String,
+    /// Set once the server has been verified.
+    pub verification: Option<ServerVerification>,
+    pub last_checked: Option<DateTime<Utc>>,
+}
+
+/// Issue a verification token for a Minecraft Java server listing.
+///
+/// Returns the existing token if one has already been issued. The listing
+/// is verified once the token is found in the server's MOTD or DNS TXT
+/// record by [`server_verification_check`], and is re-checked periodically
+/// afterwards, so the token should be left in place.
+#[utoipa::path(
+	context_path = "/project",
+	tag = "projects",
+	responses((status = OK, body = ServerVerificationStatus))
+)]
+#[post("/{id}/server-verification")]
+pub async fn server_verification_start(
+    req: HttpRequest,
+    info: web::Path<(String,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<web::Json<ServerVerificationStatus>, ApiError> {
+    let (project, address) =
+        get_server_project(&req, info, &pool, &redis, &session_queue).await?;
+
+    DBServerVerification::insert(
+        project.inner.id,
+        &server_verification::generate_token(),
+        &**pool,
+    )
+    .await
+    .wrap_internal_err("failed to issue verification token")?;
+
+    let status = verification_status(project.inner.id, &address, &pool)
+        .await?
+        .wrap_internal_err("verification token was not persisted")?;
+    Ok(web::Json(ServerVerificationStatus {
+        verification: project
+            .inner
+            .components
+            .minecraft_java_server
+            .and_then(|server| server.verification),
+        ..status
+    }))
+}
+
+/// Check a Minecraft Java server listing's verification token now.
+///
+/// Verifies the listing if the token is found in the server's MOTD or DNS
+/// TXT record, otherwise returns a bad request error.
+#[utoipa::path(
+	context_path = "/project",
+	tag = "projects",
+	responses((status = OK, body = ServerVerificationStatus))
+)]
+#[post("/{id}/server-verification/check")]
+pub async fn server_verification_check(
+    req: HttpRequest,
+    info: web::Path<(String,)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    session_queue: web::Data<AuthQueue>,
+    search_state: web::Data<SearchState>,
+) -> Result<web::Json<ServerVerificationStatus>, ApiError> {
+    let (project, address) =
+        get_server_project(&req, info, &pool, &redis, &session_queue).await?;
+
+    let status = verification_status(project.inner.id, &address, &pool)
+        .await?
+        .wrap_request_err(
+            "no verification token has been issued for this server",
+        )?;
+
+    let method =
+        server_verification::check_server(&address, &status.token).await;
+    DBServerVerification::record_check(
+        project.inner.id,
+        method.is_some(),
+        &**pool,
+    )
+    .await
+    .wrap_internal_err("failed to record verification check")?;
+    let Some(method) = method else {
+        return Err(ApiError::Request(eyre!(
+            "the verification token was not found in the server's MOTD or in a TXT record at `{}`",
+            status.dns_record
+        )));
+    };
+
+    let verification = server_verification::new_verification(method);
+    let updated = DBServerVerification::set_project_verification(
+        project.inner.id,
+        &address,
+        Some(&verification),
+        &**pool,
+    )
+    .await
+    .wrap_internal_err("failed to verify server")?;
+    if !updated {
+        return Err(ApiError::Conflict(eyre!(
+            "the server's address was changed during verification"
+        )));
+    }
+
+    clear_project_cache_and_queue_search(
+        &redis,
+        &search_state,
+        project.inner.id,
+        project.inner.slug,
+        None,
+    )
+    .await?;
+
+    Ok(web::Json(ServerVerificationStatus {
+        verification: Some(verification),
+        last_checked: Some(Utc::now()),
+        ..status
+    }))
+}
+
+/// Fetches a project with a Minecraft Java server listing which the user
+/// can edit, along with the listing's address.
+async fn get_server_project(
+    req: &HttpRequest,
+    info: web::Path<(String,)>,
+    pool: &PgPool,
+    redis: &RedisPool,
+    session_queue: &AuthQueue,
+) -> Result<(db_models::project_item::ProjectQueryResult, String), ApiError> {
+    let (string,) = info.into_inner();
+
+    let user = get_user_from_headers(
+        req,
+        pool,
+        redis,
+        session_queue,
+        Scopes::PROJECT_WRITE,
+    )
+    .await
+    .wrap_auth_err("authenticating API request")?
+    .1;
+
+    let project = db_models::DBProject::get(&string, pool, redis)
+        .await
+        .wrap_internal_err("failed to fetch project")?
+        .wrap_not_found_err("resource not found")?;
+
+    let (team_member, organization_team_member) =
+        db_models::DBTeamMember::get_for_project_permissions(
+            &project.inner,
+            user.id.into(),
+            pool,
+        )
+        .await
+        .wrap_internal_err("failed to fetch project permissions")?;
+
+    let can_edit_details = ProjectPermissions::get_permissions_by_role(
+        &user.role,
+        &team_member,
+        &organization_team_member,
+    )
+    .is_some_and(|perms| perms.contains(ProjectPermissions::EDIT_DETAILS));
+    if !can_edit_details {
+        return Err(ApiError::Auth(eyre!(
+            "you do not have permission to verify this project's server"
+        )));
+    }
+
+    let address = project
+        .inner
+        .components
+        .minecraft_java_server
+        .as_ref()
+        .map(|server| server.address.clone())
+        .filter(|address| !address.trim().is_empty())
+        .wrap_request_err("project is not a Minecraft Java server listing")?;
+
+    Ok((project, address))
+}
+
+async fn verification_status(
+    project_id: DBProjectId,
+    address: &str,
+    pool: &PgPool,
+) -> Result<Option<ServerVerificationStatus>, ApiError> {
+    let verification = DBServerVerification::get(project_id, pool)
+        .await
+        .wrap_internal_err("failed to fetch verification token")?;
+
+    Ok(verification.map(|verification| ServerVerificationStatus {
+        token: verification.token,
+        dns_record: server_verification::dns_record_name(address),
+        verification: None,
+        last_checked: verification.last_checked,
+    }))
+}
@@ -2,19 +2,27 @@ use crate::auth::checks::{is_visible_organization, is_visible_project};
 use crate::auth::get_user_from_headers;
 use crate::database::DBProject;
 use crate::database::PgPool;
+use crate::database::models::audit_log_item::AuditLogEntryBuilder;
 use crate::database::models::notification_item::NotificationBuilder;
+use crate::database::models::organization_role_item::DBOrganizationRole;
+use crate::database::models::payouts_split_item::DBPayoutsSplit;
 use crate::database::models::team_item::TeamAssociationId;
 use crate::database::models::{DBOrganization, DBTeam, DBTeamMember, DBUser};
-use crate::models::ids::TeamId;
+use crate::models::audit_log::{
+    AuditLogAction, AuditLogTarget, AuditedTeamMember,
+};
+use crate::models::ids::{OrganizationRoleId, TeamId};
 use crate::models::notifications::NotificationBody;
 use crate::models::pats::Scopes;
 use crate::models::teams::{OrganizationPermissions, ProjectPermissions};
 use crate::queue::session::AuthQueue;
 use crate::routes::ApiError;
+use crate::sync::realtime::broadcast_realtime_notifications;
 use crate::util::error::ApiContext as _;
 use crate::util::error::Context;
 use actix_web::{HttpRequest, HttpResponse, delete, get, patch, post, web};
 use ariadne::ids::UserId;
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use xredis::RedisPool;
@@ -453,11 +461,30 @@ pub async fn join_team(
             None,
             None,
             None,
+            None,
             &mut transaction,
         )
         .await
         .wrap_internal_err("updating team member in database")?;
 
+        if let Some(association) =
+            DBTeam::get_association(team_id, &mut transaction)
+                .await
+                .wrap_internal_err("fetching team from database")?
+        {
+            AuditLogEntryBuilder::team(
+                current_user.id.into(),
+                association,
+                AuditLogAction::TeamMemberJoined,
+                AuditLogTarget::TeamMember {
+                    user_id: current_user.id,
+                },
+            )
+            .insert(&mut transaction)
+            .await
+            .wrap_internal_err("inserting audit log entry")?;
+        }
+
         transaction
             .commit()
             .await
@@ -556,24 +583,14 @@ pub async fn add_team_member(
     match team_association {
         // If team is associated with a project, check if they have permissions to invite users to that project
         TeamAssociationId::Project(pid) => {
-            let organization =
-                DBOrganization::get_associated_organization_project_id(
-                    pid, &**pool,
+            let organization_team_member =
+                DBTeamMember::get_from_user_id_project_organization(
+                    pid,
+                    current_user.id.into(),
+                    &**pool,
                 )
                 .await
-                .wrap_internal_err("fetching organization from database")?;
-            let organization_team_member =
-                if let Some(organization) = &organization {
-                    DBTeamMember::get_from_user_id(
-                        organization.team_id,
-                        current_user.id.into(),
-                        &**pool,
-                    )
-                    .await
-                    .wrap_internal_err("fetching team member from database")?
-                } else {
-                    None
-                };
+                .wrap_internal_err("fetching team member from database")?;
             let permissions = ProjectPermissions::get_permissions_by_role(
                 &current_user.role,
                 &member,
@@ -711,7 +728,7 @@ pub async fn add_team_member(
         crate::database::models::ids::generate_team_member_id(&mut transaction)
             .await
             .wrap_internal_err("generating team member ID")?;
-    DBTeamMember {
+    let member = DBTeamMember {
         id: new_id,
         team_id,
         user_id: new_member.user_id.into(),
@@ -722,51 +739,63 @@ pub async fn add_team_member(
         accepted: force_accepted,
         payouts_split: new_member.payouts_split,
         ordering: new_member.ordering,
-    }
+        role_id: None,
+    };
+    member.insert(&mut transaction).await.wrap_internal_err(
+        "inserting database records for `add_team_member`",
+    )?;
+
+    AuditLogEntryBuilder::team(
+        current_user.id.into(),
+        team_association,
+        AuditLogAction::TeamMemberInvited,
+        AuditLogTarget::TeamMember {
+            user_id: new_member.user_id,
+        },
+    )
+    .after(&AuditedTeamMember::from(&member))
     .insert(&mut transaction)
     .await
-    .wrap_internal_err("inserting database records for `add_team_member`")?;
+    .wrap_internal_err("inserting audit log entry")?;
 
     // If the user has an opportunity to accept the invite, send a notification
-    if !force_accepted {
-        match team_association {
-            TeamAssociationId::Project(pid) => {
-                NotificationBuilder {
-                    body: NotificationBody::TeamInvite {
-                        project_id: pid.into(),
-                        team_id: team_id.into(),
-                        invited_by: current_user.id,
-                        role: new_member.role.clone(),
-                    },
-                }
-                .insert(new_member.user_id.into(), &mut transaction, &redis)
-                .await
-                .wrap_internal_err(
-                    "inserting database records for `add_team_member`",
-                )?;
-            }
+    let notification_id = if force_accepted {
+        None
+    } else {
+        let body = match team_association {
+            TeamAssociationId::Project(pid) => NotificationBody::TeamInvite {
+                project_id: pid.into(),
+                team_id: team_id.into(),
+                invited_by: current_user.id,
+                role: new_member.role.clone(),
+            },
             TeamAssociationId::Organization(oid) => {
-                NotificationBuilder {
-                    body: NotificationBody::OrganizationInvite {
-                        organization_id: oid.into(),
-                        team_id: team_id.into(),
-                        invited_by: current_user.id,
-                        role: new_member.role.clone(),
-                    },
+                NotificationBody::OrganizationInvite {
+                    organization_id: oid.into(),
+                    team_id: team_id.into(),
+                    invited_by: current_user.id,
+                    role: new_member.role.clone(),
                 }
-                .insert(new_member.user_id.into(), &mut transaction, &redis)
-                .await
-                .wrap_internal_err(
-                    "inserting database records for `add_team_member`",
-                )?;
             }
-        }
-    }
+        };
+        let notification_id = NotificationBuilder { body }
+            .insert(new_member.user_id.into(), &mut transaction, &redis)
+            .await
+            .wrap_internal_err(
+                "inserting database records for `add_team_member`",
+            )?;
+        Some(notification_id)
+    };
 
     transaction
         .commit()
         .await
         .wrap_internal_err("committing database transaction")?;
+    broadcast_realtime_notifications(
+        &redis,
+        notification_id.into_iter().collect(),
+    )
+    .await;
     DBTeamMember::clear_cache(team_id, &redis)
         .await
         .wrap_internal_err("clearing cached data from Redis")?;
@@ -783,7 +812,20 @@ pub struct EditTeamMember {
     pub organization_permissions: Option<OrganizationPermissions>,
     pub role: Option<String>,
     pub payouts_split: Option<Decimal>,
+    /// When the new `payouts_split` takes effect, which must be in the future.
+    /// Until then, revenue keeps being split by the member's current split.
+    /// Defaults to taking effect immediately.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payouts_split_effective_from: Option<DateTime<Utc>>,
     pub ordering: Option<i64>,
+    /// The organization role to assign to the member, or `null` to unassign
+    /// it. While assigned, the role's permissions replace the member's own.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "::serde_with::rust::double_option"
+    )]
+    pub role_id: Option<Option<OrganizationRoleId>>,
 }
 
 #[utoipa::path(tag = "teams", responses((status = NO_CONTENT)))]
@@ -851,7 +893,8 @@ pub async fn edit_team_member(
 
     if edit_member_db.is_owner
         && (edit_member.permissions.is_some()
-            || edit_member.organization_permissions.is_some())
+            || edit_member.organization_permissions.is_some()
+            || edit_member.role_id.is_some())
     {
         return Err(ApiError::Request(eyre::eyre!(
             "The owner's permission's in a team cannot be edited",
@@ -867,17 +910,13 @@ pub async fn edit_team_member(
                 .await
                 .wrap_internal_err("fetching organization from database")?;
             let organization_team_member =
-                if let Some(organization) = &organization {
-                    DBTeamMember::get_from_user_id(
-                        organization.team_id,
-                        current_user.id.into(),
-                        &**pool,
-                    )
-                    .await
-                    .wrap_internal_err("fetching team member from database")?
-                } else {
-                    None
-                };
+                DBTeamMember::get_from_user_id_project_organization(
+                    project_id,
+                    current_user.id.into(),
+                    &**pool,
+                )
+                .await
+                .wrap_internal_err("fetching team member from database")?;
 
             let edited_member_organization_team_member =
                 if let Some(organization) = &organization {
@@ -929,8 +968,14 @@ pub async fn edit_team_member(
                     "The organization permissions of a project team member cannot be edited",
                 )));
             }
+
+            if edit_member.role_id.is_some() {
+                return Err(ApiError::Request(eyre::eyre!(
+                    "Only organization team members can be assigned a role",
+                )));
+            }
         }
-        TeamAssociationId::Organization(_) => {
+        TeamAssociationId::Organization(organization_id) => {
             let organization_permissions =
                 OrganizationPermissions::get_permissions_by_role(
                     &current_user.role,
@@ -963,6 +1008,34 @@ pub async fn edit_team_member(
                     "You do not have permission to give this user default project permissions.",
                 )));
             }
+
+            if let Some(Some(role_id)) = edit_member.role_id {
+                let role = DBOrganizationRole::get(role_id.into(), &**pool)
+                    .await
+                    .wrap_internal_err("fetching organization role")?
+                    .filter(|role| role.organization_id == organization_id)
+                    .wrap_request_err(
+                        "the specified role does not exist in this organization",
+                    )?;
+
+                if !organization_permissions
+                    .contains(role.organization_permissions)
+                {
+                    return Err(ApiError::Request(eyre::eyre!(
+                        "The role has organization permissions that you don't have",
+                    )));
+                }
+
+                if !role.permissions.is_empty()
+                    && !organization_permissions.contains(
+                        OrganizationPermissions::EDIT_MEMBER_DEFAULT_PERMISSIONS,
+                    )
+                {
+                    return Err(ApiError::Auth(eyre::eyre!(
+                        "You do not have permission to give this user default project permissions.",
+                    )));
+                }
+            }
         }
     }
 
@@ -975,6 +1048,40 @@ pub async fn edit_team_member(
         )));
     }
 
+    // Changing a split retroactively would reallocate revenue which was
+    // already earned, so it may only be scheduled for the future
+    let scheduled_payouts_split = match (
+        edit_member.payouts_split,
+        edit_member.payouts_split_effective_from,
+    ) {
+        (_, None) => None,
+        (None, Some(_)) => {
+            return Err(ApiError::Request(eyre::eyre!(
+                "A payouts split must be given to schedule it",
+            )));
+        }
+        (Some(_), Some(effective_from)) if effective_from <= Utc::now() => {
+            return Err(ApiError::Request(eyre::eyre!(
+                "Payouts splits can only be scheduled for the future",
+            )));
+        }
+        (Some(payouts_split), Some(effective_from)) => {
+            Some((payouts_split, effective_from))
+        }
+    };
+
+    if let Some((payouts_split, effective_from)) = scheduled_payouts_split {
+        DBPayoutsSplit::schedule(
+            id,
+            user_id,
+            payouts_split,
+            effective_from,
+            &mut transaction,
+        )
+        .await
+        .wrap_internal_err("scheduling payouts split")?;
+    }
+
     DBTeamMember::edit_team_member(
         id,
         user_id,
@@ -982,14 +1089,59 @@ pub async fn edit_team_member(
         edit_member.organization_permissions,
         edit_member.role.clone(),
         None,
-        edit_member.payouts_split,
+        edit_member
+            .payouts_split
+            .filter(|_| scheduled_payouts_split.is_none()),
         edit_member.ordering,
         None,
+        edit_member.role_id.map(|x| x.map(Into::into)),
         &mut transaction,
     )
     .await
     .wrap_internal_err("updating team member in database")?;
 
+    let mut edited_member_db = edit_member_db.clone();
+    if let Some(permissions) = edit_member.permissions {
+        edited_member_db.permissions = permissions;
+    }
+    if let Some(organization_permissions) = edit_member.organization_permissions
+    {
+        edited_member_db.organization_permissions =
+            Some(organization_permissions);
+    }
+    if let Some(role) = &edit_member.role {
+        edited_member_db.role.clone_from(role);
+    }
+    if let Some(payouts_split) = edit_member.payouts_split
+        && scheduled_payouts_split.is_none()
+    {
+        edited_member_db.payouts_split = payouts_split;
+    }
+    if let Some(ordering) = edit_member.ordering {
+        edited_member_db.ordering = ordering;
+    }
+    if let Some(role_id) = edit_member.role_id {
+        edited_member_db.role_id = role_id.map(Into::into);
+    }
+
+    if let Some(entry) = AuditLogEntryBuilder::team(
+        current_user.id.into(),
+        team_association,
+        AuditLogAction::TeamMemberEdited,
+        AuditLogTarget::TeamMember {
+            user_id: user_id.into(),
+        },
+    )
+    .changes(
+        &AuditedTeamMember::from(&edit_member_db),
+        &AuditedTeamMember::from(&edited_member_db),
+    ) {
+        entry
+            .insert(&mut transaction)
+            .await
+            .wrap_internal_err("inserting audit log entry")?;
+    }
+
     transaction
         .commit()
         .await
@@ -1101,7 +1253,7 @@ pub async fn transfer_ownership(
         .wrap_internal_err("starting database transaction")?;
 
     // The following are the only places new_is_owner is modified.
-    if let Some(former_owner) =
+    let former_owner_id = if let Some(former_owner) =
         DBTeamMember::get_from_team_full(id.into(), &**pool, &redis)
             .await
             .wrap_internal_err("fetching team member from database")?
@@ -1118,11 +1270,16 @@ pub async fn transfer_ownership(
             None,
             None,
             Some(false),
+            None,
             &mut transaction,
         )
         .await
         .wrap_internal_err("updating team member in database")?;
-    }
+
+        Some(UserId::from(former_owner.user_id))
+    } else {
+        None
+    };
 
     DBTeamMember::edit_team_member(
         id.into(),
@@ -1141,6 +1298,7 @@ pub async fn transfer_ownership(
         None,
         None,
         Some(true),
+        Some(None),
         &mut transaction,
     )
     .await
@@ -1188,9 +1346,26 @@ pub async fn transfer_ownership(
             vec![]
         };
 
+    if let Some(association) = team_association_id {
+        AuditLogEntryBuilder::team(
+            current_user.id.into(),
+            association,
+            AuditLogAction::TeamOwnershipTransferred,
+            AuditLogTarget::TeamMember {
+                user_id: new_owner.user_id,
+            },
+        )
+        .before(&serde_json::json!({ "owner": former_owner_id }))
+        .after(&serde_json::json!({ "owner": new_owner.user_id }))
+        .insert(&mut transaction)
+        .await
+        .wrap_internal_err("inserting audit log entry")?;
+    }
+
     // If this team is associated with a project, notify the new owner
+    let mut notification_ids = Vec::new();
     if let Some(TeamAssociationId::Project(pid)) = team_association_id {
-        NotificationBuilder {
+        let notification_id = NotificationBuilder {
             body: NotificationBody::ProjectTransferred {
                 project_id: pid.into(),
                 new_owner_user_id: Some(new_owner.user_id),
@@ -1202,12 +1377,14 @@ pub async fn transfer_ownership(
         .wrap_internal_err(
             "inserting database records for `transfer_ownership`",
         )?;
+        notification_ids.push(notification_id);
     }
 
     transaction
         .commit()
         .await
         .wrap_internal_err("committing database transaction")?;
+    broadcast_realtime_notifications(&redis, notification_ids).await;
 
     DBTeamMember::clear_cache(id.into(), &redis)
         .await
@@ -1287,25 +1464,14 @@ pub async fn remove_team_member(
         // Organization attached to a project this team is attached to
         match team_association {
             TeamAssociationId::Project(pid) => {
-                let organization =
-                    DBOrganization::get_associated_organization_project_id(
-                        pid, &**pool,
-                    )
-                    .await
-                    .wrap_internal_err("fetching organization from database")?;
-                let organization_team_member = if let Some(organization) =
-                    &organization
-                {
-                    DBTeamMember::get_from_user_id(
-                        organization.team_id,
+                let organization_team_member =
+                    DBTeamMember::get_from_user_id_project_organization(
+                        pid,
                         current_user.id.into(),
                         &**pool,
                     )
                     .await
-                    .wrap_internal_err("fetching team member from database")?
-                } else {
-                    None
-                };
+                    .wrap_internal_err("fetching team member from database")?;
                 let permissions = ProjectPermissions::get_permissions_by_role(
                     &current_user.role,
                     &member,
@@ -1393,6 +1559,19 @@ pub async fn remove_team_member(
             }
         }
 
+        AuditLogEntryBuilder::team(
+            current_user.id.into(),
+            team_association,
+            AuditLogAction::TeamMemberRemoved,
+            AuditLogTarget::TeamMember {
+                user_id: user_id.into(),
+            },
+        )
+        .before(&AuditedTeamMember::from(&delete_member))
+        .insert(&mut transaction)
+        .await
+        .wrap_internal_err("inserting audit log entry")?;
+
         transaction
             .commit()
             .await
@@ -3,6 +3,7 @@ use crate::database;
 use crate::database::PgPool;
 use crate::database::models::image_item;
 use crate::database::models::notification_item::NotificationBuilder;
+use crate::database::models::project_webhook_item::DBProjectWebhookDelivery;
 use crate::database::models::thread_item::ThreadMessageBuilder;
 use crate::env::ENV;
 use crate::file_hosting::{FileHost, FileHostPublicity};
@@ -10,11 +11,15 @@ use crate::models::ids::{ThreadId, ThreadMessageId};
 use crate::models::images::{Image, ImageContext};
 use crate::models::notifications::NotificationBody;
 use crate::models::pats::Scopes;
+use crate::models::project_webhooks::ProjectWebhookEvent;
 use crate::models::projects::ProjectStatus;
 use crate::models::threads::{MessageBody, Thread, ThreadType};
 use crate::models::users::User;
 use crate::queue::session::AuthQueue;
 use crate::routes::ApiError;
+use crate::sync::realtime::{
+    broadcast_realtime_notifications, broadcast_thread_message,
+};
 use crate::util::error::ApiContext as _;
 use crate::util::error::Context as _;
 use actix_web::{HttpRequest, HttpResponse, delete, get, post, web};
@@ -531,6 +536,22 @@ pub async fn thread_send_message_internal(
             "inserting database records for `thread_send_message_internal`",
         )?;
 
+        if let Some(project_id) = thread.project_id
+            && !is_private
+        {
+            DBProjectWebhookDelivery::insert_for_event(
+                &ProjectWebhookEvent::ThreadMessageCreated {
+                    project_id: project_id.into(),
+                    thread_id: thread.id.into(),
+                    message_id: id.into(),
+                },
+                &mut transaction,
+            )
+            .await
+            .wrap_internal_err("queueing project webhook deliveries")?;
+        }
+
+        let mut notification_ids = Vec::new();
         if let Some(project_id) = thread.project_id {
             let project =
                 database::models::DBProject::get_id(project_id, pool, redis)
@@ -551,7 +572,7 @@ pub async fn thread_send_message_internal(
                     .await
                     .wrap_internal_err("fetching team members from database")?;
 
-                NotificationBuilder {
+                notification_ids.extend(NotificationBuilder {
                     body: NotificationBody::ModeratorMessage {
                         thread_id: thread.id.into(),
                         message_id: id.into(),
@@ -565,9 +586,9 @@ pub async fn thread_send_message_internal(
                     redis,
                 )
                 .await
-                .wrap_internal_err("inserting database records for `thread_send_message_internal`")?;
+                .wrap_internal_err("inserting database records for `thread_send_message_internal`")?);
 
-                NotificationBuilder {
+                notification_ids.extend(NotificationBuilder {
                     body: NotificationBody::ModerationMessageReceived {
                         project_id: project.inner.id.into(),
                     },
@@ -578,7 +599,7 @@ pub async fn thread_send_message_internal(
                     redis,
                 )
                 .await
-                .wrap_internal_err("inserting database records for `thread_send_message_internal`")?;
+                .wrap_internal_err("inserting database records for `thread_send_message_internal`")?);
             }
         } else if let Some(report_id) = thread.report_id {
             let report =
@@ -594,7 +615,7 @@ pub async fn thread_send_message_internal(
                 }
 
                 if user.id != report.reporter.into() && !is_private {
-                    NotificationBuilder {
+                    let notification_id = NotificationBuilder {
                         body: NotificationBody::ModeratorMessage {
                             thread_id: thread.id.into(),
                             message_id: id.into(),
@@ -605,6 +626,7 @@ pub async fn thread_send_message_internal(
                     .insert(report.reporter, &mut transaction, redis)
                     .await
                     .wrap_internal_err("inserting database records for `thread_send_message_internal`")?;
+                    notification_ids.push(notification_id);
                 }
             }
         }
@@ -664,6 +686,9 @@ pub async fn thread_send_message_internal(
             .await
             .wrap_internal_err("committing database transaction")?;
 
+        broadcast_thread_message(redis, thread.id.into(), id.into()).await;
+        broadcast_realtime_notifications(redis, notification_ids).await;
+
         Ok(())
     } else {
         Err(ApiError::NotFound(eyre::eyre!("resource not found")))
@@ -6,10 +6,13 @@ use crate::database::models::loader_fields::{
     LoaderField, LoaderFieldEnumValue, VersionField,
 };
 use crate::database::models::notification_item::NotificationBuilder;
+use crate::database::models::pgp_key_item::DBPgpKey;
+use crate::database::models::project_webhook_item::DBProjectWebhookDelivery;
 use crate::database::models::version_item::{
-    DependencyBuilder, VersionBuilder, VersionFileBuilder,
+    DBFile, DependencyBuilder, FileQueryResult, VersionBuilder,
+    VersionFileBuilder,
 };
-use crate::database::models::{self, DBOrganization, image_item};
+use crate::database::models::{self, image_item};
 use crate::env::ENV;
 use crate::file_hosting::{FileHost, FileHostPublicity};
 use crate::models::exp;
@@ -18,6 +21,7 @@ use crate::models::images::{Image, ImageContext};
 use crate::models::notifications::NotificationBody;
 use crate::models::pack::PackFileHash;
 use crate::models::pats::Scopes;
+use crate::models::project_webhooks::ProjectWebhookEvent;
 use crate::models::projects::{
     Dependency, FileType, Loader, Version, VersionFile, VersionStatus,
     VersionType,
@@ -26,13 +30,16 @@ use crate::models::projects::{DependencyType, skip_nulls};
 use crate::models::teams::ProjectPermissions;
 use crate::queue::session::AuthQueue;
 use crate::search::SearchState;
+use crate::sync::realtime::broadcast_realtime_notifications;
 use crate::util::http::HttpClient;
+use crate::util::openpgp;
 use crate::util::routes::read_from_field;
 use crate::util::validate::validation_errors_to_string;
-use crate::validate::{ValidationResult, validate_file};
+use crate::validate::{ValidationResult, validate_file, verify_file_signature};
 use actix_multipart::{Field, Multipart};
 use actix_web::web::Data;
 use actix_web::{HttpRequest, HttpResponse, post, web};
+use bytes::Bytes;
 use chrono::Utc;
 use futures::stream::StreamExt;
 use hex::ToHex;
@@ -184,8 +191,10 @@ pub async fn version_create(
         if let Err(e) = rollback_result {
             return Err(e.into());
         }
-    } else if let Ok((_, project_id, version_id)) = &result {
+    } else if let Ok((_, project_id, version_id, notification_ids)) = &result {
         transaction.commit().await?;
+        broadcast_realtime_notifications(&redis, notification_ids.clone())
+            .await;
         models::DBProject::clear_cache(*project_id, None, Some(true), &redis)
             .await?;
         search_state
@@ -197,7 +206,7 @@ pub async fn version_create(
             .await;
     }
 
-    result.map(|(response, _, _)| response)
+    result.map(|(response, _, _, _)| response)
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -211,11 +220,19 @@ async fn version_create_inner(
     pool: &PgPool,
     session_queue: &AuthQueue,
     http: &reqwest::Client,
-) -> Result<(HttpResponse, models::DBProjectId, models::DBVersionId), CreateError>
-{
+) -> Result<
+    (
+        HttpResponse,
+        models::DBProjectId,
+        models::DBVersionId,
+        Vec<models::ids::DBNotificationId>,
+    ),
+    CreateError,
+> {
     let mut initial_version_data = None;
     let mut version_builder = None;
     let mut selected_loaders = None;
+    let mut file_data = HashMap::new();
 
     let user = get_user_from_headers(
         &req,
@@ -236,9 +253,12 @@ async fn version_create_inner(
         }
 
         let result = async {
-            let content_disposition = field.content_disposition().unwrap().clone();
+            let content_disposition =
+                field.content_disposition().unwrap().clone();
             let name = content_disposition.get_name().ok_or_else(|| {
-                CreateError::MissingValueError("Missing content name".to_string())
+                CreateError::MissingValueError(
+                    "Missing content name".to_string(),
+                )
             })?;
 
             if name == "data" {
@@ -247,9 +267,11 @@ async fn version_create_inner(
                     data.extend_from_slice(&chunk?);
                 }
 
-                let version_create_data: InitialVersionData = serde_json::from_slice(&data)?;
+                let version_create_data: InitialVersionData =
+                    serde_json::from_slice(&data)?;
                 initial_version_data = Some(version_create_data);
-                let version_create_data = initial_version_data.as_ref().unwrap();
+                let version_create_data =
+                    initial_version_data.as_ref().unwrap();
                 if version_create_data.project_id.is_none() {
                     return Err(CreateError::MissingValueError(
                         "Missing project id".to_string(),
@@ -257,7 +279,9 @@ async fn version_create_inner(
                 }
 
                 version_create_data.validate().map_err(|err| {
-                    CreateError::ValidationError(validation_errors_to_string(err, None))
+                    CreateError::ValidationError(validation_errors_to_string(
+                        err, None,
+                    ))
                 })?;
 
                 if !version_create_data.status.can_be_requested() {
@@ -266,12 +290,17 @@ async fn version_create_inner(
                     ));
                 }
 
-                let project_id: models::DBProjectId = version_create_data.project_id.unwrap().into();
+                let project_id: models::DBProjectId =
+                    version_create_data.project_id.unwrap().into();
 
                 // Ensure that the project this version is being added to exists
-                if models::DBProject::get_id(project_id, &mut *transaction, redis)
-                    .await?
-                    .is_none()
+                if models::DBProject::get_id(
+                    project_id,
+                    &mut *transaction,
+                    redis,
+                )
+                .await?
+                .is_none()
                 {
                     return Err(CreateError::InvalidInput(
                         "An invalid project id was supplied".to_string(),
@@ -280,31 +309,23 @@ async fn version_create_inner(
 
                 // Check that the user creating this version is a team member
                 // of the project the version is being added to.
-                let team_member = models::DBTeamMember::get_from_user_id_project(
-                    project_id,
-                    user.id.into(),
-                    false,
-                    &mut *transaction,
-                )
-                .await?;
+                let team_member =
+                    models::DBTeamMember::get_from_user_id_project(
+                        project_id,
+                        user.id.into(),
+                        false,
+                        &mut *transaction,
+                    )
+                    .await?;
 
                 // Get organization attached, if exists, and the member project permissions
-                let organization = models::DBOrganization::get_associated_organization_project_id(
-                    project_id,
-                    &mut *transaction,
-                )
-                .await?;
-
-                let organization_team_member = if let Some(organization) = &organization {
-                    models::DBTeamMember::get_from_user_id(
-                        organization.team_id,
+                let organization_team_member =
+                    models::DBTeamMember::get_from_user_id_project_organization(
+                        project_id,
                         user.id.into(),
                         &mut *transaction,
                     )
-                    .await?
-                } else {
-                    None
-                };
+                    .await?;
 
                 let permissions = ProjectPermissions::get_permissions_by_role(
                     &user.role,
@@ -315,14 +336,19 @@ async fn version_create_inner(
 
                 if !permissions.contains(ProjectPermissions::UPLOAD_VERSION) {
                     return Err(CreateError::CustomAuthenticationError(
-                        "You don't have permission to upload this version!".to_string(),
+                        "You don't have permission to upload this version!"
+                            .to_string(),
                     ));
                 }
 
-                let version_id: VersionId = models::generate_version_id(transaction).await?.into();
+                let version_id: VersionId =
+                    models::generate_version_id(transaction).await?.into();
 
-                let all_loaders =
-                    models::loader_fields::Loader::list(&mut *transaction, redis).await?;
+                let all_loaders = models::loader_fields::Loader::list(
+                    &mut *transaction,
+                    redis,
+                )
+                .await?;
                 let loaders = version_create_data
                     .loaders
                     .iter()
@@ -331,20 +357,28 @@ async fn version_create_inner(
                             .iter()
                             .find(|y| y.loader == x.0)
                             .cloned()
-                            .ok_or_else(|| CreateError::InvalidLoader(x.0.clone()))
+                            .ok_or_else(|| {
+                                CreateError::InvalidLoader(x.0.clone())
+                            })
                     })
                     .collect::<Result<Vec<_>, _>>()?;
                 selected_loaders = Some(loaders.clone());
-                let loader_ids: Vec<models::LoaderId> = loaders.iter().map(|y| y.id).collect_vec();
+                let loader_ids: Vec<models::LoaderId> =
+                    loaders.iter().map(|y| y.id).collect_vec();
 
-                let loader_fields =
-                    LoaderField::get_fields(&loader_ids, &mut *transaction, redis).await?;
-                let mut loader_field_enum_values = LoaderFieldEnumValue::list_many_loader_fields(
-                    &loader_fields,
+                let loader_fields = LoaderField::get_fields(
+                    &loader_ids,
                     &mut *transaction,
                     redis,
                 )
                 .await?;
+                let mut loader_field_enum_values =
+                    LoaderFieldEnumValue::list_many_loader_fields(
+                        &loader_fields,
+                        &mut *transaction,
+                        redis,
+                    )
+                    .await?;
                 let version_fields = try_create_version_fields(
                     version_id,
                     &version_create_data.fields,
@@ -369,12 +403,17 @@ async fn version_create_inner(
                     author_id: user.id.into(),
                     name: version_create_data.version_title.clone(),
                     version_number: version_create_data.version_number.clone(),
-                    changelog: version_create_data.version_body.clone().unwrap_or_default(),
+                    changelog: version_create_data
+                        .version_body
+                        .clone()
+                        .unwrap_or_default(),
                     files: Vec::new(),
                     dependencies,
                     loaders: loader_ids,
                     version_fields,
-                    version_type: version_create_data.release_channel.to_string(),
+                    version_type: version_create_data
+                        .release_channel
+                        .to_string(),
                     featured: version_create_data.featured,
                     status: version_create_data.status,
                     requested_status: None,
@@ -386,21 +425,29 @@ async fn version_create_inner(
             }
 
             let version = version_builder.as_mut().ok_or_else(|| {
-                CreateError::InvalidInput(String::from("`data` field must come before file fields"))
+                CreateError::InvalidInput(String::from(
+                    "`data` field must come before file fields",
+                ))
             })?;
             let loaders = selected_loaders.as_ref().ok_or_else(|| {
-                CreateError::InvalidInput(String::from("`data` field must come before file fields"))
+                CreateError::InvalidInput(String::from(
+                    "`data` field must come before file fields",
+                ))
             })?;
             let loaders = loaders
                 .iter()
                 .map(|x| Loader(x.loader.clone()))
                 .collect::<Vec<_>>();
 
-            let version_data = initial_version_data
-                .clone()
-                .ok_or_else(|| CreateError::InvalidInput("`data` field is required".to_string()))?;
+            let version_data =
+                initial_version_data.clone().ok_or_else(|| {
+                    CreateError::InvalidInput(
+                        "`data` field is required".to_string(),
+                    )
+                })?;
 
-            let existing_file_names = version.files.iter().map(|x| x.filename.clone()).collect();
+            let existing_file_names =
+                version.files.iter().map(|x| x.filename.clone()).collect();
 
             upload_file(
                 &mut field,
@@ -418,6 +465,7 @@ async fn version_create_inner(
                 version_data.primary_file.as_deref() == Some(name),
                 version_data.file_types.get(name).copied().flatten(),
                 existing_file_names,
+                Some(&mut file_data),
                 transaction,
                 redis,
             )
@@ -439,7 +487,7 @@ async fn version_create_inner(
     let version_data = initial_version_data.ok_or_else(|| {
         CreateError::InvalidInput("`data` field is required".to_string())
     })?;
-    let builder = version_builder.ok_or_else(|| {
+    let mut builder = version_builder.ok_or_else(|| {
         CreateError::InvalidInput("`data` field is required".to_string())
     })?;
 
@@ -449,6 +497,16 @@ async fn version_create_inner(
         ));
     }
 
+    verify_file_signatures(
+        builder.project_id,
+        &mut builder.files,
+        &file_data,
+        &[],
+        file_host,
+        transaction,
+    )
+    .await?;
+
     use futures::stream::TryStreamExt;
 
     let users = sqlx::query!(
@@ -467,7 +525,7 @@ async fn version_create_inner(
     let project_id: ProjectId = builder.project_id.into();
     let version_id: VersionId = builder.version_id.into();
 
-    NotificationBuilder {
+    let notification_ids = NotificationBuilder {
         body: NotificationBody::ProjectUpdate {
             project_id,
             version_id,
@@ -476,6 +534,15 @@ async fn version_create_inner(
     .insert_many(users, &mut *transaction, redis)
     .await?;
 
+    DBProjectWebhookDelivery::insert_for_event(
+        &ProjectWebhookEvent::VersionPublished {
+            project_id,
+            version_id,
+        },
+        &mut *transaction,
+    )
+    .await?;
+
     let loader_structs = selected_loaders.unwrap_or_default();
     let (all_project_types, all_games): (Vec<String>, Vec<String>) =
         loader_structs.iter().fold((vec![], vec![]), |mut acc, x| {
@@ -524,6 +591,7 @@ async fn version_create_inner(
                 primary: file.primary,
                 size: file.size,
                 file_type: file.file_type,
+                signature: file.signature.clone(),
             })
             .collect::<Vec<_>>(),
         dependencies: version_data.dependencies,
@@ -574,6 +642,7 @@ async fn version_create_inner(
         HttpResponse::Ok().json(response),
         project_id,
         models::DBVersionId::from(version_id),
+        notification_ids,
     ))
 }
 
@@ -698,6 +767,7 @@ async fn upload_file_to_version_inner(
 ) -> Result<(HttpResponse, models::DBProjectId), CreateError> {
     let mut initial_file_data: Option<InitialFileData> = None;
     let mut file_builders: Vec<VersionFileBuilder> = Vec::new();
+    let mut uploaded_file_data = HashMap::new();
 
     let user = get_user_from_headers(
         &req,
@@ -753,24 +823,13 @@ async fn upload_file_to_version_inner(
         )
         .await?;
 
-        let organization =
-            DBOrganization::get_associated_organization_project_id(
+        let organization_team_member =
+            models::DBTeamMember::get_from_user_id_project_organization(
                 version.inner.project_id,
-                &**client,
-            )
-            .await?;
-
-        let organization_team_member = if let Some(organization) = &organization
-        {
-            models::DBTeamMember::get_from_user_id(
-                organization.team_id,
                 user.id.into(),
                 &mut *transaction,
             )
-            .await?
-        } else {
-            None
-        };
+            .await?;
 
         let permissions = ProjectPermissions::get_permissions_by_role(
             &user.role,
@@ -855,6 +914,7 @@ async fn upload_file_to_version_inner(
                 false,
                 file_data.file_types.get(name).copied().flatten(),
                 version.files.iter().map(|x| x.filename.clone()).collect(),
+                Some(&mut uploaded_file_data),
                 transaction,
                 &redis,
             )
@@ -880,6 +940,16 @@ async fn upload_file_to_version_inner(
     } else {
         let project_id = version.inner.project_id;
 
+        verify_file_signatures(
+            project_id,
+            &mut file_builders,
+            &uploaded_file_data,
+            &version.files,
+            file_host,
+            transaction,
+        )
+        .await?;
+
         for file in file_builders {
             file.insert(
                 version_id,
@@ -918,6 +988,7 @@ pub async fn upload_file(
     force_primary: bool,
     file_type: Option<FileType>,
     other_file_names: Vec<String>,
+    file_data: Option<&mut HashMap<String, Bytes>>,
     transaction: &mut PgTransaction<'_>,
     redis: &RedisPool,
 ) -> Result<(), CreateError> {
@@ -1063,6 +1134,10 @@ pub async fn upload_file(
     let file_path =
         format!("data/{project_id}/versions/{version_id}/{file_name}");
 
+    if let Some(file_data) = file_data {
+        file_data.insert(file_name.to_string(), data.clone());
+    }
+
     let upload_data = file_host
         .upload_file(content_type, &file_path, FileHostPublicity::Public, data)
         .await?;
@@ -1112,11 +1187,98 @@ pub async fn upload_file(
         primary,
         size: upload_data.content_length,
         file_type,
+        signature: None,
     });
 
     Ok(())
 }
 
+/// Verifies the detached signatures among the uploaded `files` against the
+/// files they sign, recording the results on the signed files.
+///
+/// A signature named after a file, i.e. `mod.jar.asc`, signs that file, and
+/// any other signature signs the version's primary file. Signed files which
+/// were uploaded before the signature are read back from the file host.
+async fn verify_file_signatures(
+    project_id: models::DBProjectId,
+    files: &mut [VersionFileBuilder],
+    file_data: &HashMap<String, Bytes>,
+    existing_files: &[FileQueryResult],
+    file_host: &dyn FileHost,
+    transaction: &mut PgTransaction<'_>,
+) -> Result<(), CreateError> {
+    let signatures = files
+        .iter()
+        .filter(|file| file.file_type == Some(FileType::Signature))
+        .filter_map(|file| {
+            Some((
+                file.filename.clone(),
+                file_data.get(&file.filename)?.clone(),
+            ))
+        })
+        .collect::<Vec<_>>();
+    if signatures.is_empty() {
+        return Ok(());
+    }
+
+    let keys = DBPgpKey::get_for_project(project_id, &mut *transaction).await?;
+
+    for (signature_name, signature) in signatures {
+        let signed_name =
+            openpgp::signed_file_name(&signature_name).filter(|name| {
+                files.iter().any(|file| file.filename == *name)
+                    || existing_files.iter().any(|file| file.filename == *name)
+            });
+        let is_signed =
+            |filename: &str, primary: bool, file_type: Option<FileType>| {
+                file_type != Some(FileType::Signature)
+                    && match signed_name {
+                        Some(signed_name) => filename == signed_name,
+                        None => primary,
+                    }
+            };
+
+        if let Some(file) = files.iter_mut().find(|file| {
+            is_signed(&file.filename, file.primary, file.file_type)
+        }) {
+            let data =
+                file_data.get(&file.filename).cloned().ok_or_else(|| {
+                    CreateError::InvalidInput(format!(
+                        "The file signed by {signature_name} was not uploaded"
+                    ))
+                })?;
+            file.signature = Some(
+                verify_file_signature(signature, data, keys.clone()).await?,
+            );
+        } else if let Some(file) = existing_files.iter().find(|file| {
+            is_signed(&file.filename, file.primary, file.file_type)
+        }) {
+            let key = file
+                .url
+                .strip_prefix(&ENV.CDN_URL)
+                .unwrap_or(&file.url)
+                .trim_start_matches('/');
+            let key = urlencoding::decode(key).map_err(|_| {
+                CreateError::InvalidInput(format!(
+                    "The file signed by {signature_name} has an invalid URL"
+                ))
+            })?;
+            let data =
+                file_host.read_file(&key, FileHostPublicity::Public).await?;
+
+            let result =
+                verify_file_signature(signature, data, keys.clone()).await?;
+            DBFile::set_signature(file.id, &result, &mut *transaction).await?;
+        } else {
+            return Err(CreateError::InvalidInput(format!(
+                "Could not find the file signed by {signature_name}"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
 pub fn get_name_ext(
     content_disposition: &actix_web::http::header::ContentDisposition,
 ) -> Result<(&str, &str), CreateError> {
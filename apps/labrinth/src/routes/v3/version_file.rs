@@ -3,6 +3,7 @@ use crate::auth::checks::{filter_visible_versions, is_visible_version};
 use crate::auth::{filter_visible_projects, get_user_from_headers};
 use crate::database::PgPool;
 use crate::database::ReadOnlyPgPool;
+use crate::database::models::version_file_delta_item::DBVersionFileDelta;
 use crate::models::ids::VersionId;
 use crate::models::pats::Scopes;
 use crate::models::projects::{ProjectStatus, VersionStatus, VersionType};
@@ -27,6 +28,7 @@ pub fn config(cfg: &mut actix_web::web::ServiceConfig) {
         .service(get_projects_from_hashes_route)
         .service(delete_file_route)
         .service(download_version_route)
+        .service(get_version_file_delta_route)
         .service(update_files_route)
         .service(update_files_many_route)
         .service(update_individual_files_route)
@@ -887,28 +889,14 @@ pub async fn delete_file(
                 .await
                 .wrap_internal_err("fetching version team member")?;
 
-            let organization =
-                database::models::DBOrganization::get_associated_organization_project_id(
+            let organization_team_member =
+                database::models::DBTeamMember::get_from_user_id_project_organization(
                     row.project_id,
-                    &**pool,
-                )
-                .await
-                .wrap_internal_err("fetching project organization")?;
-
-            let organization_team_member = if let Some(organization) =
-                &organization
-            {
-                database::models::DBTeamMember::get_from_user_id_organization(
-                    organization.id,
                     user.id.into(),
-                    false,
                     &**pool,
                 )
                 .await
-                .wrap_internal_err("fetching organization team member")?
-            } else {
-                None
-            };
+                .wrap_internal_err("fetching organization team member")?;
 
             let permissions = ProjectPermissions::get_permissions_by_role(
                 &user.role,
@@ -1086,3 +1074,114 @@ pub async fn download_version(
         Err(ApiError::NotFound(eyre::eyre!("resource not found")))
     }
 }
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct DeltaQuery {
+    /// Hash algorithm of both hashes (sha1 or sha512)
+    pub algorithm: Option<String>,
+}
+
+/// A binary delta which turns one version file into another.
+///
+/// Apply it to the source file with `ariadne::delta::apply`, then check the
+/// result against the target file's hash before using it.
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct VersionFileDelta {
+    /// URL of the delta
+    pub url: String,
+    /// Size of the delta in bytes
+    pub size: u32,
+}
+
+/// Get a binary delta between two files of the same project by their hashes.
+#[utoipa::path(
+	tag = "version files",
+    get,
+    operation_id = "v3VersionFileDelta",
+    params(
+        ("from_hash" = String, Path, description = "The hexadecimal hash of the file to update from"),
+        ("to_hash" = String, Path, description = "The hexadecimal hash of the file to update to"),
+        ("algorithm" = Option<String>, Query, description = "Hash algorithm of both hashes (sha1 or sha512)")
+    ),
+    responses(
+        (status = 200, description = "Expected response to a valid request", body = VersionFileDelta),
+        (
+            status = 404,
+            description = "The requested item(s) were not found, no delta is available between them, or no authorization to access the requested item(s)"
+        )
+    )
+)]
+#[get("/version_file/{from_hash}/delta/{to_hash}")]
+pub async fn get_version_file_delta_route(
+    req: HttpRequest,
+    info: web::Path<(String, String)>,
+    pool: web::Data<PgPool>,
+    redis: web::Data<RedisPool>,
+    delta_query: web::Query<DeltaQuery>,
+    session_queue: web::Data<AuthQueue>,
+) -> Result<HttpResponse, ApiError> {
+    let user_option = get_user_from_headers(
+        &req,
+        &**pool,
+        &redis,
+        &session_queue,
+        Scopes::VERSION_READ,
+    )
+    .await
+    .map(|x| x.1)
+    .ok();
+
+    let (from_hash, to_hash) = info.into_inner();
+    let hashes = [from_hash.to_lowercase(), to_hash.to_lowercase()];
+    let algorithm = delta_query
+        .algorithm
+        .clone()
+        .unwrap_or_else(|| default_algorithm_from_hashes(&hashes));
+    let [from_hash, to_hash] = hashes;
+
+    let mut files = Vec::with_capacity(2);
+    for hash in [from_hash, to_hash] {
+        let file = database::models::DBVersion::get_file_from_hash(
+            algorithm.clone(),
+            hash,
+            None,
+            &**pool,
+            &redis,
+        )
+        .await
+        .wrap_internal_err("fetching version file from database")?
+        .wrap_not_found_err("resource not found")?;
+        let version =
+            database::models::DBVersion::get(file.version_id, &**pool, &redis)
+                .await
+                .wrap_internal_err("fetching version from database")?
+                .wrap_not_found_err("resource not found")?;
+
+        if !is_visible_version(&version.inner, &user_option, &pool, &redis)
+            .await
+            .wrap_api_err("checking version visibility")?
+        {
+            return Err(ApiError::NotFound(eyre::eyre!("resource not found")));
+        }
+
+        files.push(file);
+    }
+
+    let delta = DBVersionFileDelta::get(files[0].id, files[1].id, &**pool)
+        .await
+        .wrap_internal_err("fetching version file delta from database")?;
+
+    match delta {
+        Some(DBVersionFileDelta {
+            url: Some(url),
+            size: Some(size),
+            ..
+        }) => Ok(HttpResponse::Ok().json(VersionFileDelta {
+            url,
+            size: size as u32,
+        })),
+        _ => Err(ApiError::NotFound(eyre::eyre!(
+            "no delta is available between these files"
+        ))),
+    }
+}
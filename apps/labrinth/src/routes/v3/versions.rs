@@ -7,18 +7,22 @@ use crate::auth::checks::{
 };
 use crate::auth::get_user_from_headers;
 use crate::database;
+use crate::database::models::audit_log_item::AuditLogEntryBuilder;
+use crate::database::models::image_item;
 use crate::database::models::loader_fields::{
     self, LoaderField, LoaderFieldEnumValue, VersionField,
 };
+use crate::database::models::project_webhook_item::DBProjectWebhookDelivery;
 use crate::database::models::version_item::{
     DBLoaderVersion, DependencyBuilder,
 };
-use crate::database::models::{DBOrganization, image_item};
-use crate::database::{PgPool, ReadOnlyPgPool};
+use crate::database::{PgPool, PgTransaction, ReadOnlyPgPool};
 use crate::models;
+use crate::models::audit_log::{AuditLogAction, AuditLogTarget};
 use crate::models::ids::VersionId;
 use crate::models::images::ImageContext;
 use crate::models::pats::Scopes;
+use crate::models::project_webhooks::ProjectWebhookEvent;
 use crate::models::projects::{
     Dependency, FileType, VersionStatus, VersionType,
 };
@@ -487,6 +491,7 @@ pub async fn version_edit_helper(
         .wrap_internal_err("fetching version from database")?;
 
     if let Some(version_item) = result {
+        let old_version = models::projects::Version::from(version_item.clone());
         let team_member =
             database::models::DBTeamMember::get_from_user_id_project(
                 version_item.inner.project_id,
@@ -497,26 +502,14 @@ pub async fn version_edit_helper(
             .await
             .wrap_internal_err("fetching team member from database")?;
 
-        let organization =
-            DBOrganization::get_associated_organization_project_id(
+        let organization_team_member =
+            database::models::DBTeamMember::get_from_user_id_project_organization(
                 version_item.inner.project_id,
-                &**pool,
-            )
-            .await
-            .wrap_internal_err("fetching organization from database")?;
-
-        let organization_team_member = if let Some(organization) = &organization
-        {
-            database::models::DBTeamMember::get_from_user_id(
-                organization.team_id,
                 user.id.into(),
                 &**pool,
             )
             .await
-            .wrap_internal_err("fetching team member from database")?
-        } else {
-            None
-        };
+            .wrap_internal_err("fetching team member from database")?;
 
         let permissions = ProjectPermissions::get_permissions_by_role(
             &user.role,
@@ -918,6 +911,19 @@ pub async fn version_edit_helper(
             .await
             .wrap_api_err("deleting unused images")?;
 
+            DBProjectWebhookDelivery::insert_for_event(
+                &ProjectWebhookEvent::VersionEdited {
+                    project_id: version_item.inner.project_id.into(),
+                    version_id: version_item.inner.id.into(),
+                },
+                &mut transaction,
+            )
+            .await
+            .wrap_internal_err("queueing project webhook deliveries")?;
+
+            log_version_edit(user.id.into(), old_version, &mut transaction)
+                .await?;
+
             transaction
                 .commit()
                 .await
@@ -940,6 +946,7 @@ pub async fn version_edit_helper(
                     [VersionId::from(version_item.inner.id)],
                 )
                 .await;
+
             Ok(HttpResponse::NoContent().body(""))
         } else {
             Err(ApiError::Auth(eyre::eyre!(
@@ -951,6 +958,45 @@ pub async fn version_edit_helper(
     }
 }
 
+/// Records the changes an edit made to a version in the audit log.
+///
+/// This must be called with the transaction making the edit, before it is
+/// committed, as the edited version is read through it to be compared
+/// against `old_version`.
+async fn log_version_edit(
+    actor_id: database::models::DBUserId,
+    old_version: models::projects::Version,
+    transaction: &mut PgTransaction<'_>,
+) -> Result<(), ApiError> {
+    let Some(new_version) = database::models::DBVersion::get_uncached(
+        old_version.id.into(),
+        &mut *transaction,
+    )
+    .await
+    .wrap_internal_err("fetching edited version from database")?
+    else {
+        return Ok(());
+    };
+
+    if let Some(entry) = AuditLogEntryBuilder::project(
+        actor_id,
+        old_version.project_id.into(),
+        AuditLogAction::VersionEdited,
+        AuditLogTarget::Version {
+            version_id: old_version.id,
+        },
+    )
+    .changes(&old_version, &models::projects::Version::from(new_version))
+    {
+        entry
+            .insert(&mut *transaction)
+            .await
+            .wrap_internal_err("inserting audit log entry")?;
+    }
+
+    Ok(())
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct VersionListFilters {
     pub loaders: Option<String>,
@@ -1255,26 +1301,14 @@ pub async fn version_delete(
             .await
             .wrap_internal_err("fetching project team member")?;
 
-        let organization =
-            DBOrganization::get_associated_organization_project_id(
+        let organization_team_member =
+            database::models::DBTeamMember::get_from_user_id_project_organization(
                 version.inner.project_id,
-                &**pool,
-            )
-            .await
-            .wrap_internal_err("fetching organization from database")?;
-
-        let organization_team_member = if let Some(organization) = &organization
-        {
-            database::models::DBTeamMember::get_from_user_id(
-                organization.team_id,
                 user.id.into(),
                 &**pool,
             )
             .await
-            .wrap_internal_err("fetching team member from database")?
-        } else {
-            None
-        };
+            .wrap_internal_err("fetching team member from database")?;
         let permissions = ProjectPermissions::get_permissions_by_role(
             &user.role,
             &team_member,
@@ -1327,6 +1361,32 @@ pub async fn version_delete(
         "executing `tech_review_sync::sync_project_tech_review_state`",
     )?;
 
+    AuditLogEntryBuilder::project(
+        user.id.into(),
+        version.inner.project_id,
+        AuditLogAction::VersionDeleted,
+        AuditLogTarget::Version {
+            version_id: version.inner.id.into(),
+        },
+    )
+    .before(&serde_json::json!({
+        "name": version.inner.name,
+        "version_number": version.inner.version_number,
+    }))
+    .insert(&mut transaction)
+    .await
+    .wrap_internal_err("inserting audit log entry")?;
+
+    DBProjectWebhookDelivery::insert_for_event(
+        &ProjectWebhookEvent::VersionDeleted {
+            project_id: version.inner.project_id.into(),
+            version_id: version.inner.id.into(),
+        },
+        &mut transaction,
+    )
+    .await
+    .wrap_internal_err("queueing project webhook deliveries")?;
+
     transaction
         .commit()
         .await
@@ -707,6 +707,7 @@ impl Elasticsearch {
                         "properties": {
                             "verified_plays_2w": {"type": "long"},
                             "is_online": {"type": "boolean"},
+                            "verified": {"type": "boolean"},
                             "ping": {
                                 "properties": {
                                     "data": {
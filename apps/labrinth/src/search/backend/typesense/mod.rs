@@ -818,6 +818,7 @@ impl Typesense {
             json!({"name": "version_published_timestamp", "type": "int64", "sort": true, "optional": true}),
             json!({"name": "minecraft_java_server.verified_plays_2w", "type": "int64", "sort": true, "optional": true}),
             json!({"name": "minecraft_java_server.is_online", "type": "bool", "sort": true, "optional": true}),
+            json!({"name": "minecraft_java_server.verified", "type": "bool", "facet": true, "optional": true}),
             json!({"name": "minecraft_java_server.ping.data.players_online", "type": "int32", "sort": true, "optional": true}),
             json!({"name": "dependencies", "type": "object[]", "optional": true}),
             json!({"name": "project_categories", "type": "string[]", "facet": true, "optional": true}),
@@ -2,7 +2,7 @@ use crate::models::exp;
 use crate::models::exp::minecraft::JavaServerPing;
 use crate::models::ids::{ProjectId, VersionId};
 use crate::models::projects::DependencyType;
-use crate::queue::server_ping;
+use crate::queue::server_ping::ServerEdition;
 use crate::routes::ApiError;
 use crate::util::error::ApiContext as _;
 use crate::util::error::Context as _;
@@ -13,6 +13,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{collections::HashMap, str::FromStr, sync::Arc};
+use strum::IntoEnumIterator;
 use thiserror::Error;
 use utoipa::ToSchema;
 use xredis::RedisPool;
@@ -134,55 +135,68 @@ async fn hydrate_search_results(
     hits: &mut [ResultSearchProject],
     redis_pool: &RedisPool,
 ) -> eyre::Result<()> {
-    // Minecraft Java servers should fetch the latest player count that we have
-    // from Redis, rather than the (pretty stale) data from search backend
-    // TODO: this block should be made generic over the component type,
-    // for now we can hardcode MC java servers tho
-
-    let project_ids = hits
-        .iter()
-        .filter(|hit| hit.components.minecraft_java_server.is_some())
-        .filter_map(|hit| parse_base62(&hit.project_id).ok().map(ProjectId))
-        .collect::<Vec<_>>();
-
-    let pings_by_project_id = if project_ids.is_empty() {
-        HashMap::new()
-    } else {
-        let mut redis = redis_pool.connect().await?;
-        let ping_keys = project_ids
-            .iter()
-            .map(|project_id| {
-                redis_pool
-                    .key()
-                    .entity(server_ping::REDIS_NAMESPACE, project_id)
-            })
-            .collect::<Vec<_>>();
-        let ping_results = redis
-            .get_many_deserialized::<JavaServerPing>(&ping_keys)
-            .await?;
-
-        ping_results
-            .into_iter()
-            .enumerate()
-            .filter_map(|(idx, ping)| ping.map(|ping| (project_ids[idx], ping)))
-            .collect::<HashMap<_, _>>()
-    };
-
-    for hit in hits {
-        let Some(java_server) = hit.components.minecraft_java_server.as_mut()
-        else {
-            continue;
-        };
-        if let Ok(project_id) = parse_base62(&hit.project_id).map(ProjectId) {
-            java_server.ping = pings_by_project_id.get(&project_id).cloned();
-        } else {
-            java_server.ping = None;
+    // Minecraft servers should fetch the latest player count that we have
+    // from Redis, rather than the (pretty stale) data from search backend.
+    // Each edition is pinged separately, so a listing with both a Java and a
+    // Bedrock server gets both pings.
+    // TODO: this block should be made generic over the component type
+
+    let mut ping_hits = Vec::new();
+    let mut ping_keys = Vec::new();
+    for (idx, hit) in hits.iter_mut().enumerate() {
+        let project_id = parse_base62(&hit.project_id).ok().map(ProjectId);
+        for edition in ServerEdition::iter() {
+            let Some(ping) = server_ping_mut(&mut hit.components, edition)
+            else {
+                continue;
+            };
+            *ping = None;
+            if let Some(project_id) = project_id {
+                ping_hits.push((idx, edition));
+                ping_keys.push(
+                    redis_pool
+                        .key()
+                        .entity(edition.redis_namespace(), project_id),
+                );
+            }
+        }
+    }
+    if ping_keys.is_empty() {
+        return Ok(());
+    }
+
+    let mut redis = redis_pool.connect().await?;
+    let ping_results = redis
+        .get_many_deserialized::<JavaServerPing>(&ping_keys)
+        .await?;
+    for ((idx, edition), ping) in ping_hits.into_iter().zip(ping_results) {
+        if let Some(target) =
+            server_ping_mut(&mut hits[idx].components, edition)
+        {
+            *target = ping;
         }
     }
 
     Ok(())
 }
 
+/// The ping of a listing's server for `edition`, if it has one.
+fn server_ping_mut(
+    components: &mut exp::ProjectQuery,
+    edition: ServerEdition,
+) -> Option<&mut Option<JavaServerPing>> {
+    match edition {
+        ServerEdition::Java => components
+            .minecraft_java_server
+            .as_mut()
+            .map(|server| &mut server.ping),
+        ServerEdition::Bedrock => components
+            .minecraft_bedrock_server
+            .as_mut()
+            .map(|server| &mut server.ping),
+    }
+}
+
 #[derive(Deserialize, Serialize, ToSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TasksCancelFilter {
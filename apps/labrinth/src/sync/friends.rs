@@ -1,20 +1,28 @@
 use crate::database::PgPool;
+use crate::database::models::DatabaseError;
 use crate::database::models::notification_item::DBNotification;
 use crate::models::ids::NotificationId;
 use crate::models::notifications::Notification;
 use crate::queue::socket::ActiveSockets;
 use crate::routes::internal::statuses::{
-    broadcast_to_local_friends, send_message_to_user, send_notification_to_user,
+    accept_tunnel_connection, broadcast_to_local_friends,
+    close_local_tunnel_socket, send_message, send_message_to_user,
+    send_notification_to_user,
 };
 use actix_web::web::Data;
 use ariadne::ids::UserId;
-use ariadne::networking::message::ServerToClientMessage;
+use ariadne::networking::message::{ServerToClientMessage, WorldInvite};
 use ariadne::users::UserStatus;
 use redis::{RedisWrite, ToRedisArgs, ToSingleRedisArg};
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
+use uuid::Uuid;
+use xredis::{RedisConnection, RedisPool};
 
 pub const FRIENDS_CHANNEL_NAME: &str = "friends:v4";
+const TUNNEL_CHANNEL_PREFIX: &str = "friends_tunnel:v1";
+const WORLD_INVITES_NAMESPACE: &str = "world_invites:v1";
+const WORLD_INVITE_EXPIRY_SECONDS: i64 = 60 * 60 * 24;
 
 #[derive(Serialize, Deserialize)]
 pub enum RedisFriendsMessage {
@@ -32,6 +40,41 @@ pub enum RedisFriendsMessage {
         to_user: UserId,
         notification_id: NotificationId,
     },
+    WorldInvite {
+        from: UserId,
+        to_user: UserId,
+        invite: WorldInvite,
+    },
+}
+
+/// Tunnel traffic for the launchers connected to a single replica, published
+/// to that replica's [`tunnel_channel_name`] so that busy tunnels don't hold
+/// up the friends channel.
+#[derive(Serialize, Deserialize)]
+pub enum RedisTunnelMessage {
+    Connect {
+        owner: UserId,
+        to_socket: Uuid,
+        new_socket: Uuid,
+        connected_to: Uuid,
+        /// The replica the connecting launcher is connected to
+        replica: Uuid,
+    },
+    Data {
+        socket: Uuid,
+        data: Vec<u8>,
+    },
+    Closed {
+        socket: Uuid,
+    },
+}
+
+/// Allows a friend to connect to a listening socket, recording who owns it
+/// and the replica its launcher is connected to.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct WorldInviteRecord {
+    pub owner: UserId,
+    pub replica: Uuid,
 }
 
 impl ToRedisArgs for RedisFriendsMessage {
@@ -45,6 +88,58 @@ impl ToRedisArgs for RedisFriendsMessage {
 
 impl ToSingleRedisArg for RedisFriendsMessage {}
 
+impl ToRedisArgs for RedisTunnelMessage {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        out.write_arg(&postcard::to_allocvec(&self).unwrap())
+    }
+}
+
+impl ToSingleRedisArg for RedisTunnelMessage {}
+
+pub fn tunnel_channel_name(replica: Uuid) -> String {
+    format!("{TUNNEL_CHANNEL_PREFIX}:{replica}")
+}
+
+/// Records that `to_user` was invited to connect to `socket`.
+pub async fn insert_world_invite(
+    socket: Uuid,
+    to_user: UserId,
+    record: &WorldInviteRecord,
+    redis: &RedisPool,
+) -> Result<(), DatabaseError> {
+    let mut redis = redis.connect().await?;
+    let key = world_invite_key(&redis, socket, to_user);
+
+    redis
+        .set_serialized(&key, record, Some(WORLD_INVITE_EXPIRY_SECONDS))
+        .await?;
+    Ok(())
+}
+
+pub async fn get_world_invite(
+    socket: Uuid,
+    user: UserId,
+    redis: &RedisPool,
+) -> Result<Option<WorldInviteRecord>, DatabaseError> {
+    let mut redis = redis.connect().await?;
+    let key = world_invite_key(&redis, socket, user);
+
+    redis.get_deserialized(&key).await.map_err(Into::into)
+}
+
+fn world_invite_key(
+    redis: &RedisConnection,
+    socket: Uuid,
+    user: UserId,
+) -> String {
+    redis
+        .key()
+        .entity(WORLD_INVITES_NAMESPACE, format!("{socket}:{user}"))
+}
+
 pub async fn handle_pubsub(
     mut messages: mpsc::Receiver<Vec<u8>>,
     pool: PgPool,
@@ -105,8 +200,135 @@ pub async fn handle_pubsub(
                     }
                 }
 
+                Ok(RedisFriendsMessage::WorldInvite {
+                    from,
+                    to_user,
+                    invite,
+                }) => {
+                    let _ = send_message_to_user(
+                        &sockets,
+                        to_user,
+                        &ServerToClientMessage::WorldInvite { from, invite },
+                    )
+                    .await;
+                }
+
                 Err(_) => {}
             }
         });
     }
 }
+
+/// Handles tunnel traffic sent to this replica. Messages are handled in the
+/// order they were published, so that data isn't relayed before its
+/// connection is set up.
+pub async fn handle_tunnel_pubsub(
+    mut messages: mpsc::Receiver<Vec<u8>>,
+    redis: RedisPool,
+    sockets: Data<ActiveSockets>,
+) {
+    while let Some(message) = messages.recv().await {
+        match postcard::from_bytes::<RedisTunnelMessage>(&message) {
+            Ok(RedisTunnelMessage::Connect {
+                owner,
+                to_socket,
+                new_socket,
+                connected_to,
+                replica,
+            }) => {
+                accept_tunnel_connection(
+                    owner,
+                    to_socket,
+                    new_socket,
+                    connected_to,
+                    replica,
+                    &sockets,
+                    &redis,
+                )
+                .await;
+            }
+            Ok(RedisTunnelMessage::Data { socket, data }) => {
+                if let Some(owner) =
+                    sockets.tunnel_sockets.get(&socket).map(|x| x.owner)
+                    && let Some(owner) = sockets.sockets.get(&owner)
+                {
+                    let _ = send_message(
+                        &owner,
+                        &ServerToClientMessage::SocketData { socket, data },
+                    )
+                    .await;
+                }
+            }
+            Ok(RedisTunnelMessage::Closed { socket }) => {
+                close_local_tunnel_socket(socket, &sockets).await;
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(message: RedisTunnelMessage) -> RedisTunnelMessage {
+        let args = message.to_redis_args();
+        assert_eq!(args.len(), 1);
+        postcard::from_bytes(&args[0]).unwrap()
+    }
+
+    #[test]
+    fn tunnel_messages_round_trip() {
+        let (to_socket, new_socket, connected_to, replica) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let message = round_trip(RedisTunnelMessage::Connect {
+            owner: UserId(1),
+            to_socket,
+            new_socket,
+            connected_to,
+            replica,
+        });
+        assert!(matches!(
+            message,
+            RedisTunnelMessage::Connect {
+                owner: UserId(1),
+                to_socket: a,
+                new_socket: b,
+                connected_to: c,
+                replica: d,
+            } if (a, b, c, d) == (to_socket, new_socket, connected_to, replica)
+        ));
+
+        let message = round_trip(RedisTunnelMessage::Data {
+            socket: to_socket,
+            data: vec![0, 1, 2],
+        });
+        assert!(matches!(
+            message,
+            RedisTunnelMessage::Data { socket, data }
+                if socket == to_socket && data == [0, 1, 2]
+        ));
+
+        let message =
+            round_trip(RedisTunnelMessage::Closed { socket: to_socket });
+        assert!(matches!(
+            message,
+            RedisTunnelMessage::Closed { socket } if socket == to_socket
+        ));
+    }
+
+    #[test]
+    fn tunnel_channels_are_per_replica() {
+        let replica = Uuid::new_v4();
+        assert_eq!(tunnel_channel_name(replica), tunnel_channel_name(replica));
+        assert_ne!(
+            tunnel_channel_name(replica),
+            tunnel_channel_name(Uuid::new_v4())
+        );
+        assert_ne!(tunnel_channel_name(replica), FRIENDS_CHANNEL_NAME);
+    }
+}
@@ -0,0 +1,395 @@
+use crate::database::PgPool;
+use crate::database::models::ids::{DBNotificationId, DBProjectId, DBUserId};
+use crate::database::models::notification_item::DBNotification;
+use crate::database::models::notifications_type_item::NotificationTypeItem;
+use crate::database::models::report_item::DBReport;
+use crate::database::models::thread_item::{DBThread, DBThreadMessage};
+use crate::models::ids::{
+    NotificationId, ProjectId, ThreadId, ThreadMessageId,
+};
+use crate::models::notifications::Notification;
+use crate::models::pats::Scopes;
+use crate::models::projects::ProjectStatus;
+use crate::models::realtime::RealtimeEvent;
+use crate::models::threads::{ThreadMessage, ThreadType};
+use crate::queue::realtime::ActiveEventStreams;
+use actix_web::web::Data;
+use redis::{RedisWrite, ToRedisArgs, ToSingleRedisArg};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tokio::sync::mpsc;
+use tracing::warn;
+use xredis::RedisPool;
+
+pub const REALTIME_CHANNEL_NAME: &str = "realtime:v1";
+
+#[derive(Serialize, Deserialize)]
+pub enum RedisRealtimeMessage {
+    Notifications {
+        notification_ids: Vec<NotificationId>,
+    },
+    ThreadMessage {
+        thread_id: ThreadId,
+        message_id: ThreadMessageId,
+    },
+    ProjectStatus {
+        project_id: ProjectId,
+        old_status: String,
+        new_status: String,
+    },
+}
+
+impl ToRedisArgs for RedisRealtimeMessage {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        out.write_arg(&postcard::to_allocvec(&self).unwrap())
+    }
+}
+
+impl ToSingleRedisArg for RedisRealtimeMessage {}
+
+/// Publishes a realtime event to every replica.
+///
+/// Replicas load the records an event refers to when they receive it, so
+/// events must only be published once the transaction creating those
+/// records has committed. Failures are logged rather than returned, since
+/// the change the event describes has already been made and clients can
+/// still poll for it.
+pub async fn broadcast_realtime_message(
+    redis: &RedisPool,
+    message: RedisRealtimeMessage,
+) {
+    if let Err(err) = redis.publish(REALTIME_CHANNEL_NAME, message).await {
+        warn!("Failed to publish realtime event: {err:?}");
+    }
+}
+
+pub async fn broadcast_realtime_notifications(
+    redis: &RedisPool,
+    notification_ids: Vec<DBNotificationId>,
+) {
+    if notification_ids.is_empty() {
+        return;
+    }
+
+    broadcast_realtime_message(
+        redis,
+        RedisRealtimeMessage::Notifications {
+            notification_ids: notification_ids
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        },
+    )
+    .await;
+}
+
+pub async fn broadcast_thread_message(
+    redis: &RedisPool,
+    thread_id: ThreadId,
+    message_id: ThreadMessageId,
+) {
+    broadcast_realtime_message(
+        redis,
+        RedisRealtimeMessage::ThreadMessage {
+            thread_id,
+            message_id,
+        },
+    )
+    .await;
+}
+
+pub async fn broadcast_project_status(
+    redis: &RedisPool,
+    project_id: ProjectId,
+    old_status: ProjectStatus,
+    new_status: ProjectStatus,
+) {
+    broadcast_realtime_message(
+        redis,
+        RedisRealtimeMessage::ProjectStatus {
+            project_id,
+            old_status: old_status.as_str().to_string(),
+            new_status: new_status.as_str().to_string(),
+        },
+    )
+    .await;
+}
+
+pub async fn handle_pubsub(
+    mut messages: mpsc::Receiver<Vec<u8>>,
+    pool: PgPool,
+    redis: RedisPool,
+    streams: Data<ActiveEventStreams>,
+) {
+    while let Some(message) = messages.recv().await {
+        // Most replicas have no streams open for most events, so skip
+        // decoding and loading records entirely when there's nobody to send
+        // them to.
+        if streams.is_empty() {
+            continue;
+        }
+
+        let Ok(payload) =
+            postcard::from_bytes::<RedisRealtimeMessage>(&message)
+        else {
+            continue;
+        };
+
+        let pool = pool.clone();
+        let redis = redis.clone();
+        let streams = streams.clone();
+        actix_rt::spawn(async move {
+            let result = match payload {
+                RedisRealtimeMessage::Notifications { notification_ids } => {
+                    send_notifications(
+                        notification_ids,
+                        &pool,
+                        &redis,
+                        &streams,
+                    )
+                    .await
+                }
+                RedisRealtimeMessage::ThreadMessage {
+                    thread_id,
+                    message_id,
+                } => {
+                    send_thread_message(thread_id, message_id, &pool, &streams)
+                        .await
+                }
+                RedisRealtimeMessage::ProjectStatus {
+                    project_id,
+                    old_status,
+                    new_status,
+                } => {
+                    send_project_status(
+                        project_id,
+                        ProjectStatus::from_string(&old_status),
+                        ProjectStatus::from_string(&new_status),
+                        &pool,
+                        &streams,
+                    )
+                    .await
+                }
+            };
+
+            if let Err(err) = result {
+                warn!("Failed to send realtime event: {err:?}");
+            }
+        });
+    }
+}
+
+async fn send_notifications(
+    notification_ids: Vec<NotificationId>,
+    pool: &PgPool,
+    redis: &RedisPool,
+    streams: &ActiveEventStreams,
+) -> eyre::Result<()> {
+    let ids = notification_ids
+        .into_iter()
+        .map(DBNotificationId::from)
+        .collect::<Vec<_>>();
+    let notifications = DBNotification::get_many(&ids, pool)
+        .await?
+        .into_iter()
+        .filter(|notification| streams.has_user(notification.user_id.into()))
+        .collect::<Vec<_>>();
+    if notifications.is_empty() {
+        return Ok(());
+    }
+
+    let exposed_types = NotificationTypeItem::list(pool, redis)
+        .await?
+        .into_iter()
+        .filter(|x| x.expose_in_site_notifications)
+        .map(|x| x.name)
+        .collect::<Vec<_>>();
+
+    for notification in notifications {
+        if !exposed_types.contains(&notification.body.notification_type()) {
+            continue;
+        }
+
+        let user_id = notification.user_id.into();
+        streams.send_to_user(
+            user_id,
+            |stream| stream.scopes.contains(Scopes::NOTIFICATION_READ),
+            &RealtimeEvent::Notification {
+                notification: Notification::from(notification),
+            },
+        );
+    }
+
+    Ok(())
+}
+
+async fn send_thread_message(
+    thread_id: ThreadId,
+    message_id: ThreadMessageId,
+    pool: &PgPool,
+    streams: &ActiveEventStreams,
+) -> eyre::Result<()> {
+    let Some(message) = DBThreadMessage::get(message_id.into(), pool).await?
+    else {
+        return Ok(());
+    };
+    let Some(thread) = DBThread::get(thread_id.into(), pool).await? else {
+        return Ok(());
+    };
+
+    // Moderators can read every thread, including private messages.
+    let audience = if message.body.is_private() {
+        HashSet::new()
+    } else {
+        thread_audience(&thread, pool).await?
+    };
+
+    streams.send_where(
+        |stream| {
+            stream.scopes.contains(Scopes::THREAD_READ)
+                && (stream.user.role.is_mod()
+                    || audience.contains(&DBUserId::from(stream.user.id)))
+        },
+        |stream| RealtimeEvent::ThreadMessage {
+            thread_id,
+            message: ThreadMessage::from(message.clone(), &stream.user),
+        },
+    );
+
+    Ok(())
+}
+
+async fn send_project_status(
+    project_id: ProjectId,
+    old_status: ProjectStatus,
+    new_status: ProjectStatus,
+    pool: &PgPool,
+    streams: &ActiveEventStreams,
+) -> eyre::Result<()> {
+    let audience = project_audience(project_id.into(), pool).await?;
+
+    let event = RealtimeEvent::ProjectStatus {
+        project_id,
+        old_status,
+        new_status,
+    };
+    for user_id in audience {
+        streams.send_to_user(
+            user_id.into(),
+            |stream| stream.scopes.contains(Scopes::PROJECT_READ),
+            &event,
+        );
+    }
+
+    Ok(())
+}
+
+/// Users other than moderators who can read a thread, matching
+/// `is_authorized_thread`.
+async fn thread_audience(
+    thread: &DBThread,
+    pool: &PgPool,
+) -> eyre::Result<HashSet<DBUserId>> {
+    Ok(match thread.type_ {
+        ThreadType::DirectMessage => thread.members.iter().copied().collect(),
+        ThreadType::Report => match thread.report_id {
+            Some(report_id) => DBReport::get(report_id, pool)
+                .await?
+                .map(|report| report.reporter)
+                .into_iter()
+                .collect(),
+            None => HashSet::new(),
+        },
+        ThreadType::Project => match thread.project_id {
+            Some(project_id) => project_audience(project_id, pool).await?,
+            None => HashSet::new(),
+        },
+    })
+}
+
+/// Members of a project's team or its organization's team.
+async fn project_audience(
+    project_id: DBProjectId,
+    pool: &PgPool,
+) -> eyre::Result<HashSet<DBUserId>> {
+    let user_ids = sqlx::query_scalar!(
+        r#"
+        SELECT tm.user_id "user_id!"
+        FROM mods m
+        INNER JOIN team_members tm ON tm.team_id = m.team_id
+        WHERE m.id = $1
+        UNION
+        SELECT tm.user_id "user_id!"
+        FROM mods m
+        INNER JOIN organizations o ON o.id = m.organization_id
+        INNER JOIN team_members tm ON tm.team_id = o.team_id
+        WHERE m.id = $1
+        "#,
+        project_id as DBProjectId,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(user_ids.into_iter().map(DBUserId).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(message: RedisRealtimeMessage) -> RedisRealtimeMessage {
+        let args = message.to_redis_args();
+        assert_eq!(args.len(), 1);
+        postcard::from_bytes(&args[0]).unwrap()
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        let message = round_trip(RedisRealtimeMessage::Notifications {
+            notification_ids: vec![NotificationId(1), NotificationId(2)],
+        });
+        assert!(matches!(
+            message,
+            RedisRealtimeMessage::Notifications { notification_ids }
+                if notification_ids == [NotificationId(1), NotificationId(2)]
+        ));
+
+        let message = round_trip(RedisRealtimeMessage::ThreadMessage {
+            thread_id: ThreadId(3),
+            message_id: ThreadMessageId(4),
+        });
+        assert!(matches!(
+            message,
+            RedisRealtimeMessage::ThreadMessage {
+                thread_id: ThreadId(3),
+                message_id: ThreadMessageId(4),
+            }
+        ));
+
+        let message = round_trip(RedisRealtimeMessage::ProjectStatus {
+            project_id: ProjectId(5),
+            old_status: ProjectStatus::Processing.as_str().to_string(),
+            new_status: ProjectStatus::Approved.as_str().to_string(),
+        });
+        let RedisRealtimeMessage::ProjectStatus {
+            project_id,
+            old_status,
+            new_status,
+        } = message
+        else {
+            panic!("decoded the wrong message");
+        };
+        assert_eq!(project_id, ProjectId(5));
+        assert_eq!(
+            ProjectStatus::from_string(&old_status),
+            ProjectStatus::Processing
+        );
+        assert_eq!(
+            ProjectStatus::from_string(&new_status),
+            ProjectStatus::Approved
+        );
+    }
+}
@@ -1,11 +1,16 @@
 use crate::env::ENV;
-use crate::models::ids::PayoutId;
+use crate::models::ids::{PayoutId, ProjectId};
 use crate::routes::ApiError;
 use crate::routes::internal::gotenberg::{GotenbergDocument, GotenbergError};
 use crate::util::error::ApiContext as _;
 use crate::util::error::Context;
 use actix_web::http::header::HeaderName;
-use chrono::{DateTime, Datelike, Utc};
+use ariadne::ids::base62_impl::to_base62;
+use ariadne::ids::random_base62;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use quick_xml::escape::escape;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 use std::time::Duration;
@@ -15,7 +20,10 @@ pub const MODRINTH_GENERATED_PDF_TYPE: HeaderName =
     HeaderName::from_static("modrinth-generated-pdf-type");
 pub const MODRINTH_PAYMENT_ID: HeaderName =
     HeaderName::from_static("modrinth-payment-id");
+pub const MODRINTH_REVENUE_STATEMENT_ID: HeaderName =
+    HeaderName::from_static("modrinth-revenue-statement-id");
 pub const PAYMENT_STATEMENTS_NAMESPACE: &str = "payment_statements:v4";
+pub const REVENUE_STATEMENTS_NAMESPACE: &str = "revenue_statements:v1";
 const REDIS_TIMEOUT_MARGIN_MS: u64 = 250;
 
 pub(crate) fn payment_statement_key(
@@ -27,6 +35,17 @@ pub(crate) fn payment_statement_key(
         .with_slot(PAYMENT_STATEMENTS_NAMESPACE, payment_id, payment_id)
 }
 
+pub(crate) fn revenue_statement_key(
+    redis: &RedisPool,
+    statement_id: &str,
+) -> String {
+    redis.key().with_slot(
+        REVENUE_STATEMENTS_NAMESPACE,
+        statement_id,
+        statement_id,
+    )
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PaymentStatement {
     pub payment_id: PayoutId,
@@ -41,15 +60,35 @@ pub struct PaymentStatement {
     pub currency_code: String,
 }
 
+/// A team member's monthly revenue from a single project.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RevenueStatement {
+    pub project_id: ProjectId,
+    pub project_name: String,
+    pub recipient_username: String,
+    /// First day of the month the statement covers.
+    pub month: NaiveDate,
+    /// Revenue of the project across all of its team members.
+    pub gross_amount: Decimal,
+    /// Revenue paid out to the recipient.
+    pub net_amount: Decimal,
+    /// Payouts splits the recipient had during the month, with the time
+    /// they took effect.
+    pub payouts_splits: Vec<(DateTime<Utc>, Decimal)>,
+    pub currency_code: String,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum GeneratedPdfType {
     PaymentStatement,
+    RevenueStatement,
 }
 
 impl GeneratedPdfType {
     pub fn as_str(self) -> &'static str {
         match self {
             GeneratedPdfType::PaymentStatement => "payment-statement",
+            GeneratedPdfType::RevenueStatement => "revenue-statement",
         }
     }
 }
@@ -60,6 +99,7 @@ impl FromStr for GeneratedPdfType {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "payment-statement" => Ok(GeneratedPdfType::PaymentStatement),
+            "revenue-statement" => Ok(GeneratedPdfType::RevenueStatement),
             _ => Err(s.to_owned()),
         }
     }
@@ -104,37 +144,14 @@ impl GotenbergClient {
         &self,
         statement: &PaymentStatement,
     ) -> Result<(), ApiError> {
-        let template_url = format!(
-            "{}/_internal/templates/doc/payment-statement",
-            self.site_url
-        );
-
-        let template_html = {
-            let resp = self
-                .client
-                .get(template_url)
-                .send()
-                .await
-                .wrap_internal_err(
-                    "failed to request payment statement template",
-                )?;
-            let resp = resp.error_for_status().wrap_internal_err(
-                "failed to fetch payment statement template (bad status)",
-            )?;
-            resp.text().await.wrap_internal_err(
-                "failed to read payment statement template body",
-            )?
-        };
-
-        let filled_html = fill_statement_template(&template_html, statement);
-
-        let form = reqwest::multipart::Form::new().part(
-            "files",
-            reqwest::multipart::Part::text(filled_html)
-                .file_name("index.html")
-                .mime_str("text/html")
-                .wrap_internal_err("invalid mime type for html part")?,
+        let template_html = self
+            .fetch_template(GeneratedPdfType::PaymentStatement)
+            .await?;
+        let filled_html = fill_template(
+            &template_html,
+            &payment_statement_variables(statement),
         );
+        let form = html_form(filled_html)?;
 
         let success_webhook = format!("{}/success", self.callback_base);
         let error_webhook = format!("{}/error", self.callback_base);
@@ -195,17 +212,136 @@ impl GotenbergClient {
             .await
             .wrap_api_err("executing `generate_payment_statement`")?;
 
+        self.wait_for_document(&payment_statement_key(
+            &self.redis,
+            &statement.payment_id,
+        ))
+        .await
+    }
+
+    /// Generate a PDF revenue statement via Gotenberg, tagging the request
+    /// with `statement_id` so the response can be routed back to us.
+    ///
+    /// This works like [`GotenbergClient::generate_payment_statement`], using
+    /// the `revenue-statement` template instead.
+    pub async fn generate_revenue_statement(
+        &self,
+        statement_id: &str,
+        statement: &RevenueStatement,
+    ) -> Result<(), ApiError> {
+        let template_html = self
+            .fetch_template(GeneratedPdfType::RevenueStatement)
+            .await?;
+        let filled_html = fill_template(
+            &template_html,
+            &revenue_statement_variables(statement),
+        );
+        let form = html_form(filled_html)?;
+
+        let success_webhook = format!("{}/success", self.callback_base);
+        let error_webhook = format!("{}/error", self.callback_base);
+
+        self
+            .client
+            .post(format!(
+                "{}/forms/chromium/convert/html",
+                self.gotenberg_url
+            ))
+            .header("Gotenberg-Webhook-Url", success_webhook)
+            .header("Gotenberg-Webhook-Error-Url", error_webhook)
+            .header(
+                "Gotenberg-Webhook-Extra-Http-Headers",
+                serde_json::json!({
+					"Modrinth-Revenue-Statement-Id": statement_id,
+					"Modrinth-Generated-Pdf-Type": GeneratedPdfType::RevenueStatement.as_str(),
+				}).to_string(),
+            )
+            .header(
+                "Gotenberg-Output-Filename",
+                format!(
+                    "revenue-statement-{}-{}",
+                    statement.project_id,
+                    statement.month.format("%Y-%m")
+                ),
+            )
+            .multipart(form)
+            .send()
+            .await
+            .wrap_internal_err("failed to submit HTML to Gotenberg")?
+            .error_for_status()
+            .wrap_internal_err("received an error status from Gotenberg")?;
+
+        Ok(())
+    }
+
+    /// Tells Gotenberg to generate a revenue statement PDF, and waits until we
+    /// get a response for that PDF.
+    ///
+    /// See [`GotenbergClient::wait_for_payment_statement`].
+    pub async fn wait_for_revenue_statement(
+        &self,
+        statement: &RevenueStatement,
+    ) -> Result<GotenbergDocument, ApiError> {
+        // Unlike payments, revenue statements aren't stored anywhere, so each
+        // request gets its own ID
+        let statement_id = to_base62(random_base62(11));
+
+        self.generate_revenue_statement(&statement_id, statement)
+            .await
+            .wrap_api_err("executing `generate_revenue_statement`")?;
+
+        self.wait_for_document(&revenue_statement_key(
+            &self.redis,
+            &statement_id,
+        ))
+        .await
+    }
+
+    /// Fetches the HTML template for a document type from the frontend.
+    async fn fetch_template(
+        &self,
+        r#type: GeneratedPdfType,
+    ) -> Result<String, ApiError> {
+        let template_url = format!(
+            "{}/_internal/templates/doc/{}",
+            self.site_url,
+            r#type.as_str()
+        );
+
+        let resp = self
+            .client
+            .get(template_url)
+            .send()
+            .await
+            .wrap_internal_err_with(|| {
+                eyre::eyre!("failed to request {} template", r#type.as_str())
+            })?;
+        let resp = resp.error_for_status().wrap_internal_err_with(|| {
+            eyre::eyre!(
+                "failed to fetch {} template (bad status)",
+                r#type.as_str()
+            )
+        })?;
+        resp.text().await.wrap_internal_err_with(|| {
+            eyre::eyre!("failed to read {} template body", r#type.as_str())
+        })
+    }
+
+    /// Waits until a Gotenberg response for a document is sent to
+    /// `response_key` over Redis.
+    async fn wait_for_document(
+        &self,
+        response_key: &str,
+    ) -> Result<GotenbergDocument, ApiError> {
         let timeout_ms = ENV.GOTENBERG_TIMEOUT;
         let redis_timeout_ms =
             timeout_ms.saturating_sub(REDIS_TIMEOUT_MARGIN_MS).max(1);
         let client_timeout_ms = timeout_ms.max(redis_timeout_ms + 1);
-        let response_key =
-            payment_statement_key(&self.redis, &statement.payment_id);
 
         let [_key, document] = tokio::time::timeout(
             Duration::from_millis(client_timeout_ms),
             self.redis
-                .brpop(&response_key, Duration::from_millis(redis_timeout_ms)),
+                .brpop(response_key, Duration::from_millis(redis_timeout_ms)),
         )
         .await
         .wrap_internal_err("timed out generating Gotenberg document")?
@@ -222,8 +358,20 @@ impl GotenbergClient {
     }
 }
 
-fn fill_statement_template(html: &str, s: &PaymentStatement) -> String {
-    let variables: Vec<(&str, String)> = vec![
+fn html_form(html: String) -> Result<reqwest::multipart::Form, ApiError> {
+    Ok(reqwest::multipart::Form::new().part(
+        "files",
+        reqwest::multipart::Part::text(html)
+            .file_name("index.html")
+            .mime_str("text/html")
+            .wrap_internal_err("invalid mime type for html part")?,
+    ))
+}
+
+fn payment_statement_variables(
+    s: &PaymentStatement,
+) -> Vec<(&'static str, String)> {
+    vec![
         ("statement.payment_id", s.payment_id.to_string()),
         (
             "statement.recipient_address_line_1",
@@ -259,8 +407,53 @@ fn fill_statement_template(html: &str, s: &PaymentStatement) -> String {
             "statement.fees",
             format_money(s.fees_cents, &s.currency_code),
         ),
-    ];
+    ]
+}
+
+fn revenue_statement_variables(
+    s: &RevenueStatement,
+) -> Vec<(&'static str, String)> {
+    let payouts_splits = s
+        .payouts_splits
+        .iter()
+        .map(|(effective_from, split)| {
+            format!(
+                "{} from {}",
+                split.normalize(),
+                effective_from.format("%Y-%m-%d %H:%M UTC")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let share = if s.gross_amount > Decimal::ZERO {
+        (s.net_amount / s.gross_amount * Decimal::from(100))
+            .round_dp(2)
+            .normalize()
+    } else {
+        Decimal::ZERO
+    };
+
+    vec![
+        ("statement.project_id", s.project_id.to_string()),
+        ("statement.project_name", s.project_name.clone()),
+        ("statement.recipient_username", s.recipient_username.clone()),
+        ("statement.month", s.month.format("%B %Y").to_string()),
+        (
+            "statement.gross_amount",
+            format_money(decimal_to_cents(s.gross_amount), &s.currency_code),
+        ),
+        (
+            "statement.net_amount",
+            format_money(decimal_to_cents(s.net_amount), &s.currency_code),
+        ),
+        ("statement.share", format!("{share}%")),
+        ("statement.payouts_splits", payouts_splits),
+    ]
+}
 
+/// Replaces `{key}` placeholders in the template with their values, escaped
+/// as HTML since they include user-controlled text like project names.
+fn fill_template(html: &str, variables: &[(&str, String)]) -> String {
     let mut out = String::with_capacity(html.len());
     let mut remaining = html;
     while let Some((before, rest)) = remaining.split_once('{') {
@@ -268,7 +461,7 @@ fn fill_statement_template(html: &str, s: &PaymentStatement) -> String {
         if let Some((key, after)) = rest.split_once('}') {
             let key = key.trim();
             if let Some((_, val)) = variables.iter().find(|(k, _)| *k == key) {
-                out.push_str(val);
+                out.push_str(&escape(val));
             }
             // if key not found, insert empty string
             remaining = after;
@@ -283,6 +476,13 @@ fn fill_statement_template(html: &str, s: &PaymentStatement) -> String {
     out
 }
 
+fn decimal_to_cents(amount: Decimal) -> i64 {
+    (amount * Decimal::from(100))
+        .round()
+        .to_i64()
+        .unwrap_or_default()
+}
+
 fn format_money(amount_cents: i64, currency: &str) -> String {
     rusty_money::Money::from_minor(
         amount_cents,
@@ -290,3 +490,25 @@ fn format_money(amount_cents: i64, currency: &str) -> String {
     )
     .to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_template_escapes_values() {
+        let html = fill_template(
+            "<h1>{ statement.project_name }</h1><p>{missing}</p>",
+            &[(
+                "statement.project_name",
+                "<script>alert('hi')</script> & \"more\"".to_string(),
+            )],
+        );
+
+        assert_eq!(
+            html,
+            "<h1>&lt;script&gt;alert(&apos;hi&apos;)&lt;/script&gt; &amp; \
+             &quot;more&quot;</h1><p></p>"
+        );
+    }
+}
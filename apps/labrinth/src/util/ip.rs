@@ -1,4 +1,4 @@
-use std::net::{AddrParseError, IpAddr, Ipv6Addr};
+use std::net::{AddrParseError, IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 
 pub fn convert_to_ip_v6(src: &str) -> Result<Ipv6Addr, AddrParseError> {
     let ip_addr: IpAddr = src.parse()?;
@@ -23,3 +23,196 @@ pub fn strip_ip(ip: Ipv6Addr) -> u64 {
         ])
     }
 }
+
+/// Whether `ip` is a publicly routable address, as opposed to a loopback,
+/// private, link-local, shared (CGNAT), unspecified, multicast or otherwise
+/// reserved one. Outgoing requests to user-supplied hosts must only go to
+/// public addresses, so they can't reach services inside our network.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip.to_canonical() {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                // 0.0.0.0/8, "this network"
+                || a == 0
+                // 100.64.0.0/10, shared address space (CGNAT)
+                || (a == 100 && (b & 0xc0) == 64)
+                // 192.0.0.0/24, IETF protocol assignments
+                || (a == 192 && b == 0 && c == 0)
+                // 198.18.0.0/15, benchmarking
+                || (a == 198 && (b & 0xfe) == 18)
+                // 240.0.0.0/4, reserved
+                || a >= 240)
+        }
+        IpAddr::V6(ip) => {
+            let segments = ip.segments();
+            // Prefixes which embed an IPv4 address in the last 32 bits
+            // (64:ff9b::/96 NAT64) or the second and third segments
+            // (2002::/16 6to4)
+            if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+                let [.., hi, lo] = segments;
+                return is_public_ip(IpAddr::V4(Ipv4Addr::from(
+                    (u32::from(hi) << 16) | u32::from(lo),
+                )));
+            }
+            if segments[0] == 0x2002 {
+                return is_public_ip(IpAddr::V4(Ipv4Addr::from(
+                    (u32::from(segments[1]) << 16) | u32::from(segments[2]),
+                )));
+            }
+
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // fc00::/7, unique local
+                || (segments[0] & 0xfe00) == 0xfc00
+                // fe80::/10, link-local
+                || (segments[0] & 0xffc0) == 0xfe80
+                // 2001:db8::/32, documentation
+                || (segments[0] == 0x2001 && segments[1] == 0xdb8)
+                // ::/96, deprecated IPv4-compatible addresses
+                || segments[..6] == [0; 6])
+        }
+    }
+}
+
+/// A DNS resolver for [`reqwest`] clients which fails to resolve hosts with
+/// any address that isn't [public](is_public_ip).
+///
+/// reqwest connects to exactly the addresses the resolver returns, so a host
+/// can't resolve to a public address when checked and a private one when
+/// connected to. IP literals in URLs don't go through the resolver and have
+/// to be checked separately.
+pub struct PublicIpResolver;
+
+impl reqwest::dns::Resolve for PublicIpResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(resolve_public(name))
+    }
+}
+
+async fn resolve_public(
+    name: reqwest::dns::Name,
+) -> Result<reqwest::dns::Addrs, Box<dyn std::error::Error + Send + Sync>> {
+    let addrs = tokio::net::lookup_host((name.as_str(), 0))
+        .await?
+        .collect::<Vec<SocketAddr>>();
+    if addrs.is_empty() {
+        return Err(format!("{} did not resolve", name.as_str()).into());
+    }
+    if addrs.iter().any(|addr| !is_public_ip(addr.ip())) {
+        return Err(format!(
+            "{} resolves to a non-public address",
+            name.as_str()
+        )
+        .into());
+    }
+
+    Ok(Box::new(addrs.into_iter()))
+}
+
+/// Checks that `url`'s host isn't a non-public IP literal or `localhost`.
+/// Hosts given as names are checked when they're resolved, by
+/// [`PublicIpResolver`].
+pub fn check_public_host(url: &url::Url) -> Result<(), String> {
+    match url.host() {
+        Some(url::Host::Ipv4(ip)) if !is_public_ip(IpAddr::V4(ip)) => {
+            Err(format!("{ip} is not a public address"))
+        }
+        Some(url::Host::Ipv6(ip)) if !is_public_ip(IpAddr::V6(ip)) => {
+            Err(format!("{ip} is not a public address"))
+        }
+        Some(url::Host::Domain(domain))
+            if domain.eq_ignore_ascii_case("localhost")
+                || domain.to_ascii_lowercase().ends_with(".localhost") =>
+        {
+            Err(format!("{domain} is not a public host"))
+        }
+        Some(_) => Ok(()),
+        None => Err("URL has no host".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_public_ip() {
+        for ip in ["1.1.1.1", "93.184.216.34", "2606:4700:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.0.0.1",
+            "172.16.5.4",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "100.127.255.254",
+            "0.0.0.0",
+            "0.1.2.3",
+            "224.0.0.1",
+            "255.255.255.255",
+            "198.18.0.1",
+            "240.0.0.1",
+            "::",
+            "::1",
+            "fe80::1",
+            "fd00:ec2::254",
+            "ff02::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+            "64:ff9b::a9fe:a9fe",
+            "2002:a00:1::",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn test_check_public_host() {
+        for url in [
+            "https://example.com/hook",
+            "https://1.1.1.1/hook",
+            "https://[2606:4700:4700::1111]/hook",
+        ] {
+            assert!(
+                check_public_host(&url::Url::parse(url).unwrap()).is_ok(),
+                "{url}"
+            );
+        }
+        for url in [
+            "https://169.254.169.254/latest/meta-data",
+            "https://127.0.0.1:8000/",
+            "https://10.0.0.1/",
+            "https://[::1]/",
+            "https://[::ffff:192.168.0.1]/",
+            "https://localhost:9000/",
+            "https://api.LOCALHOST/",
+            // Parsed as the IPv4 address 127.0.0.1
+            "https://0x7f000001/",
+            "https://2130706433/",
+        ] {
+            assert!(
+                check_public_host(&url::Url::parse(url).unwrap()).is_err(),
+                "{url}"
+            );
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_public_ip_resolver_rejects_loopback() {
+        use reqwest::dns::Resolve;
+
+        let result =
+            PublicIpResolver.resolve("localhost".parse().unwrap()).await;
+        assert!(result.is_err());
+    }
+}
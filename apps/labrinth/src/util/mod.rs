@@ -15,6 +15,7 @@ pub mod img;
 pub mod ip;
 pub mod kafka;
 pub mod neverbounce;
+pub mod openpgp;
 pub mod ratelimit;
 pub mod routes;
 pub mod sentry;
@@ -0,0 +1,157 @@
+//! Verification of detached OpenPGP signatures uploaded alongside version
+//! files.
+
+use std::io::Cursor;
+
+use eyre::Result;
+use pgp::types::PublicKeyTrait;
+use pgp::{Deserializable, SignedPublicKey, StandaloneSignature};
+
+use crate::database::models::pgp_key_item::DBPgpKey;
+use crate::models::projects::{FileSignature, FileSignatureStatus};
+use crate::util::error::Context;
+
+/// File extensions which detached signatures are uploaded with.
+pub const SIGNATURE_EXTENSIONS: [&str; 3] = ["asc", "gpg", "sig"];
+
+/// Parses an ASCII-armored public key and checks its self-signatures,
+/// returning its fingerprint in uppercase hex.
+pub fn parse_public_key(armored: &str) -> Result<String> {
+    let (key, _) = SignedPublicKey::from_string(armored)
+        .wrap_err("invalid OpenPGP public key")?;
+    key.verify()
+        .wrap_err("OpenPGP public key has invalid self-signatures")?;
+    Ok(hex::encode_upper(key.fingerprint().as_bytes()))
+}
+
+/// Parses a detached signature, either ASCII-armored or binary.
+pub fn parse_signature(data: &[u8]) -> Result<StandaloneSignature> {
+    if data.trim_ascii_start().starts_with(b"-----BEGIN") {
+        StandaloneSignature::from_armor_single(Cursor::new(data))
+            .map(|(signature, _)| signature)
+    } else {
+        StandaloneSignature::from_bytes(Cursor::new(data))
+    }
+    .wrap_err("invalid OpenPGP signature")
+}
+
+/// Verifies a detached `signature` over `data` against the registered
+/// `keys`, including their subkeys. Signatures which aren't OpenPGP ones
+/// are recorded as invalid.
+pub fn verify_signature(
+    signature: &[u8],
+    data: &[u8],
+    keys: &[DBPgpKey],
+) -> FileSignature {
+    let Ok(signature) = parse_signature(signature) else {
+        return FileSignature {
+            status: FileSignatureStatus::Invalid,
+            key_fingerprint: None,
+        };
+    };
+
+    let key_fingerprint = keys.iter().find_map(|key| {
+        let (public_key, _) =
+            SignedPublicKey::from_string(&key.public_key).ok()?;
+        let verified = signature.verify(&public_key, data).is_ok()
+            || public_key
+                .public_subkeys
+                .iter()
+                .any(|subkey| signature.verify(subkey, data).is_ok());
+        verified.then(|| key.fingerprint.clone())
+    });
+
+    FileSignature {
+        status: if key_fingerprint.is_some() {
+            FileSignatureStatus::Verified
+        } else {
+            FileSignatureStatus::Mismatched
+        },
+        key_fingerprint,
+    }
+}
+
+/// Gets the name of the file a detached signature signs, i.e. `mod.jar` for
+/// `mod.jar.asc`, if the signature is named after one.
+pub fn signed_file_name(signature_file_name: &str) -> Option<&str> {
+    let (name, extension) = signature_file_name.rsplit_once('.')?;
+    (SIGNATURE_EXTENSIONS.contains(&extension) && name.contains('.'))
+        .then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    const TEST_KEY: &str = include_str!("../../tests/files/pgp/test-key.asc");
+    const TEST_KEY_FINGERPRINT: &str =
+        "720043F601306E9A5A2B10AF23170B65379FDD7C";
+    const OTHER_KEY: &str = include_str!("../../tests/files/pgp/other-key.asc");
+    const SIGNED_FILE: &[u8] =
+        include_bytes!("../../tests/files/basic-mod.jar");
+    const ARMORED_SIGNATURE: &[u8] =
+        include_bytes!("../../tests/files/pgp/basic-mod.jar.asc");
+    const BINARY_SIGNATURE: &[u8] =
+        include_bytes!("../../tests/files/pgp/basic-mod.jar.sig");
+
+    fn key(public_key: &str) -> DBPgpKey {
+        DBPgpKey {
+            user_id: None,
+            organization_id: None,
+            fingerprint: parse_public_key(public_key).unwrap(),
+            public_key: public_key.to_string(),
+            created: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_parse_public_key() {
+        assert_eq!(parse_public_key(TEST_KEY).unwrap(), TEST_KEY_FINGERPRINT);
+        assert!(parse_public_key("not a key").is_err());
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let keys = [key(OTHER_KEY), key(TEST_KEY)];
+        for signature in [ARMORED_SIGNATURE, BINARY_SIGNATURE] {
+            let result = verify_signature(signature, SIGNED_FILE, &keys);
+            assert_eq!(result.status, FileSignatureStatus::Verified);
+            assert_eq!(
+                result.key_fingerprint.as_deref(),
+                Some(TEST_KEY_FINGERPRINT)
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_signature_mismatched() {
+        let result =
+            verify_signature(ARMORED_SIGNATURE, SIGNED_FILE, &[key(OTHER_KEY)]);
+        assert_eq!(result.status, FileSignatureStatus::Mismatched);
+        assert_eq!(result.key_fingerprint, None);
+
+        let result = verify_signature(
+            ARMORED_SIGNATURE,
+            b"some other file",
+            &[key(TEST_KEY)],
+        );
+        assert_eq!(result.status, FileSignatureStatus::Mismatched);
+    }
+
+    #[test]
+    fn test_verify_signature_invalid() {
+        let result =
+            verify_signature(b"not a signature", SIGNED_FILE, &[key(TEST_KEY)]);
+        assert_eq!(result.status, FileSignatureStatus::Invalid);
+        assert_eq!(result.key_fingerprint, None);
+    }
+
+    #[test]
+    fn test_signed_file_name() {
+        assert_eq!(signed_file_name("mod.jar.asc"), Some("mod.jar"));
+        assert_eq!(signed_file_name("mod-1.0.jar.sig"), Some("mod-1.0.jar"));
+        assert_eq!(signed_file_name("signature.asc"), None);
+        assert_eq!(signed_file_name("mod.jar"), None);
+    }
+}
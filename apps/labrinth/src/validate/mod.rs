@@ -2,8 +2,10 @@ use crate::database::PgTransaction;
 use crate::database::models::DatabaseError;
 use crate::database::models::legacy_loader_fields::MinecraftGameVersion;
 use crate::database::models::loader_fields::VersionField;
+use crate::database::models::pgp_key_item::DBPgpKey;
 use crate::models::pack::PackFormat;
-use crate::models::projects::{FileType, Loader};
+use crate::models::projects::{FileSignature, FileType, Loader};
+use crate::util::openpgp;
 use crate::validate::datapack::DataPackValidator;
 use crate::validate::fabric::FabricValidator;
 use crate::validate::forge::{ForgeValidator, LegacyForgeValidator};
@@ -209,6 +211,10 @@ async fn validate_minecraft_file(
     file_type: Option<FileType>,
 ) -> Result<ValidationResult, ValidationError> {
     actix_web::web::block(move || {
+        if file_type == Some(FileType::Signature) {
+            return validate_signature(&file_extension);
+        }
+
         let mut zip = match ZipArchive::new(Cursor::new(Bytes::clone(&data))) {
             Ok(zip) => MaybeProtectedZipFile::Unprotected(zip),
             Err(read_error) => MaybeProtectedZipFile::MaybeProtected {
@@ -222,17 +228,8 @@ async fn validate_minecraft_file(
                 FileType::RequiredResourcePack | FileType::OptionalResourcePack => {
                     return PackValidator.validate_maybe_protected_zip(&mut zip);
                 }
-                FileType::Signature => {
-                    // Not sure if we have a better way to detect if a file is a signature
-                    // should look into this?
-                    return if ["asc", "gpg", "sig"].contains(&file_extension.as_str()) {
-                        Ok(ValidationResult::Pass)
-                    } else {
-                        Err(ValidationError::InvalidInput(
-                            format!("File extension {file_extension} is invalid for input file").into(),
-                        ))
-                    };
-                }
+                // Handled above, as signatures aren't archives
+                FileType::Signature => {}
                 FileType::DevJar | FileType::SourcesJar | FileType::JavadocJar => {},
                 FileType::Unknown => {}
             }
@@ -292,6 +289,37 @@ async fn validate_minecraft_file(
     .await?
 }
 
+/// Checks that a file uploaded as a signature has a signature's extension.
+/// It's verified against the file it signs once the whole version has been
+/// uploaded, which records whether it's a valid OpenPGP signature.
+fn validate_signature(
+    file_extension: &str,
+) -> Result<ValidationResult, ValidationError> {
+    if !openpgp::SIGNATURE_EXTENSIONS.contains(&file_extension) {
+        return Err(ValidationError::InvalidInput(
+            format!(
+                "File extension {file_extension} is invalid for input file"
+            )
+            .into(),
+        ));
+    }
+
+    Ok(ValidationResult::Pass)
+}
+
+/// Verifies a detached signature over `data` against the keys registered
+/// to a project's owners.
+pub async fn verify_file_signature(
+    signature: Bytes,
+    data: Bytes,
+    keys: Vec<DBPgpKey>,
+) -> Result<FileSignature, ValidationError> {
+    Ok(actix_web::web::block(move || {
+        openpgp::verify_signature(&signature, &data, &keys)
+    })
+    .await?)
+}
+
 // Write tests for this
 fn game_version_supported(
     game_versions: &[MinecraftGameVersion],
@@ -0,0 +1,122 @@
+use actix_http::StatusCode;
+use actix_web::test;
+use common::api_common::{Api, ApiProject, ApiVersion, AppendsOptionalPat};
+use common::api_v3::ApiV3;
+use common::database::USER_USER_PAT;
+use common::environment::{TestEnvironment, with_test_environment};
+use serde_json::{Value, json};
+
+pub mod common;
+
+async fn get_project_audit_log(api: &ApiV3, project_id: &str) -> Vec<Value> {
+    let resp = api
+        .call(
+            test::TestRequest::get()
+                .uri(&format!("/v3/project/{project_id}/audit_log"))
+                .append_pat(USER_USER_PAT)
+                .to_request(),
+        )
+        .await;
+    assert_status!(&resp, StatusCode::OK);
+    test::read_body_json(resp).await
+}
+
+#[actix_rt::test]
+pub async fn project_edit_logs_changed_fields() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let project_id = &test_env.dummy.project_alpha.project_id;
+
+            let resp = api
+                .edit_project(
+                    project_id,
+                    json!({
+                        "name": "Edited name",
+                        "summary": "Edited summary",
+                        "license_url": "https://example.com/license",
+                    }),
+                    USER_USER_PAT,
+                )
+                .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+
+            let audit_log = get_project_audit_log(api, project_id).await;
+            let entry = audit_log
+                .iter()
+                .find(|entry| entry["action"] == "project_edited")
+                .expect("project edit should be logged");
+
+            assert_eq!(entry["target"]["type"], "project");
+            assert_eq!(entry["target"]["project_id"], project_id.as_str());
+            assert_eq!(entry["after"]["name"], "Edited name");
+            assert_eq!(entry["after"]["summary"], "Edited summary");
+            assert_eq!(
+                entry["after"]["license"]["url"],
+                "https://example.com/license"
+            );
+            assert_ne!(entry["before"]["name"], "Edited name");
+            assert_ne!(entry["before"]["summary"], "Edited summary");
+            // Only changed fields are recorded
+            assert!(entry["before"].get("slug").is_none());
+            assert!(entry["after"].get("slug").is_none());
+        },
+    )
+    .await;
+}
+
+#[actix_rt::test]
+pub async fn version_edit_logs_changed_fields() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let project_id = &test_env.dummy.project_alpha.project_id;
+            let version_id = &test_env.dummy.project_alpha.version_id;
+
+            let resp = api
+                .edit_version(
+                    version_id,
+                    json!({
+                        "name": "Edited version",
+                        "version_number": "9.9.9",
+                    }),
+                    USER_USER_PAT,
+                )
+                .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+
+            let audit_log = get_project_audit_log(api, project_id).await;
+            let entry = audit_log
+                .iter()
+                .find(|entry| entry["action"] == "version_edited")
+                .expect("version edit should be logged");
+
+            assert_eq!(entry["project_id"], project_id.as_str());
+            assert_eq!(entry["target"]["type"], "version");
+            assert_eq!(entry["target"]["version_id"], version_id.as_str());
+            assert_eq!(entry["after"]["name"], "Edited version");
+            assert_eq!(entry["after"]["version_number"], "9.9.9");
+            assert_ne!(entry["before"]["version_number"], "9.9.9");
+            assert!(entry["before"].get("changelog").is_none());
+
+            // Nothing is logged for an edit which changes nothing
+            let resp = api
+                .edit_version(
+                    version_id,
+                    json!({ "version_number": "9.9.9" }),
+                    USER_USER_PAT,
+                )
+                .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+            let version_edits = get_project_audit_log(api, project_id)
+                .await
+                .into_iter()
+                .filter(|entry| entry["action"] == "version_edited")
+                .count();
+            assert_eq!(version_edits, 1);
+        },
+    )
+    .await;
+}
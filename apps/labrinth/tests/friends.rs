@@ -0,0 +1,295 @@
+use actix_http::StatusCode;
+use actix_http::error::PayloadError;
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::test;
+use ariadne::ids::UserId;
+use ariadne::ids::base62_impl::parse_base62;
+use ariadne::networking::message::{
+    ClientToServerMessage, ServerToClientMessage, WorldInvite,
+};
+use bytes::Bytes;
+use common::api_common::{Api, AppendsOptionalPat};
+use common::api_v3::ApiV3;
+use common::database::{
+    FRIEND_USER_ID, FRIEND_USER_PAT, USER_USER_ID, USER_USER_PAT,
+};
+use common::environment::{TestEnvironment, with_test_environment};
+use either::Either;
+use std::pin::Pin;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use uuid::Uuid;
+
+pub mod common;
+
+/// A launcher connected to the friends socket.
+struct Launcher {
+    sender: mpsc::UnboundedSender<Result<Bytes, PayloadError>>,
+    body: BoxBody,
+    buffer: Vec<u8>,
+}
+
+impl Launcher {
+    async fn connect(api: &ApiV3, pat: Option<&str>) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let request = test::TestRequest::get()
+            .uri(&format!("/_internal/launcher_socket?code={}", pat.unwrap()))
+            .insert_header(("Connection", "Upgrade"))
+            .insert_header(("Upgrade", "websocket"))
+            .insert_header(("Sec-WebSocket-Version", "13"))
+            .insert_header(("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ=="))
+            .to_request();
+        let (request, _) = request.replace_payload(
+            (Box::pin(UnboundedReceiverStream::new(receiver))
+                as actix_http::BoxedPayloadStream)
+                .into(),
+        );
+
+        let resp = api.call(request).await;
+        assert_status!(&resp, StatusCode::SWITCHING_PROTOCOLS);
+
+        Self {
+            sender,
+            body: resp.into_body(),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Sends a message as a masked client frame. The mask is all zeroes, so
+    /// the payload is left as is.
+    fn send(&self, message: ClientToServerMessage) {
+        let (opcode, payload) = match message.serialize().unwrap() {
+            Either::Left(text) => (0x1, text.into_bytes()),
+            Either::Right(bytes) => (0x2, bytes),
+        };
+
+        let mut frame = vec![0x80 | opcode];
+        match payload.len() {
+            len @ ..126 => frame.push(0x80 | len as u8),
+            len @ ..65536 => {
+                frame.push(0x80 | 126);
+                frame.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+            len => {
+                frame.push(0x80 | 127);
+                frame.extend_from_slice(&(len as u64).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(&[0; 4]);
+        frame.extend_from_slice(&payload);
+
+        self.sender.send(Ok(frame.into())).unwrap();
+    }
+
+    /// Reads the next unmasked server frame, returning its opcode and
+    /// payload.
+    async fn next_frame(&mut self) -> (u8, Vec<u8>) {
+        loop {
+            if let Some(header) = self.buffer.get(..2) {
+                let opcode = header[0] & 0x0f;
+                let (len, offset): (usize, usize) = match header[1] & 0x7f {
+                    126 if self.buffer.len() >= 4 => (
+                        u16::from_be_bytes([self.buffer[2], self.buffer[3]])
+                            as usize,
+                        4,
+                    ),
+                    127 if self.buffer.len() >= 10 => (
+                        u64::from_be_bytes(
+                            self.buffer[2..10].try_into().unwrap(),
+                        ) as usize,
+                        10,
+                    ),
+                    126 | 127 => (usize::MAX, 0),
+                    len => (len as usize, 2),
+                };
+                if self.buffer.len() >= offset.saturating_add(len) {
+                    let payload = self.buffer[offset..offset + len].to_vec();
+                    self.buffer.drain(..offset + len);
+                    return (opcode, payload);
+                }
+            }
+
+            let chunk = std::future::poll_fn(|cx| {
+                Pin::new(&mut self.body).poll_next(cx)
+            })
+            .await
+            .expect("socket was closed")
+            .unwrap();
+            self.buffer.extend_from_slice(&chunk);
+        }
+    }
+
+    /// Reads messages until `f` returns `Some`, skipping the rest.
+    async fn receive<T>(
+        &mut self,
+        f: impl Fn(ServerToClientMessage) -> Option<T>,
+    ) -> T {
+        tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let (opcode, payload) = self.next_frame().await;
+                let message = match opcode {
+                    0x1 => ServerToClientMessage::deserialize(Either::Left(
+                        std::str::from_utf8(&payload).unwrap(),
+                    )),
+                    0x2 => ServerToClientMessage::deserialize(Either::Right(
+                        &payload,
+                    )),
+                    _ => continue,
+                };
+                if let Some(value) = message.ok().and_then(&f) {
+                    return value;
+                }
+            }
+        })
+        .await
+        .expect("the expected message was not received")
+    }
+}
+
+fn user_id(id: &str) -> UserId {
+    UserId(parse_base62(id).unwrap())
+}
+
+async fn befriend(api: &ApiV3) {
+    for (id, pat) in [
+        (FRIEND_USER_ID, USER_USER_PAT),
+        (USER_USER_ID, FRIEND_USER_PAT),
+    ] {
+        let resp = api
+            .call(
+                test::TestRequest::post()
+                    .uri(&format!("/v3/friend/{id}"))
+                    .append_pat(pat)
+                    .to_request(),
+            )
+            .await;
+        assert_status!(&resp, StatusCode::NO_CONTENT);
+    }
+}
+
+fn world_invite(socket: Uuid) -> WorldInvite {
+    WorldInvite {
+        socket,
+        world_name: "New World".to_string(),
+        game_version: "1.21.8".to_string(),
+        loader: "fabric".to_string(),
+        loader_version: Some("0.17.2".to_string()),
+        content_hash: "da39a3ee5e6b4b0d3255bfef95601890afd80709".to_string(),
+    }
+}
+
+#[actix_rt::test]
+pub async fn tunnel_connections_require_world_invites() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            befriend(api).await;
+
+            let mut host = Launcher::connect(api, USER_USER_PAT).await;
+            let mut guest = Launcher::connect(api, FRIEND_USER_PAT).await;
+
+            let listening_socket = Uuid::new_v4();
+            host.send(ClientToServerMessage::SocketListen {
+                socket: listening_socket,
+            });
+
+            // Being friends isn't enough to connect to the world
+            let uninvited_socket = Uuid::new_v4();
+            guest.send(ClientToServerMessage::SocketConnect {
+                user: user_id(USER_USER_ID),
+                to_socket: listening_socket,
+                socket: uninvited_socket,
+            });
+            guest
+                .receive(|message| match message {
+                    ServerToClientMessage::SocketClosed { socket }
+                        if socket == uninvited_socket =>
+                    {
+                        Some(())
+                    }
+                    _ => None,
+                })
+                .await;
+
+            host.send(ClientToServerMessage::WorldInvite {
+                to: user_id(FRIEND_USER_ID),
+                invite: world_invite(listening_socket),
+            });
+            let invite = guest
+                .receive(|message| match message {
+                    ServerToClientMessage::WorldInvite { from, invite } => {
+                        Some((from, invite))
+                    }
+                    _ => None,
+                })
+                .await;
+            assert_eq!(invite.0, user_id(USER_USER_ID));
+            assert_eq!(invite.1.socket, listening_socket);
+
+            let guest_socket = Uuid::new_v4();
+            guest.send(ClientToServerMessage::SocketConnect {
+                user: user_id(USER_USER_ID),
+                to_socket: listening_socket,
+                socket: guest_socket,
+            });
+            let host_socket = host
+                .receive(|message| match message {
+                    ServerToClientMessage::SocketConnected {
+                        to_socket,
+                        new_socket,
+                    } if to_socket == listening_socket => Some(new_socket),
+                    _ => None,
+                })
+                .await;
+
+            guest.send(ClientToServerMessage::SocketSend {
+                socket: guest_socket,
+                data: b"hello".to_vec(),
+            });
+            let data = host
+                .receive(|message| match message {
+                    ServerToClientMessage::SocketData { socket, data }
+                        if socket == host_socket =>
+                    {
+                        Some(data)
+                    }
+                    _ => None,
+                })
+                .await;
+            assert_eq!(data, b"hello");
+
+            host.send(ClientToServerMessage::SocketSend {
+                socket: host_socket,
+                data: b"welcome".to_vec(),
+            });
+            let data = guest
+                .receive(|message| match message {
+                    ServerToClientMessage::SocketData { socket, data }
+                        if socket == guest_socket =>
+                    {
+                        Some(data)
+                    }
+                    _ => None,
+                })
+                .await;
+            assert_eq!(data, b"welcome");
+
+            host.send(ClientToServerMessage::SocketClose {
+                socket: host_socket,
+            });
+            guest
+                .receive(|message| match message {
+                    ServerToClientMessage::SocketClosed { socket }
+                        if socket == guest_socket =>
+                    {
+                        Some(())
+                    }
+                    _ => None,
+                })
+                .await;
+        },
+    )
+    .await;
+}
@@ -0,0 +1,259 @@
+use actix_http::StatusCode;
+use actix_web::test;
+use common::api_common::{Api, ApiVersion, AppendsOptionalPat};
+use common::api_v3::ApiV3;
+use common::database::{FRIEND_USER_PAT, USER_USER_PAT};
+use common::dummy_data::TestFile;
+use common::environment::{TestEnvironment, with_test_environment};
+use serde_json::json;
+use sha2::Digest;
+
+pub mod common;
+
+async fn get_maven_file(
+    api: &ApiV3,
+    path: &str,
+    pat: Option<&str>,
+) -> (StatusCode, String) {
+    let resp = api
+        .call(
+            test::TestRequest::get()
+                .uri(&format!("/maven/maven/modrinth/{path}"))
+                .append_pat(pat)
+                .to_request(),
+        )
+        .await;
+    let status = resp.status();
+    let body = test::read_body(resp).await;
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[actix_rt::test]
+pub async fn pom_lists_visible_dependencies() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let alpha = &test_env.dummy.project_alpha;
+            let beta = &test_env.dummy.project_beta;
+
+            // A draft version of alpha, which is public, and the version of
+            // beta, which is private
+            let draft_version = api
+                .add_public_version_deserialized(
+                    alpha.project_id_parsed,
+                    "0.1.0",
+                    TestFile::build_random_jar(),
+                    None,
+                    None,
+                    USER_USER_PAT,
+                )
+                .await;
+            let draft_version_id = draft_version.id.to_string();
+            let resp = api
+                .edit_version(
+                    &draft_version_id,
+                    json!({ "status": "draft" }),
+                    USER_USER_PAT,
+                )
+                .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+
+            let resp = api
+                .edit_version(
+                    &alpha.version_id,
+                    json!({
+                        "dependencies": [
+                            {
+                                "version_id": draft_version_id,
+                                "dependency_type": "required",
+                            },
+                            {
+                                "version_id": beta.version_id,
+                                "dependency_type": "required",
+                            },
+                            {
+                                "project_id": beta.project_id,
+                                "dependency_type": "optional",
+                            },
+                        ]
+                    }),
+                    USER_USER_PAT,
+                )
+                .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+
+            let (status, pom) = get_maven_file(
+                api,
+                "alpha/1.2.3/alpha-1.2.3.pom",
+                USER_USER_PAT,
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+            assert!(pom.contains("<artifactId>alpha</artifactId>"));
+            assert!(pom.contains("<version>1.2.3</version>"));
+            assert!(pom.contains("<artifactId>beta</artifactId>"));
+            // Unlisted versions are referred to by their ID
+            assert!(
+                pom.contains(&format!("<version>{draft_version_id}</version>"))
+            );
+            assert!(
+                pom.contains(&format!(
+                    "<version>{}</version>",
+                    beta.version_id
+                ))
+            );
+            assert_eq!(pom.matches("<dependency>").count(), 2);
+
+            // Someone else can see neither the draft version nor beta
+            let (status, pom) = get_maven_file(
+                api,
+                "alpha/1.2.3/alpha-1.2.3.pom",
+                FRIEND_USER_PAT,
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+            assert!(!pom.contains("<dependency>"));
+
+            let (status, module) = get_maven_file(
+                api,
+                "alpha/1.2.3/alpha-1.2.3.module",
+                FRIEND_USER_PAT,
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+            let module: serde_json::Value =
+                serde_json::from_str(&module).unwrap();
+            assert_eq!(module["component"]["module"], "alpha");
+            assert!(module["variants"][0].get("dependencies").is_none());
+        },
+    )
+    .await;
+}
+
+#[actix_rt::test]
+pub async fn project_dependencies_resolve_to_a_stable_version() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let alpha = &test_env.dummy.project_alpha;
+            let beta = &test_env.dummy.project_beta;
+
+            api.add_public_version_deserialized(
+                beta.project_id_parsed,
+                "1.0.0",
+                TestFile::build_random_jar(),
+                None,
+                None,
+                USER_USER_PAT,
+            )
+            .await;
+
+            let resp = api
+                .edit_version(
+                    &alpha.version_id,
+                    json!({
+                        "dependencies": [
+                            {
+                                "project_id": beta.project_id,
+                                "dependency_type": "required",
+                            },
+                        ]
+                    }),
+                    USER_USER_PAT,
+                )
+                .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+
+            let (status, pom) = get_maven_file(
+                api,
+                "alpha/1.2.3/alpha-1.2.3.pom",
+                USER_USER_PAT,
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+            assert!(pom.contains("<artifactId>beta</artifactId>"));
+            assert!(pom.contains("<version>1.0.0</version>"));
+
+            // A newer release of beta doesn't change what alpha depends on
+            api.add_public_version_deserialized(
+                beta.project_id_parsed,
+                "2.0.0",
+                TestFile::build_random_jar(),
+                None,
+                None,
+                USER_USER_PAT,
+            )
+            .await;
+            let (status, new_pom) = get_maven_file(
+                api,
+                "alpha/1.2.3/alpha-1.2.3.pom",
+                USER_USER_PAT,
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(pom, new_pom);
+
+            // but versions of alpha published after it depend on it
+            api.add_public_version_deserialized(
+                alpha.project_id_parsed,
+                "1.3.0",
+                TestFile::build_random_jar(),
+                None,
+                Some(
+                    serde_json::from_value(json!([{
+                        "op": "add",
+                        "path": "/dependencies/0",
+                        "value": {
+                            "project_id": beta.project_id,
+                            "dependency_type": "required",
+                        },
+                    }]))
+                    .unwrap(),
+                ),
+                USER_USER_PAT,
+            )
+            .await;
+            let (status, pom) = get_maven_file(
+                api,
+                "alpha/1.3.0/alpha-1.3.0.pom",
+                USER_USER_PAT,
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+            assert!(pom.contains("<version>2.0.0</version>"));
+        },
+    )
+    .await;
+}
+
+#[actix_rt::test]
+pub async fn metadata_checksums_match_generated_files() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+
+            for file in ["alpha-1.2.3.pom", "alpha-1.2.3.module"] {
+                let path = format!("alpha/1.2.3/{file}");
+                let (status, body) = get_maven_file(api, &path, None).await;
+                assert_eq!(status, StatusCode::OK);
+
+                let (status, sha1) =
+                    get_maven_file(api, &format!("{path}.sha1"), None).await;
+                assert_eq!(status, StatusCode::OK);
+                assert_eq!(sha1, format!("{:x}", sha1::Sha1::digest(&body)));
+
+                let (status, sha512) =
+                    get_maven_file(api, &format!("{path}.sha512"), None).await;
+                assert_eq!(status, StatusCode::OK);
+                assert_eq!(
+                    sha512,
+                    format!("{:x}", sha2::Sha512::digest(&body))
+                );
+            }
+        },
+    )
+    .await;
+}
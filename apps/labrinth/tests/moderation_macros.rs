@@ -0,0 +1,325 @@
+use actix_http::StatusCode;
+use actix_web::dev::ServiceResponse;
+use actix_web::test;
+use common::api_common::{Api, ApiProject, AppendsOptionalPat};
+use common::api_v3::ApiV3;
+use common::database::{
+    ADMIN_USER_PAT, MOD_USER_PAT, USER_USER_ID, USER_USER_PAT,
+};
+use common::environment::{TestEnvironment, with_test_environment};
+use serde_json::{Value, json};
+
+pub mod common;
+
+async fn create_macro(
+    api: &ApiV3,
+    body: Value,
+    pat: Option<&str>,
+) -> ServiceResponse {
+    api.call(
+        test::TestRequest::post()
+            .uri("/_internal/moderation/macros")
+            .append_pat(pat)
+            .set_json(body)
+            .to_request(),
+    )
+    .await
+}
+
+async fn apply_macro(
+    api: &ApiV3,
+    id: &str,
+    body: Value,
+    pat: Option<&str>,
+) -> ServiceResponse {
+    api.call(
+        test::TestRequest::post()
+            .uri(&format!("/_internal/moderation/macros/{id}/apply"))
+            .append_pat(pat)
+            .set_json(body)
+            .to_request(),
+    )
+    .await
+}
+
+async fn list_macros(api: &ApiV3) -> Vec<Value> {
+    let resp = api
+        .call(
+            test::TestRequest::get()
+                .uri("/_internal/moderation/macros")
+                .append_pat(MOD_USER_PAT)
+                .to_request(),
+        )
+        .await;
+    assert_status!(&resp, StatusCode::OK);
+    test::read_body_json(resp).await
+}
+
+async fn project_status(api: &ApiV3, id: &str) -> String {
+    let resp = api.get_project(id, MOD_USER_PAT).await;
+    assert_status!(&resp, StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    body["status"].as_str().unwrap().to_string()
+}
+
+async fn thread_messages(api: &ApiV3, id: &str) -> Vec<Value> {
+    let resp = api.get_thread(id, MOD_USER_PAT).await;
+    assert_status!(&resp, StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    body["messages"].as_array().unwrap().clone()
+}
+
+async fn user_notes(api: &ApiV3) -> Value {
+    let resp = api
+        .call(
+            test::TestRequest::get()
+                .uri(&format!("/v3/user/{USER_USER_ID}"))
+                .append_pat(MOD_USER_PAT)
+                .to_request(),
+        )
+        .await;
+    assert_status!(&resp, StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    body["moderation_notes"].clone()
+}
+
+#[actix_rt::test]
+pub async fn moderation_macros_are_managed_by_admins() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let body = json!({
+                "name": "Missing license",
+                "message": "Please add a license, {reason}.",
+                "status": "withheld",
+            });
+
+            let resp = create_macro(api, body.clone(), MOD_USER_PAT).await;
+            assert_status!(&resp, StatusCode::UNAUTHORIZED);
+            let resp = create_macro(api, body.clone(), USER_USER_PAT).await;
+            assert_status!(&resp, StatusCode::UNAUTHORIZED);
+
+            let resp = create_macro(api, body.clone(), ADMIN_USER_PAT).await;
+            assert_status!(&resp, StatusCode::OK);
+            let created: Value = test::read_body_json(resp).await;
+            assert_eq!(created["name"], "Missing license");
+            assert_eq!(created["variables"], json!(["reason"]));
+            assert_eq!(created["uses"], 0);
+            let id = created["id"].as_str().unwrap();
+
+            let resp = create_macro(api, body, ADMIN_USER_PAT).await;
+            assert_status!(&resp, StatusCode::BAD_REQUEST);
+            for body in [
+                json!({ "name": "Unclosed", "message": "{reason" }),
+                json!({ "name": "Draft", "message": "Hi", "status": "draft" }),
+                json!({ "name": "", "message": "Hi" }),
+                json!({ "name": "Empty", "message": " " }),
+                json!({ "name": "Long", "message": "a".repeat(65537) }),
+            ] {
+                let resp = create_macro(api, body, ADMIN_USER_PAT).await;
+                assert_status!(&resp, StatusCode::BAD_REQUEST);
+            }
+
+            let edit = |body: Value, pat: Option<&'static str>| {
+                api.call(
+                    test::TestRequest::patch()
+                        .uri(&format!("/_internal/moderation/macros/{id}"))
+                        .append_pat(pat)
+                        .set_json(body)
+                        .to_request(),
+                )
+            };
+            let resp = edit(json!({ "name": "Renamed" }), MOD_USER_PAT).await;
+            assert_status!(&resp, StatusCode::UNAUTHORIZED);
+            let resp =
+                edit(json!({ "message": "{unclosed" }), ADMIN_USER_PAT).await;
+            assert_status!(&resp, StatusCode::BAD_REQUEST);
+            let resp = edit(
+                json!({
+                    "name": "Renamed",
+                    "note": "Asked for a license: {reason}",
+                    "status": null,
+                }),
+                ADMIN_USER_PAT,
+            )
+            .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+
+            let macros = list_macros(api).await;
+            assert_eq!(macros.len(), 1);
+            assert_eq!(macros[0]["name"], "Renamed");
+            assert_eq!(macros[0]["message"], "Please add a license, {reason}.");
+            assert_eq!(macros[0]["note"], "Asked for a license: {reason}");
+            assert!(macros[0]["status"].is_null());
+            assert_eq!(macros[0]["last_modified_by"], "1");
+
+            let resp = api
+                .call(
+                    test::TestRequest::delete()
+                        .uri(&format!("/_internal/moderation/macros/{id}"))
+                        .append_pat(ADMIN_USER_PAT)
+                        .to_request(),
+                )
+                .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+            let resp = edit(json!({ "name": "Gone" }), ADMIN_USER_PAT).await;
+            assert_status!(&resp, StatusCode::NOT_FOUND);
+            assert!(list_macros(api).await.is_empty());
+        },
+    )
+    .await;
+}
+
+#[actix_rt::test]
+pub async fn applying_macros_posts_message_sets_status_and_appends_note() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let alpha = &test_env.dummy.project_alpha;
+
+            let resp = create_macro(
+                api,
+                json!({
+                    "name": "Withhold",
+                    "message": "{project_name} was withheld: {reason}",
+                    "status": "withheld",
+                    "note": "Withheld {project_slug} for {reason}",
+                }),
+                ADMIN_USER_PAT,
+            )
+            .await;
+            assert_status!(&resp, StatusCode::OK);
+            let created: Value = test::read_body_json(resp).await;
+            let id = created["id"].as_str().unwrap();
+
+            let resp = apply_macro(
+                api,
+                id,
+                json!({
+                    "project_id": alpha.project_id,
+                    "variables": { "reason": "spam" },
+                }),
+                USER_USER_PAT,
+            )
+            .await;
+            assert_status!(&resp, StatusCode::UNAUTHORIZED);
+
+            for variables in [
+                json!({}),
+                json!({ "reason": "spam", "project_name": "Other" }),
+            ] {
+                let resp = apply_macro(
+                    api,
+                    id,
+                    json!({
+                        "project_id": alpha.project_id,
+                        "variables": variables,
+                    }),
+                    MOD_USER_PAT,
+                )
+                .await;
+                assert_status!(&resp, StatusCode::BAD_REQUEST);
+            }
+
+            let messages_before =
+                thread_messages(api, &alpha.thread_id).await.len();
+            let resp = apply_macro(
+                api,
+                id,
+                json!({
+                    "project_id": alpha.project_id,
+                    "variables": { "reason": "spam" },
+                }),
+                MOD_USER_PAT,
+            )
+            .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+
+            assert_eq!(
+                project_status(api, &alpha.project_id).await,
+                "withheld"
+            );
+
+            let messages = thread_messages(api, &alpha.thread_id).await;
+            assert_eq!(messages.len(), messages_before + 2);
+            assert!(messages.iter().any(|message| {
+                message["body"]["type"] == "text"
+                    && message["body"]["body"]
+                        .as_str()
+                        .is_some_and(|x| x.ends_with(" was withheld: spam"))
+            }));
+            assert!(
+                messages
+                    .iter()
+                    .any(|message| message["body"]["type"] == "status_change")
+            );
+
+            let notes = user_notes(api).await;
+            assert_eq!(
+                notes["notes"],
+                format!("Withheld {} for spam", alpha.project_slug)
+            );
+            assert_eq!(notes["last_author"], "2");
+
+            let macros = list_macros(api).await;
+            assert_eq!(macros[0]["uses"], 1);
+            assert!(macros[0]["last_used"].is_string());
+        },
+    )
+    .await;
+}
+
+#[actix_rt::test]
+pub async fn applying_macros_rejects_long_messages() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let alpha = &test_env.dummy.project_alpha;
+
+            let resp = create_macro(
+                api,
+                json!({
+                    "name": "Withhold",
+                    "message": "Withheld: {reason}",
+                    "status": "withheld",
+                    "note": "Withheld for {reason}",
+                }),
+                ADMIN_USER_PAT,
+            )
+            .await;
+            assert_status!(&resp, StatusCode::OK);
+            let created: Value = test::read_body_json(resp).await;
+            let id = created["id"].as_str().unwrap();
+
+            let messages_before =
+                thread_messages(api, &alpha.thread_id).await.len();
+            let resp = apply_macro(
+                api,
+                id,
+                json!({
+                    "project_id": alpha.project_id,
+                    "variables": { "reason": "a".repeat(65536) },
+                }),
+                MOD_USER_PAT,
+            )
+            .await;
+            assert_status!(&resp, StatusCode::BAD_REQUEST);
+
+            // Nothing the macro does is applied
+            assert_eq!(
+                thread_messages(api, &alpha.thread_id).await.len(),
+                messages_before
+            );
+            assert_eq!(
+                project_status(api, &alpha.project_id).await,
+                "approved"
+            );
+            assert!(user_notes(api).await.is_null());
+            assert_eq!(list_macros(api).await[0]["uses"], 0);
+        },
+    )
+    .await;
+}
@@ -0,0 +1,154 @@
+use actix_http::StatusCode;
+use actix_web::test;
+use common::api_common::{Api, ApiProject, ApiTeams, AppendsOptionalPat};
+use common::api_v3::ApiV3;
+use common::database::{FRIEND_USER_ID, FRIEND_USER_PAT, USER_USER_PAT};
+use common::environment::{TestEnvironment, with_test_environment};
+use labrinth::models::teams::{OrganizationPermissions, ProjectPermissions};
+use serde_json::{Value, json};
+
+pub mod common;
+
+#[actix_rt::test]
+pub async fn organization_roles_grant_permissions() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let zeta_organization_id =
+                &test_env.dummy.organization_zeta.organization_id;
+            let zeta_team_id = &test_env.dummy.organization_zeta.team_id;
+            let alpha_project_id = &test_env.dummy.project_alpha.project_id;
+
+            let resp = api
+                .organization_add_project(
+                    zeta_organization_id,
+                    alpha_project_id,
+                    USER_USER_PAT,
+                )
+                .await;
+            assert_status!(&resp, StatusCode::OK);
+
+            // Friend joins the organization without any permissions of
+            // their own
+            let resp = api
+                .add_user_to_team(
+                    zeta_team_id,
+                    FRIEND_USER_ID,
+                    Some(ProjectPermissions::empty()),
+                    Some(OrganizationPermissions::empty()),
+                    USER_USER_PAT,
+                )
+                .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+            let resp = api.join_team(zeta_team_id, FRIEND_USER_PAT).await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+
+            let edit_project = || async {
+                api.edit_project(
+                    alpha_project_id,
+                    json!({ "summary": "Edited by a role member" }),
+                    FRIEND_USER_PAT,
+                )
+                .await
+                .status()
+            };
+            let edit_organization = || async {
+                api.edit_organization(
+                    zeta_organization_id,
+                    json!({ "description": "Edited by a role member" }),
+                    FRIEND_USER_PAT,
+                )
+                .await
+                .status()
+            };
+
+            assert_eq!(edit_project().await, StatusCode::UNAUTHORIZED);
+            assert_eq!(edit_organization().await, StatusCode::UNAUTHORIZED);
+
+            let resp = api
+                .call(
+                    test::TestRequest::post()
+                        .uri(&format!(
+                            "/v3/organization/{zeta_organization_id}/roles"
+                        ))
+                        .append_pat(USER_USER_PAT)
+                        .set_json(json!({
+                            "name": "Editors",
+                            "permissions": ProjectPermissions::EDIT_DETAILS,
+                            "organization_permissions":
+                                OrganizationPermissions::EDIT_DETAILS,
+                        }))
+                        .to_request(),
+                )
+                .await;
+            assert_status!(&resp, StatusCode::OK);
+            let role: Value = test::read_body_json(resp).await;
+            let role_id = role["id"].as_str().unwrap();
+
+            let resp = api
+                .edit_team_member(
+                    zeta_team_id,
+                    FRIEND_USER_ID,
+                    json!({ "role_id": role_id }),
+                    USER_USER_PAT,
+                )
+                .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+
+            // The role grants its project and organization permissions...
+            assert_eq!(edit_project().await, StatusCode::NO_CONTENT);
+            assert_eq!(edit_organization().await, StatusCode::NO_CONTENT);
+
+            // ...and nothing else
+            let resp = api.remove_project(alpha_project_id, FRIEND_USER_PAT).await;
+            assert_status!(&resp, StatusCode::UNAUTHORIZED);
+            let resp = api
+                .call(
+                    test::TestRequest::post()
+                        .uri(&format!(
+                            "/v3/organization/{zeta_organization_id}/roles"
+                        ))
+                        .append_pat(FRIEND_USER_PAT)
+                        .set_json(json!({ "name": "Friends" }))
+                        .to_request(),
+                )
+                .await;
+            assert_status!(&resp, StatusCode::UNAUTHORIZED);
+
+            // An override replaces the role's permissions in one project,
+            // leaving its organization permissions alone
+            let override_uri = format!(
+                "/v3/organization/{zeta_organization_id}/roles/{role_id}/projects/{alpha_project_id}"
+            );
+            let resp = api
+                .call(
+                    test::TestRequest::put()
+                        .uri(&override_uri)
+                        .append_pat(USER_USER_PAT)
+                        .set_json(json!({
+                            "permissions": ProjectPermissions::UPLOAD_VERSION,
+                        }))
+                        .to_request(),
+                )
+                .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+
+            assert_eq!(edit_project().await, StatusCode::UNAUTHORIZED);
+            assert_eq!(edit_organization().await, StatusCode::NO_CONTENT);
+
+            let resp = api
+                .call(
+                    test::TestRequest::delete()
+                        .uri(&override_uri)
+                        .append_pat(USER_USER_PAT)
+                        .to_request(),
+                )
+                .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+
+            assert_eq!(edit_project().await, StatusCode::NO_CONTENT);
+        },
+    )
+    .await;
+}
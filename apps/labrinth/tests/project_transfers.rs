@@ -0,0 +1,529 @@
+use actix_http::StatusCode;
+use actix_web::dev::ServiceResponse;
+use actix_web::test;
+use chrono::{Duration, Utc};
+use common::api_common::{Api, ApiProject, ApiTeams, AppendsOptionalPat};
+use common::api_v3::ApiV3;
+use common::database::{
+    ADMIN_USER_PAT, ENEMY_USER_ID, ENEMY_USER_ID_PARSED, ENEMY_USER_PAT,
+    FRIEND_USER_ID, FRIEND_USER_ID_PARSED, FRIEND_USER_PAT, MOD_USER_ID_PARSED,
+    MOD_USER_PAT, USER_USER_ID, USER_USER_ID_PARSED, USER_USER_PAT,
+};
+use common::environment::{TestEnvironment, with_test_environment};
+use labrinth::database::PgPool;
+use labrinth::models::teams::OrganizationPermissions;
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+
+pub mod common;
+
+async fn create_transfer(
+    api: &ApiV3,
+    project_id: &str,
+    body: Value,
+    pat: Option<&str>,
+) -> ServiceResponse {
+    api.call(
+        test::TestRequest::post()
+            .uri(&format!("/v3/project/{project_id}/transfer"))
+            .append_pat(pat)
+            .set_json(body)
+            .to_request(),
+    )
+    .await
+}
+
+async fn request_transfer(
+    api: &ApiV3,
+    project_id: &str,
+    body: Value,
+) -> String {
+    let resp = create_transfer(api, project_id, body, USER_USER_PAT).await;
+    assert_status!(&resp, StatusCode::OK);
+    let transfer: Value = test::read_body_json(resp).await;
+    assert_eq!(transfer["status"], "pending");
+    transfer["id"].as_str().unwrap().to_string()
+}
+
+/// Accepts or declines a transfer.
+async fn respond(
+    api: &ApiV3,
+    id: &str,
+    response: &str,
+    pat: Option<&str>,
+) -> ServiceResponse {
+    api.call(
+        test::TestRequest::post()
+            .uri(&format!("/v3/project_transfer/{id}/{response}"))
+            .append_pat(pat)
+            .to_request(),
+    )
+    .await
+}
+
+async fn cancel(api: &ApiV3, id: &str, pat: Option<&str>) -> ServiceResponse {
+    api.call(
+        test::TestRequest::delete()
+            .uri(&format!("/v3/project_transfer/{id}"))
+            .append_pat(pat)
+            .to_request(),
+    )
+    .await
+}
+
+async fn get_json(api: &ApiV3, uri: &str, pat: Option<&str>) -> Value {
+    let resp = api
+        .call(
+            test::TestRequest::get()
+                .uri(uri)
+                .append_pat(pat)
+                .to_request(),
+        )
+        .await;
+    assert_status!(&resp, StatusCode::OK);
+    test::read_body_json(resp).await
+}
+
+async fn project_organization(api: &ApiV3, project_id: &str) -> Value {
+    let resp = api.get_project(project_id, ADMIN_USER_PAT).await;
+    assert_status!(&resp, StatusCode::OK);
+    let project: Value = test::read_body_json(resp).await;
+    project["organization"].clone()
+}
+
+/// Gets a member of a team, returning whether they're its owner and their
+/// payouts split.
+async fn team_member(
+    api: &ApiV3,
+    team_id: &str,
+    user_id: &str,
+) -> Option<(bool, Decimal)> {
+    let resp = api.get_team_members(team_id, ADMIN_USER_PAT).await;
+    assert_status!(&resp, StatusCode::OK);
+    let members: Vec<Value> = test::read_body_json(resp).await;
+    members
+        .into_iter()
+        .find(|member| member["user"]["id"] == user_id)
+        .map(|member| {
+            (
+                member["is_owner"].as_bool().unwrap(),
+                serde_json::from_value(member["payouts_split"].clone())
+                    .unwrap(),
+            )
+        })
+}
+
+async fn insert_payout(
+    user_id: i64,
+    project_id: i64,
+    pending: bool,
+    pool: &PgPool,
+) -> i64 {
+    sqlx::query_scalar!(
+        "
+        INSERT INTO payouts_values (user_id, mod_id, amount, created, date_available)
+        VALUES (
+            $1, $2, 10, NOW(),
+            CASE WHEN $3 THEN NOW() + INTERVAL '30 days' ELSE NOW() - INTERVAL '1 day' END
+        )
+        RETURNING id
+        ",
+        user_id,
+        project_id,
+        pending,
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+async fn payout_user(id: i64, pool: &PgPool) -> i64 {
+    sqlx::query_scalar!("SELECT user_id FROM payouts_values WHERE id = $1", id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+async fn notifications(user_id: i64, type_: &str, pool: &PgPool) -> i64 {
+    sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) "count!" FROM notifications
+        WHERE user_id = $1 AND body ->> 'type' = $2
+        "#,
+        user_id,
+        type_,
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+#[actix_rt::test]
+pub async fn transfers_between_users() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let pool = &test_env.db.pool;
+            let alpha = &test_env.dummy.project_alpha;
+            let alpha_id = alpha.project_id_parsed.0 as i64;
+
+            let resp = api
+                .edit_team_member(
+                    &alpha.team_id,
+                    USER_USER_ID,
+                    json!({
+                        "payouts_split": 50,
+                        "payouts_split_effective_from":
+                            Utc::now() + Duration::days(1),
+                    }),
+                    USER_USER_PAT,
+                )
+                .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+            let (_, payouts_split) =
+                team_member(api, &alpha.team_id, USER_USER_ID)
+                    .await
+                    .unwrap();
+            let pending =
+                insert_payout(USER_USER_ID_PARSED, alpha_id, true, pool).await;
+            let available =
+                insert_payout(USER_USER_ID_PARSED, alpha_id, false, pool).await;
+
+            let body = json!({
+                "new_owner_user_id": FRIEND_USER_ID,
+                "transfer_pending_payouts": true,
+                "transfer_payouts_split": true,
+            });
+            let resp = create_transfer(
+                api,
+                &alpha.project_id,
+                body.clone(),
+                FRIEND_USER_PAT,
+            )
+            .await;
+            assert_status!(&resp, StatusCode::UNAUTHORIZED);
+            let id =
+                request_transfer(api, &alpha.project_id, body.clone()).await;
+            let resp =
+                create_transfer(api, &alpha.project_id, body, USER_USER_PAT)
+                    .await;
+            assert_status!(&resp, StatusCode::BAD_REQUEST);
+            assert_eq!(
+                notifications(
+                    FRIEND_USER_ID_PARSED,
+                    "project_transfer_requested",
+                    pool
+                )
+                .await,
+                1
+            );
+
+            for pat in [ENEMY_USER_PAT, MOD_USER_PAT] {
+                let resp = respond(api, &id, "accept", pat).await;
+                assert_status!(&resp, StatusCode::UNAUTHORIZED);
+            }
+            let resp = respond(api, &id, "accept", FRIEND_USER_PAT).await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+            let resp = respond(api, &id, "accept", FRIEND_USER_PAT).await;
+            assert_status!(&resp, StatusCode::BAD_REQUEST);
+
+            // The previous owner stays on the team without their split
+            assert_eq!(
+                team_member(api, &alpha.team_id, FRIEND_USER_ID).await,
+                Some((true, payouts_split))
+            );
+            assert_eq!(
+                team_member(api, &alpha.team_id, USER_USER_ID).await,
+                Some((false, Decimal::ZERO))
+            );
+            let scheduled = sqlx::query_scalar!(
+                r#"
+                SELECT COUNT(*) "count!" FROM team_member_payouts_splits
+                WHERE user_id = $1 AND effective_from > NOW()
+                "#,
+                USER_USER_ID_PARSED,
+            )
+            .fetch_one(pool)
+            .await
+            .unwrap();
+            assert_eq!(scheduled, 0);
+
+            assert_eq!(payout_user(pending, pool).await, FRIEND_USER_ID_PARSED);
+            assert_eq!(payout_user(available, pool).await, USER_USER_ID_PARSED);
+
+            assert_eq!(
+                notifications(USER_USER_ID_PARSED, "project_transferred", pool)
+                    .await,
+                1
+            );
+            let resp = api.get_thread(&alpha.thread_id, MOD_USER_PAT).await;
+            assert_status!(&resp, StatusCode::OK);
+            let thread: Value = test::read_body_json(resp).await;
+            assert!(
+                thread["messages"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .any(|message| message["body"]["type"]
+                        == "ownership_transferred")
+            );
+        },
+    )
+    .await;
+}
+
+#[actix_rt::test]
+pub async fn transfers_to_and_from_organizations() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let pool = &test_env.db.pool;
+            let alpha = &test_env.dummy.project_alpha;
+            let alpha_id = alpha.project_id_parsed.0 as i64;
+            let zeta = &test_env.dummy.organization_zeta;
+
+            for body in [
+                json!({
+                    "new_owner_organization_id": zeta.organization_id,
+                    "transfer_pending_payouts": true,
+                    "transfer_payouts_split": false,
+                }),
+                json!({
+                    "new_owner_organization_id": zeta.organization_id,
+                    "transfer_pending_payouts": false,
+                    "transfer_payouts_split": true,
+                }),
+                json!({
+                    "new_owner_user_id": FRIEND_USER_ID,
+                    "new_owner_organization_id": zeta.organization_id,
+                    "transfer_pending_payouts": false,
+                    "transfer_payouts_split": false,
+                }),
+                json!({
+                    "new_owner_user_id": USER_USER_ID,
+                    "transfer_pending_payouts": false,
+                    "transfer_payouts_split": false,
+                }),
+            ] {
+                let resp = create_transfer(
+                    api,
+                    &alpha.project_id,
+                    body,
+                    USER_USER_PAT,
+                )
+                .await;
+                assert_status!(&resp, StatusCode::BAD_REQUEST);
+            }
+
+            let resp = api
+                .add_user_to_team(
+                    &zeta.team_id,
+                    FRIEND_USER_ID,
+                    None,
+                    Some(OrganizationPermissions::ADD_PROJECT),
+                    USER_USER_PAT,
+                )
+                .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+            let resp = api.join_team(&zeta.team_id, FRIEND_USER_PAT).await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+
+            // Only the organization's owner, who is notified, can accept
+            let id = request_transfer(
+                api,
+                &alpha.project_id,
+                json!({
+                    "new_owner_organization_id": zeta.organization_id,
+                    "transfer_pending_payouts": false,
+                    "transfer_payouts_split": false,
+                }),
+            )
+            .await;
+            for pat in [FRIEND_USER_PAT, MOD_USER_PAT] {
+                let resp = respond(api, &id, "accept", pat).await;
+                assert_status!(&resp, StatusCode::UNAUTHORIZED);
+            }
+            let resp = respond(api, &id, "accept", USER_USER_PAT).await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+
+            assert_eq!(
+                project_organization(api, &alpha.project_id).await,
+                zeta.organization_id.as_str()
+            );
+            assert_eq!(
+                team_member(api, &alpha.team_id, USER_USER_ID).await,
+                None
+            );
+
+            // Only the organization's owner gives up their pending payouts
+            let user_pending =
+                insert_payout(USER_USER_ID_PARSED, alpha_id, true, pool).await;
+            let friend_pending =
+                insert_payout(FRIEND_USER_ID_PARSED, alpha_id, true, pool)
+                    .await;
+            let mod_pending =
+                insert_payout(MOD_USER_ID_PARSED, alpha_id, true, pool).await;
+            let user_available =
+                insert_payout(USER_USER_ID_PARSED, alpha_id, false, pool).await;
+
+            let id = request_transfer(
+                api,
+                &alpha.project_id,
+                json!({
+                    "new_owner_user_id": ENEMY_USER_ID,
+                    "transfer_pending_payouts": true,
+                    "transfer_payouts_split": false,
+                }),
+            )
+            .await;
+            let resp = respond(api, &id, "accept", ENEMY_USER_PAT).await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+
+            assert!(
+                project_organization(api, &alpha.project_id).await.is_null()
+            );
+            assert_eq!(
+                team_member(api, &alpha.team_id, ENEMY_USER_ID)
+                    .await
+                    .map(|(is_owner, _)| is_owner),
+                Some(true)
+            );
+
+            assert_eq!(
+                payout_user(user_pending, pool).await,
+                ENEMY_USER_ID_PARSED
+            );
+            assert_eq!(
+                payout_user(friend_pending, pool).await,
+                FRIEND_USER_ID_PARSED
+            );
+            assert_eq!(
+                payout_user(mod_pending, pool).await,
+                MOD_USER_ID_PARSED
+            );
+            assert_eq!(
+                payout_user(user_available, pool).await,
+                USER_USER_ID_PARSED
+            );
+        },
+    )
+    .await;
+}
+
+#[actix_rt::test]
+pub async fn transfers_can_be_declined_and_cancelled() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let alpha = &test_env.dummy.project_alpha;
+            let body = json!({
+                "new_owner_user_id": FRIEND_USER_ID,
+                "transfer_pending_payouts": false,
+                "transfer_payouts_split": false,
+            });
+
+            let id =
+                request_transfer(api, &alpha.project_id, body.clone()).await;
+            let transfers =
+                get_json(api, "/v3/project_transfers", FRIEND_USER_PAT).await;
+            assert_eq!(transfers[0]["id"], id.as_str());
+
+            let resp = respond(api, &id, "decline", ENEMY_USER_PAT).await;
+            assert_status!(&resp, StatusCode::UNAUTHORIZED);
+            let resp = respond(api, &id, "decline", FRIEND_USER_PAT).await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+            for response in ["decline", "accept"] {
+                let resp = respond(api, &id, response, FRIEND_USER_PAT).await;
+                assert_status!(&resp, StatusCode::BAD_REQUEST);
+            }
+
+            let id = request_transfer(api, &alpha.project_id, body).await;
+            let resp = cancel(api, &id, FRIEND_USER_PAT).await;
+            assert_status!(&resp, StatusCode::UNAUTHORIZED);
+            let resp = cancel(api, &id, USER_USER_PAT).await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+            let resp = respond(api, &id, "accept", FRIEND_USER_PAT).await;
+            assert_status!(&resp, StatusCode::BAD_REQUEST);
+
+            let transfers =
+                get_json(api, "/v3/project_transfers", FRIEND_USER_PAT).await;
+            assert_eq!(transfers, json!([]));
+            let transfers = get_json(
+                api,
+                &format!("/v3/project/{}/transfers", alpha.project_id),
+                USER_USER_PAT,
+            )
+            .await;
+            let mut statuses = transfers
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|transfer| transfer["status"].as_str().unwrap())
+                .collect::<Vec<_>>();
+            statuses.sort();
+            assert_eq!(statuses, ["cancelled", "declined"]);
+
+            assert_eq!(
+                team_member(api, &alpha.team_id, USER_USER_ID)
+                    .await
+                    .map(|(is_owner, _)| is_owner),
+                Some(true)
+            );
+            assert_eq!(
+                team_member(api, &alpha.team_id, FRIEND_USER_ID).await,
+                None
+            );
+        },
+    )
+    .await;
+}
+
+#[actix_rt::test]
+pub async fn transfers_require_the_owner_to_be_unchanged() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let alpha = &test_env.dummy.project_alpha;
+            let zeta = &test_env.dummy.organization_zeta;
+
+            let id = request_transfer(
+                api,
+                &alpha.project_id,
+                json!({
+                    "new_owner_user_id": FRIEND_USER_ID,
+                    "transfer_pending_payouts": false,
+                    "transfer_payouts_split": false,
+                }),
+            )
+            .await;
+
+            let resp = api
+                .organization_add_project(
+                    &zeta.organization_id,
+                    &alpha.project_id,
+                    USER_USER_PAT,
+                )
+                .await;
+            assert_status!(&resp, StatusCode::OK);
+
+            let resp = respond(api, &id, "accept", FRIEND_USER_PAT).await;
+            assert_status!(&resp, StatusCode::BAD_REQUEST);
+            assert_eq!(
+                project_organization(api, &alpha.project_id).await,
+                zeta.organization_id.as_str()
+            );
+
+            // The transfer stays pending until it's cancelled
+            let transfers =
+                get_json(api, "/v3/project_transfers", FRIEND_USER_PAT).await;
+            assert_eq!(transfers[0]["id"], id.as_str());
+            let resp = cancel(api, &id, USER_USER_PAT).await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+        },
+    )
+    .await;
+}
@@ -0,0 +1,81 @@
+use actix_http::StatusCode;
+use actix_web::test;
+use common::api_common::{Api, AppendsOptionalPat};
+use common::api_v3::ApiV3;
+use common::database::USER_USER_PAT;
+use common::environment::{TestEnvironment, with_test_environment};
+use serde_json::{Value, json};
+
+pub mod common;
+
+#[actix_rt::test]
+pub async fn webhook_urls_must_be_public() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let project_id = &test_env.dummy.project_alpha.project_id;
+
+            for url in [
+                "http://example.com/hook",
+                "https://169.254.169.254/latest/meta-data/",
+                "https://127.0.0.1:8000/",
+                "https://0x7f000001/",
+                "https://10.1.2.3/",
+                "https://192.168.0.1/",
+                "https://100.64.0.1/",
+                "https://[::1]/",
+                "https://[fd00:ec2::254]/",
+                "https://[::ffff:169.254.169.254]/",
+                "https://localhost:9000/",
+            ] {
+                let resp = api
+                    .call(
+                        test::TestRequest::post()
+                            .uri(&format!("/v3/project/{project_id}/webhooks"))
+                            .append_pat(USER_USER_PAT)
+                            .set_json(json!({
+                                "url": url,
+                                "events": ["version_published"],
+                            }))
+                            .to_request(),
+                    )
+                    .await;
+                assert_status!(&resp, StatusCode::BAD_REQUEST);
+            }
+
+            let resp = api
+                .call(
+                    test::TestRequest::post()
+                        .uri(&format!("/v3/project/{project_id}/webhooks"))
+                        .append_pat(USER_USER_PAT)
+                        .set_json(json!({
+                            "url": "https://example.com/hook",
+                            "events": ["version_published"],
+                        }))
+                        .to_request(),
+                )
+                .await;
+            assert_status!(&resp, StatusCode::OK);
+            let webhook: Value = test::read_body_json(resp).await;
+            let webhook_id = webhook["id"].as_str().unwrap();
+
+            // Editing is held to the same rules
+            let resp = api
+                .call(
+                    test::TestRequest::patch()
+                        .uri(&format!(
+                            "/v3/project/{project_id}/webhooks/{webhook_id}"
+                        ))
+                        .append_pat(USER_USER_PAT)
+                        .set_json(json!({
+                            "url": "https://169.254.169.254/",
+                        }))
+                        .to_request(),
+                )
+                .await;
+            assert_status!(&resp, StatusCode::BAD_REQUEST);
+        },
+    )
+    .await;
+}
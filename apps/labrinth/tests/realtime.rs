@@ -0,0 +1,181 @@
+use actix_http::StatusCode;
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::ServiceResponse;
+use actix_web::test;
+use common::api_common::{Api, ApiProject, ApiTeams, AppendsOptionalPat};
+use common::api_v3::ApiV3;
+use common::database::{
+    FRIEND_USER_ID, FRIEND_USER_PAT, MOD_USER_PAT, USER_USER_PAT,
+};
+use common::environment::{TestEnvironment, with_test_environment};
+use serde_json::Value;
+use std::pin::Pin;
+use std::time::Duration;
+
+pub mod common;
+
+async fn create_ticket(api: &ApiV3, pat: Option<&str>) -> ServiceResponse {
+    api.call(
+        test::TestRequest::post()
+            .uri("/v3/events/ticket")
+            .append_pat(pat)
+            .to_request(),
+    )
+    .await
+}
+
+async fn open_stream(api: &ApiV3, pat: Option<&str>) -> BoxBody {
+    let resp = create_ticket(api, pat).await;
+    assert_status!(&resp, StatusCode::OK);
+    let ticket: Value = test::read_body_json(resp).await;
+    let ticket = ticket["ticket"].as_str().unwrap();
+
+    let resp = api
+        .call(
+            test::TestRequest::get()
+                .uri(&format!("/v3/events?ticket={ticket}"))
+                .to_request(),
+        )
+        .await;
+    assert_status!(&resp, StatusCode::OK);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "text/event-stream"
+    );
+    resp.into_body()
+}
+
+/// Reads events from a stream until one of type `event_type` arrives,
+/// skipping keepalive comments and other events.
+async fn next_event(stream: &mut BoxBody, event_type: &str) -> Value {
+    tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            let frame =
+                std::future::poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx))
+                    .await
+                    .expect("event stream ended")
+                    .unwrap();
+            let frame = std::str::from_utf8(&frame).unwrap();
+            for data in frame
+                .split("\n\n")
+                .filter_map(|frame| frame.strip_prefix("data: "))
+            {
+                let event: Value = serde_json::from_str(data).unwrap();
+                if event["type"] == event_type {
+                    return event;
+                }
+            }
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("no `{event_type}` event was received"))
+}
+
+#[actix_rt::test]
+pub async fn event_stream_tickets_are_single_use() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+
+            let resp = create_ticket(api, None).await;
+            assert_status!(&resp, StatusCode::UNAUTHORIZED);
+
+            let resp = create_ticket(api, USER_USER_PAT).await;
+            assert_status!(&resp, StatusCode::OK);
+            let ticket: Value = test::read_body_json(resp).await;
+            let uri = format!(
+                "/v3/events?ticket={}",
+                ticket["ticket"].as_str().unwrap()
+            );
+
+            let resp = api
+                .call(test::TestRequest::get().uri(&uri).to_request())
+                .await;
+            assert_status!(&resp, StatusCode::OK);
+            drop(resp);
+
+            for uri in [uri.as_str(), "/v3/events?ticket=invalid", "/v3/events"]
+            {
+                let resp = api
+                    .call(test::TestRequest::get().uri(uri).to_request())
+                    .await;
+                assert_status!(&resp, StatusCode::UNAUTHORIZED);
+            }
+
+            // Tokens are still accepted in the `Authorization` header
+            let resp = api
+                .call(
+                    test::TestRequest::get()
+                        .uri("/v3/events")
+                        .append_pat(USER_USER_PAT)
+                        .to_request(),
+                )
+                .await;
+            assert_status!(&resp, StatusCode::OK);
+        },
+    )
+    .await;
+}
+
+#[actix_rt::test]
+pub async fn event_stream_receives_notifications() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let alpha_team_id = &test_env.dummy.project_alpha.team_id;
+
+            let mut stream = open_stream(api, FRIEND_USER_PAT).await;
+
+            let resp = api
+                .add_user_to_team(
+                    alpha_team_id,
+                    FRIEND_USER_ID,
+                    None,
+                    None,
+                    USER_USER_PAT,
+                )
+                .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+
+            let event = next_event(&mut stream, "notification").await;
+            let notification = &event["notification"];
+            assert_eq!(notification["user_id"], FRIEND_USER_ID);
+            assert_eq!(notification["body"]["type"], "team_invite");
+            assert_eq!(notification["body"]["team_id"], alpha_team_id.as_str());
+        },
+    )
+    .await;
+}
+
+#[actix_rt::test]
+pub async fn event_stream_receives_thread_messages() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let alpha_thread_id = &test_env.dummy.project_alpha.thread_id;
+
+            let mut stream = open_stream(api, USER_USER_PAT).await;
+
+            let resp = api
+                .write_to_thread(
+                    alpha_thread_id,
+                    "text",
+                    "Hello from the moderators",
+                    MOD_USER_PAT,
+                )
+                .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+
+            let event = next_event(&mut stream, "thread_message").await;
+            assert_eq!(event["thread_id"], alpha_thread_id.as_str());
+            assert_eq!(
+                event["message"]["body"]["body"],
+                "Hello from the moderators"
+            );
+        },
+    )
+    .await;
+}
@@ -22,7 +22,9 @@ use serde_json::json;
 use tokio::sync::{Barrier, Notify};
 use tokio::time::timeout;
 use uuid::Uuid;
-use xredis::{KeyBuilder, RedisPool, RedisTopology, RedisValue};
+use xredis::{
+    CacheLockingStrategy, KeyBuilder, RedisPool, RedisTopology, RedisValue,
+};
 
 pub mod common;
 
@@ -565,6 +567,59 @@ async fn case_insensitive_slug_requests_share_one_cache_lock() {
     assert_eq!(fetch_count.load(Ordering::SeqCst), 1);
 }
 
+/// Protects the distributed single-flight contract: pools standing in for
+/// separate replicas share one Redis lock, so concurrent misses across all of
+/// them perform one backing fetch.
+#[actix_rt::test]
+async fn distributed_cache_lock_coalesces_misses_across_pools() {
+    labrinth::env::init().expect("failed to initialize test environment");
+    let meta_namespace =
+        format!("redis_test_distributed_lock_{}", Uuid::new_v4());
+    let mut pools = Vec::new();
+    for _ in 0..3 {
+        pools.push(
+            labrinth::database::redis::with_cache_locking_strategy(
+                meta_namespace.clone(),
+                CacheLockingStrategy::Distributed,
+            )
+            .await,
+        );
+    }
+
+    let barrier = Arc::new(Barrier::new(12));
+    let fetch_count = Arc::new(AtomicUsize::new(0));
+    let mut tasks = Vec::new();
+
+    for index in 0..12 {
+        let pool = pools[index % pools.len()].clone();
+        let barrier = barrier.clone();
+        let fetch_count = fetch_count.clone();
+        tasks.push(tokio::spawn(async move {
+            barrier.wait().await;
+            pool.get_cached_keys_raw(
+                "distributed_single_flight:v1",
+                &["shared".to_string()],
+                move |keys| async move {
+                    fetch_count.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(75)).await;
+                    let values = DashMap::new();
+                    for key in keys {
+                        values.insert(key.clone(), format!("value-{key}"));
+                    }
+                    Ok::<_, DatabaseError>(values)
+                },
+            )
+            .await
+        }));
+    }
+
+    for task in tasks {
+        let values = task.await.unwrap().unwrap();
+        assert_eq!(values.get("shared"), Some(&"value-shared".to_string()));
+    }
+    assert_eq!(fetch_count.load(Ordering::SeqCst), 1);
+}
+
 /// Protects the cluster-aware blocking pool and Pub/Sub seed rotation, which
 /// use separate connection paths from ordinary cache commands.
 #[actix_rt::test]
@@ -616,6 +671,204 @@ async fn blocking_queue_and_pubsub_work_with_cluster_connections() {
     assert!(delivered, "Pub/Sub did not recover from the invalid seed");
 }
 
+fn memory_redis_config() -> xredis::RedisConfig {
+    xredis::RedisConfig::new(
+        RedisTopology::Memory,
+        xredis::RedisConnectionType::Pooled,
+        "",
+        1000,
+        (1, 0),
+        (1, 0),
+        (1, 0),
+        CacheLockingStrategy::Local,
+        xredis::ReadReplicaStrategy::Primary,
+    )
+    .unwrap()
+}
+
+fn memory_cache_settings() -> xredis::CacheSettings {
+    xredis::CacheSettings {
+        default_expiry: 60,
+        actual_expiry: 30,
+        version_default_expiry: 60,
+        version_actual_expiry: 30,
+        encoding_format: xredis::EncodingFormat::Postcard,
+        compression_algorithm: xredis::Codec::Lz4,
+        compression_level: 1,
+        compression_threshold_bytes: 0,
+        compression_min_savings_ratio: 0.0,
+        l1: xredis::L1CacheSettings::default(),
+    }
+}
+
+/// Protects the in-memory backend's command subset: pools in one process
+/// share its data, and the cache manager, blocking queue and Pub/Sub work on
+/// top of it without a Redis server.
+#[actix_rt::test]
+async fn memory_backend_supports_cache_queue_and_pubsub() {
+    let meta_namespace = format!("redis_test_memory_{}", Uuid::new_v4());
+    let pool = RedisPool::new(
+        meta_namespace.clone(),
+        memory_redis_config(),
+        memory_cache_settings(),
+    )
+    .await
+    .unwrap();
+    let other_pool = RedisPool::new(
+        meta_namespace,
+        memory_redis_config(),
+        memory_cache_settings(),
+    )
+    .await
+    .unwrap();
+
+    let fetch_count = Arc::new(AtomicUsize::new(0));
+    for pool in [&pool, &other_pool] {
+        let fetch_count = fetch_count.clone();
+        let values = pool
+            .get_cached_keys_raw(
+                "memory:v1",
+                &["shared".to_string()],
+                move |keys| async move {
+                    fetch_count.fetch_add(1, Ordering::SeqCst);
+                    let values = DashMap::new();
+                    for key in keys {
+                        values.insert(key.clone(), format!("value-{key}"));
+                    }
+                    Ok::<_, DatabaseError>(values)
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(values.get("shared"), Some(&"value-shared".to_string()));
+    }
+    assert_eq!(fetch_count.load(Ordering::SeqCst), 1);
+
+    let mut connection = pool.connect().await.unwrap();
+    let counter_key = pool.key().entity("memory_counter", "key");
+    assert_eq!(connection.incr(&counter_key).await.unwrap(), Some(1));
+    assert_eq!(connection.incr(&counter_key).await.unwrap(), Some(2));
+    connection.delete(&counter_key).await.unwrap();
+    assert_eq!(connection.get(&counter_key).await.unwrap(), None);
+
+    let queue_key = pool.key().entity("memory_queue", "queue");
+    let waiter = {
+        let other_pool = other_pool.clone();
+        let queue_key = queue_key.clone();
+        tokio::spawn(async move {
+            other_pool.brpop(&queue_key, Duration::from_secs(5)).await
+        })
+    };
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    connection
+        .lpush(&queue_key, b"queued-value".as_slice())
+        .await
+        .unwrap();
+    let popped = waiter
+        .await
+        .unwrap()
+        .unwrap()
+        .expect("BRPOP did not receive the queued value");
+    assert_eq!(popped[1], b"queued-value");
+    assert!(
+        pool.brpop(&queue_key, Duration::from_millis(50))
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    let channel: &'static str = Box::leak(
+        format!("redis-test-memory-pubsub-{}", Uuid::new_v4()).into_boxed_str(),
+    );
+    let mut receiver = other_pool.subscribe(channel);
+    pool.publish(channel, b"memory-message".as_slice())
+        .await
+        .unwrap();
+    let message = timeout(Duration::from_secs(1), receiver.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(message, b"memory-message");
+}
+
+/// Protects the L1 cache's cross-node invalidation: writing a key through one
+/// pool evicts it from the L1 cache of every other pool, instead of them
+/// serving the old value until its TTL.
+#[actix_rt::test]
+async fn l1_cache_is_invalidated_across_pools_on_write() {
+    let meta_namespace = format!("redis_test_l1_{}", Uuid::new_v4());
+    let cache_settings = xredis::CacheSettings {
+        l1: xredis::L1CacheSettings {
+            max_entries: 16,
+            namespace_ttls: HashMap::from([(
+                "l1".to_string(),
+                Duration::from_secs(60),
+            )]),
+        },
+        ..memory_cache_settings()
+    };
+    let pool = RedisPool::new(
+        meta_namespace.clone(),
+        memory_redis_config(),
+        cache_settings.clone(),
+    )
+    .await
+    .unwrap();
+    let other_pool =
+        RedisPool::new(meta_namespace, memory_redis_config(), cache_settings)
+            .await
+            .unwrap();
+
+    let get = |pool: &RedisPool| {
+        let pool = pool.clone();
+        async move {
+            pool.get_cached_keys_raw(
+                "l1:v1",
+                &["shared".to_string()],
+                |keys| async move {
+                    Ok::<_, DatabaseError>(
+                        keys.into_iter()
+                            .map(|key| (key, "fetched".to_string()))
+                            .collect::<DashMap<_, _>>(),
+                    )
+                },
+            )
+            .await
+            .unwrap()
+            .remove("shared")
+        }
+    };
+
+    // Both pools now have the value in their L1 cache
+    assert_eq!(get(&pool).await.as_deref(), Some("fetched"));
+    assert_eq!(get(&other_pool).await.as_deref(), Some("fetched"));
+
+    let mut connection = pool.connect().await.unwrap();
+    connection
+        .set_serialized(
+            &pool.key().entity("l1:v1", "shared"),
+            RedisValue::<String, String, String>::new(
+                "shared".to_string(),
+                None,
+                chrono::Utc::now().timestamp(),
+                "written".to_string(),
+            ),
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(get(&pool).await.as_deref(), Some("written"));
+    // The other pool hears about the write over Pub/Sub
+    timeout(Duration::from_secs(1), async {
+        while get(&other_pool).await.as_deref() != Some("written") {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("other pool kept serving its L1 value");
+}
+
 /// Protects the real many-get routes as one end-to-end cluster scenario:
 /// partial cache hits, IDs and case-insensitive slugs, duplicates, misses,
 /// invalidation, file hashes, dependency caches, and visibility filtering.
@@ -0,0 +1,293 @@
+use actix_http::StatusCode;
+use actix_web::dev::ServiceResponse;
+use actix_web::test;
+use common::api_common::models::CommonItemType;
+use common::api_common::{Api, ApiProject, AppendsOptionalPat};
+use common::api_v3::ApiV3;
+use common::database::{
+    ENEMY_USER_ID, ENEMY_USER_PAT, FRIEND_USER_ID_PARSED, FRIEND_USER_PAT,
+    MOD_USER_PAT, USER_USER_ID_PARSED, USER_USER_PAT,
+};
+use common::environment::{TestEnvironment, with_test_environment};
+use serde_json::{Value, json};
+
+pub mod common;
+
+async fn create_report(
+    api: &ApiV3,
+    report_type: &str,
+    id: &str,
+    item_type: CommonItemType,
+    pat: Option<&str>,
+) -> Value {
+    let resp = api
+        .create_report(report_type, id, item_type, "Reported", pat)
+        .await;
+    assert_status!(&resp, StatusCode::OK);
+    test::read_body_json(resp).await
+}
+
+async fn get_groups(api: &ApiV3, query: &str, pat: Option<&str>) -> Value {
+    let resp = api
+        .call(
+            test::TestRequest::get()
+                .uri(&format!("/_internal/moderation/reports/groups{query}"))
+                .append_pat(pat)
+                .to_request(),
+        )
+        .await;
+    assert_status!(&resp, StatusCode::OK);
+    test::read_body_json(resp).await
+}
+
+async fn close_group(
+    api: &ApiV3,
+    key: Value,
+    pat: Option<&str>,
+) -> ServiceResponse {
+    api.call(
+        test::TestRequest::post()
+            .uri("/_internal/moderation/reports/groups/close")
+            .append_pat(pat)
+            .set_json(key)
+            .to_request(),
+    )
+    .await
+}
+
+async fn closure_messages(api: &ApiV3, report: &Value) -> usize {
+    let resp = api
+        .get_thread(report["thread_id"].as_str().unwrap(), MOD_USER_PAT)
+        .await;
+    assert_status!(&resp, StatusCode::OK);
+    let thread: Value = test::read_body_json(resp).await;
+    thread["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|message| message["body"]["type"] == "thread_closure")
+        .count()
+}
+
+async fn report_notifications(
+    user_id: i64,
+    pool: &labrinth::database::PgPool,
+) -> i64 {
+    sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) "count!" FROM notifications
+        WHERE user_id = $1 AND body ->> 'type' = 'report_status_updated'
+        "#,
+        user_id,
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+#[actix_rt::test]
+pub async fn report_groups_are_sorted_by_priority() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let alpha = &test_env.dummy.project_alpha.project_id;
+
+            // The dummy accounts are new, so only the moderator's report
+            // carries full weight.
+            create_report(
+                api,
+                "spam",
+                ENEMY_USER_ID,
+                CommonItemType::User,
+                MOD_USER_PAT,
+            )
+            .await;
+            let first = create_report(
+                api,
+                "spam",
+                alpha,
+                CommonItemType::Project,
+                USER_USER_PAT,
+            )
+            .await;
+            let second = create_report(
+                api,
+                "spam",
+                alpha,
+                CommonItemType::Project,
+                USER_USER_PAT,
+            )
+            .await;
+            let third = create_report(
+                api,
+                "spam",
+                alpha,
+                CommonItemType::Project,
+                FRIEND_USER_PAT,
+            )
+            .await;
+            create_report(
+                api,
+                "copyright",
+                alpha,
+                CommonItemType::Project,
+                ENEMY_USER_PAT,
+            )
+            .await;
+
+            let resp = api
+                .call(
+                    test::TestRequest::get()
+                        .uri("/_internal/moderation/reports/groups")
+                        .append_pat(USER_USER_PAT)
+                        .to_request(),
+                )
+                .await;
+            assert_status!(&resp, StatusCode::UNAUTHORIZED);
+
+            let groups = get_groups(api, "", MOD_USER_PAT).await;
+            let groups = groups.as_array().unwrap();
+            assert_eq!(groups.len(), 3);
+
+            assert_eq!(groups[0]["item_type"], "user");
+            assert_eq!(groups[0]["item_id"], ENEMY_USER_ID);
+            assert_eq!(groups[0]["reporters"], 1);
+
+            assert_eq!(groups[1]["item_type"], "project");
+            assert_eq!(groups[1]["item_id"], alpha.as_str());
+            assert_eq!(groups[1]["report_type"], "spam");
+            assert_eq!(
+                groups[1]["reports"],
+                json!([first["id"], second["id"], third["id"]])
+            );
+            assert_eq!(groups[1]["reporters"], 2);
+            for (field, report) in
+                [("first_reported", &first), ("last_reported", &third)]
+            {
+                let resp = api
+                    .get_report(report["id"].as_str().unwrap(), MOD_USER_PAT)
+                    .await;
+                let report: Value = test::read_body_json(resp).await;
+                assert_eq!(groups[1][field], report["created"]);
+            }
+
+            assert_eq!(groups[2]["report_type"], "copyright");
+
+            // A reporter's repeated reports don't add to the priority
+            let priorities = groups
+                .iter()
+                .map(|group| group["priority"].as_f64().unwrap())
+                .collect::<Vec<_>>();
+            assert!((priorities[0] - 2.0).abs() < 1e-9);
+            assert!((priorities[1] - 0.5).abs() < 1e-9);
+            assert!((priorities[2] - 0.25).abs() < 1e-9);
+
+            let page = get_groups(api, "?count=1&offset=1", MOD_USER_PAT).await;
+            assert_eq!(page, json!([groups[1]]));
+        },
+    )
+    .await;
+}
+
+#[actix_rt::test]
+pub async fn closing_report_groups_skips_closed_reports() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let pool = &test_env.db.pool;
+            let alpha = &test_env.dummy.project_alpha.project_id;
+
+            let first = create_report(
+                api,
+                "spam",
+                alpha,
+                CommonItemType::Project,
+                USER_USER_PAT,
+            )
+            .await;
+            let second = create_report(
+                api,
+                "spam",
+                alpha,
+                CommonItemType::Project,
+                FRIEND_USER_PAT,
+            )
+            .await;
+            let other = create_report(
+                api,
+                "copyright",
+                alpha,
+                CommonItemType::Project,
+                FRIEND_USER_PAT,
+            )
+            .await;
+            let key = json!({
+                "item_type": "project",
+                "item_id": alpha,
+                "report_type": "spam",
+            });
+
+            let resp = close_group(api, key.clone(), USER_USER_PAT).await;
+            assert_status!(&resp, StatusCode::UNAUTHORIZED);
+            let resp = close_group(
+                api,
+                json!({
+                    "item_type": "project",
+                    "item_id": "not an ID",
+                    "report_type": "spam",
+                }),
+                MOD_USER_PAT,
+            )
+            .await;
+            assert_status!(&resp, StatusCode::BAD_REQUEST);
+
+            let resp = api
+                .edit_report(
+                    first["id"].as_str().unwrap(),
+                    json!({ "closed": true }),
+                    MOD_USER_PAT,
+                )
+                .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+            assert_eq!(
+                report_notifications(USER_USER_ID_PARSED, pool).await,
+                1
+            );
+
+            let resp = close_group(api, key.clone(), MOD_USER_PAT).await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+
+            // The report closed on its own isn't closed again
+            assert_eq!(closure_messages(api, &first).await, 1);
+            assert_eq!(
+                report_notifications(USER_USER_ID_PARSED, pool).await,
+                1
+            );
+            assert_eq!(closure_messages(api, &second).await, 1);
+            assert_eq!(
+                report_notifications(FRIEND_USER_ID_PARSED, pool).await,
+                1
+            );
+
+            for report in [&first, &second] {
+                let resp = api
+                    .get_report(report["id"].as_str().unwrap(), MOD_USER_PAT)
+                    .await;
+                assert_status!(&resp, StatusCode::OK);
+                let report: Value = test::read_body_json(resp).await;
+                assert_eq!(report["closed"], true);
+            }
+            assert_eq!(closure_messages(api, &other).await, 0);
+
+            let resp = close_group(api, key, MOD_USER_PAT).await;
+            assert_status!(&resp, StatusCode::NOT_FOUND);
+
+            let groups = get_groups(api, "", MOD_USER_PAT).await;
+            assert_eq!(groups.as_array().unwrap().len(), 1);
+            assert_eq!(groups[0]["reports"], json!([other["id"]]));
+        },
+    )
+    .await;
+}
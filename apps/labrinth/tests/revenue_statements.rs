@@ -0,0 +1,191 @@
+use actix_http::StatusCode;
+use actix_web::test;
+use chrono::{DateTime, TimeZone, Utc};
+use common::api_common::{Api, AppendsOptionalPat};
+use common::api_v3::ApiV3;
+use common::database::{
+    FRIEND_USER_ID_PARSED, FRIEND_USER_PAT, USER_USER_ID_PARSED, USER_USER_PAT,
+};
+use common::environment::{TestEnvironment, with_test_environment};
+use labrinth::queue::payouts;
+use rust_decimal::Decimal;
+use serde_json::Value;
+
+pub mod common;
+
+fn date(month: u32, day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, month, day, 12, 0, 0).unwrap()
+}
+
+fn decimal(value: &Value) -> Decimal {
+    serde_json::from_value(value.clone()).unwrap()
+}
+
+async fn insert_revenue(
+    test_env: &TestEnvironment<ApiV3>,
+    project_id: i64,
+    payouts: &[(i64, i64, DateTime<Utc>)],
+) {
+    let mut transaction = test_env.db.pool.begin().await.unwrap();
+    payouts::insert_payouts(
+        payouts.iter().map(|(user_id, _, _)| *user_id).collect(),
+        vec![project_id; payouts.len()],
+        payouts
+            .iter()
+            .map(|(_, amount, _)| Decimal::from(*amount))
+            .collect(),
+        payouts.iter().map(|(_, _, created)| *created).collect(),
+        payouts.iter().map(|(_, _, created)| *created).collect(),
+        &mut transaction,
+    )
+    .await
+    .unwrap();
+    transaction.commit().await.unwrap();
+}
+
+#[actix_rt::test]
+pub async fn revenue_statements_list_and_export() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let alpha = &test_env.dummy.project_alpha;
+
+            insert_revenue(
+                &test_env,
+                alpha.project_id_parsed.0 as i64,
+                &[
+                    (USER_USER_ID_PARSED, 10, date(1, 10)),
+                    (USER_USER_ID_PARSED, 5, date(1, 20)),
+                    (FRIEND_USER_ID_PARSED, 3, date(1, 20)),
+                    (USER_USER_ID_PARSED, 7, date(2, 3)),
+                ],
+            )
+            .await;
+
+            let resp = api
+                .call(
+                    test::TestRequest::get()
+                        .uri("/v3/payout/statements")
+                        .append_pat(USER_USER_PAT)
+                        .to_request(),
+                )
+                .await;
+            assert_status!(&resp, StatusCode::OK);
+            let statements: Vec<Value> = test::read_body_json(resp).await;
+            assert_eq!(statements.len(), 2);
+            // Most recent first
+            assert_eq!(statements[0]["month"], "2025-02-01");
+            assert_eq!(decimal(&statements[0]["net"]), Decimal::from(7));
+            assert_eq!(statements[1]["month"], "2025-01-01");
+            assert_eq!(statements[1]["project_id"], alpha.project_id.as_str());
+            assert_eq!(decimal(&statements[1]["gross"]), Decimal::from(18));
+            assert_eq!(decimal(&statements[1]["net"]), Decimal::from(15));
+
+            let statement_uri =
+                format!("/v3/payout/statements/{}/2025-01", alpha.project_id);
+
+            let resp = api
+                .call(
+                    test::TestRequest::get()
+                        .uri(&statement_uri)
+                        .append_pat(USER_USER_PAT)
+                        .to_request(),
+                )
+                .await;
+            assert_status!(&resp, StatusCode::OK);
+            let statement: Value = test::read_body_json(resp).await;
+            assert_eq!(statement["month"], "2025-01-01");
+            assert_eq!(decimal(&statement["gross"]), Decimal::from(18));
+            assert_eq!(decimal(&statement["net"]), Decimal::from(15));
+            let days = statement["days"].as_array().unwrap();
+            assert_eq!(days.len(), 2);
+            assert_eq!(days[0]["date"], "2025-01-10");
+            assert_eq!(decimal(&days[1]["gross"]), Decimal::from(8));
+            assert_eq!(decimal(&days[1]["net"]), Decimal::from(5));
+
+            let resp = api
+                .call(
+                    test::TestRequest::get()
+                        .uri(&format!("{statement_uri}?format=csv"))
+                        .append_pat(USER_USER_PAT)
+                        .to_request(),
+                )
+                .await;
+            assert_status!(&resp, StatusCode::OK);
+            assert_eq!(resp.headers().get("content-type").unwrap(), "text/csv");
+            let csv = String::from_utf8(test::read_body(resp).await.to_vec())
+                .unwrap();
+            let mut lines = csv.lines();
+            assert_eq!(lines.next(), Some("date,gross,net"));
+            let rows = lines
+                .map(|line| {
+                    let values = line.split(',').collect::<Vec<_>>();
+                    (
+                        values[0].to_owned(),
+                        values[1].parse::<Decimal>().unwrap(),
+                        values[2].parse::<Decimal>().unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                rows,
+                [
+                    (
+                        "2025-01-10".to_owned(),
+                        Decimal::from(10),
+                        Decimal::from(10)
+                    ),
+                    (
+                        "2025-01-20".to_owned(),
+                        Decimal::from(8),
+                        Decimal::from(5)
+                    ),
+                ]
+            );
+
+            // Months without revenue, and other users' months, have no
+            // statement
+            for (uri, pat) in [
+                (
+                    format!(
+                        "/v3/payout/statements/{}/2025-03",
+                        alpha.project_id
+                    ),
+                    USER_USER_PAT,
+                ),
+                (
+                    format!(
+                        "/v3/payout/statements/{}/2025-02",
+                        alpha.project_id
+                    ),
+                    FRIEND_USER_PAT,
+                ),
+            ] {
+                let resp = api
+                    .call(
+                        test::TestRequest::get()
+                            .uri(&uri)
+                            .append_pat(pat)
+                            .to_request(),
+                    )
+                    .await;
+                assert_status!(&resp, StatusCode::NOT_FOUND);
+            }
+
+            let resp = api
+                .call(
+                    test::TestRequest::get()
+                        .uri(&format!(
+                            "/v3/payout/statements/{}/January",
+                            alpha.project_id
+                        ))
+                        .append_pat(USER_USER_PAT)
+                        .to_request(),
+                )
+                .await;
+            assert_status!(&resp, StatusCode::BAD_REQUEST);
+        },
+    )
+    .await;
+}
@@ -1,5 +1,6 @@
 use crate::common::{api_common::ApiTeams, database::*};
 use actix_http::StatusCode;
+use chrono::{Duration, Utc};
 use common::{
     api_v3::ApiV3,
     environment::{
@@ -442,6 +443,76 @@ async fn test_patch_project_team_member() {
     }).await;
 }
 
+#[actix_rt::test]
+async fn test_schedule_payouts_split() {
+    with_test_environment(
+        None,
+        |test_env: TestEnvironment<ApiV3>| async move {
+            let api = &test_env.api;
+            let alpha_team_id = &test_env.dummy.project_alpha.team_id;
+
+            let get_payouts_split = || async {
+                api.get_team_members_deserialized_common(
+                    alpha_team_id,
+                    USER_USER_PAT,
+                )
+                .await
+                .into_iter()
+                .find(|x| x.user.id.0 == USER_USER_ID_PARSED as u64)
+                .unwrap()
+                .payouts_split
+            };
+            let payouts_split = get_payouts_split().await;
+
+            // Revenue already earned can't be split differently
+            let resp = api
+                .edit_team_member(
+                    alpha_team_id,
+                    USER_USER_ID,
+                    json!({
+                        "payouts_split": 50,
+                        "payouts_split_effective_from":
+                            Utc::now() - Duration::hours(1),
+                    }),
+                    USER_USER_PAT,
+                )
+                .await;
+            assert_status!(&resp, StatusCode::BAD_REQUEST);
+
+            // A date needs a split to schedule
+            let resp = api
+                .edit_team_member(
+                    alpha_team_id,
+                    USER_USER_ID,
+                    json!({
+                        "payouts_split_effective_from":
+                            Utc::now() + Duration::days(1),
+                    }),
+                    USER_USER_PAT,
+                )
+                .await;
+            assert_status!(&resp, StatusCode::BAD_REQUEST);
+
+            // A future split is scheduled without changing the current one
+            let resp = api
+                .edit_team_member(
+                    alpha_team_id,
+                    USER_USER_ID,
+                    json!({
+                        "payouts_split": 50,
+                        "payouts_split_effective_from":
+                            Utc::now() + Duration::days(1),
+                    }),
+                    USER_USER_PAT,
+                )
+                .await;
+            assert_status!(&resp, StatusCode::NO_CONTENT);
+            assert_eq!(get_payouts_split().await, payouts_split);
+        },
+    )
+    .await;
+}
+
 // edit team member (Varying permissions, varying roles)
 #[actix_rt::test]
 async fn test_patch_organization_team_member() {
@@ -0,0 +1,4 @@
+CREATE TABLE project_signing_key_pins (
+	project_id TEXT PRIMARY KEY NOT NULL,
+	fingerprint TEXT NOT NULL
+);
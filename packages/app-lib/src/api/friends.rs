@@ -1,5 +1,18 @@
-use crate::state::{FriendsSocket, UserFriend};
+use crate::ErrorKind;
+use crate::api::instance::QuickPlayType;
+use crate::api::server_address::ServerAddress;
+pub use crate::event::WorldInvitePayload;
+use crate::install::InstallJobSnapshot;
+use crate::state::{
+    ContentSet, FriendsSocket, InstanceLink, InstanceMetadata, ModLoader,
+    ProjectType, ReceivedWorldInvite, UserFriend,
+};
+use ariadne::ids::UserId;
+use ariadne::ids::base62_impl::parse_base62;
+use ariadne::networking::message::WorldInvite;
 use ariadne::users::UserStatus;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 #[tracing::instrument]
 pub async fn friends() -> crate::Result<Vec<UserFriend>> {
@@ -34,3 +47,261 @@ pub async fn remove_friend(user_id: &str) -> crate::Result<()> {
 
     Ok(())
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WorldInviteInstance {
+    pub instance_id: String,
+    /// Whether the instance has the same enabled mods as the host's.
+    /// Instances with different mods may still be able to join.
+    pub content_matches: bool,
+}
+
+/// Invites a friend to a world in an instance which is open to LAN on
+/// `port`.
+#[tracing::instrument]
+pub async fn invite_to_world(
+    instance_id: &str,
+    world_name: &str,
+    port: u16,
+    user_id: &str,
+) -> crate::Result<()> {
+    let state = crate::State::get().await?;
+    let friend = parse_user_id(user_id)?;
+    let instance = get_instance(instance_id).await?;
+    let content_hash = content_hash(instance_id, &state).await?;
+
+    let content_set = instance.applied_content_set;
+    state
+        .friends_socket
+        .invite_to_world(friend, port, |socket| WorldInvite {
+            socket,
+            world_name: world_name.to_string(),
+            game_version: content_set.game_version,
+            loader: content_set.loader.as_str().to_string(),
+            loader_version: content_set.loader_version,
+            content_hash,
+        })
+        .await
+}
+
+#[tracing::instrument]
+pub async fn stop_hosting_world(port: u16) -> crate::Result<()> {
+    let state = crate::State::get().await?;
+    state.friends_socket.stop_hosting_world(port).await
+}
+
+pub async fn world_invites() -> crate::Result<Vec<WorldInvitePayload>> {
+    let state = crate::State::get().await?;
+    Ok(state
+        .friends_socket
+        .world_invites()
+        .into_iter()
+        .map(|x| WorldInvitePayload::new(x.from, &x.invite))
+        .collect())
+}
+
+/// Lists the instances which can join a world invite, having its game
+/// version and loader.
+#[tracing::instrument]
+pub async fn world_invite_instances(
+    socket: Uuid,
+) -> crate::Result<Vec<WorldInviteInstance>> {
+    let state = crate::State::get().await?;
+    let invite = get_world_invite(&state.friends_socket, socket)?.invite;
+
+    let mut instances = Vec::new();
+    for instance in crate::api::instance::list().await? {
+        if !is_compatible(&instance.applied_content_set, &invite) {
+            continue;
+        }
+        let instance_id = instance.instance.id;
+        let content_matches =
+            content_hash(&instance_id, &state).await? == invite.content_hash;
+        instances.push(WorldInviteInstance {
+            instance_id,
+            content_matches,
+        });
+    }
+
+    Ok(instances)
+}
+
+/// Creates an instance with a world invite's game version and loader.
+#[tracing::instrument]
+pub async fn create_world_invite_instance(
+    socket: Uuid,
+) -> crate::Result<InstallJobSnapshot> {
+    let state = crate::State::get().await?;
+    let invite = get_world_invite(&state.friends_socket, socket)?.invite;
+
+    crate::install::create_instance(
+        invite.world_name,
+        invite.game_version,
+        ModLoader::from_string(&invite.loader),
+        invite.loader_version,
+        None,
+        None,
+        InstanceLink::Unmanaged,
+    )
+    .await
+}
+
+/// Launches an instance into the world from an invite, connecting through
+/// the friends tunnel.
+#[tracing::instrument]
+pub async fn join_world_invite(
+    socket: Uuid,
+    instance_id: &str,
+) -> crate::Result<()> {
+    let state = crate::State::get().await?;
+    let invite = get_world_invite(&state.friends_socket, socket)?.invite;
+    let instance = get_instance(instance_id).await?;
+    if !is_compatible(&instance.applied_content_set, &invite) {
+        return Err(ErrorKind::InputError(
+            "This instance's game version or loader doesn't match the world"
+                .to_string(),
+        )
+        .into());
+    }
+
+    let address = state.friends_socket.join_world(socket).await?;
+    crate::api::instance::run(
+        instance_id,
+        QuickPlayType::Server(ServerAddress::Unresolved(address.to_string())),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument]
+pub async fn decline_world_invite(socket: Uuid) -> crate::Result<()> {
+    let state = crate::State::get().await?;
+    state.friends_socket.decline_world_invite(socket);
+
+    Ok(())
+}
+
+fn parse_user_id(user_id: &str) -> crate::Result<UserId> {
+    parse_base62(user_id).map(UserId).map_err(|_| {
+        ErrorKind::InputError(format!("Invalid user ID: {user_id}")).into()
+    })
+}
+
+fn get_world_invite(
+    friends_socket: &FriendsSocket,
+    socket: Uuid,
+) -> crate::Result<ReceivedWorldInvite> {
+    friends_socket.world_invite(socket).ok_or_else(|| {
+        ErrorKind::InputError("This world invite has expired".to_string())
+            .into()
+    })
+}
+
+async fn get_instance(instance_id: &str) -> crate::Result<InstanceMetadata> {
+    crate::api::instance::get(instance_id)
+        .await?
+        .ok_or_else(|| {
+            ErrorKind::UnmanagedInstanceError(instance_id.to_string()).into()
+        })
+}
+
+fn is_compatible(content_set: &ContentSet, invite: &WorldInvite) -> bool {
+    content_set.game_version == invite.game_version
+        && content_set.loader.as_str() == invite.loader
+        && content_set.loader_version == invite.loader_version
+}
+
+/// Hashes the enabled mods in an instance, so that a guest can tell whether
+/// theirs match the host's without listing them.
+async fn content_hash(
+    instance_id: &str,
+    state: &crate::State,
+) -> crate::Result<String> {
+    let hashes =
+        crate::state::get_content_projects(instance_id, None, None, state)
+            .await?
+            .into_iter()
+            .filter(|(_, file)| {
+                file.enabled && file.project_type == ProjectType::Mod
+            })
+            .map(|(_, file)| file.hash)
+            .collect();
+
+    Ok(hash_content(hashes))
+}
+
+fn hash_content(mut hashes: Vec<String>) -> String {
+    hashes.sort_unstable();
+    sha1_smol::Sha1::from(hashes.join("\n")).hexdigest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{ContentSetStatus, ContentSourceKind};
+    use chrono::Utc;
+
+    fn content_set() -> ContentSet {
+        ContentSet {
+            id: "content-set".to_string(),
+            instance_id: "instance".to_string(),
+            name: "Fabric 1.21.8".to_string(),
+            source_kind: ContentSourceKind::Local,
+            status: ContentSetStatus::Available,
+            game_version: "1.21.8".to_string(),
+            protocol_version: None,
+            loader: ModLoader::Fabric,
+            loader_version: Some("0.17.2".to_string()),
+            created: Utc::now(),
+            modified: Utc::now(),
+        }
+    }
+
+    fn invite() -> WorldInvite {
+        WorldInvite {
+            socket: Uuid::new_v4(),
+            world_name: "New World".to_string(),
+            game_version: "1.21.8".to_string(),
+            loader: "fabric".to_string(),
+            loader_version: Some("0.17.2".to_string()),
+            content_hash: hash_content(Vec::new()),
+        }
+    }
+
+    #[test]
+    fn compatible_instances_match_game_version_and_loader() {
+        assert!(is_compatible(&content_set(), &invite()));
+
+        let other_game_version = ContentSet {
+            game_version: "1.21.7".to_string(),
+            ..content_set()
+        };
+        assert!(!is_compatible(&other_game_version, &invite()));
+
+        let other_loader = ContentSet {
+            loader: ModLoader::Quilt,
+            ..content_set()
+        };
+        assert!(!is_compatible(&other_loader, &invite()));
+
+        let other_loader_version = ContentSet {
+            loader_version: None,
+            ..content_set()
+        };
+        assert!(!is_compatible(&other_loader_version, &invite()));
+    }
+
+    #[test]
+    fn content_hash_ignores_order() {
+        let hashes = ["a".to_string(), "b".to_string(), "c".to_string()];
+        let mut reversed = hashes.to_vec();
+        reversed.reverse();
+
+        assert_eq!(hash_content(hashes.to_vec()), hash_content(reversed));
+        assert_ne!(
+            hash_content(hashes.to_vec()),
+            hash_content(hashes[..2].to_vec())
+        );
+    }
+}
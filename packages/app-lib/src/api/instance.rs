@@ -2,7 +2,9 @@
 
 mod content;
 mod content_set_diff;
+mod export_mmc;
 mod export_mrpack;
+mod export_packwiz;
 mod get;
 mod groups;
 mod icon;
@@ -19,10 +21,12 @@ pub use self::content::{
     get_linked_modpack_content, get_linked_modpack_info, get_projects,
     list_content_sets, refresh_content_updates, sync_content_files,
 };
+pub use self::export_mmc::export_mmc;
 pub use self::export_mrpack::{
     PackExportCandidate, create_mrpack_json, export_mrpack,
     get_pack_export_candidates, get_pack_export_candidates_for_parent,
 };
+pub use self::export_packwiz::export_packwiz;
 pub use self::get::{get, get_many, list};
 pub use self::groups::{
     FAVORITES_GROUP_ID, InstanceGroup, InstanceGroupMembershipUpdate,
@@ -53,10 +57,10 @@ pub use self::run::{
 };
 pub(crate) use self::shared::{
     CONFIG_BUNDLE_FILE_TYPE, CONFIG_DIRECTORY, CONFIG_FILE_EXTENSIONS,
-    CONFIG_SYNC_ENABLED, MAX_CONFIG_BUNDLE_ENTRIES,
-    read_bounded_config_bundle_entry,
+    CONFIG_SYNC_ENABLED, merge_config_bundle, save_config_base,
 };
 pub use self::shared::{
+    SharedInstanceConfigConflict, SharedInstanceConfigResolution,
     SharedInstanceExternalFilePreview, SharedInstanceInstallPreview,
     SharedInstanceInvite, SharedInstanceInviteInstallPreview,
     SharedInstanceInviteLink, SharedInstanceJoinType,
@@ -0,0 +1,180 @@
+use super::export_mrpack::{
+    EXPORT_COPY_BUFFER_SIZE, ExportSelection, collect_export_files,
+    ensure_standard_zip_file_size,
+};
+use super::get::get;
+use super::paths::get_full_path;
+use crate::event::LoadingBarType;
+use crate::event::emit::{emit_loading, init_loading};
+use crate::state::{InstanceMetadata, ModLoader, State};
+use crate::util::io::IOError;
+use path_util::SafeRelativeUtf8UnixPathBuf;
+use serde_json::json;
+use std::collections::HashSet;
+use std::io::{Read, Seek, Write};
+use std::path::PathBuf;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Exports an instance as a MultiMC/Prism Launcher instance zip, which both
+/// launchers can import directly.
+#[tracing::instrument(skip_all)]
+pub async fn export_mmc(
+    instance_id: &str,
+    export_path: PathBuf,
+    included_export_candidates: Vec<String>,
+    excluded_export_candidates: Vec<String>,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    let _permit: tokio::sync::SemaphorePermit =
+        state.io_semaphore.0.acquire().await?;
+    let metadata = get(instance_id).await?.ok_or_else(|| {
+        crate::ErrorKind::OtherError(format!(
+            "Tried to export a nonexistent instance {instance_id}!"
+        ))
+    })?;
+    let export_selection = ExportSelection::new(
+        included_export_candidates,
+        excluded_export_candidates,
+    );
+
+    let instance_base_path = get_full_path(instance_id).await?;
+    let files = collect_export_files(
+        &instance_base_path,
+        &export_selection,
+        &HashSet::new(),
+    )
+    .await?;
+    for (_, _, size) in &files {
+        ensure_standard_zip_file_size(*size)?;
+    }
+
+    let total_bytes = files
+        .iter()
+        .fold(1_u64, |total, (_, _, size)| total.saturating_add(*size));
+    let loading_bar = init_loading(
+        LoadingBarType::PackExport {
+            instance_id: metadata.instance.id.clone(),
+            instance_name: metadata.instance.name.clone(),
+        },
+        total_bytes as f64,
+        "Exporting instance to MultiMC",
+    )
+    .await?;
+
+    let instance_cfg = create_instance_cfg(&metadata);
+    let mmc_pack = serde_json::to_vec_pretty(&create_mmc_pack(&metadata))?;
+    tokio::task::spawn_blocking(move || {
+        let file = std::fs::File::create(&export_path)
+            .map_err(|error| IOError::with_path(error, &export_path))?;
+        write_mmc_archive(
+            file,
+            files,
+            instance_cfg.as_bytes(),
+            &mmc_pack,
+            |bytes_written| {
+                emit_loading(&loading_bar, bytes_written as f64, None)
+            },
+        )
+    })
+    .await??;
+
+    Ok(())
+}
+
+fn create_instance_cfg(metadata: &InstanceMetadata) -> String {
+    // Newlines would end the INI value early
+    let name = metadata.instance.name.replace(['\r', '\n'], " ");
+
+    format!("[General]\nConfigVersion=1.2\nInstanceType=OneSix\nname={name}\n")
+}
+
+// https://github.com/PrismLauncher/PrismLauncher/blob/develop/launcher/minecraft/PackProfile.cpp
+fn create_mmc_pack(metadata: &InstanceMetadata) -> serde_json::Value {
+    let game_version = &metadata.applied_content_set.game_version;
+    let mut components = vec![json!({
+        "uid": "net.minecraft",
+        "version": game_version,
+        "important": true,
+    })];
+
+    if let Some(loader_version) = &metadata.applied_content_set.loader_version {
+        let loader_uid = match metadata.applied_content_set.loader {
+            ModLoader::Fabric => Some("net.fabricmc.fabric-loader"),
+            ModLoader::Quilt => Some("org.quiltmc.quilt-loader"),
+            ModLoader::Forge => Some("net.minecraftforge"),
+            ModLoader::NeoForge => Some("net.neoforged"),
+            ModLoader::Vanilla => None,
+        };
+
+        if matches!(
+            metadata.applied_content_set.loader,
+            ModLoader::Fabric | ModLoader::Quilt
+        ) {
+            components.push(json!({
+                "uid": "net.fabricmc.intermediary",
+                "version": game_version,
+                "dependencyOnly": true,
+            }));
+        }
+        if let Some(loader_uid) = loader_uid {
+            components.push(json!({
+                "uid": loader_uid,
+                "version": loader_version,
+            }));
+        }
+    }
+
+    json!({
+        "components": components,
+        "formatVersion": 1,
+    })
+}
+
+fn write_mmc_archive<W, F>(
+    writer: W,
+    files: Vec<(PathBuf, SafeRelativeUtf8UnixPathBuf, u64)>,
+    instance_cfg: &[u8],
+    mmc_pack: &[u8],
+    mut emit_progress: F,
+) -> crate::Result<()>
+where
+    W: Write + Seek,
+    F: FnMut(u64) -> crate::Result<()>,
+{
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated);
+    let mut writer = ZipWriter::new(writer);
+    let mut buffer = vec![0_u8; EXPORT_COPY_BUFFER_SIZE];
+
+    for (path, relative_path, _) in files {
+        writer
+            .start_file(format!(".minecraft/{relative_path}"), options)
+            .map_err(std::io::Error::from)?;
+        let mut source = std::fs::File::open(&path)
+            .map_err(|error| IOError::with_path(error, &path))?;
+        loop {
+            let bytes_read = source
+                .read(&mut buffer)
+                .map_err(|error| IOError::with_path(error, &path))?;
+            if bytes_read == 0 {
+                break;
+            }
+            writer.write_all(&buffer[..bytes_read])?;
+            emit_progress(bytes_read as u64)?;
+        }
+    }
+
+    writer
+        .start_file("instance.cfg", options)
+        .map_err(std::io::Error::from)?;
+    writer.write_all(instance_cfg)?;
+    writer
+        .start_file("mmc-pack.json", options)
+        .map_err(std::io::Error::from)?;
+    writer.write_all(mmc_pack)?;
+    writer.finish().map_err(std::io::Error::from)?;
+    emit_progress(1)?;
+
+    Ok(())
+}
@@ -29,7 +29,7 @@ const DEFAULT_SELECTED_EXPORT_PATH_PREFIXES: &[&str] = &[
     "config",
 ];
 const EXPORT_CANDIDATE_METADATA_CONCURRENCY: usize = 32;
-const EXPORT_COPY_BUFFER_SIZE: usize = 256 * 1024;
+pub(super) const EXPORT_COPY_BUFFER_SIZE: usize = 256 * 1024;
 const STANDARD_ZIP_FILE_SIZE_ERROR: &str = "Your modpack cannot be exported as it contains a file over the size limit of 4 GB";
 
 const NEVER_EXPORTABLE_PATH_PREFIXES: &[&str] = &[
@@ -81,12 +81,15 @@ struct ExportSelectionNode {
 }
 
 #[derive(Default)]
-struct ExportSelection {
+pub(super) struct ExportSelection {
     root: ExportSelectionNode,
 }
 
 impl ExportSelection {
-    fn new(included_paths: Vec<String>, excluded_paths: Vec<String>) -> Self {
+    pub(super) fn new(
+        included_paths: Vec<String>,
+        excluded_paths: Vec<String>,
+    ) -> Self {
         let mut rules = HashMap::new();
 
         for (paths, selected) in
@@ -111,7 +114,10 @@ impl ExportSelection {
         selection
     }
 
-    fn is_included(&self, path: &SafeRelativeUtf8UnixPathBuf) -> bool {
+    pub(super) fn is_included(
+        &self,
+        path: &SafeRelativeUtf8UnixPathBuf,
+    ) -> bool {
         self.resolve(path).0
     }
 
@@ -195,7 +201,49 @@ pub async fn export_mrpack(
         .map(|file| file.path.as_str().to_string())
         .collect::<HashSet<_>>();
 
-    let mut override_files = Vec::new();
+    let override_files = collect_export_files(
+        &instance_base_path,
+        &export_selection,
+        &packfile_paths,
+    )
+    .await?;
+    for (_, _, size) in &override_files {
+        ensure_standard_zip_file_size(*size)?;
+    }
+
+    let total_bytes = override_files
+        .iter()
+        .fold(1_u64, |total, (_, _, size)| total.saturating_add(*size));
+    let loading_bar = init_loading(
+        LoadingBarType::PackExport {
+            instance_id: metadata.instance.id.clone(),
+            instance_name: metadata.instance.name.clone(),
+        },
+        total_bytes as f64,
+        "Exporting instance to .mrpack",
+    )
+    .await?;
+    let data = serde_json::to_vec_pretty(&packfile)?;
+    tokio::task::spawn_blocking(move || {
+        let file = std::fs::File::create(&export_path)
+            .map_err(|error| IOError::with_path(error, &export_path))?;
+        write_mrpack_archive(file, override_files, &data, |bytes_written| {
+            emit_loading(&loading_bar, bytes_written as f64, None)
+        })
+    })
+    .await??;
+
+    Ok(())
+}
+
+/// Walks the instance directory and returns every selected, exportable file
+/// that isn't in `skipped_paths`, along with its relative path and size.
+pub(super) async fn collect_export_files(
+    instance_base_path: &PathBuf,
+    export_selection: &ExportSelection,
+    skipped_paths: &HashSet<String>,
+) -> crate::Result<Vec<(PathBuf, SafeRelativeUtf8UnixPathBuf, u64)>> {
+    let mut files = Vec::new();
     let mut directories = vec![instance_base_path.clone()];
     while let Some(directory) = directories.pop() {
         let mut read_dir = io::read_dir(&directory).await?;
@@ -206,7 +254,7 @@ pub async fn export_mrpack(
         {
             let path = entry.path();
             let relative_path =
-                pack_get_relative_path(&instance_base_path, &path)?;
+                pack_get_relative_path(instance_base_path, &path)?;
             if !is_path_exportable(&relative_path) {
                 continue;
             }
@@ -223,7 +271,7 @@ pub async fn export_mrpack(
             }
             if !file_type.is_file()
                 || !export_selection.is_included(&relative_path)
-                || packfile_paths.contains(relative_path.as_str())
+                || skipped_paths.contains(relative_path.as_str())
             {
                 continue;
             }
@@ -233,37 +281,14 @@ pub async fn export_mrpack(
                 .await
                 .map_err(|e| IOError::with_path(e, &path))?
                 .len();
-            ensure_standard_zip_file_size(size)?;
-            override_files.push((path, relative_path, size));
+            files.push((path, relative_path, size));
         }
     }
 
-    let total_bytes = override_files
-        .iter()
-        .fold(1_u64, |total, (_, _, size)| total.saturating_add(*size));
-    let loading_bar = init_loading(
-        LoadingBarType::PackExport {
-            instance_id: metadata.instance.id.clone(),
-            instance_name: metadata.instance.name.clone(),
-        },
-        total_bytes as f64,
-        "Exporting instance to .mrpack",
-    )
-    .await?;
-    let data = serde_json::to_vec_pretty(&packfile)?;
-    tokio::task::spawn_blocking(move || {
-        let file = std::fs::File::create(&export_path)
-            .map_err(|error| IOError::with_path(error, &export_path))?;
-        write_mrpack_archive(file, override_files, &data, |bytes_written| {
-            emit_loading(&loading_bar, bytes_written as f64, None)
-        })
-    })
-    .await??;
-
-    Ok(())
+    Ok(files)
 }
 
-fn ensure_standard_zip_file_size(size: u64) -> crate::Result<()> {
+pub(super) fn ensure_standard_zip_file_size(size: u64) -> crate::Result<()> {
     if size > zip::ZIP64_BYTES_THR {
         return Err(crate::ErrorKind::OtherError(
             STANDARD_ZIP_FILE_SIZE_ERROR.to_string(),
@@ -317,7 +342,9 @@ where
     Ok(())
 }
 
-fn is_path_exportable(relative_path: &SafeRelativeUtf8UnixPathBuf) -> bool {
+pub(super) fn is_path_exportable(
+    relative_path: &SafeRelativeUtf8UnixPathBuf,
+) -> bool {
     let path = relative_path.as_str();
 
     !NEVER_EXPORTABLE_PATH_PREFIXES.iter().any(|prefix| {
@@ -460,7 +487,7 @@ fn pack_get_relative_path(
     )?)
 }
 
-fn get_mrpack_environment(
+pub(super) fn get_mrpack_environment(
     environment: Option<VersionEnvironment>,
 ) -> HashMap<EnvType, SideType> {
     let (client, server) =
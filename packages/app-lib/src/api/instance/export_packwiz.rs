@@ -0,0 +1,297 @@
+use super::content::get_projects;
+use super::export_mrpack::{
+    ExportSelection, collect_export_files, get_mrpack_environment,
+    is_path_exportable,
+};
+use super::get::get;
+use super::paths::get_full_path;
+use crate::event::LoadingBarType;
+use crate::event::emit::{emit_loading, init_loading};
+use crate::pack::import::packwiz::{
+    METAFILE_SUFFIX, PACK_FILE_NAME, PackwizDownload, PackwizIndex,
+    PackwizIndexFile, PackwizIndexRef, PackwizMetafile, PackwizModrinthUpdate,
+    PackwizPack, PackwizUpdate,
+};
+use crate::pack::install_from::EnvType;
+use crate::state::{CacheBehaviour, CachedEntry, ModLoader, SideType, State};
+use crate::util::io::{self, IOError};
+use path_util::SafeRelativeUtf8UnixPathBuf;
+use sha2::Digest;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+const INDEX_FILE_NAME: &str = "index.toml";
+const PACKWIZ_HASH_FORMAT: &str = "sha256";
+const PACKWIZ_PACK_FORMAT: &str = "packwiz:1.1.0";
+
+/// Exports an instance as a packwiz tree into `export_path`.
+///
+/// Content hosted on Modrinth is written as `.pw.toml` metafiles that point
+/// at the Modrinth version, everything else is copied into the tree as-is.
+#[tracing::instrument(skip_all)]
+pub async fn export_packwiz(
+    instance_id: &str,
+    export_path: PathBuf,
+    included_export_candidates: Vec<String>,
+    excluded_export_candidates: Vec<String>,
+    version_id: Option<String>,
+    description: Option<String>,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    let _permit: tokio::sync::SemaphorePermit =
+        state.io_semaphore.0.acquire().await?;
+    let metadata = get(instance_id).await?.ok_or_else(|| {
+        crate::ErrorKind::OtherError(format!(
+            "Tried to export a nonexistent instance {instance_id}!"
+        ))
+    })?;
+    let export_selection = ExportSelection::new(
+        included_export_candidates,
+        excluded_export_candidates,
+    );
+    let instance_base_path = get_full_path(instance_id).await?;
+
+    let projects = get_projects(
+        &metadata.instance.id,
+        Some(CacheBehaviour::MustRevalidate),
+    )
+    .await?
+    .into_iter()
+    .filter(|(_, file)| file.enabled)
+    .filter_map(|(path, file)| {
+        let path = SafeRelativeUtf8UnixPathBuf::try_from(path).ok()?;
+        (is_path_exportable(&path) && export_selection.is_included(&path))
+            .then_some((path, file.hash, file.metadata?))
+    })
+    .collect::<Vec<_>>();
+    let version_ids = projects
+        .iter()
+        .map(|(_, _, metadata)| &*metadata.version_id)
+        .collect::<Vec<_>>();
+    let versions = CachedEntry::get_version_v3_many(
+        &version_ids,
+        Some(CacheBehaviour::MustRevalidate),
+        &state.pool,
+        &state.api_semaphore,
+    )
+    .await?;
+    let project_ids = projects
+        .iter()
+        .map(|(_, _, metadata)| &*metadata.project_id)
+        .collect::<Vec<_>>();
+    let project_titles = CachedEntry::get_project_many(
+        &project_ids,
+        Some(CacheBehaviour::StaleWhileRevalidateSkipOffline),
+        &state.pool,
+        &state.api_semaphore,
+    )
+    .await?
+    .into_iter()
+    .map(|project| (project.id, project.title))
+    .collect::<HashMap<_, _>>();
+
+    let mut metafiles = Vec::new();
+    for (path, hash, file_metadata) in projects {
+        let Some(version) =
+            versions.iter().find(|x| x.id == file_metadata.version_id)
+        else {
+            continue;
+        };
+        let Some(file) = version.files.iter().find(|file| {
+            file.hashes
+                .get("sha1")
+                .is_some_and(|file_hash| file_hash == &hash)
+        }) else {
+            continue;
+        };
+        let Some(sha512) = file.hashes.get("sha512") else {
+            continue;
+        };
+
+        let env = get_mrpack_environment(version.environment);
+        let side = match (&env[&EnvType::Client], &env[&EnvType::Server]) {
+            (SideType::Unsupported, _) => "server",
+            (_, SideType::Unsupported) => "client",
+            _ => "both",
+        };
+        let file_name = path
+            .as_str()
+            .rsplit_once('/')
+            .map_or(path.as_str(), |(_, name)| name)
+            .to_string();
+        let metafile_path = metafile_path(path.as_str());
+
+        metafiles.push((
+            path,
+            metafile_path,
+            PackwizMetafile {
+                name: project_titles
+                    .get(&file_metadata.project_id)
+                    .cloned()
+                    .unwrap_or_else(|| file_name.clone()),
+                filename: file_name,
+                side: Some(side.to_string()),
+                download: PackwizDownload {
+                    url: Some(file.url.clone()),
+                    hash_format: "sha512".to_string(),
+                    hash: sha512.clone(),
+                    mode: None,
+                },
+                option: None,
+                update: Some(PackwizUpdate {
+                    modrinth: Some(PackwizModrinthUpdate {
+                        mod_id: file_metadata.project_id,
+                        version: file_metadata.version_id,
+                    }),
+                }),
+            },
+        ));
+    }
+
+    let metafile_content_paths = metafiles
+        .iter()
+        .map(|(path, _, _)| path.as_str().to_string())
+        .collect::<HashSet<_>>();
+    let files = collect_export_files(
+        &instance_base_path,
+        &export_selection,
+        &metafile_content_paths,
+    )
+    .await?;
+
+    let total = (files.len() + metafiles.len() + 2) as f64;
+    let loading_bar = init_loading(
+        LoadingBarType::PackExport {
+            instance_id: metadata.instance.id.clone(),
+            instance_name: metadata.instance.name.clone(),
+        },
+        total,
+        "Exporting instance to packwiz",
+    )
+    .await?;
+
+    let mut index_files = Vec::with_capacity(files.len() + metafiles.len());
+    for (source, relative_path, _) in files {
+        // Keep the exported tree from being mistaken for packwiz metadata
+        if matches!(relative_path.as_str(), PACK_FILE_NAME | INDEX_FILE_NAME)
+            || relative_path.as_str().ends_with(METAFILE_SUFFIX)
+        {
+            continue;
+        }
+
+        let bytes = io::read(&source).await?;
+        write_packwiz_file(&export_path, relative_path.as_str(), &bytes)
+            .await?;
+        index_files.push(PackwizIndexFile {
+            file: relative_path.as_str().to_string(),
+            hash: sha256_hex(&bytes),
+            hash_format: None,
+            alias: None,
+            metafile: false,
+        });
+        emit_loading(&loading_bar, 1.0, None)?;
+    }
+
+    for (_, metafile_path, metafile) in metafiles {
+        let bytes = to_toml(&metafile)?;
+        write_packwiz_file(&export_path, &metafile_path, bytes.as_bytes())
+            .await?;
+        index_files.push(PackwizIndexFile {
+            file: metafile_path,
+            hash: sha256_hex(bytes.as_bytes()),
+            hash_format: None,
+            alias: None,
+            metafile: true,
+        });
+        emit_loading(&loading_bar, 1.0, None)?;
+    }
+
+    index_files.sort_by(|a, b| a.file.cmp(&b.file));
+    let index = to_toml(&PackwizIndex {
+        hash_format: PACKWIZ_HASH_FORMAT.to_string(),
+        files: index_files,
+    })?;
+    write_packwiz_file(&export_path, INDEX_FILE_NAME, index.as_bytes()).await?;
+    emit_loading(&loading_bar, 1.0, None)?;
+
+    let mut versions = HashMap::from([(
+        "minecraft".to_string(),
+        metadata.applied_content_set.game_version.clone(),
+    )]);
+    if let Some(loader_version) =
+        metadata.applied_content_set.loader_version.clone()
+    {
+        let loader_key = match metadata.applied_content_set.loader {
+            ModLoader::Forge => Some("forge"),
+            ModLoader::NeoForge => Some("neoforge"),
+            ModLoader::Fabric => Some("fabric"),
+            ModLoader::Quilt => Some("quilt"),
+            ModLoader::Vanilla => None,
+        };
+        if let Some(loader_key) = loader_key {
+            versions.insert(loader_key.to_string(), loader_version);
+        }
+    }
+    let pack = to_toml(&PackwizPack {
+        name: metadata.instance.name.clone(),
+        author: None,
+        version: Some(version_id.unwrap_or_else(|| "1.0.0".to_string())),
+        description,
+        pack_format: Some(PACKWIZ_PACK_FORMAT.to_string()),
+        index: PackwizIndexRef {
+            file: INDEX_FILE_NAME.to_string(),
+            hash_format: PACKWIZ_HASH_FORMAT.to_string(),
+            hash: sha256_hex(index.as_bytes()),
+        },
+        versions,
+    })?;
+    write_packwiz_file(&export_path, PACK_FILE_NAME, pack.as_bytes()).await?;
+    emit_loading(&loading_bar, 1.0, None)?;
+
+    Ok(())
+}
+
+/// `mods/sodium-0.5.3.jar` becomes `mods/sodium-0.5.3.pw.toml`
+fn metafile_path(content_path: &str) -> String {
+    let (directory, file_name) = content_path
+        .rsplit_once('/')
+        .map_or(("", content_path), |(directory, name)| (directory, name));
+    let stem = file_name
+        .rsplit_once('.')
+        .map_or(file_name, |(stem, _)| stem);
+
+    if directory.is_empty() {
+        format!("{stem}{METAFILE_SUFFIX}")
+    } else {
+        format!("{directory}/{stem}{METAFILE_SUFFIX}")
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", sha2::Sha256::digest(bytes))
+}
+
+fn to_toml<T: serde::Serialize>(value: &T) -> crate::Result<String> {
+    toml::to_string(value).map_err(|error| {
+        crate::ErrorKind::OtherError(format!(
+            "Failed to serialize packwiz file: {error}"
+        ))
+        .into()
+    })
+}
+
+async fn write_packwiz_file(
+    export_path: &Path,
+    relative_path: &str,
+    bytes: &[u8],
+) -> crate::Result<()> {
+    let path = export_path.join(relative_path);
+    if let Some(parent) = path.parent() {
+        io::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&path, bytes)
+        .await
+        .map_err(|error| IOError::with_path(error, &path))?;
+
+    Ok(())
+}
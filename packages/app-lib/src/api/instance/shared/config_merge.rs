@@ -0,0 +1,644 @@
+//! Three-way merging of shared instance config bundles.
+//!
+//! The bundle a member last synced is kept as the merge base, so edits the
+//! member made locally can be told apart from changes the owner published.
+//! `json`, `toml` and `properties` files are merged per key, other files
+//! only merge when one of the two sides is unchanged.
+
+use super::publish::read_config_bundle;
+use super::types::*;
+use super::*;
+use crate::util::io::IOError;
+use indexmap::IndexMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+const CONFIG_BASES_DIRECTORY: &str = "shared_instance_configs";
+
+type FlatEntries<T> = BTreeMap<Vec<String>, T>;
+
+/// The files to write for an incoming config bundle, along with the files
+/// that changed on both sides in ways that couldn't be merged.
+pub(crate) struct ConfigBundleMerge {
+    pub files: Vec<(String, Vec<u8>)>,
+    pub conflicts: Vec<SharedInstanceConfigConflict>,
+}
+
+pub(crate) enum ConfigMerge {
+    Resolved(Vec<u8>),
+    /// The conflicting keys, or none if the file couldn't be merged per key
+    Conflict(Vec<String>),
+}
+
+/// Merges `remote_bundle` into the config files under `config_path`.
+/// Conflicting files are resolved with `resolutions`, falling back to the
+/// owner's version of the file.
+pub(crate) async fn merge_config_bundle(
+    instance_id: &str,
+    config_path: &Path,
+    remote_bundle: bytes::Bytes,
+    resolutions: &HashMap<String, SharedInstanceConfigResolution>,
+    state: &State,
+) -> crate::Result<ConfigBundleMerge> {
+    let base_path = config_base_path(instance_id, state);
+    let base_bundle = match tokio::fs::read(&base_path).await {
+        Ok(bytes) => Some(bytes),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+        Err(error) => {
+            return Err(IOError::with_path(error, &base_path).into());
+        }
+    };
+    let (remote, base) = tokio::task::spawn_blocking(move || {
+        let remote = read_config_bundle(remote_bundle.as_ref())?;
+        let base = base_bundle.and_then(|bytes| {
+            read_config_bundle(&bytes)
+                .inspect_err(|error| {
+                    tracing::warn!(
+                        "Ignoring unreadable shared instance config base: {error}"
+                    );
+                })
+                .ok()
+        });
+        Ok::<_, crate::Error>((remote, base.unwrap_or_default()))
+    })
+    .await??;
+
+    let mut merge = ConfigBundleMerge {
+        files: Vec::new(),
+        conflicts: Vec::new(),
+    };
+    for (path, remote) in remote {
+        let local_path = config_path.join(&path);
+        let local = match tokio::fs::read(&local_path).await {
+            Ok(bytes) => Some(bytes),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+            Err(error) => {
+                return Err(IOError::with_path(error, &local_path).into());
+            }
+        };
+
+        let bytes = match merge_config_file(
+            &path,
+            base.get(&path).map(Vec::as_slice),
+            &remote,
+            local.as_deref(),
+        ) {
+            ConfigMerge::Resolved(bytes) => bytes,
+            ConfigMerge::Conflict(keys) => {
+                let resolution = resolutions
+                    .get(&path)
+                    .copied()
+                    .unwrap_or(SharedInstanceConfigResolution::UseRemote);
+                merge.conflicts.push(SharedInstanceConfigConflict {
+                    path: path.clone(),
+                    keys,
+                });
+                match (resolution, local.clone()) {
+                    (
+                        SharedInstanceConfigResolution::KeepLocal,
+                        Some(local),
+                    ) => local,
+                    _ => remote,
+                }
+            }
+        };
+        if local.as_ref() != Some(&bytes) {
+            merge.files.push((path, bytes));
+        }
+    }
+
+    Ok(merge)
+}
+
+/// Stores the bundle that was just applied as the base for the next merge.
+pub(crate) async fn save_config_base(
+    instance_id: &str,
+    bundle: &[u8],
+    state: &State,
+) -> crate::Result<()> {
+    let path = config_base_path(instance_id, state);
+    if let Some(parent) = path.parent() {
+        crate::util::io::create_dir_all(parent).await?;
+    }
+    crate::util::io::write(&path, bundle).await?;
+
+    Ok(())
+}
+
+fn config_base_path(instance_id: &str, state: &State) -> PathBuf {
+    state
+        .directories
+        .caches_dir()
+        .join(CONFIG_BASES_DIRECTORY)
+        .join(format!("{instance_id}.zip"))
+}
+
+pub(crate) fn merge_config_file(
+    path: &str,
+    base: Option<&[u8]>,
+    remote: &[u8],
+    local: Option<&[u8]>,
+) -> ConfigMerge {
+    let Some(local) = local else {
+        return ConfigMerge::Resolved(remote.to_vec());
+    };
+    if local == remote || base == Some(local) {
+        return ConfigMerge::Resolved(remote.to_vec());
+    }
+    let Some(base) = base else {
+        return ConfigMerge::Conflict(Vec::new());
+    };
+    if base == remote {
+        return ConfigMerge::Resolved(local.to_vec());
+    }
+
+    let extension = path
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_ascii_lowercase());
+    let merged = match extension.as_deref() {
+        Some("json") => merge_json(base, remote, local),
+        Some("toml") => merge_toml(base, remote, local),
+        Some("properties") => merge_properties(base, remote, local),
+        _ => None,
+    };
+
+    merged.unwrap_or_else(|| ConfigMerge::Conflict(Vec::new()))
+}
+
+/// Takes whichever side changed each key, and reports keys both sides
+/// changed differently.
+fn merge_entries<T: Clone + PartialEq>(
+    base: &FlatEntries<T>,
+    remote: &FlatEntries<T>,
+    local: &FlatEntries<T>,
+    conflicts: &mut Vec<String>,
+) -> FlatEntries<T> {
+    let keys = base
+        .keys()
+        .chain(remote.keys())
+        .chain(local.keys())
+        .collect::<BTreeSet<_>>();
+    let mut merged = BTreeMap::new();
+
+    for key in keys {
+        let (base, remote, local) =
+            (base.get(key), remote.get(key), local.get(key));
+        let value = if remote == local || remote == base {
+            local
+        } else if local == base {
+            remote
+        } else {
+            conflicts.push(key.join("."));
+            local
+        };
+        if let Some(value) = value {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+
+    merged
+}
+
+fn finish_merge(
+    conflicts: Vec<String>,
+    bytes: impl FnOnce() -> Option<Vec<u8>>,
+) -> Option<ConfigMerge> {
+    if conflicts.is_empty() {
+        bytes().map(ConfigMerge::Resolved)
+    } else {
+        Some(ConfigMerge::Conflict(conflicts))
+    }
+}
+
+/// The keys to remove from the local file, and the keys to then copy from
+/// the remote file, to turn it into `merged` without rewriting the rest.
+fn changed_keys<'a, T: PartialEq>(
+    merged: &'a FlatEntries<T>,
+    local: &'a FlatEntries<T>,
+) -> (Vec<&'a [String]>, Vec<&'a [String]>) {
+    let removed = local
+        .keys()
+        .filter(|key| !merged.contains_key(*key))
+        .map(Vec::as_slice)
+        .collect();
+    let changed = merged
+        .iter()
+        .filter(|(key, value)| local.get(*key) != Some(*value))
+        .map(|(key, _)| key.as_slice())
+        .collect();
+
+    (removed, changed)
+}
+
+/// A JSON value which keeps object keys in the order they were written, so
+/// merging into a file doesn't reorder it.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+enum OrderedJson {
+    Object(IndexMap<String, OrderedJson>),
+    Array(Vec<OrderedJson>),
+    Other(serde_json::Value),
+}
+
+fn merge_json(base: &[u8], remote: &[u8], local: &[u8]) -> Option<ConfigMerge> {
+    let parse =
+        |bytes: &[u8]| serde_json::from_slice::<OrderedJson>(bytes).ok();
+    let remote_value = parse(remote)?;
+    let mut local_value = parse(local)?;
+    let flatten = |value: &OrderedJson| {
+        let mut entries = BTreeMap::new();
+        flatten_json(value, &mut Vec::new(), &mut entries);
+        entries
+    };
+    let local_entries = flatten(&local_value);
+    let mut conflicts = Vec::new();
+    let merged = merge_entries(
+        &flatten(&parse(base)?),
+        &flatten(&remote_value),
+        &local_entries,
+        &mut conflicts,
+    );
+
+    finish_merge(conflicts, || {
+        let (removed, changed) = changed_keys(&merged, &local_entries);
+        for key in removed {
+            remove_json(&mut local_value, key);
+        }
+        for key in changed {
+            let value = key
+                .iter()
+                .try_fold(&remote_value, |value, key| match value {
+                    OrderedJson::Object(map) => map.get(key),
+                    _ => None,
+                })?
+                .clone();
+            insert_json(&mut local_value, key, value);
+        }
+
+        let mut bytes = serde_json::to_vec_pretty(&local_value).ok()?;
+        if local.ends_with(b"\n") {
+            bytes.push(b'\n');
+        }
+        Some(bytes)
+    })
+}
+
+fn flatten_json(
+    value: &OrderedJson,
+    prefix: &mut Vec<String>,
+    entries: &mut FlatEntries<OrderedJson>,
+) {
+    match value {
+        OrderedJson::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                prefix.push(key.clone());
+                flatten_json(value, prefix, entries);
+                prefix.pop();
+            }
+        }
+        _ => {
+            entries.insert(prefix.clone(), value.clone());
+        }
+    }
+}
+
+fn insert_json(target: &mut OrderedJson, key: &[String], value: OrderedJson) {
+    let Some((first, rest)) = key.split_first() else {
+        *target = value;
+        return;
+    };
+    if !matches!(target, OrderedJson::Object(_)) {
+        *target = OrderedJson::Object(IndexMap::new());
+    }
+    if let OrderedJson::Object(map) = target {
+        insert_json(
+            map.entry(first.clone())
+                .or_insert_with(|| OrderedJson::Object(IndexMap::new())),
+            rest,
+            value,
+        );
+    }
+}
+
+/// Removes a key, along with any objects left empty by removing it.
+fn remove_json(target: &mut OrderedJson, key: &[String]) {
+    let (Some((first, rest)), OrderedJson::Object(map)) =
+        (key.split_first(), target)
+    else {
+        return;
+    };
+    if rest.is_empty() {
+        map.shift_remove(first);
+    } else if let Some(child) = map.get_mut(first) {
+        remove_json(child, rest);
+        if matches!(child, OrderedJson::Object(child) if child.is_empty()) {
+            map.shift_remove(first);
+        }
+    }
+}
+
+/// TOML is compared as plain values, but the changes are made to the local
+/// document, so comments and formatting survive the merge.
+fn merge_toml(base: &[u8], remote: &[u8], local: &[u8]) -> Option<ConfigMerge> {
+    let parse = |bytes: &[u8]| {
+        let table =
+            toml::from_str::<toml::Table>(std::str::from_utf8(bytes).ok()?)
+                .ok()?;
+        let mut entries = BTreeMap::new();
+        flatten_toml(&toml::Value::Table(table), &mut Vec::new(), &mut entries);
+        Some(entries)
+    };
+    let parse_document = |bytes: &[u8]| {
+        std::str::from_utf8(bytes)
+            .ok()?
+            .parse::<toml_edit::DocumentMut>()
+            .ok()
+    };
+    let local_entries = parse(local)?;
+    let mut conflicts = Vec::new();
+    let merged = merge_entries(
+        &parse(base)?,
+        &parse(remote)?,
+        &local_entries,
+        &mut conflicts,
+    );
+
+    finish_merge(conflicts, || {
+        let remote = parse_document(remote)?;
+        let mut local = parse_document(local)?;
+        let (removed, changed) = changed_keys(&merged, &local_entries);
+        for key in removed {
+            remove_toml(local.as_item_mut(), key);
+        }
+        for key in changed {
+            let value = key
+                .iter()
+                .try_fold(remote.as_item(), |item, key| {
+                    item.as_table_like()?.get(key)
+                })?
+                .clone();
+            insert_toml(local.as_item_mut(), key, value);
+        }
+
+        Some(local.to_string().into_bytes())
+    })
+}
+
+fn flatten_toml(
+    value: &toml::Value,
+    prefix: &mut Vec<String>,
+    entries: &mut FlatEntries<toml::Value>,
+) {
+    match value {
+        toml::Value::Table(table) if !table.is_empty() => {
+            for (key, value) in table {
+                prefix.push(key.clone());
+                flatten_toml(value, prefix, entries);
+                prefix.pop();
+            }
+        }
+        _ => {
+            entries.insert(prefix.clone(), value.clone());
+        }
+    }
+}
+
+fn insert_toml(
+    target: &mut toml_edit::Item,
+    key: &[String],
+    mut value: toml_edit::Item,
+) {
+    let Some((first, rest)) = key.split_first() else {
+        *target = value;
+        return;
+    };
+    if target.as_table_like().is_none() {
+        *target = toml_edit::table();
+    }
+    // Tables created inside an inline table have to be inline too
+    let child = if target.is_inline_table() {
+        toml_edit::Item::Value(toml_edit::InlineTable::new().into())
+    } else {
+        let mut table = toml_edit::Table::new();
+        table.set_implicit(true);
+        toml_edit::Item::Table(table)
+    };
+    let Some(table) = target.as_table_like_mut() else {
+        return;
+    };
+    if rest.is_empty() {
+        // Replacing the value in place keeps the comments around it
+        match table.get_mut(first) {
+            Some(existing) => {
+                if let (toml_edit::Item::Value(existing), Some(new)) =
+                    (&*existing, value.as_value_mut())
+                {
+                    *new.decor_mut() = existing.decor().clone();
+                }
+                *existing = value;
+            }
+            None => {
+                table.insert(first, value);
+            }
+        }
+        return;
+    }
+    if table.get(first).is_none() {
+        table.insert(first, child);
+    }
+    if let Some(child) = table.get_mut(first) {
+        insert_toml(child, rest, value);
+    }
+}
+
+/// Removes a key, along with any tables left empty by removing it.
+fn remove_toml(target: &mut toml_edit::Item, key: &[String]) {
+    let (Some((first, rest)), Some(table)) =
+        (key.split_first(), target.as_table_like_mut())
+    else {
+        return;
+    };
+    if rest.is_empty() {
+        table.remove(first);
+    } else if let Some(child) = table.get_mut(first) {
+        remove_toml(child, rest);
+        if child.as_table_like().is_some_and(|child| child.is_empty()) {
+            table.remove(first);
+        }
+    }
+}
+
+/// Properties are rewritten in place on top of the local file, so comments
+/// and key order survive the merge.
+fn merge_properties(
+    base: &[u8],
+    remote: &[u8],
+    local: &[u8],
+) -> Option<ConfigMerge> {
+    let parse = |bytes: &[u8]| {
+        let text = std::str::from_utf8(bytes).ok()?;
+        Some(
+            text.lines()
+                .filter_map(parse_property)
+                .map(|(key, value)| (vec![key.to_string()], value.to_string()))
+                .collect::<FlatEntries<String>>(),
+        )
+    };
+    let local_entries = parse(local)?;
+    let mut conflicts = Vec::new();
+    let mut merged = merge_entries(
+        &parse(base)?,
+        &parse(remote)?,
+        &local_entries,
+        &mut conflicts,
+    );
+
+    finish_merge(conflicts, || {
+        let text = std::str::from_utf8(local).ok()?;
+        let mut lines = Vec::new();
+        for line in text.lines() {
+            match parse_property(line) {
+                Some((key, value)) => {
+                    let Some(merged_value) =
+                        merged.remove(&vec![key.to_string()])
+                    else {
+                        continue;
+                    };
+                    if merged_value == value {
+                        lines.push(line.to_string());
+                    } else {
+                        lines.push(format!("{key}={merged_value}"));
+                    }
+                }
+                None => lines.push(line.to_string()),
+            }
+        }
+        for (key, value) in merged {
+            lines.push(format!("{}={value}", key.join(".")));
+        }
+
+        let mut text = lines.join("\n");
+        text.push('\n');
+        Some(text.into_bytes())
+    })
+}
+
+fn parse_property(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim_start();
+    if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+        return None;
+    }
+
+    Some(match line.find(['=', ':']) {
+        Some(index) => (line[..index].trim_end(), line[index + 1..].trim()),
+        None => (line.trim_end(), ""),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge(path: &str, base: &str, remote: &str, local: &str) -> ConfigMerge {
+        merge_config_file(
+            path,
+            Some(base.as_bytes()),
+            remote.as_bytes(),
+            Some(local.as_bytes()),
+        )
+    }
+
+    fn merged(merge: ConfigMerge) -> String {
+        match merge {
+            ConfigMerge::Resolved(bytes) => String::from_utf8(bytes).unwrap(),
+            ConfigMerge::Conflict(keys) => {
+                panic!("unexpected conflict {keys:?}")
+            }
+        }
+    }
+
+    #[test]
+    fn keeps_local_keys_the_owner_did_not_touch() {
+        let merge = merge(
+            "options.properties",
+            "fov=70\nrenderDistance=12\n# graphics\nmaxFps=120\n",
+            "fov=70\nrenderDistance=16\n# graphics\nmaxFps=120\n",
+            "fov=90\nrenderDistance=12\n# graphics\nmaxFps=240\n",
+        );
+
+        assert_eq!(
+            merged(merge),
+            "fov=90\nrenderDistance=16\n# graphics\nmaxFps=240\n"
+        );
+    }
+
+    #[test]
+    fn merges_nested_json_and_toml_per_key() {
+        let json = merge(
+            "mod.json",
+            r#"{"a":{"b":1,"c":1}}"#,
+            r#"{"a":{"b":2,"c":1}}"#,
+            r#"{"a":{"b":1,"c":3}}"#,
+        );
+        let value: serde_json::Value =
+            serde_json::from_str(&merged(json)).unwrap();
+        assert_eq!(value, serde_json::json!({ "a": { "b": 2, "c": 3 } }));
+
+        let toml = merge(
+            "mod.toml",
+            "[client]\nb = 1\nc = 1\n",
+            "[client]\nb = 2\nc = 1\n",
+            "[client]\nb = 1\nc = 3\n",
+        );
+        let value: toml::Table = toml::from_str(&merged(toml)).unwrap();
+        assert_eq!(value["client"]["b"].as_integer(), Some(2));
+        assert_eq!(value["client"]["c"].as_integer(), Some(3));
+    }
+
+    #[test]
+    fn keeps_comments_and_key_order() {
+        let toml = merge(
+            "mod.toml",
+            "# Client settings\n[client]\n# Speed\nb = 1\nc = 1\n",
+            "# Client settings\n[client]\n# Speed\nb = 2\nc = 1\n",
+            "# Client settings\n[client]\n# Speed\nb = 1 # fast\nc = 3\n",
+        );
+        assert_eq!(
+            merged(toml),
+            "# Client settings\n[client]\n# Speed\nb = 2 # fast\nc = 3\n"
+        );
+
+        let json = merge(
+            "mod.json",
+            r#"{"z":1,"a":{"y":1,"b":1}}"#,
+            r#"{"z":1,"a":{"y":2,"b":1},"new":true}"#,
+            r#"{"z":3,"a":{"y":1,"b":1}}"#,
+        );
+        let keys = merged(json)
+            .lines()
+            .filter_map(|line| line.trim().split('"').nth(1).map(str::to_owned))
+            .collect::<Vec<_>>();
+        assert_eq!(keys, ["z", "a", "y", "b", "new"]);
+    }
+
+    #[test]
+    fn reports_keys_changed_on_both_sides() {
+        let merge = merge(
+            "mod.json",
+            r#"{"a":1,"b":1}"#,
+            r#"{"a":2,"b":2}"#,
+            r#"{"a":3,"b":1}"#,
+        );
+
+        assert!(matches!(merge, ConfigMerge::Conflict(keys) if keys == ["a"]));
+    }
+
+    #[test]
+    fn unknown_formats_conflict_as_a_whole() {
+        let merge = merge("mod.yml", "a", "b", "c");
+
+        assert!(
+            matches!(merge, ConfigMerge::Conflict(keys) if keys.is_empty())
+        );
+    }
+}
@@ -207,16 +207,21 @@ pub async fn get_shared_instance_update_preview(
             latest_version: version.version,
             update_available: false,
             diffs: Vec::new(),
+            config_conflicts: Vec::new(),
         }));
     }
 
     let update_available = attachment
         .applied_version
         .is_none_or(|current| current < version.version);
-    let diffs = if update_available {
-        shared_instance_update_diffs(&metadata, &version, &state).await?
+    let (diffs, config_conflicts) = if update_available {
+        (
+            shared_instance_update_diffs(&metadata, &version, &state).await?,
+            shared_instance_config_conflicts(&metadata, &version, &state)
+                .await?,
+        )
     } else {
-        Vec::new()
+        (Vec::new(), Vec::new())
     };
 
     Ok(Some(SharedInstanceUpdatePreview {
@@ -225,9 +230,51 @@ pub async fn get_shared_instance_update_preview(
         latest_version: version.version,
         update_available,
         diffs,
+        config_conflicts,
     }))
 }
 
+/// Dry-runs the config merge for `version` so members can pick how each
+/// conflicting file is resolved before updating.
+async fn shared_instance_config_conflicts(
+    metadata: &crate::state::InstanceMetadata,
+    version: &InstanceVersionResponse,
+    state: &State,
+) -> crate::Result<Vec<SharedInstanceConfigConflict>> {
+    if !CONFIG_SYNC_ENABLED {
+        return Ok(Vec::new());
+    }
+    let Some(bundle) = version
+        .external_files
+        .iter()
+        .find(|file| file.file_type == CONFIG_BUNDLE_FILE_TYPE)
+    else {
+        return Ok(Vec::new());
+    };
+
+    let response = REQWEST_CLIENT
+        .get(&bundle.url)
+        .send()
+        .await?
+        .error_for_status()?;
+    let bytes = response.bytes().await?;
+    let config_path = state
+        .directories
+        .instances_dir()
+        .join(&metadata.instance.path)
+        .join(CONFIG_DIRECTORY);
+    let merge = merge_config_bundle(
+        &metadata.instance.id,
+        &config_path,
+        bytes,
+        &HashMap::new(),
+        state,
+    )
+    .await?;
+
+    Ok(merge.conflicts)
+}
+
 pub(crate) async fn check_shared_instance_availability_before_launch(
     instance_id: &str,
     state: &State,
@@ -272,6 +319,7 @@ pub(crate) async fn check_shared_instance_availability_before_launch(
 #[tracing::instrument]
 pub async fn update_shared_instance(
     instance_id: &str,
+    config_resolutions: HashMap<String, SharedInstanceConfigResolution>,
 ) -> crate::Result<InstallJobSnapshot> {
     let state = State::get().await?;
     let metadata = crate::state::get_instance(instance_id, &state.pool)
@@ -295,7 +343,7 @@ pub async fn update_shared_instance(
     let version =
         get_latest_remote_member_version(instance_id, &attachment, &state)
             .await?;
-    let data = shared_instance_install_data(
+    let mut data = shared_instance_install_data(
         &attachment.id,
         attachment.manager_id.clone(),
         attachment.server_manager_name.clone(),
@@ -306,6 +354,7 @@ pub async fn update_shared_instance(
         &state,
     )
     .await?;
+    data.config_resolutions = config_resolutions;
     crate::install::update_shared_instance(instance_id.to_string(), data).await
 }
 
@@ -553,6 +602,7 @@ pub(super) async fn shared_instance_install_data(
         game_version: version.game_version,
         loader: version.loader,
         loader_version: shared_instance_loader_version(version.loader_version),
+        config_resolutions: HashMap::new(),
     })
 }
 
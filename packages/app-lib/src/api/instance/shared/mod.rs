@@ -95,12 +95,14 @@ pub(crate) fn read_bounded_config_bundle_entry(
 }
 
 mod client;
+mod config_merge;
 mod diff;
 mod install;
 mod invites;
 mod publish;
 mod types;
 
+pub(crate) use self::config_merge::{merge_config_bundle, save_config_base};
 pub(crate) use self::install::check_shared_instance_availability_before_launch;
 pub(crate) use self::publish::sync_shared_instance_icon;
 
@@ -120,6 +122,7 @@ pub use self::publish::{
     unlink_shared_instance, unpublish_shared_instance,
 };
 pub use self::types::{
+    SharedInstanceConfigConflict, SharedInstanceConfigResolution,
     SharedInstanceExternalFilePreview, SharedInstanceInstallPreview,
     SharedInstanceInvite, SharedInstanceInviteInstallPreview,
     SharedInstanceInviteLink, SharedInstanceJoinType,
@@ -898,7 +898,7 @@ async fn config_bundle_bytes(
     Ok(writer.close().await?)
 }
 
-fn read_config_bundle(
+pub(super) fn read_config_bundle(
     bytes: &[u8],
 ) -> crate::Result<BTreeMap<String, Vec<u8>>> {
     let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
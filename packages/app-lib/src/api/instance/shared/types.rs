@@ -97,6 +97,25 @@ pub struct SharedInstanceUpdatePreview {
     pub latest_version: i32,
     pub update_available: bool,
     pub diffs: Vec<SharedInstanceUpdateDiff>,
+    #[serde(default)]
+    pub config_conflicts: Vec<SharedInstanceConfigConflict>,
+}
+
+/// A config file both the owner and the member changed in ways that couldn't
+/// be merged automatically.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedInstanceConfigConflict {
+    pub path: String,
+    /// The conflicting keys, empty if the file couldn't be merged per key
+    pub keys: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SharedInstanceConfigResolution {
+    KeepLocal,
+    UseRemote,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
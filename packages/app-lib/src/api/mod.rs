@@ -15,6 +15,7 @@ pub mod process;
 pub mod reports;
 pub mod server_address;
 pub mod settings;
+pub mod signing_keys;
 pub mod tags;
 pub mod users;
 pub mod worlds;
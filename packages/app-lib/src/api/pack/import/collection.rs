@@ -0,0 +1,142 @@
+//! Creating instances from Modrinth collections.
+//!
+//! Every project in the collection is resolved for the instance's game
+//! version and loader through `modrinth-content-management`, the same way
+//! installing a single project with its dependencies works.
+
+use modrinth_content_management::ResolutionPreferences;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+use crate::State;
+use crate::install::{
+    InstallJobEventKind, InstallPhaseDetails, InstallPhaseId, InstallProgress,
+    InstallProgressReporter,
+};
+use crate::state::instances::commands::{
+    InstanceInstallProjectRequest, install_resolved_content_plan,
+    resolve_install_plan,
+};
+use crate::state::{CacheBehaviour, CachedEntry, ProjectType};
+use crate::util::fetch::fetch_json;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Collection {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub icon_url: Option<String>,
+    pub projects: Vec<String>,
+}
+
+#[tracing::instrument]
+pub async fn get_collection(collection_id: &str) -> crate::Result<Collection> {
+    let state = State::get().await?;
+    let collection_id = urlencoding::encode(collection_id);
+
+    fetch_json(
+        Method::GET,
+        &format!(
+            "{}collection/{}",
+            env!("MODRINTH_API_URL_V3"),
+            collection_id
+        ),
+        None,
+        None,
+        Some("/v3/collection/:id"),
+        &state.api_semaphore,
+        &state.pool,
+    )
+    .await
+}
+
+/// Installs every project of a collection that has a version compatible with
+/// the instance. Projects that can't be installed, such as modpacks or
+/// projects without a matching version, are recorded as skipped.
+#[tracing::instrument(skip(reporter))]
+pub(crate) async fn install_collection_content(
+    instance_id: &str,
+    collection: &Collection,
+    reporter: InstallProgressReporter,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    let details = InstallPhaseDetails::Instance {
+        name: collection.name.clone(),
+    };
+    reporter
+        .update(InstallPhaseId::ResolvingPack, None, details.clone())
+        .await?;
+
+    let project_ids = collection
+        .projects
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    let projects = CachedEntry::get_project_many(
+        &project_ids,
+        Some(CacheBehaviour::MustRevalidate),
+        &state.pool,
+        &state.api_semaphore,
+    )
+    .await?;
+
+    let total = projects.len() as u64;
+    for (index, project) in projects.into_iter().enumerate() {
+        let mut events = Vec::new();
+        if let Some(project_type) =
+            ProjectType::from_name(&project.project_type)
+        {
+            let plan = resolve_install_plan(
+                instance_id,
+                InstanceInstallProjectRequest {
+                    project_id: project.id.clone(),
+                    version_id: None,
+                    content_type: project_type.into(),
+                    selected: ResolutionPreferences::default(),
+                },
+                &state,
+            )
+            .await;
+
+            match plan {
+                Ok(plan) => {
+                    install_resolved_content_plan(instance_id, &plan, &state)
+                        .await?;
+                }
+                Err(error) => {
+                    tracing::warn!(
+                        "Skipping collection project {}: {error}",
+                        project.id
+                    );
+                    events.push(InstallJobEventKind::ContentFileSkipped {
+                        path: project.title.clone(),
+                        reason: error.to_string(),
+                    });
+                }
+            }
+        } else {
+            events.push(InstallJobEventKind::ContentFileSkipped {
+                path: project.title.clone(),
+                reason: format!(
+                    "{} projects can't be added to an instance",
+                    project.project_type
+                ),
+            });
+        }
+
+        reporter
+            .update_with_events(
+                InstallPhaseId::DownloadingContent,
+                Some(InstallProgress {
+                    current: index as u64 + 1,
+                    total,
+                    secondary: None,
+                }),
+                details.clone(),
+                events,
+            )
+            .await?;
+    }
+
+    Ok(())
+}
@@ -18,9 +18,11 @@ use crate::{
 };
 
 pub mod atlauncher;
+pub mod collection;
 pub mod curseforge;
 pub mod gdlauncher;
 pub mod mmc;
+pub mod packwiz;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[cfg_attr(
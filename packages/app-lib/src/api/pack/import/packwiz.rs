@@ -0,0 +1,661 @@
+//! Importing packwiz repositories (`pack.toml`, `index.toml` and `.pw.toml`
+//! metafiles) as managed instances.
+//!
+//! The packwiz tree is converted into an in-memory `.mrpack` so the regular
+//! modpack installer handles downloads, content tracking and overrides.
+
+use std::collections::HashMap;
+use std::io::{Cursor, Write};
+use std::path::PathBuf;
+
+use bytes::Bytes;
+use path_util::SafeRelativeUtf8UnixPathBuf;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::State;
+use crate::api::pack::install_from::{
+    CreatePack, CreatePackDescription, CreatePackFile, CreatePackInstance,
+    EnvType, PackDependency, PackFile, PackFileHash, PackFormat,
+};
+use crate::install::{
+    InstallErrorContext, InstallPhaseDetails, InstallPhaseId,
+    InstallProgressReporter,
+};
+use crate::prelude::ModLoader;
+use crate::state::{CacheBehaviour, CachedEntry, SideType, VersionV3};
+use crate::util::fetch::fetch;
+use crate::util::io;
+
+pub(crate) const PACK_FILE_NAME: &str = "pack.toml";
+pub(crate) const METAFILE_SUFFIX: &str = ".pw.toml";
+
+/// Where a packwiz repository is read from
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum PackwizSource {
+    /// A local directory containing `pack.toml`
+    Directory { path: PathBuf },
+    /// A URL pointing at a `pack.toml`, such as one hosted on GitHub Pages
+    Url { url: String },
+}
+
+impl PackwizSource {
+    fn display_name(&self) -> String {
+        match self {
+            PackwizSource::Directory { path } => path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| "packwiz".to_string()),
+            PackwizSource::Url { url } => url.clone(),
+        }
+    }
+
+    async fn read(
+        &self,
+        relative_path: &str,
+        state: &State,
+    ) -> crate::Result<Bytes> {
+        let relative_path =
+            SafeRelativeUtf8UnixPathBuf::try_from(relative_path.to_string())
+                .map_err(|_| {
+                    crate::ErrorKind::InputError(format!(
+                        "Invalid packwiz file path: {relative_path}"
+                    ))
+                })?;
+
+        match self {
+            PackwizSource::Directory { path } => {
+                let root = if path.is_file() {
+                    path.parent().map(PathBuf::from).unwrap_or_default()
+                } else {
+                    path.clone()
+                };
+                Ok(Bytes::from(
+                    io::read(root.join(relative_path.as_str())).await?,
+                ))
+            }
+            PackwizSource::Url { url } => {
+                let base = url::Url::parse(url)?;
+                let url = base.join(relative_path.as_str())?;
+                fetch(
+                    url.as_str(),
+                    None,
+                    None,
+                    None,
+                    &state.fetch_semaphore,
+                    &state.pool,
+                )
+                .await
+            }
+        }
+    }
+}
+
+// https://packwiz.infra.link/reference/pack-format/
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct PackwizPack {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pack_format: Option<String>,
+    pub index: PackwizIndexRef,
+    pub versions: HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct PackwizIndexRef {
+    pub file: String,
+    pub hash_format: String,
+    pub hash: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct PackwizIndex {
+    pub hash_format: String,
+    #[serde(default)]
+    pub files: Vec<PackwizIndexFile>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct PackwizIndexFile {
+    pub file: String,
+    pub hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash_format: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub metafile: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct PackwizMetafile {
+    #[serde(default)]
+    pub name: String,
+    pub filename: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub side: Option<String>,
+    pub download: PackwizDownload,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub option: Option<PackwizOption>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update: Option<PackwizUpdate>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct PackwizDownload {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub hash_format: String,
+    pub hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct PackwizOption {
+    #[serde(default)]
+    pub optional: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct PackwizUpdate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modrinth: Option<PackwizModrinthUpdate>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct PackwizModrinthUpdate {
+    pub mod_id: String,
+    pub version: String,
+}
+
+fn parse_toml<T: serde::de::DeserializeOwned>(
+    bytes: &[u8],
+    file: &str,
+) -> crate::Result<T> {
+    let text = std::str::from_utf8(bytes).map_err(|_| {
+        crate::ErrorKind::InputError(format!("{file} is not valid UTF-8"))
+    })?;
+    toml::from_str(text).map_err(|error| {
+        crate::ErrorKind::InputError(format!("Invalid {file}: {error}")).into()
+    })
+}
+
+/// Verifies `bytes` against a packwiz hash. Formats the launcher cannot
+/// compute (such as `murmur2`) are accepted without verification.
+fn verify_hash(
+    bytes: &[u8],
+    hash_format: &str,
+    expected: &str,
+    file: &str,
+) -> crate::Result<()> {
+    let actual = match hash_format {
+        "sha1" => sha1_smol::Sha1::from(bytes).hexdigest(),
+        "sha256" => format!("{:x}", sha2::Sha256::digest(bytes)),
+        "sha512" => format!("{:x}", sha2::Sha512::digest(bytes)),
+        _ => {
+            tracing::debug!(
+                "Skipping verification of {file} with unsupported hash format {hash_format}"
+            );
+            return Ok(());
+        }
+    };
+
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(
+            crate::ErrorKind::HashError(expected.to_string(), actual).into()
+        );
+    }
+
+    Ok(())
+}
+
+pub(crate) fn loader_from_versions(
+    versions: &HashMap<String, String>,
+) -> (ModLoader, Option<String>) {
+    for (key, loader) in [
+        ("neoforge", ModLoader::NeoForge),
+        ("forge", ModLoader::Forge),
+        ("quilt", ModLoader::Quilt),
+        ("fabric", ModLoader::Fabric),
+    ] {
+        if let Some(version) = versions.get(key) {
+            return (loader, Some(version.clone()));
+        }
+    }
+
+    (ModLoader::Vanilla, None)
+}
+
+fn pack_dependencies(
+    versions: &HashMap<String, String>,
+) -> crate::Result<HashMap<PackDependency, String>> {
+    let game_version = versions.get("minecraft").ok_or_else(|| {
+        crate::ErrorKind::InputError(
+            "pack.toml does not declare a Minecraft version".to_string(),
+        )
+    })?;
+
+    let mut dependencies =
+        HashMap::from([(PackDependency::Minecraft, game_version.clone())]);
+    match loader_from_versions(versions) {
+        (ModLoader::Forge, Some(version)) => {
+            dependencies.insert(PackDependency::Forge, version);
+        }
+        (ModLoader::NeoForge, Some(version)) => {
+            dependencies.insert(PackDependency::NeoForge, version);
+        }
+        (ModLoader::Fabric, Some(version)) => {
+            dependencies.insert(PackDependency::FabricLoader, version);
+        }
+        (ModLoader::Quilt, Some(version)) => {
+            dependencies.insert(PackDependency::QuiltLoader, version);
+        }
+        _ => {}
+    }
+
+    Ok(dependencies)
+}
+
+fn side_env(side: Option<&str>, optional: bool) -> HashMap<EnvType, SideType> {
+    let supported = if optional {
+        SideType::Optional
+    } else {
+        SideType::Required
+    };
+    let (client, server) = match side {
+        Some("client") => (supported, SideType::Unsupported),
+        Some("server") => (SideType::Unsupported, supported),
+        _ => (supported.clone(), supported),
+    };
+
+    HashMap::from([(EnvType::Client, client), (EnvType::Server, server)])
+}
+
+async fn read_pack(
+    source: &PackwizSource,
+    state: &State,
+) -> crate::Result<PackwizPack> {
+    let bytes = source.read(PACK_FILE_NAME, state).await?;
+    parse_toml(&bytes, PACK_FILE_NAME)
+}
+
+/// Reads `pack.toml` and returns the instance that importing it would create
+#[tracing::instrument]
+pub async fn get_packwiz_instance(
+    source: PackwizSource,
+) -> crate::Result<CreatePackInstance> {
+    let state = State::get().await?;
+    let pack = read_pack(&source, &state).await?;
+    let (modloader, loader_version) = loader_from_versions(&pack.versions);
+
+    Ok(CreatePackInstance {
+        name: pack.name,
+        game_version: pack
+            .versions
+            .get("minecraft")
+            .cloned()
+            .unwrap_or_default(),
+        modloader,
+        loader_version,
+        ..Default::default()
+    })
+}
+
+/// Resolves a packwiz repository into an `.mrpack` ready to be installed.
+///
+/// Metafiles with an `update.modrinth` section are resolved to the referenced
+/// Modrinth version, other metafiles keep their own download URL, and every
+/// non-metafile in the index is bundled as an override.
+#[tracing::instrument(skip(reporter))]
+pub(crate) async fn generate_pack_from_packwiz(
+    source: PackwizSource,
+    instance_id: String,
+    reporter: InstallProgressReporter,
+) -> crate::Result<CreatePack> {
+    let state = State::get().await?;
+
+    reporter
+        .set_context(
+            InstallErrorContext::new("read packwiz pack")
+                .source_path(source.display_name())
+                .entry_path(PACK_FILE_NAME)
+                .build(),
+        )
+        .await?;
+    let pack = read_pack(&source, &state).await?;
+    let details = InstallPhaseDetails::Modpack {
+        project_id: None,
+        version_id: None,
+        title: Some(pack.name.clone()),
+    };
+    reporter
+        .update(InstallPhaseId::ReadingPackManifest, None, details.clone())
+        .await?;
+
+    reporter
+        .set_context(
+            InstallErrorContext::new("read packwiz index")
+                .source_path(source.display_name())
+                .entry_path(pack.index.file.clone())
+                .expected_hash(pack.index.hash.clone())
+                .build(),
+        )
+        .await?;
+    let index_bytes = source.read(&pack.index.file, &state).await?;
+    verify_hash(
+        &index_bytes,
+        &pack.index.hash_format,
+        &pack.index.hash,
+        &pack.index.file,
+    )?;
+    let index: PackwizIndex = parse_toml(&index_bytes, &pack.index.file)?;
+
+    // Files in the index are relative to the index itself
+    let index_dir = pack
+        .index
+        .file
+        .rsplit_once('/')
+        .map(|(dir, _)| format!("{dir}/"))
+        .unwrap_or_default();
+
+    reporter
+        .update(InstallPhaseId::ResolvingPack, None, details.clone())
+        .await?;
+
+    let mut metafiles = Vec::new();
+    let mut overrides = Vec::new();
+    for file in &index.files {
+        let source_path = format!("{index_dir}{}", file.file);
+        let hash_format =
+            file.hash_format.as_deref().unwrap_or(&index.hash_format);
+        let bytes = source.read(&source_path, &state).await?;
+        verify_hash(&bytes, hash_format, &file.hash, &source_path)?;
+
+        if file.metafile || file.file.ends_with(METAFILE_SUFFIX) {
+            let metafile: PackwizMetafile = parse_toml(&bytes, &source_path)?;
+            let directory = file
+                .file
+                .rsplit_once('/')
+                .map(|(dir, _)| format!("{dir}/"))
+                .unwrap_or_default();
+            metafiles.push((directory, metafile));
+        } else {
+            let target =
+                file.alias.clone().unwrap_or_else(|| file.file.clone());
+            overrides.push((target, bytes));
+        }
+    }
+
+    let version_ids = metafiles
+        .iter()
+        .filter_map(|(_, metafile)| {
+            metafile
+                .update
+                .as_ref()?
+                .modrinth
+                .as_ref()
+                .map(|update| update.version.as_str())
+        })
+        .collect::<Vec<_>>();
+    let versions = CachedEntry::get_version_v3_many(
+        &version_ids,
+        Some(CacheBehaviour::MustRevalidate),
+        &state.pool,
+        &state.api_semaphore,
+    )
+    .await?
+    .into_iter()
+    .map(|version| (version.id.clone(), version))
+    .collect::<HashMap<_, _>>();
+
+    let mut files = Vec::with_capacity(metafiles.len());
+    for (directory, metafile) in metafiles {
+        if let Some(file) =
+            packwiz_pack_file(&directory, metafile, &versions, &state).await?
+        {
+            files.push(file);
+        }
+    }
+
+    let packfile = PackFormat {
+        game: "minecraft".to_string(),
+        format_version: 1,
+        version_id: pack.version.unwrap_or_else(|| "1.0.0".to_string()),
+        name: pack.name.clone(),
+        summary: pack.description,
+        files,
+        dependencies: pack_dependencies(&pack.versions)?,
+    };
+    let packfile_data = serde_json::to_vec_pretty(&packfile)?;
+    let archive = tokio::task::spawn_blocking(move || {
+        write_packwiz_mrpack(&packfile_data, overrides)
+    })
+    .await??;
+
+    Ok(CreatePack {
+        file: CreatePackFile::Bytes(archive),
+        description: CreatePackDescription {
+            icon: None,
+            override_title: Some(pack.name),
+            project_id: None,
+            version_id: None,
+            instance_id,
+            source_filename: None,
+        },
+    })
+}
+
+async fn packwiz_pack_file(
+    directory: &str,
+    metafile: PackwizMetafile,
+    versions: &HashMap<String, VersionV3>,
+    state: &State,
+) -> crate::Result<Option<PackFile>> {
+    let path = SafeRelativeUtf8UnixPathBuf::try_from(format!(
+        "{directory}{}",
+        metafile.filename
+    ))
+    .map_err(|_| {
+        crate::ErrorKind::InputError(format!(
+            "Invalid packwiz file name: {}",
+            metafile.filename
+        ))
+    })?;
+    let env = side_env(
+        metafile.side.as_deref(),
+        metafile.option.is_some_and(|option| option.optional),
+    );
+
+    if let Some(update) = metafile.update.and_then(|update| update.modrinth) {
+        let version = versions.get(&update.version).ok_or_else(|| {
+            crate::ErrorKind::InputError(format!(
+                "Modrinth version {} of project {} referenced by {path} was not found",
+                update.version, update.mod_id
+            ))
+        })?;
+        let file = version
+            .files
+            .iter()
+            .find(|file| file.filename == metafile.filename)
+            .or_else(|| version.files.iter().find(|file| file.primary))
+            .or_else(|| version.files.first())
+            .ok_or_else(|| {
+                crate::ErrorKind::InputError(format!(
+                    "Modrinth version {} has no files",
+                    update.version
+                ))
+            })?;
+
+        return Ok(Some(PackFile {
+            path,
+            hashes: file
+                .hashes
+                .clone()
+                .into_iter()
+                .map(|(algorithm, hash)| (PackFileHash::from(algorithm), hash))
+                .collect(),
+            env: Some(env),
+            downloads: vec![file.url.clone()],
+            file_size: file.size,
+        }));
+    }
+
+    let Some(url) = metafile.download.url else {
+        // `metadata:curseforge` downloads need the CurseForge API to resolve
+        tracing::warn!(
+            "Skipping packwiz file {path} with unsupported download mode {:?}",
+            metafile.download.mode
+        );
+        return Ok(None);
+    };
+
+    // The mrpack format needs a sha1 and sha512 and the real size of every
+    // file, which packwiz doesn't record, so the file is downloaded here and
+    // checked against the hash packwiz does record
+    let bytes =
+        fetch(&url, None, None, None, &state.fetch_semaphore, &state.pool)
+            .await?;
+    verify_hash(
+        &bytes,
+        &metafile.download.hash_format,
+        &metafile.download.hash,
+        path.as_str(),
+    )?;
+
+    let file_size = u32::try_from(bytes.len()).map_err(|_| {
+        crate::ErrorKind::InputError(format!("{path} is too large"))
+    })?;
+
+    Ok(Some(PackFile {
+        path,
+        hashes: direct_download_hashes(&bytes),
+        env: Some(env),
+        downloads: vec![url],
+        file_size,
+    }))
+}
+
+/// Computes the hashes an mrpack records for a file downloaded directly
+fn direct_download_hashes(bytes: &[u8]) -> HashMap<PackFileHash, String> {
+    HashMap::from([
+        (PackFileHash::Sha1, sha1_smol::Sha1::from(bytes).hexdigest()),
+        (
+            PackFileHash::Sha512,
+            format!("{:x}", sha2::Sha512::digest(bytes)),
+        ),
+    ])
+}
+
+fn write_packwiz_mrpack(
+    packfile_data: &[u8],
+    overrides: Vec<(String, Bytes)>,
+) -> crate::Result<Bytes> {
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated);
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+    for (path, bytes) in overrides {
+        if path == PACK_FILE_NAME {
+            continue;
+        }
+        writer
+            .start_file(format!("overrides/{path}"), options)
+            .map_err(std::io::Error::from)?;
+        writer.write_all(&bytes)?;
+    }
+
+    writer
+        .start_file("modrinth.index.json", options)
+        .map_err(std::io::Error::from)?;
+    writer.write_all(packfile_data)?;
+    let cursor = writer.finish().map_err(std::io::Error::from)?;
+
+    Ok(Bytes::from(cursor.into_inner()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_packwiz_metafile_with_modrinth_update() {
+        let metafile: PackwizMetafile = parse_toml(
+            br#"
+name = "Sodium"
+filename = "sodium-fabric-0.5.3.jar"
+side = "client"
+
+[download]
+url = "https://cdn.modrinth.com/data/AANobbMI/versions/OihdIimA/sodium-fabric-0.5.3.jar"
+hash-format = "sha1"
+hash = "4a2b0d4cbf2bb0a8fc1b1e4f1bb3e5c9d0e1f2a3"
+
+[update.modrinth]
+mod-id = "AANobbMI"
+version = "OihdIimA"
+"#,
+            "sodium.pw.toml",
+        )
+        .unwrap();
+
+        let update = metafile.update.unwrap().modrinth.unwrap();
+        assert_eq!(update.mod_id, "AANobbMI");
+        assert_eq!(update.version, "OihdIimA");
+        assert_eq!(metafile.side.as_deref(), Some("client"));
+    }
+
+    #[test]
+    fn maps_packwiz_sides_to_pack_env() {
+        let env = side_env(Some("client"), false);
+        assert_eq!(env[&EnvType::Client], SideType::Required);
+        assert_eq!(env[&EnvType::Server], SideType::Unsupported);
+
+        let env = side_env(Some("both"), true);
+        assert_eq!(env[&EnvType::Client], SideType::Optional);
+        assert_eq!(env[&EnvType::Server], SideType::Optional);
+    }
+
+    #[test]
+    fn rejects_mismatched_hash() {
+        assert!(verify_hash(b"packwiz", "sha256", "00", "index.toml").is_err());
+        assert!(verify_hash(b"packwiz", "murmur2", "00", "index.toml").is_ok());
+    }
+
+    #[test]
+    fn hashes_direct_downloads_for_mrpack() {
+        let hashes = direct_download_hashes(b"packwiz");
+        assert_eq!(hashes.len(), 2);
+        assert_eq!(
+            hashes[&PackFileHash::Sha1],
+            sha1_smol::Sha1::from(b"packwiz").hexdigest()
+        );
+        assert!(
+            verify_hash(
+                b"packwiz",
+                "sha512",
+                &hashes[&PackFileHash::Sha512],
+                "mod.jar"
+            )
+            .is_ok()
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::State;
+use crate::api::pack::import::packwiz::{PackwizSource, get_packwiz_instance};
 use crate::data::ModLoader;
 use crate::event::LoadingBarType;
 use crate::event::emit::{emit_loading, init_loading};
@@ -103,6 +104,10 @@ pub enum CreatePackLocation {
     FromFile {
         path: PathBuf,
     },
+    // Create a pack from a packwiz repository, either a local directory or a pack.toml URL
+    FromPackwiz {
+        source: PackwizSource,
+    },
 }
 
 #[derive(Serialize, Deserialize)]
@@ -194,6 +199,9 @@ pub async fn get_instance_from_pack(
             }),
             ..Default::default()
         }),
+        CreatePackLocation::FromPackwiz { source } => {
+            get_packwiz_instance(source).await
+        }
         CreatePackLocation::FromFile { path } => {
             let mut instance = get_local_pack_instance(&path);
             let file_size = tokio::fs::metadata(&path).await?.len();
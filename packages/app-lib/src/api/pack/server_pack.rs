@@ -0,0 +1,401 @@
+//! Generating dedicated server directories from instances and modpacks.
+//!
+//! Instances are exported to a `.mrpack` first, so both sources go through
+//! the same path: the pack's server-side files are downloaded, the overrides
+//! are extracted, and the loader's server side is installed next to them.
+
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+
+use futures::{StreamExt, TryStreamExt, stream};
+use serde::{Deserialize, Serialize};
+
+use crate::State;
+use crate::launcher::server::{ServerLaunchCommand, install_server_loader};
+use crate::pack::install_from::{
+    EnvType, PackDependency, PackFile, PackFileHash, PackFormat,
+};
+use crate::state::{ModLoader, SideType};
+use crate::util::fetch::{fetch, write};
+use crate::util::io::{self, IOError};
+
+const SERVER_PACK_DOWNLOAD_CONCURRENCY: usize = 16;
+const DEFAULT_SERVER_JVM_ARGS: &str = "-Xmx4G";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ServerPackSource {
+    Instance {
+        #[serde(rename = "instanceId")]
+        instance_id: String,
+    },
+    Mrpack {
+        path: PathBuf,
+    },
+}
+
+/// Writes a runnable server for `source` into `output_dir`.
+///
+/// Client-only content is left out using the pack's env data. Downloads go
+/// through the app's state, so it has to be initialized first.
+#[tracing::instrument]
+pub async fn generate_server_pack(
+    source: ServerPackSource,
+    output_dir: PathBuf,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    io::create_dir_all(&output_dir).await?;
+
+    match source {
+        ServerPackSource::Mrpack { path } => {
+            generate_server_pack_from_mrpack(&state, path, &output_dir).await
+        }
+        ServerPackSource::Instance { instance_id } => {
+            let included =
+                crate::api::instance::get_pack_export_candidates(&instance_id)
+                    .await?
+                    .into_iter()
+                    .filter(|candidate| candidate.default_selected)
+                    .map(|candidate| candidate.path.as_str().to_string())
+                    .collect();
+            let export_dir =
+                state.directories.caches_dir().join("server_packs");
+            io::create_dir_all(&export_dir).await?;
+            let mrpack_path = export_dir.join(format!("{instance_id}.mrpack"));
+            let result = async {
+                crate::api::instance::export_mrpack(
+                    &instance_id,
+                    mrpack_path.clone(),
+                    included,
+                    Vec::new(),
+                    None,
+                    None,
+                    None,
+                )
+                .await?;
+                generate_server_pack_from_mrpack(
+                    &state,
+                    mrpack_path.clone(),
+                    &output_dir,
+                )
+                .await
+            }
+            .await;
+
+            // A failed cleanup shouldn't hide whether the server was
+            // generated, and the export may have failed before writing
+            match io::remove_file(&mrpack_path).await {
+                Ok(()) => (),
+                Err(err) if err.kind() == ErrorKind::NotFound => (),
+                Err(err) => tracing::warn!(
+                    "Failed to remove exported server pack {}: {err}",
+                    mrpack_path.display()
+                ),
+            }
+            result
+        }
+    }
+}
+
+async fn generate_server_pack_from_mrpack(
+    state: &State,
+    mrpack_path: PathBuf,
+    output_dir: &Path,
+) -> crate::Result<()> {
+    let pack = extract_server_overrides(mrpack_path, output_dir).await?;
+
+    let files = pack
+        .files
+        .into_iter()
+        .filter(is_server_file)
+        .collect::<Vec<_>>();
+    stream::iter(files)
+        .map(Ok::<PackFile, crate::Error>)
+        .try_for_each_concurrent(
+            Some(SERVER_PACK_DOWNLOAD_CONCURRENCY),
+            |file| download_pack_file(state, file, output_dir),
+        )
+        .await?;
+
+    let (game_version, loader, loader_version) =
+        server_loader(pack.dependencies)?;
+
+    let launch_command = install_server_loader(
+        &game_version,
+        loader,
+        loader_version.as_deref(),
+        output_dir,
+    )
+    .await?;
+    write_start_scripts(state, &launch_command, output_dir).await
+}
+
+fn is_server_file(file: &PackFile) -> bool {
+    file.env.as_ref().is_none_or(|env| {
+        env.get(&EnvType::Server) != Some(&SideType::Unsupported)
+    })
+}
+
+/// Returns the Minecraft version, loader and loader version a pack depends on
+fn server_loader(
+    dependencies: impl IntoIterator<Item = (PackDependency, String)>,
+) -> crate::Result<(String, ModLoader, Option<String>)> {
+    let mut game_version = None;
+    let mut loader = ModLoader::Vanilla;
+    let mut loader_version = None;
+    for (dependency, version) in dependencies {
+        match dependency {
+            PackDependency::Minecraft => game_version = Some(version),
+            PackDependency::Forge => {
+                loader = ModLoader::Forge;
+                loader_version = Some(version);
+            }
+            PackDependency::NeoForge => {
+                loader = ModLoader::NeoForge;
+                loader_version = Some(version);
+            }
+            PackDependency::FabricLoader => {
+                loader = ModLoader::Fabric;
+                loader_version = Some(version);
+            }
+            PackDependency::QuiltLoader => {
+                loader = ModLoader::Quilt;
+                loader_version = Some(version);
+            }
+        }
+    }
+    let Some(game_version) = game_version else {
+        return Err(crate::ErrorKind::InputError(
+            "Pack did not specify Minecraft version".to_string(),
+        )
+        .into());
+    };
+
+    Ok((game_version, loader, loader_version))
+}
+
+/// Reads the pack index and extracts `overrides` followed by
+/// `server-overrides`, so server-specific files win over shared ones.
+async fn extract_server_overrides(
+    mrpack_path: PathBuf,
+    output_dir: &Path,
+) -> crate::Result<PackFormat> {
+    let output_dir = output_dir.to_path_buf();
+
+    tokio::task::spawn_blocking(move || {
+        let file = std::fs::File::open(&mrpack_path)
+            .map_err(|error| IOError::with_path(error, &mrpack_path))?;
+        let mut archive = zip::ZipArchive::new(file).map_err(|_| {
+            crate::ErrorKind::InputError(
+                "Failed to read input modpack zip".to_string(),
+            )
+        })?;
+
+        let pack: PackFormat = {
+            let mut index =
+                archive.by_name("modrinth.index.json").map_err(|_| {
+                    crate::ErrorKind::InputError(
+                        "No pack manifest found in mrpack".to_string(),
+                    )
+                })?;
+            let mut bytes = Vec::new();
+            index.read_to_end(&mut bytes).map_err(IOError::from)?;
+            serde_json::from_slice(&bytes)?
+        };
+
+        for prefix in ["overrides/", "server-overrides/"] {
+            for index in 0..archive.len() {
+                let mut entry = archive.by_index(index).map_err(|_| {
+                    crate::ErrorKind::InputError(
+                        "Failed to read input modpack zip".to_string(),
+                    )
+                })?;
+                if entry.is_dir() {
+                    continue;
+                }
+                let Some(relative_path) =
+                    entry.enclosed_name().and_then(|path| {
+                        path.strip_prefix(prefix).ok().map(PathBuf::from)
+                    })
+                else {
+                    continue;
+                };
+
+                let path = output_dir.join(relative_path);
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)
+                        .map_err(|error| IOError::with_path(error, parent))?;
+                }
+                let mut target = std::fs::File::create(&path)
+                    .map_err(|error| IOError::with_path(error, &path))?;
+                std::io::copy(&mut entry, &mut target)
+                    .map_err(|error| IOError::with_path(error, &path))?;
+            }
+        }
+
+        Ok::<PackFormat, crate::Error>(pack)
+    })
+    .await?
+}
+
+async fn download_pack_file(
+    state: &State,
+    file: PackFile,
+    output_dir: &Path,
+) -> crate::Result<()> {
+    let sha1 = file.hashes.get(&PackFileHash::Sha1).map(String::as_str);
+    let mut last_error = None;
+
+    for url in &file.downloads {
+        match fetch(url, sha1, None, None, &state.fetch_semaphore, &state.pool)
+            .await
+        {
+            Ok(bytes) => {
+                let path = output_dir.join(file.path.as_str());
+                return write(&path, &bytes, &state.io_semaphore).await;
+            }
+            Err(error) => last_error = Some(error),
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| {
+        crate::ErrorKind::InputError(format!(
+            "No downloads available for {}",
+            file.path
+        ))
+        .into()
+    }))
+}
+
+async fn write_start_scripts(
+    state: &State,
+    launch_command: &ServerLaunchCommand,
+    output_dir: &Path,
+) -> crate::Result<()> {
+    if let ServerLaunchCommand::ArgsFiles { .. } = launch_command {
+        write(
+            &output_dir.join("user_jvm_args.txt"),
+            format!("{DEFAULT_SERVER_JVM_ARGS}\n").as_bytes(),
+            &state.io_semaphore,
+        )
+        .await?;
+    }
+    let (unix, windows) = start_commands(launch_command);
+
+    let run_sh = output_dir.join("run.sh");
+    write(
+        &run_sh,
+        format!("#!/usr/bin/env sh\n{unix} \"$@\"\n").as_bytes(),
+        &state.io_semaphore,
+    )
+    .await?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        tokio::fs::set_permissions(
+            &run_sh,
+            std::fs::Permissions::from_mode(0o755),
+        )
+        .await
+        .map_err(|error| IOError::with_path(error, &run_sh))?;
+    }
+    write(
+        &output_dir.join("run.bat"),
+        format!("@echo off\r\n{windows} %*\r\npause\r\n").as_bytes(),
+        &state.io_semaphore,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Returns the commands `run.sh` and `run.bat` start the server with
+fn start_commands(launch_command: &ServerLaunchCommand) -> (String, String) {
+    match launch_command {
+        ServerLaunchCommand::Jar(jar) => (
+            format!("java {DEFAULT_SERVER_JVM_ARGS} -jar {jar} nogui"),
+            format!("java {DEFAULT_SERVER_JVM_ARGS} -jar {jar} nogui"),
+        ),
+        ServerLaunchCommand::ArgsFiles { unix, windows } => (
+            format!("java @user_jvm_args.txt @{unix} nogui"),
+            format!("java @user_jvm_args.txt @{windows} nogui"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn pack_file(env: Option<(SideType, SideType)>) -> PackFile {
+        PackFile {
+            path: "mods/example.jar".to_string().try_into().unwrap(),
+            hashes: HashMap::new(),
+            env: env.map(|(client, server)| {
+                HashMap::from([
+                    (EnvType::Client, client),
+                    (EnvType::Server, server),
+                ])
+            }),
+            downloads: Vec::new(),
+            file_size: 0,
+        }
+    }
+
+    #[test]
+    fn leaves_out_client_only_files() {
+        assert!(is_server_file(&pack_file(None)));
+        assert!(is_server_file(&pack_file(Some((
+            SideType::Unsupported,
+            SideType::Required
+        )))));
+        assert!(is_server_file(&pack_file(Some((
+            SideType::Required,
+            SideType::Optional
+        )))));
+        assert!(!is_server_file(&pack_file(Some((
+            SideType::Required,
+            SideType::Unsupported
+        )))));
+    }
+
+    #[test]
+    fn reads_loader_from_pack_dependencies() {
+        let (game_version, loader, loader_version) = server_loader([
+            (PackDependency::Minecraft, "1.20.1".to_string()),
+            (PackDependency::Forge, "47.2.0".to_string()),
+        ])
+        .unwrap();
+        assert_eq!(game_version, "1.20.1");
+        assert_eq!(loader, ModLoader::Forge);
+        assert_eq!(loader_version.as_deref(), Some("47.2.0"));
+
+        let (_, loader, loader_version) =
+            server_loader([(PackDependency::Minecraft, "1.21".to_string())])
+                .unwrap();
+        assert_eq!(loader, ModLoader::Vanilla);
+        assert_eq!(loader_version, None);
+
+        assert!(
+            server_loader([(PackDependency::FabricLoader, "0.16.0".into())])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn start_commands_use_args_files_for_forge() {
+        let (unix, windows) =
+            start_commands(&ServerLaunchCommand::Jar("server.jar".into()));
+        assert_eq!(unix, "java -Xmx4G -jar server.jar nogui");
+        assert_eq!(unix, windows);
+
+        let (unix, windows) = start_commands(&ServerLaunchCommand::ArgsFiles {
+            unix: "unix_args.txt".to_string(),
+            windows: "win_args.txt".to_string(),
+        });
+        assert_eq!(unix, "java @user_jvm_args.txt @unix_args.txt nogui");
+        assert_eq!(windows, "java @user_jvm_args.txt @win_args.txt nogui");
+    }
+}
@@ -0,0 +1,36 @@
+//! Theseus signing key pins for projects
+use crate::State;
+use crate::state::project_signing_keys;
+
+/// Gets the OpenPGP key fingerprint a project is pinned to, if any
+#[tracing::instrument]
+pub async fn get_pinned_signing_key(
+    project_id: &str,
+) -> crate::Result<Option<String>> {
+    let state = State::get().await?;
+    project_signing_keys::get_pinned_signing_key(project_id, &state.pool).await
+}
+
+/// Pins a project to an OpenPGP key fingerprint. From then on, only files
+/// with a signature verified against that key are installed for it.
+#[tracing::instrument]
+pub async fn pin_signing_key(
+    project_id: &str,
+    fingerprint: &str,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    project_signing_keys::set_pinned_signing_key(
+        project_id,
+        Some(fingerprint),
+        &state.pool,
+    )
+    .await
+}
+
+/// Unpins a project from its OpenPGP key
+#[tracing::instrument]
+pub async fn unpin_signing_key(project_id: &str) -> crate::Result<()> {
+    let state = State::get().await?;
+    project_signing_keys::set_pinned_signing_key(project_id, None, &state.pool)
+        .await
+}
@@ -116,6 +116,9 @@ pub enum ErrorKind {
     #[error("Incorrect Sha1 hash for download: {0} != {1}")]
     HashError(String, String),
 
+    #[error("Signature verification failed: {0}")]
+    SignatureError(String),
+
     #[error("Regex error: {0}")]
     RegexError(#[from] regex::Error),
 
@@ -167,6 +167,7 @@ pub fn export_app_event_bindings(
             crate::state::OnboardingChecklist,
             FriendPayload,
             FriendStatusPayload,
+            WorldInvitePayload,
             LogEvent,
             LogPayload,
             crate::state::Log4jEvent,
@@ -505,6 +506,8 @@ pub enum FriendPayload {
     UserOffline { id: String },
     StatusUpdate { user_status: FriendStatusPayload },
     StatusSync,
+    WorldInvite { invite: WorldInvitePayload },
+    WorldInviteExpired { socket: String },
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -528,6 +531,38 @@ impl From<ariadne::users::UserStatus> for FriendStatusPayload {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(
+    feature = "export-ts",
+    derive(ts_rs::TS, postcard_bindgen::PostcardBindings)
+)]
+pub struct WorldInvitePayload {
+    pub socket: String,
+    pub from: String,
+    pub world_name: String,
+    pub game_version: String,
+    pub loader: String,
+    pub loader_version: Option<String>,
+    pub content_hash: String,
+}
+
+impl WorldInvitePayload {
+    pub fn new(
+        from: ariadne::ids::UserId,
+        invite: &ariadne::networking::message::WorldInvite,
+    ) -> Self {
+        Self {
+            socket: invite.socket.to_string(),
+            from: from.to_string(),
+            world_name: invite.world_name.clone(),
+            game_version: invite.game_version.clone(),
+            loader: invite.loader.clone(),
+            loader_version: invite.loader_version.clone(),
+            content_hash: invite.content_hash.clone(),
+        }
+    }
+}
+
 pub use self::log_types::*;
 
 mod log_types {
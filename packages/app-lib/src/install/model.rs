@@ -1,3 +1,4 @@
+use crate::api::instance::SharedInstanceConfigResolution;
 use crate::api::pack::import::ImportLauncherType;
 use crate::api::pack::install_from::{CreatePackInstance, CreatePackLocation};
 use crate::state::{
@@ -6,6 +7,7 @@ use crate::state::{
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 pub type InstallModpackPreview = CreatePackInstance;
@@ -174,6 +176,14 @@ pub enum InstallRequest {
     CreateSharedInstance {
         data: SharedInstanceInstallData,
     },
+    CreateCollectionInstance {
+        collection_id: String,
+        #[serde(default)]
+        name: Option<String>,
+        game_version: String,
+        loader: ModLoader,
+        loader_version: Option<String>,
+    },
     ImportInstance {
         launcher_type: ImportLauncherType,
         base_path: PathBuf,
@@ -231,6 +241,9 @@ pub struct SharedInstanceInstallData {
     pub game_version: String,
     pub loader: ModLoader,
     pub loader_version: Option<String>,
+    /// How to resolve config files that changed on both sides, by path
+    #[serde(default)]
+    pub config_resolutions: HashMap<String, SharedInstanceConfigResolution>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -260,6 +273,9 @@ impl InstallRequest {
             Self::CreateSharedInstance { .. } => {
                 InstallJobKind::CreateSharedInstance
             }
+            Self::CreateCollectionInstance { .. } => {
+                InstallJobKind::CreateCollectionInstance
+            }
             Self::ImportInstance { .. } => InstallJobKind::ImportInstance,
             Self::DuplicateInstance { .. } => InstallJobKind::DuplicateInstance,
             Self::InstallExistingInstance { .. } => {
@@ -311,6 +327,7 @@ pub enum InstallJobKind {
     CreateInstance,
     CreateModpackInstance,
     CreateSharedInstance,
+    CreateCollectionInstance,
     ImportInstance,
     DuplicateInstance,
     InstallExistingInstance,
@@ -324,6 +341,7 @@ impl InstallJobKind {
             Self::CreateInstance => "create_instance",
             Self::CreateModpackInstance => "create_modpack_instance",
             Self::CreateSharedInstance => "create_shared_instance",
+            Self::CreateCollectionInstance => "create_collection_instance",
             Self::ImportInstance => "import_instance",
             Self::DuplicateInstance => "duplicate_instance",
             Self::InstallExistingInstance => "install_existing_instance",
@@ -338,6 +356,7 @@ impl InstallJobKind {
         match value {
             "create_modpack_instance" => Self::CreateModpackInstance,
             "create_shared_instance" => Self::CreateSharedInstance,
+            "create_collection_instance" => Self::CreateCollectionInstance,
             "import_instance" => Self::ImportInstance,
             "duplicate_instance" => Self::DuplicateInstance,
             "install_existing_instance" => Self::InstallExistingInstance,
@@ -407,6 +407,9 @@ fn display_from_request(state: &InstallJobState) -> Option<InstallJobDisplay> {
             }),
             crate::api::pack::install_from::CreatePackLocation::FromFile {
                 ..
+            }
+            | crate::api::pack::install_from::CreatePackLocation::FromPackwiz {
+                ..
             } => None,
         },
         InstallRequest::CreateSharedInstance { data } => {
@@ -418,6 +421,14 @@ fn display_from_request(state: &InstallJobState) -> Option<InstallJobDisplay> {
                     .and_then(|modpack| modpack.icon_url.clone()),
             })
         }
+        InstallRequest::CreateCollectionInstance {
+            collection_id,
+            name,
+            ..
+        } => Some(InstallJobDisplay {
+            title: name.clone().unwrap_or_else(|| collection_id.clone()),
+            icon: None,
+        }),
         InstallRequest::ImportInstance {
             instance_folder, ..
         } => Some(InstallJobDisplay {
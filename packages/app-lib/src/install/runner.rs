@@ -13,6 +13,10 @@ use super::shared_instance::{
 };
 use super::{diagnostics, recovery, store};
 use crate::ErrorKind;
+use crate::api::pack::import::collection::{
+    get_collection, install_collection_content,
+};
+use crate::api::pack::import::packwiz::generate_pack_from_packwiz;
 use crate::api::pack::install_from::{
     CreatePackLocation, generate_pack_from_file,
     generate_pack_from_version_id_with_reporter, get_instance_from_pack,
@@ -77,6 +81,23 @@ pub async fn update_shared_instance(
     start(InstallRequest::UpdateSharedInstance { instance_id, data }).await
 }
 
+pub async fn create_collection_instance(
+    collection_id: String,
+    name: Option<String>,
+    game_version: String,
+    loader: ModLoader,
+    loader_version: Option<String>,
+) -> crate::Result<InstallJobSnapshot> {
+    start(InstallRequest::CreateCollectionInstance {
+        collection_id,
+        name,
+        game_version,
+        loader,
+        loader_version,
+    })
+    .await
+}
+
 pub async fn import_instance(
     launcher_type: crate::api::pack::import::ImportLauncherType,
     base_path: PathBuf,
@@ -555,6 +576,31 @@ async fn prepare_initial_instance(
             attach_pending_shared_instance(&instance_id, &data, state).await?;
             emit_instance(&instance_id, InstancePayloadType::Edited).await?;
         }
+        InstallRequest::CreateCollectionInstance {
+            collection_id,
+            name,
+            game_version,
+            loader,
+            loader_version,
+        } => {
+            let collection = get_collection(&collection_id).await?;
+            let metadata = crate::api::instance::create(
+                name.unwrap_or(collection.name),
+                game_version,
+                loader,
+                loader_version,
+                collection.icon_url,
+                None,
+                InstanceLink::Unmanaged,
+            )
+            .await?;
+            set_display(
+                job_state,
+                metadata.instance.name,
+                metadata.instance.icon_path,
+            );
+            set_instance_id(job_state, metadata.instance.id);
+        }
         InstallRequest::ImportInstance {
             instance_folder, ..
         } => {
@@ -911,6 +957,54 @@ async fn run_request(
 
             Ok(Some(instance_id))
         }
+        InstallRequest::CreateCollectionInstance {
+            collection_id,
+            game_version,
+            loader,
+            ..
+        } => {
+            let Some(instance_id) = current_instance_id(job_state) else {
+                return Err(crate::ErrorKind::InputError(
+                    "Install job is missing its instance id".to_string(),
+                )
+                .into());
+            };
+            let collection = get_collection(&collection_id).await?;
+            install_collection_content(
+                &instance_id,
+                &collection,
+                InstallProgressReporter::new(job_id, job_state.clone()),
+            )
+            .await?;
+            update_progress(
+                job_id,
+                job_state,
+                state,
+                InstallPhaseId::DownloadingMinecraft,
+                InstallPhaseDetails::Minecraft {
+                    game_version,
+                    loader,
+                },
+            )
+            .await?;
+            let context =
+                crate::state::instances::commands::get_instance_launch_context(
+                    &instance_id,
+                    &state.pool,
+                )
+                .await?
+                .ok_or_else(|| {
+                    crate::ErrorKind::InputError("Unknown instance".to_string())
+                })?;
+            crate::launcher::install_minecraft_with_reporter(
+                &context,
+                false,
+                Some(InstallProgressReporter::new(job_id, job_state.clone())),
+            )
+            .await?;
+            emit_instance(&instance_id, InstancePayloadType::Edited).await?;
+            Ok(Some(instance_id))
+        }
         InstallRequest::ImportInstance {
             launcher_type,
             base_path,
@@ -1354,6 +1448,14 @@ pub(super) async fn install_pack(
                 .await?;
             generate_pack_from_file(path, instance_id.clone()).await?
         }
+        CreatePackLocation::FromPackwiz { source } => {
+            generate_pack_from_packwiz(
+                source,
+                instance_id.clone(),
+                reporter.clone(),
+            )
+            .await?
+        }
     };
 
     Box::pin(install_zipped_mrpack_files_with_reporter(
@@ -1515,6 +1617,7 @@ fn set_initial_display(job_state: &mut InstallJobState) {
                 CreatePackLocation::FromFile { path } => {
                     Some((get_local_pack_instance(path).name, None))
                 }
+                CreatePackLocation::FromPackwiz { .. } => None,
             }
         }
         _ => None,
@@ -1599,6 +1702,7 @@ fn install_error_code(
         }
         ErrorKind::LabrinthError(_) => "api_error",
         ErrorKind::HashError(_, _) => "hash_error",
+        ErrorKind::SignatureError(_) => "signature_error",
         ErrorKind::ZipError(_) => "archive_error",
         ErrorKind::DeserializationError(_) | ErrorKind::StripPrefixError(_) => {
             "path_error"
@@ -1641,10 +1745,13 @@ pub(super) fn modpack_details(
             version_id: Some(version_id.clone()),
             title: Some(title.clone()),
         },
-        CreatePackLocation::FromFile { .. } => InstallPhaseDetails::Modpack {
-            project_id: None,
-            version_id: None,
-            title: None,
-        },
+        CreatePackLocation::FromFile { .. }
+        | CreatePackLocation::FromPackwiz { .. } => {
+            InstallPhaseDetails::Modpack {
+                project_id: None,
+                version_id: None,
+                title: None,
+            }
+        }
     }
 }
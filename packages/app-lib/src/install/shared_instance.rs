@@ -8,9 +8,8 @@ use super::runner::{
     install_pack, modpack_details, update_content_progress, update_progress,
 };
 use crate::api::instance::{
-    CONFIG_BUNDLE_FILE_TYPE, CONFIG_DIRECTORY, CONFIG_FILE_EXTENSIONS,
-    CONFIG_SYNC_ENABLED, MAX_CONFIG_BUNDLE_ENTRIES,
-    read_bounded_config_bundle_entry,
+    CONFIG_BUNDLE_FILE_TYPE, CONFIG_DIRECTORY, CONFIG_SYNC_ENABLED,
+    SharedInstanceConfigResolution, merge_config_bundle, save_config_base,
 };
 use crate::api::pack::install_from::CreatePackLocation;
 use crate::state::instances::adapters::sqlite::content_rows;
@@ -22,7 +21,6 @@ use crate::util::fetch::{DownloadReason, REQWEST_CLIENT};
 use futures::StreamExt;
 use path_util::SafeRelativeUtf8UnixPathBuf;
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
 use uuid::Uuid;
 
 const MAX_SHARED_INSTANCE_EXTERNAL_FILE_SIZE: u64 = 500 * 1024 * 1024;
@@ -336,8 +334,13 @@ pub(super) async fn apply_shared_instance_update(
         .into_iter()
         .chain(plan.external_additions)
     {
-        install_shared_instance_external_file(instance_id, &file.file, state)
-            .await?;
+        install_shared_instance_external_file(
+            instance_id,
+            &file.file,
+            &data.config_resolutions,
+            state,
+        )
+        .await?;
         completed_content_changes += 1;
         update_content_progress(
             job_id,
@@ -353,6 +356,7 @@ pub(super) async fn apply_shared_instance_update(
         install_shared_instance_external_file(
             instance_id,
             &config_bundle,
+            &data.config_resolutions,
             state,
         )
         .await?;
@@ -673,8 +677,13 @@ pub(super) async fn apply_shared_instance_content(
         }
 
         for file in &data.external_files {
-            install_shared_instance_external_file(instance_id, file, state)
-                .await?;
+            install_shared_instance_external_file(
+                instance_id,
+                file,
+                &data.config_resolutions,
+                state,
+            )
+            .await?;
             completed_content_changes += 1;
             update_content_progress(
                 job_id,
@@ -787,6 +796,7 @@ pub(super) fn shared_instance_link(
 async fn install_shared_instance_external_file(
     instance_id: &str,
     file: &SharedInstanceExternalFileData,
+    config_resolutions: &HashMap<String, SharedInstanceConfigResolution>,
     state: &State,
 ) -> crate::Result<()> {
     if file.file_type == CONFIG_BUNDLE_FILE_TYPE && !CONFIG_SYNC_ENABLED {
@@ -868,6 +878,7 @@ async fn install_shared_instance_external_file(
         return install_shared_instance_config_bundle(
             instance_id,
             bytes,
+            config_resolutions,
             state,
         )
         .await;
@@ -920,14 +931,14 @@ fn validate_shared_instance_external_file_name(
     Ok(())
 }
 
+/// Merges the bundle into the instance's config files, keeping the edits the
+/// member made since the last sync.
 async fn install_shared_instance_config_bundle(
     instance_id: &str,
     bytes: bytes::Bytes,
+    config_resolutions: &HashMap<String, SharedInstanceConfigResolution>,
     state: &State,
 ) -> crate::Result<()> {
-    let files =
-        tokio::task::spawn_blocking(move || read_config_bundle(bytes.as_ref()))
-            .await??;
     let metadata = crate::state::instances::commands::get_instance_metadata(
         instance_id,
         &state.pool,
@@ -943,74 +954,22 @@ async fn install_shared_instance_config_bundle(
         .join(CONFIG_DIRECTORY);
     crate::util::io::create_dir_all(&config_path).await?;
 
-    for (relative_path, bytes) in files {
+    let merge = merge_config_bundle(
+        instance_id,
+        &config_path,
+        bytes.clone(),
+        config_resolutions,
+        state,
+    )
+    .await?;
+    for (relative_path, bytes) in merge.files {
         let path = config_path.join(relative_path);
         if let Some(parent) = path.parent() {
             crate::util::io::create_dir_all(parent).await?;
         }
         crate::util::io::write(path, bytes).await?;
     }
+    save_config_base(instance_id, &bytes, state).await?;
 
     Ok(())
 }
-
-fn read_config_bundle(bytes: &[u8]) -> crate::Result<Vec<(PathBuf, Vec<u8>)>> {
-    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
-        .map_err(|error| {
-            crate::ErrorKind::InputError(format!(
-                "Invalid shared instance config bundle: {error}"
-            ))
-        })?;
-    if archive.len() > MAX_CONFIG_BUNDLE_ENTRIES {
-        return Err(crate::ErrorKind::InputError(
-            "Shared instance config bundle contains too many entries"
-                .to_string(),
-        )
-        .into());
-    }
-    let mut files = Vec::new();
-    let mut total_size = 0;
-
-    for index in 0..archive.len() {
-        let file = archive.by_index(index).map_err(|error| {
-            crate::ErrorKind::InputError(format!(
-                "Invalid shared instance config bundle entry: {error}"
-            ))
-        })?;
-        if file.is_dir() {
-            continue;
-        }
-        let path = file.enclosed_name().ok_or_else(|| {
-            crate::ErrorKind::InputError(
-                "Shared instance config bundle contains an unsafe path"
-                    .to_string(),
-            )
-        })?;
-        if !is_supported_config_file(&path) {
-            return Err(crate::ErrorKind::InputError(format!(
-                "Shared instance config bundle contains unsupported file {}",
-                path.display()
-            ))
-            .into());
-        }
-        let declared_size = file.size();
-        let bytes = read_bounded_config_bundle_entry(
-            file,
-            declared_size,
-            &mut total_size,
-        )?;
-        files.push((path.to_path_buf(), bytes));
-    }
-
-    Ok(files)
-}
-
-fn is_supported_config_file(path: &std::path::Path) -> bool {
-    path.extension()
-        .and_then(|extension| extension.to_str())
-        .is_some_and(|extension| {
-            CONFIG_FILE_EXTENSIONS
-                .iter()
-                .any(|candidate| extension.eq_ignore_ascii_case(candidate))
-        })
-}
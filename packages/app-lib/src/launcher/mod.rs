@@ -36,6 +36,7 @@ pub(crate) mod hooks;
 
 pub mod download;
 pub mod quick_play_version;
+pub(crate) mod server;
 
 // All nones -> disallowed
 // 1+ true -> allowed
@@ -0,0 +1,579 @@
+//! Installing Minecraft and mod loaders into a dedicated server directory
+use crate::data::ModLoader;
+use crate::instance::QuickPlayType;
+use crate::launcher::{
+    args, get_loader_version_from_profile, parse_rules,
+    resolve_minecraft_manifest,
+};
+use crate::state::State;
+use crate::util::fetch::{fetch, fetch_json, write};
+use crate::util::io::IOError;
+use daedalus as d;
+use daedalus::minecraft::{
+    Argument, ArgumentType, DownloadType, Library, LibraryDownloads,
+    VersionInfo,
+};
+use daedalus::modded::{PartialVersionInfo, SidedDataEntry};
+use futures::{StreamExt, TryStreamExt, stream};
+use reqwest::Method;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use tokio::process::Command;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const SERVER_JAR: &str = "server.jar";
+const SERVER_LIBRARIES_DIR: &str = "libraries";
+const SERVER_LIBRARY_DOWNLOAD_CONCURRENCY: usize = 16;
+
+/// Libraries the client version lists which dedicated servers don't use,
+/// matched against the start of the maven name
+const CLIENT_ONLY_LIBRARIES: &[&str] = &[
+    "ca.weblite:java-objc-bridge:",
+    "com.mojang:blocklist:",
+    "com.mojang:patchy:",
+    "com.mojang:realms:",
+    "com.mojang:text2speech:",
+    "com.paulscode:",
+    "net.java.jinput:",
+    "net.java.jutils:",
+    "org.lwjgl:",
+    "org.lwjgl.lwjgl:",
+    "tv.twitch:",
+];
+
+/// How the generated server directory is started
+pub enum ServerLaunchCommand {
+    /// `java -jar <jar>`
+    Jar(String),
+    /// `java @<args file>`, with separate args files for Unix and Windows
+    /// since they use different classpath separators
+    ArgsFiles { unix: String, windows: String },
+}
+
+/// Downloads the Minecraft server and installs the server side of `loader`
+/// into `server_dir`, the same way the loader's own installer would.
+#[tracing::instrument]
+pub async fn install_server_loader(
+    game_version: &str,
+    loader: ModLoader,
+    loader_version: Option<&str>,
+    server_dir: &Path,
+) -> crate::Result<ServerLaunchCommand> {
+    let state = State::get().await?;
+    let (minecraft, version_index) =
+        resolve_minecraft_manifest(game_version, &state).await?;
+    let version = &minecraft.versions[version_index];
+    let version_info: VersionInfo = fetch_json(
+        Method::GET,
+        &version.url,
+        None,
+        None,
+        None,
+        &state.api_semaphore,
+        &state.pool,
+    )
+    .await?;
+
+    let server_download = version_info
+        .downloads
+        .get(&DownloadType::Server)
+        .ok_or_else(|| {
+            crate::ErrorKind::LauncherError(format!(
+                "No server downloads exist for version {game_version}"
+            ))
+        })?;
+    let bytes = fetch(
+        &server_download.url,
+        Some(&server_download.sha1),
+        None,
+        None,
+        &state.fetch_semaphore,
+        &state.pool,
+    )
+    .await?;
+    write(&server_dir.join(SERVER_JAR), &bytes, &state.io_semaphore).await?;
+
+    if loader == ModLoader::Vanilla {
+        return Ok(ServerLaunchCommand::Jar(SERVER_JAR.to_string()));
+    }
+
+    let loader_version =
+        get_loader_version_from_profile(game_version, loader, loader_version)
+            .await?
+            .ok_or_else(|| {
+                crate::ErrorKind::LauncherError(format!(
+                    "No {} version found for Minecraft {game_version}",
+                    loader.as_str()
+                ))
+            })?;
+    let mut partial: PartialVersionInfo = fetch_json(
+        Method::GET,
+        &loader_version.url,
+        None,
+        None,
+        None,
+        &state.api_semaphore,
+        &state.pool,
+    )
+    .await?;
+    for library in &mut partial.libraries {
+        library.name = library
+            .name
+            .replace(d::modded::DUMMY_REPLACE_STRING, game_version);
+    }
+
+    if matches!(loader, ModLoader::Fabric | ModLoader::Quilt) {
+        return install_knot_server(
+            &state,
+            loader,
+            &partial.libraries,
+            server_dir,
+        )
+        .await;
+    }
+
+    let loader_arguments = partial.arguments.take();
+    let version_info = d::modded::merge_partial_version(partial, version_info);
+    install_forge_server(
+        &state,
+        version_info,
+        loader_arguments.unwrap_or_default(),
+        game_version,
+        server_dir,
+    )
+    .await
+}
+
+/// Writes the server launcher jar Fabric and Quilt installers generate, which
+/// puts the loader on the classpath and starts `server.jar` through Knot.
+async fn install_knot_server(
+    state: &State,
+    loader: ModLoader,
+    libraries: &[Library],
+    server_dir: &Path,
+) -> crate::Result<ServerLaunchCommand> {
+    let (name, launcher_main_class, main_class) = if loader == ModLoader::Quilt
+    {
+        (
+            "quilt",
+            "org.quiltmc.loader.impl.launch.server.QuiltServerLauncher",
+            "org.quiltmc.loader.impl.launch.knot.KnotServer",
+        )
+    } else {
+        (
+            "fabric",
+            "net.fabricmc.loader.impl.launch.server.FabricServerLauncher",
+            "net.fabricmc.loader.impl.launch.knot.KnotServer",
+        )
+    };
+
+    download_server_libraries(state, libraries, server_dir).await?;
+    let class_path = libraries
+        .iter()
+        .filter(|library| library.downloadable && library.include_in_classpath)
+        .map(|library| {
+            Ok(format!(
+                "{SERVER_LIBRARIES_DIR}/{}",
+                d::get_path_from_artifact(&library.name)?
+            ))
+        })
+        .collect::<crate::Result<Vec<_>>>()?
+        .join(" ");
+
+    let launcher_jar = format!("{name}-server-launch.jar");
+    let jar = {
+        let mut writer = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated);
+        writer
+            .start_file("META-INF/MANIFEST.MF", options)
+            .map_err(std::io::Error::from)?;
+        writer
+            .write_all(manifest(launcher_main_class, &class_path).as_bytes())?;
+        writer
+            .start_file(format!("{name}-server-launch.properties"), options)
+            .map_err(std::io::Error::from)?;
+        writer
+            .write_all(format!("launch.mainClass={main_class}\n").as_bytes())?;
+        writer.finish().map_err(std::io::Error::from)?.into_inner()
+    };
+    write(&server_dir.join(&launcher_jar), &jar, &state.io_semaphore).await?;
+    write(
+        &server_dir.join(format!("{name}-server-launcher.properties")),
+        format!("serverJar={SERVER_JAR}\n").as_bytes(),
+        &state.io_semaphore,
+    )
+    .await?;
+
+    Ok(ServerLaunchCommand::Jar(launcher_jar))
+}
+
+/// Manifest lines are limited to 72 bytes, longer values continue on the
+/// next line after a single space.
+fn manifest(main_class: &str, class_path: &str) -> String {
+    let mut manifest = String::from("Manifest-Version: 1.0\r\n");
+    for (key, value) in [("Main-Class", main_class), ("Class-Path", class_path)]
+    {
+        let line = format!("{key}: {value}");
+        let mut chars = line.chars().peekable();
+        let mut first = true;
+        while chars.peek().is_some() {
+            let width = if first { 72 } else { 71 };
+            let chunk = chars.by_ref().take(width).collect::<String>();
+            if !first {
+                manifest.push(' ');
+            }
+            manifest.push_str(&chunk);
+            manifest.push_str("\r\n");
+            first = false;
+        }
+    }
+    manifest.push_str("\r\n");
+    manifest
+}
+
+/// Runs the server side of the Forge/NeoForge installer processors and writes
+/// the args files the Forge installer would generate.
+async fn install_forge_server(
+    state: &State,
+    mut version_info: VersionInfo,
+    loader_arguments: HashMap<ArgumentType, Vec<Argument>>,
+    game_version: &str,
+    server_dir: &Path,
+) -> crate::Result<ServerLaunchCommand> {
+    let Some(processors) = version_info.processors.take() else {
+        return Err(crate::ErrorKind::LauncherError(format!(
+            "Server packs aren't supported for legacy Forge ({})",
+            version_info.id
+        ))
+        .into());
+    };
+
+    let java_key = version_info
+        .java_version
+        .as_ref()
+        .map_or(8, |it| it.major_version);
+    let java_path = crate::api::jre::auto_install_java(java_key).await?;
+    let java_version = crate::api::jre::check_jre(java_path).await?;
+
+    let libraries = version_info
+        .libraries
+        .into_iter()
+        .filter(|library| {
+            is_server_library(library)
+                && library.rules.as_ref().is_none_or(|rules| {
+                    parse_rules(
+                        rules,
+                        &java_version.architecture,
+                        &QuickPlayType::None,
+                        true,
+                    )
+                })
+        })
+        .collect::<Vec<_>>();
+    download_server_libraries(state, &libraries, server_dir).await?;
+
+    let libraries_dir = server_dir.join(SERVER_LIBRARIES_DIR);
+    let server_jar = server_dir.join(SERVER_JAR);
+    // `get_processor_arguments` resolves the client value of each entry, so
+    // the server values are moved over before running the processors
+    let mut data = version_info
+        .data
+        .take()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(key, entry)| {
+            (
+                key,
+                SidedDataEntry {
+                    client: entry.server.clone(),
+                    server: entry.server,
+                },
+            )
+        })
+        .collect::<HashMap<_, _>>();
+    for (key, value) in [
+        ("SIDE", "server".to_string()),
+        ("MINECRAFT_JAR", server_jar.to_string_lossy().to_string()),
+        ("MINECRAFT_VERSION", game_version.to_string()),
+        ("ROOT", server_dir.to_string_lossy().to_string()),
+        ("LIBRARY_DIR", libraries_dir.to_string_lossy().to_string()),
+    ] {
+        data.insert(
+            key.to_string(),
+            SidedDataEntry {
+                client: value.clone(),
+                server: value,
+            },
+        );
+    }
+
+    for processor in processors.iter().filter(|processor| {
+        processor
+            .sides
+            .as_ref()
+            .is_none_or(|sides| sides.iter().any(|side| side == "server"))
+    }) {
+        let cp = {
+            let mut cp = processor.classpath.clone();
+            cp.push(processor.jar.clone());
+            cp
+        };
+
+        let child = Command::new(&java_version.path)
+            .arg("-cp")
+            .arg(args::get_class_paths_jar(
+                &libraries_dir,
+                &cp,
+                &java_version.architecture,
+            )?)
+            .arg(
+                args::get_processor_main_class(args::get_lib_path(
+                    &libraries_dir,
+                    &processor.jar,
+                    false,
+                )?)
+                .await?
+                .ok_or_else(|| {
+                    crate::ErrorKind::LauncherError(format!(
+                        "Could not find processor main class for {}",
+                        processor.jar
+                    ))
+                })?,
+            )
+            .args(args::get_processor_arguments(
+                &libraries_dir,
+                &processor.args,
+                &data,
+            )?)
+            .current_dir(server_dir)
+            .output()
+            .await
+            .map_err(|e| IOError::with_path(e, &java_version.path))
+            .map_err(|err| {
+                crate::ErrorKind::LauncherError(format!(
+                    "Error running processor: {err}",
+                ))
+            })?;
+
+        if !child.status.success() {
+            return Err(crate::ErrorKind::LauncherError(format!(
+                "Processor error: {}",
+                String::from_utf8_lossy(&child.stderr)
+            ))
+            .as_error());
+        }
+    }
+
+    for (file_name, separator) in
+        [("unix_args.txt", ":"), ("win_args.txt", ";")]
+    {
+        let class_path = libraries
+            .iter()
+            .filter(|library| {
+                library.downloadable && library.include_in_classpath
+            })
+            .map(|library| {
+                Ok(format!(
+                    "{SERVER_LIBRARIES_DIR}/{}",
+                    d::get_path_from_artifact(&library.name)?
+                ))
+            })
+            .collect::<crate::Result<Vec<_>>>()?
+            .join(separator);
+
+        let mut lines = server_arguments(
+            loader_arguments.get(&ArgumentType::Jvm),
+            separator,
+            &version_info.id,
+        );
+        lines.push(format!("-cp {class_path}"));
+        lines.push(version_info.main_class.clone());
+        lines.extend(server_arguments(
+            loader_arguments.get(&ArgumentType::Game),
+            separator,
+            &version_info.id,
+        ));
+        lines.push(String::new());
+
+        write(
+            &server_dir.join(file_name),
+            lines.join("\n").as_bytes(),
+            &state.io_semaphore,
+        )
+        .await?;
+    }
+
+    Ok(ServerLaunchCommand::ArgsFiles {
+        unix: "unix_args.txt".to_string(),
+        windows: "win_args.txt".to_string(),
+    })
+}
+
+/// Whether a library from the merged client and loader version is used by
+/// dedicated servers, since the Forge and NeoForge version files only
+/// describe the client
+fn is_server_library(library: &Library) -> bool {
+    library.natives.is_none()
+        && !CLIENT_ONLY_LIBRARIES
+            .iter()
+            .any(|prefix| library.name.starts_with(prefix))
+}
+
+/// Converts the loader's client launch arguments to their server
+/// equivalents. Ruled arguments only apply to clients and are dropped.
+fn server_arguments(
+    arguments: Option<&Vec<Argument>>,
+    separator: &str,
+    version_name: &str,
+) -> Vec<String> {
+    arguments
+        .into_iter()
+        .flatten()
+        .filter_map(|argument| match argument {
+            Argument::Normal(argument) => Some(argument),
+            Argument::Ruled { .. } => None,
+        })
+        .map(|argument| match argument.as_str() {
+            "forgeclient" => "forgeserver".to_string(),
+            "neoforgeclient" => "neoforgeserver".to_string(),
+            "fmlclient" => "fmlserver".to_string(),
+            argument => argument
+                .replace("${library_directory}", SERVER_LIBRARIES_DIR)
+                .replace("${classpath_separator}", separator)
+                .replace("${version_name}", version_name),
+        })
+        .collect()
+}
+
+async fn download_server_libraries(
+    state: &State,
+    libraries: &[Library],
+    server_dir: &Path,
+) -> crate::Result<()> {
+    let libraries_dir = server_dir.join(SERVER_LIBRARIES_DIR);
+
+    stream::iter(libraries.iter().filter(|library| library.downloadable))
+        .map(Ok::<&Library, crate::Error>)
+        .try_for_each_concurrent(
+            Some(SERVER_LIBRARY_DOWNLOAD_CONCURRENCY),
+            |library| {
+                let libraries_dir = &libraries_dir;
+                async move {
+                    let artifact_path =
+                        d::get_path_from_artifact(&library.name)?;
+                    let path = libraries_dir.join(&artifact_path);
+                    if path.exists() {
+                        return Ok(());
+                    }
+
+                    if let Some(LibraryDownloads {
+                        artifact: Some(artifact),
+                        ..
+                    }) = &library.downloads
+                        && !artifact.url.is_empty()
+                    {
+                        let bytes = fetch(
+                            &artifact.url,
+                            Some(&artifact.sha1),
+                            None,
+                            None,
+                            &state.fetch_semaphore,
+                            &state.pool,
+                        )
+                        .await?;
+                        write(&path, &bytes, &state.io_semaphore).await?;
+                    } else {
+                        // Same fallback as `download_libraries`: the URL is
+                        // constructed, so a failed download isn't fatal
+                        let url = format!(
+                            "{}{artifact_path}",
+                            library
+                                .url
+                                .as_deref()
+                                .unwrap_or("https://libraries.minecraft.net/")
+                        );
+                        match fetch(
+                            &url,
+                            None,
+                            None,
+                            None,
+                            &state.fetch_semaphore,
+                            &state.pool,
+                        )
+                        .await
+                        {
+                            Ok(bytes) => {
+                                write(&path, &bytes, &state.io_semaphore)
+                                    .await?;
+                            }
+                            Err(err) => {
+                                tracing::debug!(
+                                    "Failed to download library {} from {url}: {err}",
+                                    library.name
+                                );
+                            }
+                        }
+                    }
+
+                    Ok(())
+                }
+            },
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library(name: &str) -> Library {
+        serde_json::from_value(serde_json::json!({ "name": name })).unwrap()
+    }
+
+    #[test]
+    fn leaves_client_only_libraries_off_server_classpath() {
+        assert!(!is_server_library(&library("org.lwjgl:lwjgl:3.3.1")));
+        assert!(!is_server_library(&library(
+            "com.mojang:text2speech:1.17.9"
+        )));
+        assert!(!is_server_library(&library("net.java.jinput:jinput:2.0.5")));
+        assert!(is_server_library(&library("com.mojang:brigadier:1.1.8")));
+        assert!(is_server_library(&library(
+            "net.minecraftforge:fmlloader:1.20.1-47.2.0"
+        )));
+        assert!(is_server_library(&library("io.netty:netty-all:4.1.82")));
+    }
+
+    #[test]
+    fn converts_client_arguments_to_server_arguments() {
+        let arguments = vec![
+            Argument::Normal("--launchTarget".to_string()),
+            Argument::Normal("forgeclient".to_string()),
+            Argument::Normal(
+                "-p ${library_directory}/a.jar${classpath_separator}b.jar"
+                    .to_string(),
+            ),
+        ];
+        assert_eq!(
+            server_arguments(Some(&arguments), ";", "1.20.1-forge"),
+            vec!["--launchTarget", "forgeserver", "-p libraries/a.jar;b.jar",]
+        );
+    }
+
+    #[test]
+    fn wraps_long_manifest_lines() {
+        let class_path = "libraries/".repeat(20);
+        let manifest = manifest("net.example.Main", &class_path);
+
+        assert!(manifest.starts_with("Manifest-Version: 1.0\r\n"));
+        assert!(manifest.ends_with("\r\n\r\n"));
+        for line in manifest.split("\r\n") {
+            assert!(line.len() <= 72);
+        }
+        let unwrapped = manifest.replace("\r\n ", "");
+        assert!(unwrapped.contains(&format!("Class-Path: {class_path}\r\n")));
+    }
+}
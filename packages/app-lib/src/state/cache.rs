@@ -599,6 +599,25 @@ pub struct VersionFile {
     pub primary: bool,
     pub size: u32,
     pub file_type: Option<FileType>,
+    #[serde(default)]
+    pub signature: Option<FileSignature>,
+}
+
+/// The result of verifying a file's detached OpenPGP signature against the
+/// keys registered by the project's members.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FileSignature {
+    pub status: FileSignatureStatus,
+    pub key_fingerprint: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FileSignatureStatus {
+    Verified,
+    Mismatched,
+    #[serde(other)]
+    Unknown,
 }
 
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
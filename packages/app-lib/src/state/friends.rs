@@ -1,13 +1,14 @@
 use crate::ErrorKind;
 use crate::data::ModrinthCredentials;
-use crate::event::FriendPayload;
 use crate::event::emit::{emit_friend, emit_notification};
+use crate::event::{FriendPayload, WorldInvitePayload};
 use crate::state::tunnel::InternalTunnelSocket;
 use crate::state::{ProcessManager, TunnelSocket};
 use crate::util::fetch::{FetchSemaphore, fetch_advanced, fetch_json};
+use crate::util::network::tcp_listen_any_loopback;
 use ariadne::ids::UserId;
 use ariadne::networking::message::{
-    ClientToServerMessage, ServerToClientMessage,
+    ClientToServerMessage, ServerToClientMessage, WorldInvite,
 };
 use ariadne::users::UserStatus;
 use async_tungstenite::WebSocketSender;
@@ -31,6 +32,7 @@ use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 use tokio::net::tcp::OwnedReadHalf;
 use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
 use uuid::Uuid;
 
 pub(super) type WriteSocket =
@@ -43,6 +45,19 @@ pub struct FriendsSocket {
     connection_generation: Arc<AtomicU64>,
     user_statuses: Arc<DashMap<UserId, UserStatus>>,
     tunnel_sockets: TunnelSockets,
+    /// Invites received from friends, by the socket they invite to
+    world_invites: Arc<DashMap<Uuid, ReceivedWorldInvite>>,
+    /// Listening sockets for worlds opened to LAN, by their local port
+    hosted_worlds: DashMap<u16, TunnelSocket>,
+    /// Local listeners relaying connections to friends' worlds, by the
+    /// socket they connect to
+    joined_worlds: DashMap<Uuid, JoinHandle<()>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ReceivedWorldInvite {
+    pub from: UserId,
+    pub invite: WorldInvite,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -67,6 +82,9 @@ impl FriendsSocket {
             connection_generation: Arc::new(AtomicU64::new(0)),
             user_statuses: Arc::new(DashMap::new()),
             tunnel_sockets: Arc::new(DashMap::new()),
+            world_invites: Arc::new(DashMap::new()),
+            hosted_worlds: DashMap::new(),
+            joined_worlds: DashMap::new(),
         }
     }
 
@@ -125,6 +143,7 @@ impl FriendsSocket {
                         self.connection_generation.clone();
                     let statuses = self.user_statuses.clone();
                     let sockets = self.tunnel_sockets.clone();
+                    let world_invites = self.world_invites.clone();
 
                     tokio::spawn(async move {
                         let mut read_stream = read;
@@ -211,7 +230,11 @@ impl FriendsSocket {
                                             ServerToClientMessage::FriendRequestRejected { .. } => {}, // TODO
 
                                             ServerToClientMessage::FriendSocketListening { .. } => {}, // TODO
-                                            ServerToClientMessage::FriendSocketStoppedListening { .. } => {}, // TODO
+                                            ServerToClientMessage::FriendSocketStoppedListening { user, socket } => {
+                                                if world_invites.remove_if(&socket, |_, x| x.from == user).is_some() {
+                                                    let _ = emit_friend(FriendPayload::WorldInviteExpired { socket: socket.to_string() }).await;
+                                                }
+                                            },
 
                                             ServerToClientMessage::SocketConnected { to_socket, new_socket } => {
                                                 if let Some(connected_to) = sockets.get(&to_socket)
@@ -219,7 +242,7 @@ impl FriendsSocket {
                                                         && let Ok(new_stream) = TcpStream::connect(local_addr).await {
                                                             let (read, write) = new_stream.into_split();
                                                             sockets.insert(new_socket, Arc::new(InternalTunnelSocket::Connected(Mutex::new(write))));
-                                                            Self::socket_read_loop(write_handle.clone(), read, new_socket);
+                                                            Self::socket_read_loop(write_handle.clone(), sockets.clone(), read, new_socket);
                                                             continue;
                                                         }
                                                 let _ = Self::send_message(&write_handle, ClientToServerMessage::SocketClose { socket: new_socket }).await;
@@ -233,6 +256,12 @@ impl FriendsSocket {
                                                         let _ = stream.lock().await.write_all(&data).await;
                                                     }
                                             },
+
+                                            ServerToClientMessage::WorldInvite { from, invite } => {
+                                                let payload = WorldInvitePayload::new(from, &invite);
+                                                world_invites.insert(invite.socket, ReceivedWorldInvite { from, invite });
+                                                let _ = emit_friend(FriendPayload::WorldInvite { invite: payload }).await;
+                                            },
                                         }
                                     }
                                 }
@@ -343,6 +372,12 @@ impl FriendsSocket {
 
         self.user_statuses.clear();
         self.tunnel_sockets.clear();
+        self.world_invites.clear();
+        self.hosted_worlds.clear();
+        self.joined_worlds.retain(|_, task| {
+            task.abort();
+            false
+        });
 
         if let Some(mut write_half) = write_half {
             SinkExt::close(&mut write_half).await?;
@@ -464,6 +499,115 @@ impl FriendsSocket {
         self.create_tunnel_socket(socket_id, socket)
     }
 
+    /// Invites a friend to a world opened to LAN on `port`, listening for
+    /// their connections if this is the first invite to it.
+    #[tracing::instrument(skip(self, invite))]
+    pub async fn invite_to_world(
+        &self,
+        friend: UserId,
+        port: u16,
+        invite: impl FnOnce(Uuid) -> WorldInvite,
+    ) -> crate::Result<()> {
+        let hosted_socket =
+            self.hosted_worlds.get(&port).map(|x| x.socket_id());
+        let socket = match hosted_socket {
+            Some(socket) => socket,
+            None => {
+                let socket = self.open_port(port).await?;
+                let socket_id = socket.socket_id();
+                self.hosted_worlds.insert(port, socket);
+                socket_id
+            }
+        };
+
+        Self::send_message(
+            &self.write,
+            ClientToServerMessage::WorldInvite {
+                to: friend,
+                invite: invite(socket),
+            },
+        )
+        .await
+    }
+
+    /// Stops accepting connections to a world opened to LAN on `port`.
+    /// Friends who already joined stay connected.
+    #[tracing::instrument(skip(self))]
+    pub async fn stop_hosting_world(&self, port: u16) -> crate::Result<()> {
+        if let Some((_, socket)) = self.hosted_worlds.remove(&port) {
+            socket.shutdown().await?;
+        }
+        Ok(())
+    }
+
+    pub fn world_invites(&self) -> Vec<ReceivedWorldInvite> {
+        self.world_invites
+            .iter()
+            .map(|x| x.value().clone())
+            .collect()
+    }
+
+    pub fn world_invite(&self, socket: Uuid) -> Option<ReceivedWorldInvite> {
+        self.world_invites.get(&socket).map(|x| x.value().clone())
+    }
+
+    pub fn decline_world_invite(&self, socket: Uuid) {
+        self.world_invites.remove(&socket);
+    }
+
+    /// Listens on a local port, relaying each connection to it through the
+    /// friend's listening socket from an invite. Returns the local address
+    /// to connect the game to.
+    #[tracing::instrument(skip(self))]
+    pub async fn join_world(&self, socket: Uuid) -> crate::Result<SocketAddr> {
+        let invite = self.world_invite(socket).ok_or_else(|| {
+            ErrorKind::OtherError("This world invite has expired".to_string())
+        })?;
+
+        let listener = tcp_listen_any_loopback().await?;
+        let local_addr = listener.local_addr()?;
+
+        let write = self.write.clone();
+        let sockets = self.tunnel_sockets.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let new_socket = Uuid::new_v4();
+                let (read, write_half) = stream.into_split();
+                sockets.insert(
+                    new_socket,
+                    Arc::new(InternalTunnelSocket::Connected(Mutex::new(
+                        write_half,
+                    ))),
+                );
+                if Self::send_message(
+                    &write,
+                    ClientToServerMessage::SocketConnect {
+                        user: invite.from,
+                        to_socket: socket,
+                        socket: new_socket,
+                    },
+                )
+                .await
+                .is_err()
+                {
+                    sockets.remove(&new_socket);
+                    continue;
+                }
+                Self::socket_read_loop(
+                    write.clone(),
+                    sockets.clone(),
+                    read,
+                    new_socket,
+                );
+            }
+        });
+        if let Some(old_task) = self.joined_worlds.insert(socket, task) {
+            old_task.abort();
+        }
+
+        Ok(local_addr)
+    }
+
     pub async fn is_connected(&self) -> bool {
         self.write.read().await.is_some()
     }
@@ -483,6 +627,7 @@ impl FriendsSocket {
 
     fn socket_read_loop(
         write: WriteSocket,
+        sockets: TunnelSockets,
         mut read_half: OwnedReadHalf,
         socket_id: Uuid,
     ) {
@@ -503,6 +648,14 @@ impl FriendsSocket {
                     }
                 };
             }
+
+            if sockets.remove(&socket_id).is_some() {
+                let _ = Self::send_message(
+                    &write,
+                    ClientToServerMessage::SocketClose { socket: socket_id },
+                )
+                .await;
+            }
         });
     }
 
@@ -524,3 +677,80 @@ impl FriendsSocket {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn received_invite(socket: Uuid) -> ReceivedWorldInvite {
+        ReceivedWorldInvite {
+            from: UserId(1),
+            invite: WorldInvite {
+                socket,
+                world_name: "New World".to_string(),
+                game_version: "1.21.8".to_string(),
+                loader: "fabric".to_string(),
+                loader_version: None,
+                content_hash: String::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn world_invites_can_be_declined() {
+        let friends_socket = FriendsSocket::new();
+        let socket = Uuid::new_v4();
+        friends_socket
+            .world_invites
+            .insert(socket, received_invite(socket));
+
+        assert_eq!(
+            friends_socket.world_invite(socket).map(|x| x.from),
+            Some(UserId(1))
+        );
+        assert_eq!(friends_socket.world_invites().len(), 1);
+
+        friends_socket.decline_world_invite(socket);
+        assert!(friends_socket.world_invite(socket).is_none());
+        assert!(friends_socket.world_invites().is_empty());
+    }
+
+    #[tokio::test]
+    async fn joining_worlds_requires_an_invite() {
+        let friends_socket = FriendsSocket::new();
+        assert!(friends_socket.join_world(Uuid::new_v4()).await.is_err());
+        assert!(friends_socket.joined_worlds.is_empty());
+    }
+
+    #[tokio::test]
+    async fn joined_worlds_relay_local_connections() {
+        let friends_socket = FriendsSocket::new();
+        let socket = Uuid::new_v4();
+        friends_socket
+            .world_invites
+            .insert(socket, received_invite(socket));
+
+        let address = friends_socket.join_world(socket).await.unwrap();
+        assert!(address.ip().is_loopback());
+        assert!(friends_socket.joined_worlds.contains_key(&socket));
+
+        // Each connection from the game gets its own tunnel socket
+        let _game = TcpStream::connect(address).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while friends_socket.tunnel_sockets.is_empty() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert!(friends_socket.tunnel_sockets.iter().all(|x| matches!(
+            **x.value(),
+            InternalTunnelSocket::Connected(_)
+        )));
+
+        friends_socket.disconnect().await.unwrap();
+        assert!(friends_socket.joined_worlds.is_empty());
+        assert!(friends_socket.tunnel_sockets.is_empty());
+    }
+}
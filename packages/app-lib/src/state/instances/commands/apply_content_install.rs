@@ -4,7 +4,8 @@ use crate::state::instances::{
 };
 use crate::state::{
     CacheBehaviour, CachedEntry, Dependency, DependencyType, KnownModrinthFile,
-    ModLoader, ProjectType, State, Version, cache_file_hash,
+    ModLoader, ProjectType, State, Version, VersionFile, cache_file_hash,
+    project_signing_keys,
 };
 use crate::util::fetch::{self, DownloadMeta, DownloadReason};
 use crate::util::io;
@@ -15,6 +16,9 @@ use modrinth_content_management::{
     ResolutionPreferences, ResolveContentPlan, ResolveContentRequest,
     ResolvedContent,
 };
+use reqwest::Method;
+use serde::Deserialize;
+use sha2::Digest;
 use std::path::{Path, PathBuf};
 
 pub(crate) struct ContentScope {
@@ -354,6 +358,7 @@ pub(crate) async fn add_project_from_version(
         version_id,
         reason,
         dependent_on_version_id,
+        None,
         state,
     )
     .await?;
@@ -362,11 +367,17 @@ pub(crate) async fn add_project_from_version(
         .await
 }
 
+/// Downloads the primary file of a version.
+///
+/// When `replacing_path` is the path of an installed file the version is
+/// replacing, a binary delta from that file is tried first, falling back to
+/// the full file.
 pub(crate) async fn download_project_version(
     instance_id: &str,
     version_id: &str,
     reason: DownloadReason,
     dependent_on_version_id: Option<String>,
+    replacing_path: Option<&str>,
     state: &State,
 ) -> crate::Result<DownloadedProjectVersion> {
     let scope = resolve_content_scope(instance_id, None, state).await?;
@@ -401,21 +412,41 @@ pub(crate) async fn download_project_version(
                 "No files for input version present!".to_string(),
             )
         })?;
+    project_signing_keys::check_file_signature(
+        &version.project_id,
+        file,
+        &state.pool,
+    )
+    .await?;
     let download_meta = DownloadMeta {
         reason,
         game_version: content_set.game_version,
         loader: content_set.loader.as_str().to_string(),
         dependent_on: dependent_on_version_id,
     };
-    let bytes = fetch::fetch(
-        &file.url,
-        file.hashes.get("sha1").map(|hash| hash.as_str()),
-        Some(&download_meta),
-        None,
-        &state.fetch_semaphore,
-        &state.pool,
-    )
-    .await?;
+    let delta_bytes = match replacing_path {
+        Some(replacing_path) => {
+            let replacing_path =
+                instance_full_path(state, &scope.instance).join(replacing_path);
+            fetch_delta_update(&replacing_path, file, &download_meta, state)
+                .await
+        }
+        None => None,
+    };
+    let bytes = match delta_bytes {
+        Some(bytes) => bytes,
+        None => {
+            fetch::fetch(
+                &file.url,
+                file.hashes.get("sha1").map(|hash| hash.as_str()),
+                Some(&download_meta),
+                None,
+                &state.fetch_semaphore,
+                &state.pool,
+            )
+            .await?
+        }
+    };
     let project_type = ProjectType::get_from_loaders(version.loaders.clone())
         .ok_or_else(|| {
         crate::ErrorKind::InputError(format!(
@@ -435,6 +466,84 @@ pub(crate) async fn download_project_version(
     })
 }
 
+#[derive(Deserialize)]
+struct VersionFileDelta {
+    url: String,
+}
+
+/// Builds a version file from the installed file at `old_path` and a binary
+/// delta, if the API has one between them.
+///
+/// Returns `None` when no delta is available or anything goes wrong,
+/// including the result not matching the file's sha512, in which case the
+/// full file should be downloaded instead.
+async fn fetch_delta_update(
+    old_path: &Path,
+    file: &VersionFile,
+    download_meta: &DownloadMeta,
+    state: &State,
+) -> Option<Bytes> {
+    let new_sha1 = file.hashes.get("sha1")?;
+    let new_sha512 = file.hashes.get("sha512")?.clone();
+
+    let result = async {
+        let old = Bytes::from(io::read(old_path).await?);
+        let old_sha1 = fetch::sha1_async(old.clone()).await?;
+
+        let delta = fetch::fetch_json::<VersionFileDelta>(
+            Method::GET,
+            &format!(
+                "{}version_file/{old_sha1}/delta/{new_sha1}?algorithm=sha1",
+                env!("MODRINTH_API_URL_V3")
+            ),
+            None,
+            None,
+            Some("/v3/version_file/:sha1/delta/:sha1"),
+            &state.fetch_semaphore,
+            &state.pool,
+        )
+        .await?;
+        let delta = fetch::fetch(
+            &delta.url,
+            None,
+            Some(download_meta),
+            None,
+            &state.fetch_semaphore,
+            &state.pool,
+        )
+        .await?;
+
+        let bytes = tokio::task::spawn_blocking(move || {
+            let bytes = ariadne::delta::apply(&old, &delta).ok()?;
+            let sha512 = format!("{:x}", sha2::Sha512::digest(&bytes));
+
+            (sha512 == new_sha512).then(|| Bytes::from(bytes))
+        })
+        .await?;
+
+        Ok::<_, crate::Error>(bytes)
+    }
+    .await;
+
+    match result {
+        Ok(Some(bytes)) => Some(bytes),
+        Ok(None) => {
+            tracing::debug!(
+                "Delta for {} did not produce the expected file",
+                file.filename
+            );
+            None
+        }
+        Err(err) => {
+            tracing::debug!(
+                "No delta update available for {}: {err}",
+                file.filename
+            );
+            None
+        }
+    }
+}
+
 pub(crate) async fn add_downloaded_project_version(
     instance_id: &str,
     downloaded: DownloadedProjectVersion,
@@ -12,8 +12,8 @@ use std::collections::{HashMap, HashSet};
 
 use super::apply_content_install::{
     DownloadedProjectVersion, add_downloaded_project_version,
-    add_project_from_version, download_project_version, remove_project,
-    rename_project_companion_file, toggle_disable_project,
+    download_project_version, remove_project, rename_project_companion_file,
+    toggle_disable_project,
 };
 use super::check_content_updates::{ContentUpdate, check_content_updates};
 
@@ -92,11 +92,18 @@ async fn apply_content_update(
     update: &ContentUpdate,
     state: &State,
 ) -> crate::Result<String> {
-    let mut new_path = add_project_from_version(
+    let downloaded = download_project_version(
         instance_id,
         &update.update_version_id,
         DownloadReason::Update,
         Some(update.current_version_id.clone()),
+        Some(project_path),
+        state,
+    )
+    .await?;
+    let mut new_path = add_downloaded_project_version(
+        instance_id,
+        downloaded,
         ContentSourceKind::Local,
         state,
     )
@@ -231,6 +238,7 @@ async fn download_planned_projects(
                         &update.update_version_id,
                         DownloadReason::Update,
                         Some(update.current_version_id.clone()),
+                        Some(&update.relative_path),
                         state,
                     )
                     .await?;
@@ -245,6 +253,7 @@ async fn download_planned_projects(
                         &dependency.version_id,
                         DownloadReason::Dependency,
                         Some(dependency.parent_version_id.clone()),
+                        None,
                         state,
                     )
                     .await?;
@@ -909,6 +909,7 @@ impl From<LegacyModrinthVersion> for Version {
                         }
                         LegacyFileType::Unknown => FileType::Unknown,
                     }),
+                    signature: None,
                 })
                 .collect::<Vec<_>>(),
             dependencies: value
@@ -56,6 +56,7 @@ pub use self::mr_auth::*;
 mod legacy_converter;
 
 pub mod attached_world_data;
+pub mod project_signing_keys;
 pub mod server_join_log;
 
 // Global state
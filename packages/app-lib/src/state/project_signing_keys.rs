@@ -0,0 +1,107 @@
+//! Projects the user has pinned to an OpenPGP signing key. Files of pinned
+//! projects are only installed if Modrinth verified their signature against
+//! the pinned key.
+
+use sqlx::{Executor, Sqlite};
+
+use crate::state::{FileSignatureStatus, VersionFile};
+
+pub async fn get_pinned_signing_key<'e, E>(
+    project_id: &str,
+    exec: E,
+) -> crate::Result<Option<String>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let fingerprint = sqlx::query_scalar::<_, String>(
+        "
+		SELECT fingerprint
+		FROM project_signing_key_pins
+		WHERE project_id = ?
+		",
+    )
+    .bind(project_id)
+    .fetch_optional(exec)
+    .await?;
+
+    Ok(fingerprint)
+}
+
+pub async fn set_pinned_signing_key<'e, E>(
+    project_id: &str,
+    fingerprint: Option<&str>,
+    exec: E,
+) -> crate::Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    if let Some(fingerprint) = fingerprint {
+        sqlx::query(
+            "
+			INSERT INTO project_signing_key_pins (project_id, fingerprint)
+			VALUES (?, ?)
+			ON CONFLICT (project_id) DO UPDATE SET
+				fingerprint = excluded.fingerprint
+			",
+        )
+        .bind(project_id)
+        .bind(fingerprint.to_ascii_uppercase())
+        .execute(exec)
+        .await?;
+    } else {
+        sqlx::query(
+            "
+			DELETE FROM project_signing_key_pins
+			WHERE project_id = ?
+			",
+        )
+        .bind(project_id)
+        .execute(exec)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Checks that `file` carries a signature verified against the key
+/// `project_id` is pinned to, if it's pinned to one.
+pub async fn check_file_signature<'e, E>(
+    project_id: &str,
+    file: &VersionFile,
+    exec: E,
+) -> crate::Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let Some(pinned) = get_pinned_signing_key(project_id, exec).await? else {
+        return Ok(());
+    };
+
+    let Some(signature) = &file.signature else {
+        return Err(crate::ErrorKind::SignatureError(format!(
+            "{} is not signed, but project {project_id} is pinned to key {pinned}",
+            file.filename
+        ))
+        .into());
+    };
+
+    match (signature.status, &signature.key_fingerprint) {
+        (FileSignatureStatus::Verified, Some(fingerprint))
+            if *fingerprint == pinned =>
+        {
+            Ok(())
+        }
+        (FileSignatureStatus::Verified, Some(fingerprint)) => {
+            Err(crate::ErrorKind::SignatureError(format!(
+                "{} is signed by key {fingerprint}, but project {project_id} is pinned to key {pinned}",
+                file.filename
+            ))
+            .into())
+        }
+        _ => Err(crate::ErrorKind::SignatureError(format!(
+            "{} has a signature which doesn't match any of its authors' keys",
+            file.filename
+        ))
+        .into()),
+    }
+}
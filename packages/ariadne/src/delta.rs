@@ -0,0 +1,506 @@
+//! Binary deltas between two versions of a file.
+//!
+//! Deltas are zip-aware: when both files are zip archives, every entry of the
+//! new archive whose compressed data also appears in the old archive is
+//! encoded as a copy from the old file, and everything else (changed entries,
+//! local headers with new timestamps, the central directory) is stored
+//! inline. Files which are not zip archives are stored inline in full, so
+//! callers should compare the delta size against the target size before
+//! keeping it.
+//!
+//! The format is a magic header, the length of the target file, and a list of
+//! operations:
+//!
+//! - `0`: copy `u64` bytes from the old file starting at offset `u64`
+//! - `1`: insert the following `u64` bytes
+//! - `2`: end of the delta
+//!
+//! All integers are little-endian.
+
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Magic bytes at the start of every delta.
+pub const MAGIC: &[u8; 8] = b"MRDELTA1";
+
+const OP_COPY: u8 = 0;
+const OP_INSERT: u8 = 1;
+const OP_END: u8 = 2;
+
+const EOCD_SIGNATURE: u32 = 0x0605_4b50;
+const EOCD_LEN: usize = 22;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const CENTRAL_HEADER_LEN: usize = 46;
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const LOCAL_HEADER_LEN: usize = 30;
+const MAX_ZIP_COMMENT_LEN: usize = u16::MAX as usize;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum DeltaError {
+    #[error("delta does not start with the expected magic bytes")]
+    InvalidMagic,
+    #[error("delta ended unexpectedly")]
+    Truncated,
+    #[error("unknown delta operation {0}")]
+    UnknownOperation(u8),
+    #[error("delta copies bytes outside of the source file")]
+    CopyOutOfBounds,
+    #[error("delta produced {actual} bytes, expected {expected}")]
+    LengthMismatch { expected: u64, actual: u64 },
+}
+
+/// Computes a delta which turns `old` into `new`.
+pub fn diff(old: &[u8], new: &[u8]) -> Vec<u8> {
+    let mut encoder = Encoder::new(new.len());
+
+    let (Some(old_entries), Some(new_entries)) =
+        (zip_entries(old), zip_entries(new))
+    else {
+        encoder.insert(new);
+        return encoder.finish();
+    };
+
+    let old_by_key = old_entries
+        .iter()
+        .map(|entry| (&entry.key, entry))
+        .collect::<HashMap<_, _>>();
+
+    let mut cursor = 0;
+    for entry in &new_entries {
+        if entry.start > cursor {
+            encoder.insert(&new[cursor..entry.start]);
+        }
+
+        let new_bytes = &new[entry.start..entry.end];
+        let new_data = &new[entry.data_start..entry.end];
+        match old_by_key.get(&entry.key) {
+            Some(old_entry)
+                if old[old_entry.start..old_entry.end] == *new_bytes =>
+            {
+                encoder.copy(old_entry.start, new_bytes.len());
+            }
+            Some(old_entry)
+                if old[old_entry.data_start..old_entry.end] == *new_data =>
+            {
+                encoder.insert(&new[entry.start..entry.data_start]);
+                encoder.copy(old_entry.data_start, new_data.len());
+            }
+            _ => encoder.insert(new_bytes),
+        }
+
+        cursor = entry.end;
+    }
+    encoder.insert(&new[cursor..]);
+
+    encoder.finish()
+}
+
+/// Applies a delta produced by [`diff`] to `old`, returning the new file.
+pub fn apply(old: &[u8], delta: &[u8]) -> Result<Vec<u8>, DeltaError> {
+    let mut reader = Reader { data: delta };
+
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(DeltaError::InvalidMagic);
+    }
+
+    let expected = reader.u64()?;
+    // The expected length is untrusted, so only use it as a capacity hint when
+    // it is plausible.
+    let mut output = Vec::with_capacity(
+        usize::try_from(expected)
+            .unwrap_or(0)
+            .min(old.len().saturating_add(delta.len())),
+    );
+
+    // Rejects an operation before it runs if it would grow the output past the
+    // expected length, so a malicious delta can't make us allocate without
+    // bound.
+    let check_len = |output: &Vec<u8>, len: usize| {
+        let actual = (output.len() as u64).saturating_add(len as u64);
+        if actual > expected {
+            return Err(DeltaError::LengthMismatch { expected, actual });
+        }
+        Ok(())
+    };
+
+    loop {
+        match reader.u8()? {
+            OP_COPY => {
+                let offset = reader.len()?;
+                let len = reader.len()?;
+                check_len(&output, len)?;
+                let bytes = offset
+                    .checked_add(len)
+                    .and_then(|end| old.get(offset..end))
+                    .ok_or(DeltaError::CopyOutOfBounds)?;
+                output.extend_from_slice(bytes);
+            }
+            OP_INSERT => {
+                let len = reader.len()?;
+                check_len(&output, len)?;
+                output.extend_from_slice(reader.take(len)?);
+            }
+            OP_END => break,
+            op => return Err(DeltaError::UnknownOperation(op)),
+        }
+    }
+
+    if output.len() as u64 != expected {
+        return Err(DeltaError::LengthMismatch {
+            expected,
+            actual: output.len() as u64,
+        });
+    }
+
+    Ok(output)
+}
+
+/// Writes operations, merging adjacent inserts and contiguous copies.
+struct Encoder {
+    output: Vec<u8>,
+    pending_copy: Option<(usize, usize)>,
+    pending_insert: Vec<u8>,
+}
+
+impl Encoder {
+    fn new(target_len: usize) -> Self {
+        let mut output = Vec::new();
+        output.extend_from_slice(MAGIC);
+        output.extend_from_slice(&(target_len as u64).to_le_bytes());
+
+        Self {
+            output,
+            pending_copy: None,
+            pending_insert: Vec::new(),
+        }
+    }
+
+    fn copy(&mut self, offset: usize, len: usize) {
+        self.flush_insert();
+
+        match &mut self.pending_copy {
+            Some((start, pending_len)) if *start + *pending_len == offset => {
+                *pending_len += len;
+            }
+            _ => {
+                self.flush_copy();
+                self.pending_copy = Some((offset, len));
+            }
+        }
+    }
+
+    fn insert(&mut self, bytes: &[u8]) {
+        if bytes.is_empty() {
+            return;
+        }
+
+        self.flush_copy();
+        self.pending_insert.extend_from_slice(bytes);
+    }
+
+    fn flush_copy(&mut self) {
+        if let Some((offset, len)) = self.pending_copy.take() {
+            self.output.push(OP_COPY);
+            self.output
+                .extend_from_slice(&(offset as u64).to_le_bytes());
+            self.output.extend_from_slice(&(len as u64).to_le_bytes());
+        }
+    }
+
+    fn flush_insert(&mut self) {
+        if self.pending_insert.is_empty() {
+            return;
+        }
+
+        self.output.push(OP_INSERT);
+        self.output.extend_from_slice(
+            &(self.pending_insert.len() as u64).to_le_bytes(),
+        );
+        self.output.append(&mut self.pending_insert);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.flush_copy();
+        self.flush_insert();
+        self.output.push(OP_END);
+        self.output
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DeltaError> {
+        if self.data.len() < len {
+            return Err(DeltaError::Truncated);
+        }
+
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, DeltaError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u64(&mut self) -> Result<u64, DeltaError> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().expect("8 bytes")))
+    }
+
+    fn len(&mut self) -> Result<usize, DeltaError> {
+        usize::try_from(self.u64()?).map_err(|_| DeltaError::Truncated)
+    }
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct EntryKey {
+    name: Vec<u8>,
+    crc32: u32,
+    compressed_size: u32,
+    uncompressed_size: u32,
+}
+
+/// The raw byte range of a zip entry, from its local header up to the next
+/// entry or the central directory.
+struct ZipEntry {
+    key: EntryKey,
+    start: usize,
+    /// Where the local header ends and the entry's data begins.
+    data_start: usize,
+    end: usize,
+}
+
+/// Reads the entries of a zip archive from its central directory, sorted by
+/// their position in the file. Returns `None` for anything which isn't a
+/// plain (non-zip64) archive.
+fn zip_entries(data: &[u8]) -> Option<Vec<ZipEntry>> {
+    let search_start = data
+        .len()
+        .checked_sub(EOCD_LEN)?
+        .saturating_sub(MAX_ZIP_COMMENT_LEN);
+    let eocd = (search_start..=data.len() - EOCD_LEN)
+        .rev()
+        .find(|&offset| read_u32(data, offset) == Some(EOCD_SIGNATURE))?;
+
+    let entry_count = read_u16(data, eocd + 10)? as usize;
+    let directory_size = read_u32(data, eocd + 12)? as usize;
+    let directory_start = read_u32(data, eocd + 16)? as usize;
+    let directory_end = directory_start.checked_add(directory_size)?;
+    if directory_end > eocd {
+        return None;
+    }
+
+    let mut entries = Vec::with_capacity(entry_count);
+    let mut offset = directory_start;
+    for _ in 0..entry_count {
+        if read_u32(data, offset)? != CENTRAL_HEADER_SIGNATURE {
+            return None;
+        }
+
+        let crc32 = read_u32(data, offset + 16)?;
+        let compressed_size = read_u32(data, offset + 20)?;
+        let uncompressed_size = read_u32(data, offset + 24)?;
+        let name_len = read_u16(data, offset + 28)? as usize;
+        let extra_len = read_u16(data, offset + 30)? as usize;
+        let comment_len = read_u16(data, offset + 32)? as usize;
+        let local_offset = read_u32(data, offset + 42)?;
+        if local_offset == u32::MAX {
+            return None;
+        }
+
+        let name_start = offset + CENTRAL_HEADER_LEN;
+        let name = data.get(name_start..name_start + name_len)?.to_vec();
+
+        entries.push(ZipEntry {
+            key: EntryKey {
+                name,
+                crc32,
+                compressed_size,
+                uncompressed_size,
+            },
+            start: local_offset as usize,
+            data_start: 0,
+            end: 0,
+        });
+
+        offset = name_start + name_len + extra_len + comment_len;
+    }
+
+    entries.sort_by_key(|entry| entry.start);
+    let mut next_start = directory_start;
+    for entry in entries.iter_mut().rev() {
+        if entry.start > next_start {
+            return None;
+        }
+
+        if read_u32(data, entry.start)? != LOCAL_HEADER_SIGNATURE {
+            return None;
+        }
+
+        let name_len = read_u16(data, entry.start + 26)? as usize;
+        let extra_len = read_u16(data, entry.start + 28)? as usize;
+        entry.data_start =
+            entry.start + LOCAL_HEADER_LEN + name_len + extra_len;
+        if entry.data_start > next_start {
+            return None;
+        }
+
+        entry.end = next_start;
+        next_start = entry.start;
+    }
+
+    Some(entries)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a zip archive of stored (uncompressed) entries. The CRCs are
+    /// fake, which is fine since deltas only use them to match entries.
+    fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut directory = Vec::new();
+
+        for (index, (name, contents)) in entries.iter().enumerate() {
+            let crc32 = index as u32 ^ contents.len() as u32;
+            let size = contents.len() as u32;
+            let local_offset = data.len() as u32;
+
+            data.extend_from_slice(&LOCAL_HEADER_SIGNATURE.to_le_bytes());
+            data.extend_from_slice(&[0; 10]);
+            data.extend_from_slice(&crc32.to_le_bytes());
+            data.extend_from_slice(&size.to_le_bytes());
+            data.extend_from_slice(&size.to_le_bytes());
+            data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            data.extend_from_slice(&0u16.to_le_bytes());
+            data.extend_from_slice(name.as_bytes());
+            data.extend_from_slice(contents);
+
+            directory
+                .extend_from_slice(&CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+            directory.extend_from_slice(&[0; 12]);
+            directory.extend_from_slice(&crc32.to_le_bytes());
+            directory.extend_from_slice(&size.to_le_bytes());
+            directory.extend_from_slice(&size.to_le_bytes());
+            directory.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            directory.extend_from_slice(&[0; 12]);
+            directory.extend_from_slice(&local_offset.to_le_bytes());
+            directory.extend_from_slice(name.as_bytes());
+        }
+
+        let directory_start = data.len() as u32;
+        data.extend_from_slice(&directory);
+        data.extend_from_slice(&EOCD_SIGNATURE.to_le_bytes());
+        data.extend_from_slice(&[0; 6]);
+        data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        data.extend_from_slice(&directory_start.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn round_trip_zip() {
+        let unchanged = vec![7; 4096];
+        let old = zip(&[("a.class", &unchanged), ("b.class", b"old contents")]);
+        let new = zip(&[
+            ("a.class", &unchanged),
+            ("b.class", b"new contents!"),
+            ("c.class", b"added"),
+        ]);
+
+        let delta = diff(&old, &new);
+        assert!(delta.len() < new.len() / 2);
+        assert_eq!(apply(&old, &delta), Ok(new));
+    }
+
+    #[test]
+    fn round_trip_plain_file() {
+        let old = b"not a zip".to_vec();
+        let new = b"also not a zip".to_vec();
+
+        assert_eq!(apply(&old, &diff(&old, &new)), Ok(new));
+    }
+
+    #[test]
+    fn rejects_truncated_delta() {
+        let old = zip(&[("a.class", &[1; 256])]);
+        let new = zip(&[("a.class", &[1; 256]), ("b.class", b"added")]);
+        let delta = diff(&old, &new);
+
+        for len in [0, 4, MAGIC.len() + 4, delta.len() - 1] {
+            let result = apply(&old, &delta[..len]);
+            assert!(
+                matches!(
+                    result,
+                    Err(DeltaError::Truncated | DeltaError::InvalidMagic)
+                ),
+                "truncated to {len} bytes: {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_oversized_operations() {
+        let old = vec![0; 16];
+
+        let mut encoder = Encoder::new(4);
+        encoder.insert(&[1; 8]);
+        assert_eq!(
+            apply(&old, &encoder.finish()),
+            Err(DeltaError::LengthMismatch {
+                expected: 4,
+                actual: 8
+            })
+        );
+
+        let mut encoder = Encoder::new(4);
+        encoder.copy(0, 16);
+        assert_eq!(
+            apply(&old, &encoder.finish()),
+            Err(DeltaError::LengthMismatch {
+                expected: 4,
+                actual: 16
+            })
+        );
+
+        // An insert claiming a huge length is rejected before it is read
+        let mut delta = MAGIC.to_vec();
+        delta.extend_from_slice(&4u64.to_le_bytes());
+        delta.push(OP_INSERT);
+        delta.extend_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            apply(&old, &delta),
+            Err(DeltaError::LengthMismatch { expected: 4, .. }
+                | DeltaError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn rejects_short_output() {
+        let mut encoder = Encoder::new(8);
+        encoder.insert(&[1; 4]);
+        assert_eq!(
+            apply(&[], &encoder.finish()),
+            Err(DeltaError::LengthMismatch {
+                expected: 8,
+                actual: 4
+            })
+        );
+    }
+}
@@ -1,3 +1,4 @@
+pub mod delta;
 pub mod ids;
 pub mod networking;
 pub mod users;
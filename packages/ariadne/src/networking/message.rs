@@ -13,6 +13,13 @@ pub enum ClientToServerMessage {
     SocketListen {
         socket: Uuid,
     },
+    /// Opens `socket` as a connection to `to_socket`, a listening socket of
+    /// the friend `user`.
+    SocketConnect {
+        user: UserId,
+        to_socket: Uuid,
+        socket: Uuid,
+    },
     SocketClose {
         socket: Uuid,
     },
@@ -21,6 +28,12 @@ pub enum ClientToServerMessage {
         #[serde(with = "serde_bytes")]
         data: Vec<u8>,
     },
+
+    /// Invites a friend to a world shared through a listening socket.
+    WorldInvite {
+        to: UserId,
+        invite: WorldInvite,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -48,6 +61,7 @@ pub enum ServerToClientMessage {
     },
     FriendSocketStoppedListening {
         user: UserId,
+        socket: Uuid,
     },
 
     SocketConnected {
@@ -62,4 +76,132 @@ pub enum ServerToClientMessage {
         #[serde(with = "serde_bytes")]
         data: Vec<u8>,
     },
+
+    WorldInvite {
+        from: UserId,
+        invite: WorldInvite,
+    },
+}
+
+/// A world opened to LAN by a friend, along with what's needed to join it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldInvite {
+    /// The listening socket to connect to.
+    pub socket: Uuid,
+    pub world_name: String,
+    pub game_version: String,
+    pub loader: String,
+    pub loader_version: Option<String>,
+    /// Hash of the enabled mods in the host's instance.
+    pub content_hash: String,
+}
+
+impl WorldInvite {
+    pub const MAX_FIELD_LENGTH: usize = 128;
+
+    /// Whether every field fits within [`Self::MAX_FIELD_LENGTH`].
+    pub fn is_valid(&self) -> bool {
+        [
+            &self.world_name,
+            &self.game_version,
+            &self.loader,
+            &self.content_hash,
+        ]
+        .into_iter()
+        .chain(&self.loader_version)
+        .all(|x| x.len() <= Self::MAX_FIELD_LENGTH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use either::Either;
+
+    fn invite() -> WorldInvite {
+        WorldInvite {
+            socket: Uuid::new_v4(),
+            world_name: "New World".to_string(),
+            game_version: "1.21.8".to_string(),
+            loader: "fabric".to_string(),
+            loader_version: Some("0.17.2".to_string()),
+            content_hash: "da39a3ee5e6b4b0d3255bfef95601890afd80709"
+                .to_string(),
+        }
+    }
+
+    #[test]
+    fn world_invite_fields_are_bounded() {
+        assert!(invite().is_valid());
+
+        let long = "a".repeat(WorldInvite::MAX_FIELD_LENGTH + 1);
+        assert!(
+            !WorldInvite {
+                world_name: long.clone(),
+                ..invite()
+            }
+            .is_valid()
+        );
+        assert!(
+            !WorldInvite {
+                loader_version: Some(long),
+                ..invite()
+            }
+            .is_valid()
+        );
+        assert!(
+            WorldInvite {
+                loader_version: None,
+                ..invite()
+            }
+            .is_valid()
+        );
+    }
+
+    #[test]
+    fn world_invites_are_sent_as_text() {
+        let invite = invite();
+        let message = ClientToServerMessage::WorldInvite {
+            to: UserId(1),
+            invite: invite.clone(),
+        };
+        let Ok(Either::Left(text)) = message.serialize() else {
+            panic!("world invites should be serialized as text");
+        };
+
+        let Ok(ClientToServerMessage::WorldInvite {
+            to,
+            invite: received,
+        }) = ClientToServerMessage::deserialize(Either::Left(&text))
+        else {
+            panic!("world invite should round trip");
+        };
+        assert_eq!(to, UserId(1));
+        assert_eq!(received.socket, invite.socket);
+        assert_eq!(received.world_name, invite.world_name);
+        assert_eq!(received.loader_version, invite.loader_version);
+        assert_eq!(received.content_hash, invite.content_hash);
+    }
+
+    #[test]
+    fn socket_data_is_sent_as_binary() {
+        let socket = Uuid::new_v4();
+        let message = ServerToClientMessage::SocketData {
+            socket,
+            data: vec![0, 1, 2, 255],
+        };
+        let Ok(Either::Right(bytes)) = message.serialize() else {
+            panic!("socket data should be serialized as binary");
+        };
+
+        let Ok(ServerToClientMessage::SocketData {
+            socket: received,
+            data,
+        }) = ServerToClientMessage::deserialize(Either::Right(&bytes))
+        else {
+            panic!("socket data should round trip");
+        };
+        assert_eq!(received, socket);
+        assert_eq!(data, [0, 1, 2, 255]);
+    }
 }
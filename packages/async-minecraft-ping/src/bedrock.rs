@@ -0,0 +1,311 @@
+//! This module defines a client for the RakNet
+//! [unconnected ping](https://wiki.vg/Raknet_Protocol#Unconnected_Ping)
+//! used by Minecraft: Bedrock Edition servers to report their status.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::net::UdpSocket;
+
+use crate::server::ServerError;
+
+const UNCONNECTED_PING_ID: u8 = 0x01;
+const UNCONNECTED_PONG_ID: u8 = 0x1c;
+const OFFLINE_MESSAGE_MAGIC: [u8; 16] = [
+    0x00, 0xff, 0xff, 0x00, 0xfe, 0xfe, 0xfe, 0xfe, 0xfd, 0xfd, 0xfd, 0xfd, 0x12, 0x34, 0x56, 0x78,
+];
+const MAX_PONG_LENGTH: usize = 1500;
+const DEFAULT_PORT: u16 = 19132;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The decoded server ID string from a Bedrock
+/// unconnected pong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BedrockStatusResponse {
+    /// The edition of the server, "MCPE" for Bedrock
+    /// or "MCEE" for Education Edition.
+    pub edition: String,
+
+    /// The first line of the server's MOTD.
+    pub motd: String,
+
+    /// The server's network protocol version.
+    pub protocol: i32,
+
+    /// The server's Minecraft version, i.e. "1.21.2".
+    pub version: String,
+
+    /// The number of players currently online.
+    pub players_online: i32,
+
+    /// The configured maximum number of players for the
+    /// server.
+    pub players_max: i32,
+
+    /// The server's unique ID.
+    pub server_id: Option<String>,
+
+    /// The second line of the server's MOTD, usually the
+    /// world name.
+    pub sub_motd: Option<String>,
+
+    /// The server's default game mode, i.e. "Survival".
+    pub game_mode: Option<String>,
+
+    /// The server's IPv4 port.
+    pub port_v4: Option<u16>,
+
+    /// The server's IPv6 port.
+    pub port_v6: Option<u16>,
+}
+
+impl BedrockStatusResponse {
+    /// Parses the semicolon-separated server ID string,
+    /// i.e. `MCPE;Dedicated Server;390;1.14.60;0;10;...`.
+    pub fn parse(server_id: &str) -> Result<Self, ServerError> {
+        let invalid = || ServerError::InvalidBedrockResponse(server_id.to_string());
+        let fields = server_id.split(';').collect::<Vec<_>>();
+        if fields.len() < 6 {
+            return Err(invalid());
+        }
+        let optional = |index: usize| {
+            fields
+                .get(index)
+                .filter(|field| !field.is_empty())
+                .map(|field| field.to_string())
+        };
+
+        Ok(BedrockStatusResponse {
+            edition: fields[0].to_string(),
+            motd: fields[1].to_string(),
+            protocol: fields[2].parse().map_err(|_| invalid())?,
+            version: fields[3].to_string(),
+            players_online: fields[4].parse().map_err(|_| invalid())?,
+            players_max: fields[5].parse().map_err(|_| invalid())?,
+            server_id: optional(6),
+            sub_motd: optional(7),
+            game_mode: optional(8),
+            port_v4: optional(10).and_then(|port| port.parse().ok()),
+            port_v6: optional(11).and_then(|port| port.parse().ok()),
+        })
+    }
+}
+
+/// Builder for a Minecraft Bedrock
+/// status ping.
+pub struct BedrockConnectionConfig {
+    address: String,
+    port: u16,
+    timeout: Duration,
+    client_guid: u64,
+}
+
+impl BedrockConnectionConfig {
+    /// Initiates the Bedrock server
+    /// ping build process.
+    pub fn build<T: Into<String>>(address: T) -> Self {
+        BedrockConnectionConfig {
+            address: address.into(),
+            port: DEFAULT_PORT,
+            timeout: DEFAULT_TIMEOUT,
+            client_guid: std::process::id() as u64,
+        }
+    }
+
+    /// Sets a specific port for the
+    /// ping to use. If not specified, the
+    /// default port of 19132 will be used.
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Sets a specific timeout for the
+    /// ping to use. If not specified, the
+    /// timeout defaults to two seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends an unconnected ping to the server
+    /// and consumes the builder.
+    ///
+    /// The timeout covers both resolving the address
+    /// and waiting for the pong, since UDP has no
+    /// connection step to time out on.
+    pub async fn ping(self) -> Result<BedrockStatusResponse, ServerError> {
+        tokio::time::timeout(self.timeout, self.ping_inner())
+            .await
+            .map_err(|_| ServerError::ConnectionTimedOut)?
+    }
+
+    async fn ping_inner(&self) -> Result<BedrockStatusResponse, ServerError> {
+        let address = tokio::net::lookup_host((self.address.as_str(), self.port))
+            .await
+            .map_err(|_| ServerError::FailedToConnect)?
+            .next()
+            .ok_or(ServerError::FailedToConnect)?;
+        let bind_address = if address.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(bind_address)
+            .await
+            .map_err(|_| ServerError::FailedToConnect)?;
+        socket
+            .connect(address)
+            .await
+            .map_err(|_| ServerError::FailedToConnect)?;
+
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_millis() as u64);
+        socket
+            .send(&encode_unconnected_ping(time, self.client_guid))
+            .await
+            .map_err(|_| ServerError::ProtocolError)?;
+
+        let mut buffer = [0; MAX_PONG_LENGTH];
+        loop {
+            let length = socket
+                .recv(&mut buffer)
+                .await
+                .map_err(|_| ServerError::ProtocolError)?;
+
+            // Ignore stray datagrams rather than failing the ping
+            if let Some(server_id) = decode_unconnected_pong(&buffer[..length], time) {
+                return BedrockStatusResponse::parse(&server_id);
+            }
+        }
+    }
+}
+
+/// Convenience wrapper for easily pinging
+/// a Bedrock server on the default port.
+pub async fn ping_bedrock(address: String) -> Result<BedrockStatusResponse, ServerError> {
+    BedrockConnectionConfig::build(address).ping().await
+}
+
+fn encode_unconnected_ping(time: u64, client_guid: u64) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(33);
+    packet.push(UNCONNECTED_PING_ID);
+    packet.extend_from_slice(&time.to_be_bytes());
+    packet.extend_from_slice(&OFFLINE_MESSAGE_MAGIC);
+    packet.extend_from_slice(&client_guid.to_be_bytes());
+    packet
+}
+
+/// Returns the server ID string from an unconnected pong
+/// answering the ping sent at `time`.
+fn decode_unconnected_pong(packet: &[u8], time: u64) -> Option<String> {
+    let (&id, rest) = packet.split_first()?;
+    if id != UNCONNECTED_PONG_ID || rest.len() < 34 {
+        return None;
+    }
+    let (pong_time, rest) = rest.split_at(8);
+    let (_server_guid, rest) = rest.split_at(8);
+    let (magic, rest) = rest.split_at(16);
+    if pong_time != time.to_be_bytes() || magic != OFFLINE_MESSAGE_MAGIC {
+        return None;
+    }
+
+    let (length, rest) = rest.split_at(2);
+    let length = u16::from_be_bytes([length[0], length[1]]) as usize;
+    let server_id = rest.get(..length)?;
+    String::from_utf8(server_id.to_vec()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SERVER_ID: &str =
+        "MCPE;Dedicated Server;390;1.14.60;3;10;13253860892328930865;Bedrock level;Survival;1;19132;19133;";
+
+    fn encode_unconnected_pong(time: &[u8], server_id: &str) -> Vec<u8> {
+        let mut packet = vec![UNCONNECTED_PONG_ID];
+        packet.extend_from_slice(time);
+        packet.extend_from_slice(&42u64.to_be_bytes());
+        packet.extend_from_slice(&OFFLINE_MESSAGE_MAGIC);
+        packet.extend_from_slice(&(server_id.len() as u16).to_be_bytes());
+        packet.extend_from_slice(server_id.as_bytes());
+        packet
+    }
+
+    #[test]
+    fn test_parse_server_id() {
+        let status = BedrockStatusResponse::parse(SERVER_ID).unwrap();
+        assert_eq!(status.edition, "MCPE");
+        assert_eq!(status.motd, "Dedicated Server");
+        assert_eq!(status.protocol, 390);
+        assert_eq!(status.version, "1.14.60");
+        assert_eq!(status.players_online, 3);
+        assert_eq!(status.players_max, 10);
+        assert_eq!(status.sub_motd.as_deref(), Some("Bedrock level"));
+        assert_eq!(status.game_mode.as_deref(), Some("Survival"));
+        assert_eq!(status.port_v4, Some(19132));
+        assert_eq!(status.port_v6, Some(19133));
+    }
+
+    #[test]
+    fn test_parse_minimal_server_id() {
+        let status = BedrockStatusResponse::parse("MCPE;A server;390;1.14.60;0;10").unwrap();
+        assert_eq!(status.server_id, None);
+        assert_eq!(status.sub_motd, None);
+    }
+
+    #[test]
+    fn test_parse_invalid_server_id() {
+        assert!(BedrockStatusResponse::parse("MCPE;A server").is_err());
+        assert!(BedrockStatusResponse::parse("MCPE;A server;x;1.14.60;0;10").is_err());
+    }
+
+    #[test]
+    fn test_decode_pong_rejects_other_pings() {
+        let pong = encode_unconnected_pong(&7u64.to_be_bytes(), SERVER_ID);
+        assert_eq!(
+            decode_unconnected_pong(&pong, 7).as_deref(),
+            Some(SERVER_ID)
+        );
+        assert_eq!(decode_unconnected_pong(&pong, 8), None);
+        assert_eq!(decode_unconnected_pong(&pong[..20], 7), None);
+    }
+
+    #[tokio::test]
+    async fn test_ping_local_responder() {
+        let responder = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let port = responder.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let mut buffer = [0; 64];
+            let (length, peer) = responder.recv_from(&mut buffer).await.unwrap();
+            assert_eq!(length, 33);
+            assert_eq!(buffer[0], UNCONNECTED_PING_ID);
+            assert_eq!(buffer[9..25], OFFLINE_MESSAGE_MAGIC);
+
+            let pong = encode_unconnected_pong(&buffer[1..9], SERVER_ID);
+            responder.send_to(&pong, peer).await.unwrap();
+        });
+
+        let status = BedrockConnectionConfig::build("127.0.0.1")
+            .with_port(port)
+            .ping()
+            .await
+            .unwrap();
+        assert_eq!(status.motd, "Dedicated Server");
+        assert_eq!(status.players_online, 3);
+    }
+
+    #[tokio::test]
+    async fn test_ping_times_out_without_responder() {
+        let responder = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let port = responder.local_addr().unwrap().port();
+
+        let result = BedrockConnectionConfig::build("127.0.0.1")
+            .with_port(port)
+            .with_timeout(Duration::from_millis(100))
+            .ping()
+            .await;
+        assert!(matches!(result, Err(ServerError::ConnectionTimedOut)));
+    }
+}
@@ -0,0 +1,257 @@
+//! This module defines a client for the
+//! [legacy server list ping](https://wiki.vg/Server_List_Ping#1.6)
+//! answered by servers from Beta 1.8 up to 1.6, which predate
+//! the handshake/status exchange.
+
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::server::{ServerDescription, ServerError, ServerPlayers, ServerVersion, StatusResponse};
+
+const LEGACY_PING_ID: u8 = 0xfe;
+const LEGACY_PING_PAYLOAD: u8 = 0x01;
+const PLUGIN_MESSAGE_ID: u8 = 0xfa;
+const KICK_ID: u8 = 0xff;
+const PING_HOST_CHANNEL: &str = "MC|PingHost";
+/// Protocol version sent in the ping, that of 1.6.4. Older
+/// servers ignore everything after the payload byte.
+const LEGACY_PROTOCOL_VERSION: u8 = 74;
+/// Longest kick message we accept, in UTF-16 code units.
+const MAX_KICK_LENGTH: usize = 32_767;
+
+/// The decoded response to a legacy ping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegacyStatusResponse {
+    /// The server's protocol version. Servers older than
+    /// 1.4 don't report one.
+    pub protocol: Option<i32>,
+
+    /// The server's Minecraft version, i.e. "1.6.4". Servers
+    /// older than 1.4 don't report one.
+    pub version: Option<String>,
+
+    /// The server's MOTD.
+    pub motd: String,
+
+    /// The number of players currently online.
+    pub players_online: i32,
+
+    /// The configured maximum number of players for the
+    /// server.
+    pub players_max: i32,
+}
+
+impl LegacyStatusResponse {
+    /// Parses the kick message a server answers the legacy
+    /// ping with, either `§1\0protocol\0version\0motd\0online\0max`
+    /// (1.4 to 1.6) or `motd§online§max` (Beta 1.8 to 1.3).
+    pub fn parse(message: &str) -> Result<Self, ServerError> {
+        let invalid = || ServerError::InvalidLegacyResponse(message.to_string());
+
+        if let Some(fields) = message.strip_prefix("§1\0") {
+            let fields = fields.split('\0').collect::<Vec<_>>();
+            let [protocol, version, motd, online, max] = fields[..] else {
+                return Err(invalid());
+            };
+            return Ok(LegacyStatusResponse {
+                protocol: Some(protocol.parse().map_err(|_| invalid())?),
+                version: Some(version.to_string()),
+                motd: motd.to_string(),
+                players_online: online.parse().map_err(|_| invalid())?,
+                players_max: max.parse().map_err(|_| invalid())?,
+            });
+        }
+
+        // The MOTD itself may contain `§` formatting codes
+        let mut fields = message.rsplitn(3, '§');
+        let (Some(max), Some(online), Some(motd)) = (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(invalid());
+        };
+        Ok(LegacyStatusResponse {
+            protocol: None,
+            version: None,
+            motd: motd.to_string(),
+            players_online: online.parse().map_err(|_| invalid())?,
+            players_max: max.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl From<LegacyStatusResponse> for StatusResponse {
+    /// Converts the legacy response to the modern one. Servers
+    /// that don't report a version get an empty version name and
+    /// a protocol version of 0.
+    fn from(legacy: LegacyStatusResponse) -> Self {
+        StatusResponse {
+            version: ServerVersion {
+                name: legacy.version.unwrap_or_default(),
+                protocol: legacy.protocol.unwrap_or_default(),
+            },
+            players: ServerPlayers {
+                max: legacy.players_max,
+                online: legacy.players_online,
+                sample: None,
+            },
+            description: ServerDescription::Plain(legacy.motd),
+            favicon: None,
+        }
+    }
+}
+
+/// Sends a legacy ping over `stream` and reads the response.
+///
+/// `address` and `port` are sent to the server as the host
+/// the client connected to, like the handshake does.
+pub(crate) async fn ping(
+    mut stream: TcpStream,
+    address: &str,
+    port: u16,
+    timeout: Duration,
+) -> Result<LegacyStatusResponse, ServerError> {
+    tokio::time::timeout(timeout, async {
+        stream
+            .write_all(&encode_ping(address, port))
+            .await
+            .map_err(|_| ServerError::ProtocolError)?;
+        stream
+            .flush()
+            .await
+            .map_err(|_| ServerError::ProtocolError)?;
+
+        let id = stream
+            .read_u8()
+            .await
+            .map_err(|_| ServerError::ProtocolError)?;
+        let length = stream
+            .read_u16()
+            .await
+            .map_err(|_| ServerError::ProtocolError)? as usize;
+        if id != KICK_ID || length > MAX_KICK_LENGTH {
+            return Err(ServerError::ProtocolError);
+        }
+
+        let mut message = vec![0; length * 2];
+        stream
+            .read_exact(&mut message)
+            .await
+            .map_err(|_| ServerError::ProtocolError)?;
+        LegacyStatusResponse::parse(&decode_utf16(&message))
+    })
+    .await
+    .map_err(|_| ServerError::ConnectionTimedOut)?
+}
+
+fn encode_ping(address: &str, port: u16) -> Vec<u8> {
+    let mut data = vec![LEGACY_PROTOCOL_VERSION];
+    write_utf16(&mut data, address);
+    data.extend_from_slice(&(port as i32).to_be_bytes());
+
+    let mut packet = vec![LEGACY_PING_ID, LEGACY_PING_PAYLOAD, PLUGIN_MESSAGE_ID];
+    write_utf16(&mut packet, PING_HOST_CHANNEL);
+    packet.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    packet.extend_from_slice(&data);
+    packet
+}
+
+fn write_utf16(out: &mut Vec<u8>, text: &str) {
+    let encoded = text.encode_utf16().collect::<Vec<_>>();
+    out.extend_from_slice(&(encoded.len() as u16).to_be_bytes());
+    out.extend(encoded.into_iter().flat_map(u16::to_be_bytes));
+}
+
+fn decode_utf16(bytes: &[u8]) -> String {
+    String::from_utf16_lossy(
+        &bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect::<Vec<_>>(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tokio::net::TcpListener;
+
+    fn encode_kick(message: &str) -> Vec<u8> {
+        let mut packet = vec![KICK_ID];
+        write_utf16(&mut packet, message);
+        packet
+    }
+
+    #[test]
+    fn test_encode_ping() {
+        let packet = encode_ping("localhost", 25565);
+        assert_eq!(packet[..3], [0xfe, 0x01, 0xfa]);
+        // channel: length 11, then 22 bytes of UTF-16
+        assert_eq!(packet[3..5], [0x00, 0x0b]);
+        // data: protocol + host (2 + 18 bytes) + port
+        assert_eq!(packet[27..29], [0x00, 0x19]);
+        assert_eq!(packet[29], LEGACY_PROTOCOL_VERSION);
+        assert_eq!(packet[packet.len() - 4..], 25565i32.to_be_bytes());
+    }
+
+    #[test]
+    fn test_parse_1_4_response() {
+        let status =
+            LegacyStatusResponse::parse("§1\x0074\x001.6.4\x00A Minecraft Server\x003\x0020")
+                .unwrap();
+        assert_eq!(status.protocol, Some(74));
+        assert_eq!(status.version.as_deref(), Some("1.6.4"));
+        assert_eq!(status.motd, "A Minecraft Server");
+        assert_eq!(status.players_online, 3);
+        assert_eq!(status.players_max, 20);
+    }
+
+    #[test]
+    fn test_parse_beta_response() {
+        let status = LegacyStatusResponse::parse("§aA §lBeta§r Server§3§20").unwrap();
+        assert_eq!(status.protocol, None);
+        assert_eq!(status.version, None);
+        assert_eq!(status.motd, "§aA §lBeta§r Server");
+        assert_eq!(status.players_online, 3);
+        assert_eq!(status.players_max, 20);
+    }
+
+    #[test]
+    fn test_parse_invalid_response() {
+        assert!(LegacyStatusResponse::parse("A Minecraft Server").is_err());
+        assert!(LegacyStatusResponse::parse("§1\x0074\x001.6.4\x00A Minecraft Server").is_err());
+        assert!(LegacyStatusResponse::parse("A Minecraft Server§x§20").is_err());
+    }
+
+    #[test]
+    fn test_legacy_into_status_response() {
+        let status = StatusResponse::from(LegacyStatusResponse::parse("Beta§3§20").unwrap());
+        assert_eq!(status.version.name, "");
+        assert_eq!(status.version.protocol, 0);
+        assert_eq!(status.players.online, 3);
+        assert!(matches!(status.description, ServerDescription::Plain(motd) if motd == "Beta"));
+    }
+
+    #[tokio::test]
+    async fn test_ping_local_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut header = [0; 3];
+            stream.read_exact(&mut header).await.unwrap();
+            assert_eq!(header, [0xfe, 0x01, 0xfa]);
+
+            let kick = encode_kick("§1\x0074\x001.6.4\x00Legacy\x000\x0010");
+            stream.write_all(&kick).await.unwrap();
+        });
+
+        let stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let status = ping(stream, "127.0.0.1", port, Duration::from_secs(2))
+            .await
+            .unwrap();
+        assert_eq!(status.motd, "Legacy");
+        assert_eq!(status.players_max, 10);
+    }
+}
@@ -1,5 +1,13 @@
+mod bedrock;
+mod legacy;
 mod protocol;
+#[cfg(feature = "query")]
+mod query;
 mod server;
+pub use bedrock::{ping_bedrock, BedrockConnectionConfig, BedrockStatusResponse};
+pub use legacy::LegacyStatusResponse;
+#[cfg(feature = "query")]
+pub use query::{query, QueryConfig, QueryResponse};
 pub use server::{
     connect, ConnectionConfig, ServerDescription, ServerError, ServerPlayer, ServerPlayers,
     ServerVersion, StatusConnection, StatusResponse,
@@ -0,0 +1,378 @@
+//! This module defines a client for the UDP
+//! [Query](https://wiki.vg/Query) protocol, which servers
+//! with `enable-query` set expose alongside ServerListPing.
+//!
+//! It's only compiled with the `query` feature enabled.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tokio::net::UdpSocket;
+
+use crate::server::ServerError;
+
+const MAGIC: [u8; 2] = [0xfe, 0xfd];
+const HANDSHAKE_TYPE: u8 = 0x09;
+const STAT_TYPE: u8 = 0x00;
+/// `splitnum\0\x80\0`, sent before the key/value section
+/// of a full stat response.
+const KEY_VALUE_PADDING: usize = 11;
+/// `\x01player_\0\0`, sent before the player section of a
+/// full stat response.
+const PLAYER_PADDING: usize = 10;
+const MAX_RESPONSE_LENGTH: usize = 65_535;
+const DEFAULT_PORT: u16 = 25565;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The decoded response to a full stat Query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryResponse {
+    /// The server's MOTD.
+    pub motd: String,
+
+    /// The server's game type, always "SMP".
+    pub game_type: String,
+
+    /// The server's game ID, always "MINECRAFT".
+    pub game_id: String,
+
+    /// The server's Minecraft version, i.e. "1.21.1".
+    pub version: String,
+
+    /// The server software, i.e. "Paper on 1.21.1", if the
+    /// server reports one.
+    pub server_mod: Option<String>,
+
+    /// The plugins the server reports, i.e. "WorldEdit 7.3.6".
+    pub plugins: Vec<String>,
+
+    /// The name of the server's main world.
+    pub map: String,
+
+    /// The number of players currently online.
+    pub players_online: i32,
+
+    /// The configured maximum number of players for the
+    /// server.
+    pub players_max: i32,
+
+    /// The port the server is listening on.
+    pub host_port: u16,
+
+    /// The IP the server is listening on.
+    pub host_ip: String,
+
+    /// The names of the players currently online.
+    pub players: Vec<String>,
+}
+
+impl QueryResponse {
+    /// Parses the payload of a full stat response, everything
+    /// after the type and session ID.
+    fn parse(payload: &[u8]) -> Result<Self, ServerError> {
+        let mut reader = payload
+            .get(KEY_VALUE_PADDING..)
+            .ok_or(ServerError::InvalidQueryResponse)?;
+
+        let mut values = HashMap::new();
+        loop {
+            let key = read_string(&mut reader)?;
+            if key.is_empty() {
+                break;
+            }
+            values.insert(key, read_string(&mut reader)?);
+        }
+
+        let mut reader = reader
+            .get(PLAYER_PADDING..)
+            .ok_or(ServerError::InvalidQueryResponse)?;
+        let mut players = Vec::new();
+        loop {
+            let player = read_string(&mut reader)?;
+            if player.is_empty() {
+                break;
+            }
+            players.push(player);
+        }
+
+        let mut value = |key: &str| values.remove(key).unwrap_or_default();
+        let number = |value: String| value.parse().map_err(|_| ServerError::InvalidQueryResponse);
+
+        // i.e. "Paper on 1.21.1: WorldEdit 7.3.6; LuckPerms 5.4.141"
+        let plugins = value("plugins");
+        let (server_mod, plugins) = match plugins.split_once(": ") {
+            Some((server_mod, plugins)) => (server_mod, plugins),
+            None => (plugins.as_str(), ""),
+        };
+
+        Ok(QueryResponse {
+            motd: value("hostname"),
+            game_type: value("gametype"),
+            game_id: value("game_id"),
+            version: value("version"),
+            server_mod: Some(server_mod.to_string()).filter(|server_mod| !server_mod.is_empty()),
+            plugins: plugins
+                .split("; ")
+                .filter(|plugin| !plugin.is_empty())
+                .map(str::to_string)
+                .collect(),
+            map: value("map"),
+            players_online: number(value("numplayers"))?,
+            players_max: number(value("maxplayers"))?,
+            host_port: number(value("hostport")).unwrap_or(0) as u16,
+            host_ip: value("hostip"),
+            players,
+        })
+    }
+}
+
+/// Builder for a Minecraft
+/// Query request.
+pub struct QueryConfig {
+    address: String,
+    port: u16,
+    timeout: Duration,
+    session_id: i32,
+}
+
+impl QueryConfig {
+    /// Initiates the Query
+    /// build process.
+    pub fn build<T: Into<String>>(address: T) -> Self {
+        QueryConfig {
+            address: address.into(),
+            port: DEFAULT_PORT,
+            timeout: DEFAULT_TIMEOUT,
+            session_id: std::process::id() as i32,
+        }
+    }
+
+    /// Sets a specific port for the
+    /// Query to use. If not specified, the
+    /// default port of 25565 will be used.
+    ///
+    /// Servers can set `query.port` to listen
+    /// on a different port than the game.
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Sets a specific timeout for the
+    /// Query to use. If not specified, the
+    /// timeout defaults to two seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Performs the handshake and full stat request
+    /// and consumes the builder.
+    ///
+    /// Like the Bedrock ping, the timeout covers the
+    /// whole exchange.
+    pub async fn query(self) -> Result<QueryResponse, ServerError> {
+        tokio::time::timeout(self.timeout, self.query_inner())
+            .await
+            .map_err(|_| ServerError::ConnectionTimedOut)?
+    }
+
+    async fn query_inner(&self) -> Result<QueryResponse, ServerError> {
+        let address = tokio::net::lookup_host((self.address.as_str(), self.port))
+            .await
+            .map_err(|_| ServerError::FailedToConnect)?
+            .next()
+            .ok_or(ServerError::FailedToConnect)?;
+        let bind_address = if address.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(bind_address)
+            .await
+            .map_err(|_| ServerError::FailedToConnect)?;
+        socket
+            .connect(address)
+            .await
+            .map_err(|_| ServerError::FailedToConnect)?;
+
+        // Only the low 4 bits of each byte are read by the server
+        let session_id = self.session_id & 0x0f0f_0f0f;
+
+        let mut buffer = vec![0; MAX_RESPONSE_LENGTH];
+        socket
+            .send(&encode_request(HANDSHAKE_TYPE, session_id, &[]))
+            .await
+            .map_err(|_| ServerError::ProtocolError)?;
+        let payload = receive(&socket, &mut buffer, HANDSHAKE_TYPE, session_id).await?;
+        let mut reader = payload;
+        let challenge = read_string(&mut reader)?
+            .parse::<i32>()
+            .map_err(|_| ServerError::InvalidQueryResponse)?;
+
+        // Four bytes of padding request the full stat, rather
+        // than the basic one
+        let mut stat_payload = challenge.to_be_bytes().to_vec();
+        stat_payload.extend_from_slice(&[0; 4]);
+        socket
+            .send(&encode_request(STAT_TYPE, session_id, &stat_payload))
+            .await
+            .map_err(|_| ServerError::ProtocolError)?;
+        let payload = receive(&socket, &mut buffer, STAT_TYPE, session_id).await?;
+        QueryResponse::parse(payload)
+    }
+}
+
+/// Convenience wrapper for easily querying
+/// a server on the default port.
+pub async fn query(address: String) -> Result<QueryResponse, ServerError> {
+    QueryConfig::build(address).query().await
+}
+
+fn encode_request(request_type: u8, session_id: i32, payload: &[u8]) -> Vec<u8> {
+    let mut packet = MAGIC.to_vec();
+    packet.push(request_type);
+    packet.extend_from_slice(&session_id.to_be_bytes());
+    packet.extend_from_slice(payload);
+    packet
+}
+
+/// Receives the response of type `response_type` for our
+/// session, returning its payload.
+async fn receive<'a>(
+    socket: &UdpSocket,
+    buffer: &'a mut [u8],
+    response_type: u8,
+    session_id: i32,
+) -> Result<&'a [u8], ServerError> {
+    loop {
+        let length = socket
+            .recv(buffer)
+            .await
+            .map_err(|_| ServerError::ProtocolError)?;
+
+        // Ignore stray datagrams rather than failing the query
+        if length >= 5 && buffer[0] == response_type && buffer[1..5] == session_id.to_be_bytes() {
+            return Ok(&buffer[5..length]);
+        }
+    }
+}
+
+/// Reads a null-terminated string, advancing the reader
+/// past the terminator.
+fn read_string(reader: &mut &[u8]) -> Result<String, ServerError> {
+    let end = reader
+        .iter()
+        .position(|&byte| byte == 0)
+        .ok_or(ServerError::InvalidQueryResponse)?;
+    let string = String::from_utf8_lossy(&reader[..end]).into_owned();
+    *reader = &reader[end + 1..];
+    Ok(string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_stat_payload(values: &[(&str, &str)], players: &[&str]) -> Vec<u8> {
+        let mut payload = b"splitnum\0\x80\0".to_vec();
+        for (key, value) in values {
+            payload.extend_from_slice(key.as_bytes());
+            payload.push(0);
+            payload.extend_from_slice(value.as_bytes());
+            payload.push(0);
+        }
+        payload.push(0);
+        payload.extend_from_slice(b"\x01player_\0\0");
+        for player in players {
+            payload.extend_from_slice(player.as_bytes());
+            payload.push(0);
+        }
+        payload.push(0);
+        payload
+    }
+
+    const VALUES: &[(&str, &str)] = &[
+        ("hostname", "A Minecraft Server"),
+        ("gametype", "SMP"),
+        ("game_id", "MINECRAFT"),
+        ("version", "1.21.1"),
+        (
+            "plugins",
+            "Paper on 1.21.1: WorldEdit 7.3.6; LuckPerms 5.4.141",
+        ),
+        ("map", "world"),
+        ("numplayers", "2"),
+        ("maxplayers", "20"),
+        ("hostport", "25565"),
+        ("hostip", "127.0.0.1"),
+    ];
+
+    #[test]
+    fn test_parse_full_stat() {
+        let payload = encode_stat_payload(VALUES, &["Player1", "Player2"]);
+        let response = QueryResponse::parse(&payload).unwrap();
+        assert_eq!(response.motd, "A Minecraft Server");
+        assert_eq!(response.version, "1.21.1");
+        assert_eq!(response.server_mod.as_deref(), Some("Paper on 1.21.1"));
+        assert_eq!(response.plugins, ["WorldEdit 7.3.6", "LuckPerms 5.4.141"]);
+        assert_eq!(response.map, "world");
+        assert_eq!(response.players_online, 2);
+        assert_eq!(response.players_max, 20);
+        assert_eq!(response.host_port, 25565);
+        assert_eq!(response.players, ["Player1", "Player2"]);
+    }
+
+    #[test]
+    fn test_parse_vanilla_plugins() {
+        let mut values = VALUES.to_vec();
+        values[4] = ("plugins", "");
+        let response = QueryResponse::parse(&encode_stat_payload(&values, &[])).unwrap();
+        assert_eq!(response.server_mod, None);
+        assert!(response.plugins.is_empty());
+        assert!(response.players.is_empty());
+    }
+
+    #[test]
+    fn test_parse_truncated_stat() {
+        let payload = encode_stat_payload(VALUES, &["Player1"]);
+        assert!(QueryResponse::parse(&payload[..40]).is_err());
+        assert!(QueryResponse::parse(&payload[..5]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_query_local_responder() {
+        let responder = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let port = responder.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let mut buffer = [0; 64];
+            let (length, peer) = responder.recv_from(&mut buffer).await.unwrap();
+            assert_eq!(length, 7);
+            assert_eq!(buffer[..3], [0xfe, 0xfd, HANDSHAKE_TYPE]);
+            let session_id = buffer[3..7].to_vec();
+
+            let mut handshake = vec![HANDSHAKE_TYPE];
+            handshake.extend_from_slice(&session_id);
+            handshake.extend_from_slice(b"9513307\0");
+            responder.send_to(&handshake, peer).await.unwrap();
+
+            let (length, peer) = responder.recv_from(&mut buffer).await.unwrap();
+            assert_eq!(length, 15);
+            assert_eq!(buffer[2], STAT_TYPE);
+            assert_eq!(buffer[7..11], 9513307i32.to_be_bytes());
+
+            let mut stat = vec![STAT_TYPE];
+            stat.extend_from_slice(&session_id);
+            stat.extend_from_slice(&encode_stat_payload(VALUES, &["Player1"]));
+            responder.send_to(&stat, peer).await.unwrap();
+        });
+
+        let response = QueryConfig::build("127.0.0.1")
+            .with_port(port)
+            .query()
+            .await
+            .unwrap();
+        assert_eq!(response.motd, "A Minecraft Server");
+        assert_eq!(response.players, ["Player1"]);
+    }
+}
@@ -7,6 +7,7 @@ use serde::Deserialize;
 use thiserror::Error;
 use tokio::net::TcpStream;
 
+use crate::legacy::{self, LegacyStatusResponse};
 use crate::protocol::{self, AsyncReadRawPacket, AsyncWriteRawPacket};
 
 #[derive(Error, Debug)]
@@ -25,6 +26,15 @@ pub enum ServerError {
 
     #[error("mismatched pong payload (expected \"{expected}\", got \"{actual}\")")]
     MismatchedPayload { expected: u64, actual: u64 },
+
+    #[error("invalid Bedrock server ID: \"{0}\"")]
+    InvalidBedrockResponse(String),
+
+    #[error("invalid legacy ping response: \"{0}\"")]
+    InvalidLegacyResponse(String),
+
+    #[error("invalid Query response")]
+    InvalidQueryResponse,
 }
 
 impl From<protocol::ProtocolError> for ServerError {
@@ -166,14 +176,7 @@ impl ConnectionConfig {
     /// Connects to the server and consumes the builder.
     pub async fn connect(self) -> Result<StatusConnection, ServerError> {
         let (address, port) = self.resolve_address().await;
-
-        let stream = tokio::time::timeout(
-            self.timeout,
-            TcpStream::connect(format!("{}:{}", address, port)),
-        )
-        .await
-        .map_err(|_| ServerError::ConnectionTimedOut)?
-        .map_err(|_| ServerError::FailedToConnect)?;
+        let stream = self.open_stream(&address, port).await?;
 
         Ok(StatusConnection {
             stream,
@@ -184,6 +187,63 @@ impl ConnectionConfig {
         })
     }
 
+    /// Connects to the server, reads its status and
+    /// consumes the builder.
+    ///
+    /// If the server connects but closes the connection
+    /// or answers the ServerListPing status call with an
+    /// unexpected packet, i.e. because it predates 1.7,
+    /// the status is read with the legacy ping instead.
+    /// Should that fail too, the original error is
+    /// returned.
+    pub async fn status(self) -> Result<StatusResponse, ServerError> {
+        let (address, port) = self.resolve_address().await;
+        let stream = self.open_stream(&address, port).await?;
+
+        let mut connection = StatusConnection {
+            stream,
+            protocol_version: self.protocol_version,
+            address: address.clone(),
+            port,
+            timeout: self.timeout,
+        };
+        let response = match connection.request_status().await {
+            Ok(response) => response,
+            Err(error) if is_legacy_server_error(&error) => {
+                // Legacy servers close the connection after an
+                // unknown packet, so the ping needs a new one
+                let legacy = match self.open_stream(&address, port).await {
+                    Ok(stream) => legacy::ping(stream, &address, port, self.timeout).await,
+                    Err(legacy_error) => Err(legacy_error),
+                };
+                return legacy.map(Into::into).map_err(|_| error.into());
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        serde_json::from_str(&response.body).map_err(|_| ServerError::InvalidJson(response.body))
+    }
+
+    /// Reads the server's status with the legacy ping
+    /// used by servers from Beta 1.8 up to 1.6, and
+    /// consumes the builder.
+    pub async fn legacy_ping(self) -> Result<LegacyStatusResponse, ServerError> {
+        let (address, port) = self.resolve_address().await;
+        let stream = self.open_stream(&address, port).await?;
+
+        legacy::ping(stream, &address, port, self.timeout).await
+    }
+
+    async fn open_stream(&self, address: &str, port: u16) -> Result<TcpStream, ServerError> {
+        tokio::time::timeout(
+            self.timeout,
+            TcpStream::connect(format!("{}:{}", address, port)),
+        )
+        .await
+        .map_err(|_| ServerError::ConnectionTimedOut)?
+        .map_err(|_| ServerError::FailedToConnect)
+    }
+
     #[cfg(feature = "srv")]
     async fn resolve_address(&self) -> (String, u16) {
         if !self.srv_lookup {
@@ -249,6 +309,24 @@ impl StatusConnection {
     /// status body is accessible via the `status`
     /// property on `PingConnection`.
     pub async fn status(mut self) -> Result<PingConnection, ServerError> {
+        let response = self.request_status().await?;
+
+        let status: StatusResponse = serde_json::from_str(&response.body)
+            .map_err(|_| ServerError::InvalidJson(response.body))?;
+
+        Ok(PingConnection {
+            stream: self.stream,
+            protocol_version: self.protocol_version,
+            address: self.address,
+            port: self.port,
+            status,
+            timeout: self.timeout,
+        })
+    }
+
+    async fn request_status(
+        &mut self,
+    ) -> Result<protocol::ResponsePacket, protocol::ProtocolError> {
         let handshake = protocol::HandshakePacket::new(
             self.protocol_version,
             self.address.to_string(),
@@ -263,20 +341,27 @@ impl StatusConnection {
             .write_packet_with_timeout(protocol::RequestPacket::new(), self.timeout)
             .await?;
 
-        let response: protocol::ResponsePacket =
-            self.stream.read_packet_with_timeout(self.timeout).await?;
-
-        let status: StatusResponse = serde_json::from_str(&response.body)
-            .map_err(|_| ServerError::InvalidJson(response.body))?;
+        self.stream.read_packet_with_timeout(self.timeout).await
+    }
+}
 
-        Ok(PingConnection {
-            stream: self.stream,
-            protocol_version: self.protocol_version,
-            address: self.address,
-            port: self.port,
-            status,
-            timeout: self.timeout,
-        })
+/// Whether a failed status call means the server predates
+/// it: such servers either close the connection or answer
+/// with a kick packet, which doesn't parse as a modern
+/// packet. Timeouts and malformed responses don't.
+fn is_legacy_server_error(error: &protocol::ProtocolError) -> bool {
+    match error {
+        protocol::ProtocolError::Io(error) => matches!(
+            error.kind(),
+            std::io::ErrorKind::UnexpectedEof
+                | std::io::ErrorKind::ConnectionReset
+                | std::io::ErrorKind::ConnectionAborted
+                | std::io::ErrorKind::BrokenPipe
+        ),
+        protocol::ProtocolError::InvalidPacketLength
+        | protocol::ProtocolError::InvalidVarInt
+        | protocol::ProtocolError::InvalidPacketId { .. } => true,
+        protocol::ProtocolError::InvalidResponseBody | protocol::ProtocolError::Timeout(_) => false,
     }
 }
 
@@ -419,6 +504,91 @@ mod tests {
         assert_eq!(config.protocol_version, 47);
     }
 
+    #[tokio::test]
+    async fn test_status_falls_back_to_legacy_ping() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            // A pre-1.7 server drops the connection on the handshake
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut id = [0; 2];
+            stream.read_exact(&mut id).await.unwrap();
+            drop(stream);
+
+            let (mut stream, _) = listener.accept().await.unwrap();
+            stream.read_exact(&mut id).await.unwrap();
+            assert_eq!(id, [0xfe, 0x01]);
+            let message = "Old server§1§8".encode_utf16().collect::<Vec<_>>();
+            let mut kick = vec![0xff];
+            kick.extend_from_slice(&(message.len() as u16).to_be_bytes());
+            kick.extend(message.into_iter().flat_map(u16::to_be_bytes));
+            stream.write_all(&kick).await.unwrap();
+        });
+
+        let status = ConnectionConfig::build("127.0.0.1")
+            .with_port(port)
+            .status()
+            .await
+            .unwrap();
+        assert_eq!(status.players.online, 1);
+        assert_eq!(status.players.max, 8);
+        assert!(
+            matches!(status.description, ServerDescription::Plain(motd) if motd == "Old server")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_status_does_not_fall_back_on_timeout() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let status = tokio::spawn(
+            ConnectionConfig::build("127.0.0.1")
+                .with_port(port)
+                .with_timeout(Duration::from_millis(200))
+                .status(),
+        );
+
+        // The server accepts the connection but never answers
+        let (_stream, _) = listener.accept().await.unwrap();
+        assert!(matches!(
+            status.await.unwrap(),
+            Err(ServerError::ProtocolError)
+        ));
+        let legacy_connection =
+            tokio::time::timeout(Duration::from_millis(200), listener.accept()).await;
+        assert!(legacy_connection.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_status_keeps_error_when_legacy_ping_fails() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            // Answers the handshake with a packet of an unknown ID
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut id = [0; 2];
+            stream.read_exact(&mut id).await.unwrap();
+            stream.write_all(&[0x01, 0x05]).await.unwrap();
+
+            let (mut stream, _) = listener.accept().await.unwrap();
+            stream.read_exact(&mut id).await.unwrap();
+            stream
+                .write_all(&[0xff, 0x00, 0x01, 0x00, 0x41])
+                .await
+                .unwrap();
+        });
+
+        let status = ConnectionConfig::build("127.0.0.1")
+            .with_port(port)
+            .status()
+            .await;
+        assert!(matches!(status, Err(ServerError::ProtocolError)));
+    }
+
     #[cfg(feature = "srv")]
     #[test]
     fn test_connection_config_with_srv_lookup() {
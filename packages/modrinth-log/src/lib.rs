@@ -8,7 +8,8 @@ use tracing::Level;
 use tracing::level_filters::LevelFilter;
 use tracing_ecs::ECSLayerBuilder;
 use tracing_subscriber::{
-    EnvFilter, layer::SubscriberExt, util::SubscriberInitExt,
+    EnvFilter, Layer, Registry, layer::Identity, layer::SubscriberExt,
+    util::SubscriberInitExt,
 };
 
 /// How this service will output logs to the terminal output.
@@ -62,6 +63,21 @@ pub fn init() -> Result<()> {
 ///
 /// Errors if logging could not be initialized.
 pub fn init_with_config(compact: bool) -> Result<()> {
+    init_with_layer(compact, Identity::new())
+}
+
+/// Initializes logging for Modrinth services, with an extra `layer` which
+/// receives every span and event alongside the output layers.
+///
+/// See [`init_with_config`] for `compact`.
+///
+/// # Errors
+///
+/// Errors if logging could not be initialized.
+pub fn init_with_layer<L>(compact: bool, layer: L) -> Result<()>
+where
+    L: Layer<Registry> + Send + Sync + 'static,
+{
     let output_format = match env_var(OUTPUT_FORMAT_ENV_VAR) {
         Ok(format) => format
             .parse::<OutputFormat>()
@@ -91,7 +107,7 @@ pub fn init_with_config(compact: bool) -> Result<()> {
         }
     };
 
-    let registry = tracing_subscriber::registry();
+    let registry = tracing_subscriber::registry().with(layer);
 
     let registry = registry
         .with(env_filter)
@@ -1,14 +1,13 @@
-//! Example/testing binary for checking if a MaxMind database can be loaded from
+//! Example/testing binary for checking if MaxMind databases can be loaded from
 //! the current environment.
 
 use std::net::IpAddr;
 
 use eyre::Result;
-use maxminddb::geoip2;
-use modrinth_util::Context;
 use tracing::info;
 
-/// Looks up country details for an IP using the MaxMind database
+/// Looks up location and network details for an IP using the MaxMind
+/// databases
 #[derive(Debug, clap::Parser)]
 struct Args {
     /// IP address to look up
@@ -20,16 +19,12 @@ async fn main() -> Result<()> {
     let args = <Args as clap::Parser>::parse();
     tracing_subscriber::fmt().init();
 
-    let maxmind = modrinth_maxmind::init_reader()
-        .await
-        .wrap_err("failed to create reader")?;
+    let maxmind = modrinth_maxmind::MaxMind::new().await;
 
     let ip = args.ip;
-    let country = maxmind
-        .lookup::<geoip2::Country>(ip)
-        .wrap_err("failed to lookup country")?;
+    let lookup = maxmind.lookup(ip).await;
 
-    info!("Country details for {ip:?}:\n{country:#?}");
+    info!("Details for {ip:?}:\n{lookup:#?}");
 
     Ok(())
 }
@@ -3,10 +3,13 @@
 use std::{
     io::{Cursor, Read},
     net::IpAddr,
-    path::Path,
-    sync::Arc,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Weak},
+    time::Duration,
 };
 
+use arc_swap::ArcSwapOption;
 use flate2::read::GzDecoder;
 pub use maxminddb::{self, geoip2};
 
@@ -16,39 +19,277 @@ use modrinth_util::{Context, env_var};
 use tokio::fs;
 use tracing::{debug, info, warn};
 
-/// MaxMind GeoIP database reader for use as a `web::Data` parameter.
-#[derive(Debug, Clone)]
-pub struct MaxMind {
-    /// Database reader.
+/// How often databases are reloaded if `MAXMIND_REFRESH_INTERVAL_SECS` is not
+/// set. MaxMind publishes GeoLite updates twice a week.
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Which MaxMind product line the databases are downloaded from.
+///
+/// Lookups return the same [`GeoLookup`] for both editions, but GeoLite has
+/// no anonymizer database, so [`GeoLookup::is_anonymizer`] and
+/// [`GeoLookup::is_hosting`] are always [`None`] with it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Edition {
+    /// Free GeoLite2 databases.
+    #[default]
+    GeoLite,
+    /// Paid GeoIP2 databases.
+    GeoIp,
+}
+
+impl FromStr for Edition {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "geolite" => Ok(Self::GeoLite),
+            "geoip" => Ok(Self::GeoIp),
+            _ => Err(eyre!("invalid MaxMind edition '{s}'")),
+        }
+    }
+}
+
+impl Edition {
+    /// MaxMind's edition ID for the database of a given `kind`, used for
+    /// downloads and cache file names.
     ///
-    /// If the backend was not configured with MaxMind, the reader will not be
-    /// available.
-    pub reader: Option<Arc<maxminddb::Reader<Bytes>>>,
+    /// Returns [`None`] if this edition has no such database.
+    #[must_use]
+    pub const fn edition_id(self, kind: DatabaseKind) -> Option<&'static str> {
+        match (self, kind) {
+            (Self::GeoLite, DatabaseKind::Location) => Some("GeoLite2-City"),
+            (Self::GeoLite, DatabaseKind::Asn) => Some("GeoLite2-ASN"),
+            (Self::GeoLite, DatabaseKind::AnonymousIp) => None,
+            (Self::GeoIp, DatabaseKind::Location) => Some("GeoIP2-City"),
+            (Self::GeoIp, DatabaseKind::Asn) => Some("GeoIP2-ISP"),
+            (Self::GeoIp, DatabaseKind::AnonymousIp) => {
+                Some("GeoIP2-Anonymous-IP")
+            }
+        }
+    }
+}
+
+/// The kinds of database a [`MaxMind`] reads from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatabaseKind {
+    /// Country and subdivision of an IP.
+    Location,
+    /// Autonomous system number and organization of an IP.
+    Asn,
+    /// Whether an IP belongs to a VPN, proxy, Tor exit node or hosting
+    /// provider.
+    AnonymousIp,
+}
+
+impl DatabaseKind {
+    const ALL: [Self; 3] = [Self::Location, Self::Asn, Self::AnonymousIp];
+
+    /// Environment variable which overrides the database with a local file.
+    const fn path_env_var(self) -> &'static str {
+        match self {
+            Self::Location => "MAXMIND_DB",
+            Self::Asn => "MAXMIND_ASN_DB",
+            Self::AnonymousIp => "MAXMIND_ANONYMOUS_IP_DB",
+        }
+    }
+}
+
+/// Everything known about an IP address across the configured databases.
+///
+/// Fields are [`None`] if the database they come from is not available or
+/// has no record for the IP.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GeoLookup {
+    /// ISO 3166-1 country code, e.g. `US`.
+    pub country: Option<String>,
+    /// ISO 3166-2 code of the most specific subdivision within the country,
+    /// without the country prefix, e.g. `CA` for California.
+    pub region: Option<String>,
+    /// Autonomous system number of the network.
+    pub asn: Option<u32>,
+    /// Organization which owns the autonomous system.
+    pub organization: Option<String>,
+    /// Whether the IP is a VPN, public or residential proxy, or Tor exit
+    /// node.
+    pub is_anonymizer: Option<bool>,
+    /// Whether the IP belongs to a hosting or VPN provider rather than an
+    /// end user network.
+    pub is_hosting: Option<bool>,
+}
+
+/// MaxMind GeoIP database readers for use as a `web::Data` parameter.
+///
+/// Readers are reloaded periodically in the background and swapped in
+/// atomically, so lookups never wait on a reload.
+#[derive(Debug, Clone, Default)]
+pub struct MaxMind {
+    databases: Arc<Databases>,
+}
+
+#[derive(Debug, Default)]
+struct Databases {
+    location: Database,
+    asn: Database,
+    anonymous_ip: Database,
+}
+
+impl Databases {
+    fn get(&self, kind: DatabaseKind) -> &Database {
+        match kind {
+            DatabaseKind::Location => &self.location,
+            DatabaseKind::Asn => &self.asn,
+            DatabaseKind::AnonymousIp => &self.anonymous_ip,
+        }
+    }
+
+    fn get_mut(&mut self, kind: DatabaseKind) -> &mut Database {
+        match kind {
+            DatabaseKind::Location => &mut self.location,
+            DatabaseKind::Asn => &mut self.asn,
+            DatabaseKind::AnonymousIp => &mut self.anonymous_ip,
+        }
+    }
+}
+
+/// A database reader and where to reload it from.
+///
+/// If the backend was not configured with this database, the reader will not
+/// be available.
+#[derive(Debug, Default)]
+struct Database {
+    source: Option<Source>,
+    reader: ArcSwapOption<maxminddb::Reader<Bytes>>,
+}
+
+#[derive(Debug)]
+enum Source {
+    File(PathBuf),
+    Download {
+        edition_id: &'static str,
+        account_id: String,
+        license_key: String,
+    },
 }
 
 impl MaxMind {
-    /// Creates a [`MaxMind`] with no reader.
+    /// Creates a [`MaxMind`] with no readers.
     #[must_use]
-    pub const fn none() -> Self {
-        Self { reader: None }
+    pub fn none() -> Self {
+        Self::default()
     }
 
-    /// Attempts to create a [`MaxMind`] with a MaxMind GeoIP database reader.
+    /// Attempts to create a [`MaxMind`] with MaxMind GeoIP database readers.
+    ///
+    /// Each database is read from the file in its environment variable
+    /// (`MAXMIND_DB`, `MAXMIND_ASN_DB` or `MAXMIND_ANONYMOUS_IP_DB`), or
+    /// otherwise downloaded and cached using `MAXMIND_ACCOUNT_ID` and
+    /// `MAXMIND_LICENSE_KEY`. `MAXMIND_EDITION` selects the `geolite` (default)
+    /// or `geoip` databases.
     ///
-    /// This reads creation and download parameters from environment variables.
+    /// Databases are reloaded every `MAXMIND_REFRESH_INTERVAL_SECS`, or daily
+    /// by default. An interval of 0 disables reloading.
     ///
-    /// If the database could not be created or downloaded, this will make a
-    /// [`MaxMind`] with no reader.
+    /// If a database could not be created or downloaded, lookups will skip
+    /// it until a reload succeeds.
     pub async fn new() -> Self {
-        Self {
-            reader: init_reader()
-                .await
-                .inspect_err(|err| {
-                    warn!("Failed to initialize MaxMind: {err:#}");
-                })
-                .map(Arc::new)
-                .ok(),
+        let mut databases = Databases::default();
+        let refresh_interval = match refresh_interval() {
+            Ok(interval) => interval,
+            Err(err) => {
+                warn!("Failed to configure MaxMind: {err:#}");
+                return Self::none();
+            }
+        };
+
+        match sources() {
+            Ok(sources) => {
+                for (kind, source) in sources {
+                    databases.get_mut(kind).source = Some(source);
+                }
+            }
+            Err(err) => {
+                warn!("Failed to configure MaxMind: {err:#}");
+                return Self::none();
+            }
         }
+
+        for kind in DatabaseKind::ALL {
+            let database = databases.get(kind);
+            let Some(source) = &database.source else {
+                continue;
+            };
+            match load(source, true).await {
+                Ok(reader) => database.reader.store(Some(Arc::new(reader))),
+                Err(err) => {
+                    warn!("Failed to initialize MaxMind {kind:?}: {err:#}");
+                }
+            }
+        }
+
+        let maxmind = Self {
+            databases: Arc::new(databases),
+        };
+        if let Some(interval) = refresh_interval {
+            tokio::spawn(refresh_periodically(
+                Arc::downgrade(&maxmind.databases),
+                interval,
+            ));
+        }
+        maxmind
+    }
+
+    /// Reloads every configured database, downloading fresh copies where
+    /// they're not read from a file.
+    ///
+    /// A database which fails to reload keeps its previous reader.
+    pub async fn refresh(&self) {
+        refresh(&self.databases).await;
+    }
+
+    /// Queries the MaxMind databases for everything known about an IP
+    /// address.
+    pub async fn lookup(&self, ip: impl Into<IpAddr>) -> GeoLookup {
+        let ip = ip.into();
+        let mut result = GeoLookup::default();
+
+        if let Some(reader) = self.databases.location.reader.load_full()
+            && let Ok(Some(city)) = reader.lookup::<geoip2::City>(ip)
+        {
+            result.country =
+                city.country.and_then(|c| c.iso_code).map(str::to_string);
+            result.region = city
+                .subdivisions
+                .and_then(|s| s.into_iter().next_back())
+                .and_then(|s| s.iso_code)
+                .map(str::to_string);
+        }
+
+        if let Some(reader) = self.databases.asn.reader.load_full()
+            && let Ok(Some(asn)) = reader.lookup::<geoip2::Asn>(ip)
+        {
+            result.asn = asn.autonomous_system_number;
+            result.organization =
+                asn.autonomous_system_organization.map(str::to_string);
+        }
+
+        // The anonymous IP database only has records for anonymous networks,
+        // so a missing record means the IP is not one.
+        if let Some(reader) = self.databases.anonymous_ip.reader.load_full()
+            && let Ok(anonymous) = reader.lookup::<geoip2::AnonymousIp>(ip)
+        {
+            let anonymous = anonymous.as_ref();
+            result.is_anonymizer = Some(anonymous.is_some_and(|a| {
+                a.is_anonymous_vpn == Some(true)
+                    || a.is_public_proxy == Some(true)
+                    || a.is_residential_proxy == Some(true)
+                    || a.is_tor_exit_node == Some(true)
+            }));
+            result.is_hosting = Some(
+                anonymous.is_some_and(|a| a.is_hosting_provider == Some(true)),
+            );
+        }
+
+        result
     }
 
     /// Queries the MaxMind database for the ISO country code of an IP address.
@@ -56,7 +297,7 @@ impl MaxMind {
     /// If MaxMind is not configured or the database could not be read, returns
     /// [`None`].
     pub async fn query_country(&self, ip: impl Into<IpAddr>) -> Option<String> {
-        let reader = self.reader.as_ref()?;
+        let reader = self.databases.location.reader.load_full()?;
         reader
             .lookup::<geoip2::Country>(ip.into())
             .ok()?
@@ -65,58 +306,149 @@ impl MaxMind {
     }
 }
 
-/// Creates a [`maxminddb::Reader`] for use in [`MaxMind::reader`].
+fn refresh_interval() -> Result<Option<Duration>> {
+    let Ok(secs) = env_var("MAXMIND_REFRESH_INTERVAL_SECS") else {
+        return Ok(Some(DEFAULT_REFRESH_INTERVAL));
+    };
+    let secs = secs
+        .parse::<u64>()
+        .wrap_err("invalid `MAXMIND_REFRESH_INTERVAL_SECS`")?;
+    Ok((secs > 0).then(|| Duration::from_secs(secs)))
+}
+
+/// Resolves where each database is read from.
+fn sources() -> Result<Vec<(DatabaseKind, Source)>> {
+    let edition = match env_var("MAXMIND_EDITION") {
+        Ok(edition) => edition.parse::<Edition>()?,
+        Err(_) => Edition::default(),
+    };
+    let credentials = env_var("MAXMIND_ACCOUNT_ID")
+        .and_then(|id| Ok((id, env_var("MAXMIND_LICENSE_KEY")?)));
+
+    let mut sources = Vec::new();
+    for kind in DatabaseKind::ALL {
+        if let Ok(db_path) = env_var(kind.path_env_var()) {
+            sources.push((kind, Source::File(db_path.into())));
+            continue;
+        }
+
+        let Some(edition_id) = edition.edition_id(kind) else {
+            continue;
+        };
+        match &credentials {
+            Ok((account_id, license_key)) => sources.push((
+                kind,
+                Source::Download {
+                    edition_id,
+                    account_id: account_id.clone(),
+                    license_key: license_key.clone(),
+                },
+            )),
+            Err(err) => {
+                warn!("MaxMind {kind:?} database is not configured: {err:#}");
+            }
+        }
+    }
+
+    Ok(sources)
+}
+
+async fn refresh_periodically(databases: Weak<Databases>, interval: Duration) {
+    loop {
+        tokio::time::sleep(interval).await;
+
+        // Stop once every `MaxMind` sharing these databases is dropped
+        let Some(databases) = databases.upgrade() else {
+            return;
+        };
+        refresh(&databases).await;
+    }
+}
+
+async fn refresh(databases: &Databases) {
+    for kind in DatabaseKind::ALL {
+        let database = databases.get(kind);
+        let Some(source) = &database.source else {
+            continue;
+        };
+        match load(source, false).await {
+            Ok(reader) => {
+                database.reader.store(Some(Arc::new(reader)));
+                info!("Reloaded MaxMind {kind:?} database");
+            }
+            Err(err) => {
+                warn!("Failed to reload MaxMind {kind:?} database: {err:#}");
+            }
+        }
+    }
+}
+
+/// Creates a [`maxminddb::Reader`] for a database.
+///
+/// If `use_cache` is set, a previously downloaded copy is used instead of
+/// downloading the database again.
 ///
 /// # Errors
 ///
 /// Errors if the database is not present, or could not be downloaded (i.e.
-/// missing license key).
-pub async fn init_reader() -> Result<maxminddb::Reader<Bytes>> {
-    let db = if let Ok(db_path) = env_var("MAXMIND_DB") {
-        info!("Using MaxMind database at {db_path:?}");
+/// invalid license key).
+async fn load(
+    source: &Source,
+    use_cache: bool,
+) -> Result<maxminddb::Reader<Bytes>> {
+    let db = match source {
+        Source::File(db_path) => {
+            info!("Using MaxMind database at {db_path:?}");
 
-        fs::read(&db_path)
-            .await
-            .map(Bytes::from)
-            .wrap_err_with(|| {
+            fs::read(db_path).await.map(Bytes::from).wrap_err_with(|| {
                 eyre!("failed to read database from {db_path:?}")
             })?
-    } else {
-        let account_id = env_var("MAXMIND_ACCOUNT_ID")?;
-        let license_key = env_var("MAXMIND_LICENSE_KEY")?;
-
-        let dirs = directories::ProjectDirs::from(
-            "com.modrinth",
-            "Modrinth",
-            "modrinth-backend",
-        )
-        .wrap_err("failed to get cache directory")?;
-        let cache_dir = dirs.cache_dir();
-        let db_path = cache_dir.join("geolite.mmdb");
-
-        match fs::read(&db_path).await {
-            Ok(db) => {
-                info!("Using cached MaxMind database at {db_path:?}");
-                Bytes::from(db)
-            }
-            Err(err) => {
-                debug!(
-                    "Failed to read MaxMind database from {db_path:?}, will download: {err}"
-                );
+        }
+        Source::Download {
+            edition_id,
+            account_id,
+            license_key,
+        } => {
+            let dirs = directories::ProjectDirs::from(
+                "com.modrinth",
+                "Modrinth",
+                "modrinth-backend",
+            )
+            .wrap_err("failed to get cache directory")?;
+            let cache_dir = dirs.cache_dir();
+            let db_path = cache_dir.join(format!("{edition_id}.mmdb"));
 
-                let db = download(&account_id, &license_key).await?;
+            let cached = if use_cache {
+                fs::read(&db_path).await
+            } else {
+                Err(std::io::ErrorKind::NotFound.into())
+            };
 
-                match write_to_cache(cache_dir, &db_path, &db).await {
-                    Ok(()) => {
-                        info!("Wrote GeoIP database cache to {db_path:?}");
-                    }
-                    Err(err) => warn!(
-                        "Failed to write GeoIP database cache to {db_path:?}: {err:?}",
-                    ),
+            match cached {
+                Ok(db) => {
+                    info!("Using cached MaxMind database at {db_path:?}");
+                    Bytes::from(db)
                 }
+                Err(err) => {
+                    debug!(
+                        "Failed to read MaxMind database from {db_path:?}, will download: {err}"
+                    );
 
-                info!("Downloaded and cached database");
-                db
+                    let db =
+                        download(edition_id, account_id, license_key).await?;
+
+                    match write_to_cache(cache_dir, &db_path, &db).await {
+                        Ok(()) => {
+                            info!("Wrote GeoIP database cache to {db_path:?}");
+                        }
+                        Err(err) => warn!(
+                            "Failed to write GeoIP database cache to {db_path:?}: {err:?}",
+                        ),
+                    }
+
+                    info!("Downloaded and cached database");
+                    db
+                }
             }
         }
     };
@@ -124,10 +456,14 @@ pub async fn init_reader() -> Result<maxminddb::Reader<Bytes>> {
     maxminddb::Reader::from_source(db).wrap_err("failed to create reader")
 }
 
-async fn download(account_id: &str, license_key: &str) -> Result<Bytes> {
-    info!("Downloading MaxMind GeoIP database");
+async fn download(
+    edition_id: &str,
+    account_id: &str,
+    license_key: &str,
+) -> Result<Bytes> {
+    info!("Downloading MaxMind {edition_id} database");
     let db = reqwest::Client::new()
-        .get("https://download.maxmind.com/geoip/databases/GeoLite2-Country/download?suffix=tar.gz")
+        .get(format!("https://download.maxmind.com/geoip/databases/{edition_id}/download?suffix=tar.gz"))
         .basic_auth(account_id, Some(license_key))
         .send()
         .await
@@ -172,3 +508,140 @@ async fn write_to_cache(
         .wrap_err("failed to write to file")?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IP: [u8; 4] = [1, 2, 3, 4];
+
+    /// Encodes a UTF-8 string in the MaxMind DB data format.
+    fn string(value: &str) -> Vec<u8> {
+        let mut bytes = vec![0x40 | value.len() as u8];
+        bytes.extend_from_slice(value.as_bytes());
+        bytes
+    }
+
+    /// Builds an IPv4 database where every IP is in `country`.
+    fn database(country: &str) -> Vec<u8> {
+        // A single node with both records pointing at the start of the data
+        // section, so the first bit of any IP resolves to the same record
+        let mut db = vec![0, 0, 17, 0, 0, 17];
+        db.extend_from_slice(&[0; 16]);
+
+        db.push(0xE1);
+        db.extend(string("country"));
+        db.push(0xE1);
+        db.extend(string("iso_code"));
+        db.extend(string(country));
+
+        db.extend_from_slice(b"\xAB\xCD\xEFMaxMind.com");
+        db.push(0xE9);
+        db.extend(string("node_count"));
+        db.extend_from_slice(&[0xC1, 1]);
+        db.extend(string("record_size"));
+        db.extend_from_slice(&[0xA1, 24]);
+        db.extend(string("ip_version"));
+        db.extend_from_slice(&[0xA1, 4]);
+        db.extend(string("database_type"));
+        db.extend(string("Test-City"));
+        db.extend(string("languages"));
+        db.extend_from_slice(&[0x00, 0x04]);
+        db.extend(string("binary_format_major_version"));
+        db.extend_from_slice(&[0xA1, 2]);
+        db.extend(string("binary_format_minor_version"));
+        db.push(0xA0);
+        db.extend(string("build_epoch"));
+        db.extend_from_slice(&[0x00, 0x02]);
+        db.extend(string("description"));
+        db.push(0xE0);
+        db
+    }
+
+    /// Path of a database file unique to this test process.
+    fn database_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "modrinth-maxmind-{}-{name}.mmdb",
+            std::process::id()
+        ))
+    }
+
+    /// Creates a [`MaxMind`] reading its location database from `path`.
+    async fn maxmind(path: &Path) -> MaxMind {
+        let source = Source::File(path.to_owned());
+        let reader = load(&source, true).await.unwrap();
+        let mut databases = Databases::default();
+        databases.location.source = Some(source);
+        databases.location.reader.store(Some(Arc::new(reader)));
+        MaxMind {
+            databases: Arc::new(databases),
+        }
+    }
+
+    #[tokio::test]
+    async fn refresh_replaces_reader() {
+        let path = database_path("replace");
+        fs::write(&path, database("US")).await.unwrap();
+        let maxmind = maxmind(&path).await;
+        assert_eq!(maxmind.query_country(IP).await.as_deref(), Some("US"));
+
+        fs::write(&path, database("CA")).await.unwrap();
+        // Lookups keep using the old reader until a refresh
+        assert_eq!(maxmind.query_country(IP).await.as_deref(), Some("US"));
+        maxmind.refresh().await;
+        assert_eq!(maxmind.query_country(IP).await.as_deref(), Some("CA"));
+        assert_eq!(maxmind.lookup(IP).await.country.as_deref(), Some("CA"));
+
+        fs::remove_file(&path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_refresh_keeps_previous_reader() {
+        let path = database_path("failed");
+        fs::write(&path, database("US")).await.unwrap();
+        let maxmind = maxmind(&path).await;
+
+        fs::write(&path, b"not a database").await.unwrap();
+        maxmind.refresh().await;
+        assert_eq!(maxmind.query_country(IP).await.as_deref(), Some("US"));
+
+        fs::remove_file(&path).await.unwrap();
+        maxmind.refresh().await;
+        assert_eq!(maxmind.query_country(IP).await.as_deref(), Some("US"));
+    }
+
+    #[tokio::test]
+    async fn refreshes_periodically_until_dropped() {
+        let path = database_path("periodic");
+        fs::write(&path, database("US")).await.unwrap();
+        let maxmind = maxmind(&path).await;
+        let task = tokio::spawn(refresh_periodically(
+            Arc::downgrade(&maxmind.databases),
+            Duration::from_millis(10),
+        ));
+
+        fs::write(&path, database("CA")).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while maxmind.query_country(IP).await.as_deref() != Some("CA") {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("database was not reloaded");
+
+        // Clones share the databases, so the task only stops once every
+        // one of them is dropped
+        let clone = maxmind.clone();
+        drop(maxmind);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!task.is_finished());
+
+        drop(clone);
+        tokio::time::timeout(Duration::from_secs(5), task)
+            .await
+            .expect("refresh task did not stop")
+            .unwrap();
+
+        fs::remove_file(&path).await.unwrap();
+    }
+}
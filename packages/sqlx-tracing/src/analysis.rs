@@ -0,0 +1,352 @@
+//! Aggregation of the spans created by this crate.
+//!
+//! [`QueryAnalysisLayer`] is a [`tracing_subscriber::Layer`] which watches
+//! `sqlx.*` spans as they open and close, so it sees every query run through
+//! a traced [`Pool`](crate::Pool), connection or transaction without
+//! changing how they're executed.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use prometheus::{
+    HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry,
+    exponential_buckets,
+};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Subscriber, warn};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+const STATEMENT_FIELD: &str = "db.query.text";
+const ROUTE_FIELD: &str = "http.route";
+const SPAN_PREFIX: &str = "sqlx.";
+
+/// Statements are truncated to this many characters when used as metric
+/// labels.
+const MAX_LABEL_LEN: usize = 200;
+
+/// A [`tracing_subscriber::Layer`] which analyses the queries traced by this
+/// crate.
+///
+/// - Queries slower than [`with_slow_query_threshold`] are logged with their
+///   normalized statement.
+/// - A normalized statement running [`with_n_plus_one_threshold`] times
+///   inside one parent span is logged as a possible N+1 query.
+/// - Query latencies are recorded per statement, and query counts and total
+///   query time per route, in [`QueryMetrics`]. Routes are taken from the
+///   nearest ancestor span with an `http.route` field.
+///
+/// [`with_slow_query_threshold`]: Self::with_slow_query_threshold
+/// [`with_n_plus_one_threshold`]: Self::with_n_plus_one_threshold
+#[derive(Debug)]
+pub struct QueryAnalysisLayer {
+    slow_query_threshold: Option<Duration>,
+    n_plus_one_threshold: Option<usize>,
+    metrics: QueryMetrics,
+}
+
+impl QueryAnalysisLayer {
+    /// Create a layer which only records metrics.
+    pub fn new() -> prometheus::Result<Self> {
+        Ok(Self {
+            slow_query_threshold: None,
+            n_plus_one_threshold: None,
+            metrics: QueryMetrics::new()?,
+        })
+    }
+
+    /// Log queries which take at least `threshold`.
+    pub fn with_slow_query_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_query_threshold = Some(threshold);
+        self
+    }
+
+    /// Log statements which run at least `threshold` times in one parent
+    /// span.
+    pub fn with_n_plus_one_threshold(mut self, threshold: usize) -> Self {
+        self.n_plus_one_threshold = Some(threshold);
+        self
+    }
+
+    /// The metrics recorded by this layer, to be registered once the
+    /// application's registry exists.
+    pub fn metrics(&self) -> QueryMetrics {
+        self.metrics.clone()
+    }
+}
+
+/// Prometheus metrics recorded by a [`QueryAnalysisLayer`].
+#[derive(Debug, Clone)]
+pub struct QueryMetrics {
+    statement_duration: HistogramVec,
+    n_plus_one: IntCounterVec,
+    route_queries: HistogramVec,
+    route_query_duration: HistogramVec,
+}
+
+impl QueryMetrics {
+    fn new() -> prometheus::Result<Self> {
+        Ok(Self {
+            statement_duration: HistogramVec::new(
+                HistogramOpts::new(
+                    "sqlx_query_duration_seconds",
+                    "Duration of SQL queries by normalized statement",
+                ),
+                &["statement"],
+            )?,
+            n_plus_one: IntCounterVec::new(
+                Opts::new(
+                    "sqlx_query_n_plus_one_total",
+                    "Number of parent spans which repeated a normalized \
+                     statement past the N+1 threshold",
+                ),
+                &["statement"],
+            )?,
+            route_queries: HistogramVec::new(
+                HistogramOpts::new(
+                    "sqlx_route_queries",
+                    "Number of SQL queries run per request by route",
+                )
+                .buckets(exponential_buckets(1.0, 2.0, 10)?),
+                &["route"],
+            )?,
+            route_query_duration: HistogramVec::new(
+                HistogramOpts::new(
+                    "sqlx_route_query_duration_seconds",
+                    "Total duration of SQL queries run per request by route",
+                ),
+                &["route"],
+            )?,
+        })
+    }
+
+    /// Register every metric with `registry`.
+    pub fn register(&self, registry: &Registry) -> prometheus::Result<()> {
+        registry.register(Box::new(self.statement_duration.clone()))?;
+        registry.register(Box::new(self.n_plus_one.clone()))?;
+        registry.register(Box::new(self.route_queries.clone()))?;
+        registry.register(Box::new(self.route_query_duration.clone()))?;
+        Ok(())
+    }
+}
+
+/// Normalize a SQL statement so that executions of the same query compare
+/// equal.
+///
+/// Runs of whitespace are collapsed into one space, and string and numeric
+/// literals are replaced with `?`. Bind parameters like `$1` are kept.
+pub fn normalize_statement(statement: &str) -> String {
+    let mut normalized = String::with_capacity(statement.len());
+    let mut chars = statement.chars().peekable();
+    let mut pending_space = false;
+
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            pending_space = !normalized.is_empty();
+            continue;
+        }
+        if pending_space {
+            normalized.push(' ');
+            pending_space = false;
+        }
+
+        let follows_word = normalized
+            .chars()
+            .next_back()
+            .is_some_and(|prev| prev.is_alphanumeric() || "_$".contains(prev));
+
+        match c {
+            '\'' => {
+                // `''` is an escaped quote inside the literal
+                while let Some(c) = chars.next() {
+                    if c == '\'' && chars.next_if_eq(&'\'').is_none() {
+                        break;
+                    }
+                }
+                normalized.push('?');
+            }
+            c if c.is_ascii_digit() && !follows_word => {
+                while chars
+                    .next_if(|c| c.is_ascii_alphanumeric() || *c == '.')
+                    .is_some()
+                {}
+                normalized.push('?');
+            }
+            c => normalized.push(c),
+        }
+    }
+
+    normalized
+}
+
+fn statement_label(statement: &str) -> &str {
+    match statement.char_indices().nth(MAX_LABEL_LEN) {
+        Some((end, _)) => &statement[..end],
+        None => statement,
+    }
+}
+
+/// Stored on `sqlx.*` spans which run a statement.
+struct QuerySpan {
+    statement: String,
+    started: Instant,
+}
+
+/// Stored on the parents of `sqlx.*` spans.
+struct StatementCounts(HashMap<String, usize>);
+
+/// Stored on spans with an `http.route` field.
+struct RouteStats {
+    route: String,
+    queries: usize,
+    query_duration: Duration,
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    statement: Option<String>,
+    route: Option<String>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            STATEMENT_FIELD => self.statement = Some(value.to_owned()),
+            ROUTE_FIELD => self.route = Some(value.to_owned()),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            STATEMENT_FIELD => self.statement = Some(format!("{value:?}")),
+            ROUTE_FIELD => self.route = Some(format!("{value:?}")),
+            _ => {}
+        }
+    }
+}
+
+impl<S> Layer<S> for QueryAnalysisLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(
+        &self,
+        attrs: &Attributes<'_>,
+        id: &Id,
+        ctx: Context<'_, S>,
+    ) {
+        let mut fields = FieldVisitor::default();
+        attrs.record(&mut fields);
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        if let Some(route) = fields.route {
+            span.extensions_mut().insert(RouteStats {
+                route,
+                queries: 0,
+                query_duration: Duration::ZERO,
+            });
+        }
+
+        let Some(statement) = fields.statement else {
+            return;
+        };
+        if !attrs.metadata().name().starts_with(SPAN_PREFIX) {
+            return;
+        }
+        let statement = normalize_statement(&statement);
+
+        if let Some(threshold) = self.n_plus_one_threshold
+            && let Some(parent) = span.parent()
+        {
+            let count = {
+                let mut extensions = parent.extensions_mut();
+                match extensions.get_mut::<StatementCounts>() {
+                    Some(counts) => {
+                        let count =
+                            counts.0.entry(statement.clone()).or_default();
+                        *count += 1;
+                        *count
+                    }
+                    None => {
+                        extensions.insert(StatementCounts(HashMap::from([(
+                            statement.clone(),
+                            1,
+                        )])));
+                        1
+                    }
+                }
+            };
+
+            // Only report each statement once per parent
+            if count == threshold {
+                self.metrics
+                    .n_plus_one
+                    .with_label_values(&[statement_label(&statement)])
+                    .inc();
+                warn!(
+                    %statement,
+                    parent = parent.name(),
+                    count,
+                    "possible N+1 query"
+                );
+            }
+        }
+
+        span.extensions_mut().insert(QuerySpan {
+            statement,
+            started: Instant::now(),
+        });
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+
+        if let Some(stats) = span.extensions().get::<RouteStats>() {
+            self.metrics
+                .route_queries
+                .with_label_values(&[&stats.route])
+                .observe(stats.queries as f64);
+            self.metrics
+                .route_query_duration
+                .with_label_values(&[&stats.route])
+                .observe(stats.query_duration.as_secs_f64());
+        }
+
+        let Some(query) = span.extensions_mut().remove::<QuerySpan>() else {
+            return;
+        };
+        let elapsed = query.started.elapsed();
+
+        self.metrics
+            .statement_duration
+            .with_label_values(&[statement_label(&query.statement)])
+            .observe(elapsed.as_secs_f64());
+
+        let route = span.scope().skip(1).find_map(|ancestor| {
+            let mut extensions = ancestor.extensions_mut();
+            let stats = extensions.get_mut::<RouteStats>()?;
+            stats.queries += 1;
+            stats.query_duration += elapsed;
+            Some(stats.route.clone())
+        });
+
+        if let Some(threshold) = self.slow_query_threshold
+            && elapsed >= threshold
+        {
+            warn!(
+                statement = %query.statement,
+                route = route.as_deref().unwrap_or_default(),
+                elapsed_ms = elapsed.as_millis() as u64,
+                "slow query"
+            );
+        }
+    }
+}
@@ -13,6 +13,9 @@ mod transaction;
 
 pub use sqlx::Executor;
 
+#[cfg(feature = "analysis")]
+pub mod analysis;
+
 #[cfg(feature = "postgres")]
 pub mod postgres;
 
@@ -0,0 +1,58 @@
+#![cfg(all(feature = "analysis", feature = "sqlite"))]
+
+use sqlx_tracing::analysis::{QueryAnalysisLayer, normalize_statement};
+use tracing::Instrument;
+use tracing_subscriber::layer::SubscriberExt;
+
+#[test]
+fn normalizes_statements() {
+    assert_eq!(
+        normalize_statement(
+            "
+            SELECT id, title FROM mods
+            WHERE id = 5 AND title = 'it''s' AND downloads > 1.5
+            "
+        ),
+        "SELECT id, title FROM mods WHERE id = ? AND title = ? AND downloads > ?"
+    );
+    assert_eq!(
+        normalize_statement("SELECT v2 FROM t WHERE id = ANY($1)"),
+        "SELECT v2 FROM t WHERE id = ANY($1)"
+    );
+}
+
+#[tokio::test]
+async fn records_repeated_statements() {
+    let layer = QueryAnalysisLayer::new()
+        .unwrap()
+        .with_n_plus_one_threshold(3);
+    let registry = prometheus::Registry::new();
+    layer.metrics().register(&registry).unwrap();
+
+    let subscriber = tracing_subscriber::registry().with(layer);
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let pool = sqlx::SqlitePool::connect(":memory:").await.unwrap();
+    let pool = sqlx_tracing::Pool::from(pool);
+
+    async {
+        for id in 0..4 {
+            let sql = format!("SELECT {id}");
+            let _: i32 =
+                sqlx::query_scalar(&sql).fetch_one(&pool).await.unwrap();
+        }
+    }
+    .instrument(tracing::info_span!("get_many"))
+    .await;
+
+    let metrics = prometheus::TextEncoder::new()
+        .encode_to_string(&registry.gather())
+        .unwrap();
+    assert!(metrics.contains(
+        "sqlx_query_duration_seconds_count{statement=\"SELECT ?\"} 4"
+    ));
+    assert!(
+        metrics
+            .contains("sqlx_query_n_plus_one_total{statement=\"SELECT ?\"} 1")
+    );
+}
@@ -246,6 +246,17 @@ export type OrganizationMember = {
 	accepted: boolean
 	payouts_split: number
 	ordering: number
+	role_id: ModrinthId | null
+}
+
+export type OrganizationRole = {
+	id: ModrinthId
+	organization_id: ModrinthId
+	name: string
+	permissions: TeamMemberPermissions
+	organization_permissions: OrganizationPermissions
+	project_overrides: Record<ModrinthId, TeamMemberPermissions>
+	created: string
 }
 
 export type Collection = {
@@ -373,6 +384,8 @@ export enum TeamMemberPermission {
 	DELETE_PROJECT = 1 << 7,
 	VIEW_ANALYTICS = 1 << 8,
 	VIEW_PAYOUTS = 1 << 9,
+	MANAGE_WEBHOOKS = 1 << 10,
+	VIEW_AUDIT_LOG = 1 << 11,
 }
 
 export type TeamMemberPermissions = number
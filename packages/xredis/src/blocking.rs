@@ -5,6 +5,7 @@ use prometheus::Registry;
 
 use super::RedisPool;
 use super::config::{RedisConfig, RedisTopology};
+use super::memory::MemoryStore;
 use super::metrics::{
     LogicalPoolStatus, LogicalPoolStatusProvider,
     register_blocking_pool_metrics,
@@ -24,6 +25,7 @@ pub(super) struct RedisBlockingPool {
 enum RedisBlockingPoolInner {
     Standalone(deadpool_redis::Pool),
     Cluster(deadpool_redis::cluster::Pool),
+    Memory(MemoryStore),
 }
 
 impl RedisBlockingPool {
@@ -67,6 +69,9 @@ impl RedisBlockingPool {
                 retain_cluster_pool(pool.clone());
                 RedisBlockingPoolInner::Cluster(pool)
             }
+            RedisTopology::Memory => {
+                RedisBlockingPoolInner::Memory(MemoryStore::shared())
+            }
         };
 
         Ok(Self { inner })
@@ -106,6 +111,9 @@ impl RedisBlockingPool {
                         "reading from clustered Redis blocking queue",
                     )?
                 }
+                RedisBlockingPoolInner::Memory(store) => {
+                    return Ok(store.brpop(key, timeout).await);
+                }
             };
 
         Ok(response.map(|(key, value)| [key, value]))
@@ -121,6 +129,9 @@ impl LogicalPoolStatusProvider for RedisBlockingPool {
             RedisBlockingPoolInner::Cluster(pool) => {
                 LogicalPoolStatus::from_deadpool(pool.status())
             }
+            RedisBlockingPoolInner::Memory(_) => {
+                LogicalPoolStatus::shared_multiplexed()
+            }
         }
     }
 }
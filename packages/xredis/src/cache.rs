@@ -10,6 +10,7 @@ use chrono::{TimeZone, Utc};
 use dashmap::DashMap;
 use eyre::{Result, WrapErr, eyre};
 use futures::stream::{FuturesUnordered, StreamExt};
+use prometheus::Registry;
 use redis::aio::ConnectionLike;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
@@ -17,12 +18,17 @@ use thiserror::Error;
 use tokio::time::{Instant, timeout_at};
 use tracing::{Instrument, info_span};
 
+use super::CacheLockingStrategy;
 use super::commands;
-use super::connection::RoutableConnection;
+use super::connection::{RedisBackend, RoutableConnection};
 use super::key::KeyBuilder;
+use super::metrics::CacheLockMetrics;
 
+mod l1;
 mod locking;
 
+pub use l1::L1CacheSettings;
+pub(crate) use l1::{INVALIDATION_CHANNEL, L1Cache};
 use locking::{
     LockAcquisition, LockCoordinator, LockWaiter, WAIT_TIMEOUT, normalize_key,
 };
@@ -106,6 +112,7 @@ pub struct CacheSettings {
     pub compression_level: i32,
     pub compression_threshold_bytes: usize,
     pub compression_min_savings_ratio: f64,
+    pub l1: L1CacheSettings,
 }
 
 impl CacheSettings {
@@ -164,6 +171,23 @@ impl CacheSettings {
         }
     }
 
+    /// Re-frames an encoded value without compression, so that decoding it
+    /// again only has to deserialize it.
+    fn decompress_value(&self, value: &[u8]) -> Option<Vec<u8>> {
+        let (codec, payload) = value.split_first()?;
+        match Codec::try_from(*codec).ok()? {
+            Codec::Raw => Some(value.to_vec()),
+            Codec::Lz4 => {
+                let payload =
+                    lz4_flex::block::decompress_size_prepended(payload).ok()?;
+                let mut decompressed = Vec::with_capacity(payload.len() + 1);
+                decompressed.push(Codec::Raw as u8);
+                decompressed.extend(payload);
+                Some(decompressed)
+            }
+        }
+    }
+
     fn expiries(&self, namespace: &str) -> (i64, i64) {
         match namespace
             .split_once(':')
@@ -183,15 +207,103 @@ pub struct CacheManager {
     key_builder: KeyBuilder,
     settings: CacheSettings,
     locking: LockCoordinator,
+    l1: Option<L1Cache>,
 }
 
 impl CacheManager {
-    pub fn new(key_builder: KeyBuilder, settings: CacheSettings) -> Self {
-        Self {
-            locking: LockCoordinator::new(),
+    pub(crate) fn new(
+        key_builder: KeyBuilder,
+        settings: CacheSettings,
+        locking_strategy: CacheLockingStrategy,
+        backend: RedisBackend,
+    ) -> Result<Self> {
+        let metrics = CacheLockMetrics::new()
+            .wrap_err("creating Redis cache lock metrics")?;
+        let l1 = L1Cache::new(settings.l1.clone())
+            .wrap_err("creating Redis L1 cache")?;
+
+        Ok(Self {
+            locking: LockCoordinator::new(
+                locking_strategy,
+                backend,
+                key_builder.clone(),
+                metrics,
+            ),
             key_builder,
             settings,
+            l1,
+        })
+    }
+
+    pub(crate) fn register_metrics(&self, registry: &Registry) -> Result<()> {
+        self.locking
+            .metrics()
+            .register(registry)
+            .wrap_err("registering Redis cache lock metrics")?;
+        if let Some(l1) = &self.l1 {
+            l1.metrics()
+                .register(registry)
+                .wrap_err("registering Redis L1 cache metrics")?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn l1(&self) -> Option<&L1Cache> {
+        self.l1.as_ref()
+    }
+
+    /// Reads `keys` from the L1 cache, fetching the rest with `fetch` and
+    /// caching what `to_l1` returns for them. Reads which must see the
+    /// primary bypass the L1 cache entirely.
+    async fn get_many_through_l1<F, Fut>(
+        &self,
+        namespace: &str,
+        keys: Vec<String>,
+        routing: CacheReadRouting,
+        to_l1: impl Fn(&[u8]) -> Option<Vec<u8>>,
+        fetch: F,
+    ) -> Result<Vec<Option<Vec<u8>>>>
+    where
+        F: FnOnce(Vec<String>) -> Fut,
+        Fut: Future<Output = Result<Vec<Option<Vec<u8>>>>>,
+    {
+        let l1 = match (&self.l1, routing) {
+            (Some(l1), CacheReadRouting::ReplicaOptional)
+                if l1.caches(namespace) =>
+            {
+                l1
+            }
+            _ => return fetch(keys).await,
+        };
+
+        let mut values = keys
+            .iter()
+            .map(|key| l1.get(namespace, key).map(|value| value.to_vec()))
+            .collect::<Vec<_>>();
+        let missing = values
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| value.is_none())
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            return Ok(values);
+        }
+
+        let generation = l1.generation();
+        let fetched =
+            fetch(missing.iter().map(|&index| keys[index].clone()).collect())
+                .await?;
+        for (index, value) in missing.into_iter().zip(fetched) {
+            if let Some(value) = &value
+                && let Some(cached) = to_l1(value)
+            {
+                l1.insert(namespace, keys[index].clone(), cached, generation);
+            }
+            values[index] = value;
         }
+
+        Ok(values)
     }
 
     pub fn settings(&self) -> &CacheSettings {
@@ -369,31 +481,22 @@ impl CacheManager {
                                         .entity(slug_namespace, logical_key)
                                 })
                                 .collect::<Vec<_>>();
-                            let mut connection =
-                                provider.connect().await.wrap_err(
-                                    "connecting to Redis for slug lookup",
-                                )?;
-                            let values = match routing {
-                                CacheReadRouting::ReplicaOptional => {
-                                    commands::get_many_strings(
-                                        &mut connection,
-                                        &keys,
-                                    )
-                                    .await
-                                }
-                                CacheReadRouting::Primary => {
-                                    commands::get_many_strings_primary(
-                                        &mut connection,
-                                        &keys,
-                                    )
-                                    .await
-                                }
-                            }
-                            .wrap_err("fetching Redis cache slug values")?;
+                            let values = self
+                                .get_many_through_l1(
+                                    slug_namespace,
+                                    keys,
+                                    routing,
+                                    |value| Some(value.to_vec()),
+                                    |keys| fetch_slugs(provider, keys, routing),
+                                )
+                                .await?;
                             eyre::Ok(
                                 values
                                     .into_iter()
                                     .flatten()
+                                    .filter_map(|value| {
+                                        String::from_utf8(value).ok()
+                                    })
                                     .collect::<Vec<_>>(),
                             )
                         }
@@ -416,21 +519,16 @@ impl CacheManager {
                         .map(|key| self.key_builder.entity(namespace, key))
                         .collect::<Vec<_>>();
 
-                    let mut connection = provider
-                        .connect()
-                        .await
-                        .wrap_err("connecting to Redis for cache lookup")?;
                     let mut cached_values = HashMap::new();
-                    let values = match routing {
-                        CacheReadRouting::ReplicaOptional => {
-                            commands::get_many(&mut connection, &keys).await
-                        }
-                        CacheReadRouting::Primary => {
-                            commands::get_many_primary(&mut connection, &keys)
-                                .await
-                        }
-                    }
-                    .wrap_err("fetching Redis cache values")?;
+                    let values = self
+                        .get_many_through_l1(
+                            namespace,
+                            keys,
+                            routing,
+                            |value| self.settings.decompress_value(value),
+                            |keys| fetch_values(provider, keys, routing),
+                        )
+                        .await?;
                     for value in values {
                         if let Some(value) = value.and_then(|value| {
                             self.settings
@@ -488,8 +586,7 @@ impl CacheManager {
                     continue;
                 }
 
-                let lock_key = self.key_builder.entity(namespace, &key);
-                let acquisition = self.locking.acquire(lock_key);
+                let acquisition = self.locking.acquire(namespace, &key).await;
 
                 match acquisition {
                     LockAcquisition::Owned(guard) => {
@@ -522,6 +619,8 @@ impl CacheManager {
                     .collect::<Vec<_>>();
 
                 let fill_deadline = Instant::now() + FILL_TIMEOUT;
+                let l1_generation =
+                    self.l1.as_ref().map_or(0, L1Cache::generation);
 
                 let values = timeout_at(fill_deadline, closure(fetch_ids))
                     .await
@@ -562,6 +661,18 @@ impl CacheManager {
                     )
                     .await
                     .wrap_err("writing Redis cache value")?;
+                    if let Some(l1) = &self.l1
+                        && l1.caches(namespace)
+                        && let Some(decompressed) =
+                            self.settings.decompress_value(encoded)
+                    {
+                        l1.insert(
+                            namespace,
+                            redis_key,
+                            decompressed,
+                            l1_generation,
+                        );
+                    }
                     if let Some(slug) = slug
                         && let Some(slug_namespace) = slug_namespace
                     {
@@ -579,6 +690,14 @@ impl CacheManager {
                         )
                         .await
                         .wrap_err("writing Redis cache slug")?;
+                        if let Some(l1) = &self.l1 {
+                            l1.insert(
+                                slug_namespace,
+                                slug_key,
+                                canonical_key.into_bytes(),
+                                l1_generation,
+                            );
+                        }
                     }
                 }
 
@@ -644,6 +763,57 @@ impl CacheManager {
     }
 }
 
+async fn fetch_slugs<P>(
+    provider: &P,
+    keys: Vec<String>,
+    routing: CacheReadRouting,
+) -> Result<Vec<Option<Vec<u8>>>>
+where
+    P: ConnectionProvider,
+{
+    let mut connection = provider
+        .connect()
+        .await
+        .wrap_err("connecting to Redis for slug lookup")?;
+    let values = match routing {
+        CacheReadRouting::ReplicaOptional => {
+            commands::get_many_strings(&mut connection, &keys).await
+        }
+        CacheReadRouting::Primary => {
+            commands::get_many_strings_primary(&mut connection, &keys).await
+        }
+    }
+    .wrap_err("fetching Redis cache slug values")?;
+
+    Ok(values
+        .into_iter()
+        .map(|value| value.map(String::into_bytes))
+        .collect())
+}
+
+async fn fetch_values<P>(
+    provider: &P,
+    keys: Vec<String>,
+    routing: CacheReadRouting,
+) -> Result<Vec<Option<Vec<u8>>>>
+where
+    P: ConnectionProvider,
+{
+    let mut connection = provider
+        .connect()
+        .await
+        .wrap_err("connecting to Redis for cache lookup")?;
+    match routing {
+        CacheReadRouting::ReplicaOptional => {
+            commands::get_many(&mut connection, &keys).await
+        }
+        CacheReadRouting::Primary => {
+            commands::get_many_primary(&mut connection, &keys).await
+        }
+    }
+    .wrap_err("fetching Redis cache values")
+}
+
 fn remove_resolved_ids<I, T, K, S>(
     ids: &DashMap<String, I>,
     value: &RedisValue<T, K, S>,
@@ -736,7 +906,7 @@ fn lock_timeout_error(
     locks_waiting: usize,
 ) -> eyre::Report {
     eyre!(
-        "timeout waiting on local Redis cache lock ({locks_released}/{locks_waiting} released)"
+        "timeout waiting on Redis cache lock ({locks_released}/{locks_waiting} released)"
     )
 }
 
@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use dashmap::DashMap;
+use dashmap::mapref::entry::Entry;
+use eyre::{Result, WrapErr};
+use redis::aio::ConnectionLike;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::commands;
+use crate::metrics::CacheL1Metrics;
+
+/// Carries postcard-encoded lists of Redis keys to evict from every node's
+/// L1 cache.
+pub(crate) const INVALIDATION_CHANNEL: &str = "xredis_l1_invalidations";
+
+/// The share of a full cache evicted at once, so that a burst of inserts
+/// doesn't rescan the map for every entry.
+const EVICTION_DIVISOR: usize = 8;
+
+/// Configures the in-process L1 cache tier in front of Redis.
+///
+/// Only namespaces with a TTL are cached. TTLs are looked up by the full
+/// namespace, then by the part before the first `:`, like Redis expiries.
+#[derive(Debug, Clone, Default)]
+pub struct L1CacheSettings {
+    pub max_entries: usize,
+    pub namespace_ttls: HashMap<String, Duration>,
+}
+
+impl L1CacheSettings {
+    fn ttl(&self, namespace: &str) -> Option<Duration> {
+        self.namespace_ttls.get(namespace).copied().or_else(|| {
+            namespace
+                .split_once(':')
+                .and_then(|(prefix, _)| self.namespace_ttls.get(prefix))
+                .copied()
+        })
+    }
+}
+
+struct L1Entry {
+    /// `None` for a key which was invalidated, kept so that a read which
+    /// started before the invalidation can't cache what it read.
+    value: Option<Arc<[u8]>>,
+    /// The generation of the read which produced the value, or of the
+    /// invalidation.
+    generation: u64,
+    expires_at: Instant,
+}
+
+/// A bounded map of Redis keys to their values in this process.
+///
+/// Values are kept decompressed, so a hit skips both the Redis round-trip and
+/// LZ4. Entries are invalidated when keys are deleted through any node, and
+/// otherwise live for at most their namespace's TTL, which bounds staleness
+/// if an invalidation is missed while Pub/Sub reconnects.
+///
+/// Every invalidation starts a new generation. Reads from Redis note the
+/// generation they started in, and their values aren't cached if one of
+/// their keys has been invalidated or rewritten since.
+#[derive(Clone)]
+pub(crate) struct L1Cache {
+    entries: Arc<DashMap<String, L1Entry>>,
+    generation: Arc<AtomicU64>,
+    /// How long invalidated keys are remembered: long enough to outlast any
+    /// value a racing read could otherwise cache.
+    invalidation_ttl: Duration,
+    settings: Arc<L1CacheSettings>,
+    metrics: CacheL1Metrics,
+}
+
+impl L1Cache {
+    pub(crate) fn new(settings: L1CacheSettings) -> Result<Option<Self>> {
+        if settings.max_entries == 0 || settings.namespace_ttls.is_empty() {
+            return Ok(None);
+        }
+
+        let metrics = CacheL1Metrics::new()
+            .wrap_err("creating Redis L1 cache metrics")?;
+
+        Ok(Some(Self {
+            entries: Arc::new(DashMap::with_capacity(settings.max_entries)),
+            generation: Arc::new(AtomicU64::new(0)),
+            invalidation_ttl: settings
+                .namespace_ttls
+                .values()
+                .max()
+                .copied()
+                .unwrap_or_default(),
+            settings: Arc::new(settings),
+            metrics,
+        }))
+    }
+
+    pub(crate) fn metrics(&self) -> &CacheL1Metrics {
+        &self.metrics
+    }
+
+    pub(crate) fn caches(&self, namespace: &str) -> bool {
+        self.settings.ttl(namespace).is_some()
+    }
+
+    /// The current generation, to pass to [`Self::insert`] for values read
+    /// from Redis after this call.
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn get(&self, namespace: &str, key: &str) -> Option<Arc<[u8]>> {
+        let value = self
+            .entries
+            .get(key)
+            .filter(|entry| entry.expires_at > Instant::now())
+            .and_then(|entry| entry.value.clone());
+
+        if value.is_some() {
+            self.metrics.hit(namespace);
+        } else {
+            self.metrics.miss(namespace);
+        }
+        value
+    }
+
+    /// Caches a value read from Redis in `generation`, unless the key was
+    /// invalidated or cached by a newer read since.
+    pub(crate) fn insert(
+        &self,
+        namespace: &str,
+        key: String,
+        value: impl Into<Arc<[u8]>>,
+        generation: u64,
+    ) {
+        let Some(ttl) = self.settings.ttl(namespace) else {
+            return;
+        };
+
+        if self.entries.len() >= self.settings.max_entries
+            && !self.entries.contains_key(&key)
+        {
+            self.evict();
+        }
+
+        let entry = L1Entry {
+            value: Some(value.into()),
+            generation,
+            expires_at: Instant::now() + ttl,
+        };
+        match self.entries.entry(key) {
+            Entry::Occupied(mut occupied) => {
+                if occupied.get().generation <= generation {
+                    occupied.insert(entry);
+                }
+            }
+            Entry::Vacant(vacant) => {
+                vacant.insert(entry);
+            }
+        }
+    }
+
+    pub(crate) fn invalidate(&self, keys: &[String]) {
+        let generation = self.generation.fetch_add(1, Ordering::AcqRel) + 1;
+        let expires_at = Instant::now() + self.invalidation_ttl;
+
+        let mut removed = 0;
+        for key in keys {
+            let previous = self.entries.insert(
+                key.clone(),
+                L1Entry {
+                    value: None,
+                    generation,
+                    expires_at,
+                },
+            );
+            if previous.is_some_and(|entry| entry.value.is_some()) {
+                removed += 1;
+            }
+        }
+        self.metrics.invalidated(removed);
+    }
+
+    /// Invalidates `keys` here, then tells every other node to do the same.
+    pub(crate) async fn broadcast_invalidation<C>(
+        &self,
+        connection: &mut C,
+        keys: &[String],
+    ) where
+        C: ConnectionLike,
+    {
+        if keys.is_empty() {
+            return;
+        }
+
+        self.invalidate(keys);
+
+        let message = match postcard::to_allocvec(keys) {
+            Ok(message) => message,
+            Err(error) => {
+                warn!("failed to encode L1 cache invalidation: {error:?}");
+                return;
+            }
+        };
+        // The deletion already happened, so a failed broadcast is only
+        // logged; other nodes catch up once their entries expire.
+        if let Err(error) =
+            commands::publish(connection, INVALIDATION_CHANNEL, message).await
+        {
+            warn!("failed to broadcast L1 cache invalidation: {error:?}");
+        }
+    }
+
+    /// Applies invalidations broadcast by other nodes until the subscription
+    /// closes.
+    pub(crate) async fn listen(self, mut receiver: mpsc::Receiver<Vec<u8>>) {
+        while let Some(message) = receiver.recv().await {
+            match postcard::from_bytes::<Vec<String>>(&message) {
+                Ok(keys) => self.invalidate(&keys),
+                Err(error) => {
+                    warn!("received invalid L1 cache invalidation: {error:?}");
+                }
+            }
+        }
+    }
+
+    /// Drops expired entries, then an arbitrary share of the rest if the
+    /// cache is still full.
+    fn evict(&self) {
+        let now = Instant::now();
+        self.entries.retain(|_, entry| entry.expires_at > now);
+
+        if self.entries.len() < self.settings.max_entries {
+            return;
+        }
+
+        let count = (self.settings.max_entries / EVICTION_DIVISOR).max(1);
+        let keys = self
+            .entries
+            .iter()
+            .take(count)
+            .map(|entry| entry.key().clone())
+            .collect::<Vec<_>>();
+        for key in &keys {
+            self.entries.remove(key);
+        }
+        self.metrics.evicted(keys.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn l1_cache() -> L1Cache {
+        L1Cache::new(L1CacheSettings {
+            max_entries: 16,
+            namespace_ttls: HashMap::from([(
+                "projects".to_string(),
+                Duration::from_secs(60),
+            )]),
+        })
+        .unwrap()
+        .unwrap()
+    }
+
+    #[test]
+    fn read_started_before_invalidation_is_not_cached() {
+        let l1 = l1_cache();
+        let key = "projects:1".to_string();
+
+        // A read fetches the old value from Redis, then the key is rewritten
+        // and invalidated before the read caches it
+        let generation = l1.generation();
+        l1.invalidate(std::slice::from_ref(&key));
+        l1.insert("projects", key.clone(), b"stale".to_vec(), generation);
+        assert!(l1.get("projects", &key).is_none());
+
+        // A read which started after the invalidation may cache its value
+        let generation = l1.generation();
+        l1.insert("projects", key.clone(), b"fresh".to_vec(), generation);
+        assert_eq!(l1.get("projects", &key).as_deref(), Some(&b"fresh"[..]));
+    }
+
+    #[test]
+    fn older_read_does_not_replace_newer_value() {
+        let l1 = l1_cache();
+        let key = "projects:1".to_string();
+
+        let old_generation = l1.generation();
+        l1.invalidate(std::slice::from_ref(&key));
+        let new_generation = l1.generation();
+
+        l1.insert("projects", key.clone(), b"fresh".to_vec(), new_generation);
+        l1.insert("projects", key.clone(), b"stale".to_vec(), old_generation);
+        assert_eq!(l1.get("projects", &key).as_deref(), Some(&b"fresh"[..]));
+    }
+}
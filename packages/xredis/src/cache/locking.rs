@@ -1,8 +1,16 @@
+mod distributed;
 mod local;
 
 use std::time::Duration;
 
-pub(super) use self::local::{LockAcquisition, LockCoordinator, LockWaiter};
+use eyre::Result;
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::CacheLockingStrategy;
+use crate::connection::RedisBackend;
+use crate::key::KeyBuilder;
+use crate::metrics::CacheLockMetrics;
 
 pub(super) const WAIT_TIMEOUT: Duration = Duration::from_secs(5);
 
@@ -15,3 +23,146 @@ pub(super) fn normalize_key(key: &str, case_sensitive: bool) -> String {
         key.to_lowercase()
     }
 }
+
+/// Coordinates which caller fills a missing cache key.
+///
+/// Callers in one process always coalesce on the local lock first. With the
+/// distributed strategy, the local owner then competes for a Redis lock, so
+/// that only one task per process polls Redis while another replica fills.
+#[derive(Clone)]
+pub(super) struct LockCoordinator {
+    local: local::LockCoordinator,
+    distributed: Option<distributed::LockCoordinator>,
+    key_builder: KeyBuilder,
+    metrics: CacheLockMetrics,
+}
+
+impl LockCoordinator {
+    pub(super) fn new(
+        strategy: CacheLockingStrategy,
+        backend: RedisBackend,
+        key_builder: KeyBuilder,
+        metrics: CacheLockMetrics,
+    ) -> Self {
+        let distributed = match strategy {
+            CacheLockingStrategy::Local => None,
+            CacheLockingStrategy::Distributed => {
+                Some(distributed::LockCoordinator::new(
+                    backend,
+                    key_builder.clone(),
+                    metrics.clone(),
+                ))
+            }
+        };
+
+        Self {
+            local: local::LockCoordinator::new(),
+            distributed,
+            key_builder,
+            metrics,
+        }
+    }
+
+    pub(super) fn metrics(&self) -> &CacheLockMetrics {
+        &self.metrics
+    }
+
+    pub(super) async fn acquire(
+        &self,
+        namespace: &str,
+        key: &str,
+    ) -> LockAcquisition {
+        let local_key = self.key_builder.entity(namespace, key);
+        let local = match self.local.acquire(local_key) {
+            local::LockAcquisition::Owned(guard) => guard,
+            local::LockAcquisition::Waiting(waiter) => {
+                self.metrics.acquisition("local", "waiting");
+                return LockAcquisition::Waiting(LockWaiter {
+                    inner: LockWaiterInner::Local(waiter),
+                    metrics: self.metrics.clone(),
+                });
+            }
+        };
+
+        let Some(distributed) = &self.distributed else {
+            self.metrics.acquisition("local", "owned");
+            return LockAcquisition::Owned(OwnedLockGuard {
+                _distributed: None,
+                _local: local,
+            });
+        };
+
+        match distributed.acquire(namespace, key).await {
+            Ok(distributed::LockAcquisition::Owned(guard)) => {
+                self.metrics.acquisition("distributed", "owned");
+                LockAcquisition::Owned(OwnedLockGuard {
+                    _distributed: Some(guard),
+                    _local: local,
+                })
+            }
+            // The local lock is held while waiting, so other tasks in this
+            // process wait on it instead of polling Redis themselves.
+            Ok(distributed::LockAcquisition::Waiting(waiter)) => {
+                self.metrics.acquisition("distributed", "waiting");
+                LockAcquisition::Waiting(LockWaiter {
+                    inner: LockWaiterInner::Distributed(waiter, local),
+                    metrics: self.metrics.clone(),
+                })
+            }
+            // Redis being unavailable shouldn't stop the cache from filling,
+            // so fall back to coalescing within this process only.
+            Err(error) => {
+                self.metrics.acquisition("distributed", "error");
+                warn!(
+                    namespace,
+                    key, "failed to acquire distributed cache lock: {error:?}"
+                );
+                LockAcquisition::Owned(OwnedLockGuard {
+                    _distributed: None,
+                    _local: local,
+                })
+            }
+        }
+    }
+}
+
+pub(super) enum LockAcquisition {
+    Owned(OwnedLockGuard),
+    Waiting(LockWaiter),
+}
+
+/// Releases the distributed lock, if any, before the local one.
+pub(super) struct OwnedLockGuard {
+    _distributed: Option<distributed::OwnedLockGuard>,
+    _local: local::OwnedLockGuard,
+}
+
+pub(super) struct LockWaiter {
+    inner: LockWaiterInner,
+    metrics: CacheLockMetrics,
+}
+
+enum LockWaiterInner {
+    Local(local::LockWaiter),
+    Distributed(distributed::LockWaiter, local::OwnedLockGuard),
+}
+
+impl LockWaiter {
+    pub(super) async fn wait(self, deadline: Instant) -> Result<()> {
+        let (strategy, result) = match self.inner {
+            LockWaiterInner::Local(waiter) => {
+                ("local", waiter.wait(deadline).await)
+            }
+            LockWaiterInner::Distributed(waiter, local) => {
+                let result = waiter.wait(deadline).await;
+                drop(local);
+                ("distributed", result)
+            }
+        };
+
+        if result.is_err() && Instant::now() >= deadline {
+            self.metrics.wait_timeout(strategy);
+        }
+        result
+    }
+}
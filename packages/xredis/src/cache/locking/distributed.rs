@@ -0,0 +1,294 @@
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use eyre::{Result, WrapErr, bail};
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, sleep, sleep_until};
+use tracing::warn;
+
+use crate::connection::RedisBackend;
+use crate::key::KeyBuilder;
+use crate::metrics::CacheLockMetrics;
+
+const LOCKS_NAMESPACE: &str = "_cache_locks";
+const FENCES_NAMESPACE: &str = "_cache_lock_fences";
+
+/// How long a lock is held without being renewed. A holder which crashes
+/// blocks other replicas for at most this long.
+const LEASE: Duration = Duration::from_secs(10);
+const RENEW_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Fence counters only need to outlive any lock taken with them.
+const FENCE_EXPIRY: Duration = Duration::from_secs(24 * 60 * 60);
+
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+const MAX_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Takes the lock if it's free, returning `{1, fence}`, or returns
+/// `{0, fence}` of the current holder.
+///
+/// The fence is drawn from a per-key counter, so it's unique to each holder
+/// and a holder whose lease expired can't renew or release its successor's
+/// lock.
+static ACQUIRE_SCRIPT: LazyLock<redis::Script> = LazyLock::new(|| {
+    redis::Script::new(
+        r"
+        local holder = redis.call('GET', KEYS[1])
+        if holder then
+            return {0, tonumber(holder)}
+        end
+        local fence = redis.call('INCR', KEYS[2])
+        redis.call('PEXPIRE', KEYS[2], ARGV[2])
+        redis.call('SET', KEYS[1], fence, 'PX', ARGV[1])
+        return {1, fence}
+        ",
+    )
+});
+
+static RENEW_SCRIPT: LazyLock<redis::Script> = LazyLock::new(|| {
+    redis::Script::new(
+        r"
+        if redis.call('GET', KEYS[1]) == ARGV[1] then
+            return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+        end
+        return 0
+        ",
+    )
+});
+
+static RELEASE_SCRIPT: LazyLock<redis::Script> = LazyLock::new(|| {
+    redis::Script::new(
+        r"
+        if redis.call('GET', KEYS[1]) == ARGV[1] then
+            return redis.call('DEL', KEYS[1])
+        end
+        return 0
+        ",
+    )
+});
+
+/// Reads the holder through a script rather than `GET`, so that polls are
+/// always answered by the primary even when reads are routed to replicas.
+static HOLDER_SCRIPT: LazyLock<redis::Script> =
+    LazyLock::new(|| redis::Script::new("return redis.call('GET', KEYS[1])"));
+
+/// Coordinates cache fills across every process sharing a Redis deployment.
+#[derive(Clone)]
+pub(in crate::cache) struct LockCoordinator {
+    backend: RedisBackend,
+    key_builder: KeyBuilder,
+    metrics: CacheLockMetrics,
+}
+
+impl LockCoordinator {
+    pub(in crate::cache) fn new(
+        backend: RedisBackend,
+        key_builder: KeyBuilder,
+        metrics: CacheLockMetrics,
+    ) -> Self {
+        Self {
+            backend,
+            key_builder,
+            metrics,
+        }
+    }
+
+    /// Tries to take the lock for an entity key. The lock and fence keys
+    /// share the entity's slot tag, so the script runs on one cluster node.
+    pub(in crate::cache) async fn acquire(
+        &self,
+        namespace: &str,
+        key: &str,
+    ) -> Result<LockAcquisition> {
+        let logical_key = format!("{namespace}:{key}");
+        let lock_key =
+            self.key_builder
+                .with_slot(LOCKS_NAMESPACE, &logical_key, key);
+        let fence_key =
+            self.key_builder
+                .with_slot(FENCES_NAMESPACE, &logical_key, key);
+
+        let mut connection = self
+            .backend
+            .connect()
+            .await
+            .wrap_err("connecting to acquire distributed cache lock")?;
+        let (acquired, fence): (bool, i64) = ACQUIRE_SCRIPT
+            .key(&lock_key)
+            .key(&fence_key)
+            .arg(LEASE.as_millis() as u64)
+            .arg(FENCE_EXPIRY.as_millis() as u64)
+            .invoke_async(&mut connection)
+            .await
+            .wrap_err("acquiring distributed cache lock")?;
+
+        Ok(if acquired {
+            LockAcquisition::Owned(OwnedLockGuard::new(
+                self.backend.clone(),
+                self.metrics.clone(),
+                lock_key,
+                fence,
+            ))
+        } else {
+            LockAcquisition::Waiting(LockWaiter {
+                backend: self.backend.clone(),
+                lock_key,
+                fence,
+            })
+        })
+    }
+}
+
+pub(in crate::cache) enum LockAcquisition {
+    Owned(OwnedLockGuard),
+    Waiting(LockWaiter),
+}
+
+/// Holds a distributed lock, renewing its lease until dropped.
+pub(in crate::cache) struct OwnedLockGuard {
+    backend: RedisBackend,
+    lock_key: String,
+    fence: i64,
+    renewal: JoinHandle<()>,
+}
+
+impl OwnedLockGuard {
+    fn new(
+        backend: RedisBackend,
+        metrics: CacheLockMetrics,
+        lock_key: String,
+        fence: i64,
+    ) -> Self {
+        let renewal = tokio::spawn(renew_lease(
+            backend.clone(),
+            metrics,
+            lock_key.clone(),
+            fence,
+        ));
+
+        Self {
+            backend,
+            lock_key,
+            fence,
+            renewal,
+        }
+    }
+}
+
+impl Drop for OwnedLockGuard {
+    fn drop(&mut self) {
+        self.renewal.abort();
+
+        // Without a runtime the lock is left to expire with its lease.
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+
+        let backend = self.backend.clone();
+        let lock_key = std::mem::take(&mut self.lock_key);
+        let fence = self.fence;
+        runtime.spawn(async move {
+            if let Err(error) = release(&backend, &lock_key, fence).await {
+                warn!(
+                    %lock_key,
+                    "failed to release distributed cache lock: {error:?}"
+                );
+            }
+        });
+    }
+}
+
+async fn renew_lease(
+    backend: RedisBackend,
+    metrics: CacheLockMetrics,
+    lock_key: String,
+    fence: i64,
+) {
+    loop {
+        sleep(RENEW_INTERVAL).await;
+
+        let renewed = async {
+            let mut connection = backend.connect().await?;
+            RENEW_SCRIPT
+                .key(&lock_key)
+                .arg(fence)
+                .arg(LEASE.as_millis() as u64)
+                .invoke_async::<bool>(&mut connection)
+                .await
+                .wrap_err("renewing distributed cache lock")
+        }
+        .await;
+
+        match renewed {
+            Ok(true) => {}
+            Ok(false) => {
+                metrics.lease_lost();
+                warn!(
+                    %lock_key,
+                    "distributed cache lock lease was lost while filling"
+                );
+                return;
+            }
+            // The lease may still be renewed before it expires.
+            Err(error) => {
+                warn!(
+                    %lock_key,
+                    "failed to renew distributed cache lock: {error:?}"
+                );
+            }
+        }
+    }
+}
+
+async fn release(
+    backend: &RedisBackend,
+    lock_key: &str,
+    fence: i64,
+) -> Result<()> {
+    let mut connection = backend.connect().await?;
+    RELEASE_SCRIPT
+        .key(lock_key)
+        .arg(fence)
+        .invoke_async::<()>(&mut connection)
+        .await
+        .wrap_err("releasing distributed cache lock")
+}
+
+/// Waits for the holder seen at acquisition to release the lock or lose it.
+pub(in crate::cache) struct LockWaiter {
+    backend: RedisBackend,
+    lock_key: String,
+    fence: i64,
+}
+
+impl LockWaiter {
+    pub(in crate::cache) async fn wait(self, deadline: Instant) -> Result<()> {
+        let mut interval = MIN_POLL_INTERVAL;
+
+        loop {
+            let mut connection = self
+                .backend
+                .connect()
+                .await
+                .wrap_err("connecting to poll distributed cache lock")?;
+            let holder: Option<i64> = HOLDER_SCRIPT
+                .key(&self.lock_key)
+                .invoke_async(&mut connection)
+                .await
+                .wrap_err("polling distributed cache lock")?;
+            drop(connection);
+
+            if holder != Some(self.fence) {
+                return Ok(());
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                bail!("timeout waiting for distributed Redis cache lock");
+            }
+
+            sleep_until(deadline.min(now + interval)).await;
+            interval = (interval * 2).min(MAX_POLL_INTERVAL);
+        }
+    }
+}
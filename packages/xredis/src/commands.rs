@@ -224,6 +224,25 @@ where
     Ok(())
 }
 
+#[tracing::instrument(skip_all)]
+pub async fn publish<C, M>(
+    connection: &mut C,
+    channel: &str,
+    message: M,
+) -> Result<()>
+where
+    C: ConnectionLike,
+    M: ToRedisArgs + Send + Sync,
+{
+    cmd("PUBLISH")
+        .arg(channel)
+        .arg(message)
+        .query_async::<()>(connection)
+        .await
+        .wrap_err("publishing to Redis channel")?;
+    Ok(())
+}
+
 #[tracing::instrument(skip_all)]
 pub async fn lpush<C, D>(connection: &mut C, key: &str, value: D) -> Result<()>
 where
@@ -45,10 +45,13 @@ impl FromStr for CacheLockingStrategy {
 pub enum RedisTopology {
     Standalone,
     Cluster,
+    /// An in-process store for tests and single-node deployments, shared by
+    /// every pool in the process.
+    Memory,
 }
 
 #[derive(Debug, Error)]
-#[error("invalid Redis topology; expected `standalone` or `cluster`")]
+#[error("invalid Redis topology; expected `standalone`, `cluster` or `memory`")]
 pub struct InvalidRedisMode;
 
 impl FromStr for RedisTopology {
@@ -58,6 +61,7 @@ impl FromStr for RedisTopology {
         match value {
             "standalone" => Ok(Self::Standalone),
             "cluster" => Ok(Self::Cluster),
+            "memory" => Ok(Self::Memory),
             _ => Err(InvalidRedisMode),
         }
     }
@@ -116,6 +120,7 @@ pub(crate) enum RedisBackendConfig {
     StandalonePooled(RedisPoolSize),
     ClusterPooled(RedisPoolSize),
     ClusterMultiplexed,
+    Memory,
 }
 
 #[derive(Debug, Clone)]
@@ -176,8 +181,13 @@ pub enum RedisConfigError {
         max: usize,
         min: usize,
     },
-    #[error("unsupported Redis cache locking strategy `{strategy}`")]
-    UnsupportedCacheLockingStrategy { strategy: CacheLockingStrategy },
+    #[error(
+        "unsupported Redis configuration: `{strategy}` cache locking in `{mode:?}` mode"
+    )]
+    UnsupportedCacheLockingStrategy {
+        mode: RedisTopology,
+        strategy: CacheLockingStrategy,
+    },
 }
 
 impl RedisConfig {
@@ -193,13 +203,6 @@ impl RedisConfig {
         cache_locking_strategy: CacheLockingStrategy,
         read_replica_strategy: ReadReplicaStrategy,
     ) -> Result<Self> {
-        if cache_locking_strategy == CacheLockingStrategy::Distributed {
-            return Err(RedisConfigError::UnsupportedCacheLockingStrategy {
-                strategy: cache_locking_strategy,
-            }
-            .into());
-        }
-
         let seed_urls = raw_urls
             .split(',')
             .map(str::trim)
@@ -207,11 +210,26 @@ impl RedisConfig {
             .map(ToOwned::to_owned)
             .collect::<Vec<_>>();
 
-        if seed_urls.is_empty() {
+        if seed_urls.is_empty() && mode != RedisTopology::Memory {
             return Err(RedisConfigError::MissingUrl.into());
         }
 
         let backend = match (mode, connection_type) {
+            // There's only ever one process to coordinate, and the in-memory
+            // backend can't run the distributed lock's scripts.
+            (RedisTopology::Memory, _)
+                if cache_locking_strategy
+                    == CacheLockingStrategy::Distributed =>
+            {
+                return Err(
+                    RedisConfigError::UnsupportedCacheLockingStrategy {
+                        mode,
+                        strategy: cache_locking_strategy,
+                    }
+                    .into(),
+                );
+            }
+            (RedisTopology::Memory, _) => RedisBackendConfig::Memory,
             (RedisTopology::Standalone, RedisConnectionType::Pooled) => {
                 if seed_urls.len() != 1 {
                     return Err(RedisConfigError::MultipleStandaloneUrls.into());
@@ -11,6 +11,7 @@ use redis::cluster_routing::RoutingInfo;
 use tracing::warn;
 
 use crate::ReadReplicaStrategy;
+use crate::memory::MemoryStore;
 
 use super::config::{RedisBackendConfig, RedisConfig, RedisPoolSize};
 use super::metrics::{
@@ -27,6 +28,7 @@ pub(crate) enum RedisBackend {
     StandalonePooled(deadpool_redis::Pool),
     ClusterPooled(deadpool_redis::cluster::Pool),
     ClusterMultiplexed(redis::cluster_async::ClusterConnection),
+    Memory(MemoryStore),
 }
 
 pub(crate) struct RedisConnection {
@@ -37,6 +39,7 @@ enum RedisConnectionInner {
     StandalonePooled(deadpool_redis::Connection),
     ClusterPooled(deadpool_redis::cluster::Connection),
     ClusterMultiplexed(redis::cluster_async::ClusterConnection),
+    Memory(MemoryStore),
 }
 
 pub(crate) trait RoutableConnection: ConnectionLike {
@@ -59,6 +62,9 @@ impl RedisBackend {
             RedisBackendConfig::ClusterMultiplexed => {
                 Self::cluster_multiplexed(config).await
             }
+            RedisBackendConfig::Memory => {
+                Ok(Self::Memory(MemoryStore::shared()))
+            }
         }
     }
 
@@ -163,6 +169,7 @@ impl RedisBackend {
             Self::ClusterMultiplexed(connection) => {
                 RedisConnectionInner::ClusterMultiplexed(connection.clone())
             }
+            Self::Memory(store) => RedisConnectionInner::Memory(store.clone()),
         };
 
         Ok(RedisConnection { inner })
@@ -182,7 +189,7 @@ impl LogicalPoolStatusProvider for RedisBackend {
             Self::ClusterPooled(pool) => {
                 LogicalPoolStatus::from_deadpool(pool.status())
             }
-            Self::ClusterMultiplexed(_) => {
+            Self::ClusterMultiplexed(_) | Self::Memory(_) => {
                 LogicalPoolStatus::shared_multiplexed()
             }
         }
@@ -204,6 +211,7 @@ impl ConnectionLike for RedisConnectionInner {
             Self::ClusterMultiplexed(connection) => {
                 connection.req_packed_command(cmd)
             }
+            Self::Memory(store) => store.req_packed_command(cmd),
         }
     }
 
@@ -223,6 +231,9 @@ impl ConnectionLike for RedisConnectionInner {
             Self::ClusterMultiplexed(connection) => {
                 connection.req_packed_commands(cmd, offset, count)
             }
+            Self::Memory(store) => {
+                store.req_packed_commands(cmd, offset, count)
+            }
         }
     }
 
@@ -231,6 +242,7 @@ impl ConnectionLike for RedisConnectionInner {
             Self::StandalonePooled(connection) => connection.get_db(),
             Self::ClusterPooled(connection) => connection.get_db(),
             Self::ClusterMultiplexed(connection) => connection.get_db(),
+            Self::Memory(store) => store.get_db(),
         }
     }
 }
@@ -274,6 +286,9 @@ impl RoutableConnection for RedisConnection {
                 RedisConnectionInner::ClusterMultiplexed(connection) => {
                     connection.route_command(command, routing).await
                 }
+                RedisConnectionInner::Memory(store) => {
+                    command.query_async(store).await
+                }
             }
         })
     }
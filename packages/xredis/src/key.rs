@@ -43,7 +43,7 @@ impl KeyBuilder {
         slot_tag: impl Display,
     ) -> String {
         match self.mode {
-            RedisTopology::Standalone => {
+            RedisTopology::Standalone | RedisTopology::Memory => {
                 format!("{}_{}:{}", self.meta_namespace, namespace, logical_key)
             }
             RedisTopology::Cluster => format!(
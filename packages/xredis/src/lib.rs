@@ -19,15 +19,16 @@ mod commands;
 mod config;
 mod connection;
 mod key;
+mod memory;
 mod metrics;
 mod pubsub;
 mod routing;
 mod util;
 
-use cache::{CacheManager, ConnectionProvider};
+use cache::{CacheManager, ConnectionProvider, INVALIDATION_CHANNEL, L1Cache};
 pub use cache::{
     CacheSettings, Codec, EncodingFormat, InvalidCodec, InvalidEncodingFormat,
-    RedisValue,
+    L1CacheSettings, RedisValue,
 };
 pub use config::{
     CacheLockingStrategy, InvalidCacheLockingStrategy,
@@ -51,6 +52,7 @@ pub struct RedisConnection {
     inner: connection::RedisConnection,
     key_builder: KeyBuilder,
     settings: CacheSettings,
+    l1: Option<L1Cache>,
 }
 
 impl RedisPool {
@@ -72,15 +74,28 @@ impl RedisPool {
             .await
             .wrap_err("creating Redis blocking pool")?;
         let key_builder = KeyBuilder::new(meta_namespace, config.topology());
-        let cache = CacheManager::new(key_builder.clone(), cache_settings);
+        let cache = CacheManager::new(
+            key_builder.clone(),
+            cache_settings,
+            config.cache_locking_strategy(),
+            backend.clone(),
+        )
+        .wrap_err("creating Redis cache manager")?;
 
-        Ok(Self {
+        let pool = Self {
             backend,
             blocking,
             cache,
             config,
             key_builder,
-        })
+        };
+
+        if let Some(l1) = pool.cache.l1() {
+            let receiver = pool.subscribe(INVALIDATION_CHANNEL);
+            tokio::spawn(l1.clone().listen(receiver));
+        }
+
+        Ok(pool)
     }
 
     pub fn key(&self) -> &KeyBuilder {
@@ -98,6 +113,7 @@ impl RedisPool {
                 .wrap_err("connecting to Redis")?,
             key_builder: self.key_builder.clone(),
             settings: self.cache.settings().clone(),
+            l1: self.cache.l1().cloned(),
         })
     }
 
@@ -110,7 +126,10 @@ impl RedisPool {
             .wrap_err("registering Redis command pool metrics")?;
         self.blocking
             .register_metrics(registry)
-            .wrap_err("registering Redis blocking pool metrics")
+            .wrap_err("registering Redis blocking pool metrics")?;
+        self.cache
+            .register_metrics(registry)
+            .wrap_err("registering Redis cache lock metrics")
     }
 
     pub async fn get_cached_keys<F, Fut, T, K, E>(
@@ -247,6 +266,7 @@ impl RedisConnection {
         &self.key_builder
     }
 
+    /// Sets a key, evicting it from the L1 cache of every node.
     pub async fn set<D>(
         &mut self,
         key: &str,
@@ -262,9 +282,13 @@ impl RedisConnection {
             data,
             expiry.unwrap_or(self.settings.default_expiry),
         )
-        .await
+        .await?;
+        self.broadcast_invalidation(key).await;
+        Ok(())
     }
 
+    /// Sets a key to a cache-encoded value, evicting it from the L1 cache of
+    /// every node.
     pub async fn set_serialized<D>(
         &mut self,
         key: &str,
@@ -281,7 +305,9 @@ impl RedisConnection {
             expiry,
             &self.settings,
         )
-        .await
+        .await?;
+        self.broadcast_invalidation(key).await;
+        Ok(())
     }
 
     pub async fn get(&mut self, key: &str) -> Result<Option<String>> {
@@ -323,12 +349,27 @@ impl RedisConnection {
             .await
     }
 
+    /// Deletes a key, evicting it from the L1 cache of every node.
     pub async fn delete(&mut self, key: &str) -> Result<()> {
-        commands::delete(&mut self.inner, key).await
+        commands::delete(&mut self.inner, key).await?;
+        self.broadcast_invalidation(key).await;
+        Ok(())
     }
 
+    /// Deletes keys, evicting them from the L1 cache of every node.
     pub async fn delete_many(&mut self, keys: &[String]) -> Result<()> {
-        commands::delete_many(&mut self.inner, keys).await
+        commands::delete_many(&mut self.inner, keys).await?;
+        if let Some(l1) = &self.l1 {
+            l1.broadcast_invalidation(&mut self.inner, keys).await;
+        }
+        Ok(())
+    }
+
+    async fn broadcast_invalidation(&mut self, key: &str) {
+        if let Some(l1) = &self.l1 {
+            l1.broadcast_invalidation(&mut self.inner, &[key.to_owned()])
+                .await;
+        }
     }
 
     pub async fn lpush<D>(&mut self, key: &str, value: D) -> Result<()>
@@ -0,0 +1,495 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard};
+use std::time::Duration;
+
+use redis::aio::ConnectionLike;
+use redis::{Arg, RedisError, RedisResult, Value};
+use tokio::sync::{Notify, broadcast, mpsc};
+use tokio::time::{Instant, timeout_at};
+
+/// Every in-memory pool in a process shares one store, like pools connected
+/// to the same Redis server. Keys are already separated by meta namespace.
+static SHARED_STORE: LazyLock<MemoryStore> = LazyLock::new(MemoryStore::new);
+
+/// Expired keys are dropped when they're next accessed, and all of them are
+/// swept after this many writes so keys which are never read again don't
+/// accumulate.
+const SWEEP_INTERVAL_WRITES: usize = 4096;
+
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// An in-process stand-in for Redis, implementing the subset of commands
+/// used through xredis.
+#[derive(Clone)]
+pub(crate) struct MemoryStore {
+    inner: Arc<MemoryStoreInner>,
+}
+
+struct MemoryStoreInner {
+    entries: Mutex<HashMap<Vec<u8>, Entry>>,
+    writes: AtomicUsize,
+    pushed: Notify,
+    channels: Mutex<HashMap<Vec<u8>, broadcast::Sender<Vec<u8>>>>,
+}
+
+struct Entry {
+    data: Data,
+    expires_at: Option<Instant>,
+}
+
+enum Data {
+    String(Vec<u8>),
+    List(VecDeque<Vec<u8>>),
+    Set(HashSet<Vec<u8>>),
+}
+
+impl Entry {
+    fn is_live(&self, now: Instant) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+impl MemoryStore {
+    fn new() -> Self {
+        Self {
+            inner: Arc::new(MemoryStoreInner {
+                entries: Mutex::new(HashMap::new()),
+                writes: AtomicUsize::new(0),
+                pushed: Notify::new(),
+                channels: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    pub(crate) fn shared() -> Self {
+        SHARED_STORE.clone()
+    }
+
+    pub(crate) async fn brpop(
+        &self,
+        key: &str,
+        timeout: Duration,
+    ) -> Option<[Vec<u8>; 2]> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let notified = self.inner.pushed.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some(value) = self.rpop(key.as_bytes()) {
+                return Some([key.as_bytes().to_vec(), value]);
+            }
+
+            timeout_at(deadline, notified).await.ok()?;
+        }
+    }
+
+    /// Forwards messages published to `channel` until the receiver is
+    /// dropped.
+    pub(crate) fn subscribe(
+        &self,
+        channel: &str,
+        sender: mpsc::Sender<Vec<u8>>,
+    ) {
+        let mut receiver = self
+            .channels()
+            .entry(channel.as_bytes().to_vec())
+            .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+            .subscribe();
+
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(message) => {
+                        if sender.send(message).await.is_err() {
+                            return;
+                        }
+                    }
+                    // Like a Redis subscriber which can't keep up, messages
+                    // are dropped rather than buffered without bound.
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+        });
+    }
+
+    fn entries(&self) -> MutexGuard<'_, HashMap<Vec<u8>, Entry>> {
+        self.inner
+            .entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn channels(
+        &self,
+    ) -> MutexGuard<'_, HashMap<Vec<u8>, broadcast::Sender<Vec<u8>>>> {
+        self.inner
+            .channels
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn rpop(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let mut entries = self.entries();
+        let now = Instant::now();
+        let value = match entries.get_mut(key) {
+            Some(entry) if entry.is_live(now) => match &mut entry.data {
+                Data::List(list) => list.pop_back(),
+                _ => None,
+            },
+            _ => None,
+        };
+        if matches!(
+            entries.get(key).map(|entry| &entry.data),
+            Some(Data::List(list)) if list.is_empty()
+        ) {
+            entries.remove(key);
+        }
+        value
+    }
+
+    fn execute_all<'a>(
+        &self,
+        commands: impl Iterator<Item = &'a redis::Cmd>,
+    ) -> RedisResult<Vec<Value>> {
+        let mut entries = self.entries();
+        let mut pushed = false;
+        let results = commands
+            .map(|command| {
+                let args = command_args(command);
+                self.execute(&mut entries, &args, &mut pushed)
+            })
+            .collect::<RedisResult<Vec<_>>>();
+        drop(entries);
+
+        if pushed {
+            self.inner.pushed.notify_waiters();
+        }
+        results
+    }
+
+    fn execute(
+        &self,
+        entries: &mut HashMap<Vec<u8>, Entry>,
+        args: &[Vec<u8>],
+        pushed: &mut bool,
+    ) -> RedisResult<Value> {
+        let Some((name, args)) = args.split_first() else {
+            return Err(command_error("empty command"));
+        };
+        let name = String::from_utf8_lossy(name).to_ascii_uppercase();
+        let now = Instant::now();
+
+        // Commands with several keys check expiry per key themselves.
+        if let Some(key) = args.first()
+            && entries.get(key).is_some_and(|entry| !entry.is_live(now))
+        {
+            entries.remove(key);
+        }
+
+        match name.as_str() {
+            "PING" => Ok(Value::SimpleString("PONG".to_owned())),
+            "GET" => {
+                let [key] = args else {
+                    return Err(arity_error(&name));
+                };
+                match entries.get(key).map(|entry| &entry.data) {
+                    None => Ok(Value::Nil),
+                    Some(Data::String(value)) => {
+                        Ok(Value::BulkString(value.clone()))
+                    }
+                    Some(_) => Err(wrong_type_error()),
+                }
+            }
+            "MGET" => {
+                if args.is_empty() {
+                    return Err(arity_error(&name));
+                }
+                Ok(Value::Array(
+                    args.iter()
+                        .map(|key| match entries.get(key) {
+                            Some(Entry {
+                                data: Data::String(value),
+                                expires_at,
+                            }) if expires_at
+                                .is_none_or(|expires_at| expires_at > now) =>
+                            {
+                                Value::BulkString(value.clone())
+                            }
+                            _ => Value::Nil,
+                        })
+                        .collect(),
+                ))
+            }
+            "SET" => {
+                let [key, value, options @ ..] = args else {
+                    return Err(arity_error(&name));
+                };
+                let mut expires_at = None;
+                let mut only_if_missing = false;
+                let mut options = options.iter();
+                while let Some(option) = options.next() {
+                    match option.to_ascii_uppercase().as_slice() {
+                        b"EX" => {
+                            let seconds = parse_expiry(options.next())?;
+                            expires_at = Some(now + seconds_duration(seconds));
+                        }
+                        b"PX" => {
+                            let millis = parse_expiry(options.next())?;
+                            expires_at = Some(now + millis_duration(millis));
+                        }
+                        b"NX" => only_if_missing = true,
+                        _ => return Err(command_error("unsupported option")),
+                    }
+                }
+
+                if only_if_missing && entries.contains_key(key) {
+                    return Ok(Value::Nil);
+                }
+                entries.insert(
+                    key.clone(),
+                    Entry {
+                        data: Data::String(value.clone()),
+                        expires_at,
+                    },
+                );
+                self.record_write(entries);
+                Ok(Value::Okay)
+            }
+            "DEL" => {
+                if args.is_empty() {
+                    return Err(arity_error(&name));
+                }
+                let deleted = args
+                    .iter()
+                    .filter(|key| {
+                        entries
+                            .remove(key.as_slice())
+                            .is_some_and(|entry| entry.is_live(now))
+                    })
+                    .count();
+                Ok(Value::Int(deleted as i64))
+            }
+            "EXPIRE" | "PEXPIRE" => {
+                let [key, expiry, ..] = args else {
+                    return Err(arity_error(&name));
+                };
+                let expiry = parse_int(Some(expiry))?;
+                let Some(entry) = entries.get_mut(key) else {
+                    return Ok(Value::Int(0));
+                };
+                entry.expires_at = Some(
+                    now + if name == "EXPIRE" {
+                        seconds_duration(expiry)
+                    } else {
+                        millis_duration(expiry)
+                    },
+                );
+                Ok(Value::Int(1))
+            }
+            "INCR" => {
+                let [key] = args else {
+                    return Err(arity_error(&name));
+                };
+                let entry = entries.entry(key.clone()).or_insert(Entry {
+                    data: Data::String(b"0".to_vec()),
+                    expires_at: None,
+                });
+                let Data::String(value) = &mut entry.data else {
+                    return Err(wrong_type_error());
+                };
+                let incremented = parse_int(Some(value))?
+                    .checked_add(1)
+                    .ok_or_else(|| command_error("increment would overflow"))?;
+                *value = incremented.to_string().into_bytes();
+                self.record_write(entries);
+                Ok(Value::Int(incremented))
+            }
+            "LPUSH" => {
+                let [key, values @ ..] = args else {
+                    return Err(arity_error(&name));
+                };
+                if values.is_empty() {
+                    return Err(arity_error(&name));
+                }
+                let entry = entries.entry(key.clone()).or_insert(Entry {
+                    data: Data::List(VecDeque::new()),
+                    expires_at: None,
+                });
+                let Data::List(list) = &mut entry.data else {
+                    return Err(wrong_type_error());
+                };
+                for value in values {
+                    list.push_front(value.clone());
+                }
+                let len = list.len();
+                *pushed = true;
+                self.record_write(entries);
+                Ok(Value::Int(len as i64))
+            }
+            "SADD" | "SREM" => {
+                let [key, members @ ..] = args else {
+                    return Err(arity_error(&name));
+                };
+                if members.is_empty() {
+                    return Err(arity_error(&name));
+                }
+                if name == "SREM" && !entries.contains_key(key) {
+                    return Ok(Value::Int(0));
+                }
+                let entry = entries.entry(key.clone()).or_insert(Entry {
+                    data: Data::Set(HashSet::new()),
+                    expires_at: None,
+                });
+                let Data::Set(set) = &mut entry.data else {
+                    return Err(wrong_type_error());
+                };
+                let changed = members
+                    .iter()
+                    .filter(|member| {
+                        if name == "SADD" {
+                            set.insert((*member).clone())
+                        } else {
+                            set.remove(member.as_slice())
+                        }
+                    })
+                    .count();
+                if set.is_empty() {
+                    entries.remove(key);
+                }
+                self.record_write(entries);
+                Ok(Value::Int(changed as i64))
+            }
+            "SMEMBERS" | "SSCAN" => {
+                let Some(key) = args.first() else {
+                    return Err(arity_error(&name));
+                };
+                let members = match entries.get(key).map(|entry| &entry.data) {
+                    None => Vec::new(),
+                    Some(Data::Set(set)) => {
+                        set.iter().cloned().map(Value::BulkString).collect()
+                    }
+                    Some(_) => return Err(wrong_type_error()),
+                };
+                // The whole set is returned in one page, so the scan cursor
+                // is always finished.
+                Ok(if name == "SSCAN" {
+                    Value::Array(vec![
+                        Value::BulkString(b"0".to_vec()),
+                        Value::Array(members),
+                    ])
+                } else {
+                    Value::Array(members)
+                })
+            }
+            "PUBLISH" => {
+                let [channel, message] = args else {
+                    return Err(arity_error(&name));
+                };
+                let receivers = self
+                    .channels()
+                    .get(channel)
+                    .and_then(|sender| sender.send(message.clone()).ok())
+                    .unwrap_or(0);
+                Ok(Value::Int(receivers as i64))
+            }
+            _ => Err(command_error(&format!(
+                "command `{name}` is not supported by the in-memory backend"
+            ))),
+        }
+    }
+
+    fn record_write(&self, entries: &mut HashMap<Vec<u8>, Entry>) {
+        let writes = self.inner.writes.fetch_add(1, Ordering::Relaxed) + 1;
+        if writes.is_multiple_of(SWEEP_INTERVAL_WRITES) {
+            let now = Instant::now();
+            entries.retain(|_, entry| entry.is_live(now));
+        }
+    }
+}
+
+impl ConnectionLike for MemoryStore {
+    fn req_packed_command<'a>(
+        &'a mut self,
+        command: &'a redis::Cmd,
+    ) -> redis::RedisFuture<'a, Value> {
+        Box::pin(async move {
+            let mut results = self.execute_all(std::iter::once(command))?;
+            Ok(results.pop().unwrap_or(Value::Nil))
+        })
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        pipeline: &'a redis::Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> redis::RedisFuture<'a, Vec<Value>> {
+        Box::pin(async move {
+            // Every pipeline runs under one lock, so it's atomic either way.
+            let commands = pipeline.cmd_iter().collect::<Vec<_>>();
+            let transaction = count == 1 && offset == commands.len() + 1;
+            let results = self.execute_all(commands.into_iter())?;
+
+            // A transaction's results are read from the reply to `EXEC`,
+            // which is queued after `MULTI` and every command.
+            Ok(if transaction {
+                vec![Value::Array(results)]
+            } else {
+                results.into_iter().skip(offset).take(count).collect()
+            })
+        })
+    }
+
+    fn get_db(&self) -> i64 {
+        0
+    }
+}
+
+fn command_args(command: &redis::Cmd) -> Vec<Vec<u8>> {
+    command
+        .args_iter()
+        .map(|arg| match arg {
+            Arg::Simple(arg) => arg.to_vec(),
+            // Scans always start from and finish at the first page.
+            Arg::Cursor => b"0".to_vec(),
+        })
+        .collect()
+}
+
+fn parse_int(value: Option<&Vec<u8>>) -> RedisResult<i64> {
+    value
+        .and_then(|value| std::str::from_utf8(value).ok())
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| command_error("value is not an integer or out of range"))
+}
+
+fn parse_expiry(value: Option<&Vec<u8>>) -> RedisResult<i64> {
+    match parse_int(value)? {
+        expiry if expiry > 0 => Ok(expiry),
+        _ => Err(command_error("invalid expire time")),
+    }
+}
+
+fn seconds_duration(seconds: i64) -> Duration {
+    Duration::from_secs(seconds.max(0) as u64)
+}
+
+fn millis_duration(millis: i64) -> Duration {
+    Duration::from_millis(millis.max(0) as u64)
+}
+
+fn arity_error(name: &str) -> RedisError {
+    command_error(&format!("wrong number of arguments for `{name}`"))
+}
+
+fn wrong_type_error() -> RedisError {
+    command_error("operation against a key holding the wrong kind of value")
+}
+
+fn command_error(message: &str) -> RedisError {
+    std::io::Error::other(format!("in-memory Redis: {message}")).into()
+}
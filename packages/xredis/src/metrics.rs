@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use eyre::{Result, WrapErr};
-use prometheus::{IntGauge, Registry};
+use prometheus::{IntCounter, IntCounterVec, IntGauge, Opts, Registry};
 
 const METRICS_UPDATE_INTERVAL: Duration = Duration::from_secs(5);
 
@@ -173,3 +173,150 @@ where
 
     Ok(())
 }
+
+/// Counters for cache fill locks, labelled by the locking strategy which
+/// handled them.
+#[derive(Clone)]
+pub(crate) struct CacheLockMetrics {
+    acquisitions: IntCounterVec,
+    wait_timeouts: IntCounterVec,
+    leases_lost: IntCounter,
+}
+
+impl CacheLockMetrics {
+    pub(crate) fn new() -> Result<Self> {
+        let acquisitions = IntCounterVec::new(
+            Opts::new(
+                "labrinth_redis_cache_lock_acquisitions_total",
+                "Redis cache lock acquisition attempts by strategy and outcome",
+            ),
+            &["strategy", "outcome"],
+        )
+        .wrap_err("creating Redis cache lock acquisitions metric")?;
+        let wait_timeouts = IntCounterVec::new(
+            Opts::new(
+                "labrinth_redis_cache_lock_wait_timeouts_total",
+                "Waits on a Redis cache lock which reached their deadline",
+            ),
+            &["strategy"],
+        )
+        .wrap_err("creating Redis cache lock wait timeouts metric")?;
+        let leases_lost = IntCounter::new(
+            "labrinth_redis_cache_lock_leases_lost_total",
+            "Distributed Redis cache locks which expired or were taken over before their holder released them",
+        )
+        .wrap_err("creating Redis cache lock lost leases metric")?;
+
+        Ok(Self {
+            acquisitions,
+            wait_timeouts,
+            leases_lost,
+        })
+    }
+
+    pub(crate) fn register(&self, registry: &Registry) -> Result<()> {
+        registry
+            .register(Box::new(self.acquisitions.clone()))
+            .wrap_err("registering Redis cache lock acquisitions metric")?;
+        registry
+            .register(Box::new(self.wait_timeouts.clone()))
+            .wrap_err("registering Redis cache lock wait timeouts metric")?;
+        registry
+            .register(Box::new(self.leases_lost.clone()))
+            .wrap_err("registering Redis cache lock lost leases metric")?;
+        Ok(())
+    }
+
+    pub(crate) fn acquisition(&self, strategy: &str, outcome: &str) {
+        self.acquisitions
+            .with_label_values(&[strategy, outcome])
+            .inc();
+    }
+
+    pub(crate) fn wait_timeout(&self, strategy: &str) {
+        self.wait_timeouts.with_label_values(&[strategy]).inc();
+    }
+
+    pub(crate) fn lease_lost(&self) {
+        self.leases_lost.inc();
+    }
+}
+
+/// Counters for the in-process L1 cache tier, labelled by cache namespace.
+#[derive(Clone)]
+pub(crate) struct CacheL1Metrics {
+    hits: IntCounterVec,
+    misses: IntCounterVec,
+    evictions: IntCounter,
+    invalidations: IntCounter,
+}
+
+impl CacheL1Metrics {
+    pub(crate) fn new() -> Result<Self> {
+        let hits = IntCounterVec::new(
+            Opts::new(
+                "labrinth_redis_cache_l1_hits_total",
+                "Redis cache lookups answered by the in-process L1 cache",
+            ),
+            &["namespace"],
+        )
+        .wrap_err("creating Redis L1 cache hits metric")?;
+        let misses = IntCounterVec::new(
+            Opts::new(
+                "labrinth_redis_cache_l1_misses_total",
+                "Redis cache lookups which missed the in-process L1 cache",
+            ),
+            &["namespace"],
+        )
+        .wrap_err("creating Redis L1 cache misses metric")?;
+        let evictions = IntCounter::new(
+            "labrinth_redis_cache_l1_evictions_total",
+            "Unexpired entries evicted to keep the in-process L1 cache bounded",
+        )
+        .wrap_err("creating Redis L1 cache evictions metric")?;
+        let invalidations = IntCounter::new(
+            "labrinth_redis_cache_l1_invalidations_total",
+            "Keys invalidated in the in-process L1 cache, locally or by another node",
+        )
+        .wrap_err("creating Redis L1 cache invalidations metric")?;
+
+        Ok(Self {
+            hits,
+            misses,
+            evictions,
+            invalidations,
+        })
+    }
+
+    pub(crate) fn register(&self, registry: &Registry) -> Result<()> {
+        registry
+            .register(Box::new(self.hits.clone()))
+            .wrap_err("registering Redis L1 cache hits metric")?;
+        registry
+            .register(Box::new(self.misses.clone()))
+            .wrap_err("registering Redis L1 cache misses metric")?;
+        registry
+            .register(Box::new(self.evictions.clone()))
+            .wrap_err("registering Redis L1 cache evictions metric")?;
+        registry
+            .register(Box::new(self.invalidations.clone()))
+            .wrap_err("registering Redis L1 cache invalidations metric")?;
+        Ok(())
+    }
+
+    pub(crate) fn hit(&self, namespace: &str) {
+        self.hits.with_label_values(&[namespace]).inc();
+    }
+
+    pub(crate) fn miss(&self, namespace: &str) {
+        self.misses.with_label_values(&[namespace]).inc();
+    }
+
+    pub(crate) fn evicted(&self, count: usize) {
+        self.evictions.inc_by(count as u64);
+    }
+
+    pub(crate) fn invalidated(&self, count: usize) {
+        self.invalidations.inc_by(count as u64);
+    }
+}
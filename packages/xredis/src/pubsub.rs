@@ -7,6 +7,7 @@ use tokio::sync::mpsc;
 use tracing::{info, warn};
 
 use super::RedisPool;
+use super::connection::RedisBackend;
 
 const PUBSUB_BUFFER_SIZE: usize = 1024;
 const INITIAL_RECONNECT_BACKOFF: Duration = Duration::from_millis(250);
@@ -19,9 +20,13 @@ enum SubscriptionOutcome {
 
 impl RedisPool {
     pub fn subscribe(&self, channel: &'static str) -> mpsc::Receiver<Vec<u8>> {
-        let seed_urls = self.config.seed_urls().to_vec();
         let (sender, receiver) = mpsc::channel(PUBSUB_BUFFER_SIZE);
-        tokio::spawn(run_subscription(seed_urls, channel, sender));
+        if let RedisBackend::Memory(store) = &self.backend {
+            store.subscribe(channel, sender);
+        } else {
+            let seed_urls = self.config.seed_urls().to_vec();
+            tokio::spawn(run_subscription(seed_urls, channel, sender));
+        }
         receiver
     }
 